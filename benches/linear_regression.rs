@@ -0,0 +1,65 @@
+//! Prediction latency of a trained linear regressor, for batches and for single observations.
+//!
+//! Run with `cargo bench --bench linear_regression`. This compares the allocating `predict` with
+//! `predict_into` on a preallocated buffer, and with the earlier approach of copying the inputs to
+//! add an intercept column before multiplying by the coefficients.
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use nalgebra::{DMatrix, DVector};
+use slearning::linear_regression::{LinearParameters, OlsRegressor};
+use slearning::SupervisedModel;
+
+const NUM_VARS: usize = 20;
+
+fn inputs(num_obs: usize) -> DMatrix<f64> {
+    DMatrix::from_fn(num_obs, NUM_VARS, |i, j| ((i * NUM_VARS + j) as f64).sin())
+}
+
+fn prediction(c: &mut Criterion) {
+    let train_inputs = inputs(1000);
+    let outputs = DVector::from_fn(1000, |i, _| (i as f64).cos());
+    let mut model = OlsRegressor::new(true);
+    model.train(train_inputs, outputs).unwrap();
+    let LinearParameters {
+        intercept,
+        coefficients,
+    } = model.parameters().unwrap();
+    let full_coefficients = DVector::from_iterator(
+        NUM_VARS + 1,
+        intercept.into_iter().chain(coefficients.iter().copied()),
+    );
+
+    let mut group = c.benchmark_group("linear_regression_prediction");
+    for num_obs in [1, 10_000] {
+        let batch = inputs(num_obs);
+        let mut predictions = DVector::zeros(num_obs);
+        group.bench_with_input(
+            BenchmarkId::new("copy_intercept_column", num_obs),
+            &batch,
+            |b, batch| {
+                b.iter(|| {
+                    let full_inputs = black_box(batch).clone().insert_column(0, 1.0);
+                    full_inputs * &full_coefficients
+                })
+            },
+        );
+        group.bench_with_input(BenchmarkId::new("predict", num_obs), &batch, |b, batch| {
+            b.iter(|| model.predict(black_box(batch)).unwrap())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("predict_into", num_obs),
+            &batch,
+            |b, batch| {
+                b.iter(|| {
+                    model
+                        .predict_into(black_box(batch), &mut predictions)
+                        .unwrap();
+                    black_box(&predictions);
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, prediction);
+criterion_main!(benches);
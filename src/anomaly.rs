@@ -0,0 +1,689 @@
+//! Detectors of anomalous observations (outliers).
+//!
+//! Each detector is trained on a matrix of observations and gives every observation an anomaly
+//! score, where higher scores are more anomalous. Scores above the detector's threshold are
+//! outliers, and predicting gives one for outliers and zero for inliers (so outliers are the
+//! positive class, as in [`crate::metrics`]).
+//!
+//! The threshold is usually set from a `contamination` parameter, the expected proportion of
+//! outliers in the training data, so that this proportion of the training observations have
+//! scores above the threshold.
+use crate::covariance::MinCovDet;
+use crate::diagnostics::{Diagnostics, Warning};
+use crate::distance::Euclidean;
+use crate::kernel::Kernel;
+use crate::neighbors::{NearestNeighbors, Neighbors};
+use crate::optim::ConvergenceConfig;
+use crate::random::Rng;
+use crate::special::chi_squared_quantile;
+use crate::stats::sorted_quantile;
+use crate::traits::UnsupervisedModel;
+use crate::utils::total_cmp;
+use crate::validation::{
+    check_2d_nonempty, check_fitted, check_fraction, check_num_vars, check_positive,
+};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+fn validate_contamination<T: RealField>(contamination: &T) -> SLearningResult<()> {
+    check_positive(contamination.clone(), "Contamination")?;
+    if *contamination > nalgebra::convert(0.5) {
+        return Err(SLearningError::InvalidParameters(
+            "Contamination must be at most one half.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The score above which the `contamination` proportion of the training scores lie.
+fn contamination_threshold<T: RealField + Copy>(scores: &DVector<T>, contamination: T) -> T {
+    let mut sorted: Vec<T> = scores.iter().copied().collect();
+    sorted.sort_by(total_cmp);
+    sorted_quantile(&sorted, T::one() - contamination)
+}
+
+fn outlier_labels<T: RealField + Copy>(scores: &DVector<T>, threshold: T) -> DVector<T> {
+    scores.map(|score| {
+        if score > threshold {
+            T::one()
+        } else {
+            T::zero()
+        }
+    })
+}
+
+/// The average path length of an unsuccessful search in a binary search tree with `n` nodes, which
+/// is used to normalise the path lengths in an isolation tree.
+fn average_path_length(n: usize) -> f64 {
+    const EULER_GAMMA: f64 = 0.5772156649015329;
+    match n {
+        0 | 1 => 0.0,
+        2 => 1.0,
+        _ => {
+            let n = n as f64;
+            2.0 * ((n - 1.0).ln() + EULER_GAMMA) - 2.0 * (n - 1.0) / n
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum IsolationNode<T> {
+    Split {
+        feature: usize,
+        value: T,
+        left: Box<IsolationNode<T>>,
+        right: Box<IsolationNode<T>>,
+    },
+    Leaf {
+        size: usize,
+    },
+}
+
+impl<T> IsolationNode<T>
+where
+    T: RealField + Copy,
+{
+    /// Isolate the given observations with random splits, up to `max_depth`.
+    fn build(
+        inputs: &DMatrix<T>,
+        indices: Vec<usize>,
+        depth: usize,
+        max_depth: usize,
+        rng: &mut Rng,
+    ) -> Self {
+        if depth >= max_depth || indices.len() <= 1 {
+            return Self::Leaf {
+                size: indices.len(),
+            };
+        }
+        // Only split on variables that still vary, since the others cannot separate anything.
+        let ranges: Vec<(usize, T, T)> = (0..inputs.ncols())
+            .filter_map(|j| {
+                let (min, max) = indices.iter().fold(
+                    (inputs[(indices[0], j)], inputs[(indices[0], j)]),
+                    |(min, max), &i| (min.min(inputs[(i, j)]), max.max(inputs[(i, j)])),
+                );
+                (min < max).then_some((j, min, max))
+            })
+            .collect();
+        if ranges.is_empty() {
+            return Self::Leaf {
+                size: indices.len(),
+            };
+        }
+        let (feature, min, max) = ranges[rng.below(ranges.len())];
+        let value = min + (max - min) * rng.uniform::<T>();
+        let (left, right): (Vec<usize>, Vec<usize>) = indices
+            .into_iter()
+            .partition(|&i| inputs[(i, feature)] < value);
+        Self::Split {
+            feature,
+            value,
+            left: Box::new(Self::build(inputs, left, depth + 1, max_depth, rng)),
+            right: Box::new(Self::build(inputs, right, depth + 1, max_depth, rng)),
+        }
+    }
+
+    /// The number of splits needed to isolate `row`, plus an estimate of the remaining splits
+    /// needed if the leaf had more than one training observation.
+    fn path_length(&self, inputs: &DMatrix<T>, row: usize) -> f64 {
+        let mut node = self;
+        let mut depth = 0.0;
+        loop {
+            match node {
+                Self::Split {
+                    feature,
+                    value,
+                    left,
+                    right,
+                } => {
+                    node = if inputs[(row, *feature)] < *value {
+                        left
+                    } else {
+                        right
+                    };
+                    depth += 1.0;
+                }
+                Self::Leaf { size } => return depth + average_path_length(*size),
+            }
+        }
+    }
+}
+
+/// Isolation forest, which detects outliers as the observations that are easiest to isolate with
+/// random splits.
+///
+/// Each tree is grown on a random subsample by repeatedly splitting on a random variable at a
+/// random value. Outliers tend to be isolated after fewer splits, so the anomaly score of an
+/// observation is `2^(-h / c)`, where `h` is its mean path length over the trees and `c` is the
+/// expected path length for the subsample size. Scores are between zero and one, with scores much
+/// greater than one half indicating outliers.
+#[derive(Debug)]
+pub struct IsolationForest<T>
+where
+    T: RealField,
+{
+    /// Scores above this are outliers.
+    pub threshold: Option<T>,
+    n_trees: usize,
+    max_samples: usize,
+    contamination: T,
+    seed: u64,
+    trees: Option<Vec<IsolationNode<T>>>,
+    /// The number of observations each tree was grown on.
+    subsample_size: usize,
+    num_vars: usize,
+}
+
+impl<T> IsolationForest<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(n_trees: usize, max_samples: usize, contamination: T) -> SLearningResult<Self> {
+        if n_trees == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Number of trees must be at least one.".to_string(),
+            ));
+        }
+        if max_samples < 2 {
+            return Err(SLearningError::InvalidParameters(
+                "Maximum number of samples must be at least two.".to_string(),
+            ));
+        }
+        validate_contamination(&contamination)?;
+        Ok(Self {
+            threshold: None,
+            n_trees,
+            max_samples,
+            contamination,
+            seed: 0,
+            trees: None,
+            subsample_size: 0,
+            num_vars: 0,
+        })
+    }
+
+    /// Use `seed` for the random subsamples and splits.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    /// The anomaly score of each observation, where higher scores are more anomalous.
+    pub fn score_samples(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let trees = check_fitted(&self.trees)?;
+        check_num_vars(self.num_vars, inputs.ncols())?;
+        let normalisation = average_path_length(self.subsample_size) * trees.len() as f64;
+        Ok(DVector::from_fn(inputs.nrows(), |i, _| {
+            let total_path_length: f64 = trees.iter().map(|tree| tree.path_length(inputs, i)).sum();
+            nalgebra::convert(2f64.powf(-total_path_length / normalisation))
+        }))
+    }
+}
+
+impl<T> Default for IsolationForest<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self::new(100, 256, nalgebra::convert(0.1)).expect("The default parameters are valid.")
+    }
+}
+
+impl<T> UnsupervisedModel<T> for IsolationForest<T>
+where
+    T: RealField + Copy,
+{
+    fn train(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        // The expected path length of a single observation is zero, so scores would be undefined.
+        if inputs.nrows() < 2 {
+            return Err(SLearningError::InvalidData(
+                "Cannot train an isolation forest with fewer than two observations.".to_string(),
+            ));
+        }
+        let subsample_size = self.max_samples.min(inputs.nrows());
+        let max_depth = (subsample_size as f64).log2().ceil() as usize;
+        let mut rng = Rng::new(self.seed);
+        let trees = (0..self.n_trees)
+            .map(|_| {
+                let indices = rng.sample_indices(inputs.nrows(), subsample_size);
+                IsolationNode::build(inputs, indices, 0, max_depth, &mut rng)
+            })
+            .collect();
+        self.trees = Some(trees);
+        self.subsample_size = subsample_size;
+        self.num_vars = inputs.ncols();
+
+        let scores = self.score_samples(inputs)?;
+        self.threshold = Some(contamination_threshold(&scores, self.contamination));
+        Ok(())
+    }
+
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let scores = self.score_samples(inputs)?;
+        let threshold = self.threshold.ok_or(SLearningError::UntrainedModel)?;
+        Ok(outlier_labels(&scores, threshold))
+    }
+}
+
+/// Local outlier factor (LOF), which detects outliers as the observations in regions of much lower
+/// density than their nearest neighbours.
+///
+/// The local density of an observation is the inverse of its mean reachability distance to its
+/// `k` nearest neighbours, where the reachability distance to a neighbour is at least that
+/// neighbour's distance to its own `k`-th nearest neighbour. The anomaly score is the mean local
+/// density of the neighbours divided by the observation's own local density, which is close to one
+/// for inliers and larger for outliers.
+///
+/// Training computes the scores and labels of the training observations (`training_scores` and
+/// `training_labels`). Scoring new observations, including with [`UnsupervisedModel::predict`], is
+/// only allowed in novelty detection mode (see [`LocalOutlierFactor::with_novelty`]), since the
+/// training observations would otherwise be counted as their own neighbours.
+#[derive(Debug)]
+pub struct LocalOutlierFactor<T>
+where
+    T: RealField,
+{
+    /// Scores above this are outliers.
+    pub threshold: Option<T>,
+    /// The anomaly score of each training observation.
+    pub training_scores: Option<DVector<T>>,
+    /// Whether each training observation is an outlier (one) or an inlier (zero).
+    pub training_labels: Option<DVector<T>>,
+    n_neighbors: usize,
+    contamination: T,
+    novelty: bool,
+    index: Option<NearestNeighbors<T, Euclidean>>,
+    /// The distance from each training observation to its `k`-th nearest neighbour.
+    k_distances: DVector<T>,
+    /// The local density of each training observation.
+    local_densities: DVector<T>,
+}
+
+impl<T> LocalOutlierFactor<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(n_neighbors: usize, contamination: T) -> SLearningResult<Self> {
+        if n_neighbors == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Number of neighbours must be at least one.".to_string(),
+            ));
+        }
+        validate_contamination(&contamination)?;
+        Ok(Self {
+            threshold: None,
+            training_scores: None,
+            training_labels: None,
+            n_neighbors,
+            contamination,
+            novelty: false,
+            index: None,
+            k_distances: DVector::zeros(0),
+            local_densities: DVector::zeros(0),
+        })
+    }
+
+    /// Whether to allow scoring new observations (novelty detection), rather than only the
+    /// training observations.
+    pub fn with_novelty(self, novelty: bool) -> Self {
+        Self { novelty, ..self }
+    }
+
+    /// The local density of each query, from its neighbours among the training observations.
+    fn reachability_densities(&self, neighbors: &Neighbors<T>) -> DVector<T> {
+        // Avoid infinite densities when there are duplicate observations.
+        let epsilon: T = nalgebra::convert(1e-10);
+        let k: T = nalgebra::convert(self.n_neighbors as f64);
+        DVector::from_fn(neighbors.indices.len(), |i, _| {
+            let total_reachability = neighbors.indices[i]
+                .iter()
+                .enumerate()
+                .fold(T::zero(), |acc, (column, &j)| {
+                    acc + neighbors.distances[(i, column)].max(self.k_distances[j])
+                });
+            T::one() / (total_reachability / k + epsilon)
+        })
+    }
+
+    fn outlier_factors(&self, neighbors: &Neighbors<T>, densities: &DVector<T>) -> DVector<T> {
+        let k: T = nalgebra::convert(self.n_neighbors as f64);
+        DVector::from_fn(neighbors.indices.len(), |i, _| {
+            let neighbor_density = neighbors.indices[i]
+                .iter()
+                .fold(T::zero(), |acc, &j| acc + self.local_densities[j]);
+            neighbor_density / k / densities[i]
+        })
+    }
+
+    /// The anomaly score of each new observation, which requires novelty detection mode.
+    pub fn score_samples(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let index = check_fitted(&self.index)?;
+        if !self.novelty {
+            return Err(SLearningError::InvalidParameters(
+                "Scoring new observations requires novelty detection mode.".to_string(),
+            ));
+        }
+        check_num_vars(index.inputs().ncols(), inputs.ncols())?;
+        let neighbors = index.query(inputs, self.n_neighbors)?;
+        let densities = self.reachability_densities(&neighbors);
+        Ok(self.outlier_factors(&neighbors, &densities))
+    }
+}
+
+impl<T> Default for LocalOutlierFactor<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self::new(20, nalgebra::convert(0.1)).expect("The default parameters are valid.")
+    }
+}
+
+impl<T> UnsupervisedModel<T> for LocalOutlierFactor<T>
+where
+    T: RealField + Copy,
+{
+    fn train(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        if self.n_neighbors >= inputs.nrows() {
+            let error_msg = format!(
+                "Number of neighbours must be less than the number of observations ({}).",
+                inputs.nrows()
+            );
+            return Err(SLearningError::InvalidParameters(error_msg));
+        }
+        let index = NearestNeighbors::new(inputs.clone(), Euclidean)?;
+        let neighbors = index.query_indexed(self.n_neighbors)?;
+        self.k_distances = neighbors
+            .distances
+            .column(self.n_neighbors - 1)
+            .into_owned();
+        self.local_densities = self.reachability_densities(&neighbors);
+        let scores = self.outlier_factors(&neighbors, &self.local_densities);
+
+        let threshold = contamination_threshold(&scores, self.contamination);
+        self.training_labels = Some(outlier_labels(&scores, threshold));
+        self.training_scores = Some(scores);
+        self.threshold = Some(threshold);
+        self.index = Some(index);
+        Ok(())
+    }
+
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let scores = self.score_samples(inputs)?;
+        let threshold = self.threshold.ok_or(SLearningError::UntrainedModel)?;
+        Ok(outlier_labels(&scores, threshold))
+    }
+}
+
+/// Solve the one-class SVM dual problem, `min a' K a / 2` subject to `0 <= a_i <= upper` and
+/// `sum(a) = 1`, using sequential minimal optimisation (SMO). Returns the dual coefficients, the
+/// offset, and whether the largest violation of the optimality conditions fell to the tolerance
+/// within the maximum number of iterations.
+fn solve_one_class_dual<T>(
+    gram: &DMatrix<T>,
+    upper: T,
+    convergence: &ConvergenceConfig<T>,
+) -> (DVector<T>, T, bool)
+where
+    T: RealField + Copy,
+{
+    let n = gram.nrows();
+    let tiny: T = nalgebra::convert(1e-12);
+
+    // Start from the first observations at the upper bound, which is a feasible point.
+    let mut alpha = DVector::zeros(n);
+    let mut remaining = T::one();
+    for i in 0..n {
+        alpha[i] = remaining.min(upper);
+        remaining -= alpha[i];
+        if remaining <= T::zero() {
+            break;
+        }
+    }
+    let mut gradient = gram * &alpha;
+
+    let mut converged = false;
+    for _ in 0..convergence.max_iter() {
+        // The most violating pair: the coefficient that can increase with the smallest gradient,
+        // and the coefficient that can decrease with the largest gradient.
+        let increase = (0..n)
+            .filter(|&i| alpha[i] < upper)
+            .min_by(|&a, &b| total_cmp(&gradient[a], &gradient[b]));
+        let decrease = (0..n)
+            .filter(|&j| alpha[j] > T::zero())
+            .max_by(|&a, &b| total_cmp(&gradient[a], &gradient[b]));
+        let (Some(i), Some(j)) = (increase, decrease) else {
+            converged = true;
+            break;
+        };
+        if gradient[j] - gradient[i] <= convergence.tol() {
+            converged = true;
+            break;
+        }
+        let curvature =
+            (gram[(i, i)] + gram[(j, j)] - gram[(i, j)] * nalgebra::convert(2.0)).max(tiny);
+        let step = ((gradient[j] - gradient[i]) / curvature)
+            .min(upper - alpha[i])
+            .min(alpha[j]);
+        alpha[i] += step;
+        alpha[j] -= step;
+        gradient += (gram.column(i) - gram.column(j)) * step;
+    }
+
+    // The offset is the gradient at the free coefficients, or the middle of its feasible range if
+    // there are none.
+    let free: Vec<usize> = (0..n)
+        .filter(|&i| alpha[i] > T::zero() && alpha[i] < upper)
+        .collect();
+    let offset = if free.is_empty() {
+        let lower = (0..n)
+            .filter(|&i| alpha[i] >= upper)
+            .map(|i| gradient[i])
+            .fold(None, |acc: Option<T>, g| Some(acc.map_or(g, |a| a.max(g))));
+        let higher = (0..n)
+            .filter(|&i| alpha[i] <= T::zero())
+            .map(|i| gradient[i])
+            .fold(None, |acc: Option<T>, g| Some(acc.map_or(g, |a| a.min(g))));
+        match (lower, higher) {
+            (Some(lower), Some(higher)) => (lower + higher) * nalgebra::convert(0.5),
+            (Some(bound), None) | (None, Some(bound)) => bound,
+            (None, None) => T::zero(),
+        }
+    } else {
+        free.iter().fold(T::zero(), |acc, &i| acc + gradient[i])
+            / nalgebra::convert(free.len() as f64)
+    };
+    (alpha, offset, converged)
+}
+
+/// One-class support vector machine (SVM), which detects novelties as observations outside a
+/// boundary around the training data.
+///
+/// The boundary separates the training data from the origin in the feature space of the kernel,
+/// with maximum margin. The parameter `nu` is an upper bound on the proportion of training
+/// observations outside the boundary, and a lower bound on the proportion of support vectors. The
+/// anomaly score is the negative of the decision function, so observations outside the boundary
+/// have positive scores and the threshold is zero.
+#[derive(Debug)]
+pub struct OneClassSvm<T, K>
+where
+    T: RealField,
+{
+    /// Scores above this are outliers. This is always zero once trained.
+    pub threshold: Option<T>,
+    /// The training observations with non-zero dual coefficients.
+    pub support_vectors: Option<DMatrix<T>>,
+    /// The dual coefficient of each support vector, which sum to one.
+    pub dual_coefficients: Option<DVector<T>>,
+    /// The offset of the decision function.
+    pub offset: Option<T>,
+    kernel: K,
+    nu: T,
+    convergence: ConvergenceConfig<T>,
+    diagnostics: Diagnostics,
+}
+
+impl<T, K> OneClassSvm<T, K>
+where
+    T: RealField + Copy,
+    K: Kernel<T>,
+{
+    pub fn new(kernel: K, nu: T) -> SLearningResult<Self> {
+        check_fraction(nu, "Nu")?;
+        Ok(Self {
+            threshold: None,
+            support_vectors: None,
+            dual_coefficients: None,
+            offset: None,
+            kernel,
+            nu,
+            convergence: ConvergenceConfig::new(100_000, nalgebra::convert(1e-6))
+                .expect("The default parameters are valid."),
+            diagnostics: Diagnostics::default(),
+        })
+    }
+
+    /// Set when the SMO solver stops, based on the largest violation of the optimality
+    /// conditions (the difference between the gradients of the most violating pair).
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+
+    /// Send warnings from training, e.g. that it stopped before converging, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// The signed distance of each observation to the boundary (in the kernel feature space),
+    /// which is positive for observations inside the boundary.
+    pub fn decision_function(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let (Some(support_vectors), Some(dual_coefficients), Some(offset)) =
+            (&self.support_vectors, &self.dual_coefficients, self.offset)
+        else {
+            return Err(SLearningError::UntrainedModel);
+        };
+        check_num_vars(support_vectors.ncols(), inputs.ncols())?;
+        let kernel_matrix = self.kernel.matrix(inputs, support_vectors)?;
+        Ok((kernel_matrix * dual_coefficients).add_scalar(-offset))
+    }
+
+    /// The anomaly score of each observation, where higher scores are more anomalous.
+    pub fn score_samples(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        Ok(-self.decision_function(inputs)?)
+    }
+}
+
+impl<T, K> UnsupervisedModel<T> for OneClassSvm<T, K>
+where
+    T: RealField + Copy,
+    K: Kernel<T>,
+{
+    fn train(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        let gram = self.kernel.gram_matrix(inputs);
+        let upper = T::one() / (self.nu * nalgebra::convert(inputs.nrows() as f64));
+        let (alpha, offset, converged) = solve_one_class_dual(&gram, upper, &self.convergence);
+        if !converged {
+            self.diagnostics.warn(Warning::NotConverged {
+                max_iter: self.convergence.max_iter(),
+            });
+        }
+
+        let support: Vec<usize> = (0..inputs.nrows())
+            .filter(|&i| alpha[i] > T::zero())
+            .collect();
+        self.support_vectors = Some(inputs.select_rows(support.iter()));
+        self.dual_coefficients = Some(alpha.select_rows(support.iter()));
+        self.offset = Some(offset);
+        self.threshold = Some(T::zero());
+        Ok(())
+    }
+
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let scores = self.score_samples(inputs)?;
+        let threshold = self.threshold.ok_or(SLearningError::UntrainedModel)?;
+        Ok(outlier_labels(&scores, threshold))
+    }
+}
+
+/// Elliptic envelope, which detects outliers as observations far from a robust estimate of the
+/// location and covariance of roughly normally distributed data.
+///
+/// The location and covariance are estimated with [`MinCovDet`], and the anomaly score is the
+/// squared Mahalanobis distance from the location. For normally distributed data this follows a
+/// chi-squared distribution, so the threshold is its `1 - contamination` quantile.
+#[derive(Debug)]
+pub struct EllipticEnvelope<T>
+where
+    T: RealField,
+{
+    /// Scores above this are outliers.
+    pub threshold: Option<T>,
+    /// The robust estimator of the location and covariance.
+    pub covariance_estimator: MinCovDet<T>,
+    contamination: T,
+}
+
+impl<T> EllipticEnvelope<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(contamination: T) -> SLearningResult<Self> {
+        validate_contamination(&contamination)?;
+        Ok(Self {
+            threshold: None,
+            covariance_estimator: MinCovDet::default(),
+            contamination,
+        })
+    }
+
+    /// Use the given proportion of observations in the raw MCD support.
+    pub fn with_support_fraction(self, support_fraction: T) -> SLearningResult<Self> {
+        Ok(Self {
+            covariance_estimator: MinCovDet::new(support_fraction)?,
+            ..self
+        })
+    }
+
+    /// The anomaly score (squared Mahalanobis distance) of each observation.
+    pub fn score_samples(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        self.covariance_estimator.predict(inputs)
+    }
+}
+
+impl<T> Default for EllipticEnvelope<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self::new(nalgebra::convert(0.1)).expect("The default parameters are valid.")
+    }
+}
+
+impl<T> UnsupervisedModel<T> for EllipticEnvelope<T>
+where
+    T: RealField + Copy,
+{
+    fn train(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        self.covariance_estimator.train(inputs)?;
+        let contamination = nalgebra::try_convert::<T, f64>(self.contamination)
+            .expect("The contamination is between zero and one half.");
+        let quantile = chi_squared_quantile(1.0 - contamination, inputs.ncols() as f64);
+        self.threshold = Some(nalgebra::convert(quantile));
+        Ok(())
+    }
+
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let scores = self.score_samples(inputs)?;
+        let threshold = self.threshold.ok_or(SLearningError::UntrainedModel)?;
+        Ok(outlier_labels(&scores, threshold))
+    }
+}
@@ -0,0 +1,837 @@
+//! Clustering, which groups observations so that observations in the same cluster are more similar
+//! to each other than to those in other clusters.
+//!
+//! Cluster labels are indices from zero, with one label per observation. Models that can leave
+//! observations out of every cluster (noise) label them with `None`.
+use crate::diagnostics::{Diagnostics, Warning};
+use crate::distance::{squared_euclidean_matrix, Metric};
+use crate::optim::ConvergenceConfig;
+use crate::random::Rng;
+use crate::stats::sorted_quantile;
+use crate::traits::UnsupervisedModel;
+use crate::utils::total_cmp;
+use crate::validation::{
+    check_2d_nonempty, check_finite, check_fitted, check_non_negative, check_num_vars,
+    check_positive,
+};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+/// Ordering points to identify the clustering structure (OPTICS), a density-based clustering that
+/// works at every density level at once (Ankerst et al., 1999).
+///
+/// An observation is a core point at distance `eps` if at least `min_samples` observations
+/// (including itself) are within `eps` of it, and its core distance is the smallest such `eps`.
+/// Fitting orders the observations so that each one is close to a core point earlier in the
+/// ordering, and records its reachability distance: the distance to that core point, but at least
+/// the core point's core distance. Clusters are the valleys in the reachability distances of the
+/// ordering, so DBSCAN clusters for any `eps` up to `max_eps` can be extracted from one fit with
+/// [`extract_dbscan`](Self::extract_dbscan), rather than refitting with each `eps`.
+#[derive(Debug)]
+pub struct Optics<T, M>
+where
+    T: RealField,
+{
+    /// The observations in the cluster ordering.
+    pub ordering: Option<Vec<usize>>,
+    /// The reachability distance of each observation, which is infinite for the first observation
+    /// of the ordering and for observations more than `max_eps` from every earlier core point.
+    pub reachability: Option<DVector<T>>,
+    /// The core distance of each observation, which is infinite if it is more than `max_eps`.
+    pub core_distances: Option<DVector<T>>,
+    min_samples: usize,
+    max_eps: Option<T>,
+    metric: M,
+}
+
+impl<T, M> Optics<T, M>
+where
+    T: RealField + Copy,
+    M: Metric<T>,
+{
+    pub fn new(min_samples: usize, metric: M) -> SLearningResult<Self> {
+        if min_samples < 2 {
+            return Err(SLearningError::InvalidParameters(
+                "Minimum number of samples must be at least two.".to_string(),
+            ));
+        }
+        Ok(Self {
+            ordering: None,
+            reachability: None,
+            core_distances: None,
+            min_samples,
+            max_eps: None,
+            metric,
+        })
+    }
+
+    /// Only consider observations within `max_eps` of each other as neighbours, which limits the
+    /// `eps` that clusters can be extracted with.
+    pub fn with_max_eps(self, max_eps: T) -> SLearningResult<Self> {
+        check_positive(max_eps, "Maximum eps")?;
+        Ok(Self {
+            max_eps: Some(max_eps),
+            ..self
+        })
+    }
+
+    fn within_max_eps(&self, distance: T) -> bool {
+        self.max_eps.is_none_or(|max_eps| distance <= max_eps)
+    }
+
+    /// The cluster of each observation (or `None` for noise) in the DBSCAN clustering with this
+    /// `eps` and `min_samples`, which must be at most `max_eps`.
+    ///
+    /// A new cluster starts at each core point in the ordering that is not reachable within `eps`
+    /// from earlier observations, and the following reachable observations join it. Clusters are
+    /// numbered in the order they start.
+    pub fn extract_dbscan(&self, eps: T) -> SLearningResult<Vec<Option<usize>>> {
+        let (Some(ordering), Some(reachability), Some(core_distances)) =
+            (&self.ordering, &self.reachability, &self.core_distances)
+        else {
+            return Err(SLearningError::UntrainedModel);
+        };
+        check_non_negative(eps, "Eps")?;
+        if !self.within_max_eps(eps) {
+            return Err(SLearningError::InvalidParameters(
+                "Eps cannot be more than the maximum eps.".to_string(),
+            ));
+        }
+        let mut labels = vec![None; ordering.len()];
+        let mut num_clusters = 0;
+        for &point in ordering {
+            if reachability[point] > eps {
+                if core_distances[point] > eps {
+                    continue;
+                }
+                num_clusters += 1;
+            }
+            // The first observation has an infinite reachability, so a cluster has always been
+            // started by now.
+            labels[point] = Some(num_clusters - 1);
+        }
+        Ok(labels)
+    }
+}
+
+impl<T, M> UnsupervisedModel<T> for Optics<T, M>
+where
+    T: RealField + Copy,
+    M: Metric<T>,
+{
+    fn train(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        check_finite(inputs)?;
+        let num_obs = inputs.nrows();
+        if self.min_samples > num_obs {
+            let error_msg = format!(
+                "Minimum number of samples is {}, but must be at most the number of observations ({}).",
+                self.min_samples, num_obs
+            );
+            return Err(SLearningError::InvalidParameters(error_msg));
+        }
+        let distances = self.metric.pairwise(inputs, inputs)?;
+        let infinity: T = nalgebra::convert(f64::INFINITY);
+        let core_distances = DVector::from_fn(num_obs, |i, _| {
+            let mut row: Vec<T> = distances.row(i).iter().copied().collect();
+            row.sort_by(total_cmp);
+            let core_distance = row[self.min_samples - 1];
+            match self.within_max_eps(core_distance) {
+                true => core_distance,
+                false => infinity,
+            }
+        });
+
+        // Repeatedly take the unprocessed observation with the smallest reachability distance
+        // (the lowest index on ties), and update the reachability of its neighbours if it is a
+        // core point.
+        let mut reachability = DVector::from_element(num_obs, infinity);
+        let mut processed = vec![false; num_obs];
+        let mut ordering = Vec::with_capacity(num_obs);
+        for _ in 0..num_obs {
+            let point = (0..num_obs)
+                .filter(|&i| !processed[i])
+                .min_by(|&a, &b| total_cmp(&reachability[a], &reachability[b]).then(a.cmp(&b)))
+                .expect("There are unprocessed observations.");
+            processed[point] = true;
+            ordering.push(point);
+            if core_distances[point] == infinity {
+                // Not a core point, so nothing is reachable from it.
+                continue;
+            }
+            for other in 0..num_obs {
+                let distance = distances[(point, other)];
+                if processed[other] || !self.within_max_eps(distance) {
+                    continue;
+                }
+                let new_reachability = distance.max(core_distances[point]);
+                if new_reachability < reachability[other] {
+                    reachability[other] = new_reachability;
+                }
+            }
+        }
+
+        self.ordering = Some(ordering);
+        self.reachability = Some(reachability);
+        self.core_distances = Some(core_distances);
+        Ok(())
+    }
+
+    /// OPTICS only orders the training observations, so cannot cluster new observations. Use
+    /// [`extract_dbscan`](Optics::extract_dbscan) for the clusters of the training observations.
+    fn predict(&self, _inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        check_fitted(&self.ordering)?;
+        Err(SLearningError::InvalidParameters(
+            "OPTICS cannot predict the clusters of new observations. Use extract_dbscan for the training observations.".to_string(),
+        ))
+    }
+}
+
+/// Affinity propagation, which clusters observations around exemplars chosen from the observations
+/// by passing messages between them (Frey & Dueck, Clustering by Passing Messages Between Data
+/// Points, 2007).
+///
+/// Each observation sends every other a responsibility (how well suited the other is as its
+/// exemplar) and receives an availability (how appropriate it would be to choose the other), until
+/// the exemplars stop changing for the patience of the convergence configuration. The number of
+/// clusters is not fixed in advance, but grows with the preference, the similarity of each
+/// observation to itself, which defaults to the median similarity. Damping the messages with the
+/// previous ones avoids oscillations.
+///
+/// The similarities are the negative squared Euclidean distances between observations, or can be
+/// given directly with [`fit_similarity`](Self::fit_similarity).
+#[derive(Debug)]
+pub struct AffinityPropagation<T>
+where
+    T: RealField,
+{
+    /// The exemplar of each cluster, in increasing order.
+    pub exemplar_indices: Option<Vec<usize>>,
+    /// The cluster of each training observation.
+    pub labels: Option<Vec<usize>>,
+    /// Whether the exemplars stopped changing before the maximum number of iterations.
+    pub converged: Option<bool>,
+    damping: T,
+    preference: Option<T>,
+    convergence: ConvergenceConfig<T>,
+    seed: u64,
+    /// The exemplars (rows), when fitted to observations rather than a similarity matrix.
+    exemplars: Option<DMatrix<T>>,
+    diagnostics: Diagnostics,
+}
+
+impl<T> AffinityPropagation<T>
+where
+    T: RealField + Copy,
+{
+    /// Affinity propagation with a damping of one half, at most 200 iterations and 15 iterations
+    /// without changes for convergence.
+    pub fn new() -> Self {
+        Self {
+            exemplar_indices: None,
+            labels: None,
+            converged: None,
+            damping: nalgebra::convert(0.5),
+            preference: None,
+            convergence: ConvergenceConfig::new(200, T::zero())
+                .and_then(|convergence| convergence.with_patience(15))
+                .expect("The default parameters are valid."),
+            seed: 0,
+            exemplars: None,
+            diagnostics: Diagnostics::default(),
+        }
+    }
+
+    /// Set the weight of the previous messages in each update.
+    pub fn with_damping(self, damping: T) -> SLearningResult<Self> {
+        if !damping.is_finite() || damping < nalgebra::convert(0.5) || damping >= T::one() {
+            return Err(SLearningError::InvalidParameters(
+                "Damping must be at least one half and less than one.".to_string(),
+            ));
+        }
+        Ok(Self { damping, ..self })
+    }
+
+    /// Set the similarity of each observation to itself, where larger values give more clusters.
+    pub fn with_preference(self, preference: T) -> SLearningResult<Self> {
+        if !preference.is_finite() {
+            return Err(SLearningError::InvalidParameters(
+                "Preference must be finite.".to_string(),
+            ));
+        }
+        Ok(Self {
+            preference: Some(preference),
+            ..self
+        })
+    }
+
+    /// Set when the iterations stop: once the exemplars have not changed for the patience (15 if
+    /// it is not set), or after the maximum number of iterations. The tolerance is not used.
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+
+    /// Set the seed for the tiny noise added to the similarities, which breaks ties between
+    /// equally good exemplars.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    /// Send warnings from training, e.g. that it stopped before converging, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// Fit to a square matrix of the similarity of each observation (rows) to each other
+    /// (columns), whose diagonal is replaced by the preference.
+    pub fn fit_similarity(&mut self, similarity: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(similarity)?;
+        check_finite(similarity)?;
+        if !similarity.is_square() {
+            let error_msg = format!(
+                "The similarity matrix has {} rows and {} columns. These must be equal.",
+                similarity.nrows(),
+                similarity.ncols()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        self.exemplars = None;
+        let n = similarity.nrows();
+        if n == 1 {
+            self.exemplar_indices = Some(vec![0]);
+            self.labels = Some(vec![0]);
+            self.converged = Some(true);
+            return Ok(());
+        }
+
+        let preference = self.preference.unwrap_or_else(|| {
+            let mut sorted: Vec<T> = similarity.iter().copied().collect();
+            sorted.sort_by(total_cmp);
+            sorted_quantile(&sorted, nalgebra::convert(0.5))
+        });
+        // Tiny noise, relative to each similarity, so that ties are broken the same way in every
+        // iteration rather than oscillating.
+        let mut rng = Rng::new(self.seed);
+        let tiny: T = nalgebra::convert(100.0 * f64::MIN_POSITIVE);
+        let similarity = DMatrix::from_fn(n, n, |i, j| {
+            let value = match i == j {
+                true => preference,
+                false => similarity[(i, j)],
+            };
+            value + (T::default_epsilon() * value + tiny) * rng.standard_normal()
+        });
+
+        let keep = self.damping;
+        let update = T::one() - self.damping;
+        let mut responsibility = DMatrix::zeros(n, n);
+        let mut availability = DMatrix::zeros(n, n);
+        let mut exemplars = vec![false; n];
+        let mut unchanged = 0;
+        let patience = self.convergence.patience().unwrap_or(15);
+        let mut converged = false;
+        for _ in 0..self.convergence.max_iter() {
+            // r(i, k) = s(i, k) - max over k' != k of (a(i, k') + s(i, k')).
+            let combined = &availability + &similarity;
+            for i in 0..n {
+                let (mut best, mut best_value, mut second_value) = (0, None, None);
+                for k in 0..n {
+                    let value = combined[(i, k)];
+                    if best_value.is_none_or(|best_value| value > best_value) {
+                        second_value = best_value;
+                        best_value = Some(value);
+                        best = k;
+                    } else if second_value.is_none_or(|second_value| value > second_value) {
+                        second_value = Some(value);
+                    }
+                }
+                let (best_value, second_value) = (best_value.unwrap(), second_value.unwrap());
+                for k in 0..n {
+                    let competitor = match k == best {
+                        true => second_value,
+                        false => best_value,
+                    };
+                    responsibility[(i, k)] =
+                        keep * responsibility[(i, k)] + update * (similarity[(i, k)] - competitor);
+                }
+            }
+
+            // a(i, k) = min(0, r(k, k) + sum over i' not in {i, k} of max(0, r(i', k))), and
+            // a(k, k) = sum over i' != k of max(0, r(i', k)).
+            for k in 0..n {
+                let positive_total = (0..n).filter(|&i| i != k).fold(T::zero(), |total, i| {
+                    total + responsibility[(i, k)].max(T::zero())
+                });
+                for i in 0..n {
+                    let new_availability = match i == k {
+                        true => positive_total,
+                        false => (responsibility[(k, k)] + positive_total
+                            - responsibility[(i, k)].max(T::zero()))
+                        .min(T::zero()),
+                    };
+                    availability[(i, k)] = keep * availability[(i, k)] + update * new_availability;
+                }
+            }
+
+            let new_exemplars: Vec<bool> = (0..n)
+                .map(|k| availability[(k, k)] + responsibility[(k, k)] > T::zero())
+                .collect();
+            unchanged = match new_exemplars == exemplars {
+                true => unchanged + 1,
+                false => 0,
+            };
+            exemplars = new_exemplars;
+            if unchanged >= patience && exemplars.contains(&true) {
+                converged = true;
+                break;
+            }
+        }
+
+        if !converged {
+            self.diagnostics.warn(Warning::NotConverged {
+                max_iter: self.convergence.max_iter(),
+            });
+        }
+        let mut exemplar_indices: Vec<usize> = (0..n).filter(|&k| exemplars[k]).collect();
+        if exemplar_indices.is_empty() {
+            return Err(SLearningError::InvalidData(
+                "No exemplars were found. Try a larger preference or more iterations.".to_string(),
+            ));
+        }
+        // Refine each exemplar to the member of its cluster with the largest total similarity to
+        // the other members, then reassign the observations to the refined exemplars.
+        let labels = nearest_exemplar(&similarity, &exemplar_indices);
+        for (cluster, exemplar) in exemplar_indices.iter_mut().enumerate() {
+            let members: Vec<usize> = (0..n).filter(|&i| labels[i] == cluster).collect();
+            *exemplar = *members
+                .iter()
+                .max_by(|&&a, &&b| {
+                    let total = |k| {
+                        members
+                            .iter()
+                            .fold(T::zero(), |t, &i| t + similarity[(i, k)])
+                    };
+                    total_cmp(&total(a), &total(b))
+                })
+                .expect("Every cluster has its exemplar as a member.");
+        }
+        exemplar_indices.sort_unstable();
+        self.labels = Some(nearest_exemplar(&similarity, &exemplar_indices));
+        self.exemplar_indices = Some(exemplar_indices);
+        self.converged = Some(converged);
+        Ok(())
+    }
+}
+
+impl<T> Default for AffinityPropagation<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> UnsupervisedModel<T> for AffinityPropagation<T>
+where
+    T: RealField + Copy,
+{
+    fn train(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        check_finite(inputs)?;
+        let similarity = -squared_euclidean_matrix(inputs, inputs);
+        self.fit_similarity(&similarity)?;
+        let exemplar_indices = self
+            .exemplar_indices
+            .as_ref()
+            .expect("The model was just fitted.");
+        self.exemplars = Some(inputs.select_rows(exemplar_indices.iter()));
+        Ok(())
+    }
+
+    /// The cluster of each observation, which is the cluster of its closest exemplar. This needs
+    /// the model to have been fitted to observations, rather than a similarity matrix.
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        check_fitted(&self.exemplar_indices)?;
+        let Some(exemplars) = &self.exemplars else {
+            return Err(SLearningError::InvalidParameters(
+                "Predicting needs a model fitted to observations, rather than a similarity matrix."
+                    .to_string(),
+            ));
+        };
+        check_num_vars(exemplars.ncols(), inputs.ncols())?;
+        let distances = squared_euclidean_matrix(inputs, exemplars);
+        Ok(DVector::from_fn(inputs.nrows(), |i, _| {
+            nalgebra::convert(distances.row(i).transpose().argmin().0 as f64)
+        }))
+    }
+}
+
+/// The cluster of the most similar exemplar to each observation, where each exemplar is in its own
+/// cluster.
+fn nearest_exemplar<T: RealField + Copy>(
+    similarity: &DMatrix<T>,
+    exemplar_indices: &[usize],
+) -> Vec<usize> {
+    (0..similarity.nrows())
+        .map(|i| match exemplar_indices.iter().position(|&k| k == i) {
+            Some(cluster) => cluster,
+            None => (0..exemplar_indices.len())
+                .max_by(|&a, &b| {
+                    total_cmp(
+                        &similarity[(i, exemplar_indices[a])],
+                        &similarity[(i, exemplar_indices[b])],
+                    )
+                })
+                .expect("There is at least one exemplar."),
+        })
+        .collect()
+}
+
+/// A clustering feature: the number of observations in a subcluster, and the sum and sum of
+/// squared norms of the observations, which are enough to merge subclusters and compute their
+/// centroids and radii.
+#[derive(Debug, Clone)]
+struct ClusteringFeature<T>
+where
+    T: RealField,
+{
+    num_obs: usize,
+    linear_sum: DVector<T>,
+    squared_sum: T,
+    /// The node that this summarises, if it is not in a leaf.
+    child: Option<Box<CfNode<T>>>,
+}
+
+impl<T> ClusteringFeature<T>
+where
+    T: RealField + Copy,
+{
+    fn from_point(point: DVector<T>) -> Self {
+        Self {
+            num_obs: 1,
+            squared_sum: point.norm_squared(),
+            linear_sum: point,
+            child: None,
+        }
+    }
+
+    /// The clustering feature of all the observations in a node.
+    fn from_node(node: CfNode<T>) -> Self {
+        let mut feature = node.entries[0].summary();
+        for entry in &node.entries[1..] {
+            feature.absorb(entry);
+        }
+        feature.child = Some(Box::new(node));
+        feature
+    }
+
+    /// The counts and sums of this feature, without its child.
+    fn summary(&self) -> Self {
+        Self {
+            num_obs: self.num_obs,
+            linear_sum: self.linear_sum.clone(),
+            squared_sum: self.squared_sum,
+            child: None,
+        }
+    }
+
+    fn absorb(&mut self, other: &Self) {
+        self.num_obs += other.num_obs;
+        self.linear_sum += &other.linear_sum;
+        self.squared_sum += other.squared_sum;
+    }
+
+    fn centroid(&self) -> DVector<T> {
+        &self.linear_sum / nalgebra::convert::<f64, T>(self.num_obs as f64)
+    }
+
+    /// The root mean squared distance of the observations from the centroid, if this feature
+    /// absorbed `other`.
+    fn merged_radius(&self, other: &Self) -> T {
+        let num_obs: T = nalgebra::convert((self.num_obs + other.num_obs) as f64);
+        let linear_sum = &self.linear_sum + &other.linear_sum;
+        let squared_sum = self.squared_sum + other.squared_sum;
+        (squared_sum / num_obs - linear_sum.norm_squared() / (num_obs * num_obs))
+            .max(T::zero())
+            .sqrt()
+    }
+}
+
+/// A node of a CF-tree, whose entries summarise either subclusters (in a leaf) or child nodes.
+#[derive(Debug, Clone)]
+struct CfNode<T>
+where
+    T: RealField,
+{
+    entries: Vec<ClusteringFeature<T>>,
+}
+
+impl<T> CfNode<T>
+where
+    T: RealField + Copy,
+{
+    /// The entry whose centroid is closest to `point`.
+    fn closest(&self, point: &DVector<T>) -> Option<usize> {
+        (0..self.entries.len()).min_by(|&a, &b| {
+            let distance = |i: usize| (self.entries[i].centroid() - point).norm_squared();
+            total_cmp(&distance(a), &distance(b))
+        })
+    }
+
+    /// Insert a single observation, returning a new sibling node if this node had to be split.
+    fn insert(
+        &mut self,
+        feature: ClusteringFeature<T>,
+        threshold: T,
+        branching_factor: usize,
+    ) -> Option<CfNode<T>> {
+        let point = feature.centroid();
+        match self.closest(&point) {
+            Some(closest) if self.entries[closest].child.is_some() => {
+                let entry = &mut self.entries[closest];
+                entry.absorb(&feature);
+                let child = entry.child.as_mut().expect("The entry has a child.");
+                if let Some(sibling) = child.insert(feature, threshold, branching_factor) {
+                    // Both halves of the split child need new summaries.
+                    let child = *entry.child.take().expect("The entry has a child.");
+                    *entry = ClusteringFeature::from_node(child);
+                    self.entries.push(ClusteringFeature::from_node(sibling));
+                }
+            }
+            Some(closest) if self.entries[closest].merged_radius(&feature) <= threshold => {
+                self.entries[closest].absorb(&feature);
+            }
+            _ => self.entries.push(feature),
+        }
+        match self.entries.len() > branching_factor {
+            true => Some(self.split()),
+            false => None,
+        }
+    }
+
+    /// Split the entries between this node and a new sibling, around the two entries whose
+    /// centroids are furthest apart.
+    fn split(&mut self) -> CfNode<T> {
+        let centroids: Vec<DVector<T>> = self.entries.iter().map(|e| e.centroid()).collect();
+        let mut seeds = (0, 1);
+        let mut furthest = T::zero();
+        for i in 0..centroids.len() {
+            for j in 0..i {
+                let distance = (&centroids[i] - &centroids[j]).norm_squared();
+                if distance > furthest {
+                    furthest = distance;
+                    seeds = (j, i);
+                }
+            }
+        }
+        let (kept, moved): (Vec<_>, Vec<_>) = self
+            .entries
+            .drain(..)
+            .zip(&centroids)
+            .enumerate()
+            .partition(|(i, (_, centroid))| {
+                *i == seeds.0
+                    || (*i != seeds.1
+                        && (*centroid - &centroids[seeds.0]).norm_squared()
+                            <= (*centroid - &centroids[seeds.1]).norm_squared())
+            });
+        self.entries = kept.into_iter().map(|(_, (entry, _))| entry).collect();
+        CfNode {
+            entries: moved.into_iter().map(|(_, (entry, _))| entry).collect(),
+        }
+    }
+
+    /// The clustering features of the subclusters in the leaves under this node.
+    fn subclusters<'a>(&'a self, subclusters: &mut Vec<&'a ClusteringFeature<T>>) {
+        for entry in &self.entries {
+            match &entry.child {
+                Some(child) => child.subclusters(subclusters),
+                None => subclusters.push(entry),
+            }
+        }
+    }
+}
+
+/// Balanced iterative reducing and clustering using hierarchies (BIRCH), which summarises the
+/// observations in a tree of subclusters that is built incrementally, so it can cluster data that
+/// arrives in chunks or does not fit in memory (Zhang et al., 1996).
+///
+/// Each observation joins the closest subcluster (found by descending the tree to the closest
+/// child at each level) if the subcluster's radius would stay at most `threshold`, and otherwise
+/// starts a new subcluster. Nodes with more than `branching_factor` entries are split in two.
+///
+/// The subclusters are the clusters, unless a number of clusters is set, when a final global step
+/// merges the subclusters by Ward's agglomerative clustering of their centroids, weighted by their
+/// numbers of observations, until there are that many clusters (or fewer if there are fewer
+/// subclusters). Predicting gives the cluster of the closest subcluster centroid.
+#[derive(Debug)]
+pub struct Birch<T>
+where
+    T: RealField,
+{
+    /// The centroid of each subcluster (rows).
+    pub subcluster_centers: Option<DMatrix<T>>,
+    /// The cluster of each subcluster.
+    pub subcluster_labels: Option<Vec<usize>>,
+    threshold: T,
+    branching_factor: usize,
+    n_clusters: Option<usize>,
+    root: Option<CfNode<T>>,
+    num_vars: usize,
+}
+
+impl<T> Birch<T>
+where
+    T: RealField + Copy,
+{
+    /// BIRCH with a branching factor of 50.
+    pub fn new(threshold: T) -> SLearningResult<Self> {
+        check_positive(threshold, "Threshold")?;
+        Ok(Self {
+            subcluster_centers: None,
+            subcluster_labels: None,
+            threshold,
+            branching_factor: 50,
+            n_clusters: None,
+            root: None,
+            num_vars: 0,
+        })
+    }
+
+    pub fn with_branching_factor(self, branching_factor: usize) -> SLearningResult<Self> {
+        if branching_factor < 2 {
+            return Err(SLearningError::InvalidParameters(
+                "Branching factor must be at least two.".to_string(),
+            ));
+        }
+        Ok(Self {
+            branching_factor,
+            ..self
+        })
+    }
+
+    /// Merge the subclusters into this many clusters.
+    pub fn with_n_clusters(self, n_clusters: usize) -> SLearningResult<Self> {
+        if n_clusters == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Number of clusters must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            n_clusters: Some(n_clusters),
+            ..self
+        })
+    }
+
+    /// Add these observations to the tree, then redo the global clustering of the subclusters.
+    pub fn partial_fit(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        check_finite(inputs)?;
+        if self.root.is_some() {
+            check_num_vars(self.num_vars, inputs.ncols())?;
+        }
+        let mut root = self.root.take().unwrap_or(CfNode {
+            entries: Vec::new(),
+        });
+        for row in inputs.row_iter() {
+            let feature = ClusteringFeature::from_point(row.transpose());
+            if let Some(sibling) = root.insert(feature, self.threshold, self.branching_factor) {
+                root = CfNode {
+                    entries: vec![
+                        ClusteringFeature::from_node(root),
+                        ClusteringFeature::from_node(sibling),
+                    ],
+                };
+            }
+        }
+
+        let mut subclusters = Vec::new();
+        root.subclusters(&mut subclusters);
+        let centers = DMatrix::from_fn(subclusters.len(), inputs.ncols(), |i, j| {
+            subclusters[i].linear_sum[j] / nalgebra::convert(subclusters[i].num_obs as f64)
+        });
+        let counts: Vec<usize> = subclusters.iter().map(|s| s.num_obs).collect();
+        self.subcluster_labels = Some(match self.n_clusters {
+            Some(n_clusters) => ward_labels(&centers, &counts, n_clusters),
+            None => (0..subclusters.len()).collect(),
+        });
+        self.subcluster_centers = Some(centers);
+        self.num_vars = inputs.ncols();
+        self.root = Some(root);
+        Ok(())
+    }
+}
+
+impl<T> UnsupervisedModel<T> for Birch<T>
+where
+    T: RealField + Copy,
+{
+    /// Build a new tree from these observations.
+    fn train(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        self.root = None;
+        self.partial_fit(inputs)
+    }
+
+    /// The cluster of each observation, which is the cluster of its closest subcluster centroid.
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let centers = check_fitted(&self.subcluster_centers)?;
+        let labels = check_fitted(&self.subcluster_labels)?;
+        check_num_vars(centers.ncols(), inputs.ncols())?;
+        let distances = squared_euclidean_matrix(inputs, centers);
+        Ok(DVector::from_fn(inputs.nrows(), |i, _| {
+            nalgebra::convert(labels[distances.row(i).transpose().argmin().0] as f64)
+        }))
+    }
+}
+
+/// The cluster of each weighted point after merging them by Ward's method until there are
+/// `n_clusters` clusters, numbered in order of their first point.
+fn ward_labels<T: RealField + Copy>(
+    points: &DMatrix<T>,
+    weights: &[usize],
+    n_clusters: usize,
+) -> Vec<usize> {
+    // The weight, centroid and points of each remaining cluster.
+    let mut clusters: Vec<(T, DVector<T>, Vec<usize>)> = (0..points.nrows())
+        .map(|i| {
+            let weight = nalgebra::convert(weights[i] as f64);
+            (weight, points.row(i).transpose(), vec![i])
+        })
+        .collect();
+    while clusters.len() > n_clusters {
+        // Merging two clusters increases the within-cluster sum of squares by
+        // `w_a w_b / (w_a + w_b) ||c_a - c_b||^2`.
+        let mut best = (0, 1);
+        let mut best_cost = None;
+        for a in 0..clusters.len() {
+            for b in (a + 1)..clusters.len() {
+                let (w_a, c_a, _) = &clusters[a];
+                let (w_b, c_b, _) = &clusters[b];
+                let cost = *w_a * *w_b / (*w_a + *w_b) * (c_a - c_b).norm_squared();
+                if best_cost.is_none_or(|best_cost| cost < best_cost) {
+                    best_cost = Some(cost);
+                    best = (a, b);
+                }
+            }
+        }
+        let (w_b, c_b, members_b) = clusters.remove(best.1);
+        let (w_a, c_a, members_a) = &mut clusters[best.0];
+        *c_a = (&*c_a * *w_a + c_b * w_b) / (*w_a + w_b);
+        *w_a += w_b;
+        members_a.extend(members_b);
+    }
+    let mut labels = vec![0; points.nrows()];
+    clusters.sort_by_key(|(_, _, members)| *members.iter().min().expect("Clusters are not empty."));
+    for (cluster, (_, _, members)) in clusters.iter().enumerate() {
+        for &i in members {
+            labels[i] = cluster;
+        }
+    }
+    labels
+}
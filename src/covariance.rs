@@ -0,0 +1,475 @@
+//! Estimators of the location and covariance of multivariate data.
+//!
+//! Each estimator is trained on a matrix of observations (one row per observation) and stores the
+//! estimated location, covariance, and precision (the inverse of the covariance). Predicting gives
+//! the squared Mahalanobis distance of each observation from the estimated location, and
+//! observations can be sampled from the normal distribution with the estimated location and
+//! covariance.
+use crate::distance::squared_mahalanobis_rows;
+use crate::random::Rng;
+use crate::special::chi_squared_quantile;
+use crate::stats::median;
+use crate::traits::UnsupervisedModel;
+use crate::utils::total_cmp;
+use crate::validation::{check_2d_nonempty, check_finite, check_fraction, check_num_vars};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+fn column_means<T: RealField + Copy>(inputs: &DMatrix<T>) -> DVector<T> {
+    inputs.row_mean().transpose()
+}
+
+fn centered<T: RealField + Copy>(inputs: &DMatrix<T>, location: &DVector<T>) -> DMatrix<T> {
+    DMatrix::from_fn(inputs.nrows(), inputs.ncols(), |i, j| {
+        inputs[(i, j)] - location[j]
+    })
+}
+
+/// The maximum likelihood estimate of the covariance of observations about `location`.
+fn scatter<T: RealField + Copy>(inputs: &DMatrix<T>, location: &DVector<T>) -> DMatrix<T> {
+    let centered = centered(inputs, location);
+    let num_obs: T = nalgebra::convert(inputs.nrows() as f64);
+    (centered.transpose() * &centered) / num_obs
+}
+
+fn precision<T: RealField + Copy>(covariance: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+    covariance.clone().try_inverse().ok_or_else(|| {
+        SLearningError::InvalidData("The covariance matrix is not invertible.".to_string())
+    })
+}
+
+fn predict_mahalanobis<T: RealField + Copy>(
+    inputs: &DMatrix<T>,
+    location: &Option<DVector<T>>,
+    precision: &Option<DMatrix<T>>,
+) -> SLearningResult<DVector<T>> {
+    match (location, precision) {
+        (Some(location), Some(precision)) => {
+            check_num_vars(location.len(), inputs.ncols())?;
+            Ok(squared_mahalanobis_rows(inputs, location, precision))
+        }
+        _ => Err(SLearningError::UntrainedModel),
+    }
+}
+
+/// `n` observations (rows) drawn from the normal distribution with this location and covariance.
+fn sample_normal<T: RealField + Copy>(
+    location: &Option<DVector<T>>,
+    covariance: &Option<DMatrix<T>>,
+    n: usize,
+    rng: &mut Rng,
+) -> SLearningResult<DMatrix<T>> {
+    let (Some(location), Some(covariance)) = (location, covariance) else {
+        return Err(SLearningError::UntrainedModel);
+    };
+    let factor = covariance.clone().cholesky().ok_or_else(|| {
+        SLearningError::InvalidData("The covariance matrix is not positive definite.".to_string())
+    })?;
+    let standard = DMatrix::from_fn(n, location.len(), |_, _| rng.standard_normal::<T>());
+    let mut samples = standard * factor.l().transpose();
+    for mut row in samples.row_iter_mut() {
+        row += location.transpose();
+    }
+    Ok(samples)
+}
+
+/// Maximum likelihood estimate of the covariance.
+#[derive(Debug)]
+pub struct EmpiricalCovariance<T>
+where
+    T: RealField,
+{
+    /// The estimated mean of each variable.
+    pub location: Option<DVector<T>>,
+    pub covariance: Option<DMatrix<T>>,
+    /// The inverse of the estimated covariance.
+    pub precision: Option<DMatrix<T>>,
+}
+
+impl<T: RealField> EmpiricalCovariance<T> {
+    pub fn new() -> Self {
+        Self {
+            location: None,
+            covariance: None,
+            precision: None,
+        }
+    }
+}
+
+impl<T> EmpiricalCovariance<T>
+where
+    T: RealField + Copy,
+{
+    /// Draw `n` observations (rows) from the normal distribution with the estimated location and
+    /// covariance.
+    pub fn sample(&self, n: usize, rng: &mut Rng) -> SLearningResult<DMatrix<T>> {
+        sample_normal(&self.location, &self.covariance, n, rng)
+    }
+}
+
+impl<T> Default for EmpiricalCovariance<T>
+where
+    T: RealField,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> UnsupervisedModel<T> for EmpiricalCovariance<T>
+where
+    T: RealField + Copy,
+{
+    fn train(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        check_finite(inputs)?;
+        let location = column_means(inputs);
+        let covariance = scatter(inputs, &location);
+        self.precision = Some(precision(&covariance)?);
+        self.location = Some(location);
+        self.covariance = Some(covariance);
+        Ok(())
+    }
+
+    /// The squared Mahalanobis distance of each observation from the estimated location.
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        predict_mahalanobis(inputs, &self.location, &self.precision)
+    }
+}
+
+/// Ledoit-Wolf shrinkage estimate of the covariance.
+///
+/// The empirical covariance is shrunk towards a scaled identity matrix, with the amount of
+/// shrinkage chosen to minimise the expected squared error. This is always well-conditioned, even
+/// when there are more variables than observations.
+#[derive(Debug)]
+pub struct LedoitWolf<T>
+where
+    T: RealField,
+{
+    /// The estimated mean of each variable.
+    pub location: Option<DVector<T>>,
+    pub covariance: Option<DMatrix<T>>,
+    /// The inverse of the estimated covariance.
+    pub precision: Option<DMatrix<T>>,
+    /// The weight (between zero and one) given to the scaled identity matrix.
+    pub shrinkage: Option<T>,
+}
+
+impl<T: RealField> LedoitWolf<T> {
+    pub fn new() -> Self {
+        Self {
+            location: None,
+            covariance: None,
+            precision: None,
+            shrinkage: None,
+        }
+    }
+}
+
+impl<T> LedoitWolf<T>
+where
+    T: RealField + Copy,
+{
+    /// Draw `n` observations (rows) from the normal distribution with the estimated location and
+    /// covariance.
+    pub fn sample(&self, n: usize, rng: &mut Rng) -> SLearningResult<DMatrix<T>> {
+        sample_normal(&self.location, &self.covariance, n, rng)
+    }
+}
+
+impl<T> Default for LedoitWolf<T>
+where
+    T: RealField,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The Ledoit-Wolf shrinkage for observations that are already centered.
+fn ledoit_wolf_shrinkage<T: RealField + Copy>(centered: &DMatrix<T>, empirical: &DMatrix<T>) -> T {
+    let num_obs: T = nalgebra::convert(centered.nrows() as f64);
+    let num_vars: T = nalgebra::convert(centered.ncols() as f64);
+    let mu = empirical.trace() / num_vars;
+
+    // Distance between the empirical covariance and the shrinkage target.
+    let mut target_distance = empirical.clone();
+    for i in 0..target_distance.nrows() {
+        target_distance[(i, i)] -= mu;
+    }
+    let delta = target_distance.norm_squared() / num_vars;
+
+    // Variance of the empirical covariance entries.
+    let squared = centered.component_mul(centered);
+    let beta_sum = (squared.transpose() * &squared).sum();
+    let beta = (beta_sum / num_obs - empirical.norm_squared()) / (num_vars * num_obs);
+
+    let beta = beta.min(delta);
+    if beta.is_zero() {
+        T::zero()
+    } else {
+        beta / delta
+    }
+}
+
+impl<T> UnsupervisedModel<T> for LedoitWolf<T>
+where
+    T: RealField + Copy,
+{
+    fn train(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        check_finite(inputs)?;
+        let location = column_means(inputs);
+        let centered = centered(inputs, &location);
+        let empirical = scatter(inputs, &location);
+
+        let shrinkage = ledoit_wolf_shrinkage(&centered, &empirical);
+        let num_vars: T = nalgebra::convert(inputs.ncols() as f64);
+        let mu = empirical.trace() / num_vars;
+        let mut covariance = empirical * (T::one() - shrinkage);
+        for i in 0..covariance.nrows() {
+            covariance[(i, i)] += shrinkage * mu;
+        }
+
+        self.precision = Some(precision(&covariance)?);
+        self.location = Some(location);
+        self.covariance = Some(covariance);
+        self.shrinkage = Some(shrinkage);
+        Ok(())
+    }
+
+    /// The squared Mahalanobis distance of each observation from the estimated location.
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        predict_mahalanobis(inputs, &self.location, &self.precision)
+    }
+}
+
+/// Minimum Covariance Determinant (MCD) robust estimate of the location and covariance.
+///
+/// This finds the subset of observations (the support) whose covariance has the smallest
+/// determinant, so that outliers have little influence on the estimate. The support is found using
+/// "concentration steps" from several deterministic starting subsets, then the estimate is
+/// corrected for consistency with the normal distribution and re-weighted to improve efficiency.
+#[derive(Debug)]
+pub struct MinCovDet<T>
+where
+    T: RealField,
+{
+    /// The estimated location of each variable.
+    pub location: Option<DVector<T>>,
+    pub covariance: Option<DMatrix<T>>,
+    /// The inverse of the estimated covariance.
+    pub precision: Option<DMatrix<T>>,
+    /// Whether each training observation was used in the final (re-weighted) estimate.
+    pub support: Option<Vec<bool>>,
+    /// The proportion of observations in the raw MCD support. Defaults to `(n + p + 1) / 2n`.
+    support_fraction: Option<T>,
+}
+
+impl<T> MinCovDet<T>
+where
+    T: RealField,
+{
+    pub fn new(support_fraction: T) -> SLearningResult<Self> {
+        check_fraction(support_fraction.clone(), "Support fraction")?;
+        Ok(Self {
+            location: None,
+            covariance: None,
+            precision: None,
+            support: None,
+            support_fraction: Some(support_fraction),
+        })
+    }
+}
+
+impl<T> MinCovDet<T>
+where
+    T: RealField + Copy,
+{
+    /// Draw `n` observations (rows) from the normal distribution with the estimated location and
+    /// covariance.
+    pub fn sample(&self, n: usize, rng: &mut Rng) -> SLearningResult<DMatrix<T>> {
+        sample_normal(&self.location, &self.covariance, n, rng)
+    }
+}
+
+impl<T> Default for MinCovDet<T>
+where
+    T: RealField,
+{
+    fn default() -> Self {
+        Self {
+            location: None,
+            covariance: None,
+            precision: None,
+            support: None,
+            support_fraction: None,
+        }
+    }
+}
+
+/// The (Cholesky) log-determinant of a covariance matrix, or `None` if it is singular.
+fn log_determinant<T: RealField + Copy>(covariance: &DMatrix<T>) -> Option<T> {
+    let cholesky = covariance.clone().cholesky()?;
+    let diagonal = cholesky.l_dirty().diagonal();
+    let two: T = nalgebra::convert(2.0);
+    Some(diagonal.iter().fold(T::zero(), |acc, &d| acc + d.ln()) * two)
+}
+
+/// Indices of the `count` smallest values.
+fn smallest_indices<T: RealField + Copy>(values: &DVector<T>, count: usize) -> Vec<usize> {
+    let mut indices: Vec<usize> = (0..values.len()).collect();
+    indices.sort_by(|&a, &b| total_cmp(&values[a], &values[b]));
+    indices.truncate(count);
+    indices.sort_unstable();
+    indices
+}
+
+struct Support<T: RealField> {
+    location: DVector<T>,
+    covariance: DMatrix<T>,
+    log_determinant: T,
+}
+
+/// Repeatedly replace the support with the observations closest to its location, which never
+/// increases the determinant of its covariance, until it converges.
+fn concentrate<T: RealField + Copy>(
+    inputs: &DMatrix<T>,
+    initial: Vec<usize>,
+    support_size: usize,
+) -> Option<Support<T>> {
+    const MAX_STEPS: usize = 100;
+
+    let mut indices = initial;
+    let mut best: Option<Support<T>> = None;
+    for _ in 0..MAX_STEPS {
+        let subset = inputs.select_rows(&indices);
+        let location = column_means(&subset);
+        let covariance = scatter(&subset, &location);
+        let log_det = log_determinant(&covariance)?;
+        if let Some(previous) = &best {
+            if log_det >= previous.log_determinant {
+                break;
+            }
+        }
+        let precision = covariance.clone().try_inverse()?;
+        let distances = squared_mahalanobis_rows(inputs, &location, &precision);
+        best = Some(Support {
+            location,
+            covariance,
+            log_determinant: log_det,
+        });
+        let next = smallest_indices(&distances, support_size);
+        if next == indices {
+            break;
+        }
+        indices = next;
+    }
+    best
+}
+
+/// Deterministic starting subsets for the concentration steps.
+fn initial_subsets<T: RealField + Copy>(
+    inputs: &DMatrix<T>,
+    support_size: usize,
+) -> Vec<Vec<usize>> {
+    let mut subsets = vec![];
+
+    // The observations closest to the coordinate-wise median, after scaling each variable by its
+    // median absolute deviation.
+    let medians = DVector::from_fn(inputs.ncols(), |j, _| {
+        median(&inputs.column(j).into_owned())
+    });
+    let scales = DVector::from_fn(inputs.ncols(), |j, _| {
+        let deviations = inputs.column(j).map(|x| (x - medians[j]).abs());
+        let mad = median(&deviations);
+        if mad.is_zero() {
+            T::one()
+        } else {
+            mad
+        }
+    });
+    let distances = DVector::from_fn(inputs.nrows(), |i, _| {
+        (0..inputs.ncols()).fold(T::zero(), |acc, j| {
+            let z = (inputs[(i, j)] - medians[j]) / scales[j];
+            acc + z * z
+        })
+    });
+    subsets.push(smallest_indices(&distances, support_size));
+
+    // The observations closest to the classical estimate.
+    let location = column_means(inputs);
+    if let Some(precision) = scatter(inputs, &location).try_inverse() {
+        let distances = squared_mahalanobis_rows(inputs, &location, &precision);
+        subsets.push(smallest_indices(&distances, support_size));
+    }
+    subsets
+}
+
+impl<T> UnsupervisedModel<T> for MinCovDet<T>
+where
+    T: RealField + Copy,
+{
+    fn train(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        check_finite(inputs)?;
+        let (num_obs, num_vars) = inputs.shape();
+        let support_size = match self.support_fraction {
+            Some(fraction) => {
+                let size: T = fraction * nalgebra::convert(num_obs as f64);
+                nalgebra::try_convert::<T, f64>(size.ceil()).unwrap_or(0.0) as usize
+            }
+            None => (num_obs + num_vars + 1).div_ceil(2),
+        }
+        .min(num_obs);
+        if support_size <= num_vars {
+            return Err(SLearningError::InvalidData(format!(
+                "The support has {} observation(s), but must have more than the number of variables ({}).",
+                support_size, num_vars
+            )));
+        }
+
+        let raw = initial_subsets(inputs, support_size)
+            .into_iter()
+            .filter_map(|initial| concentrate(inputs, initial, support_size))
+            .min_by(|a, b| total_cmp(&a.log_determinant, &b.log_determinant))
+            .ok_or_else(|| {
+                SLearningError::InvalidData(
+                    "The covariance matrix of every candidate support is singular.".to_string(),
+                )
+            })?;
+
+        // Scale the raw covariance so that it is consistent at the normal distribution.
+        let degrees_of_freedom = num_vars as f64;
+        let raw_precision = precision(&raw.covariance)?;
+        let distances = squared_mahalanobis_rows(inputs, &raw.location, &raw_precision);
+        let consistency: T =
+            median(&distances) / nalgebra::convert(chi_squared_quantile(0.5, degrees_of_freedom));
+        let raw_covariance = raw.covariance * consistency;
+        let distances = distances / consistency;
+
+        // Re-weight by only using the observations that are not flagged as outliers.
+        let threshold: T = nalgebra::convert(chi_squared_quantile(0.975, degrees_of_freedom));
+        let support: Vec<bool> = distances.iter().map(|&d| d < threshold).collect();
+        let support_indices: Vec<usize> = (0..num_obs).filter(|&i| support[i]).collect();
+        let subset = inputs.select_rows(&support_indices);
+        let location = column_means(&subset);
+        let covariance = scatter(&subset, &location);
+        let (location, covariance, precision) = match precision(&covariance) {
+            Ok(precision) => (location, covariance, precision),
+            Err(_) => (raw.location, raw_covariance, raw_precision / consistency),
+        };
+
+        self.precision = Some(precision);
+        self.location = Some(location);
+        self.covariance = Some(covariance);
+        self.support = Some(support);
+        Ok(())
+    }
+
+    /// The squared Mahalanobis distance of each observation from the estimated location.
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        predict_mahalanobis(inputs, &self.location, &self.precision)
+    }
+}
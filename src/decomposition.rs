@@ -0,0 +1,891 @@
+//! Decompositions of multivariate data into components.
+use crate::diagnostics::{Diagnostics, Warning};
+use crate::optim::ConvergenceConfig;
+use crate::random::Rng;
+use crate::stats::OnlineMeanVariance;
+use crate::traits::Transformer;
+use crate::utils::total_cmp;
+use crate::validation::{
+    check_2d_nonempty, check_finite, check_fitted, check_non_negative, check_num_vars, is_missing,
+};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+/// The columns of `inputs` minus their means, and the means.
+fn center<T: RealField + Copy>(inputs: &DMatrix<T>) -> (DMatrix<T>, DVector<T>) {
+    let mean = inputs.row_mean().transpose();
+    let centered = DMatrix::from_fn(inputs.nrows(), inputs.ncols(), |i, j| {
+        inputs[(i, j)] - mean[j]
+    });
+    (centered, mean)
+}
+
+/// The lower Cholesky factor of a view's covariance matrix.
+fn covariance_factor<T: RealField + Copy>(
+    covariance: DMatrix<T>,
+    view: &str,
+) -> SLearningResult<DMatrix<T>> {
+    match covariance.cholesky() {
+        Some(cholesky) => Ok(cholesky.l()),
+        None => {
+            let error_msg = format!(
+                "The covariance matrix of the {} view is not positive definite. Try adding regularization.",
+                view
+            );
+            Err(SLearningError::InvalidData(error_msg))
+        }
+    }
+}
+
+/// The projection of centered observations onto some weights.
+fn project<T: RealField + Copy>(
+    inputs: &DMatrix<T>,
+    mean: &Option<DVector<T>>,
+    weights: &Option<DMatrix<T>>,
+) -> SLearningResult<DMatrix<T>> {
+    let mean = check_fitted(mean)?;
+    let weights = check_fitted(weights)?;
+    check_num_vars(mean.len(), inputs.ncols())?;
+    let centered = DMatrix::from_fn(inputs.nrows(), inputs.ncols(), |i, j| {
+        inputs[(i, j)] - mean[j]
+    });
+    Ok(centered * weights)
+}
+
+/// Canonical correlation analysis (CCA) of two views of the same observations.
+///
+/// Finds pairs of linear projections, one of each view, such that the projections in each pair
+/// have the largest possible correlation, and are uncorrelated with the projections in the other
+/// pairs. The projections (canonical variates) of the training data have unit variance.
+///
+/// A regularization can be added to the diagonal of each view's covariance matrix, which is needed
+/// when a view has more variables than observations or collinear variables.
+#[derive(Debug)]
+pub struct Cca<T>
+where
+    T: RealField,
+{
+    /// The weights of the first view's variables (rows) in each component (columns).
+    pub x_weights: Option<DMatrix<T>>,
+    /// The weights of the second view's variables (rows) in each component (columns).
+    pub y_weights: Option<DMatrix<T>>,
+    /// The correlation between the projections of the two views in each component, in decreasing
+    /// order.
+    pub correlations: Option<DVector<T>>,
+    n_components: usize,
+    regularization: T,
+    x_mean: Option<DVector<T>>,
+    y_mean: Option<DVector<T>>,
+}
+
+impl<T> Cca<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(n_components: usize) -> SLearningResult<Self> {
+        if n_components == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Number of components must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            x_weights: None,
+            y_weights: None,
+            correlations: None,
+            n_components,
+            regularization: T::zero(),
+            x_mean: None,
+            y_mean: None,
+        })
+    }
+
+    /// Add `regularization` to the diagonal of each view's covariance matrix.
+    pub fn with_regularization(self, regularization: T) -> SLearningResult<Self> {
+        check_non_negative(regularization, "Regularization")?;
+        Ok(Self {
+            regularization,
+            ..self
+        })
+    }
+
+    pub fn fit(&mut self, x: &DMatrix<T>, y: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(x)?;
+        check_2d_nonempty(y)?;
+        check_finite(x)?;
+        check_finite(y)?;
+        if x.nrows() != y.nrows() {
+            let error_msg = format!(
+                "The first view has {} observation(s), but the second view has {} observation(s). These must be equal.",
+                x.nrows(),
+                y.nrows()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        let max_components = x.ncols().min(y.ncols());
+        if self.n_components > max_components {
+            let error_msg = format!(
+                "Number of components is {}, but must be at most {}, the number of variables in the smaller view.",
+                self.n_components, max_components
+            );
+            return Err(SLearningError::InvalidParameters(error_msg));
+        }
+
+        let (x_centered, x_mean) = center(x);
+        let (y_centered, y_mean) = center(y);
+        let num_obs: T = nalgebra::convert(x.nrows() as f64);
+        let regularized = |centered: &DMatrix<T>| {
+            let mut covariance = centered.transpose() * centered / num_obs;
+            for i in 0..centered.ncols() {
+                covariance[(i, i)] += self.regularization;
+            }
+            covariance
+        };
+        let x_factor = covariance_factor(regularized(&x_centered), "first")?;
+        let y_factor = covariance_factor(regularized(&y_centered), "second")?;
+        let cross_covariance = x_centered.transpose() * &y_centered / num_obs;
+
+        // In whitened coordinates, the canonical directions are the singular vectors of the
+        // whitened cross-covariance `L_x^-1 S_xy L_y^-T`.
+        let half_whitened = x_factor
+            .solve_lower_triangular(&cross_covariance)
+            .expect("The Cholesky factor is invertible.");
+        let whitened = y_factor
+            .solve_lower_triangular(&half_whitened.transpose())
+            .expect("The Cholesky factor is invertible.")
+            .transpose();
+        let svd = whitened.svd(true, true);
+        let u = svd.u.expect("The left singular vectors were computed.");
+        let v_t = svd.v_t.expect("The right singular vectors were computed.");
+
+        let k = self.n_components;
+        let mut x_weights = x_factor
+            .transpose()
+            .solve_upper_triangular(&u.columns(0, k).into_owned())
+            .expect("The Cholesky factor is invertible.");
+        let mut y_weights = y_factor
+            .transpose()
+            .solve_upper_triangular(&v_t.rows(0, k).transpose())
+            .expect("The Cholesky factor is invertible.");
+        // Choose the sign of each pair so that the largest first view weight is positive.
+        for component in 0..k {
+            let largest = x_weights.column(component).iamax();
+            if x_weights[(largest, component)].is_negative() {
+                x_weights.column_mut(component).neg_mut();
+                y_weights.column_mut(component).neg_mut();
+            }
+        }
+
+        self.correlations = Some(svd.singular_values.rows(0, k).into_owned());
+        self.x_weights = Some(x_weights);
+        self.y_weights = Some(y_weights);
+        self.x_mean = Some(x_mean);
+        self.y_mean = Some(y_mean);
+        Ok(())
+    }
+
+    /// The canonical variates of observations of the first view (one column per component).
+    pub fn transform_x(&self, x: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        project(x, &self.x_mean, &self.x_weights)
+    }
+
+    /// The canonical variates of observations of the second view (one column per component).
+    pub fn transform_y(&self, y: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        project(y, &self.y_mean, &self.y_weights)
+    }
+}
+
+/// How observations are encoded as sparse combinations of the atoms of a dictionary.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SparseCoding<T> {
+    /// Minimise `||x - D a||^2 / 2 + alpha ||a||_1` by coordinate descent, so larger `alpha` gives
+    /// sparser codes.
+    Lasso { alpha: T },
+    /// Orthogonal matching pursuit, which greedily adds the atom most correlated with the residual
+    /// and refits by least squares, until `n_nonzero` atoms are used.
+    Omp { n_nonzero: usize },
+}
+
+impl<T> SparseCoding<T>
+where
+    T: RealField + Copy,
+{
+    fn validate(&self) -> SLearningResult<()> {
+        match self {
+            SparseCoding::Lasso { alpha } => check_non_negative(*alpha, "Alpha"),
+            SparseCoding::Omp { n_nonzero: 0 } => Err(SLearningError::InvalidParameters(
+                "Number of non-zero coefficients must be at least one.".to_string(),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// The code of each row of `inputs` (rows) for the dictionary with atoms as the columns of
+    /// `atoms`.
+    fn encode(&self, inputs: &DMatrix<T>, atoms: &DMatrix<T>) -> DMatrix<T> {
+        let gram = atoms.transpose() * atoms;
+        let correlations = inputs * atoms;
+        let mut codes = DMatrix::zeros(inputs.nrows(), atoms.ncols());
+        for i in 0..inputs.nrows() {
+            let correlation = correlations.row(i).transpose();
+            let code = match *self {
+                SparseCoding::Lasso { alpha } => lasso_code(&gram, &correlation, alpha),
+                SparseCoding::Omp { n_nonzero } => omp_code(&gram, &correlation, n_nonzero),
+            };
+            codes.set_row(i, &code.transpose());
+        }
+        codes
+    }
+}
+
+/// Minimise `a' G a / 2 - a' c + alpha ||a||_1` by cyclic coordinate descent.
+fn lasso_code<T: RealField + Copy>(
+    gram: &DMatrix<T>,
+    correlation: &DVector<T>,
+    alpha: T,
+) -> DVector<T> {
+    const MAX_SWEEPS: usize = 1000;
+    let tol = T::default_epsilon().sqrt();
+    let mut code = DVector::zeros(correlation.len());
+    // `G a`, which is updated as each coefficient changes.
+    let mut gram_code = DVector::zeros(correlation.len());
+    for _ in 0..MAX_SWEEPS {
+        let mut max_change = T::zero();
+        for j in 0..code.len() {
+            let curvature = gram[(j, j)];
+            if curvature.is_zero() {
+                continue;
+            }
+            let partial = correlation[j] - gram_code[j] + curvature * code[j];
+            let shrunk = partial.abs() - alpha;
+            let new_value = match shrunk > T::zero() {
+                true => shrunk.copysign(partial) / curvature,
+                false => T::zero(),
+            };
+            let change = new_value - code[j];
+            if !change.is_zero() {
+                gram_code.axpy(change, &gram.column(j), T::one());
+                code[j] = new_value;
+            }
+            max_change = max_change.max(change.abs());
+        }
+        if max_change <= tol * (T::one() + code.amax()) {
+            break;
+        }
+    }
+    code
+}
+
+/// Orthogonal matching pursuit using only the Gram matrix of the atoms and their correlations with
+/// the observation.
+fn omp_code<T: RealField + Copy>(
+    gram: &DMatrix<T>,
+    correlation: &DVector<T>,
+    n_nonzero: usize,
+) -> DVector<T> {
+    let tol = T::default_epsilon().sqrt();
+    let mut code = DVector::zeros(correlation.len());
+    let mut selected: Vec<usize> = Vec::new();
+    let mut residual_correlation = correlation.clone();
+    while selected.len() < n_nonzero.min(correlation.len()) {
+        let candidate = (0..correlation.len())
+            .filter(|j| !selected.contains(j))
+            .max_by(|&a, &b| {
+                total_cmp(
+                    &residual_correlation[a].abs(),
+                    &residual_correlation[b].abs(),
+                )
+            })
+            .expect("There are unselected atoms.");
+        if residual_correlation[candidate].abs() <= tol {
+            break;
+        }
+        selected.push(candidate);
+        let sub_gram = gram
+            .select_rows(selected.iter())
+            .select_columns(selected.iter());
+        let Some(cholesky) = sub_gram.cholesky() else {
+            // The new atom is a combination of the selected atoms, so cannot reduce the residual.
+            selected.pop();
+            break;
+        };
+        let sub_code = cholesky.solve(&correlation.select_rows(selected.iter()));
+        code.fill(T::zero());
+        for (&j, &value) in selected.iter().zip(sub_code.iter()) {
+            code[j] = value;
+        }
+        residual_correlation = correlation - gram * &code;
+    }
+    code
+}
+
+/// Dictionary learning, which learns a set of atoms such that each observation is approximately a
+/// sparse combination of them.
+///
+/// Each iteration encodes a batch of observations with the current dictionary, then updates the
+/// atoms by block coordinate descent on the accumulated statistics of all the codes so far, with
+/// older batches given less weight (Mairal et al., Online Dictionary Learning for Sparse Coding,
+/// 2009). By default every observation is in every batch. Atoms have a norm of at most one, and
+/// are initialised to randomly chosen observations. Training stops once no atom changes by more
+/// than the tolerance in an iteration.
+///
+/// Transforming gives the sparse code of each observation (one column per atom).
+#[derive(Debug)]
+pub struct DictionaryLearning<T>
+where
+    T: RealField,
+{
+    /// The atoms of the dictionary (rows).
+    pub components: Option<DMatrix<T>>,
+    n_components: usize,
+    coding: SparseCoding<T>,
+    batch_size: Option<usize>,
+    seed: u64,
+    convergence: ConvergenceConfig<T>,
+    diagnostics: Diagnostics,
+}
+
+impl<T> DictionaryLearning<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(n_components: usize, coding: SparseCoding<T>) -> SLearningResult<Self> {
+        if n_components == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Number of components must be at least one.".to_string(),
+            ));
+        }
+        coding.validate()?;
+        Ok(Self {
+            components: None,
+            n_components,
+            coding,
+            batch_size: None,
+            seed: 0,
+            convergence: ConvergenceConfig::new(100, nalgebra::convert(1e-6))
+                .expect("The default parameters are valid."),
+            diagnostics: Diagnostics::default(),
+        })
+    }
+
+    /// Set when the iterations stop, based on the largest change in any atom.
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+
+    /// Send warnings from training, e.g. that it stopped before converging, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// Encode a random batch of this many observations in each iteration (mini-batch dictionary
+    /// learning), rather than all of them.
+    pub fn with_batch_size(self, batch_size: usize) -> SLearningResult<Self> {
+        if batch_size == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Batch size must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            batch_size: Some(batch_size),
+            ..self
+        })
+    }
+
+    /// Set the seed for the initial atoms and the batches.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+}
+
+/// Scale a vector to have a norm of at most one.
+fn clip_norm<T: RealField + Copy>(mut atom: DVector<T>) -> DVector<T> {
+    let norm = atom.norm();
+    if norm > T::one() {
+        atom /= norm;
+    }
+    atom
+}
+
+/// A random observation scaled to have a norm of one, or a random unit vector if it is zero.
+fn random_atom<T: RealField + Copy>(inputs: &DMatrix<T>, rng: &mut Rng) -> DVector<T> {
+    let observation = inputs.row(rng.below(inputs.nrows())).transpose();
+    match observation.norm().is_zero() {
+        true => DVector::from_fn(inputs.ncols(), |_, _| rng.standard_normal()).normalize(),
+        false => observation.normalize(),
+    }
+}
+
+impl<T> Transformer<T> for DictionaryLearning<T>
+where
+    T: RealField + Copy,
+{
+    fn fit(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        check_finite(inputs)?;
+        let num_obs = inputs.nrows();
+        let num_vars = inputs.ncols();
+        let k = self.n_components;
+        let mut rng = Rng::new(self.seed);
+        let mut atoms = DMatrix::zeros(num_vars, k);
+        for j in 0..k {
+            atoms.set_column(j, &random_atom(inputs, &mut rng));
+        }
+
+        let batch_size = self.batch_size.unwrap_or(num_obs).min(num_obs);
+        let batch_t: T = nalgebra::convert(batch_size as f64);
+        let mut code_moments = DMatrix::zeros(k, k);
+        let mut input_moments = DMatrix::zeros(num_vars, k);
+        let mut converged = false;
+        for step in 0..self.convergence.max_iter() {
+            let batch = match batch_size == num_obs {
+                true => inputs.clone(),
+                false => inputs.select_rows(rng.sample_indices(num_obs, batch_size).iter()),
+            };
+            let codes = self.coding.encode(&batch, &atoms);
+
+            // Down-weight the statistics of earlier batches, whose codes used worse dictionaries.
+            // With every observation in every batch, only the latest codes are kept.
+            let theta = match step + 1 < batch_size {
+                true => (step + 1) * batch_size,
+                false => batch_size * batch_size + step + 1 - batch_size,
+            } as f64;
+            let beta: T = match batch_size == num_obs {
+                true => T::zero(),
+                false => nalgebra::convert((theta + 1.0 - batch_size as f64) / (theta + 1.0)),
+            };
+            code_moments = code_moments * beta + codes.transpose() * &codes / batch_t;
+            input_moments = input_moments * beta + batch.transpose() * &codes / batch_t;
+
+            let previous_atoms = atoms.clone();
+            for j in 0..k {
+                let curvature = code_moments[(j, j)];
+                let atom = match curvature > T::default_epsilon() {
+                    true => {
+                        let residual = input_moments.column(j) - &atoms * code_moments.column(j);
+                        clip_norm(atoms.column(j) + residual / curvature)
+                    }
+                    // The atom is unused, so replace it with a random observation.
+                    false => random_atom(inputs, &mut rng),
+                };
+                atoms.set_column(j, &atom);
+            }
+            if (&atoms - previous_atoms).amax() <= self.convergence.tol() {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            self.diagnostics.warn(Warning::NotConverged {
+                max_iter: self.convergence.max_iter(),
+            });
+        }
+        self.components = Some(atoms.transpose());
+        Ok(())
+    }
+
+    fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let components = check_fitted(&self.components)?;
+        check_num_vars(components.ncols(), inputs.ncols())?;
+        check_finite(inputs)?;
+        Ok(self.coding.encode(inputs, &components.transpose()))
+    }
+}
+
+/// Flip the sign of each component (row) so that its largest loading is positive, so that repeated
+/// fits give the same components.
+fn fix_signs<T: RealField + Copy>(components: &mut DMatrix<T>) {
+    for component in 0..components.nrows() {
+        let largest = components.row(component).transpose().iamax();
+        if components[(component, largest)].is_negative() {
+            components.row_mut(component).neg_mut();
+        }
+    }
+}
+
+/// Principal component analysis (PCA) that is fitted one batch of observations at a time, so the
+/// data never has to be held in memory at once.
+///
+/// Each batch updates the components by an SVD of the previous components (scaled by their
+/// singular values), the centred batch, and a row correcting for the shift in the mean (Ross et
+/// al., Incremental Learning for Robust Visual Tracking, 2008). After any sequence of batches,
+/// the components match those of a full SVD of all the observations seen so far, up to sign and
+/// the variance lost in truncating to `n_components` at each step.
+///
+/// Transforming gives the projection of the centred observations onto the components (one column
+/// per component). Each component's sign is chosen so that its largest loading is positive.
+#[derive(Debug)]
+pub struct IncrementalPca<T>
+where
+    T: RealField,
+{
+    /// The principal axes (rows), in order of decreasing explained variance.
+    pub components: Option<DMatrix<T>>,
+    /// The singular values of the centred observations for each component.
+    pub singular_values: Option<DVector<T>>,
+    /// The variance of the observations along each component.
+    pub explained_variance: Option<DVector<T>>,
+    /// The proportion of the total variance explained by each component.
+    pub explained_variance_ratio: Option<DVector<T>>,
+    moments: Option<OnlineMeanVariance<T>>,
+    n_components: usize,
+    batch_size: Option<usize>,
+}
+
+impl<T> IncrementalPca<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(n_components: usize) -> SLearningResult<Self> {
+        if n_components == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Number of components must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            components: None,
+            singular_values: None,
+            explained_variance: None,
+            explained_variance_ratio: None,
+            moments: None,
+            n_components,
+            batch_size: None,
+        })
+    }
+
+    /// When fitting, pass the observations to `partial_fit` in batches of this many (the last
+    /// batch takes any remainder), rather than all at once.
+    pub fn with_batch_size(self, batch_size: usize) -> SLearningResult<Self> {
+        if batch_size == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Batch size must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            batch_size: Some(batch_size),
+            ..self
+        })
+    }
+
+    /// The number of observations seen so far.
+    pub fn count(&self) -> usize {
+        self.moments.as_ref().map_or(0, |moments| moments.count())
+    }
+
+    /// The mean of the observations seen so far.
+    pub fn mean(&self) -> SLearningResult<DVector<T>> {
+        check_fitted(&self.moments)?.mean()
+    }
+
+    /// Update the components with a batch of observations. The first batch must have at least
+    /// `n_components` observations and variables.
+    pub fn partial_fit(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        check_finite(inputs)?;
+        let k = self.n_components;
+        let (mut moments, stacked) = match (&self.moments, &self.components, &self.singular_values)
+        {
+            (Some(moments), Some(components), Some(singular_values)) => {
+                check_num_vars(components.ncols(), inputs.ncols())?;
+                let (centered, batch_mean) = center(inputs);
+                let previous_count = moments.count() as f64;
+                let batch_count = inputs.nrows() as f64;
+                let correction: T = nalgebra::convert(
+                    (previous_count * batch_count / (previous_count + batch_count)).sqrt(),
+                );
+                let mean_shift = (moments.mean()? - batch_mean).transpose() * correction;
+
+                let mut stacked = DMatrix::zeros(k + inputs.nrows() + 1, inputs.ncols());
+                stacked
+                    .rows_mut(0, k)
+                    .copy_from(&(DMatrix::from_diagonal(singular_values) * components));
+                stacked.rows_mut(k, inputs.nrows()).copy_from(&centered);
+                stacked.row_mut(k + inputs.nrows()).copy_from(&mean_shift);
+                (moments.clone(), stacked)
+            }
+            _ => {
+                if k > inputs.nrows() || k > inputs.ncols() {
+                    let error_msg = format!(
+                        "Number of components is {}, but must be at most {}, the smaller of the number of observations and variables in the first batch.",
+                        k,
+                        inputs.nrows().min(inputs.ncols())
+                    );
+                    return Err(SLearningError::InvalidParameters(error_msg));
+                }
+                (OnlineMeanVariance::new(inputs.ncols()), center(inputs).0)
+            }
+        };
+        moments.update_batch(inputs)?;
+
+        let svd = stacked.svd(false, true);
+        let mut components = svd
+            .v_t
+            .expect("The right singular vectors were computed.")
+            .rows(0, k)
+            .into_owned();
+        fix_signs(&mut components);
+        let singular_values = svd.singular_values.rows(0, k).into_owned();
+        let squared = singular_values.map(|value| value * value);
+        let total_variance = moments.var(0)?.sum() * nalgebra::convert(moments.count() as f64);
+        self.explained_variance_ratio = Some(match total_variance.is_zero() {
+            true => DVector::zeros(k),
+            false => &squared / total_variance,
+        });
+        self.explained_variance = Some(match moments.count() > 1 {
+            true => squared / nalgebra::convert::<f64, T>((moments.count() - 1) as f64),
+            false => DVector::zeros(k),
+        });
+        self.singular_values = Some(singular_values);
+        self.components = Some(components);
+        self.moments = Some(moments);
+        Ok(())
+    }
+
+    /// Map projections back to the original variables.
+    pub fn inverse_transform(&self, projections: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let components = check_fitted(&self.components)?;
+        if projections.ncols() != components.nrows() {
+            let error_msg = format!(
+                "This model has {} components, but this input has {} columns. These must be equal.",
+                components.nrows(),
+                projections.ncols()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        let mean = self.mean()?;
+        let mut outputs = projections * components;
+        for mut row in outputs.row_iter_mut() {
+            row += mean.transpose();
+        }
+        Ok(outputs)
+    }
+}
+
+impl<T> Transformer<T> for IncrementalPca<T>
+where
+    T: RealField + Copy,
+{
+    fn fit(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        self.components = None;
+        self.singular_values = None;
+        self.moments = None;
+        let batch_size = self.batch_size.unwrap_or(inputs.nrows());
+        let mut start = 0;
+        while start < inputs.nrows() {
+            let size = batch_size.min(inputs.nrows() - start);
+            self.partial_fit(&inputs.rows(start, size).into_owned())?;
+            start += size;
+        }
+        Ok(())
+    }
+
+    fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let components = check_fitted(&self.components)?;
+        check_num_vars(components.ncols(), inputs.ncols())?;
+        let mean = self.mean()?;
+        let centered = DMatrix::from_fn(inputs.nrows(), inputs.ncols(), |i, j| {
+            inputs[(i, j)] - mean[j]
+        });
+        Ok(centered * components.transpose())
+    }
+}
+
+/// Principal component analysis (PCA) of observations with missing (NaN) values.
+///
+/// Fitting starts by filling in each missing value with the mean of the observed values of its
+/// variable, then alternates between finding the components of the filled-in observations and
+/// replacing the missing values with their reconstruction from the top `n_components` components
+/// (an expectation-maximisation style iterative SVD). It stops when the largest change in a
+/// filled-in value is at most the tolerance.
+///
+/// Transforming gives the scores of each observation that best reconstruct its observed values,
+/// by least squares, which are the usual projections when no values are missing.
+#[derive(Debug)]
+pub struct MissingValuesPca<T>
+where
+    T: RealField,
+{
+    /// The principal axes (rows), in order of decreasing explained variance.
+    pub components: Option<DMatrix<T>>,
+    /// The mean of each variable, after filling in the missing values.
+    pub mean: Option<DVector<T>>,
+    /// The variance of the filled-in observations along each component.
+    pub explained_variance: Option<DVector<T>>,
+    /// The training observations with their missing values filled in.
+    pub imputed: Option<DMatrix<T>>,
+    n_components: usize,
+    convergence: ConvergenceConfig<T>,
+    diagnostics: Diagnostics,
+}
+
+impl<T> MissingValuesPca<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(n_components: usize) -> SLearningResult<Self> {
+        if n_components == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Number of components must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            components: None,
+            mean: None,
+            explained_variance: None,
+            imputed: None,
+            n_components,
+            convergence: ConvergenceConfig::default(),
+            diagnostics: Diagnostics::default(),
+        })
+    }
+
+    /// Set when the iterations stop, based on the largest change in any filled-in value.
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+
+    /// Send warnings from training, e.g. that it stopped before converging, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// The observations with their missing values replaced by their reconstruction from the
+    /// components.
+    pub fn impute(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let components = check_fitted(&self.components)?;
+        let mean = check_fitted(&self.mean)?;
+        let scores = self.transform(inputs)?;
+        let mut imputed = inputs.clone();
+        for j in 0..inputs.ncols() {
+            for i in 0..inputs.nrows() {
+                if is_missing(&inputs[(i, j)]) {
+                    imputed[(i, j)] =
+                        mean[j] + scores.row(i).dot(&components.column(j).transpose());
+                }
+            }
+        }
+        Ok(imputed)
+    }
+}
+
+impl<T> Transformer<T> for MissingValuesPca<T>
+where
+    T: RealField + Copy,
+{
+    fn fit(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        let k = self.n_components;
+        if k > inputs.nrows() || k > inputs.ncols() {
+            let error_msg = format!(
+                "Number of components is {}, but must be at most {}, the smaller of the number of observations and variables.",
+                k,
+                inputs.nrows().min(inputs.ncols())
+            );
+            return Err(SLearningError::InvalidParameters(error_msg));
+        }
+        let mut missing = Vec::new();
+        let mut filled = inputs.clone();
+        for (var, mut column) in filled.column_iter_mut().enumerate() {
+            let observed: Vec<T> = column.iter().copied().filter(|x| !is_missing(x)).collect();
+            if observed.is_empty() {
+                let error_msg = format!("Variable {} has no observed values.", var);
+                return Err(SLearningError::InvalidData(error_msg));
+            }
+            if let Some(obs) = column.iter().position(|x| !is_missing(x) && !x.is_finite()) {
+                let error_msg = format!(
+                    "Input has a non-finite value for observation {} and variable {}.",
+                    obs, var
+                );
+                return Err(SLearningError::InvalidData(error_msg));
+            }
+            let mean = observed.iter().fold(T::zero(), |acc, &x| acc + x)
+                / nalgebra::convert(observed.len() as f64);
+            for (obs, value) in column.iter_mut().enumerate() {
+                if is_missing(value) {
+                    *value = mean;
+                    missing.push((obs, var));
+                }
+            }
+        }
+
+        let mut fitted = None;
+        let mut converged = false;
+        for _ in 0..self.convergence.max_iter() {
+            let (centered, mean) = center(&filled);
+            let svd = centered.svd(true, true);
+            let u = svd.u.expect("The left singular vectors were computed.");
+            let v_t = svd.v_t.expect("The right singular vectors were computed.");
+            let singular_values = svd.singular_values.rows(0, k).into_owned();
+            let mut change = T::zero();
+            for &(obs, var) in &missing {
+                let reconstruction = (0..k).fold(mean[var], |acc, c| {
+                    acc + u[(obs, c)] * singular_values[c] * v_t[(c, var)]
+                });
+                change = change.max((reconstruction - filled[(obs, var)]).abs());
+                filled[(obs, var)] = reconstruction;
+            }
+            fitted = Some((v_t.rows(0, k).into_owned(), mean, singular_values));
+            if change <= self.convergence.tol() {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            self.diagnostics.warn(Warning::NotConverged {
+                max_iter: self.convergence.max_iter(),
+            });
+        }
+
+        let (mut components, mean, singular_values) =
+            fitted.expect("There is at least one iteration.");
+        fix_signs(&mut components);
+        self.explained_variance = Some(match inputs.nrows() > 1 {
+            true => {
+                singular_values.map(|value| value * value)
+                    / nalgebra::convert::<f64, T>((inputs.nrows() - 1) as f64)
+            }
+            false => DVector::zeros(k),
+        });
+        self.components = Some(components);
+        self.mean = Some(mean);
+        self.imputed = Some(filled);
+        Ok(())
+    }
+
+    fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let components = check_fitted(&self.components)?;
+        let mean = check_fitted(&self.mean)?;
+        check_num_vars(components.ncols(), inputs.ncols())?;
+        let mut scores = DMatrix::zeros(inputs.nrows(), components.nrows());
+        for (i, row) in inputs.row_iter().enumerate() {
+            let observed: Vec<usize> = (0..row.len()).filter(|&j| !is_missing(&row[j])).collect();
+            let centered =
+                DVector::from_iterator(observed.len(), observed.iter().map(|&j| row[j] - mean[j]));
+            let score = match observed.len() {
+                0 => DVector::zeros(components.nrows()),
+                num_observed if num_observed == row.len() => components * centered,
+                // The minimum-norm least squares scores for the observed variables.
+                _ => components
+                    .select_columns(observed.iter())
+                    .transpose()
+                    .svd(true, true)
+                    .solve(&centered, T::default_epsilon())
+                    .expect("The singular vectors were computed."),
+            };
+            scores.set_row(i, &score.transpose());
+        }
+        Ok(scores)
+    }
+}
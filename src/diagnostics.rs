@@ -0,0 +1,89 @@
+//! Non-fatal warnings from training, e.g. that the fit may be inaccurate, which models pass to a
+//! sink that is set with their `with_diagnostics` method. Without a sink, warnings are not
+//! computed at all.
+use std::fmt;
+use std::sync::Arc;
+
+/// A problem found while training that doesn't stop the model from being used.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Warning {
+    /// The normal matrix of a least squares fit has this condition number, which is so large that
+    /// more than half of the precision of the coefficients may be lost.
+    IllConditioned { condition_number: f64 },
+    /// An iterative fit stopped after its maximum number of iterations without converging.
+    NotConverged { max_iter: usize },
+    /// An iterative fit stopped after this many iterations, before its maximum, because the line
+    /// search could not decrease the objective, so it may not have converged.
+    LineSearchFailed { iterations: usize },
+    /// These input variables have (nearly) zero variance relative to their magnitude.
+    LowVariance { variables: Vec<usize> },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::IllConditioned { condition_number } => write!(
+                f,
+                "The normal matrix has condition number {:e}, so the coefficients may be inaccurate.",
+                condition_number
+            ),
+            Self::NotConverged { max_iter } => write!(
+                f,
+                "Stopped after the maximum of {} iterations without converging.",
+                max_iter
+            ),
+            Self::LineSearchFailed { iterations } => write!(
+                f,
+                "Stopped after {} iterations without converging, because the line search could not decrease the objective.",
+                iterations
+            ),
+            Self::LowVariance { variables } => {
+                let variables: Vec<String> = variables.iter().map(|var| var.to_string()).collect();
+                write!(
+                    f,
+                    "Input variable(s) {} have (nearly) zero variance.",
+                    variables.join(", ")
+                )
+            }
+        }
+    }
+}
+
+type Sink = Arc<dyn Fn(&Warning) + Send + Sync>;
+
+/// Where a model sends its [`Warning`]s. The default sink ignores them.
+#[derive(Clone, Default)]
+pub struct Diagnostics {
+    sink: Option<Sink>,
+}
+
+impl Diagnostics {
+    /// Call `sink` with each warning, e.g. to log or collect them.
+    pub fn new<F>(sink: F) -> Self
+    where
+        F: Fn(&Warning) + Send + Sync + 'static,
+    {
+        Self {
+            sink: Some(Arc::new(sink)),
+        }
+    }
+
+    /// Whether there is a sink, so that checks which only produce warnings can be skipped.
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    pub(crate) fn warn(&self, warning: Warning) {
+        if let Some(sink) = &self.sink {
+            sink(&warning);
+        }
+    }
+}
+
+impl fmt::Debug for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Diagnostics")
+            .field("enabled", &self.is_enabled())
+            .finish()
+    }
+}
@@ -0,0 +1,269 @@
+//! Distance metrics between pairs of observations.
+use crate::{RowView, SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+/// Trait for a distance metric between two observations.
+pub trait Metric<T>
+where
+    T: RealField + Copy,
+{
+    /// The distance between two observations, which must have the same number of variables.
+    fn distance(&self, x: RowView<T>, y: RowView<T>) -> T;
+
+    /// The distance between each row of `x` (rows of the output) and each row of `y` (columns of
+    /// the output). Both inputs must have the same number of variables.
+    fn pairwise(&self, x: &DMatrix<T>, y: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        validate_num_vars(x, y)?;
+        Ok(DMatrix::from_fn(x.nrows(), y.nrows(), |i, j| {
+            self.distance(x.row(i), y.row(j))
+        }))
+    }
+}
+
+pub(crate) fn validate_num_vars<T: RealField>(
+    x: &DMatrix<T>,
+    y: &DMatrix<T>,
+) -> SLearningResult<()> {
+    if x.ncols() != y.ncols() {
+        let error_msg = format!(
+            "The first input has {} variables, but the second input has {} variables. These must be equal.",
+            x.ncols(),
+            y.ncols()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(())
+}
+
+/// The distance between each row of `x` (rows of the output) and each row of `y` (columns of the
+/// output), using `metric`.
+pub fn pairwise_distances<T, M>(
+    x: &DMatrix<T>,
+    y: &DMatrix<T>,
+    metric: &M,
+) -> SLearningResult<DMatrix<T>>
+where
+    T: RealField + Copy,
+    M: Metric<T>,
+{
+    metric.pairwise(x, y)
+}
+
+/// The matrix of squared Euclidean distances between each row of `x` and each row of `y`.
+///
+/// This uses `||x - y||^2 = ||x||^2 + ||y||^2 - 2 x · y`, so that most of the work is a single
+/// matrix multiplication.
+pub(crate) fn squared_euclidean_matrix<T: RealField + Copy>(
+    x: &DMatrix<T>,
+    y: &DMatrix<T>,
+) -> DMatrix<T> {
+    let x_norms: Vec<T> = x.row_iter().map(|row| row.norm_squared()).collect();
+    let y_norms: Vec<T> = y.row_iter().map(|row| row.norm_squared()).collect();
+    let two: T = nalgebra::convert(2.0);
+    let dots = x * y.transpose();
+    // Cancellation can make this slightly negative for (nearly) identical rows.
+    DMatrix::from_fn(x.nrows(), y.nrows(), |i, j| {
+        (x_norms[i] + y_norms[j] - two * dots[(i, j)]).max(T::zero())
+    })
+}
+
+fn validate_mahalanobis_dimensions<T: RealField>(
+    num_vars: usize,
+    mean: &DVector<T>,
+    precision: &DMatrix<T>,
+) -> SLearningResult<()> {
+    if precision.nrows() != mean.len() || precision.ncols() != mean.len() {
+        let error_msg = format!(
+            "The mean has {} variables, but the precision matrix has shape {:?}. The precision matrix must be square with the same number of variables.",
+            mean.len(),
+            precision.shape()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    if num_vars != mean.len() {
+        let error_msg = format!(
+            "The mean has {} variables, but the input has {} variables. These must be equal.",
+            mean.len(),
+            num_vars
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(())
+}
+
+/// Squared Mahalanobis distance of each row of `inputs` from `mean`, without validating shapes.
+pub(crate) fn squared_mahalanobis_rows<T: RealField + Copy>(
+    inputs: &DMatrix<T>,
+    mean: &DVector<T>,
+    precision: &DMatrix<T>,
+) -> DVector<T> {
+    let centered = DMatrix::from_fn(inputs.nrows(), inputs.ncols(), |i, j| {
+        inputs[(i, j)] - mean[j]
+    });
+    let projected = &centered * precision;
+    DVector::from_fn(inputs.nrows(), |i, _| {
+        projected.row(i).dot(&centered.row(i))
+    })
+}
+
+/// The Mahalanobis distance of `x` from a distribution with the given mean and precision (inverse
+/// covariance) matrix.
+pub fn mahalanobis_distance<T: RealField + Copy>(
+    x: &DVector<T>,
+    mean: &DVector<T>,
+    precision: &DMatrix<T>,
+) -> SLearningResult<T> {
+    validate_mahalanobis_dimensions(x.len(), mean, precision)?;
+    let centered = x - mean;
+    Ok((precision * &centered).dot(&centered).max(T::zero()).sqrt())
+}
+
+/// The Mahalanobis distance of each row of `inputs` from a distribution with the given mean and
+/// precision (inverse covariance) matrix.
+pub fn mahalanobis_distances<T: RealField + Copy>(
+    inputs: &DMatrix<T>,
+    mean: &DVector<T>,
+    precision: &DMatrix<T>,
+) -> SLearningResult<DVector<T>> {
+    validate_mahalanobis_dimensions(inputs.ncols(), mean, precision)?;
+    Ok(squared_mahalanobis_rows(inputs, mean, precision).map(|d| d.max(T::zero()).sqrt()))
+}
+
+/// Euclidean (L2) distance.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Euclidean;
+
+impl<T> Metric<T> for Euclidean
+where
+    T: RealField + Copy,
+{
+    fn distance(&self, x: RowView<T>, y: RowView<T>) -> T {
+        (x - y).norm()
+    }
+
+    fn pairwise(&self, x: &DMatrix<T>, y: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        validate_num_vars(x, y)?;
+        Ok(squared_euclidean_matrix(x, y).map(|distance| distance.sqrt()))
+    }
+}
+
+/// Squared Euclidean distance.
+///
+/// This is not a true metric (it does not satisfy the triangle inequality), but it gives the same
+/// nearest neighbours as the Euclidean distance and is cheaper to compute.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SquaredEuclidean;
+
+impl<T> Metric<T> for SquaredEuclidean
+where
+    T: RealField + Copy,
+{
+    fn distance(&self, x: RowView<T>, y: RowView<T>) -> T {
+        (x - y).norm_squared()
+    }
+
+    fn pairwise(&self, x: &DMatrix<T>, y: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        validate_num_vars(x, y)?;
+        Ok(squared_euclidean_matrix(x, y))
+    }
+}
+
+/// Manhattan (L1 or city block) distance.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Manhattan;
+
+impl<T> Metric<T> for Manhattan
+where
+    T: RealField + Copy,
+{
+    fn distance(&self, x: RowView<T>, y: RowView<T>) -> T {
+        x.iter()
+            .zip(y.iter())
+            .fold(T::zero(), |acc, (&a, &b)| acc + (a - b).abs())
+    }
+}
+
+/// Minkowski (Lp) distance, which generalises the Manhattan (p = 1) and Euclidean (p = 2)
+/// distances.
+#[derive(Debug, Clone, Copy)]
+pub struct Minkowski<T> {
+    pub p: T,
+}
+
+impl<T> Minkowski<T>
+where
+    T: RealField,
+{
+    pub fn new(p: T) -> SLearningResult<Self> {
+        if !p.is_finite() || p < T::one() {
+            return Err(SLearningError::InvalidParameters(
+                "The order p must be finite and at least one.".to_string(),
+            ));
+        }
+        Ok(Self { p })
+    }
+}
+
+impl<T> Metric<T> for Minkowski<T>
+where
+    T: RealField + Copy,
+{
+    fn distance(&self, x: RowView<T>, y: RowView<T>) -> T {
+        x.iter()
+            .zip(y.iter())
+            .fold(T::zero(), |acc, (&a, &b)| acc + (a - b).abs().powf(self.p))
+            .powf(T::one() / self.p)
+    }
+}
+
+/// Chebyshev (L-infinity) distance, the largest absolute difference between any variable.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Chebyshev;
+
+impl<T> Metric<T> for Chebyshev
+where
+    T: RealField + Copy,
+{
+    fn distance(&self, x: RowView<T>, y: RowView<T>) -> T {
+        x.iter()
+            .zip(y.iter())
+            .fold(T::zero(), |acc, (&a, &b)| acc.max((a - b).abs()))
+    }
+}
+
+/// Cosine distance, one minus the cosine of the angle between the observations.
+///
+/// An observation of all zeros is treated as being orthogonal to everything, so has a distance of
+/// one.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Cosine;
+
+impl<T> Metric<T> for Cosine
+where
+    T: RealField + Copy,
+{
+    fn distance(&self, x: RowView<T>, y: RowView<T>) -> T {
+        let norms = x.norm() * y.norm();
+        if norms.is_zero() {
+            return T::one();
+        }
+        T::one() - x.dot(&y) / norms
+    }
+}
+
+/// Hamming distance, the proportion of variables that are not equal.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Hamming;
+
+impl<T> Metric<T> for Hamming
+where
+    T: RealField + Copy,
+{
+    fn distance(&self, x: RowView<T>, y: RowView<T>) -> T {
+        if x.is_empty() {
+            return T::zero();
+        }
+        let num_different = x.iter().zip(y.iter()).filter(|(a, b)| a != b).count();
+        nalgebra::convert::<f64, T>(num_different as f64) / nalgebra::convert(x.len() as f64)
+    }
+}
@@ -0,0 +1,278 @@
+//! Gaussian process models, which put a Gaussian process prior (with covariance given by a
+//! [`Kernel`]) on a latent function of the inputs.
+use crate::diagnostics::{Diagnostics, Warning};
+use crate::kernel::Kernel;
+use crate::math::{log1pexp, sigmoid};
+use crate::metrics::{binary_class_probabilities, min_expected_cost_classes};
+use crate::optim::ConvergenceConfig;
+use crate::traits::{BinaryPrediction, SupervisedModel};
+use crate::validation::{
+    check_2d_nonempty, check_consistent_length, check_finite, check_finite_values, check_fitted,
+    check_num_vars,
+};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+/// The log-likelihood of binary labels with probabilities `logistic(latent)`.
+fn bernoulli_log_likelihood<T: RealField + Copy>(latent: &DVector<T>, outputs: &DVector<T>) -> T {
+    latent
+        .iter()
+        .zip(outputs.iter())
+        .fold(T::zero(), |total, (&f, &y)| match y.is_one() {
+            true => total - log1pexp(-f),
+            false => total - log1pexp(f),
+        })
+}
+
+/// The lower Cholesky factor of `I + W^1/2 K W^1/2`, which is well-conditioned since its
+/// eigenvalues are at least one when the kernel is positive semi-definite. Otherwise (e.g. with
+/// some parameters of the sigmoid kernel), it may not be positive definite.
+fn laplace_factor<T: RealField + Copy>(
+    gram: &DMatrix<T>,
+    sqrt_weights: &DVector<T>,
+) -> SLearningResult<DMatrix<T>> {
+    let n = gram.nrows();
+    let b_matrix = DMatrix::from_fn(n, n, |i, j| {
+        let identity = if i == j { T::one() } else { T::zero() };
+        identity + sqrt_weights[i] * gram[(i, j)] * sqrt_weights[j]
+    });
+    match b_matrix.cholesky() {
+        Some(cholesky) => Ok(cholesky.l()),
+        None => Err(SLearningError::InvalidData(
+            "The Laplace approximation failed because the kernel matrix is not positive semi-definite. Use a positive semi-definite kernel."
+                .to_string(),
+        )),
+    }
+}
+
+/// The posterior mean and variance of the latent function at some observations.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LatentPrediction<T>
+where
+    T: RealField,
+{
+    pub mean: DVector<T>,
+    pub variance: DVector<T>,
+}
+
+/// The quantities from training a [`GpcClassifier`] that are needed to make predictions.
+#[derive(Debug)]
+struct GpcFit<T>
+where
+    T: RealField,
+{
+    inputs: DMatrix<T>,
+    /// The gradient of the log-likelihood at the posterior mode, `y - pi`.
+    residuals: DVector<T>,
+    /// The square root of the negative Hessian of the log-likelihood at the posterior mode.
+    sqrt_weights: DVector<T>,
+    /// The lower Cholesky factor of `I + W^1/2 K W^1/2`.
+    factor: DMatrix<T>,
+}
+
+/// Binary Gaussian process classifier, using the Laplace approximation.
+///
+/// The probability that an observation is positive is `logistic(f(x))`, where the latent function
+/// `f` has a zero-mean Gaussian process prior with covariance given by the kernel. The posterior
+/// of `f` is approximated by a Gaussian centred at its mode, which is found by Newton's method
+/// (Rasmussen & Williams, Gaussian Processes for Machine Learning, algorithms 3.1 and 3.2).
+///
+/// Probabilities average the logistic function over the uncertainty in the latent function, so
+/// they are pulled towards one half far from the training data. Predictions are the class with
+/// the higher probability.
+#[derive(Debug)]
+pub struct GpcClassifier<T, K>
+where
+    T: RealField,
+{
+    /// The posterior mode of the latent function at each training observation.
+    pub latent_mode: Option<DVector<T>>,
+    /// The Laplace approximation to the log marginal likelihood, which can be used to compare
+    /// kernels.
+    pub log_marginal_likelihood: Option<T>,
+    kernel: K,
+    convergence: ConvergenceConfig<T>,
+    diagnostics: Diagnostics,
+    fit: Option<GpcFit<T>>,
+}
+
+impl<T, K> GpcClassifier<T, K>
+where
+    T: RealField + Copy,
+    K: Kernel<T>,
+{
+    pub fn new(kernel: K) -> Self {
+        Self {
+            latent_mode: None,
+            log_marginal_likelihood: None,
+            kernel,
+            convergence: ConvergenceConfig::default(),
+            diagnostics: Diagnostics::default(),
+            fit: None,
+        }
+    }
+
+    /// Set when Newton's method for the posterior mode stops, based on the change in the
+    /// log-posterior.
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+
+    /// Send warnings from training, e.g. that it stopped before converging, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// The posterior mean and variance of the latent function at each observation.
+    pub fn predict_latent(&self, inputs: &DMatrix<T>) -> SLearningResult<LatentPrediction<T>> {
+        let fit = check_fitted(&self.fit)?;
+        check_num_vars(fit.inputs.ncols(), inputs.ncols())?;
+        check_finite(inputs)?;
+        let cross_kernel = self.kernel.matrix(&fit.inputs, inputs)?;
+        let mean = cross_kernel.transpose() * &fit.residuals;
+        let scaled = DMatrix::from_fn(cross_kernel.nrows(), cross_kernel.ncols(), |i, j| {
+            fit.sqrt_weights[i] * cross_kernel[(i, j)]
+        });
+        let v = fit
+            .factor
+            .solve_lower_triangular(&scaled)
+            .expect("The Cholesky factor is invertible.");
+        let variance = DVector::from_fn(inputs.nrows(), |j, _| {
+            let prior = self.kernel.compute(inputs.row(j), inputs.row(j));
+            (prior - v.column(j).norm_squared()).max(T::zero())
+        });
+        Ok(LatentPrediction { mean, variance })
+    }
+
+    /// The probability that each observation is positive, averaged over the posterior of the
+    /// latent function with the probit approximation `logistic(mean / sqrt(1 + pi variance / 8))`.
+    pub fn predict_proba(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let latent = self.predict_latent(inputs)?;
+        let scale: T = nalgebra::convert(std::f64::consts::PI / 8.0);
+        Ok(latent.mean.zip_map(&latent.variance, |mean, variance| {
+            sigmoid(mean / (T::one() + scale * variance).sqrt())
+        }))
+    }
+
+    /// The predicted classes, with the probabilities of the positive class from
+    /// [`predict_proba`](Self::predict_proba). These agree, since the averaged probability is at
+    /// least one half exactly when the latent mean is at least zero.
+    pub fn predict_with_scores(&self, inputs: &DMatrix<T>) -> SLearningResult<BinaryPrediction<T>> {
+        let probabilities = self.predict_proba(inputs)?;
+        Ok(BinaryPrediction::from_scores(
+            probabilities,
+            nalgebra::convert(0.5),
+        ))
+    }
+
+    /// The class of each observation that minimises the expected cost under the predicted
+    /// probabilities, given the cost of predicting each true class (rows) as each class
+    /// (columns), with the negative class first. See [`min_expected_cost_classes`].
+    pub fn predict_min_cost(
+        &self,
+        inputs: &DMatrix<T>,
+        cost_matrix: &DMatrix<T>,
+    ) -> SLearningResult<DVector<T>> {
+        let probabilities = binary_class_probabilities(&self.predict_proba(inputs)?);
+        min_expected_cost_classes(&probabilities, cost_matrix)
+    }
+}
+
+impl<T, K> SupervisedModel<T> for GpcClassifier<T, K>
+where
+    T: RealField + Copy,
+    K: Kernel<T>,
+{
+    fn train(&mut self, inputs: DMatrix<T>, outputs: DVector<T>) -> SLearningResult<()> {
+        check_2d_nonempty(&inputs)?;
+        check_consistent_length(&inputs, &outputs)?;
+        check_finite(&inputs)?;
+        check_finite_values(&outputs, "outputs")?;
+        if outputs.iter().any(|y| !y.is_zero() && !y.is_one()) {
+            return Err(SLearningError::InvalidData(
+                "Binary labels must be zero or one.".to_string(),
+            ));
+        }
+
+        let n = inputs.nrows();
+        let gram = self.kernel.gram_matrix(&inputs);
+        let half: T = nalgebra::convert(0.5);
+        let mut latent = DVector::zeros(n);
+        let mut previous_objective: Option<T> = None;
+        let mut iteration = 0;
+        loop {
+            let probabilities = latent.map(sigmoid);
+            let weights = probabilities.map(|p| p * (T::one() - p));
+            let sqrt_weights = weights.map(|w| w.sqrt());
+            let factor = laplace_factor(&gram, &sqrt_weights)?;
+            let residuals = &outputs - &probabilities;
+
+            // A Newton step for the posterior mode, `f = (K^-1 + W)^-1 (W f + y - pi)`, written
+            // as `f = K a` so that `K` is never inverted.
+            let b = weights.component_mul(&latent) + &residuals;
+            let scaled = sqrt_weights.component_mul(&(&gram * &b));
+            let solved = factor
+                .transpose()
+                .solve_upper_triangular(
+                    &factor
+                        .solve_lower_triangular(&scaled)
+                        .expect("The Cholesky factor is invertible."),
+                )
+                .expect("The Cholesky factor is invertible.");
+            let a = &b - sqrt_weights.component_mul(&solved);
+            let new_latent = &gram * &a;
+
+            let objective =
+                bernoulli_log_likelihood(&new_latent, &outputs) - half * a.dot(&new_latent);
+            latent = new_latent;
+            iteration += 1;
+            let converged = previous_objective
+                .is_some_and(|previous| (objective - previous).abs() <= self.convergence.tol());
+            if converged {
+                break;
+            }
+            if iteration >= self.convergence.max_iter() {
+                self.diagnostics.warn(Warning::NotConverged {
+                    max_iter: self.convergence.max_iter(),
+                });
+                break;
+            }
+            previous_objective = Some(objective);
+        }
+
+        // Refactorise at the final mode, for the marginal likelihood and predictions.
+        let probabilities = latent.map(sigmoid);
+        let sqrt_weights = probabilities.map(|p| (p * (T::one() - p)).sqrt());
+        let factor = laplace_factor(&gram, &sqrt_weights)?;
+        let residuals = &outputs - &probabilities;
+        // At the mode, `K^-1 f = y - pi`.
+        let log_determinant = factor.diagonal().map(|d| d.ln()).sum();
+        self.log_marginal_likelihood = Some(
+            bernoulli_log_likelihood(&latent, &outputs)
+                - half * residuals.dot(&latent)
+                - log_determinant,
+        );
+        self.latent_mode = Some(latent);
+        self.fit = Some(GpcFit {
+            inputs,
+            residuals,
+            sqrt_weights,
+            factor,
+        });
+        Ok(())
+    }
+
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let latent = self.predict_latent(inputs)?;
+        Ok(latent.mean.map(|mean| match mean >= T::zero() {
+            true => T::one(),
+            false => T::zero(),
+        }))
+    }
+}
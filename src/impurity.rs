@@ -0,0 +1,82 @@
+//! Impurity measures for the distribution of class labels.
+//!
+//! Each measure is zero when all labels are the same, and is largest when the labels are spread
+//! evenly over the classes. They can be computed from a slice of labels, or from class counts such
+//! as the output of [`unique_with_counts`](crate::utils::unique_with_counts). An empty set of
+//! labels has zero impurity.
+use std::hash::Hash;
+
+use crate::utils::unique_with_counts;
+
+/// The proportion of observations in each class, given the class counts.
+fn proportions<T, I>(counts: I) -> Vec<f64>
+where
+    I: IntoIterator<Item = (T, u64)>,
+{
+    let mut counts: Vec<u64> = counts.into_iter().map(|(_, count)| count).collect();
+    // Sort so that the floating point sums do not depend on the (possibly arbitrary) class order.
+    counts.sort_unstable();
+    let total: u64 = counts.iter().sum();
+    if total == 0 {
+        return vec![];
+    }
+    counts
+        .into_iter()
+        .map(|count| count as f64 / total as f64)
+        .collect()
+}
+
+/// Shannon entropy (in bits) of the class distribution described by `counts`.
+pub fn entropy_from_counts<T, I>(counts: I) -> f64
+where
+    I: IntoIterator<Item = (T, u64)>,
+{
+    -proportions(counts)
+        .into_iter()
+        .filter(|&proportion| proportion > 0.0)
+        .map(|proportion| proportion * proportion.log2())
+        .sum::<f64>()
+}
+
+/// Gini impurity of the class distribution described by `counts`.
+///
+/// This is the probability that two observations drawn with replacement have different classes.
+pub fn gini_from_counts<T, I>(counts: I) -> f64
+where
+    I: IntoIterator<Item = (T, u64)>,
+{
+    let proportions = proportions(counts);
+    if proportions.is_empty() {
+        return 0.0;
+    }
+    1.0 - proportions.into_iter().map(|p| p * p).sum::<f64>()
+}
+
+/// Misclassification rate of the class distribution described by `counts`.
+///
+/// This is the proportion of observations that are not in the most common class.
+pub fn misclassification_rate_from_counts<T, I>(counts: I) -> f64
+where
+    I: IntoIterator<Item = (T, u64)>,
+{
+    let proportions = proportions(counts);
+    if proportions.is_empty() {
+        return 0.0;
+    }
+    1.0 - proportions.into_iter().fold(0.0, f64::max)
+}
+
+/// Shannon entropy (in bits) of `labels`.
+pub fn entropy<T: Eq + Hash>(labels: &[T]) -> f64 {
+    entropy_from_counts(unique_with_counts(labels))
+}
+
+/// Gini impurity of `labels`.
+pub fn gini<T: Eq + Hash>(labels: &[T]) -> f64 {
+    gini_from_counts(unique_with_counts(labels))
+}
+
+/// Misclassification rate of `labels`, when every label is predicted as the most common class.
+pub fn misclassification_rate<T: Eq + Hash>(labels: &[T]) -> f64 {
+    misclassification_rate_from_counts(unique_with_counts(labels))
+}
@@ -0,0 +1,234 @@
+//! Model-agnostic tools for inspecting how a trained model uses its inputs.
+use crate::random::Rng;
+use crate::traits::SupervisedModel;
+use crate::validation::{check_2d_nonempty, check_consistent_length};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+fn validate_dimensions<T: RealField>(
+    inputs: &DMatrix<T>,
+    outputs: &DVector<T>,
+) -> SLearningResult<()> {
+    check_2d_nonempty(inputs)?;
+    check_consistent_length(inputs, outputs)?;
+    Ok(())
+}
+
+/// The result of [`permutation_importance`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PermutationImportance<T>
+where
+    T: RealField,
+{
+    /// The score of the model on the unshuffled data.
+    pub baseline_score: T,
+    /// The decrease in score from each shuffle, with a row for each variable and a column for each
+    /// repeat.
+    pub importances: DMatrix<T>,
+    /// The mean decrease in score for each variable.
+    pub importances_mean: DVector<T>,
+    /// The standard deviation of the decrease in score for each variable.
+    pub importances_std: DVector<T>,
+}
+
+/// The importance of each input variable to a trained model, measured by how much the model's
+/// score decreases when that variable's values are shuffled between observations.
+///
+/// `score` is called with the true and predicted outputs, and higher scores must be better (e.g.
+/// use the negative of an error). Each variable is shuffled `n_repeats` times, using random
+/// shuffles from `seed`.
+pub fn permutation_importance<T, M, S>(
+    model: &M,
+    inputs: &DMatrix<T>,
+    outputs: &DVector<T>,
+    score: S,
+    n_repeats: usize,
+    seed: u64,
+) -> SLearningResult<PermutationImportance<T>>
+where
+    T: RealField + Copy,
+    M: SupervisedModel<T>,
+    S: Fn(&DVector<T>, &DVector<T>) -> T,
+{
+    validate_dimensions(inputs, outputs)?;
+    if n_repeats == 0 {
+        return Err(SLearningError::InvalidParameters(
+            "Number of repeats must be at least one.".to_string(),
+        ));
+    }
+    let baseline_score = score(outputs, &model.predict(inputs)?);
+
+    let mut rng = Rng::new(seed);
+    let mut importances = DMatrix::zeros(inputs.ncols(), n_repeats);
+    let mut shuffled = inputs.clone();
+    for j in 0..inputs.ncols() {
+        for repeat in 0..n_repeats {
+            let permutation = rng.permutation(inputs.nrows());
+            for (i, &source) in permutation.iter().enumerate() {
+                shuffled[(i, j)] = inputs[(source, j)];
+            }
+            let shuffled_score = score(outputs, &model.predict(&shuffled)?);
+            importances[(j, repeat)] = baseline_score - shuffled_score;
+        }
+        shuffled.set_column(j, &inputs.column(j));
+    }
+
+    let (importances_mean, importances_std) = mean_and_std_by_row(&importances);
+    Ok(PermutationImportance {
+        baseline_score,
+        importances,
+        importances_mean,
+        importances_std,
+    })
+}
+
+/// The result of [`partial_dependence`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialDependence<T>
+where
+    T: RealField,
+{
+    /// The values the variable was set to.
+    pub grid: DVector<T>,
+    /// The mean prediction over all observations at each grid value.
+    pub average: DVector<T>,
+    /// The individual conditional expectation (ICE) curves, i.e. the prediction for each
+    /// observation (rows) at each grid value (columns).
+    pub individual: DMatrix<T>,
+}
+
+/// How the predictions of a trained model depend on one input variable, averaging over the other
+/// variables.
+///
+/// For each value in `grid`, every observation's value of the variable at `feature_index` is
+/// replaced by that grid value and the predictions are averaged.
+pub fn partial_dependence<T, M>(
+    model: &M,
+    inputs: &DMatrix<T>,
+    feature_index: usize,
+    grid: &DVector<T>,
+) -> SLearningResult<PartialDependence<T>>
+where
+    T: RealField + Copy,
+    M: SupervisedModel<T>,
+{
+    check_2d_nonempty(inputs)?;
+    if feature_index >= inputs.ncols() {
+        let error_msg = format!(
+            "Feature index {} is out of range for an input with {} variables.",
+            feature_index,
+            inputs.ncols()
+        );
+        return Err(SLearningError::InvalidParameters(error_msg));
+    }
+    if grid.is_empty() {
+        return Err(SLearningError::InvalidParameters(
+            "The grid must contain at least one value.".to_string(),
+        ));
+    }
+
+    let mut modified = inputs.clone();
+    let mut individual = DMatrix::zeros(inputs.nrows(), grid.len());
+    for (k, &value) in grid.iter().enumerate() {
+        modified.column_mut(feature_index).fill(value);
+        individual.set_column(k, &model.predict(&modified)?);
+    }
+    Ok(PartialDependence {
+        grid: grid.clone(),
+        average: individual.row_mean().transpose(),
+        individual,
+    })
+}
+
+/// The result of [`stability_report`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StabilityReport<T>
+where
+    T: RealField,
+{
+    /// The prediction for each observation (rows) by the model trained with each seed (columns).
+    pub predictions: DMatrix<T>,
+    /// The mean prediction for each observation.
+    pub predictions_mean: DVector<T>,
+    /// The standard deviation of the predictions for each observation.
+    pub predictions_std: DVector<T>,
+    /// Each value of the summary (rows) of the model trained with each seed (columns).
+    pub summaries: DMatrix<T>,
+    /// The mean of each value of the summary.
+    pub summaries_mean: DVector<T>,
+    /// The standard deviation of each value of the summary.
+    pub summaries_std: DVector<T>,
+}
+
+/// How much a model with randomness in training varies with its seed.
+///
+/// A model is made by `make_model` with each seed from zero to `n_seeds - 1`, trained on the same
+/// data, and used to predict the training inputs. `summary` gives any values of a trained model to
+/// compare between seeds, e.g. its coefficients or feature importances, and must return the same
+/// number of values for every seed.
+pub fn stability_report<T, M, F, S>(
+    mut make_model: F,
+    inputs: &DMatrix<T>,
+    outputs: &DVector<T>,
+    n_seeds: usize,
+    summary: S,
+) -> SLearningResult<StabilityReport<T>>
+where
+    T: RealField + Copy,
+    M: SupervisedModel<T>,
+    F: FnMut(u64) -> M,
+    S: Fn(&M) -> DVector<T>,
+{
+    validate_dimensions(inputs, outputs)?;
+    if n_seeds < 2 {
+        return Err(SLearningError::InvalidParameters(
+            "Number of seeds must be at least two.".to_string(),
+        ));
+    }
+    let mut predictions = DMatrix::zeros(inputs.nrows(), n_seeds);
+    let mut summaries: Option<DMatrix<T>> = None;
+    for seed in 0..n_seeds {
+        let mut model = make_model(seed as u64);
+        model.train(inputs.clone(), outputs.clone())?;
+        predictions.set_column(seed, &model.predict(inputs)?);
+        let values = summary(&model);
+        let summaries = summaries.get_or_insert_with(|| DMatrix::zeros(values.len(), n_seeds));
+        if values.len() != summaries.nrows() {
+            let error_msg = format!(
+                "The summary of the first model has {} value(s), but the summary of the model with seed {} has {} value(s). These must be equal.",
+                summaries.nrows(),
+                seed,
+                values.len()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        summaries.set_column(seed, &values);
+    }
+    let summaries = summaries.expect("There are at least two seeds.");
+
+    let (predictions_mean, predictions_std) = mean_and_std_by_row(&predictions);
+    let (summaries_mean, summaries_std) = mean_and_std_by_row(&summaries);
+    Ok(StabilityReport {
+        predictions,
+        predictions_mean,
+        predictions_std,
+        summaries,
+        summaries_mean,
+        summaries_std,
+    })
+}
+
+/// The mean and (population) standard deviation of each row, from the deviations from the mean so
+/// that rows of equal values have a standard deviation of exactly zero.
+fn mean_and_std_by_row<T: RealField + Copy>(values: &DMatrix<T>) -> (DVector<T>, DVector<T>) {
+    let mean = values.column_mean();
+    let num_columns: T = nalgebra::convert(values.ncols() as f64);
+    let std = DVector::from_fn(values.nrows(), |i, _| {
+        let squares = values
+            .row(i)
+            .iter()
+            .fold(T::zero(), |total, &value| total + (value - mean[i]).powi(2));
+        (squares / num_columns).sqrt()
+    });
+    (mean, std)
+}
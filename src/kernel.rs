@@ -0,0 +1,223 @@
+//! Kernel functions, which measure the similarity between pairs of observations.
+//!
+//! Kernel methods only use the data through these pairwise similarities, so each kernel can build
+//! the full matrix of similarities between two sets of observations (the Gram matrix) in one go.
+//! The kernels built on dot products or distances do this with a single matrix multiplication,
+//! rather than looping over every pair of rows.
+use crate::distance::{squared_euclidean_matrix, validate_num_vars};
+use crate::validation::check_positive;
+use crate::{RowView, SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, RealField};
+
+/// Trait for a kernel.
+///
+/// Most kernels are positive semi-definite, i.e. every Gram matrix they build is, which many
+/// kernel methods assume. The exception here is [`Sigmoid`], which is only positive
+/// semi-definite for some parameters, so models that need this may fail to train with it.
+pub trait Kernel<T>
+where
+    T: RealField + Copy,
+{
+    /// The similarity between two observations.
+    fn compute(&self, x: RowView<T>, y: RowView<T>) -> T;
+
+    /// The kernel matrix between each row of `x` (rows of the output) and each row of `y` (columns
+    /// of the output).
+    fn matrix(&self, x: &DMatrix<T>, y: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        validate_num_vars(x, y)?;
+        Ok(DMatrix::from_fn(x.nrows(), y.nrows(), |i, j| {
+            self.compute(x.row(i), y.row(j))
+        }))
+    }
+
+    /// The (symmetric) kernel matrix between each pair of rows of `x`.
+    fn gram_matrix(&self, x: &DMatrix<T>) -> DMatrix<T> {
+        self.matrix(x, x)
+            .expect("A matrix always has the same number of variables as itself.")
+    }
+}
+
+/// Linear kernel, `x · y`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Linear;
+
+impl<T> Kernel<T> for Linear
+where
+    T: RealField + Copy,
+{
+    fn compute(&self, x: RowView<T>, y: RowView<T>) -> T {
+        x.dot(&y)
+    }
+
+    fn matrix(&self, x: &DMatrix<T>, y: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        validate_num_vars(x, y)?;
+        Ok(x * y.transpose())
+    }
+}
+
+/// Polynomial kernel, `(gamma * x · y + coef0)^degree`.
+#[derive(Debug, Clone, Copy)]
+pub struct Polynomial<T> {
+    pub degree: u32,
+    pub gamma: T,
+    pub coef0: T,
+}
+
+impl<T> Polynomial<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(degree: u32, gamma: T, coef0: T) -> SLearningResult<Self> {
+        if degree == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Degree must be at least one.".to_string(),
+            ));
+        }
+        check_positive(gamma, "Gamma")?;
+        Ok(Self {
+            degree,
+            gamma,
+            coef0,
+        })
+    }
+
+    fn apply(&self, dot: T) -> T {
+        (self.gamma * dot + self.coef0).powi(self.degree as i32)
+    }
+}
+
+impl<T> Kernel<T> for Polynomial<T>
+where
+    T: RealField + Copy,
+{
+    fn compute(&self, x: RowView<T>, y: RowView<T>) -> T {
+        self.apply(x.dot(&y))
+    }
+
+    fn matrix(&self, x: &DMatrix<T>, y: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        validate_num_vars(x, y)?;
+        Ok((x * y.transpose()).map(|dot| self.apply(dot)))
+    }
+}
+
+/// Radial basis function (Gaussian) kernel, `exp(-gamma * ||x - y||^2)`.
+#[derive(Debug, Clone, Copy)]
+pub struct Rbf<T> {
+    pub gamma: T,
+}
+
+impl<T> Rbf<T>
+where
+    T: RealField,
+{
+    pub fn new(gamma: T) -> SLearningResult<Self> {
+        check_positive(gamma.clone(), "Gamma")?;
+        Ok(Self { gamma })
+    }
+}
+
+impl<T> Kernel<T> for Rbf<T>
+where
+    T: RealField + Copy,
+{
+    fn compute(&self, x: RowView<T>, y: RowView<T>) -> T {
+        (-self.gamma * (x - y).norm_squared()).exp()
+    }
+
+    fn matrix(&self, x: &DMatrix<T>, y: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        validate_num_vars(x, y)?;
+        Ok(squared_euclidean_matrix(x, y).map(|distance| (-self.gamma * distance).exp()))
+    }
+}
+
+/// Sigmoid (hyperbolic tangent) kernel, `tanh(gamma * x · y + coef0)`.
+///
+/// This is not positive semi-definite for all parameters, but is commonly used regardless.
+#[derive(Debug, Clone, Copy)]
+pub struct Sigmoid<T> {
+    pub gamma: T,
+    pub coef0: T,
+}
+
+impl<T> Sigmoid<T>
+where
+    T: RealField,
+{
+    pub fn new(gamma: T, coef0: T) -> Self {
+        Self { gamma, coef0 }
+    }
+}
+
+impl<T> Kernel<T> for Sigmoid<T>
+where
+    T: RealField + Copy,
+{
+    fn compute(&self, x: RowView<T>, y: RowView<T>) -> T {
+        (self.gamma * x.dot(&y) + self.coef0).tanh()
+    }
+
+    fn matrix(&self, x: &DMatrix<T>, y: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        validate_num_vars(x, y)?;
+        Ok((x * y.transpose()).map(|dot| (self.gamma * dot + self.coef0).tanh()))
+    }
+}
+
+/// The smoothness parameter of a [`Matern`] kernel.
+///
+/// Only the half-integer values with closed-form kernels are supported. Larger values give
+/// smoother functions, approaching the [`Rbf`] kernel as the smoothness goes to infinity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MaternNu {
+    /// ν = 1/2, which is the exponential (Ornstein-Uhlenbeck) kernel.
+    Half,
+    /// ν = 3/2, giving once-differentiable functions.
+    ThreeHalves,
+    /// ν = 5/2, giving twice-differentiable functions.
+    FiveHalves,
+}
+
+/// Matérn kernel with the given length scale and smoothness.
+#[derive(Debug, Clone, Copy)]
+pub struct Matern<T> {
+    pub length_scale: T,
+    pub nu: MaternNu,
+}
+
+impl<T> Matern<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(length_scale: T, nu: MaternNu) -> SLearningResult<Self> {
+        check_positive(length_scale, "Length scale")?;
+        Ok(Self { length_scale, nu })
+    }
+
+    fn apply(&self, squared_distance: T) -> T {
+        let scaled = squared_distance.sqrt() / self.length_scale;
+        match self.nu {
+            MaternNu::Half => (-scaled).exp(),
+            MaternNu::ThreeHalves => {
+                let z = scaled * nalgebra::convert(3.0f64.sqrt());
+                (T::one() + z) * (-z).exp()
+            }
+            MaternNu::FiveHalves => {
+                let z = scaled * nalgebra::convert(5.0f64.sqrt());
+                (T::one() + z + z * z / nalgebra::convert(3.0)) * (-z).exp()
+            }
+        }
+    }
+}
+
+impl<T> Kernel<T> for Matern<T>
+where
+    T: RealField + Copy,
+{
+    fn compute(&self, x: RowView<T>, y: RowView<T>) -> T {
+        self.apply((x - y).norm_squared())
+    }
+
+    fn matrix(&self, x: &DMatrix<T>, y: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        validate_num_vars(x, y)?;
+        Ok(squared_euclidean_matrix(x, y).map(|distance| self.apply(distance)))
+    }
+}
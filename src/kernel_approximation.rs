@@ -0,0 +1,183 @@
+//! Explicit approximate feature maps for kernels, so that a linear model trained on the features
+//! approximates a kernel method, at a cost that grows linearly rather than quadratically with the
+//! number of observations.
+use crate::kernel::{Kernel, Rbf};
+use crate::random::Rng;
+use crate::traits::Transformer;
+use crate::validation::{check_2d_nonempty, check_finite, check_fitted, check_num_vars};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+fn validate_n_components(n_components: usize) -> SLearningResult<()> {
+    if n_components == 0 {
+        return Err(SLearningError::InvalidParameters(
+            "Number of components must be at least one.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The Nyström approximation of a kernel's feature map (Williams & Seeger, Using the Nyström
+/// Method to Speed Up Kernel Machines, 2001).
+///
+/// A random sample of `n_components` training observations are kept as landmarks, and each
+/// observation is mapped to its kernel values with the landmarks, whitened by the inverse square
+/// root of the landmarks' Gram matrix. The dot products of the features then approximate the
+/// kernel, exactly for the landmarks. Any [`Kernel`] can be used, e.g. a polynomial kernel.
+#[derive(Debug)]
+pub struct Nystroem<T, K>
+where
+    T: RealField,
+{
+    /// The training observations (rows) used as landmarks.
+    pub landmarks: Option<DMatrix<T>>,
+    kernel: K,
+    n_components: usize,
+    seed: u64,
+    /// The inverse square root of the landmarks' Gram matrix.
+    normalization: Option<DMatrix<T>>,
+}
+
+impl<T, K> Nystroem<T, K>
+where
+    T: RealField + Copy,
+    K: Kernel<T>,
+{
+    pub fn new(kernel: K, n_components: usize) -> SLearningResult<Self> {
+        validate_n_components(n_components)?;
+        Ok(Self {
+            landmarks: None,
+            kernel,
+            n_components,
+            seed: 0,
+            normalization: None,
+        })
+    }
+
+    /// Set the seed for sampling the landmarks.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+}
+
+impl<T, K> Transformer<T> for Nystroem<T, K>
+where
+    T: RealField + Copy,
+    K: Kernel<T>,
+{
+    fn fit(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        check_finite(inputs)?;
+        if self.n_components > inputs.nrows() {
+            let error_msg = format!(
+                "Cannot sample {} landmarks from {} observation(s).",
+                self.n_components,
+                inputs.nrows()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        let mut rng = Rng::new(self.seed);
+        let landmarks = inputs.select_rows(&rng.sample_indices(inputs.nrows(), self.n_components));
+
+        // Eigenvalues that are zero up to rounding (e.g. from duplicate landmarks) are left out of
+        // the inverse, as in a pseudo-inverse.
+        let eigen = self.kernel.gram_matrix(&landmarks).symmetric_eigen();
+        let largest = eigen.eigenvalues.max().max(T::zero());
+        let tolerance =
+            largest * T::default_epsilon() * nalgebra::convert(self.n_components as f64);
+        let inverse_sqrt = eigen.eigenvalues.map(|value| match value > tolerance {
+            true => T::one() / value.sqrt(),
+            false => T::zero(),
+        });
+        let scaled = DMatrix::from_fn(self.n_components, self.n_components, |i, j| {
+            eigen.eigenvectors[(i, j)] * inverse_sqrt[j]
+        });
+        self.normalization = Some(&scaled * eigen.eigenvectors.transpose());
+        self.landmarks = Some(landmarks);
+        Ok(())
+    }
+
+    fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let landmarks = check_fitted(&self.landmarks)?;
+        let normalization = check_fitted(&self.normalization)?;
+        check_num_vars(landmarks.ncols(), inputs.ncols())?;
+        check_finite(inputs)?;
+        Ok(self.kernel.matrix(inputs, landmarks)? * normalization)
+    }
+}
+
+/// Random Fourier features approximating the radial basis function kernel (Rahimi & Recht, Random
+/// Features for Large-Scale Kernel Machines, 2007).
+///
+/// Each feature is `sqrt(2 / n_components) cos(w · x + b)`, with `w` normally distributed with
+/// variance `2 gamma` in each variable and `b` uniform between zero and `2 pi`. The dot products of
+/// the features approximate the kernel, with an error that shrinks like one over the square root
+/// of the number of components. Unlike [`Nystroem`], the features do not depend on the data.
+#[derive(Debug)]
+pub struct RbfSampler<T>
+where
+    T: RealField,
+{
+    /// The random directions (columns) for each feature.
+    pub weights: Option<DMatrix<T>>,
+    /// The random offset of each feature.
+    pub offsets: Option<DVector<T>>,
+    kernel: Rbf<T>,
+    n_components: usize,
+    seed: u64,
+}
+
+impl<T> RbfSampler<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(kernel: Rbf<T>, n_components: usize) -> SLearningResult<Self> {
+        validate_n_components(n_components)?;
+        Ok(Self {
+            weights: None,
+            offsets: None,
+            kernel,
+            n_components,
+            seed: 0,
+        })
+    }
+
+    /// Set the seed for the random features.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+}
+
+impl<T> Transformer<T> for RbfSampler<T>
+where
+    T: RealField + Copy,
+{
+    fn fit(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        check_finite(inputs)?;
+        let scale = (self.kernel.gamma * nalgebra::convert(2.0)).sqrt();
+        let mut rng = Rng::new(self.seed);
+        self.weights = Some(DMatrix::from_fn(
+            inputs.ncols(),
+            self.n_components,
+            |_, _| scale * rng.standard_normal(),
+        ));
+        self.offsets = Some(DVector::from_fn(self.n_components, |_, _| {
+            T::two_pi() * rng.uniform()
+        }));
+        Ok(())
+    }
+
+    fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let weights = check_fitted(&self.weights)?;
+        let offsets = check_fitted(&self.offsets)?;
+        check_num_vars(weights.nrows(), inputs.ncols())?;
+        check_finite(inputs)?;
+        let scale: T = nalgebra::convert((2.0 / self.n_components as f64).sqrt());
+        let mut features = inputs * weights;
+        for (mut column, &offset) in features.column_iter_mut().zip(offsets.iter()) {
+            column.apply(|value| *value = scale * (*value + offset).cos());
+        }
+        Ok(features)
+    }
+}
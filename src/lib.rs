@@ -1,9 +1,43 @@
+pub mod anomaly;
+pub mod cluster;
+pub mod covariance;
+pub mod decomposition;
+pub mod diagnostics;
+pub mod distance;
 mod error;
+pub mod gaussian_process;
+pub mod impurity;
+pub mod inspection;
+pub mod kernel;
+pub mod kernel_approximation;
 pub mod linear_regression;
+pub mod math;
+pub mod metrics;
+pub mod model_selection;
+pub mod multilabel;
+pub mod neighbors;
+pub mod optim;
+pub mod ordinal_regression;
+pub mod preprocessing;
+pub mod random;
+pub mod random_projection;
+pub mod ranking;
+pub mod recommendation;
+pub mod semi_supervised;
+mod special;
+pub mod stats;
+pub mod survival;
+pub mod timeseries;
 mod traits;
+pub mod utils;
+pub mod validation;
 
 pub use error::SLearningError;
 
 pub type SLearningResult<T> = Result<T, error::SLearningError>;
 
-pub use traits::{SupervisedModel, UnsupervisedModel};
+/// A view of a single row (observation) of a `DMatrix`.
+pub type RowView<'a, T> =
+    nalgebra::MatrixView<'a, T, nalgebra::U1, nalgebra::Dyn, nalgebra::U1, nalgebra::Dyn>;
+
+pub use traits::{BinaryPrediction, FrozenModel, SupervisedModel, Transformer, UnsupervisedModel};
@@ -1,101 +1,1620 @@
+use crate::diagnostics::{Diagnostics, Warning};
+use crate::optim::ConvergenceConfig;
+use crate::stats::median;
 use crate::traits::SupervisedModel;
 
-use crate::{SLearningError, SLearningResult};
+use crate::validation::{
+    check_2d_nonempty, check_consistent_length, check_fitted, check_non_negative, check_num_vars,
+    is_missing,
+};
+use crate::{RowView, SLearningError, SLearningResult};
 use nalgebra::{self, DMatrix, DVector, RealField};
+use std::borrow::Cow;
 
 fn validate_train_dimensions<T: RealField>(
     inputs: &DMatrix<T>,
     outputs: &DVector<T>,
 ) -> SLearningResult<()> {
-    let num_input_obs = inputs.nrows();
-    let num_output_obs = outputs.len();
+    check_2d_nonempty(inputs)?;
+    check_consistent_length(inputs, outputs)?;
+    validate_not_missing(inputs)?;
+    if let Some(obs) = outputs.iter().position(is_missing) {
+        let error_msg = format!("Output has a missing (NaN) value for observation {}.", obs);
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(())
+}
+
+fn validate_not_missing<T: RealField>(inputs: &DMatrix<T>) -> SLearningResult<()> {
+    for (var, column) in inputs.column_iter().enumerate() {
+        if let Some(obs) = column.iter().position(is_missing) {
+            let error_msg = format!(
+                "Input has a missing (NaN) value for observation {} and variable {}.",
+                obs, var
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+    }
+    Ok(())
+}
+
+/// How to fill in a missing input value, from the observed training values of the same variable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImputeStrategy<T> {
+    Mean,
+    Median,
+    Constant(T),
+}
+
+impl<T> ImputeStrategy<T>
+where
+    T: RealField + Copy,
+{
+    fn fill_value(&self, var: usize, observed: Vec<T>) -> SLearningResult<T> {
+        if let Self::Constant(value) = self {
+            return Ok(*value);
+        }
+        if observed.is_empty() {
+            let error_msg = format!("Variable {} has no observed values to impute from.", var);
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        match self {
+            Self::Mean => {
+                let num_obs: T = nalgebra::convert(observed.len() as f64);
+                Ok(observed.into_iter().fold(T::zero(), |total, x| total + x) / num_obs)
+            }
+            _ => Ok(median(&DVector::from_vec(observed))),
+        }
+    }
+}
+
+/// What a model does with missing (NaN) values in its training data and the inputs it predicts.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MissingValues<T> {
+    /// Fail with an error naming the first missing value. This is the default.
+    #[default]
+    Error,
+    /// Train without the observations that have any missing value. The prediction for an
+    /// observation with a missing input value is also missing.
+    DropRows,
+    /// Replace each missing input value with a statistic of the observed training values of its
+    /// variable, when both training and predicting. Training observations with a missing output
+    /// are dropped.
+    Impute(ImputeStrategy<T>),
+}
+
+/// A [`MissingValues`] policy, and the values that it imputes once trained.
+#[derive(Debug)]
+struct MissingValueHandler<T>
+where
+    T: RealField,
+{
+    policy: MissingValues<T>,
+    fill_values: Option<DVector<T>>,
+}
+
+impl<T> MissingValueHandler<T>
+where
+    T: RealField,
+{
+    fn new(policy: MissingValues<T>) -> Self {
+        Self {
+            policy,
+            fill_values: None,
+        }
+    }
+}
+
+impl<T> MissingValueHandler<T>
+where
+    T: RealField + Copy,
+{
+    /// Apply the policy to the training data. With [`MissingValues::Error`], the missing values
+    /// are left for `validate_train_dimensions` to report.
+    fn prepare_train(
+        &mut self,
+        inputs: DMatrix<T>,
+        outputs: DVector<T>,
+    ) -> SLearningResult<(DMatrix<T>, DVector<T>)> {
+        // Rows can only be dropped if they line up, otherwise leave the mismatch to be reported.
+        if matches!(self.policy, MissingValues::Error) || inputs.nrows() != outputs.len() {
+            return Ok((inputs, outputs));
+        }
+        let impute = matches!(self.policy, MissingValues::Impute(_));
+        let keep: Vec<usize> = (0..inputs.nrows())
+            .filter(|&i| {
+                !is_missing(&outputs[i]) && (impute || !inputs.row(i).iter().any(is_missing))
+            })
+            .collect();
+        let mut inputs = inputs.select_rows(&keep);
+        let outputs = outputs.select_rows(&keep);
+        if let MissingValues::Impute(strategy) = self.policy {
+            let mut fill_values = DVector::zeros(inputs.ncols());
+            for (var, mut column) in inputs.column_iter_mut().enumerate() {
+                let observed = column.iter().copied().filter(|x| !is_missing(x)).collect();
+                fill_values[var] = strategy.fill_value(var, observed)?;
+                column
+                    .iter_mut()
+                    .filter(|x| is_missing(*x))
+                    .for_each(|x| *x = fill_values[var]);
+            }
+            self.fill_values = Some(fill_values);
+        }
+        Ok((inputs, outputs))
+    }
+
+    /// Apply the policy to inputs to predict, only copying them if values need to be imputed.
+    fn prepare_predict<'a>(&self, inputs: &'a DMatrix<T>) -> SLearningResult<Cow<'a, DMatrix<T>>> {
+        match self.policy {
+            MissingValues::Error => validate_not_missing(inputs)?,
+            MissingValues::DropRows => (),
+            MissingValues::Impute(_) if inputs.iter().any(is_missing) => {
+                let fill_values = check_fitted(&self.fill_values)?;
+                check_num_vars(fill_values.len(), inputs.ncols())?;
+                let filled = DMatrix::from_fn(inputs.nrows(), inputs.ncols(), |i, j| {
+                    match is_missing(&inputs[(i, j)]) {
+                        true => fill_values[j],
+                        false => inputs[(i, j)],
+                    }
+                });
+                return Ok(Cow::Owned(filled));
+            }
+            MissingValues::Impute(_) => (),
+        }
+        Ok(Cow::Borrowed(inputs))
+    }
+}
+
+fn get_full_inputs<T: RealField>(inputs: DMatrix<T>, fit_intercept: bool) -> DMatrix<T> {
+    if !fit_intercept {
+        return inputs;
+    }
+    inputs.insert_column(0, T::one())
+}
+
+/// Lower and upper bounds on the coefficients of a linear model, one pair for each input
+/// variable.
+///
+/// The intercept (if any) is never bounded. A coefficient can be left unbounded on either side by
+/// using an infinite bound.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CoefficientBounds<T>
+where
+    T: RealField,
+{
+    lower: DVector<T>,
+    upper: DVector<T>,
+}
+
+impl<T> CoefficientBounds<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(lower: DVector<T>, upper: DVector<T>) -> SLearningResult<Self> {
+        if lower.len() != upper.len() {
+            let error_msg = format!(
+                "There are {} lower bound(s), but {} upper bound(s). These must be equal.",
+                lower.len(),
+                upper.len()
+            );
+            return Err(SLearningError::InvalidParameters(error_msg));
+        }
+        if lower.iter().zip(upper.iter()).any(|(l, u)| l > u) {
+            return Err(SLearningError::InvalidParameters(
+                "Lower bounds cannot be greater than upper bounds.".to_string(),
+            ));
+        }
+        Ok(Self { lower, upper })
+    }
+
+    pub fn lower(&self) -> &DVector<T> {
+        &self.lower
+    }
+
+    pub fn upper(&self) -> &DVector<T> {
+        &self.upper
+    }
+}
+
+/// Minimise `beta' normal_matrix beta / 2 - beta' moments` subject to the bounds, using projected
+/// coordinate descent. The first coefficient is unbounded if `fit_intercept` is true.
+fn bounded_quadratic_minimum<T>(
+    normal_matrix: &DMatrix<T>,
+    moments: &DVector<T>,
+    bounds: &CoefficientBounds<T>,
+    fit_intercept: bool,
+) -> SLearningResult<DVector<T>>
+where
+    T: RealField + Copy,
+{
+    const MAX_SWEEPS: usize = 10_000;
+    let offset = if fit_intercept { 1 } else { 0 };
+    let clamp = |index: usize, value: T| {
+        if index < offset {
+            return value;
+        }
+        value
+            .max(bounds.lower[index - offset])
+            .min(bounds.upper[index - offset])
+    };
+    let tol = T::default_epsilon().sqrt();
+
+    let num_coefficients = moments.len();
+    let mut beta = DVector::from_fn(num_coefficients, |i, _| clamp(i, T::zero()));
+    for _ in 0..MAX_SWEEPS {
+        let mut max_change = T::zero();
+        for j in 0..num_coefficients {
+            let curvature = normal_matrix[(j, j)];
+            let new_value = if curvature.is_zero() {
+                // The objective does not depend on this coefficient.
+                beta[j]
+            } else {
+                let residual = moments[j] - normal_matrix.row(j).dot(&beta.transpose());
+                clamp(j, beta[j] + residual / curvature)
+            };
+            max_change = max_change.max((new_value - beta[j]).abs());
+            beta[j] = new_value;
+        }
+        if max_change <= tol * (T::one() + beta.amax()) {
+            return Ok(beta);
+        }
+    }
+    Err(SLearningError::InvalidData(
+        "The bounded least squares solver did not converge.".to_string(),
+    ))
+}
+
+/// Warn about input variables whose variance is tiny compared to their mean square, which makes
+/// their coefficients sensitive to rounding.
+fn warn_low_variance<T: RealField + Copy>(inputs: &DMatrix<T>, diagnostics: &Diagnostics) {
+    if !diagnostics.is_enabled() || inputs.nrows() == 0 {
+        return;
+    }
+    let tol = T::default_epsilon().sqrt();
+    let num_obs: T = nalgebra::convert(inputs.nrows() as f64);
+    let variables: Vec<usize> = inputs
+        .column_iter()
+        .enumerate()
+        .filter(|(_, column)| column.variance() <= tol * column.norm_squared() / num_obs)
+        .map(|(var, _)| var)
+        .collect();
+    if !variables.is_empty() {
+        diagnostics.warn(Warning::LowVariance { variables });
+    }
+}
+
+/// What a linear regressor does with input variables that are constant, whose coefficients cannot
+/// be estimated without a penalty or bounds, since they are indistinguishable from the intercept
+/// (or, without an intercept, from zero).
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum ConstantColumns {
+    /// Fail with an error naming the constant variables. This is the default.
+    #[default]
+    Error,
+    /// Train without the constant variables, and give them coefficients of zero.
+    Drop,
+}
+
+/// The input variables whose coefficients cannot be estimated by least squares: those that are
+/// constant when there is an intercept, and those that are always zero otherwise.
+fn constant_columns<T: RealField>(inputs: &DMatrix<T>, fit_intercept: bool) -> Vec<usize> {
+    (0..inputs.ncols())
+        .filter(|&var| {
+            let column = inputs.column(var);
+            match fit_intercept {
+                true => column.iter().all(|x| *x == column[0]),
+                false => column.iter().all(|x| x.is_zero()),
+            }
+        })
+        .collect()
+}
+
+fn train_linear_regressor<T>(
+    inputs: &DMatrix<T>,
+    outputs: &DVector<T>,
+    fit_intercept: bool,
+    penalties: &DVector<T>,
+    bounds: Option<&CoefficientBounds<T>>,
+    constant: ConstantColumns,
+    diagnostics: &Diagnostics,
+) -> SLearningResult<DVector<T>>
+where
+    T: RealField + Copy,
+{
+    validate_train_dimensions(inputs, outputs)?;
+    if penalties.iter().all(|penalty| penalty.is_zero()) && bounds.is_none() {
+        let constant_vars = constant_columns(inputs, fit_intercept);
+        if !constant_vars.is_empty() {
+            return train_without_constant_columns(
+                inputs,
+                outputs,
+                fit_intercept,
+                &constant_vars,
+                constant,
+                diagnostics,
+            );
+        }
+    }
+    if let Some(bounds) = bounds {
+        if bounds.lower.len() != inputs.ncols() {
+            let error_msg = format!(
+                "The bounds have {} variables, but the input has {} variables. These must be equal.",
+                bounds.lower.len(),
+                inputs.ncols()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+    }
+    // TODO: Is there a way to avoid this clone? At least for when `fit_intercept` is false.
+    let full_inputs = &get_full_inputs(inputs.clone(), fit_intercept);
 
-    if num_input_obs == 0 || num_output_obs == 0 {
-        return Err(SLearningError::InvalidData(
-            "Cannot train with zero observations.".to_string(),
+    let mut normal_matrix_inverse = full_inputs.transpose() * full_inputs;
+    add_penalty(&mut normal_matrix_inverse, penalties, fit_intercept);
+    if let Some(bounds) = bounds {
+        let moments = full_inputs.transpose() * outputs;
+        return bounded_quadratic_minimum(&normal_matrix_inverse, &moments, bounds, fit_intercept);
+    }
+    let condition_number = diagnostics.is_enabled().then(|| {
+        let eigenvalues = normal_matrix_inverse.clone().symmetric_eigenvalues();
+        match eigenvalues.min() > T::zero() {
+            true => eigenvalues.max() / eigenvalues.min(),
+            false => nalgebra::convert(f64::INFINITY),
+        }
+    });
+    if !normal_matrix_inverse.try_inverse_mut() {
+        return Err(rank_deficiency_error(full_inputs, fit_intercept));
+    }
+    if let Some(condition_number) = condition_number {
+        if condition_number > T::one() / T::default_epsilon().sqrt() {
+            diagnostics.warn(Warning::IllConditioned {
+                condition_number: nalgebra::try_convert(condition_number)
+                    .expect("The condition number is a number."),
+            });
+        }
+    }
+    let beta_hat = normal_matrix_inverse * full_inputs.transpose() * outputs;
+    Ok(beta_hat)
+}
+
+/// Add the ridge penalty of each input variable to the diagonal of the normal matrix.
+fn add_penalty<T: RealField + Copy>(
+    normal_matrix: &mut DMatrix<T>,
+    penalties: &DVector<T>,
+    fit_intercept: bool,
+) {
+    // The intercept should not be penalised, so don't add to first diagonal if `fit_intercept` is true.
+    let start = if fit_intercept { 1 } else { 0 };
+    for index in start..normal_matrix.nrows() {
+        normal_matrix[(index, index)] += penalties[index - start];
+    }
+}
+
+/// The trace of the hat matrix `X (X^T X + P)^+ X^T` of a (ridge) linear regression, i.e. its
+/// effective degrees of freedom, from the unpenalised normal matrix `X^T X` and the penalty of
+/// each input variable. The pseudo-inverse gives the rank of `X` when it is unpenalised.
+fn hat_matrix_trace<T: RealField + Copy>(
+    normal_matrix: &DMatrix<T>,
+    penalties: &DVector<T>,
+    fit_intercept: bool,
+) -> T {
+    let mut penalised = normal_matrix.clone();
+    add_penalty(&mut penalised, penalties, fit_intercept);
+    let tol = T::default_epsilon() * penalised.amax() * nalgebra::convert(penalised.nrows() as f64);
+    let inverse = penalised
+        .pseudo_inverse(tol)
+        .expect("The tolerance is not negative.");
+    (inverse * normal_matrix).trace()
+}
+
+/// The ridge penalty of each input variable. With `scales`, each penalty is multiplied by the
+/// variable's squared scale, which is the same as penalising the coefficients of the variables
+/// divided by their scales. Variables with a scale of zero are not rescaled.
+fn variable_penalties<T: RealField + Copy>(
+    penalty: T,
+    scales: Option<&DVector<T>>,
+    num_vars: usize,
+) -> DVector<T> {
+    DVector::from_fn(num_vars, |var, _| match scales.map(|scales| scales[var]) {
+        Some(scale) if scale > T::zero() => penalty * scale * scale,
+        _ => penalty,
+    })
+}
+
+/// The scale of each input variable when standardising: its standard deviation with an
+/// intercept, or its root mean square without one (so that the variables are not centred).
+fn standardization_scales<T: RealField + Copy>(
+    inputs: &DMatrix<T>,
+    fit_intercept: bool,
+) -> DVector<T> {
+    let num_obs: T = nalgebra::convert(inputs.nrows().max(1) as f64);
+    DVector::from_fn(inputs.ncols(), |var, _| {
+        let column = inputs.column(var);
+        let mean = match fit_intercept {
+            true => column.sum() / num_obs,
+            false => T::zero(),
+        };
+        let sum_squares = column
+            .iter()
+            .fold(T::zero(), |acc, &x| acc + (x - mean) * (x - mean));
+        (sum_squares / num_obs).sqrt()
+    })
+}
+
+/// The columns of `full_inputs` that are (nearly) linear combinations of the columns before them,
+/// found by Gram-Schmidt orthogonalisation. The rank is the number of other columns.
+fn dependent_columns<T: RealField + Copy>(full_inputs: &DMatrix<T>) -> Vec<usize> {
+    let tol = T::default_epsilon().sqrt();
+    let mut basis: Vec<DVector<T>> = Vec::new();
+    let mut dependent = Vec::new();
+    for (j, column) in full_inputs.column_iter().enumerate() {
+        let mut residual = column.into_owned();
+        for direction in &basis {
+            let projection = direction.dot(&residual);
+            residual.axpy(-projection, direction, T::one());
+        }
+        let norm = residual.norm();
+        if norm <= tol * column.norm() || norm.is_zero() {
+            dependent.push(j);
+        } else {
+            basis.push(residual / norm);
+        }
+    }
+    dependent
+}
+
+/// The error for a singular normal matrix, with the rank of the inputs and the variables that are
+/// collinear with the ones before them, so that they can be removed.
+fn rank_deficiency_error<T: RealField + Copy>(
+    full_inputs: &DMatrix<T>,
+    fit_intercept: bool,
+) -> SLearningError {
+    let dependent = dependent_columns(full_inputs);
+    let mut error_msg = format!(
+        "The normal matrix is not invertible. The inputs have rank {}, but there are {} coefficients to estimate.",
+        full_inputs.ncols() - dependent.len(),
+        full_inputs.ncols()
+    );
+    let offset = if fit_intercept { 1 } else { 0 };
+    let vars: Vec<String> = dependent
+        .iter()
+        .filter(|&&j| j >= offset)
+        .map(|j| (j - offset).to_string())
+        .collect();
+    if !vars.is_empty() {
+        let earlier = match fit_intercept {
+            true => "the intercept and earlier variables",
+            false => "earlier variables",
+        };
+        error_msg.push_str(&format!(
+            " Input variable(s) {} are (nearly) linear combinations of {}.",
+            vars.join(", "),
+            earlier
         ));
     }
+    SLearningError::InvalidData(error_msg)
+}
+
+fn train_without_constant_columns<T>(
+    inputs: &DMatrix<T>,
+    outputs: &DVector<T>,
+    fit_intercept: bool,
+    constant_vars: &[usize],
+    constant: ConstantColumns,
+    diagnostics: &Diagnostics,
+) -> SLearningResult<DVector<T>>
+where
+    T: RealField + Copy,
+{
+    if constant == ConstantColumns::Error {
+        let vars = constant_vars
+            .iter()
+            .map(|var| var.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let error_msg = match fit_intercept {
+            true => format!(
+                "Input variable(s) {} are constant, so they cannot be distinguished from the intercept. Remove them or drop constant columns.",
+                vars
+            ),
+            false => format!(
+                "Input variable(s) {} are always zero, so their coefficients cannot be estimated. Remove them or drop constant columns.",
+                vars
+            ),
+        };
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    let kept_vars: Vec<usize> = (0..inputs.ncols())
+        .filter(|var| !constant_vars.contains(var))
+        .collect();
+    let kept_coefficients = train_linear_regressor(
+        &inputs.select_columns(&kept_vars),
+        outputs,
+        fit_intercept,
+        &DVector::zeros(kept_vars.len()),
+        None,
+        ConstantColumns::Error,
+        diagnostics,
+    )?;
+    let offset = if fit_intercept { 1 } else { 0 };
+    let mut coefficients = DVector::zeros(inputs.ncols() + offset);
+    if fit_intercept {
+        coefficients[0] = kept_coefficients[0];
+    }
+    for (kept, &var) in kept_vars.iter().enumerate() {
+        coefficients[var + offset] = kept_coefficients[kept + offset];
+    }
+    Ok(coefficients)
+}
+
+/// The sufficient statistics of a linear regression, `X^T X` and `X^T y` (with a column of ones in
+/// `X` for the intercept), so that a model can be trained on data held in separate shards or on
+/// separate machines.
+///
+/// Each shard updates its own statistics, which are then merged and used to train the model with
+/// e.g. [`OlsRegressor::train_normal_equations`], giving the same coefficients as training on all
+/// the data at once. Only these small matrices need to be shared, not the data itself. Missing
+/// values are not allowed, and constant input variables are not detected.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NormalEquations<T>
+where
+    T: RealField,
+{
+    fit_intercept: bool,
+    count: usize,
+    normal_matrix: DMatrix<T>,
+    moments: DVector<T>,
+    /// `y^T y`, for the residual sum of squares.
+    output_sum_squares: T,
+}
+
+impl<T> NormalEquations<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(num_vars: usize, fit_intercept: bool) -> Self {
+        let num_coefficients = num_vars + if fit_intercept { 1 } else { 0 };
+        Self {
+            fit_intercept,
+            count: 0,
+            normal_matrix: DMatrix::zeros(num_coefficients, num_coefficients),
+            moments: DVector::zeros(num_coefficients),
+            output_sum_squares: T::zero(),
+        }
+    }
+
+    /// Add a batch of observations (one per row).
+    pub fn update(&mut self, inputs: &DMatrix<T>, outputs: &DVector<T>) -> SLearningResult<()> {
+        validate_train_dimensions(inputs, outputs)?;
+        check_num_vars(self.num_vars(), inputs.ncols())?;
+        let full_inputs = get_full_inputs(inputs.clone(), self.fit_intercept);
+        self.normal_matrix += full_inputs.transpose() * &full_inputs;
+        self.moments += full_inputs.transpose() * outputs;
+        self.output_sum_squares += outputs.norm_squared();
+        self.count += inputs.nrows();
+        Ok(())
+    }
+
+    /// Add the observations summarised by `other`, e.g. from another shard.
+    pub fn merge(&mut self, other: &Self) -> SLearningResult<()> {
+        if self.num_vars() != other.num_vars() {
+            let error_msg = format!(
+                "These normal equations have {} variables, but the other normal equations have {} variables. These must be equal.",
+                self.num_vars(),
+                other.num_vars()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        if self.fit_intercept != other.fit_intercept {
+            return Err(SLearningError::InvalidData(
+                "Cannot merge normal equations with and without an intercept.".to_string(),
+            ));
+        }
+        self.normal_matrix += &other.normal_matrix;
+        self.moments += &other.moments;
+        self.output_sum_squares += other.output_sum_squares;
+        self.count += other.count;
+        Ok(())
+    }
+
+    /// The number of observations seen so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn num_vars(&self) -> usize {
+        self.moments.len() - if self.fit_intercept { 1 } else { 0 }
+    }
+
+    /// The scale of each input variable when standardising (see [`standardization_scales`]),
+    /// from the sums and sums of squares in the normal matrix.
+    fn standardization_scales(&self) -> DVector<T> {
+        let offset = if self.fit_intercept { 1 } else { 0 };
+        let num_obs: T = nalgebra::convert(self.count.max(1) as f64);
+        DVector::from_fn(self.num_vars(), |var, _| {
+            let index = var + offset;
+            let mean_square = self.normal_matrix[(index, index)] / num_obs;
+            let variance = match self.fit_intercept {
+                true => {
+                    let mean = self.normal_matrix[(0, index)] / num_obs;
+                    mean_square - mean * mean
+                }
+                false => mean_square,
+            };
+            variance.max(T::zero()).sqrt()
+        })
+    }
+
+    /// The ridge penalty of each input variable (see [`variable_penalties`]).
+    fn variable_penalties(&self, penalty: T, standardize: bool) -> DVector<T> {
+        let scales = standardize.then(|| self.standardization_scales());
+        variable_penalties(penalty, scales.as_ref(), self.num_vars())
+    }
+
+    /// The coefficients that minimise the (penalised) squared error.
+    fn solve(
+        &self,
+        fit_intercept: bool,
+        penalty: &T,
+        standardize: bool,
+        bounds: Option<&CoefficientBounds<T>>,
+    ) -> SLearningResult<DVector<T>> {
+        if fit_intercept != self.fit_intercept {
+            let error_msg = match fit_intercept {
+                true => "The model has an intercept, but the normal equations do not. These must match.",
+                false => "The normal equations have an intercept, but the model does not. These must match.",
+            };
+            return Err(SLearningError::InvalidParameters(error_msg.to_string()));
+        }
+        if self.count == 0 {
+            return Err(SLearningError::InvalidData(
+                "Cannot train with zero observations.".to_string(),
+            ));
+        }
+        let mut normal_matrix = self.normal_matrix.clone();
+        add_penalty(
+            &mut normal_matrix,
+            &self.variable_penalties(*penalty, standardize),
+            fit_intercept,
+        );
+        if let Some(bounds) = bounds {
+            if bounds.lower.len() != self.num_vars() {
+                let error_msg = format!(
+                    "The bounds have {} variables, but the normal equations have {} variables. These must be equal.",
+                    bounds.lower.len(),
+                    self.num_vars()
+                );
+                return Err(SLearningError::InvalidData(error_msg));
+            }
+            return bounded_quadratic_minimum(&normal_matrix, &self.moments, bounds, fit_intercept);
+        }
+        normal_matrix
+            .cholesky()
+            .map(|cholesky| cholesky.solve(&self.moments))
+            .ok_or_else(|| {
+                let error_msg = format!(
+                    "The normal matrix is not invertible. There are {} coefficients to estimate, but the inputs are linearly dependent.",
+                    self.moments.len()
+                );
+                SLearningError::InvalidData(error_msg)
+            })
+    }
+
+    /// The residual sum of squares of the coefficients, `y^T y - 2 b^T X^T y + b^T X^T X b`.
+    fn residual_sum_of_squares(&self, coefficients: &DVector<T>) -> T {
+        let two: T = nalgebra::convert(2.0);
+        let rss = self.output_sum_squares - two * coefficients.dot(&self.moments)
+            + coefficients.dot(&(&self.normal_matrix * coefficients));
+        rss.max(T::zero())
+    }
+}
+
+/// Write the predictions of a linear regressor into `predictions`, without copying the inputs to
+/// add an intercept column.
+fn predict_linear_regressor_into<T>(
+    inputs: &DMatrix<T>,
+    coefficients: &Option<DVector<T>>,
+    fit_intercept: bool,
+    predictions: &mut DVector<T>,
+) -> SLearningResult<()>
+where
+    T: RealField + Copy,
+{
+    let Some(coefficient_estimates) = coefficients else {
+        return Err(SLearningError::UntrainedModel);
+    };
+    let offset = if fit_intercept { 1 } else { 0 };
+    check_num_vars(coefficient_estimates.len(), inputs.ncols() + offset)?;
+    if predictions.len() != inputs.nrows() {
+        let error_msg = format!(
+            "Input has {} observation(s), but there is space for {} prediction(s). These must be equal.",
+            inputs.nrows(),
+            predictions.len()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    let intercept = if fit_intercept {
+        coefficient_estimates[0]
+    } else {
+        T::zero()
+    };
+    predictions.fill(intercept);
+    let slopes = coefficient_estimates.rows(offset, inputs.ncols());
+    predictions.gemv(T::one(), inputs, &slopes, T::one());
+    Ok(())
+}
+
+fn predict_linear_regressor<T>(
+    inputs: &DMatrix<T>,
+    coefficients: &Option<DVector<T>>,
+    fit_intercept: bool,
+) -> SLearningResult<DVector<T>>
+where
+    T: RealField + Copy,
+{
+    let mut predictions = DVector::zeros(inputs.nrows());
+    predict_linear_regressor_into(inputs, coefficients, fit_intercept, &mut predictions)?;
+    Ok(predictions)
+}
+
+/// The contribution of each input variable to a single prediction of a linear model.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation<T>
+where
+    T: RealField,
+{
+    /// The intercept, which is zero if the model has no intercept.
+    pub intercept: T,
+    /// Each variable's coefficient multiplied by its value.
+    pub contributions: DVector<T>,
+}
+
+impl<T> Explanation<T>
+where
+    T: RealField + Copy,
+{
+    /// The prediction being explained, which is the intercept plus the sum of the contributions.
+    pub fn prediction(&self) -> T {
+        self.intercept + self.contributions.sum()
+    }
+}
+
+/// The fitted parameters of a linear regressor as plain data, so they can be stored or used for
+/// predictions outside this crate, and restored with the regressor's `with_parameters`.
+///
+/// The prediction for an observation `x` is the intercept (or zero, if there is none) plus the sum
+/// of `coefficients[j] * x[j]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LinearParameters<T> {
+    /// The intercept, if the model has one.
+    pub intercept: Option<T>,
+    /// The coefficient of each input variable, in the order of the input columns.
+    pub coefficients: Vec<T>,
+}
+
+impl<T> LinearParameters<T>
+where
+    T: RealField + Copy,
+{
+    /// The coefficients in the form used by the regressors (with the intercept first if there is
+    /// one), and whether there is an intercept.
+    fn into_coefficients(self) -> (DVector<T>, bool) {
+        match self.intercept {
+            Some(intercept) => {
+                let mut coefficients = self.coefficients;
+                coefficients.insert(0, intercept);
+                (DVector::from_vec(coefficients), true)
+            }
+            None => (DVector::from_vec(self.coefficients), false),
+        }
+    }
+
+    /// The prediction as an arithmetic formula in the named variables, e.g.
+    /// `1.5 + 2 * x - 0.25 * y`.
+    pub fn to_formula(&self, names: &[&str]) -> SLearningResult<String> {
+        self.render(names, |name| name.to_string())
+    }
+
+    /// The prediction as a SQL expression in the named columns, e.g.
+    /// `1.5 + 2 * "x" - 0.25 * "y"`, so predictions can be made in a database. The column names
+    /// are quoted, so they can contain any characters.
+    pub fn to_sql(&self, columns: &[&str]) -> SLearningResult<String> {
+        self.render(columns, |column| {
+            format!("\"{}\"", column.replace('"', "\"\""))
+        })
+    }
+
+    fn render<F>(&self, names: &[&str], render_name: F) -> SLearningResult<String>
+    where
+        F: Fn(&str) -> String,
+    {
+        if names.len() != self.coefficients.len() {
+            let error_msg = format!(
+                "There are {} coefficient(s), but {} variable name(s). These must be equal.",
+                self.coefficients.len(),
+                names.len()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        if self
+            .intercept
+            .iter()
+            .chain(&self.coefficients)
+            .any(|p| !p.is_finite())
+        {
+            return Err(SLearningError::InvalidData(
+                "Cannot export parameters that are not finite.".to_string(),
+            ));
+        }
+        let mut expression = self.intercept.map(|intercept| intercept.to_string());
+        for (coefficient, name) in self.coefficients.iter().zip(names) {
+            let term = format!("{} * {}", coefficient.abs(), render_name(name));
+            expression = Some(match (expression, *coefficient < T::zero()) {
+                (None, false) => term,
+                (None, true) => format!("-{}", term),
+                (Some(expression), false) => format!("{} + {}", expression, term),
+                (Some(expression), true) => format!("{} - {}", expression, term),
+            });
+        }
+        Ok(expression.unwrap_or_else(|| "0".to_string()))
+    }
+}
+
+fn linear_parameters<T>(
+    coefficients: &Option<DVector<T>>,
+    fit_intercept: bool,
+) -> SLearningResult<LinearParameters<T>>
+where
+    T: RealField + Copy,
+{
+    let Some(coefficient_estimates) = coefficients else {
+        return Err(SLearningError::UntrainedModel);
+    };
+    let offset = if fit_intercept { 1 } else { 0 };
+    Ok(LinearParameters {
+        intercept: fit_intercept.then(|| coefficient_estimates[0]),
+        coefficients: coefficient_estimates.iter().skip(offset).copied().collect(),
+    })
+}
+
+fn explain_linear_regressor<T>(
+    input: RowView<T>,
+    coefficients: &Option<DVector<T>>,
+    fit_intercept: bool,
+) -> SLearningResult<Explanation<T>>
+where
+    T: RealField + Copy,
+{
+    let Some(coefficient_estimates) = coefficients else {
+        return Err(SLearningError::UntrainedModel);
+    };
+    let offset = if fit_intercept { 1 } else { 0 };
+    check_num_vars(coefficient_estimates.len(), input.len() + offset)?;
+    let slopes = coefficient_estimates.rows(offset, input.len());
+    Ok(Explanation {
+        intercept: if fit_intercept {
+            coefficient_estimates[0]
+        } else {
+            T::zero()
+        },
+        contributions: input.transpose().component_mul(&slopes),
+    })
+}
+
+/// Simple linear regression using Ordinary Least Squares (OLS)
+///
+/// Simple linear regression uses linear coefficients to model a single output variable as a
+/// function of one or more input variables.
+#[derive(Debug)]
+pub struct OlsRegressor<T>
+where
+    T: RealField,
+{
+    /// The estimated coefficients from the fitted data.
+    pub coefficients: Option<DVector<T>>,
+    /// The maximum likelihood estimate of the variance of the errors, assuming they are
+    /// independent and normally distributed.
+    pub noise_variance: Option<T>,
+    /// Whether an intercept term should be included in the model.
+    fit_intercept: bool,
+    /// Optional bounds on the coefficients of the input variables.
+    bounds: Option<CoefficientBounds<T>>,
+    /// The number of observations in the training data.
+    num_train_obs: usize,
+    /// What to do with missing values.
+    missing_values: MissingValueHandler<T>,
+    /// What to do with constant input variables.
+    constant_columns: ConstantColumns,
+    /// Where to send warnings from training.
+    diagnostics: Diagnostics,
+}
+
+impl<T: RealField> OlsRegressor<T> {
+    pub fn new(fit_intercept: bool) -> Self {
+        Self {
+            coefficients: None,
+            noise_variance: None,
+            fit_intercept,
+            bounds: None,
+            num_train_obs: 0,
+            missing_values: MissingValueHandler::new(MissingValues::Error),
+            constant_columns: ConstantColumns::Error,
+            diagnostics: Diagnostics::default(),
+        }
+    }
+
+    /// Constrain the coefficients of the input variables to lie within `bounds`.
+    pub fn with_bounds(self, bounds: CoefficientBounds<T>) -> Self {
+        Self {
+            bounds: Some(bounds),
+            ..self
+        }
+    }
+
+    /// Set what to do with missing (NaN) values when training and predicting.
+    pub fn with_missing_values(self, missing_values: MissingValues<T>) -> Self {
+        Self {
+            missing_values: MissingValueHandler::new(missing_values),
+            ..self
+        }
+    }
+
+    /// Set what to do with constant input variables when training.
+    pub fn with_constant_columns(self, constant_columns: ConstantColumns) -> Self {
+        Self {
+            constant_columns,
+            ..self
+        }
+    }
+
+    /// Send warnings from training, e.g. that the inputs are ill-conditioned, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+}
+
+impl<T> Default for OlsRegressor<T>
+where
+    T: RealField,
+{
+    fn default() -> Self {
+        Self {
+            coefficients: None,
+            noise_variance: None,
+            fit_intercept: true,
+            bounds: None,
+            num_train_obs: 0,
+            missing_values: MissingValueHandler::new(MissingValues::Error),
+            constant_columns: ConstantColumns::Error,
+            diagnostics: Diagnostics::default(),
+        }
+    }
+}
+
+impl<T> OlsRegressor<T>
+where
+    T: RealField + Copy,
+{
+    /// The contribution of each input variable to the prediction for a single observation.
+    pub fn explain(&self, input: RowView<T>) -> SLearningResult<Explanation<T>> {
+        explain_linear_regressor(input, &self.coefficients, self.fit_intercept)
+    }
+
+    /// Write the predictions into `predictions`, which must have one element per observation. This
+    /// avoids allocating when predicting repeatedly, e.g. one observation at a time.
+    pub fn predict_into(
+        &self,
+        inputs: &DMatrix<T>,
+        predictions: &mut DVector<T>,
+    ) -> SLearningResult<()> {
+        let inputs = self.missing_values.prepare_predict(inputs)?;
+        predict_linear_regressor_into(&inputs, &self.coefficients, self.fit_intercept, predictions)
+    }
+
+    /// The fitted intercept and coefficients as plain data.
+    pub fn parameters(&self) -> SLearningResult<LinearParameters<T>> {
+        linear_parameters(&self.coefficients, self.fit_intercept)
+    }
+
+    /// Use these parameters instead of training, e.g. to restore a model from
+    /// [`parameters`](Self::parameters). Whether the model has an intercept follows the
+    /// parameters. The noise variance is unknown, so the likelihood and information criteria are
+    /// unavailable until the model is trained.
+    pub fn with_parameters(self, parameters: LinearParameters<T>) -> Self {
+        let (coefficients, fit_intercept) = parameters.into_coefficients();
+        Self {
+            coefficients: Some(coefficients),
+            noise_variance: None,
+            fit_intercept,
+            num_train_obs: 0,
+            ..self
+        }
+    }
+
+    /// The log-likelihood of the data under the fitted model, with independent normally
+    /// distributed errors with variance [`noise_variance`](Self::noise_variance).
+    pub fn log_likelihood(&self, inputs: &DMatrix<T>, outputs: &DVector<T>) -> SLearningResult<T> {
+        let noise_variance = self.noise_variance.ok_or(SLearningError::UntrainedModel)?;
+        let predictions = self.predict(inputs)?;
+        check_consistent_length(inputs, outputs)?;
+        let num_obs: T = nalgebra::convert(outputs.len() as f64);
+        let two_pi: T = nalgebra::convert(2.0 * std::f64::consts::PI);
+        let half: T = nalgebra::convert(0.5);
+        let residual_sum_of_squares = (outputs - predictions).norm_squared();
+        Ok(-half
+            * (num_obs * (two_pi * noise_variance).ln() + residual_sum_of_squares / noise_variance))
+    }
+
+    /// The maximised log-likelihood of the training data, and the number of estimated parameters
+    /// (the coefficients and the noise variance).
+    fn training_fit(&self) -> SLearningResult<(T, T)> {
+        match (&self.coefficients, self.noise_variance) {
+            (Some(coefficients), Some(noise_variance)) => {
+                let num_obs: T = nalgebra::convert(self.num_train_obs as f64);
+                let two_pi: T = nalgebra::convert(2.0 * std::f64::consts::PI);
+                let half: T = nalgebra::convert(0.5);
+                let log_likelihood = -half * num_obs * ((two_pi * noise_variance).ln() + T::one());
+                let num_params = nalgebra::convert(coefficients.len() as f64 + 1.0);
+                Ok((log_likelihood, num_params))
+            }
+            _ => Err(SLearningError::UntrainedModel),
+        }
+    }
+
+    /// Akaike's information criterion of the fitted model, `2k - 2 log L`, where `k` is the number
+    /// of estimated parameters and `L` is the maximised likelihood of the training data. Lower is
+    /// better.
+    pub fn aic(&self) -> SLearningResult<T> {
+        let (log_likelihood, num_params) = self.training_fit()?;
+        let two: T = nalgebra::convert(2.0);
+        Ok(two * (num_params - log_likelihood))
+    }
+
+    /// The Bayesian information criterion of the fitted model, `k ln(n) - 2 log L`, where `n` is
+    /// the number of training observations. This penalises extra parameters more than the AIC
+    /// unless there are very few observations. Lower is better.
+    pub fn bic(&self) -> SLearningResult<T> {
+        let (log_likelihood, num_params) = self.training_fit()?;
+        let num_obs: T = nalgebra::convert(self.num_train_obs as f64);
+        let two: T = nalgebra::convert(2.0);
+        Ok(num_params * num_obs.ln() - two * log_likelihood)
+    }
+
+    /// Train on the sufficient statistics of the data instead of the data itself, e.g. merged from
+    /// several shards. The normal equations must have an intercept exactly when the model does.
+    pub fn train_normal_equations(
+        &mut self,
+        normal_equations: &NormalEquations<T>,
+    ) -> SLearningResult<()> {
+        let coefficients =
+            normal_equations.solve(self.fit_intercept, &T::zero(), false, self.bounds.as_ref())?;
+        let num_obs: T = nalgebra::convert(normal_equations.count() as f64);
+        self.noise_variance =
+            Some(normal_equations.residual_sum_of_squares(&coefficients) / num_obs);
+        self.num_train_obs = normal_equations.count();
+        self.coefficients = Some(coefficients);
+        Ok(())
+    }
+}
+
+impl<T> SupervisedModel<T> for OlsRegressor<T>
+where
+    T: RealField + Copy,
+{
+    fn train(&mut self, inputs: DMatrix<T>, outputs: DVector<T>) -> SLearningResult<()> {
+        let (inputs, outputs) = self.missing_values.prepare_train(inputs, outputs)?;
+        warn_low_variance(&inputs, &self.diagnostics);
+        self.coefficients = Some(train_linear_regressor(
+            &inputs,
+            &outputs,
+            self.fit_intercept,
+            &DVector::zeros(inputs.ncols()),
+            self.bounds.as_ref(),
+            self.constant_columns,
+            &self.diagnostics,
+        )?);
+        let residuals = &outputs - self.predict(&inputs)?;
+        let num_obs: T = nalgebra::convert(outputs.len() as f64);
+        self.noise_variance = Some(residuals.norm_squared() / num_obs);
+        self.num_train_obs = outputs.len();
+        Ok(())
+    }
+
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let inputs = self.missing_values.prepare_predict(inputs)?;
+        predict_linear_regressor(&inputs, &self.coefficients, self.fit_intercept)
+    }
+}
+
+/// The ridge coefficients for each column of `outputs` (one column of coefficients per target,
+/// with the intercept first if any), each with its own penalty.
+///
+/// All the targets share one eigendecomposition of the (centred, scaled) normal matrix
+/// `Z^T Z = V diag(d) V^T`, after which each target only needs `V diag(1 / (d + penalty)) V^T`.
+fn train_multi_output_ridge<T>(
+    inputs: &DMatrix<T>,
+    outputs: &DMatrix<T>,
+    fit_intercept: bool,
+    penalties: &DVector<T>,
+    standardize: bool,
+) -> SLearningResult<DMatrix<T>>
+where
+    T: RealField + Copy,
+{
+    check_2d_nonempty(inputs)?;
+    if inputs.nrows() != outputs.nrows() {
+        let error_msg = format!(
+            "Input has {} observation(s), but output has {} observation(s). These must be equal.",
+            inputs.nrows(),
+            outputs.nrows()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    if penalties.len() != outputs.ncols() {
+        let error_msg = format!(
+            "There are {} target penalties, but the output has {} targets. These must be equal.",
+            penalties.len(),
+            outputs.ncols()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    validate_not_missing(inputs)?;
+    for (target, column) in outputs.column_iter().enumerate() {
+        if let Some(obs) = column.iter().position(is_missing) {
+            let error_msg = format!(
+                "Output has a missing (NaN) value for observation {} and target {}.",
+                obs, target
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+    }
+
+    // With an intercept, centring the inputs and outputs leaves the intercept unpenalised.
+    let num_obs: T = nalgebra::convert(inputs.nrows() as f64);
+    let (input_means, output_means) = match fit_intercept {
+        true => (
+            inputs.row_sum().transpose() / num_obs,
+            outputs.row_sum().transpose() / num_obs,
+        ),
+        false => (
+            DVector::zeros(inputs.ncols()),
+            DVector::zeros(outputs.ncols()),
+        ),
+    };
+    let scales = match standardize {
+        true => standardization_scales(inputs, fit_intercept).map(|scale| {
+            if scale > T::zero() {
+                scale
+            } else {
+                T::one()
+            }
+        }),
+        false => DVector::from_element(inputs.ncols(), T::one()),
+    };
+    let scaled = DMatrix::from_fn(inputs.nrows(), inputs.ncols(), |i, j| {
+        (inputs[(i, j)] - input_means[j]) / scales[j]
+    });
+    let centered_outputs = DMatrix::from_fn(outputs.nrows(), outputs.ncols(), |i, t| {
+        outputs[(i, t)] - output_means[t]
+    });
+
+    let eigen = (scaled.transpose() * &scaled).symmetric_eigen();
+    let rotated_moments = eigen.eigenvectors.transpose() * scaled.transpose() * centered_outputs;
+    let tol =
+        T::default_epsilon() * eigen.eigenvalues.amax() * nalgebra::convert(inputs.ncols() as f64);
+    let offset = if fit_intercept { 1 } else { 0 };
+    let mut coefficients = DMatrix::zeros(inputs.ncols() + offset, outputs.ncols());
+    for target in 0..outputs.ncols() {
+        let mut shrunk = rotated_moments.column(target).into_owned();
+        for (index, eigenvalue) in eigen.eigenvalues.iter().enumerate() {
+            let denominator = eigenvalue.max(T::zero()) + penalties[target];
+            if denominator <= tol {
+                let error_msg = format!(
+                    "The inputs are linearly dependent, so the penalty for target {} must be greater than zero.",
+                    target
+                );
+                return Err(SLearningError::InvalidData(error_msg));
+            }
+            shrunk[index] /= denominator;
+        }
+        let slopes = (&eigen.eigenvectors * shrunk).component_div(&scales);
+        if fit_intercept {
+            coefficients[(0, target)] = output_means[target] - input_means.dot(&slopes);
+        }
+        coefficients
+            .view_mut((offset, target), (inputs.ncols(), 1))
+            .copy_from(&slopes);
+    }
+    Ok(coefficients)
+}
+
+/// Ridge is Ordinary Least Squares (OLS) with L2 penalty on the number of coefficients.
+///
+/// The penalty is a non-negative real value. A penalty of zero means that ridge regression is
+/// equivalent to simple linear regression.
+#[derive(Debug)]
+pub struct RidgeRegressor<T>
+where
+    T: RealField,
+{
+    pub penalty: T,
+    fit_intercept: bool,
+    pub coefficients: Option<DVector<T>>,
+    /// The coefficients for each target (columns) when trained on multiple outputs, with the
+    /// intercept first if any.
+    pub multi_output_coefficients: Option<DMatrix<T>>,
+    /// The penalty for each target when training on multiple outputs, instead of `penalty`.
+    target_penalties: Option<DVector<T>>,
+    /// The effective degrees of freedom and residual sum of squares of the training data, if
+    /// trained without bounds.
+    training_fit: Option<(T, T)>,
+    /// The number of observations in the training data.
+    num_train_obs: usize,
+    /// Whether to penalise the coefficients of the standardised input variables.
+    standardize: bool,
+    /// Optional bounds on the coefficients of the input variables.
+    bounds: Option<CoefficientBounds<T>>,
+    /// What to do with missing values.
+    missing_values: MissingValueHandler<T>,
+    /// What to do with constant input variables, which only matters without a penalty.
+    constant_columns: ConstantColumns,
+    /// Where to send warnings from training.
+    diagnostics: Diagnostics,
+}
+
+impl<T> RidgeRegressor<T>
+where
+    T: RealField,
+{
+    pub fn new(penalty: T, fit_intercept: bool) -> SLearningResult<Self> {
+        if penalty.is_negative() {
+            return Err(SLearningError::InvalidParameters(
+                "Penalty cannot be less than zero.".to_string(),
+            ));
+        }
+        Ok(Self {
+            penalty,
+            fit_intercept,
+            coefficients: None,
+            multi_output_coefficients: None,
+            target_penalties: None,
+            training_fit: None,
+            num_train_obs: 0,
+            standardize: false,
+            bounds: None,
+            missing_values: MissingValueHandler::new(MissingValues::Error),
+            constant_columns: ConstantColumns::Error,
+            diagnostics: Diagnostics::default(),
+        })
+    }
+
+    /// Use a separate penalty for each target when training on multiple outputs, instead of the
+    /// same penalty for all of them. The number of penalties must match the number of targets,
+    /// which is checked when training.
+    pub fn with_target_penalties(self, target_penalties: DVector<T>) -> SLearningResult<Self> {
+        if target_penalties.is_empty() {
+            return Err(SLearningError::InvalidParameters(
+                "Target penalties must have at least one penalty.".to_string(),
+            ));
+        }
+        for penalty in target_penalties.iter() {
+            check_non_negative(penalty.clone(), "Penalty")?;
+        }
+        Ok(Self {
+            target_penalties: Some(target_penalties),
+            ..self
+        })
+    }
+
+    /// Whether to standardise the input variables before penalising their coefficients, so that
+    /// the penalty does not depend on the units of the variables. The coefficients are still
+    /// those of the original variables.
+    ///
+    /// With an intercept, each variable is divided by its standard deviation (its centring is
+    /// absorbed by the unpenalised intercept), and otherwise by its root mean square. Constant
+    /// variables are not rescaled.
+    pub fn with_standardize(self, standardize: bool) -> Self {
+        Self {
+            standardize,
+            ..self
+        }
+    }
+
+    /// Constrain the coefficients of the input variables to lie within `bounds`.
+    pub fn with_bounds(self, bounds: CoefficientBounds<T>) -> Self {
+        Self {
+            bounds: Some(bounds),
+            ..self
+        }
+    }
+
+    /// Set what to do with missing (NaN) values when training and predicting.
+    pub fn with_missing_values(self, missing_values: MissingValues<T>) -> Self {
+        Self {
+            missing_values: MissingValueHandler::new(missing_values),
+            ..self
+        }
+    }
+
+    /// Set what to do with constant input variables when training.
+    pub fn with_constant_columns(self, constant_columns: ConstantColumns) -> Self {
+        Self {
+            constant_columns,
+            ..self
+        }
+    }
 
-    if num_input_obs != num_output_obs {
-        let error_msg = format!(
-            "Input has {} observation(s), but output has {} observation(s). These must be equal.",
-            num_input_obs, num_output_obs
-        );
-        return Err(SLearningError::InvalidData(error_msg));
+    /// Send warnings from training, e.g. that the inputs are ill-conditioned, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
     }
-    Ok(())
 }
 
-fn get_full_inputs<T: RealField>(inputs: DMatrix<T>, fit_intercept: bool) -> DMatrix<T> {
-    if !fit_intercept {
-        return inputs;
+impl<T> RidgeRegressor<T>
+where
+    T: RealField + Copy,
+{
+    /// The contribution of each input variable to the prediction for a single observation.
+    pub fn explain(&self, input: RowView<T>) -> SLearningResult<Explanation<T>> {
+        explain_linear_regressor(input, &self.coefficients, self.fit_intercept)
+    }
+
+    /// Write the predictions into `predictions`, which must have one element per observation. This
+    /// avoids allocating when predicting repeatedly, e.g. one observation at a time.
+    pub fn predict_into(
+        &self,
+        inputs: &DMatrix<T>,
+        predictions: &mut DVector<T>,
+    ) -> SLearningResult<()> {
+        let inputs = self.missing_values.prepare_predict(inputs)?;
+        predict_linear_regressor_into(&inputs, &self.coefficients, self.fit_intercept, predictions)
+    }
+
+    /// The fitted intercept and coefficients as plain data.
+    pub fn parameters(&self) -> SLearningResult<LinearParameters<T>> {
+        linear_parameters(&self.coefficients, self.fit_intercept)
+    }
+
+    /// Use these parameters instead of training, e.g. to restore a model from
+    /// [`parameters`](Self::parameters). Whether the model has an intercept follows the
+    /// parameters.
+    pub fn with_parameters(self, parameters: LinearParameters<T>) -> Self {
+        let (coefficients, fit_intercept) = parameters.into_coefficients();
+        Self {
+            coefficients: Some(coefficients),
+            multi_output_coefficients: None,
+            fit_intercept,
+            training_fit: None,
+            ..self
+        }
+    }
+
+    /// The effective degrees of freedom of the fitted model, which is the trace of its hat matrix
+    /// `H`, where the fitted values of the training data are `H y`. This counts the intercept (if
+    /// any) as one, and decreases from the number of coefficients towards that as the penalty
+    /// increases.
+    pub fn effective_degrees_of_freedom(&self) -> SLearningResult<T> {
+        Ok(self.training_fit()?.0)
+    }
+
+    /// The generalised cross-validation (GCV) score of the fitted model,
+    /// `n RSS / (n - df)^2`, where `RSS` is the residual sum of squares of the `n` training
+    /// observations and `df` is the [effective degrees of freedom](Self::effective_degrees_of_freedom).
+    /// This approximates the leave-one-out mean squared error, so penalties can be compared
+    /// without refitting. Lower is better.
+    pub fn gcv(&self) -> SLearningResult<T> {
+        let (degrees_of_freedom, residual_sum_of_squares) = self.training_fit()?;
+        let num_obs: T = nalgebra::convert(self.num_train_obs as f64);
+        if degrees_of_freedom >= num_obs {
+            let error_msg = format!(
+                "The effective degrees of freedom ({}) must be less than the number of training observations ({}).",
+                degrees_of_freedom, self.num_train_obs
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        let residual_dof = num_obs - degrees_of_freedom;
+        Ok(num_obs * residual_sum_of_squares / (residual_dof * residual_dof))
+    }
+
+    fn training_fit(&self) -> SLearningResult<(T, T)> {
+        check_fitted(&self.coefficients)?;
+        self.training_fit.ok_or_else(|| {
+            SLearningError::InvalidData(
+                "The effective degrees of freedom are only known for a model trained without bounds."
+                    .to_string(),
+            )
+        })
+    }
+
+    /// Train on a matrix of outputs (one column per target), with the penalty for each target
+    /// given by [`with_target_penalties`](Self::with_target_penalties) or else `penalty`. All the
+    /// targets are fitted with one factorisation of the inputs. Missing values are not allowed,
+    /// and bounds are not supported.
+    pub fn train_multi_output(
+        &mut self,
+        inputs: DMatrix<T>,
+        outputs: DMatrix<T>,
+    ) -> SLearningResult<()> {
+        if self.bounds.is_some() {
+            return Err(SLearningError::InvalidParameters(
+                "Bounds are not supported when training on multiple outputs.".to_string(),
+            ));
+        }
+        let penalties = match &self.target_penalties {
+            Some(penalties) => penalties.clone(),
+            None => DVector::from_element(outputs.ncols(), self.penalty),
+        };
+        self.multi_output_coefficients = Some(train_multi_output_ridge(
+            &inputs,
+            &outputs,
+            self.fit_intercept,
+            &penalties,
+            self.standardize,
+        )?);
+        Ok(())
+    }
+
+    /// The predictions for each target (columns) of a model trained on multiple outputs.
+    pub fn predict_multi_output(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let coefficients = check_fitted(&self.multi_output_coefficients)?;
+        let offset = if self.fit_intercept { 1 } else { 0 };
+        check_num_vars(coefficients.nrows() - offset, inputs.ncols())?;
+        validate_not_missing(inputs)?;
+        let mut predictions = inputs * coefficients.rows(offset, inputs.ncols());
+        if self.fit_intercept {
+            for mut row in predictions.row_iter_mut() {
+                row += coefficients.row(0);
+            }
+        }
+        Ok(predictions)
+    }
+
+    /// Train on the sufficient statistics of the data instead of the data itself, e.g. merged from
+    /// several shards. The normal equations must have an intercept exactly when the model does.
+    pub fn train_normal_equations(
+        &mut self,
+        normal_equations: &NormalEquations<T>,
+    ) -> SLearningResult<()> {
+        let coefficients = normal_equations.solve(
+            self.fit_intercept,
+            &self.penalty,
+            self.standardize,
+            self.bounds.as_ref(),
+        )?;
+        self.training_fit = self.bounds.is_none().then(|| {
+            let penalties = normal_equations.variable_penalties(self.penalty, self.standardize);
+            (
+                hat_matrix_trace(
+                    &normal_equations.normal_matrix,
+                    &penalties,
+                    self.fit_intercept,
+                ),
+                normal_equations.residual_sum_of_squares(&coefficients),
+            )
+        });
+        self.num_train_obs = normal_equations.count();
+        self.coefficients = Some(coefficients);
+        Ok(())
     }
-    inputs.insert_column(0, T::one())
 }
 
-fn train_linear_regressor<T>(
-    inputs: &DMatrix<T>,
-    outputs: &DVector<T>,
-    fit_intercept: bool,
-    penalty: &T,
-) -> SLearningResult<DVector<T>>
+impl<T> SupervisedModel<T> for RidgeRegressor<T>
 where
     T: RealField + Copy,
 {
-    validate_train_dimensions(inputs, outputs)?;
-    // TODO: Is there a way to avoid this clone? At least for when `fit_intercept` is false.
-    let full_inputs = &get_full_inputs(inputs.clone(), fit_intercept);
-
-    let mut normal_matrix_inverse = full_inputs.transpose() * full_inputs;
-    if !penalty.is_zero() {
-        // The intercept should not be penalised, so don't add to first diagonal if `fit_intercept` is true.
-        let start = if fit_intercept { 1 } else { 0 };
-        let end = normal_matrix_inverse.shape().0;
-        for index in start..end {
-            normal_matrix_inverse[(index, index)] += *penalty;
-        }
+    fn train(&mut self, inputs: DMatrix<T>, outputs: DVector<T>) -> SLearningResult<()> {
+        let (inputs, outputs) = self.missing_values.prepare_train(inputs, outputs)?;
+        warn_low_variance(&inputs, &self.diagnostics);
+        let scales = self
+            .standardize
+            .then(|| standardization_scales(&inputs, self.fit_intercept));
+        let penalties = variable_penalties(self.penalty, scales.as_ref(), inputs.ncols());
+        self.coefficients = Some(train_linear_regressor(
+            &inputs,
+            &outputs,
+            self.fit_intercept,
+            &penalties,
+            self.bounds.as_ref(),
+            self.constant_columns,
+            &self.diagnostics,
+        )?);
+        self.training_fit = match self.bounds {
+            Some(_) => None,
+            None => {
+                let residuals = &outputs - self.predict(&inputs)?;
+                let full_inputs = get_full_inputs(inputs, self.fit_intercept);
+                let normal_matrix = full_inputs.transpose() * &full_inputs;
+                Some((
+                    hat_matrix_trace(&normal_matrix, &penalties, self.fit_intercept),
+                    residuals.norm_squared(),
+                ))
+            }
+        };
+        self.num_train_obs = outputs.len();
+        Ok(())
     }
-    if !normal_matrix_inverse.try_inverse_mut() {
-        return Err(SLearningError::InvalidData(
-            "The normal matrix is not invertible.".to_string(),
-        ));
+
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let inputs = self.missing_values.prepare_predict(inputs)?;
+        predict_linear_regressor(&inputs, &self.coefficients, self.fit_intercept)
     }
-    let beta_hat = normal_matrix_inverse * full_inputs.transpose() * outputs;
-    Ok(beta_hat)
 }
 
-fn predict_linear_regressor<T>(
-    inputs: &DMatrix<T>,
-    coefficients: &Option<DVector<T>>,
-    fit_intercept: bool,
-) -> SLearningResult<DVector<T>>
+/// Solve `min ||a x - b||` subject to `x >= 0`, using the active set method of Lawson and Hanson.
+fn non_negative_least_squares<T>(a: &DMatrix<T>, b: &DVector<T>) -> SLearningResult<DVector<T>>
 where
-    T: RealField,
+    T: RealField + Copy,
 {
-    match &coefficients {
-        Some(coefficient_estimates) => {
-            // TODO: Same question as above about clone.
-            let full_inputs = &get_full_inputs(inputs.clone(), fit_intercept);
-            if full_inputs.ncols() != coefficient_estimates.len() {
-                let error_msg = format!(
-                    "This model was trained with {} variables, but this input has {} variables. These must be equal.",
-                    coefficient_estimates.len(),
-                    full_inputs.ncols()
-                );
-                return Err(SLearningError::InvalidData(error_msg));
+    let num_vars = a.ncols();
+    let max_iter = 3 * num_vars.max(1);
+    let column_sums = a.row_iter().fold(DVector::zeros(num_vars), |acc, row| {
+        acc + row.transpose().abs()
+    });
+    let tol = T::default_epsilon()
+        * nalgebra::convert(10.0 * a.nrows().max(num_vars) as f64)
+        * column_sums.max();
+
+    let mut coefficients = DVector::zeros(num_vars);
+    let mut passive = vec![false; num_vars];
+    let mut gradient = a.transpose() * b;
+    for _ in 0..max_iter {
+        // Move the variable that would most reduce the residual into the passive set.
+        let candidate = (0..num_vars)
+            .filter(|&j| !passive[j] && gradient[j] > tol)
+            .max_by(|&i, &j| gradient[i].partial_cmp(&gradient[j]).unwrap());
+        let Some(candidate) = candidate else {
+            return Ok(coefficients);
+        };
+        passive[candidate] = true;
+
+        loop {
+            let indices: Vec<usize> = (0..num_vars).filter(|&j| passive[j]).collect();
+            let subset = a.select_columns(indices.iter());
+            let subset_solution = subset
+                .svd(true, true)
+                .solve(b, T::default_epsilon())
+                .map_err(|msg| SLearningError::Unknown(msg.to_string()))?;
+            let mut solution = DVector::zeros(num_vars);
+            for (&index, &value) in indices.iter().zip(subset_solution.iter()) {
+                solution[index] = value;
+            }
+            if indices.iter().all(|&j| solution[j] > T::zero()) {
+                coefficients = solution;
+                break;
+            }
+            // Step towards the solution until the first passive variable hits zero, then move
+            // every variable at zero back into the active set.
+            let step = indices
+                .iter()
+                .filter(|&&j| solution[j] <= T::zero())
+                .map(|&j| coefficients[j] / (coefficients[j] - solution[j]))
+                .fold(T::one(), |acc, ratio| acc.min(ratio));
+            coefficients += (solution - &coefficients) * step;
+            for &j in &indices {
+                if coefficients[j] <= tol {
+                    coefficients[j] = T::zero();
+                    passive[j] = false;
+                }
             }
-            Ok(full_inputs * coefficient_estimates)
         }
-        None => Err(SLearningError::UntrainedModel),
+        gradient = a.transpose() * (b - a * &coefficients);
     }
+    Err(SLearningError::InvalidData(
+        "The non-negative least squares solver did not converge.".to_string(),
+    ))
 }
 
-/// Simple linear regression using Ordinary Least Squares (OLS)
+/// Linear regression with the coefficients constrained to be non-negative (NNLS).
 ///
-/// Simple linear regression uses linear coefficients to model a single output variable as a
-/// function of one or more input variables.
+/// This is useful when the coefficients are known to be non-negative, e.g. when modelling an
+/// output as a mixture of the inputs. The intercept, if fitted, is not constrained.
 #[derive(Debug)]
-pub struct OlsRegressor<T>
+pub struct NnlsRegressor<T>
 where
     T: RealField,
 {
@@ -103,18 +1622,29 @@ where
     pub coefficients: Option<DVector<T>>,
     /// Whether an intercept term should be included in the model.
     fit_intercept: bool,
+    /// What to do with missing values.
+    missing_values: MissingValueHandler<T>,
 }
 
-impl<T: RealField> OlsRegressor<T> {
+impl<T: RealField> NnlsRegressor<T> {
     pub fn new(fit_intercept: bool) -> Self {
         Self {
             coefficients: None,
             fit_intercept,
+            missing_values: MissingValueHandler::new(MissingValues::Error),
+        }
+    }
+
+    /// Set what to do with missing (NaN) values when training and predicting.
+    pub fn with_missing_values(self, missing_values: MissingValues<T>) -> Self {
+        Self {
+            missing_values: MissingValueHandler::new(missing_values),
+            ..self
         }
     }
 }
 
-impl<T> Default for OlsRegressor<T>
+impl<T> Default for NnlsRegressor<T>
 where
     T: RealField,
 {
@@ -122,71 +1652,237 @@ where
         Self {
             coefficients: None,
             fit_intercept: true,
+            missing_values: MissingValueHandler::new(MissingValues::Error),
         }
     }
 }
 
-impl<T> SupervisedModel<T> for OlsRegressor<T>
+impl<T> NnlsRegressor<T>
+where
+    T: RealField + Copy,
+{
+    /// The contribution of each input variable to the prediction for a single observation.
+    pub fn explain(&self, input: RowView<T>) -> SLearningResult<Explanation<T>> {
+        explain_linear_regressor(input, &self.coefficients, self.fit_intercept)
+    }
+
+    /// Write the predictions into `predictions`, which must have one element per observation. This
+    /// avoids allocating when predicting repeatedly, e.g. one observation at a time.
+    pub fn predict_into(
+        &self,
+        inputs: &DMatrix<T>,
+        predictions: &mut DVector<T>,
+    ) -> SLearningResult<()> {
+        let inputs = self.missing_values.prepare_predict(inputs)?;
+        predict_linear_regressor_into(&inputs, &self.coefficients, self.fit_intercept, predictions)
+    }
+
+    /// The fitted intercept and coefficients as plain data.
+    pub fn parameters(&self) -> SLearningResult<LinearParameters<T>> {
+        linear_parameters(&self.coefficients, self.fit_intercept)
+    }
+
+    /// Use these parameters instead of training, e.g. to restore a model from
+    /// [`parameters`](Self::parameters). Whether the model has an intercept follows the
+    /// parameters.
+    pub fn with_parameters(self, parameters: LinearParameters<T>) -> Self {
+        let (coefficients, fit_intercept) = parameters.into_coefficients();
+        Self {
+            coefficients: Some(coefficients),
+            fit_intercept,
+            ..self
+        }
+    }
+}
+
+impl<T> SupervisedModel<T> for NnlsRegressor<T>
 where
     T: RealField + Copy,
 {
     fn train(&mut self, inputs: DMatrix<T>, outputs: DVector<T>) -> SLearningResult<()> {
-        self.coefficients = Some(train_linear_regressor(
-            &inputs,
-            &outputs,
-            self.fit_intercept,
-            &nalgebra::zero(),
-        )?);
+        let (inputs, outputs) = self.missing_values.prepare_train(inputs, outputs)?;
+        validate_train_dimensions(&inputs, &outputs)?;
+        if !self.fit_intercept {
+            self.coefficients = Some(non_negative_least_squares(&inputs, &outputs)?);
+            return Ok(());
+        }
+        // Centering removes the (unconstrained) intercept from the problem, which can then be
+        // recovered from the means.
+        let input_means = inputs.row_mean();
+        let output_mean = outputs.mean();
+        let centered_inputs = DMatrix::from_fn(inputs.nrows(), inputs.ncols(), |i, j| {
+            inputs[(i, j)] - input_means[j]
+        });
+        let centered_outputs = outputs.add_scalar(-output_mean);
+        let slopes = non_negative_least_squares(&centered_inputs, &centered_outputs)?;
+        let intercept = output_mean - input_means.dot(&slopes.transpose());
+        self.coefficients = Some(slopes.insert_row(0, intercept));
         Ok(())
     }
 
     fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
-        predict_linear_regressor(inputs, &self.coefficients, self.fit_intercept)
+        let inputs = self.missing_values.prepare_predict(inputs)?;
+        predict_linear_regressor(&inputs, &self.coefficients, self.fit_intercept)
     }
 }
 
-/// Ridge is Ordinary Least Squares (OLS) with L2 penalty on the number of coefficients.
+/// The structure of the errors of a [`GlsRegressor`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorCovariance<T>
+where
+    T: RealField,
+{
+    /// A known covariance matrix of the errors, with one row and column for each training
+    /// observation. This only needs to be known up to a constant multiple.
+    Known(DMatrix<T>),
+    /// Errors following a first-order autoregressive process with the given autocorrelation, for
+    /// observations that are equally spaced in time.
+    Ar1(T),
+}
+
+impl<T> ErrorCovariance<T>
+where
+    T: RealField + Copy,
+{
+    fn validate(&self) -> SLearningResult<()> {
+        if let Self::Ar1(rho) = self {
+            if *rho <= -T::one() || *rho >= T::one() {
+                return Err(SLearningError::InvalidParameters(
+                    "The autocorrelation must be strictly between minus one and one.".to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Transform the rows of `matrix` so that errors with this covariance become uncorrelated with
+    /// equal variance.
+    fn whiten(&self, matrix: DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let num_obs = matrix.nrows();
+        match self {
+            Self::Known(covariance) => {
+                if covariance.nrows() != num_obs || covariance.ncols() != num_obs {
+                    let error_msg = format!(
+                        "The error covariance matrix has shape {:?}, but there are {} observation(s). The covariance matrix must be square with a row for each observation.",
+                        covariance.shape(),
+                        num_obs
+                    );
+                    return Err(SLearningError::InvalidData(error_msg));
+                }
+                let cholesky = covariance.clone().cholesky().ok_or_else(|| {
+                    SLearningError::InvalidData(
+                        "The error covariance matrix is not positive definite.".to_string(),
+                    )
+                })?;
+                let mut whitened = matrix;
+                cholesky.l_dirty().solve_lower_triangular_mut(&mut whitened);
+                Ok(whitened)
+            }
+            Self::Ar1(rho) => {
+                // The Prais-Winsten transformation, which keeps the first observation.
+                let first_scale = (T::one() - *rho * *rho).sqrt();
+                Ok(DMatrix::from_fn(num_obs, matrix.ncols(), |i, j| {
+                    if i == 0 {
+                        matrix[(0, j)] * first_scale
+                    } else {
+                        matrix[(i, j)] - matrix[(i - 1, j)] * *rho
+                    }
+                }))
+            }
+        }
+    }
+}
+
+/// Generalised Least Squares (GLS) linear regression, for errors that are correlated or have
+/// unequal variances.
 ///
-/// The penalty is a non-negative real value. A penalty of zero means that ridge regression is
-/// equivalent to simple linear regression.
+/// The inputs and outputs are whitened using the error covariance, then fitted by ordinary least
+/// squares. Predictions are made on the original scale.
 #[derive(Debug)]
-pub struct RidgeRegressor<T>
+pub struct GlsRegressor<T>
 where
     T: RealField,
 {
-    pub penalty: T,
-    fit_intercept: bool,
+    /// The estimated coefficients from the fitted data.
     pub coefficients: Option<DVector<T>>,
+    /// Whether an intercept term should be included in the model.
+    fit_intercept: bool,
+    error_covariance: ErrorCovariance<T>,
 }
 
-impl<T> RidgeRegressor<T>
+impl<T> GlsRegressor<T>
 where
-    T: RealField,
+    T: RealField + Copy,
 {
-    pub fn new(penalty: T, fit_intercept: bool) -> SLearningResult<Self> {
-        if penalty.is_negative() {
-            return Err(SLearningError::InvalidParameters(
-                "Penalty cannot be less than zero.".to_string(),
-            ));
-        }
+    pub fn new(error_covariance: ErrorCovariance<T>, fit_intercept: bool) -> SLearningResult<Self> {
+        error_covariance.validate()?;
         Ok(Self {
-            penalty,
-            fit_intercept,
             coefficients: None,
+            fit_intercept,
+            error_covariance,
         })
     }
+
+    /// The contribution of each input variable to the prediction for a single observation.
+    pub fn explain(&self, input: RowView<T>) -> SLearningResult<Explanation<T>> {
+        explain_linear_regressor(input, &self.coefficients, self.fit_intercept)
+    }
+
+    /// Write the predictions into `predictions`, which must have one element per observation. This
+    /// avoids allocating when predicting repeatedly, e.g. one observation at a time.
+    pub fn predict_into(
+        &self,
+        inputs: &DMatrix<T>,
+        predictions: &mut DVector<T>,
+    ) -> SLearningResult<()> {
+        predict_linear_regressor_into(inputs, &self.coefficients, self.fit_intercept, predictions)
+    }
+
+    /// The fitted intercept and coefficients as plain data.
+    pub fn parameters(&self) -> SLearningResult<LinearParameters<T>> {
+        linear_parameters(&self.coefficients, self.fit_intercept)
+    }
+
+    /// Use these parameters instead of training, e.g. to restore a model from
+    /// [`parameters`](Self::parameters). Whether the model has an intercept follows the
+    /// parameters.
+    pub fn with_parameters(self, parameters: LinearParameters<T>) -> Self {
+        let (coefficients, fit_intercept) = parameters.into_coefficients();
+        Self {
+            coefficients: Some(coefficients),
+            fit_intercept,
+            ..self
+        }
+    }
 }
 
-impl<T> SupervisedModel<T> for RidgeRegressor<T>
+impl<T> SupervisedModel<T> for GlsRegressor<T>
 where
     T: RealField + Copy,
 {
     fn train(&mut self, inputs: DMatrix<T>, outputs: DVector<T>) -> SLearningResult<()> {
+        validate_train_dimensions(&inputs, &outputs)?;
+        // The intercept column is whitened along with the inputs, so it is added here.
+        let full_inputs = self
+            .error_covariance
+            .whiten(get_full_inputs(inputs, self.fit_intercept))?;
+        let whitened_outputs = self
+            .error_covariance
+            .whiten(DMatrix::from_column_slice(
+                outputs.len(),
+                1,
+                outputs.as_slice(),
+            ))?
+            .column(0)
+            .into_owned();
         self.coefficients = Some(train_linear_regressor(
-            &inputs,
-            &outputs,
-            self.fit_intercept,
-            &self.penalty,
+            &full_inputs,
+            &whitened_outputs,
+            false,
+            &DVector::zeros(full_inputs.ncols()),
+            None,
+            ConstantColumns::Error,
+            &Diagnostics::default(),
         )?);
         Ok(())
     }
@@ -195,3 +1891,346 @@ where
         predict_linear_regressor(inputs, &self.coefficients, self.fit_intercept)
     }
 }
+
+/// The training data of one group of a [`MixedLmRegressor`].
+struct MixedLmGroup<T>
+where
+    T: RealField,
+{
+    /// The group's number.
+    index: usize,
+    /// The fixed effects design, including the intercept column if there is one.
+    fixed: DMatrix<T>,
+    /// The random effects design.
+    random: DMatrix<T>,
+    outputs: DVector<T>,
+}
+
+/// The quantities from an E-step of [`MixedLmRegressor`], given the current variance parameters.
+struct MixedLmExpectation<T>
+where
+    T: RealField,
+{
+    coefficients: DVector<T>,
+    random_effects: DMatrix<T>,
+    log_likelihood: T,
+    /// The sum over groups of the conditional second moments of the random effects.
+    random_effects_moment: DMatrix<T>,
+    /// The sum over observations of the conditional second moments of the errors.
+    residual_moment: T,
+}
+
+/// The log-determinant of a matrix from its Cholesky factor.
+fn cholesky_log_determinant<T: RealField + Copy>(lower: &DMatrix<T>) -> T {
+    lower
+        .diagonal()
+        .iter()
+        .fold(T::zero(), |acc, &d| acc + d.ln())
+        * nalgebra::convert(2.0)
+}
+
+/// Linear mixed-effects model, for observations in groups (e.g. repeated measurements of the same
+/// subject) that are not independent.
+///
+/// Each observation in group `g` is modelled as `y = x^T beta + z^T b_g + e`, where `beta` are the
+/// fixed effects shared by every group, the random effects `b_g` are normally distributed with
+/// covariance `G` independently for each group, and the errors `e` are independent with variance
+/// `sigma^2`. The random effects design `z` is a one for the random intercept, followed by the
+/// input variables that have random slopes.
+///
+/// The variance parameters are estimated by expectation maximisation (EM), either by restricted
+/// maximum likelihood (REML, the default), which accounts for the degrees of freedom used by the
+/// fixed effects, or by maximum likelihood. Given these, the fixed effects are the generalised
+/// least squares estimates and the random effects are the best linear unbiased predictions.
+#[derive(Debug)]
+pub struct MixedLmRegressor<T>
+where
+    T: RealField,
+{
+    /// The estimated fixed effects, with the intercept first if there is one.
+    pub coefficients: Option<DVector<T>>,
+    /// The predicted random effects of each group (rows), with the random intercept first. Groups
+    /// with no training observations have random effects of zero.
+    pub random_effects: Option<DMatrix<T>>,
+    /// The covariance of the random effects, `G`.
+    pub random_effects_covariance: Option<DMatrix<T>>,
+    /// The variance of the errors, `sigma^2`.
+    pub residual_variance: Option<T>,
+    /// The log-likelihood (or restricted log-likelihood, for REML) at the estimates.
+    pub log_likelihood: Option<T>,
+    /// Whether an intercept term should be included in the fixed effects.
+    fit_intercept: bool,
+    /// The input variables with a random slope for each group.
+    random_slopes: Vec<usize>,
+    reml: bool,
+    convergence: ConvergenceConfig<T>,
+    diagnostics: Diagnostics,
+}
+
+impl<T> MixedLmRegressor<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(fit_intercept: bool, random_slopes: Vec<usize>) -> Self {
+        Self {
+            coefficients: None,
+            random_effects: None,
+            random_effects_covariance: None,
+            residual_variance: None,
+            log_likelihood: None,
+            fit_intercept,
+            random_slopes,
+            reml: true,
+            convergence: ConvergenceConfig::default(),
+            diagnostics: Diagnostics::default(),
+        }
+    }
+
+    /// Estimate by maximum likelihood instead of restricted maximum likelihood.
+    pub fn with_reml(self, reml: bool) -> Self {
+        Self { reml, ..self }
+    }
+
+    /// Stop EM once the log-likelihood improves by at most the tolerance.
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+
+    /// Send warnings from training, e.g. that it stopped before converging, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// The random effects design for some observations: a column of ones, then the inputs with
+    /// random slopes.
+    fn random_design(&self, inputs: &DMatrix<T>) -> DMatrix<T> {
+        DMatrix::from_fn(inputs.nrows(), 1 + self.random_slopes.len(), |i, j| {
+            if j == 0 {
+                T::one()
+            } else {
+                inputs[(i, self.random_slopes[j - 1])]
+            }
+        })
+    }
+
+    /// Estimate the fixed and random effects and the moments needed for the M-step, given the
+    /// variance parameters.
+    fn expectation(
+        &self,
+        groups: &[MixedLmGroup<T>],
+        num_groups: usize,
+        covariance: &DMatrix<T>,
+        residual_variance: T,
+    ) -> SLearningResult<MixedLmExpectation<T>> {
+        let num_fixed = groups[0].fixed.ncols();
+        let num_random = covariance.nrows();
+        let log_two_pi: T = nalgebra::convert((2.0 * std::f64::consts::PI).ln());
+        let half: T = nalgebra::convert(0.5);
+
+        // The inverse of the marginal covariance V = Z G Z^T + sigma^2 I of each group.
+        let mut inverses = Vec::with_capacity(groups.len());
+        let mut information = DMatrix::zeros(num_fixed, num_fixed);
+        let mut score = DVector::zeros(num_fixed);
+        let mut log_likelihood = T::zero();
+        for group in groups {
+            let num_obs = group.outputs.len();
+            let marginal = &group.random * covariance * group.random.transpose()
+                + DMatrix::identity(num_obs, num_obs) * residual_variance;
+            let cholesky = marginal.cholesky().ok_or_else(|| {
+                SLearningError::InvalidData(
+                    "The marginal covariance of a group is not positive definite.".to_string(),
+                )
+            })?;
+            log_likelihood -= (nalgebra::convert::<f64, T>(num_obs as f64) * log_two_pi
+                + cholesky_log_determinant(cholesky.l_dirty()))
+                * half;
+            let inverse = cholesky.inverse();
+            information += group.fixed.transpose() * &inverse * &group.fixed;
+            score += group.fixed.transpose() * &inverse * &group.outputs;
+            inverses.push(inverse);
+        }
+        let information_cholesky = information.cholesky().ok_or_else(|| {
+            SLearningError::InvalidData(
+                "The fixed effects are not identifiable from the inputs.".to_string(),
+            )
+        })?;
+        let coefficients = information_cholesky.solve(&score);
+        if self.reml {
+            log_likelihood += (nalgebra::convert::<f64, T>(num_fixed as f64) * log_two_pi
+                - cholesky_log_determinant(information_cholesky.l_dirty()))
+                * half;
+        }
+        let information_inverse = information_cholesky.inverse();
+
+        let mut random_effects = DMatrix::zeros(num_groups, num_random);
+        let mut random_effects_moment = DMatrix::zeros(num_random, num_random);
+        let mut residual_moment = T::zero();
+        for (group, inverse) in groups.iter().zip(&inverses) {
+            let residuals = &group.outputs - &group.fixed * &coefficients;
+            let weighted_residuals = inverse * &residuals;
+            log_likelihood -= residuals.dot(&weighted_residuals) * half;
+            let effects = covariance * group.random.transpose() * &weighted_residuals;
+            let errors = &residuals - &group.random * &effects;
+
+            // This group's block of the REML projection matrix, which is just V^{-1} for ML.
+            let projection = if self.reml {
+                let weighted_fixed = inverse * &group.fixed;
+                inverse - &weighted_fixed * &information_inverse * weighted_fixed.transpose()
+            } else {
+                inverse.clone()
+            };
+            random_effects_moment += &effects * effects.transpose() + covariance
+                - covariance * group.random.transpose() * &projection * &group.random * covariance;
+            residual_moment += errors.norm_squared()
+                + residual_variance * nalgebra::convert(group.outputs.len() as f64)
+                - residual_variance * residual_variance * projection.trace();
+            random_effects.set_row(group.index, &effects.transpose());
+        }
+        Ok(MixedLmExpectation {
+            coefficients,
+            random_effects,
+            log_likelihood,
+            random_effects_moment,
+            residual_moment,
+        })
+    }
+
+    /// `groups` gives the group of each observation, numbered from zero.
+    pub fn train(
+        &mut self,
+        inputs: DMatrix<T>,
+        outputs: DVector<T>,
+        groups: &[usize],
+    ) -> SLearningResult<()> {
+        validate_train_dimensions(&inputs, &outputs)?;
+        if groups.len() != outputs.len() {
+            let error_msg = format!(
+                "Output has {} observation(s), but there are {} group(s). These must be equal.",
+                outputs.len(),
+                groups.len()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        if let Some(&column) = self
+            .random_slopes
+            .iter()
+            .find(|&&column| column >= inputs.ncols())
+        {
+            let error_msg = format!(
+                "Random slope column {} is out of range for an input with {} variables.",
+                column,
+                inputs.ncols()
+            );
+            return Err(SLearningError::InvalidParameters(error_msg));
+        }
+
+        let random_inputs = self.random_design(&inputs);
+        let fixed_inputs = get_full_inputs(inputs, self.fit_intercept);
+        let num_groups = groups.iter().max().expect("There is at least one group.") + 1;
+        let mut members = vec![Vec::new(); num_groups];
+        for (i, &group) in groups.iter().enumerate() {
+            members[group].push(i);
+        }
+        let group_data: Vec<MixedLmGroup<T>> = members
+            .iter()
+            .enumerate()
+            .filter(|(_, rows)| !rows.is_empty())
+            .map(|(index, rows)| MixedLmGroup {
+                index,
+                fixed: fixed_inputs.select_rows(rows),
+                random: random_inputs.select_rows(rows),
+                outputs: outputs.select_rows(rows),
+            })
+            .collect();
+
+        // Start by splitting the variance of the least squares residuals between the random
+        // intercept and the errors.
+        let initial_coefficients = train_linear_regressor(
+            &fixed_inputs,
+            &outputs,
+            false,
+            &DVector::zeros(fixed_inputs.ncols()),
+            None,
+            ConstantColumns::Error,
+            &Diagnostics::default(),
+        )?;
+        let initial_variance = (&outputs - &fixed_inputs * initial_coefficients).norm_squared()
+            / nalgebra::convert(outputs.len() as f64 * 2.0);
+        let num_random = random_inputs.ncols();
+        let mut covariance = DMatrix::identity(num_random, num_random) * initial_variance;
+        let mut residual_variance = initial_variance;
+
+        let num_nonempty: T = nalgebra::convert(group_data.len() as f64);
+        let num_obs: T = nalgebra::convert(outputs.len() as f64);
+        let mut previous_log_likelihood: Option<T> = None;
+        let mut expectation;
+        let mut iteration = 0;
+        loop {
+            expectation =
+                self.expectation(&group_data, num_groups, &covariance, residual_variance)?;
+            iteration += 1;
+            let converged = previous_log_likelihood.is_some_and(|previous| {
+                expectation.log_likelihood - previous <= self.convergence.tol()
+            });
+            if converged {
+                break;
+            }
+            if iteration >= self.convergence.max_iter() {
+                self.diagnostics.warn(Warning::NotConverged {
+                    max_iter: self.convergence.max_iter(),
+                });
+                break;
+            }
+            previous_log_likelihood = Some(expectation.log_likelihood);
+            let mut updated_covariance = &expectation.random_effects_moment / num_nonempty;
+            updated_covariance.fill_lower_triangle_with_upper_triangle();
+            covariance = updated_covariance;
+            residual_variance = expectation.residual_moment / num_obs;
+        }
+
+        self.coefficients = Some(expectation.coefficients);
+        self.random_effects = Some(expectation.random_effects);
+        self.random_effects_covariance = Some(covariance);
+        self.residual_variance = Some(residual_variance);
+        self.log_likelihood = Some(expectation.log_likelihood);
+        Ok(())
+    }
+
+    /// Predictions from the fixed effects only, i.e. for a new group.
+    pub fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        predict_linear_regressor(inputs, &self.coefficients, self.fit_intercept)
+    }
+
+    /// Predictions including the random effects of each observation's group. Groups that were not
+    /// in the training data have random effects of zero.
+    pub fn predict_with_groups(
+        &self,
+        inputs: &DMatrix<T>,
+        groups: &[usize],
+    ) -> SLearningResult<DVector<T>> {
+        let fixed_predictions = self.predict(inputs)?;
+        let random_effects = check_fitted(&self.random_effects)?;
+        if groups.len() != inputs.nrows() {
+            let error_msg = format!(
+                "Input has {} observation(s), but there are {} group(s). These must be equal.",
+                inputs.nrows(),
+                groups.len()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        let random_inputs = self.random_design(inputs);
+        Ok(DVector::from_fn(inputs.nrows(), |i, _| {
+            let random_part = match groups[i] < random_effects.nrows() {
+                true => random_inputs.row(i).dot(&random_effects.row(groups[i])),
+                false => T::zero(),
+            };
+            fixed_predictions[i] + random_part
+        }))
+    }
+}
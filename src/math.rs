@@ -0,0 +1,71 @@
+//! Numerically stable versions of functions that are common in probabilistic models.
+//!
+//! The naive versions of these overflow (or lose all precision) for inputs of large magnitude,
+//! e.g. `exp(1000.0)` is infinite, even though `log(exp(1000.0) + exp(999.0))` is not.
+use nalgebra::{DMatrix, DVector, RealField};
+
+/// The logistic sigmoid function, `1 / (1 + exp(-x))`.
+pub fn sigmoid<T: RealField + Copy>(x: T) -> T {
+    if x >= T::zero() {
+        T::one() / (T::one() + (-x).exp())
+    } else {
+        // Avoid overflow of `exp(-x)` for large negative `x`.
+        let exp_x = x.exp();
+        exp_x / (T::one() + exp_x)
+    }
+}
+
+/// The softplus function, `log(1 + exp(x))`.
+pub fn log1pexp<T: RealField + Copy>(x: T) -> T {
+    if x > T::zero() {
+        x + (-x).exp().ln_1p()
+    } else {
+        x.exp().ln_1p()
+    }
+}
+
+/// `log(sum(exp(x)))` over the values of `x`.
+///
+/// This is negative infinity if `x` is empty.
+pub fn log_sum_exp<T: RealField + Copy>(x: &DVector<T>) -> T {
+    log_sum_exp_slice(x.as_slice())
+}
+
+fn log_sum_exp_slice<T: RealField + Copy>(values: &[T]) -> T {
+    match values.iter().copied().reduce(|a, b| a.max(b)) {
+        None => nalgebra::convert(f64::NEG_INFINITY),
+        // Either every value is negative infinity, or some value is positive infinity.
+        Some(max) if !max.is_finite() => max,
+        Some(max) => {
+            max + values
+                .iter()
+                .fold(T::zero(), |acc, &x| acc + (x - max).exp())
+                .ln()
+        }
+    }
+}
+
+/// The softmax of `x`, `exp(x) / sum(exp(x))`, whose values are positive and sum to one.
+pub fn softmax<T: RealField + Copy>(x: &DVector<T>) -> DVector<T> {
+    let normaliser = log_sum_exp(x);
+    x.map(|value| (value - normaliser).exp())
+}
+
+/// `log(sum(exp(row)))` for each row of `x`.
+pub fn log_sum_exp_rows<T: RealField + Copy>(x: &DMatrix<T>) -> DVector<T> {
+    DVector::from_iterator(
+        x.nrows(),
+        x.row_iter().map(|row| {
+            let values: Vec<T> = row.iter().copied().collect();
+            log_sum_exp_slice(&values)
+        }),
+    )
+}
+
+/// The softmax of each row of `x`, so each row of the output sums to one.
+pub fn softmax_rows<T: RealField + Copy>(x: &DMatrix<T>) -> DMatrix<T> {
+    let normalisers = log_sum_exp_rows(x);
+    DMatrix::from_fn(x.nrows(), x.ncols(), |i, j| {
+        (x[(i, j)] - normalisers[i]).exp()
+    })
+}
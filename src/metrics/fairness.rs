@@ -0,0 +1,130 @@
+//! Metrics for comparing the predictions of a binary classifier between groups of observations,
+//! e.g. defined by a sensitive attribute.
+//!
+//! Each observation has a group id, and the metrics are computed for each group separately. The
+//! gaps between groups are the largest difference of a metric between any two groups, so they are
+//! zero when every group is treated alike.
+use super::{query_members, validate_binary_labels, validate_lengths, BinaryConfusion};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{DVector, RealField};
+
+/// The predictions for one group of observations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GroupMetrics<T> {
+    /// The group id.
+    pub group: usize,
+    pub confusion: BinaryConfusion,
+    pub accuracy: T,
+    /// The proportion of positives in the group that are predicted to be positive, or zero if
+    /// there are no positives.
+    pub true_positive_rate: T,
+    /// The proportion of negatives in the group that are predicted to be positive, or zero if
+    /// there are no negatives.
+    pub false_positive_rate: T,
+    /// The proportion of the group that is predicted to be positive.
+    pub selection_rate: T,
+}
+
+fn validate_groups(num_obs: usize, groups: &[usize]) -> SLearningResult<()> {
+    if groups.len() != num_obs {
+        let error_msg = format!(
+            "There are {} prediction(s), but {} group id(s). These must be equal.",
+            num_obs,
+            groups.len()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(())
+}
+
+/// The range of some values, or zero if there are none.
+fn gap<T: RealField + Copy>(values: impl Iterator<Item = T>) -> T {
+    let (min, max) = values
+        .fold(None, |range: Option<(T, T)>, value| match range {
+            Some((min, max)) => Some((min.min(value), max.max(value))),
+            None => Some((value, value)),
+        })
+        .unwrap_or((T::zero(), T::zero()));
+    max - min
+}
+
+/// The metrics of the predictions for each group, in order of group id.
+pub fn group_metrics<T>(
+    actual: &DVector<T>,
+    predicted: &DVector<T>,
+    groups: &[usize],
+) -> SLearningResult<Vec<GroupMetrics<T>>>
+where
+    T: RealField + Copy,
+{
+    validate_lengths(actual, predicted)?;
+    validate_groups(actual.len(), groups)?;
+    query_members(groups)
+        .into_iter()
+        .map(|members| {
+            let confusion = BinaryConfusion::from_predictions(
+                &actual.select_rows(&members),
+                &predicted.select_rows(&members),
+            )?;
+            let selection_rate = nalgebra::convert(
+                (confusion.true_positives + confusion.false_positives) as f64
+                    / members.len() as f64,
+            );
+            Ok(GroupMetrics {
+                group: groups[members[0]],
+                confusion,
+                accuracy: confusion.accuracy(),
+                true_positive_rate: confusion.recall(),
+                false_positive_rate: confusion.false_positive_rate(),
+                selection_rate,
+            })
+        })
+        .collect()
+}
+
+/// The largest difference between the proportions of two groups that are predicted to be
+/// positive (demographic parity asks for these to be equal).
+pub fn demographic_parity_difference<T>(
+    predicted: &DVector<T>,
+    groups: &[usize],
+) -> SLearningResult<T>
+where
+    T: RealField + Copy,
+{
+    if predicted.is_empty() {
+        return Err(SLearningError::InvalidData(
+            "Cannot compute metrics with zero observations.".to_string(),
+        ));
+    }
+    validate_binary_labels(predicted)?;
+    validate_groups(predicted.len(), groups)?;
+    Ok(gap(query_members(groups).into_iter().map(|members| {
+        let positives = members.iter().filter(|&&i| predicted[i].is_one()).count();
+        nalgebra::convert(positives as f64 / members.len() as f64)
+    })))
+}
+
+/// The larger of the largest differences between the true positive rates and between the false
+/// positive rates of two groups (equalised odds asks for both rates to be equal).
+///
+/// Only the groups with positives are compared for the true positive rate, and only the groups
+/// with negatives for the false positive rate, since the rate is undefined otherwise.
+pub fn equalized_odds_difference<T>(
+    actual: &DVector<T>,
+    predicted: &DVector<T>,
+    groups: &[usize],
+) -> SLearningResult<T>
+where
+    T: RealField + Copy,
+{
+    let metrics = group_metrics(actual, predicted, groups)?;
+    let true_positive_gap = gap(metrics
+        .iter()
+        .filter(|group| group.confusion.true_positives + group.confusion.false_negatives > 0)
+        .map(|group| group.true_positive_rate));
+    let false_positive_gap = gap(metrics
+        .iter()
+        .filter(|group| group.confusion.false_positives + group.confusion.true_negatives > 0)
+        .map(|group| group.false_positive_rate));
+    Ok(true_positive_gap.max(false_positive_gap))
+}
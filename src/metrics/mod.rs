@@ -0,0 +1,607 @@
+//! Metrics for evaluating the predictions of trained models.
+//!
+//! Binary classification labels are represented by zero (negative) and one (positive), in the same
+//! type as the model outputs. Multilabel classification labels are represented by indicator
+//! matrices, with a row for each observation and a column of binary labels for each label.
+//! Ranking metrics take a non-negative relevance and a score for each observation, with a query id
+//! that groups the observations that are ranked together.
+pub mod fairness;
+
+use std::collections::BTreeMap;
+
+use crate::utils::total_cmp;
+use crate::validation::{check_finite_values, check_fraction};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+fn validate_lengths<T: RealField>(actual: &DVector<T>, other: &DVector<T>) -> SLearningResult<()> {
+    if actual.is_empty() {
+        return Err(SLearningError::InvalidData(
+            "Cannot compute metrics with zero observations.".to_string(),
+        ));
+    }
+    if actual.len() != other.len() {
+        let error_msg = format!(
+            "The true labels have {} observation(s), but the predictions have {} observation(s). These must be equal.",
+            actual.len(),
+            other.len()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(())
+}
+
+fn validate_binary_labels<T: RealField>(labels: &DVector<T>) -> SLearningResult<()> {
+    if labels
+        .iter()
+        .any(|label| !label.is_zero() && !label.is_one())
+    {
+        return Err(SLearningError::InvalidData(
+            "Binary labels must be zero or one.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The ratio of two counts, or zero if the denominator is zero.
+fn ratio<T: RealField>(numerator: usize, denominator: usize) -> T {
+    if denominator == 0 {
+        return T::zero();
+    }
+    nalgebra::convert(numerator as f64 / denominator as f64)
+}
+
+/// The counts of correct and incorrect predictions of a binary classifier.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BinaryConfusion {
+    pub true_positives: usize,
+    pub false_positives: usize,
+    pub true_negatives: usize,
+    pub false_negatives: usize,
+}
+
+impl BinaryConfusion {
+    pub fn from_predictions<T: RealField>(
+        actual: &DVector<T>,
+        predicted: &DVector<T>,
+    ) -> SLearningResult<Self> {
+        validate_lengths(actual, predicted)?;
+        validate_binary_labels(actual)?;
+        validate_binary_labels(predicted)?;
+        let mut confusion = Self::default();
+        for (a, p) in actual.iter().zip(predicted.iter()) {
+            match (a.is_one(), p.is_one()) {
+                (true, true) => confusion.true_positives += 1,
+                (false, true) => confusion.false_positives += 1,
+                (false, false) => confusion.true_negatives += 1,
+                (true, false) => confusion.false_negatives += 1,
+            }
+        }
+        Ok(confusion)
+    }
+
+    /// The proportion of positive predictions that are correct, or zero if there are no positive
+    /// predictions.
+    pub fn precision<T: RealField>(&self) -> T {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_positives,
+        )
+    }
+
+    /// The proportion of positives that are predicted to be positive (the true positive rate), or
+    /// zero if there are no positives.
+    pub fn recall<T: RealField>(&self) -> T {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_negatives,
+        )
+    }
+
+    /// The proportion of negatives that are predicted to be positive, or zero if there are no
+    /// negatives.
+    pub fn false_positive_rate<T: RealField>(&self) -> T {
+        ratio(
+            self.false_positives,
+            self.false_positives + self.true_negatives,
+        )
+    }
+
+    /// The harmonic mean of the precision and recall, or zero if there are no true positives.
+    pub fn f1_score<T: RealField>(&self) -> T {
+        ratio(
+            2 * self.true_positives,
+            2 * self.true_positives + self.false_positives + self.false_negatives,
+        )
+    }
+
+    /// The proportion of predictions that are correct.
+    pub fn accuracy<T: RealField>(&self) -> T {
+        ratio(
+            self.true_positives + self.true_negatives,
+            self.true_positives + self.false_positives + self.true_negatives + self.false_negatives,
+        )
+    }
+}
+
+/// The metric to optimise when choosing a decision threshold with [`tune_threshold`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ThresholdMetric<T> {
+    /// The F1 score.
+    F1,
+    /// Youden's J statistic, the true positive rate minus the false positive rate.
+    YoudenJ,
+    /// The precision, among thresholds with at least the given recall.
+    PrecisionAtRecall(T),
+}
+
+impl<T> ThresholdMetric<T>
+where
+    T: RealField + Copy,
+{
+    pub fn validate(&self) -> SLearningResult<()> {
+        if let Self::PrecisionAtRecall(recall) = self {
+            check_fraction(*recall, "Recall")?;
+        }
+        Ok(())
+    }
+
+    /// The value of the metric, or `None` if the confusion does not satisfy its constraint.
+    fn score(&self, confusion: &BinaryConfusion) -> Option<T> {
+        match self {
+            Self::F1 => Some(confusion.f1_score()),
+            Self::YoudenJ => Some(confusion.recall::<T>() - confusion.false_positive_rate()),
+            Self::PrecisionAtRecall(minimum_recall) => {
+                (confusion.recall::<T>() >= *minimum_recall).then(|| confusion.precision())
+            }
+        }
+    }
+}
+
+/// The result of [`tune_threshold`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TunedThreshold<T> {
+    /// Observations with a score at least this are predicted to be positive.
+    pub threshold: T,
+    /// The value of the metric at this threshold.
+    pub score: T,
+}
+
+/// The decision threshold on classifier scores that gives the best value of `metric`.
+///
+/// Every distinct score is tried as a threshold, predicting observations with a score at least the
+/// threshold to be positive. If several thresholds are equally good, the largest is returned.
+pub fn tune_threshold<T>(
+    actual: &DVector<T>,
+    scores: &DVector<T>,
+    metric: ThresholdMetric<T>,
+) -> SLearningResult<TunedThreshold<T>>
+where
+    T: RealField + Copy,
+{
+    metric.validate()?;
+    validate_lengths(actual, scores)?;
+    validate_binary_labels(actual)?;
+    check_finite_values(scores, "scores")?;
+    let num_positives = actual.iter().filter(|label| label.is_one()).count();
+    if num_positives == 0 || num_positives == actual.len() {
+        return Err(SLearningError::InvalidData(
+            "Both classes must be present to tune a threshold.".to_string(),
+        ));
+    }
+
+    let mut order: Vec<usize> = (0..scores.len()).collect();
+    order.sort_by(|&i, &j| total_cmp(&scores[j], &scores[i]));
+
+    // Lower the threshold through the distinct scores, moving observations to positive predictions.
+    let mut confusion = BinaryConfusion {
+        true_positives: 0,
+        false_positives: 0,
+        true_negatives: actual.len() - num_positives,
+        false_negatives: num_positives,
+    };
+    let mut best: Option<TunedThreshold<T>> = None;
+    let mut position = 0;
+    while position < order.len() {
+        let threshold = scores[order[position]];
+        while position < order.len() && scores[order[position]] == threshold {
+            if actual[order[position]].is_one() {
+                confusion.true_positives += 1;
+                confusion.false_negatives -= 1;
+            } else {
+                confusion.false_positives += 1;
+                confusion.true_negatives -= 1;
+            }
+            position += 1;
+        }
+        if let Some(score) = metric.score(&confusion) {
+            if best.is_none_or(|best| score > best.score) {
+                best = Some(TunedThreshold { threshold, score });
+            }
+        }
+    }
+    // The lowest threshold has a recall of one, so satisfies the constraint of any valid metric.
+    best.ok_or(SLearningError::InvalidData(
+        "No threshold satisfies the constraint of the metric.".to_string(),
+    ))
+}
+
+fn validate_class_labels<T: RealField + Copy>(
+    labels: &DVector<T>,
+    num_classes: usize,
+) -> SLearningResult<()> {
+    let num_classes_t: T = nalgebra::convert(num_classes as f64);
+    if labels
+        .iter()
+        .any(|&label| label.is_negative() || label >= num_classes_t || label != label.floor())
+    {
+        let error_msg = format!(
+            "Class labels must be whole numbers from zero to {}.",
+            num_classes.saturating_sub(1)
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(())
+}
+
+fn validate_cost_matrix<T: RealField>(
+    cost_matrix: &DMatrix<T>,
+    num_classes: usize,
+) -> SLearningResult<()> {
+    if cost_matrix.shape() != (num_classes, num_classes) {
+        let error_msg = format!(
+            "The cost matrix has {} row(s) and {} column(s), but there are {} classes. It must be square, with a row and column for each class.",
+            cost_matrix.nrows(),
+            cost_matrix.ncols(),
+            num_classes
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(())
+}
+
+/// The number of observations of each true class (rows) predicted to be each class (columns), for
+/// classes numbered from zero to `num_classes - 1`.
+pub fn confusion_matrix<T>(
+    actual: &DVector<T>,
+    predicted: &DVector<T>,
+    num_classes: usize,
+) -> SLearningResult<DMatrix<T>>
+where
+    T: RealField + Copy,
+{
+    validate_lengths(actual, predicted)?;
+    validate_class_labels(actual, num_classes)?;
+    validate_class_labels(predicted, num_classes)?;
+    let mut confusion = DMatrix::zeros(num_classes, num_classes);
+    for (&a, &p) in actual.iter().zip(predicted.iter()) {
+        let a: f64 = nalgebra::try_convert(a).expect("The label is a whole number.");
+        let p: f64 = nalgebra::try_convert(p).expect("The label is a whole number.");
+        confusion[(a as usize, p as usize)] += T::one();
+    }
+    Ok(confusion)
+}
+
+/// The total cost of some predictions, given their [`confusion_matrix`] and the cost of predicting
+/// each true class (rows) as each class (columns).
+pub fn total_cost<T>(confusion: &DMatrix<T>, cost_matrix: &DMatrix<T>) -> SLearningResult<T>
+where
+    T: RealField + Copy,
+{
+    if !confusion.is_square() {
+        let error_msg = format!(
+            "The confusion matrix has {} row(s) and {} column(s). It must be square, with a row and column for each class.",
+            confusion.nrows(),
+            confusion.ncols()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    validate_cost_matrix(cost_matrix, confusion.nrows())?;
+    Ok(confusion.component_mul(cost_matrix).sum())
+}
+
+/// The class that minimises the expected cost for each observation, given the probability of each
+/// class (columns) for each observation (rows) and the cost of predicting each true class (rows) as
+/// each class (columns). Ties go to the lowest class.
+///
+/// With a cost of one for every mistake and zero for correct predictions, this is the most
+/// probable class.
+pub fn min_expected_cost_classes<T>(
+    probabilities: &DMatrix<T>,
+    cost_matrix: &DMatrix<T>,
+) -> SLearningResult<DVector<T>>
+where
+    T: RealField + Copy,
+{
+    validate_cost_matrix(cost_matrix, probabilities.ncols())?;
+    let expected_costs = probabilities * cost_matrix;
+    Ok(DVector::from_fn(probabilities.nrows(), |i, _| {
+        let (class, _) = expected_costs.row(i).transpose().argmin();
+        nalgebra::convert(class as f64)
+    }))
+}
+
+/// The probabilities of the negative and positive class (columns), from the probability that each
+/// observation is positive.
+pub(crate) fn binary_class_probabilities<T: RealField + Copy>(
+    probabilities: &DVector<T>,
+) -> DMatrix<T> {
+    DMatrix::from_fn(probabilities.len(), 2, |i, class| match class {
+        0 => T::one() - probabilities[i],
+        _ => probabilities[i],
+    })
+}
+
+/// The result of [`calibration_curve`], with an entry for each non-empty bin.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalibrationCurve<T>
+where
+    T: RealField,
+{
+    /// The mean predicted probability in each bin.
+    pub mean_predicted: DVector<T>,
+    /// The proportion of positives in each bin.
+    pub observed_frequency: DVector<T>,
+    /// The number of observations in each bin.
+    pub counts: Vec<usize>,
+}
+
+/// The data for a calibration curve (reliability diagram) of predicted probabilities.
+///
+/// The probabilities are split into `n_bins` bins of equal width between zero and one, and the
+/// mean prediction in each bin is compared with the proportion of positives. Well-calibrated
+/// probabilities give points near the diagonal. Empty bins are left out.
+pub fn calibration_curve<T>(
+    actual: &DVector<T>,
+    probabilities: &DVector<T>,
+    n_bins: usize,
+) -> SLearningResult<CalibrationCurve<T>>
+where
+    T: RealField + Copy,
+{
+    validate_lengths(actual, probabilities)?;
+    validate_binary_labels(actual)?;
+    if n_bins == 0 {
+        return Err(SLearningError::InvalidParameters(
+            "Number of bins must be at least one.".to_string(),
+        ));
+    }
+    check_finite_values(probabilities, "probabilities")?;
+    if probabilities
+        .iter()
+        .any(|p| p.is_negative() || *p > T::one())
+    {
+        return Err(SLearningError::InvalidData(
+            "Probabilities must be between zero and one.".to_string(),
+        ));
+    }
+
+    let mut prediction_sums = vec![T::zero(); n_bins];
+    let mut positive_counts = vec![0; n_bins];
+    let mut counts = vec![0; n_bins];
+    let n_bins_t: T = nalgebra::convert(n_bins as f64);
+    for (&label, &probability) in actual.iter().zip(probabilities.iter()) {
+        let scaled = nalgebra::try_convert::<T, f64>((probability * n_bins_t).floor())
+            .expect("The scaled probability is a small non-negative number.");
+        // A probability of exactly one goes in the last bin.
+        let bin = (scaled as usize).min(n_bins - 1);
+        prediction_sums[bin] += probability;
+        counts[bin] += 1;
+        if label.is_one() {
+            positive_counts[bin] += 1;
+        }
+    }
+
+    let non_empty: Vec<usize> = (0..n_bins).filter(|&bin| counts[bin] > 0).collect();
+    Ok(CalibrationCurve {
+        mean_predicted: DVector::from_iterator(
+            non_empty.len(),
+            non_empty
+                .iter()
+                .map(|&bin| prediction_sums[bin] / nalgebra::convert(counts[bin] as f64)),
+        ),
+        observed_frequency: DVector::from_iterator(
+            non_empty.len(),
+            non_empty
+                .iter()
+                .map(|&bin| ratio(positive_counts[bin], counts[bin])),
+        ),
+        counts: non_empty.iter().map(|&bin| counts[bin]).collect(),
+    })
+}
+
+fn validate_indicator_matrices<T: RealField>(
+    actual: &DMatrix<T>,
+    predicted: &DMatrix<T>,
+) -> SLearningResult<()> {
+    if actual.is_empty() {
+        return Err(SLearningError::InvalidData(
+            "Cannot compute metrics with zero observations.".to_string(),
+        ));
+    }
+    if actual.shape() != predicted.shape() {
+        let error_msg = format!(
+            "The true labels have shape {:?}, but the predictions have shape {:?}. These must be equal.",
+            actual.shape(),
+            predicted.shape()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    if actual
+        .iter()
+        .chain(predicted.iter())
+        .any(|label| !label.is_zero() && !label.is_one())
+    {
+        return Err(SLearningError::InvalidData(
+            "Binary labels must be zero or one.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The proportion of individual labels that are predicted incorrectly, over every observation and
+/// label of a multilabel classifier.
+pub fn hamming_loss<T>(actual: &DMatrix<T>, predicted: &DMatrix<T>) -> SLearningResult<T>
+where
+    T: RealField + Copy,
+{
+    validate_indicator_matrices(actual, predicted)?;
+    let num_wrong = actual
+        .iter()
+        .zip(predicted.iter())
+        .filter(|(a, p)| a != p)
+        .count();
+    Ok(ratio(num_wrong, actual.len()))
+}
+
+/// The proportion of observations whose whole set of labels is predicted exactly by a multilabel
+/// classifier.
+pub fn subset_accuracy<T>(actual: &DMatrix<T>, predicted: &DMatrix<T>) -> SLearningResult<T>
+where
+    T: RealField + Copy,
+{
+    validate_indicator_matrices(actual, predicted)?;
+    let num_exact = actual
+        .row_iter()
+        .zip(predicted.row_iter())
+        .filter(|(a, p)| a == p)
+        .count();
+    Ok(ratio(num_exact, actual.nrows()))
+}
+
+/// The observations in each query, in order of query id.
+pub(crate) fn query_members(queries: &[usize]) -> Vec<Vec<usize>> {
+    let mut members: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for (i, &query) in queries.iter().enumerate() {
+        members.entry(query).or_default().push(i);
+    }
+    members.into_values().collect()
+}
+
+fn validate_ranking_data<T: RealField>(
+    relevance: &DVector<T>,
+    scores: &DVector<T>,
+    queries: &[usize],
+) -> SLearningResult<()> {
+    validate_lengths(relevance, scores)?;
+    if queries.len() != relevance.len() {
+        let error_msg = format!(
+            "The true labels have {} observation(s), but there are {} query id(s). These must be equal.",
+            relevance.len(),
+            queries.len()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    check_finite_values(relevance, "relevance values")?;
+    check_finite_values(scores, "scores")?;
+    if relevance.iter().any(|r| *r < T::zero()) {
+        return Err(SLearningError::InvalidData(
+            "Relevance cannot be negative.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The observations of a query in order of decreasing score, with ties in their original order.
+fn ranked_by_score<T: RealField + Copy>(members: &[usize], scores: &DVector<T>) -> Vec<usize> {
+    let mut ranked = members.to_vec();
+    ranked.sort_by(|&i, &j| scores[j].partial_cmp(&scores[i]).unwrap());
+    ranked
+}
+
+/// The mean over queries of the normalised discounted cumulative gain (NDCG) of the ranking given
+/// by the scores.
+///
+/// The observation at rank `r` (from one) contributes a gain of `2^relevance - 1`, discounted by
+/// `log2(r + 1)`, and the sum is divided by that of the best possible ranking. Only the first
+/// `cutoff` ranks of each query count, if given. Queries with no relevant observations are left
+/// out of the mean.
+pub fn ndcg<T>(
+    relevance: &DVector<T>,
+    scores: &DVector<T>,
+    queries: &[usize],
+    cutoff: Option<usize>,
+) -> SLearningResult<T>
+where
+    T: RealField + Copy,
+{
+    validate_ranking_data(relevance, scores, queries)?;
+    if cutoff == Some(0) {
+        return Err(SLearningError::InvalidParameters(
+            "Cutoff must be at least one.".to_string(),
+        ));
+    }
+    let two: T = nalgebra::convert(2.0);
+    let discounted_gain = |ranked: &[T]| {
+        ranked
+            .iter()
+            .take(cutoff.unwrap_or(ranked.len()))
+            .enumerate()
+            .fold(T::zero(), |total, (rank, &r)| {
+                let discount: T = nalgebra::convert((rank as f64 + 2.0).log2());
+                total + (two.powf(r) - T::one()) / discount
+            })
+    };
+
+    let mut total = T::zero();
+    let mut num_queries = 0;
+    for members in query_members(queries) {
+        let mut ideal: Vec<T> = members.iter().map(|&i| relevance[i]).collect();
+        ideal.sort_by(|a, b| b.partial_cmp(a).unwrap());
+        let ideal_gain = discounted_gain(&ideal);
+        if ideal_gain.is_zero() {
+            continue;
+        }
+        let ranked: Vec<T> = ranked_by_score(&members, scores)
+            .iter()
+            .map(|&i| relevance[i])
+            .collect();
+        total += discounted_gain(&ranked) / ideal_gain;
+        num_queries += 1;
+    }
+    if num_queries == 0 {
+        return Err(SLearningError::InvalidData(
+            "At least one query must have a relevant observation.".to_string(),
+        ));
+    }
+    Ok(total / nalgebra::convert(num_queries as f64))
+}
+
+/// The mean over queries of the average precision of the ranking given by the scores.
+///
+/// Observations with a relevance greater than zero are relevant. The average precision of a query
+/// is the mean, over its relevant observations, of the proportion of relevant observations ranked
+/// at or above each one. Queries with no relevant observations are left out of the mean.
+pub fn mean_average_precision<T>(
+    relevance: &DVector<T>,
+    scores: &DVector<T>,
+    queries: &[usize],
+) -> SLearningResult<T>
+where
+    T: RealField + Copy,
+{
+    validate_ranking_data(relevance, scores, queries)?;
+    let mut total = T::zero();
+    let mut num_queries = 0;
+    for members in query_members(queries) {
+        let mut num_relevant = 0;
+        let mut precision_sum = T::zero();
+        for (rank, &i) in ranked_by_score(&members, scores).iter().enumerate() {
+            if relevance[i] > T::zero() {
+                num_relevant += 1;
+                precision_sum += ratio(num_relevant, rank + 1);
+            }
+        }
+        if num_relevant == 0 {
+            continue;
+        }
+        total += precision_sum / nalgebra::convert(num_relevant as f64);
+        num_queries += 1;
+    }
+    if num_queries == 0 {
+        return Err(SLearningError::InvalidData(
+            "At least one query must have a relevant observation.".to_string(),
+        ));
+    }
+    Ok(total / nalgebra::convert(num_queries as f64))
+}
@@ -0,0 +1,208 @@
+//! Paired statistical tests for comparing the cross-validation scores of two models.
+//!
+//! The scores of the two models must come from the same folds, in the same order (e.g. by
+//! cross-validating both with the same [`KFold`](super::KFold)), so that each pair of scores is
+//! for the same test observations. The folds share training observations, so their scores are not
+//! independent, and the paired t-test and Wilcoxon signed-rank test tend to find differences that
+//! are not there. The 5×2cv test is designed to avoid this.
+use crate::special::{standard_normal_cdf, students_t_two_sided};
+use crate::validation::{check_finite, check_finite_values};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+/// The result of a test whose statistic has a Student's t distribution when the models are equally
+/// good.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TTest<T> {
+    /// The t statistic, which is positive when the first model has higher scores.
+    pub statistic: T,
+    pub degrees_of_freedom: usize,
+    /// The two-sided p-value.
+    pub p_value: T,
+}
+
+/// The result of a [`wilcoxon_signed_rank`] test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WilcoxonTest<T> {
+    /// The smaller of the sums of the ranks of the positive and the negative score differences.
+    pub statistic: T,
+    /// The two-sided p-value.
+    pub p_value: T,
+}
+
+fn to_f64<T: RealField + Copy>(value: T) -> f64 {
+    nalgebra::try_convert(value).expect("The value is a finite number.")
+}
+
+/// The differences between the paired scores of the two models.
+fn score_differences<T>(scores_a: &DVector<T>, scores_b: &DVector<T>) -> SLearningResult<Vec<f64>>
+where
+    T: RealField + Copy,
+{
+    if scores_a.len() != scores_b.len() {
+        let error_msg = format!(
+            "The first model has {} score(s), but the second has {} score(s). These must be equal.",
+            scores_a.len(),
+            scores_b.len()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    if scores_a.len() < 2 {
+        return Err(SLearningError::InvalidData(
+            "At least two pairs of scores are needed to compare models.".to_string(),
+        ));
+    }
+    check_finite_values(scores_a, "scores")?;
+    check_finite_values(scores_b, "scores")?;
+    Ok(scores_a
+        .iter()
+        .zip(scores_b.iter())
+        .map(|(&a, &b)| to_f64(a - b))
+        .collect())
+}
+
+/// The paired t-test of whether two models have the same mean score, with `n - 1` degrees of
+/// freedom for `n` pairs of scores.
+pub fn paired_t_test<T>(scores_a: &DVector<T>, scores_b: &DVector<T>) -> SLearningResult<TTest<T>>
+where
+    T: RealField + Copy,
+{
+    let differences = score_differences(scores_a, scores_b)?;
+    let n = differences.len() as f64;
+    let mean = differences.iter().sum::<f64>() / n;
+    let variance = differences.iter().map(|d| (d - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    if variance <= 0.0 {
+        return Err(SLearningError::InvalidData(
+            "The score differences are all equal, so the t statistic is undefined.".to_string(),
+        ));
+    }
+    let statistic = mean / (variance / n).sqrt();
+    let degrees_of_freedom = differences.len() - 1;
+    Ok(TTest {
+        statistic: nalgebra::convert(statistic),
+        degrees_of_freedom,
+        p_value: nalgebra::convert(students_t_two_sided(statistic, degrees_of_freedom as f64)),
+    })
+}
+
+/// The Wilcoxon signed-rank test of whether the differences between two models' scores are
+/// symmetric about zero.
+///
+/// Pairs with equal scores are dropped, and tied absolute differences get their average rank. The
+/// p-value is exact when there are no ties, and otherwise uses the normal approximation with a
+/// correction for ties.
+pub fn wilcoxon_signed_rank<T>(
+    scores_a: &DVector<T>,
+    scores_b: &DVector<T>,
+) -> SLearningResult<WilcoxonTest<T>>
+where
+    T: RealField + Copy,
+{
+    let mut differences = score_differences(scores_a, scores_b)?;
+    differences.retain(|&d| d != 0.0);
+    if differences.is_empty() {
+        return Err(SLearningError::InvalidData(
+            "The scores of the two models are all equal, so they cannot be ranked.".to_string(),
+        ));
+    }
+    differences.sort_by(|a, b| a.abs().total_cmp(&b.abs()));
+
+    let n = differences.len();
+    let mut positive_rank_sum = 0.0;
+    let mut tie_correction = 0.0;
+    let mut start = 0;
+    while start < n {
+        let mut end = start + 1;
+        while end < n && differences[end].abs() == differences[start].abs() {
+            end += 1;
+        }
+        // Ranks start at one, so the tied differences `start..end` share rank `(start + end + 1) / 2`.
+        let rank = (start + end + 1) as f64 / 2.0;
+        let ties = (end - start) as f64;
+        positive_rank_sum +=
+            rank * differences[start..end].iter().filter(|&&d| d > 0.0).count() as f64;
+        tie_correction += ties.powi(3) - ties;
+        start = end;
+    }
+    let total = (n * (n + 1)) as f64 / 2.0;
+    let statistic = positive_rank_sum.min(total - positive_rank_sum);
+
+    let p_value = if tie_correction == 0.0 {
+        // The rank sums are integers, and each rank is positive with probability one half, so
+        // build the distribution of the positive rank sum one rank at a time.
+        let mut probabilities = vec![0.0; n * (n + 1) / 2 + 1];
+        probabilities[0] = 1.0;
+        for rank in 1..=n {
+            for sum in (0..probabilities.len()).rev() {
+                let without = probabilities[sum];
+                let with = if sum >= rank {
+                    probabilities[sum - rank]
+                } else {
+                    0.0
+                };
+                probabilities[sum] = 0.5 * (without + with);
+            }
+        }
+        let lower_tail: f64 = probabilities[..=statistic as usize].iter().sum();
+        (2.0 * lower_tail).min(1.0)
+    } else {
+        let n = n as f64;
+        let mean = total / 2.0;
+        let variance = n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - tie_correction / 48.0;
+        let z = (statistic - mean) / variance.sqrt();
+        (2.0 * standard_normal_cdf(z)).min(1.0)
+    };
+    Ok(WilcoxonTest {
+        statistic: nalgebra::convert(statistic),
+        p_value: nalgebra::convert(p_value),
+    })
+}
+
+/// The 5×2cv paired t-test (Dietterich, Approximate Statistical Tests for Comparing Supervised
+/// Classification Learning Algorithms, 1998), with five degrees of freedom.
+///
+/// The scores come from five replications (rows) of two-fold cross-validation (columns), each with
+/// a different shuffle, e.g. from [`KFold::new(2)`](super::KFold::new) with five different seeds.
+/// The statistic is the score difference of the first fold of the first replication, divided by
+/// the square root of the mean of the variances of the differences within each replication.
+pub fn five_by_two_cv_test<T>(
+    scores_a: &DMatrix<T>,
+    scores_b: &DMatrix<T>,
+) -> SLearningResult<TTest<T>>
+where
+    T: RealField + Copy,
+{
+    for scores in [scores_a, scores_b] {
+        if scores.shape() != (5, 2) {
+            let error_msg = format!(
+                "Scores have {} row(s) and {} column(s), but the 5x2cv test needs 5 rows (replications) and 2 columns (folds).",
+                scores.nrows(),
+                scores.ncols()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+    }
+    check_finite(scores_a)?;
+    check_finite(scores_b)?;
+    let differences = (scores_a - scores_b).map(to_f64);
+    let mean_variance = differences
+        .row_iter()
+        .map(|replication| {
+            let mean = replication.mean();
+            replication.iter().map(|d| (d - mean).powi(2)).sum::<f64>()
+        })
+        .sum::<f64>()
+        / 5.0;
+    if mean_variance <= 0.0 {
+        return Err(SLearningError::InvalidData(
+            "The score differences within each replication are equal, so the t statistic is undefined."
+                .to_string(),
+        ));
+    }
+    let statistic = differences[(0, 0)] / mean_variance.sqrt();
+    Ok(TTest {
+        statistic: nalgebra::convert(statistic),
+        degrees_of_freedom: 5,
+        p_value: nalgebra::convert(students_t_two_sided(statistic, 5.0)),
+    })
+}
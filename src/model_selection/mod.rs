@@ -0,0 +1,602 @@
+//! Tools for choosing and tuning models.
+pub mod compare;
+
+use crate::metrics::{query_members, tune_threshold, ThresholdMetric};
+use crate::random::Rng;
+use crate::stats::sorted_quantile;
+use crate::traits::{BinaryPrediction, SupervisedModel};
+use crate::utils::total_cmp;
+use crate::validation::{check_consistent_length, check_finite_values, check_open_unit_interval};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+use std::time::{Duration, Instant};
+
+/// A binary classifier that thresholds the scores of another model, with the threshold tuned to
+/// optimise a metric.
+///
+/// The wrapped model is trained on the zero/one labels and its predictions are used as scores
+/// (e.g. a regression model, or a classifier predicting probabilities). The threshold is then
+/// chosen on the training data using [`tune_threshold`], and predictions are one for scores at least
+/// the threshold and zero otherwise.
+#[derive(Debug)]
+pub struct ThresholdClassifier<M, T>
+where
+    T: RealField,
+{
+    pub model: M,
+    metric: ThresholdMetric<T>,
+    /// The tuned threshold.
+    pub threshold: Option<T>,
+}
+
+impl<M, T> ThresholdClassifier<M, T>
+where
+    M: SupervisedModel<T>,
+    T: RealField + Copy,
+{
+    pub fn new(model: M, metric: ThresholdMetric<T>) -> SLearningResult<Self> {
+        metric.validate()?;
+        Ok(Self {
+            model,
+            metric,
+            threshold: None,
+        })
+    }
+
+    /// The scores of the wrapped model, before thresholding.
+    pub fn predict_scores(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        if self.threshold.is_none() {
+            return Err(SLearningError::UntrainedModel);
+        }
+        self.model.predict(inputs)
+    }
+
+    /// The predicted labels, with the scores of the wrapped model that they were thresholded from.
+    pub fn predict_with_scores(&self, inputs: &DMatrix<T>) -> SLearningResult<BinaryPrediction<T>> {
+        let threshold = self.threshold.ok_or(SLearningError::UntrainedModel)?;
+        let scores = self.model.predict(inputs)?;
+        Ok(BinaryPrediction::from_scores(scores, threshold))
+    }
+}
+
+impl<M, T> SupervisedModel<T> for ThresholdClassifier<M, T>
+where
+    M: SupervisedModel<T>,
+    T: RealField + Copy,
+{
+    fn train(&mut self, inputs: DMatrix<T>, outputs: DVector<T>) -> SLearningResult<()> {
+        self.threshold = None;
+        self.model.train(inputs.clone(), outputs.clone())?;
+        let scores = self.model.predict(&inputs)?;
+        self.threshold = Some(tune_threshold(&outputs, &scores, self.metric)?.threshold);
+        Ok(())
+    }
+
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        Ok(self.predict_with_scores(inputs)?.labels)
+    }
+}
+
+/// The indices of a random sample of `n_samples` of `num_obs` observations, with or without
+/// replacement, in random order.
+///
+/// With `strata` (e.g. the class of each observation), each stratum gets a share of the sample
+/// proportional to its size, rounded by largest remainder, so the sample has (nearly) the same
+/// composition as the data.
+pub fn resample_indices(
+    num_obs: usize,
+    n_samples: usize,
+    strata: Option<&[usize]>,
+    replace: bool,
+    seed: u64,
+) -> SLearningResult<Vec<usize>> {
+    if n_samples == 0 {
+        return Err(SLearningError::InvalidParameters(
+            "Number of samples must be at least one.".to_string(),
+        ));
+    }
+    if num_obs == 0 {
+        return Err(SLearningError::InvalidData(
+            "Cannot resample zero observations.".to_string(),
+        ));
+    }
+    if !replace && n_samples > num_obs {
+        let error_msg = format!(
+            "Cannot draw {} samples without replacement from {} observation(s).",
+            n_samples, num_obs
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    let groups = strata_groups(num_obs, strata)?;
+    let shares = proportional_shares(&groups, n_samples, num_obs);
+
+    let mut rng = Rng::new(seed);
+    let mut indices = Vec::with_capacity(n_samples);
+    for (members, share) in groups.iter().zip(shares) {
+        match replace {
+            true => indices.extend((0..share).map(|_| members[rng.below(members.len())])),
+            false => indices.extend(
+                rng.sample_indices(members.len(), share)
+                    .into_iter()
+                    .map(|member| members[member]),
+            ),
+        }
+    }
+    rng.shuffle(&mut indices);
+    Ok(indices)
+}
+
+/// The observations in each stratum, or all of them in one group without strata.
+fn strata_groups(num_obs: usize, strata: Option<&[usize]>) -> SLearningResult<Vec<Vec<usize>>> {
+    match strata {
+        Some(strata) if strata.len() != num_obs => {
+            let error_msg = format!(
+                "There are {} observation(s), but {} strata label(s). These must be equal.",
+                num_obs,
+                strata.len()
+            );
+            Err(SLearningError::InvalidData(error_msg))
+        }
+        Some(strata) => Ok(query_members(strata)),
+        None => Ok(vec![(0..num_obs).collect()]),
+    }
+}
+
+/// Split `n_samples` between the groups in proportion to their sizes, giving each group the floor
+/// of its share, then one more to the groups with the largest remainders until the shares add up.
+fn proportional_shares(groups: &[Vec<usize>], n_samples: usize, num_obs: usize) -> Vec<usize> {
+    let mut shares: Vec<usize> = groups
+        .iter()
+        .map(|members| n_samples * members.len() / num_obs)
+        .collect();
+    let mut by_remainder: Vec<usize> = (0..groups.len()).collect();
+    by_remainder.sort_by_key(|&g| std::cmp::Reverse(n_samples * groups[g].len() % num_obs));
+    let shortfall = n_samples - shares.iter().sum::<usize>();
+    for &g in by_remainder.iter().take(shortfall) {
+        shares[g] += 1;
+    }
+    shares
+}
+
+/// Strata for a continuous variable, e.g. a regression target: the number of the quantile bin
+/// (from zero to `n_bins - 1`) that each value falls in.
+///
+/// The bins have (nearly) equal numbers of observations, except that equal values always share a
+/// bin. Pass the bins as the strata of [`train_test_split`] or [`resample_indices`] so that each
+/// part has a similar distribution of the variable.
+pub fn quantile_bins<T>(values: &DVector<T>, n_bins: usize) -> SLearningResult<Vec<usize>>
+where
+    T: RealField + Copy,
+{
+    if n_bins == 0 {
+        return Err(SLearningError::InvalidParameters(
+            "Number of bins must be at least one.".to_string(),
+        ));
+    }
+    check_finite_values(values, "values")?;
+    let num_obs = values.len();
+    let mut order: Vec<usize> = (0..num_obs).collect();
+    order.sort_by(|&i, &j| total_cmp(&values[i], &values[j]));
+    let mut bins = vec![0; num_obs];
+    let mut bin = 0;
+    for (rank, &i) in order.iter().enumerate() {
+        if rank == 0 || values[i] != values[order[rank - 1]] {
+            bin = rank * n_bins / num_obs;
+        }
+        bins[i] = bin;
+    }
+    Ok(bins)
+}
+
+/// A random sample of `n_samples` observations (rows of `inputs`, and `outputs`), e.g. a bootstrap
+/// sample or a class-balanced subsample. See [`resample_indices`].
+pub fn resample<T>(
+    inputs: &DMatrix<T>,
+    outputs: &DVector<T>,
+    n_samples: usize,
+    strata: Option<&[usize]>,
+    replace: bool,
+    seed: u64,
+) -> SLearningResult<(DMatrix<T>, DVector<T>)>
+where
+    T: RealField + Copy,
+{
+    check_consistent_length(inputs, outputs)?;
+    let indices = resample_indices(inputs.nrows(), n_samples, strata, replace, seed)?;
+    Ok((inputs.select_rows(&indices), outputs.select_rows(&indices)))
+}
+
+/// The observations used to train and to test a model, e.g. in one round of cross-validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fold {
+    pub train: Vec<usize>,
+    pub test: Vec<usize>,
+}
+
+/// Randomly split `num_obs` observations into a training set and a test set with a fraction
+/// `test_size` of the observations (rounded to the nearest whole number, and leaving at least one in
+/// each set).
+///
+/// With `strata` (e.g. the class of each observation, or the [`quantile_bins`] of a continuous
+/// target), each stratum contributes to the test set in proportion to its size, rounded by largest
+/// remainder, so both sets have (nearly) the same composition as the data.
+pub fn train_test_split<T>(
+    num_obs: usize,
+    test_size: T,
+    strata: Option<&[usize]>,
+    seed: u64,
+) -> SLearningResult<Fold>
+where
+    T: RealField + Copy,
+{
+    check_open_unit_interval(test_size, "Test size")?;
+    if num_obs < 2 {
+        let error_msg = format!(
+            "Cannot split {} observation(s) into a training and a test set.",
+            num_obs
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    let groups = strata_groups(num_obs, strata)?;
+    let num_test: f64 = nalgebra::try_convert(test_size * nalgebra::convert(num_obs as f64))
+        .expect("The test size is a finite number.");
+    let num_test = (num_test.round() as usize).clamp(1, num_obs - 1);
+
+    let mut rng = Rng::new(seed);
+    let mut is_test = vec![false; num_obs];
+    for (members, share) in groups
+        .iter()
+        .zip(proportional_shares(&groups, num_test, num_obs))
+    {
+        for member in rng.sample_indices(members.len(), share) {
+            is_test[members[member]] = true;
+        }
+    }
+    let (mut test, mut train): (Vec<usize>, Vec<usize>) = (0..num_obs).partition(|&i| is_test[i]);
+    rng.shuffle(&mut train);
+    rng.shuffle(&mut test);
+    Ok(Fold { train, test })
+}
+
+/// K-fold cross-validation, where the observations are split into folds of (nearly) equal size and
+/// each fold is held out for testing in turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KFold {
+    n_folds: usize,
+    seed: Option<u64>,
+}
+
+impl KFold {
+    pub fn new(n_folds: usize) -> SLearningResult<Self> {
+        if n_folds < 2 {
+            return Err(SLearningError::InvalidParameters(
+                "Number of folds must be at least two.".to_string(),
+            ));
+        }
+        Ok(Self {
+            n_folds,
+            seed: None,
+        })
+    }
+
+    /// Shuffle the observations with this seed before splitting them, rather than making each fold
+    /// a contiguous block of observations.
+    pub fn with_shuffle(self, seed: u64) -> Self {
+        Self {
+            seed: Some(seed),
+            ..self
+        }
+    }
+
+    /// The folds of `num_obs` observations. The first `num_obs % n_folds` folds test one more
+    /// observation than the others.
+    pub fn split(&self, num_obs: usize) -> SLearningResult<Vec<Fold>> {
+        if num_obs < self.n_folds {
+            let error_msg = format!(
+                "Cannot split {} observation(s) into {} folds.",
+                num_obs, self.n_folds
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        let order = match self.seed {
+            Some(seed) => Rng::new(seed).permutation(num_obs),
+            None => (0..num_obs).collect(),
+        };
+        let mut start = 0;
+        Ok((0..self.n_folds)
+            .map(|fold| {
+                let size = num_obs / self.n_folds + usize::from(fold < num_obs % self.n_folds);
+                let end = start + size;
+                let test = order[start..end].to_vec();
+                let train = order[..start]
+                    .iter()
+                    .chain(&order[end..])
+                    .copied()
+                    .collect();
+                start = end;
+                Fold { train, test }
+            })
+            .collect())
+    }
+}
+
+/// The result of training and scoring a model on one fold.
+#[derive(Debug)]
+pub struct FoldResult<M, T> {
+    /// The model trained on the training observations of the fold.
+    pub model: M,
+    /// The score of the model's predictions for the test observations of the fold.
+    pub score: T,
+    pub fit_time: Duration,
+    /// The time taken to predict and score the test observations.
+    pub score_time: Duration,
+    pub fold: Fold,
+}
+
+/// The results of [`cross_validate`], with a result for each fold.
+#[derive(Debug)]
+pub struct CrossValidation<M, T> {
+    pub folds: Vec<FoldResult<M, T>>,
+}
+
+impl<M, T> CrossValidation<M, T>
+where
+    T: RealField + Copy,
+{
+    /// The score of each fold.
+    pub fn scores(&self) -> DVector<T> {
+        DVector::from_iterator(self.folds.len(), self.folds.iter().map(|fold| fold.score))
+    }
+
+    pub fn mean_score(&self) -> T {
+        self.scores().mean()
+    }
+
+    /// A percentile bootstrap confidence interval for the mean score, from `n_bootstrap` resamples
+    /// of the fold scores. The folds share training observations, so their scores are not
+    /// independent and the interval tends to be too narrow, especially with few folds.
+    pub fn confidence_interval(
+        &self,
+        level: T,
+        n_bootstrap: usize,
+        seed: u64,
+    ) -> SLearningResult<(T, T)> {
+        check_open_unit_interval(level, "Confidence level")?;
+        if n_bootstrap == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Number of bootstrap resamples must be at least one.".to_string(),
+            ));
+        }
+        let scores = self.scores();
+        check_finite_values(&scores, "fold scores")?;
+        let mut rng = Rng::new(seed);
+        let mut means: Vec<T> = (0..n_bootstrap)
+            .map(|_| {
+                let indices = rng.bootstrap_indices(scores.len());
+                scores.select_rows(&indices).mean()
+            })
+            .collect();
+        means.sort_by(total_cmp);
+        let half: T = nalgebra::convert(0.5);
+        let tail = (T::one() - level) * half;
+        Ok((
+            sorted_quantile(&means, tail),
+            sorted_quantile(&means, T::one() - tail),
+        ))
+    }
+}
+
+/// Train a new model from `make_model` on the training observations of each fold, and score its
+/// predictions for the test observations with `score(actual, predicted)`.
+pub fn cross_validate<M, T, F, S>(
+    mut make_model: F,
+    inputs: &DMatrix<T>,
+    outputs: &DVector<T>,
+    folds: &KFold,
+    score: S,
+) -> SLearningResult<CrossValidation<M, T>>
+where
+    M: SupervisedModel<T>,
+    T: RealField + Copy,
+    F: FnMut() -> M,
+    S: Fn(&DVector<T>, &DVector<T>) -> SLearningResult<T>,
+{
+    check_consistent_length(inputs, outputs)?;
+    let results = folds
+        .split(inputs.nrows())?
+        .into_iter()
+        .map(|fold| {
+            let mut model = make_model();
+            let start = Instant::now();
+            model.train(
+                inputs.select_rows(&fold.train),
+                outputs.select_rows(&fold.train),
+            )?;
+            let fit_time = start.elapsed();
+            let start = Instant::now();
+            let predictions = model.predict(&inputs.select_rows(&fold.test))?;
+            let fold_score = score(&outputs.select_rows(&fold.test), &predictions)?;
+            Ok(FoldResult {
+                model,
+                score: fold_score,
+                fit_time,
+                score_time: start.elapsed(),
+                fold,
+            })
+        })
+        .collect::<SLearningResult<Vec<_>>>()?;
+    Ok(CrossValidation { folds: results })
+}
+
+/// Hyperparameter search over a grid of candidate parameters, choosing the candidate with the best
+/// mean cross-validated score.
+///
+/// A model is made from each candidate with `make_model`, and scored with `score(actual,
+/// predicted)`, where higher scores are better unless [`minimise`](Self::minimise) is set.
+pub struct GridSearchCv<P, F, S> {
+    candidates: Vec<P>,
+    make_model: F,
+    score: S,
+    folds: KFold,
+    minimise: bool,
+}
+
+/// The result of [`GridSearchCv::search`].
+#[derive(Debug)]
+pub struct GridSearchResult<M, T>
+where
+    T: RealField,
+{
+    /// The index of the best candidate.
+    pub best_index: usize,
+    /// The mean cross-validated score of each candidate.
+    pub mean_scores: DVector<T>,
+    /// A model with the best candidate parameters, trained on all of the observations.
+    pub model: M,
+}
+
+impl<P, F, S> GridSearchCv<P, F, S> {
+    pub fn new(candidates: Vec<P>, make_model: F, score: S, folds: KFold) -> SLearningResult<Self> {
+        if candidates.is_empty() {
+            return Err(SLearningError::InvalidParameters(
+                "There must be at least one candidate.".to_string(),
+            ));
+        }
+        Ok(Self {
+            candidates,
+            make_model,
+            score,
+            folds,
+            minimise: false,
+        })
+    }
+
+    /// Choose the candidate with the lowest score, e.g. for an error rather than an accuracy.
+    pub fn minimise(self) -> Self {
+        Self {
+            minimise: true,
+            ..self
+        }
+    }
+
+    pub fn candidates(&self) -> &[P] {
+        &self.candidates
+    }
+
+    /// Cross-validate every candidate, then train the best one on all of the observations.
+    pub fn search<M, T>(
+        &self,
+        inputs: &DMatrix<T>,
+        outputs: &DVector<T>,
+    ) -> SLearningResult<GridSearchResult<M, T>>
+    where
+        M: SupervisedModel<T>,
+        T: RealField + Copy,
+        F: Fn(&P) -> M,
+        S: Fn(&DVector<T>, &DVector<T>) -> SLearningResult<T>,
+    {
+        let mean_scores = self
+            .candidates
+            .iter()
+            .map(|candidate| {
+                let results = cross_validate(
+                    || (self.make_model)(candidate),
+                    inputs,
+                    outputs,
+                    &self.folds,
+                    &self.score,
+                )?;
+                Ok(results.mean_score())
+            })
+            .collect::<SLearningResult<Vec<T>>>()?;
+        let mean_scores = DVector::from_vec(mean_scores);
+        let best_index = match self.minimise {
+            true => mean_scores.argmin().0,
+            false => mean_scores.argmax().0,
+        };
+        let mut model = (self.make_model)(&self.candidates[best_index]);
+        model.train(inputs.clone(), outputs.clone())?;
+        Ok(GridSearchResult {
+            best_index,
+            mean_scores,
+            model,
+        })
+    }
+}
+
+/// The result of one outer fold of [`nested_cv`].
+#[derive(Debug)]
+pub struct NestedFoldResult<T> {
+    /// The index of the candidate chosen by the search on the outer training observations.
+    pub best_index: usize,
+    /// The mean inner cross-validated score of the chosen candidate, which is optimistic since it
+    /// was used to choose the candidate.
+    pub inner_score: T,
+    /// The score of the chosen model on the outer test observations.
+    pub outer_score: T,
+    pub fold: Fold,
+}
+
+/// The results of [`nested_cv`], with a result for each outer fold.
+#[derive(Debug)]
+pub struct NestedCv<T> {
+    pub folds: Vec<NestedFoldResult<T>>,
+}
+
+impl<T> NestedCv<T>
+where
+    T: RealField + Copy,
+{
+    /// The score of each outer fold.
+    pub fn outer_scores(&self) -> DVector<T> {
+        DVector::from_iterator(
+            self.folds.len(),
+            self.folds.iter().map(|fold| fold.outer_score),
+        )
+    }
+
+    /// The estimate of the score of the whole procedure, including the search, on new data.
+    pub fn mean_score(&self) -> T {
+        self.outer_scores().mean()
+    }
+}
+
+/// Nested cross-validation: run `search` on the training observations of each outer fold, and
+/// score the model it chooses on the held out test observations.
+///
+/// The test observations of each outer fold play no part in choosing the parameters, so the mean
+/// outer score is an unbiased estimate of how well the search generalises, unlike the best score
+/// of a single search.
+pub fn nested_cv<P, F, S, M, T>(
+    search: &GridSearchCv<P, F, S>,
+    outer_folds: &KFold,
+    inputs: &DMatrix<T>,
+    outputs: &DVector<T>,
+) -> SLearningResult<NestedCv<T>>
+where
+    M: SupervisedModel<T>,
+    T: RealField + Copy,
+    F: Fn(&P) -> M,
+    S: Fn(&DVector<T>, &DVector<T>) -> SLearningResult<T>,
+{
+    check_consistent_length(inputs, outputs)?;
+    let folds = outer_folds
+        .split(inputs.nrows())?
+        .into_iter()
+        .map(|fold| {
+            let result = search.search(
+                &inputs.select_rows(&fold.train),
+                &outputs.select_rows(&fold.train),
+            )?;
+            let predictions = result.model.predict(&inputs.select_rows(&fold.test))?;
+            let outer_score = (search.score)(&outputs.select_rows(&fold.test), &predictions)?;
+            Ok(NestedFoldResult {
+                best_index: result.best_index,
+                inner_score: result.mean_scores[result.best_index],
+                outer_score,
+                fold,
+            })
+        })
+        .collect::<SLearningResult<Vec<_>>>()?;
+    Ok(NestedCv { folds })
+}
@@ -0,0 +1,284 @@
+//! Classification where each observation can have several labels at once.
+//!
+//! The labels of each observation are represented by a row of an indicator matrix, with a column
+//! for each label that is one if the observation has it and zero otherwise. [`MultiLabelBinarizer`]
+//! converts between sets of labels and this representation.
+use std::fmt::Debug;
+
+use crate::traits::SupervisedModel;
+use crate::validation::{
+    check_2d_nonempty, check_consistent_length, check_finite, check_fitted, check_num_vars,
+};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+/// Converts between sets of labels and indicator matrices.
+///
+/// The columns of the indicator matrix are the labels seen when fitting, in sorted order.
+#[derive(Debug, Clone)]
+pub struct MultiLabelBinarizer<L> {
+    classes: Option<Vec<L>>,
+}
+
+impl<L> MultiLabelBinarizer<L>
+where
+    L: Ord + Clone + Debug,
+{
+    pub fn new() -> Self {
+        Self { classes: None }
+    }
+
+    /// The labels, in the order of the columns of the indicator matrix.
+    pub fn classes(&self) -> SLearningResult<&[L]> {
+        check_fitted(&self.classes).map(Vec::as_slice)
+    }
+
+    pub fn fit(&mut self, label_sets: &[Vec<L>]) -> SLearningResult<()> {
+        let mut classes: Vec<L> = label_sets.iter().flatten().cloned().collect();
+        classes.sort();
+        classes.dedup();
+        self.classes = Some(classes);
+        Ok(())
+    }
+
+    pub fn transform<T: RealField>(&self, label_sets: &[Vec<L>]) -> SLearningResult<DMatrix<T>> {
+        let classes = self.classes()?;
+        let mut indicators = DMatrix::zeros(label_sets.len(), classes.len());
+        for (i, labels) in label_sets.iter().enumerate() {
+            for label in labels {
+                let column = classes.binary_search(label).map_err(|_| {
+                    SLearningError::InvalidData(format!(
+                        "Label {:?} was not seen when fitting.",
+                        label
+                    ))
+                })?;
+                indicators[(i, column)] = T::one();
+            }
+        }
+        Ok(indicators)
+    }
+
+    pub fn fit_transform<T: RealField>(
+        &mut self,
+        label_sets: &[Vec<L>],
+    ) -> SLearningResult<DMatrix<T>> {
+        self.fit(label_sets)?;
+        self.transform(label_sets)
+    }
+
+    /// The set of labels for each row of an indicator matrix.
+    pub fn inverse_transform<T: RealField>(
+        &self,
+        indicators: &DMatrix<T>,
+    ) -> SLearningResult<Vec<Vec<L>>> {
+        let classes = self.classes()?;
+        if indicators.ncols() != classes.len() {
+            let error_msg = format!(
+                "There are {} labels, but the indicator matrix has {} columns. These must be equal.",
+                classes.len(),
+                indicators.ncols()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        Ok(indicators
+            .row_iter()
+            .map(|row| {
+                row.iter()
+                    .zip(classes)
+                    .filter(|(indicator, _)| indicator.is_one())
+                    .map(|(_, label)| label.clone())
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+impl<L> Default for MultiLabelBinarizer<L>
+where
+    L: Ord + Clone + Debug,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn validate_multilabel_train<T: RealField>(
+    inputs: &DMatrix<T>,
+    outputs: &DMatrix<T>,
+) -> SLearningResult<()> {
+    check_2d_nonempty(inputs)?;
+    check_finite(inputs)?;
+    if outputs.ncols() == 0 {
+        return Err(SLearningError::InvalidData(
+            "There must be at least one label.".to_string(),
+        ));
+    }
+    check_consistent_length(inputs, &outputs.column(0).into_owned())
+}
+
+/// A multilabel classifier that trains an independent binary classifier for each label.
+///
+/// The classifiers are created by calling `factory`, and each one is trained on a column of the
+/// indicator matrix.
+#[derive(Debug)]
+pub struct MultiOutputClassifier<M, F> {
+    /// The classifier for each label.
+    pub models: Vec<M>,
+    factory: F,
+    num_vars: usize,
+}
+
+impl<M, F> MultiOutputClassifier<M, F>
+where
+    F: Fn() -> M,
+{
+    pub fn new(factory: F) -> Self {
+        Self {
+            models: Vec::new(),
+            factory,
+            num_vars: 0,
+        }
+    }
+
+    pub fn train<T>(&mut self, inputs: DMatrix<T>, outputs: DMatrix<T>) -> SLearningResult<()>
+    where
+        T: RealField,
+        M: SupervisedModel<T>,
+    {
+        validate_multilabel_train(&inputs, &outputs)?;
+        self.num_vars = inputs.ncols();
+        self.models.clear();
+        for column in outputs.column_iter() {
+            let mut model = (self.factory)();
+            model.train(inputs.clone(), column.into_owned())?;
+            self.models.push(model);
+        }
+        Ok(())
+    }
+
+    /// The indicator matrix of predicted labels.
+    pub fn predict<T>(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>>
+    where
+        T: RealField,
+        M: SupervisedModel<T>,
+    {
+        if self.models.is_empty() {
+            return Err(SLearningError::UntrainedModel);
+        }
+        check_num_vars(self.num_vars, inputs.ncols())?;
+        check_finite(inputs)?;
+        let columns = self
+            .models
+            .iter()
+            .map(|model| model.predict(inputs))
+            .collect::<SLearningResult<Vec<DVector<T>>>>()?;
+        Ok(DMatrix::from_columns(&columns))
+    }
+}
+
+/// A multilabel classifier that trains a binary classifier for each label in turn, giving each
+/// classifier the earlier labels in the chain as extra inputs, so that correlations between labels
+/// can be used.
+///
+/// In training, the extra inputs are the true labels. In prediction, they are the predictions of
+/// the earlier classifiers in the chain. The chain follows the order of the columns unless another
+/// order is given.
+#[derive(Debug)]
+pub struct ClassifierChain<M, F> {
+    /// The classifier for each label, in the order of the chain.
+    pub models: Vec<M>,
+    factory: F,
+    order: Option<Vec<usize>>,
+    /// The order of the chain used in training.
+    fitted_order: Vec<usize>,
+    num_vars: usize,
+}
+
+impl<M, F> ClassifierChain<M, F>
+where
+    F: Fn() -> M,
+{
+    pub fn new(factory: F) -> Self {
+        Self {
+            models: Vec::new(),
+            factory,
+            order: None,
+            fitted_order: Vec::new(),
+            num_vars: 0,
+        }
+    }
+
+    /// Chain the labels in this order of columns, which must be a permutation of the columns of
+    /// the indicator matrix.
+    pub fn with_order(self, order: Vec<usize>) -> Self {
+        Self {
+            order: Some(order),
+            ..self
+        }
+    }
+
+    pub fn train<T>(&mut self, inputs: DMatrix<T>, outputs: DMatrix<T>) -> SLearningResult<()>
+    where
+        T: RealField,
+        M: SupervisedModel<T>,
+    {
+        validate_multilabel_train(&inputs, &outputs)?;
+        let num_labels = outputs.ncols();
+        let order = match &self.order {
+            Some(order) => {
+                let mut sorted = order.clone();
+                sorted.sort_unstable();
+                if sorted != (0..num_labels).collect::<Vec<usize>>() {
+                    let error_msg = format!(
+                        "The chain order must be a permutation of the {} label columns.",
+                        num_labels
+                    );
+                    return Err(SLearningError::InvalidParameters(error_msg));
+                }
+                order.clone()
+            }
+            None => (0..num_labels).collect(),
+        };
+
+        self.num_vars = inputs.ncols();
+        self.models.clear();
+        let mut chain_inputs = inputs;
+        for &label in &order {
+            let mut model = (self.factory)();
+            model.train(chain_inputs.clone(), outputs.column(label).into_owned())?;
+            self.models.push(model);
+            let num_columns = chain_inputs.ncols();
+            chain_inputs = chain_inputs.insert_column(num_columns, T::zero());
+            chain_inputs
+                .column_mut(num_columns)
+                .copy_from(&outputs.column(label));
+        }
+        self.fitted_order = order;
+        Ok(())
+    }
+
+    /// The indicator matrix of predicted labels, in the original order of the columns.
+    pub fn predict<T>(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>>
+    where
+        T: RealField,
+        M: SupervisedModel<T>,
+    {
+        if self.models.is_empty() {
+            return Err(SLearningError::UntrainedModel);
+        }
+        check_num_vars(self.num_vars, inputs.ncols())?;
+        check_finite(inputs)?;
+        let mut predictions = DMatrix::zeros(inputs.nrows(), self.models.len());
+        let mut chain_inputs = inputs.clone();
+        for (model, &label) in self.models.iter().zip(&self.fitted_order) {
+            let label_predictions = model.predict(&chain_inputs)?;
+            predictions.set_column(label, &label_predictions);
+            let num_columns = chain_inputs.ncols();
+            chain_inputs = chain_inputs.insert_column(num_columns, T::zero());
+            chain_inputs
+                .column_mut(num_columns)
+                .copy_from(&label_predictions);
+        }
+        Ok(predictions)
+    }
+}
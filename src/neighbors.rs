@@ -0,0 +1,362 @@
+//! Nearest neighbour search, either exact ([`NearestNeighbors`]) or approximate with
+//! locality-sensitive hashing ([`LshNeighbors`]).
+use std::collections::HashMap;
+
+use crate::distance::{validate_num_vars, Metric};
+use crate::random::Rng;
+use crate::utils::total_cmp;
+use crate::validation::{check_finite, check_positive};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+/// The nearest neighbours of each query observation, closest first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Neighbors<T>
+where
+    T: RealField,
+{
+    /// The rows of the indexed observations that are neighbours of each query.
+    pub indices: Vec<Vec<usize>>,
+    /// The distance from each query (rows) to each of its neighbours (columns).
+    pub distances: DMatrix<T>,
+}
+
+/// An index for finding the nearest neighbours of observations among a set of observations, using
+/// an exhaustive (brute force) search.
+#[derive(Debug, Clone)]
+pub struct NearestNeighbors<T, M>
+where
+    T: RealField,
+{
+    inputs: DMatrix<T>,
+    metric: M,
+}
+
+impl<T, M> NearestNeighbors<T, M>
+where
+    T: RealField + Copy,
+    M: Metric<T>,
+{
+    pub fn new(inputs: DMatrix<T>, metric: M) -> SLearningResult<Self> {
+        if inputs.nrows() == 0 {
+            return Err(SLearningError::InvalidData(
+                "Cannot build an index with zero observations.".to_string(),
+            ));
+        }
+        check_finite(&inputs)?;
+        Ok(Self { inputs, metric })
+    }
+
+    /// The indexed observations.
+    pub fn inputs(&self) -> &DMatrix<T> {
+        &self.inputs
+    }
+
+    pub fn metric(&self) -> &M {
+        &self.metric
+    }
+
+    /// The `k` nearest indexed observations to each row of `queries`.
+    pub fn query(&self, queries: &DMatrix<T>, k: usize) -> SLearningResult<Neighbors<T>> {
+        validate_num_vars(&self.inputs, queries)?;
+        check_finite(queries)?;
+        validate_k(k, self.inputs.nrows())?;
+        let distances = self.metric.pairwise(queries, &self.inputs)?;
+        Ok(nearest(&distances, k, |_, _| false))
+    }
+
+    /// The `k` nearest other indexed observations to each indexed observation, i.e. excluding each
+    /// observation from its own neighbours.
+    pub fn query_indexed(&self, k: usize) -> SLearningResult<Neighbors<T>> {
+        validate_k(k, self.inputs.nrows() - 1)?;
+        let distances = self.metric.pairwise(&self.inputs, &self.inputs)?;
+        Ok(nearest(&distances, k, |query, candidate| {
+            query == candidate
+        }))
+    }
+}
+
+fn validate_k(k: usize, available: usize) -> SLearningResult<()> {
+    if k == 0 || k > available {
+        let error_msg = format!(
+            "Number of neighbours must be between one and {}, but is {}.",
+            available, k
+        );
+        return Err(SLearningError::InvalidParameters(error_msg));
+    }
+    Ok(())
+}
+
+/// The `k` smallest distances in each row, ignoring the pairs where `exclude` is true. Ties are
+/// broken by the lower index.
+fn nearest<T, F>(distances: &DMatrix<T>, k: usize, exclude: F) -> Neighbors<T>
+where
+    T: RealField + Copy,
+    F: Fn(usize, usize) -> bool,
+{
+    let mut indices = Vec::with_capacity(distances.nrows());
+    let mut nearest_distances = DMatrix::zeros(distances.nrows(), k);
+    for i in 0..distances.nrows() {
+        let mut candidates: Vec<usize> =
+            (0..distances.ncols()).filter(|&j| !exclude(i, j)).collect();
+        candidates
+            .sort_by(|&a, &b| total_cmp(&distances[(i, a)], &distances[(i, b)]).then(a.cmp(&b)));
+        candidates.truncate(k);
+        for (column, &j) in candidates.iter().enumerate() {
+            nearest_distances[(i, column)] = distances[(i, j)];
+        }
+        indices.push(candidates);
+    }
+    Neighbors {
+        indices,
+        distances: nearest_distances,
+    }
+}
+
+/// A family of locality-sensitive hash functions, which give close observations the same hash
+/// value with a higher probability than distant ones.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LshFamily<T> {
+    /// The sign of the projection onto a random direction (Charikar, Similarity Estimation
+    /// Techniques from Rounding Algorithms, 2002). Two observations collide with a probability
+    /// that decreases with the angle between them, so this suits the cosine distance.
+    RandomHyperplane,
+    /// The projection onto a random direction with normally distributed entries, shifted by a
+    /// random offset and divided into buckets of width `bucket_width` (Datar et al.,
+    /// Locality-Sensitive Hashing Scheme Based on p-Stable Distributions, 2004). Two observations
+    /// collide with a probability that decreases with their Euclidean distance.
+    PStable { bucket_width: T },
+}
+
+/// The hash tables of an [`LshNeighbors`] index.
+///
+/// Each table hashes observations with several hash functions from the family at once, so only
+/// observations that collide in all of them share a bucket. More hashes per table give smaller
+/// buckets (faster queries, but more missed neighbours), and more tables find more of the
+/// neighbours.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LshConfig<T> {
+    family: LshFamily<T>,
+    n_tables: usize,
+    n_hashes: usize,
+    seed: u64,
+}
+
+impl<T> LshConfig<T>
+where
+    T: RealField + Copy,
+{
+    /// A configuration with 10 tables of 4 hashes each.
+    pub fn new(family: LshFamily<T>) -> SLearningResult<Self> {
+        if let LshFamily::PStable { bucket_width } = family {
+            check_positive(bucket_width, "Bucket width")?;
+        }
+        Ok(Self {
+            family,
+            n_tables: 10,
+            n_hashes: 4,
+            seed: 0,
+        })
+    }
+
+    pub fn with_tables(self, n_tables: usize, n_hashes: usize) -> SLearningResult<Self> {
+        if n_tables == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Number of tables must be at least one.".to_string(),
+            ));
+        }
+        if n_hashes == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Number of hashes per table must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            n_tables,
+            n_hashes,
+            ..self
+        })
+    }
+
+    /// Set the seed for the random hash functions.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+}
+
+/// One hash table: the hash functions, and the indexed observations in each bucket.
+#[derive(Debug, Clone)]
+struct LshTable<T>
+where
+    T: RealField,
+{
+    /// The random direction of each hash function (rows).
+    directions: DMatrix<T>,
+    /// The random offset of each hash function, which is only used by [`LshFamily::PStable`].
+    offsets: DVector<T>,
+    buckets: HashMap<Vec<i64>, Vec<usize>>,
+}
+
+impl<T> LshTable<T>
+where
+    T: RealField + Copy,
+{
+    fn key(&self, family: &LshFamily<T>, projections: &DMatrix<T>, row: usize) -> Vec<i64> {
+        (0..self.directions.nrows())
+            .map(|hash| match *family {
+                LshFamily::RandomHyperplane => (projections[(row, hash)] >= T::zero()) as i64,
+                LshFamily::PStable { bucket_width } => {
+                    let bucket =
+                        ((projections[(row, hash)] + self.offsets[hash]) / bucket_width).floor();
+                    nalgebra::try_convert::<T, f64>(bucket).expect("The bucket is a number.") as i64
+                }
+            })
+            .collect()
+    }
+
+    /// The bucket of each row of `inputs`.
+    fn keys(&self, family: &LshFamily<T>, inputs: &DMatrix<T>) -> Vec<Vec<i64>> {
+        let projections = inputs * self.directions.transpose();
+        (0..inputs.nrows())
+            .map(|row| self.key(family, &projections, row))
+            .collect()
+    }
+}
+
+/// An index for finding approximate nearest neighbours with locality-sensitive hashing (LSH).
+///
+/// The indexed observations are put into the buckets of several hash tables, and only the
+/// observations that share a bucket with a query in at least one table are candidates for its
+/// neighbours, so a query compares against a small fraction of a large index. Neighbours are the
+/// closest candidates by the metric, so some true neighbours can be missed, but the distances
+/// returned are exact. When a query has fewer than `k` candidates, all the indexed observations
+/// are searched instead.
+///
+/// The hash family should match the metric: [`LshFamily::RandomHyperplane`] for
+/// [`Cosine`](crate::distance::Cosine), and [`LshFamily::PStable`] for
+/// [`Euclidean`](crate::distance::Euclidean).
+#[derive(Debug, Clone)]
+pub struct LshNeighbors<T, M>
+where
+    T: RealField,
+{
+    inputs: DMatrix<T>,
+    metric: M,
+    family: LshFamily<T>,
+    tables: Vec<LshTable<T>>,
+}
+
+impl<T, M> LshNeighbors<T, M>
+where
+    T: RealField + Copy,
+    M: Metric<T>,
+{
+    pub fn new(inputs: DMatrix<T>, metric: M, config: LshConfig<T>) -> SLearningResult<Self> {
+        if inputs.nrows() == 0 {
+            return Err(SLearningError::InvalidData(
+                "Cannot build an index with zero observations.".to_string(),
+            ));
+        }
+        check_finite(&inputs)?;
+        let mut rng = Rng::new(config.seed);
+        let tables = (0..config.n_tables)
+            .map(|_| {
+                let directions = DMatrix::from_fn(config.n_hashes, inputs.ncols(), |_, _| {
+                    rng.standard_normal()
+                });
+                let offsets = DVector::from_fn(config.n_hashes, |_, _| match config.family {
+                    LshFamily::RandomHyperplane => T::zero(),
+                    LshFamily::PStable { bucket_width } => rng.uniform::<T>() * bucket_width,
+                });
+                let mut table = LshTable {
+                    directions,
+                    offsets,
+                    buckets: HashMap::new(),
+                };
+                for (i, key) in table.keys(&config.family, &inputs).into_iter().enumerate() {
+                    table.buckets.entry(key).or_insert_with(Vec::new).push(i);
+                }
+                table
+            })
+            .collect();
+        Ok(Self {
+            inputs,
+            metric,
+            family: config.family,
+            tables,
+        })
+    }
+
+    /// The indexed observations.
+    pub fn inputs(&self) -> &DMatrix<T> {
+        &self.inputs
+    }
+
+    pub fn metric(&self) -> &M {
+        &self.metric
+    }
+
+    /// The indexed observations that share a bucket with each row of `queries` in any table, in
+    /// increasing order.
+    fn candidates(&self, queries: &DMatrix<T>) -> Vec<Vec<usize>> {
+        let mut candidates = vec![Vec::new(); queries.nrows()];
+        for table in &self.tables {
+            for (query, key) in table.keys(&self.family, queries).iter().enumerate() {
+                if let Some(bucket) = table.buckets.get(key) {
+                    candidates[query].extend(bucket);
+                }
+            }
+        }
+        for query_candidates in candidates.iter_mut() {
+            query_candidates.sort_unstable();
+            query_candidates.dedup();
+        }
+        candidates
+    }
+
+    /// The `k` nearest candidates to each row of `queries`, ignoring the pairs where `exclude` is
+    /// true. Ties are broken by the lower index.
+    fn nearest_candidates<F>(&self, queries: &DMatrix<T>, k: usize, exclude: F) -> Neighbors<T>
+    where
+        F: Fn(usize, usize) -> bool,
+    {
+        let mut indices = Vec::with_capacity(queries.nrows());
+        let mut distances = DMatrix::zeros(queries.nrows(), k);
+        for (query, mut candidates) in self.candidates(queries).into_iter().enumerate() {
+            candidates.retain(|&j| !exclude(query, j));
+            if candidates.len() < k {
+                candidates = (0..self.inputs.nrows())
+                    .filter(|&j| !exclude(query, j))
+                    .collect();
+            }
+            let mut scored: Vec<(T, usize)> = candidates
+                .into_iter()
+                .map(|j| {
+                    let distance = self.metric.distance(queries.row(query), self.inputs.row(j));
+                    (distance, j)
+                })
+                .collect();
+            scored.sort_by(|a, b| total_cmp(&a.0, &b.0).then(a.1.cmp(&b.1)));
+            scored.truncate(k);
+            for (column, &(distance, _)) in scored.iter().enumerate() {
+                distances[(query, column)] = distance;
+            }
+            indices.push(scored.into_iter().map(|(_, j)| j).collect());
+        }
+        Neighbors { indices, distances }
+    }
+
+    /// The approximate `k` nearest indexed observations to each row of `queries`.
+    pub fn query(&self, queries: &DMatrix<T>, k: usize) -> SLearningResult<Neighbors<T>> {
+        validate_num_vars(&self.inputs, queries)?;
+        check_finite(queries)?;
+        validate_k(k, self.inputs.nrows())?;
+        Ok(self.nearest_candidates(queries, k, |_, _| false))
+    }
+
+    /// The approximate `k` nearest other indexed observations to each indexed observation, i.e.
+    /// excluding each observation from its own neighbours.
+    pub fn query_indexed(&self, k: usize) -> SLearningResult<Neighbors<T>> {
+        validate_k(k, self.inputs.nrows() - 1)?;
+        Ok(self.nearest_candidates(&self.inputs, k, |query, candidate| query == candidate))
+    }
+}
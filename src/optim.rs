@@ -0,0 +1,728 @@
+//! Solvers for unconstrained minimisation of smooth functions.
+//!
+//! A model with no closed-form solution describes its loss as an [`Objective`] (a value and its
+//! gradient), then minimises it with any of the solvers here. Every solver stops according to a
+//! shared [`ConvergenceConfig`], and the solvers with an explicit step size take a
+//! [`LearningRate`] schedule.
+//!
+//! Long fits can be monitored (or stopped early) with a callback, which is given a
+//! [`TrainProgress`] after every iteration, and the objective value at every iteration is kept in
+//! [`Minimum::history`].
+use std::collections::VecDeque;
+use std::ops::ControlFlow;
+
+use crate::diagnostics::{Diagnostics, Warning};
+use crate::validation::{check_fraction, check_non_negative, check_positive};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DVector, RealField};
+
+/// Trait for a differentiable function to be minimised.
+pub trait Objective<T>
+where
+    T: RealField + Copy,
+{
+    fn value(&self, params: &DVector<T>) -> T;
+
+    fn gradient(&self, params: &DVector<T>) -> DVector<T>;
+
+    /// The value and gradient together, which can be overridden when it is cheaper to compute
+    /// them at the same time.
+    fn value_and_gradient(&self, params: &DVector<T>) -> (T, DVector<T>) {
+        (self.value(params), self.gradient(params))
+    }
+}
+
+/// Trait for an algorithm that minimises an [`Objective`].
+pub trait Solver<T>
+where
+    T: RealField + Copy,
+{
+    fn minimize<O: Objective<T>>(
+        &self,
+        objective: &O,
+        initial: DVector<T>,
+    ) -> SLearningResult<Minimum<T>> {
+        self.minimize_with_callback(objective, initial, |_| ControlFlow::Continue(()))
+    }
+
+    /// Minimise the objective, calling `callback` after every iteration. The solver stops early
+    /// (without having converged, unless the gradient is already small enough) if the callback
+    /// returns [`ControlFlow::Break`].
+    fn minimize_with_callback<O, F>(
+        &self,
+        objective: &O,
+        initial: DVector<T>,
+        callback: F,
+    ) -> SLearningResult<Minimum<T>>
+    where
+        O: Objective<T>,
+        F: FnMut(&TrainProgress<T>) -> ControlFlow<()>;
+}
+
+/// The state of a solver after an iteration, which is passed to the training callback.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrainProgress<T> {
+    /// The number of iterations completed so far.
+    pub iteration: usize,
+    /// The objective value after this iteration.
+    pub value: T,
+    /// The Euclidean norm of the gradient after this iteration.
+    pub gradient_norm: T,
+}
+
+/// The result of minimising an objective.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Minimum<T>
+where
+    T: RealField,
+{
+    /// The parameters at the (approximate) minimum.
+    pub params: DVector<T>,
+    /// The objective value at `params`.
+    pub value: T,
+    /// The number of iterations that were run.
+    pub iterations: usize,
+    /// Whether the solver converged before reaching its iteration limit.
+    pub converged: bool,
+    /// The objective value at the initial parameters, then after each iteration, so this has
+    /// `iterations + 1` values.
+    pub history: Vec<T>,
+}
+
+/// When an iterative solver should stop.
+///
+/// The solver stops when either:
+/// - the largest absolute value of the gradient is at most `tol` (converged),
+/// - the objective has not decreased by more than `tol` for `patience` consecutive iterations
+///   (converged), if a patience is set, or
+/// - it has run `max_iter` iterations (not converged).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ConvergenceConfig<T> {
+    max_iter: usize,
+    tol: T,
+    patience: Option<usize>,
+}
+
+impl<T> ConvergenceConfig<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(max_iter: usize, tol: T) -> SLearningResult<Self> {
+        if max_iter == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Maximum number of iterations must be at least one.".to_string(),
+            ));
+        }
+        check_non_negative(tol, "Tolerance")?;
+        Ok(Self {
+            max_iter,
+            tol,
+            patience: None,
+        })
+    }
+
+    /// Also stop once the objective has not improved for `patience` consecutive iterations.
+    pub fn with_patience(self, patience: usize) -> SLearningResult<Self> {
+        if patience == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Patience must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            patience: Some(patience),
+            ..self
+        })
+    }
+
+    pub fn max_iter(&self) -> usize {
+        self.max_iter
+    }
+
+    pub fn tol(&self) -> T {
+        self.tol
+    }
+
+    pub fn patience(&self) -> Option<usize> {
+        self.patience
+    }
+}
+
+impl<T> Default for ConvergenceConfig<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self::new(1000, nalgebra::convert(1e-6)).expect("The default parameters are valid.")
+    }
+}
+
+/// A schedule for the step size of each iteration of a solver.
+///
+/// Iterations are counted from zero.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LearningRate<T> {
+    /// The same step size for every iteration.
+    Constant(T),
+    /// `initial / (iteration + 1)^power`.
+    InverseScaling { initial: T, power: T },
+    /// `initial * decay^iteration`, for a decay between zero and one.
+    ExponentialDecay { initial: T, decay: T },
+    /// Cosine annealing from `initial` down to `minimum` over `period` iterations, then restarting
+    /// from `initial`.
+    Cosine {
+        initial: T,
+        minimum: T,
+        period: usize,
+    },
+}
+
+impl<T> LearningRate<T>
+where
+    T: RealField + Copy,
+{
+    /// The step size for the given iteration.
+    pub fn rate(&self, iteration: usize) -> T {
+        let iteration_t: T = nalgebra::convert(iteration as f64);
+        match *self {
+            Self::Constant(rate) => rate,
+            Self::InverseScaling { initial, power } => {
+                initial / (iteration_t + T::one()).powf(power)
+            }
+            Self::ExponentialDecay { initial, decay } => decay.powf(iteration_t) * initial,
+            Self::Cosine {
+                initial,
+                minimum,
+                period,
+            } => {
+                let progress: T = nalgebra::convert((iteration % period) as f64 / period as f64);
+                let half: T = nalgebra::convert(0.5);
+                minimum + (initial - minimum) * half * (T::one() + (T::pi() * progress).cos())
+            }
+        }
+    }
+
+    pub fn validate(&self) -> SLearningResult<()> {
+        let initial = match *self {
+            Self::Constant(rate) => rate,
+            Self::InverseScaling { initial, power } => {
+                if power.is_negative() {
+                    return Err(SLearningError::InvalidParameters(
+                        "Power cannot be less than zero.".to_string(),
+                    ));
+                }
+                initial
+            }
+            Self::ExponentialDecay { initial, decay } => {
+                check_fraction(decay, "Decay")?;
+                initial
+            }
+            Self::Cosine {
+                initial,
+                minimum,
+                period,
+            } => {
+                if period == 0 {
+                    return Err(SLearningError::InvalidParameters(
+                        "Period must be at least one.".to_string(),
+                    ));
+                }
+                if minimum.is_negative() || minimum > initial {
+                    return Err(SLearningError::InvalidParameters(
+                        "Minimum learning rate must be between zero and the initial learning rate."
+                            .to_string(),
+                    ));
+                }
+                initial
+            }
+        };
+        check_positive(initial, "Learning rate")?;
+        Ok(())
+    }
+}
+
+fn validate_value<T: RealField>(value: &T) -> SLearningResult<()> {
+    if !value.is_finite() {
+        return Err(SLearningError::InvalidData(
+            "The objective is not finite at the current parameters.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Tracks the progress of a solver against its [`ConvergenceConfig`].
+struct Progress<T> {
+    config: ConvergenceConfig<T>,
+    iterations: usize,
+    best_value: Option<T>,
+    iterations_without_improvement: usize,
+    history: Vec<T>,
+}
+
+impl<T> Progress<T>
+where
+    T: RealField + Copy,
+{
+    fn new(config: ConvergenceConfig<T>, initial_value: T) -> Self {
+        Self {
+            config,
+            iterations: 0,
+            best_value: None,
+            iterations_without_improvement: 0,
+            history: vec![initial_value],
+        }
+    }
+
+    fn has_iterations_left(&self) -> bool {
+        self.iterations < self.config.max_iter
+    }
+
+    /// Record the objective at the start of an iteration, returning whether the solver has
+    /// converged.
+    fn record(&mut self, value: T, gradient: &DVector<T>) -> bool {
+        if gradient.amax() <= self.config.tol {
+            return true;
+        }
+        match self.best_value {
+            Some(best) if value > best - self.config.tol => {
+                self.iterations_without_improvement += 1;
+            }
+            _ => self.iterations_without_improvement = 0,
+        }
+        if self.best_value.is_none_or(|best| value < best) {
+            self.best_value = Some(value);
+        }
+        matches!(
+            self.config.patience,
+            Some(patience) if self.iterations_without_improvement >= patience
+        )
+    }
+
+    /// Record the end of an iteration, returning whether the callback asked to stop.
+    fn advance<F>(&mut self, value: T, gradient: &DVector<T>, callback: &mut F) -> bool
+    where
+        F: FnMut(&TrainProgress<T>) -> ControlFlow<()>,
+    {
+        self.iterations += 1;
+        self.history.push(value);
+        callback(&TrainProgress {
+            iteration: self.iterations,
+            value,
+            gradient_norm: gradient.norm(),
+        })
+        .is_break()
+    }
+
+    fn finish(self, params: DVector<T>, value: T, converged: bool) -> Minimum<T> {
+        Minimum {
+            params,
+            value,
+            iterations: self.iterations,
+            converged,
+            history: self.history,
+        }
+    }
+}
+
+/// Backtracking line search, which shrinks the step along `direction` until the objective has
+/// decreased sufficiently (the Armijo condition).
+///
+/// Returns `None` if no step decreases the objective, e.g. when `direction` is not a descent
+/// direction or the parameters are already at a minimum (up to floating point precision).
+fn backtracking_line_search<T, O>(
+    objective: &O,
+    params: &DVector<T>,
+    value: T,
+    gradient: &DVector<T>,
+    direction: &DVector<T>,
+    initial_step: T,
+) -> Option<(DVector<T>, T, DVector<T>)>
+where
+    T: RealField + Copy,
+    O: Objective<T>,
+{
+    const MAX_HALVINGS: usize = 60;
+    let sufficient_decrease: T = nalgebra::convert(1e-4);
+    let shrink: T = nalgebra::convert(0.5);
+
+    let slope = gradient.dot(direction);
+    if !slope.is_negative() {
+        return None;
+    }
+    let mut step = initial_step;
+    for _ in 0..MAX_HALVINGS {
+        let candidate = params + direction * step;
+        let (candidate_value, candidate_gradient) = objective.value_and_gradient(&candidate);
+        if candidate_value.is_finite()
+            && candidate_value <= value + sufficient_decrease * step * slope
+        {
+            return Some((candidate, candidate_value, candidate_gradient));
+        }
+        step *= shrink;
+    }
+    None
+}
+
+/// Batch gradient descent.
+///
+/// By default each step size is chosen with a backtracking line search, but a fixed
+/// [`LearningRate`] schedule can be used instead.
+#[derive(Debug, Clone)]
+pub struct GradientDescent<T>
+where
+    T: RealField,
+{
+    convergence: ConvergenceConfig<T>,
+    learning_rate: Option<LearningRate<T>>,
+}
+
+impl<T> GradientDescent<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            learning_rate: None,
+        }
+    }
+
+    /// Use the step sizes from `learning_rate`, rather than a line search.
+    pub fn with_learning_rate(self, learning_rate: LearningRate<T>) -> SLearningResult<Self> {
+        learning_rate.validate()?;
+        Ok(Self {
+            learning_rate: Some(learning_rate),
+            ..self
+        })
+    }
+}
+
+impl<T> Default for GradientDescent<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self::new(ConvergenceConfig::default())
+    }
+}
+
+impl<T> Solver<T> for GradientDescent<T>
+where
+    T: RealField + Copy,
+{
+    fn minimize_with_callback<O, F>(
+        &self,
+        objective: &O,
+        initial: DVector<T>,
+        mut callback: F,
+    ) -> SLearningResult<Minimum<T>>
+    where
+        O: Objective<T>,
+        F: FnMut(&TrainProgress<T>) -> ControlFlow<()>,
+    {
+        let mut params = initial;
+        let (mut value, mut gradient) = objective.value_and_gradient(&params);
+        validate_value(&value)?;
+        let mut progress = Progress::new(self.convergence, value);
+
+        let mut step = T::one();
+        while progress.has_iterations_left() {
+            if progress.record(value, &gradient) {
+                return Ok(progress.finish(params, value, true));
+            }
+            match &self.learning_rate {
+                Some(learning_rate) => {
+                    params -= &gradient * learning_rate.rate(progress.iterations);
+                    (value, gradient) = objective.value_and_gradient(&params);
+                    validate_value(&value)?;
+                }
+                None => {
+                    let direction = -&gradient;
+                    let Some((new_params, new_value, new_gradient)) = backtracking_line_search(
+                        objective, &params, value, &gradient, &direction, step,
+                    ) else {
+                        break;
+                    };
+                    // Try a larger step next time, since backtracking can only shrink it.
+                    let distance = (&new_params - &params).norm() / direction.norm();
+                    step = distance * nalgebra::convert(2.0);
+                    params = new_params;
+                    value = new_value;
+                    gradient = new_gradient;
+                }
+            }
+            if progress.advance(value, &gradient, &mut callback) {
+                break;
+            }
+        }
+        let converged = gradient.amax() <= self.convergence.tol;
+        Ok(progress.finish(params, value, converged))
+    }
+}
+
+/// Limited-memory BFGS, a quasi-Newton method that approximates the inverse Hessian from the
+/// most recent changes in the parameters and gradient.
+#[derive(Debug, Clone)]
+pub struct Lbfgs<T>
+where
+    T: RealField,
+{
+    /// The number of previous updates used to approximate the inverse Hessian.
+    memory: usize,
+    convergence: ConvergenceConfig<T>,
+}
+
+impl<T> Lbfgs<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(memory: usize, convergence: ConvergenceConfig<T>) -> SLearningResult<Self> {
+        if memory == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Memory must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            memory,
+            convergence,
+        })
+    }
+
+    /// Set when the solver stops.
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+}
+
+impl<T> Default for Lbfgs<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self {
+            memory: 10,
+            convergence: ConvergenceConfig::default(),
+        }
+    }
+}
+
+/// The parameters that minimise a model's objective, found by L-BFGS with the model's convergence
+/// config, warning `diagnostics` if it stopped without converging, either at the iteration limit or
+/// when the line search failed.
+pub(crate) fn minimize_lbfgs<T, O>(
+    objective: &O,
+    initial: DVector<T>,
+    convergence: ConvergenceConfig<T>,
+    diagnostics: &Diagnostics,
+) -> SLearningResult<DVector<T>>
+where
+    T: RealField + Copy,
+    O: Objective<T>,
+{
+    let minimum = Lbfgs::default()
+        .with_convergence(convergence)
+        .minimize(objective, initial)?;
+    if !minimum.converged {
+        // Without a callback, stopping before the iteration limit means the line search failed.
+        diagnostics.warn(match minimum.iterations < convergence.max_iter() {
+            true => Warning::LineSearchFailed {
+                iterations: minimum.iterations,
+            },
+            false => Warning::NotConverged {
+                max_iter: convergence.max_iter(),
+            },
+        });
+    }
+    Ok(minimum.params)
+}
+
+/// The L-BFGS search direction, using the two-loop recursion.
+fn lbfgs_direction<T: RealField + Copy>(
+    gradient: &DVector<T>,
+    history: &VecDeque<(DVector<T>, DVector<T>, T)>,
+) -> DVector<T> {
+    let mut q = gradient.clone();
+    let mut alphas = Vec::with_capacity(history.len());
+    for (s, y, rho) in history.iter().rev() {
+        let alpha = *rho * s.dot(&q);
+        q -= y * alpha;
+        alphas.push(alpha);
+    }
+    // Scale by an estimate of the inverse Hessian's size along the most recent step.
+    if let Some((s, y, _)) = history.back() {
+        q *= s.dot(y) / y.norm_squared();
+    }
+    for ((s, y, rho), alpha) in history.iter().zip(alphas.into_iter().rev()) {
+        let beta = *rho * y.dot(&q);
+        q += s * (alpha - beta);
+    }
+    -q
+}
+
+impl<T> Solver<T> for Lbfgs<T>
+where
+    T: RealField + Copy,
+{
+    fn minimize_with_callback<O, F>(
+        &self,
+        objective: &O,
+        initial: DVector<T>,
+        mut callback: F,
+    ) -> SLearningResult<Minimum<T>>
+    where
+        O: Objective<T>,
+        F: FnMut(&TrainProgress<T>) -> ControlFlow<()>,
+    {
+        let mut params = initial;
+        let (mut value, mut gradient) = objective.value_and_gradient(&params);
+        validate_value(&value)?;
+        let mut progress = Progress::new(self.convergence, value);
+
+        let mut history: VecDeque<(DVector<T>, DVector<T>, T)> = VecDeque::new();
+        while progress.has_iterations_left() {
+            if progress.record(value, &gradient) {
+                return Ok(progress.finish(params, value, true));
+            }
+            let mut direction = lbfgs_direction(&gradient, &history);
+            let mut initial_step = T::one();
+            if history.is_empty() {
+                // Without any curvature information, take a conservative first step.
+                initial_step = T::one() / gradient.norm().max(T::one());
+            } else if !gradient.dot(&direction).is_negative() {
+                history.clear();
+                direction = -&gradient;
+            }
+
+            let Some((new_params, new_value, new_gradient)) = backtracking_line_search(
+                objective,
+                &params,
+                value,
+                &gradient,
+                &direction,
+                initial_step,
+            ) else {
+                break;
+            };
+            let s = &new_params - &params;
+            let y = &new_gradient - &gradient;
+            let curvature = s.dot(&y);
+            // Only keep updates that keep the inverse Hessian approximation positive definite.
+            if curvature > T::zero() {
+                if history.len() == self.memory {
+                    history.pop_front();
+                }
+                history.push_back((s, y, T::one() / curvature));
+            }
+            params = new_params;
+            value = new_value;
+            gradient = new_gradient;
+            if progress.advance(value, &gradient, &mut callback) {
+                break;
+            }
+        }
+        let converged = gradient.amax() <= self.convergence.tol;
+        Ok(progress.finish(params, value, converged))
+    }
+}
+
+/// Adam, gradient descent with per-parameter step sizes adapted from running estimates of the
+/// first and second moments of the gradient.
+#[derive(Debug, Clone)]
+pub struct Adam<T>
+where
+    T: RealField,
+{
+    learning_rate: LearningRate<T>,
+    /// Decay rate of the running mean of the gradient.
+    beta1: T,
+    /// Decay rate of the running mean of the squared gradient.
+    beta2: T,
+    /// Added to the denominator of each step to avoid dividing by zero.
+    epsilon: T,
+    convergence: ConvergenceConfig<T>,
+}
+
+impl<T> Adam<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(
+        learning_rate: LearningRate<T>,
+        convergence: ConvergenceConfig<T>,
+    ) -> SLearningResult<Self> {
+        learning_rate.validate()?;
+        Ok(Self {
+            learning_rate,
+            beta1: nalgebra::convert(0.9),
+            beta2: nalgebra::convert(0.999),
+            epsilon: nalgebra::convert(1e-8),
+            convergence,
+        })
+    }
+}
+
+impl<T> Default for Adam<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self::new(
+            LearningRate::Constant(nalgebra::convert(1e-3)),
+            ConvergenceConfig::default(),
+        )
+        .expect("The default parameters are valid.")
+    }
+}
+
+impl<T> Solver<T> for Adam<T>
+where
+    T: RealField + Copy,
+{
+    fn minimize_with_callback<O, F>(
+        &self,
+        objective: &O,
+        initial: DVector<T>,
+        mut callback: F,
+    ) -> SLearningResult<Minimum<T>>
+    where
+        O: Objective<T>,
+        F: FnMut(&TrainProgress<T>) -> ControlFlow<()>,
+    {
+        let mut params = initial;
+        let mut first_moment = DVector::zeros(params.len());
+        let mut second_moment = DVector::zeros(params.len());
+        let mut beta1_power = T::one();
+        let mut beta2_power = T::one();
+
+        let (mut value, mut gradient) = objective.value_and_gradient(&params);
+        validate_value(&value)?;
+        let mut progress = Progress::new(self.convergence, value);
+        while progress.has_iterations_left() {
+            if progress.record(value, &gradient) {
+                return Ok(progress.finish(params, value, true));
+            }
+            first_moment = first_moment * self.beta1 + &gradient * (T::one() - self.beta1);
+            second_moment = second_moment * self.beta2
+                + gradient.component_mul(&gradient) * (T::one() - self.beta2);
+            beta1_power *= self.beta1;
+            beta2_power *= self.beta2;
+
+            // Correct the bias of the moment estimates towards their initial value of zero.
+            let step_size = self.learning_rate.rate(progress.iterations) / (T::one() - beta1_power);
+            let second_correction = T::one() - beta2_power;
+            for i in 0..params.len() {
+                params[i] -= step_size * first_moment[i]
+                    / ((second_moment[i] / second_correction).sqrt() + self.epsilon);
+            }
+            (value, gradient) = objective.value_and_gradient(&params);
+            validate_value(&value)?;
+            if progress.advance(value, &gradient, &mut callback) {
+                break;
+            }
+        }
+        let converged = gradient.amax() <= self.convergence.tol;
+        Ok(progress.finish(params, value, converged))
+    }
+}
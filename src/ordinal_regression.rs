@@ -0,0 +1,297 @@
+//! Regression for ordered categorical outputs, such as ratings.
+//!
+//! The categories (classes) are numbered from zero in order, in the same type as the inputs.
+use crate::diagnostics::Diagnostics;
+use crate::math::sigmoid;
+use crate::metrics::min_expected_cost_classes;
+use crate::optim::{minimize_lbfgs, ConvergenceConfig, Objective};
+use crate::traits::SupervisedModel;
+use crate::validation::{
+    check_2d_nonempty, check_consistent_length, check_finite, check_finite_values, check_num_vars,
+};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+/// The class number of each output, checking that they are numbered from zero without gaps.
+fn class_indices<T: RealField + Copy>(
+    outputs: &DVector<T>,
+) -> SLearningResult<(Vec<usize>, usize)> {
+    check_finite_values(outputs, "ordinal labels")?;
+    // Classes are numbered without gaps, so each is below the number of observations.
+    let num_obs: T = nalgebra::convert(outputs.len() as f64);
+    let mut classes = Vec::with_capacity(outputs.len());
+    for &output in outputs.iter() {
+        if output.is_negative() || output.round() != output {
+            return Err(SLearningError::InvalidData(
+                "Ordinal labels must be non-negative integers.".to_string(),
+            ));
+        }
+        if output >= num_obs {
+            let error_msg = format!(
+                "Ordinal labels must be less than the number of observations ({}).",
+                outputs.len()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        let class = nalgebra::try_convert::<T, f64>(output).expect("The label is an integer.");
+        classes.push(class as usize);
+    }
+    let num_classes = classes.iter().max().map_or(0, |&max| max + 1);
+    if num_classes < 2 {
+        return Err(SLearningError::InvalidData(
+            "There must be at least two classes.".to_string(),
+        ));
+    }
+    if let Some(empty) = (0..num_classes).find(|class| !classes.contains(class)) {
+        let error_msg = format!(
+            "Class {} has no observations. Classes must be numbered from zero without gaps.",
+            empty
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok((classes, num_classes))
+}
+
+/// The negative log-likelihood of a proportional odds model, divided by the number of
+/// observations.
+///
+/// The parameters are the coefficients, then the first threshold, then the logarithms of the gaps
+/// between consecutive thresholds (which keeps the thresholds in increasing order).
+struct CumulativeLogitLikelihood<'a, T>
+where
+    T: RealField,
+{
+    inputs: &'a DMatrix<T>,
+    classes: &'a [usize],
+    num_classes: usize,
+}
+
+impl<T> CumulativeLogitLikelihood<'_, T>
+where
+    T: RealField + Copy,
+{
+    fn thresholds(&self, params: &DVector<T>) -> DVector<T> {
+        let num_vars = self.inputs.ncols();
+        let mut thresholds = DVector::zeros(self.num_classes - 1);
+        thresholds[0] = params[num_vars];
+        for k in 1..self.num_classes - 1 {
+            thresholds[k] = thresholds[k - 1] + params[num_vars + k].exp();
+        }
+        thresholds
+    }
+}
+
+impl<T> Objective<T> for CumulativeLogitLikelihood<'_, T>
+where
+    T: RealField + Copy,
+{
+    fn value(&self, params: &DVector<T>) -> T {
+        self.value_and_gradient(params).0
+    }
+
+    fn gradient(&self, params: &DVector<T>) -> DVector<T> {
+        self.value_and_gradient(params).1
+    }
+
+    fn value_and_gradient(&self, params: &DVector<T>) -> (T, DVector<T>) {
+        let num_vars = self.inputs.ncols();
+        let num_thresholds = self.num_classes - 1;
+        let coefficients = params.rows(0, num_vars);
+        let thresholds = self.thresholds(params);
+        let linear_predictors = self.inputs * coefficients;
+
+        let mut value = T::zero();
+        let mut coefficient_gradient = DVector::zeros(num_vars);
+        let mut threshold_gradient = DVector::zeros(num_thresholds);
+        for (i, &class) in self.classes.iter().enumerate() {
+            let eta = linear_predictors[i];
+            // The cumulative probability and density at the thresholds either side of the class.
+            let (upper_cdf, upper_density) = match class < num_thresholds {
+                true => {
+                    let cdf = sigmoid(thresholds[class] - eta);
+                    (cdf, cdf * (T::one() - cdf))
+                }
+                false => (T::one(), T::zero()),
+            };
+            let (lower_cdf, lower_density) = match class > 0 {
+                true => {
+                    let cdf = sigmoid(thresholds[class - 1] - eta);
+                    (cdf, cdf * (T::one() - cdf))
+                }
+                false => (T::zero(), T::zero()),
+            };
+            let probability = upper_cdf - lower_cdf;
+            value -= probability.ln();
+
+            coefficient_gradient.axpy(
+                (upper_density - lower_density) / probability,
+                &self.inputs.row(i).transpose(),
+                T::one(),
+            );
+            if class < num_thresholds {
+                threshold_gradient[class] -= upper_density / probability;
+            }
+            if class > 0 {
+                threshold_gradient[class - 1] += lower_density / probability;
+            }
+        }
+
+        // Chain rule from the thresholds to the first threshold and the log gaps: every threshold
+        // depends on the first, and threshold k depends on the gaps up to k.
+        let mut gradient = DVector::zeros(params.len());
+        gradient
+            .rows_mut(0, num_vars)
+            .copy_from(&coefficient_gradient);
+        let mut suffix_sum = T::zero();
+        for k in (0..num_thresholds).rev() {
+            suffix_sum += threshold_gradient[k];
+            if k == 0 {
+                gradient[num_vars] = suffix_sum;
+            } else {
+                gradient[num_vars + k] = suffix_sum * params[num_vars + k].exp();
+            }
+        }
+
+        let num_obs: T = nalgebra::convert(self.classes.len() as f64);
+        (value / num_obs, gradient / num_obs)
+    }
+}
+
+/// Proportional odds (cumulative logit) model for ordered classes.
+///
+/// The probability that an observation is in class `k` or below is `logistic(theta_k - x^T beta)`,
+/// where the thresholds `theta` are increasing, so larger values of `x^T beta` shift the
+/// probabilities towards higher classes. The coefficients and thresholds are estimated by maximum
+/// likelihood with L-BFGS. There is no intercept, since it would be absorbed into the thresholds.
+///
+/// Predictions are the most probable class.
+#[derive(Debug)]
+pub struct OrdinalRegressor<T>
+where
+    T: RealField,
+{
+    pub coefficients: Option<DVector<T>>,
+    /// The increasing thresholds between consecutive classes, one fewer than the classes.
+    pub thresholds: Option<DVector<T>>,
+    convergence: ConvergenceConfig<T>,
+    diagnostics: Diagnostics,
+}
+
+impl<T> OrdinalRegressor<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new() -> Self {
+        Self {
+            coefficients: None,
+            thresholds: None,
+            convergence: ConvergenceConfig::default(),
+            diagnostics: Diagnostics::default(),
+        }
+    }
+
+    /// Set when L-BFGS stops, based on the gradient of the objective.
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+
+    /// Send warnings from training, e.g. that it stopped before converging, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// The probability of each class (columns) for each observation (rows).
+    pub fn predict_proba(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let (coefficients, thresholds) = match (&self.coefficients, &self.thresholds) {
+            (Some(coefficients), Some(thresholds)) => (coefficients, thresholds),
+            _ => return Err(SLearningError::UntrainedModel),
+        };
+        check_num_vars(coefficients.len(), inputs.ncols())?;
+        check_finite(inputs)?;
+        let linear_predictors = inputs * coefficients;
+        let num_classes = thresholds.len() + 1;
+        Ok(DMatrix::from_fn(inputs.nrows(), num_classes, |i, k| {
+            let cdf = |class: usize| match class < thresholds.len() {
+                true => sigmoid(thresholds[class] - linear_predictors[i]),
+                false => T::one(),
+            };
+            match k {
+                0 => cdf(0),
+                _ => cdf(k) - cdf(k - 1),
+            }
+        }))
+    }
+
+    /// The class of each observation that minimises the expected cost under the predicted
+    /// probabilities, given the cost of predicting each true class (rows) as each class
+    /// (columns). See [`min_expected_cost_classes`].
+    pub fn predict_min_cost(
+        &self,
+        inputs: &DMatrix<T>,
+        cost_matrix: &DMatrix<T>,
+    ) -> SLearningResult<DVector<T>> {
+        min_expected_cost_classes(&self.predict_proba(inputs)?, cost_matrix)
+    }
+}
+
+impl<T> Default for OrdinalRegressor<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> SupervisedModel<T> for OrdinalRegressor<T>
+where
+    T: RealField + Copy,
+{
+    fn train(&mut self, inputs: DMatrix<T>, outputs: DVector<T>) -> SLearningResult<()> {
+        check_2d_nonempty(&inputs)?;
+        check_consistent_length(&inputs, &outputs)?;
+        check_finite(&inputs)?;
+        let (classes, num_classes) = class_indices(&outputs)?;
+
+        // Start with no effect of the inputs, and the thresholds at the logits of the cumulative
+        // proportions of the classes.
+        let num_vars = inputs.ncols();
+        let mut initial = DVector::zeros(num_vars + num_classes - 1);
+        let mut cumulative_count = 0;
+        let mut previous_logit = T::zero();
+        for k in 0..num_classes - 1 {
+            cumulative_count += classes.iter().filter(|&&class| class == k).count();
+            let proportion = cumulative_count as f64 / classes.len() as f64;
+            let logit: T = nalgebra::convert((proportion / (1.0 - proportion)).ln());
+            initial[num_vars + k] = match k {
+                0 => logit,
+                _ => (logit - previous_logit).ln(),
+            };
+            previous_logit = logit;
+        }
+
+        let objective = CumulativeLogitLikelihood {
+            inputs: &inputs,
+            classes: &classes,
+            num_classes,
+        };
+        let params = minimize_lbfgs(&objective, initial, self.convergence, &self.diagnostics)?;
+        self.thresholds = Some(objective.thresholds(&params));
+        self.coefficients = Some(params.rows(0, num_vars).into_owned());
+        Ok(())
+    }
+
+    fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let probabilities = self.predict_proba(inputs)?;
+        Ok(DVector::from_fn(inputs.nrows(), |i, _| {
+            let (class, _) = probabilities.row(i).transpose().argmax();
+            nalgebra::convert(class as f64)
+        }))
+    }
+}
@@ -0,0 +1,543 @@
+//! Transformers for preparing input data for a model.
+use crate::traits::Transformer;
+use crate::utils::total_cmp;
+use crate::validation::{
+    check_2d_nonempty, check_consistent_length, check_finite, check_finite_values, check_fitted,
+    check_num_vars, check_positive,
+};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+use std::fmt;
+use std::sync::Arc;
+
+type MatrixFunction<T> = Arc<dyn Fn(&DMatrix<T>) -> DMatrix<T> + Send + Sync>;
+
+/// A transformer that applies a function to the inputs, e.g. `log1p`, clipping or a unit
+/// conversion, so that a simple step does not need its own [`Transformer`] implementation.
+///
+/// The function must return one row for each observation, but can change the number of variables.
+/// Fitting only records the number of variables, which later inputs must match.
+#[derive(Clone)]
+pub struct FunctionTransformer<T> {
+    function: MatrixFunction<T>,
+    inverse: Option<MatrixFunction<T>>,
+    num_vars: Option<usize>,
+}
+
+impl<T> FunctionTransformer<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new<F>(function: F) -> Self
+    where
+        F: Fn(&DMatrix<T>) -> DMatrix<T> + Send + Sync + 'static,
+    {
+        Self {
+            function: Arc::new(function),
+            inverse: None,
+            num_vars: None,
+        }
+    }
+
+    /// A transformer that applies `function` to each value of the inputs.
+    pub fn elementwise<F>(function: F) -> Self
+    where
+        F: Fn(T) -> T + Send + Sync + 'static,
+    {
+        Self::new(move |inputs: &DMatrix<T>| inputs.map(&function))
+    }
+
+    /// Set the inverse of the function, for [`inverse_transform`](Self::inverse_transform).
+    pub fn with_inverse<F>(self, inverse: F) -> Self
+    where
+        F: Fn(&DMatrix<T>) -> DMatrix<T> + Send + Sync + 'static,
+    {
+        Self {
+            inverse: Some(Arc::new(inverse)),
+            ..self
+        }
+    }
+
+    /// Map transformed data back to the original variables with the inverse function.
+    pub fn inverse_transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        check_fitted(&self.num_vars)?;
+        let inverse = self.inverse.as_ref().ok_or_else(|| {
+            SLearningError::InvalidParameters(
+                "This function transformer has no inverse.".to_string(),
+            )
+        })?;
+        apply(inverse, inputs)
+    }
+}
+
+/// Apply `function`, checking that it keeps every observation.
+fn apply<T: RealField>(
+    function: &MatrixFunction<T>,
+    inputs: &DMatrix<T>,
+) -> SLearningResult<DMatrix<T>> {
+    let outputs = function(inputs);
+    if outputs.nrows() != inputs.nrows() {
+        let error_msg = format!(
+            "Input has {} observation(s), but the function returned {} observation(s). These must be equal.",
+            inputs.nrows(),
+            outputs.nrows()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(outputs)
+}
+
+impl<T> Transformer<T> for FunctionTransformer<T>
+where
+    T: RealField + Copy,
+{
+    fn fit(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        self.num_vars = Some(inputs.ncols());
+        Ok(())
+    }
+
+    fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let &num_vars = check_fitted(&self.num_vars)?;
+        check_num_vars(num_vars, inputs.ncols())?;
+        apply(&self.function, inputs)
+    }
+}
+
+impl<T> fmt::Debug for FunctionTransformer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FunctionTransformer")
+            .field("has_inverse", &self.inverse.is_some())
+            .field("num_vars", &self.num_vars)
+            .finish()
+    }
+}
+
+/// A transformer that applies several transformers to the same inputs and concatenates their
+/// outputs column-wise, e.g. the original variables alongside some of their principal components.
+///
+/// The columns of each transformer's output are in the order the transformers were given. Every
+/// transformer must return the same number of observations.
+pub struct FeatureUnion<T> {
+    transformers: Vec<Box<dyn Transformer<T> + Send + Sync>>,
+}
+
+impl<T> FeatureUnion<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(transformers: Vec<Box<dyn Transformer<T> + Send + Sync>>) -> SLearningResult<Self> {
+        if transformers.is_empty() {
+            return Err(SLearningError::InvalidParameters(
+                "At least one transformer is needed.".to_string(),
+            ));
+        }
+        Ok(Self { transformers })
+    }
+
+    pub fn transformers(&self) -> &[Box<dyn Transformer<T> + Send + Sync>] {
+        &self.transformers
+    }
+
+    /// Stack the outputs of the transformers side by side.
+    fn concatenate(outputs: Vec<DMatrix<T>>) -> SLearningResult<DMatrix<T>> {
+        let num_obs = outputs[0].nrows();
+        if let Some((i, output)) = outputs
+            .iter()
+            .enumerate()
+            .find(|(_, output)| output.nrows() != num_obs)
+        {
+            let error_msg = format!(
+                "Transformer 0 returned {} observation(s), but transformer {} returned {} observation(s). These must be equal.",
+                num_obs,
+                i,
+                output.nrows()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        let num_columns = outputs.iter().map(|output| output.ncols()).sum();
+        let mut features = DMatrix::zeros(num_obs, num_columns);
+        let mut start = 0;
+        for output in outputs {
+            features
+                .columns_mut(start, output.ncols())
+                .copy_from(&output);
+            start += output.ncols();
+        }
+        Ok(features)
+    }
+}
+
+impl<T> Transformer<T> for FeatureUnion<T>
+where
+    T: RealField + Copy,
+{
+    fn fit(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        for transformer in &mut self.transformers {
+            transformer.fit(inputs)?;
+        }
+        Ok(())
+    }
+
+    fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let outputs = self
+            .transformers
+            .iter()
+            .map(|transformer| transformer.transform(inputs))
+            .collect::<SLearningResult<Vec<_>>>()?;
+        Self::concatenate(outputs)
+    }
+
+    fn fit_transform(&mut self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let outputs = self
+            .transformers
+            .iter_mut()
+            .map(|transformer| transformer.fit_transform(inputs))
+            .collect::<SLearningResult<Vec<_>>>()?;
+        Self::concatenate(outputs)
+    }
+}
+
+impl<T> fmt::Debug for FeatureUnion<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FeatureUnion")
+            .field("num_transformers", &self.transformers.len())
+            .finish()
+    }
+}
+
+/// The sum of the targets and the squared targets of some (value, target) pairs.
+fn target_sums<T: RealField + Copy>(pairs: &[(T, T)]) -> (T, T) {
+    pairs
+        .iter()
+        .fold((T::zero(), T::zero()), |(sum, squares), &(_, y)| {
+            (sum + y, squares + y * y)
+        })
+}
+
+/// Add the thresholds of a tree of depth at most `depth` on `pairs` (sorted by value) to
+/// `thresholds`, in increasing order. Each split is the one that most reduces the squared error of
+/// the targets, leaving at least `min_samples_leaf` pairs on each side.
+fn tree_thresholds<T: RealField + Copy>(
+    pairs: &[(T, T)],
+    depth: usize,
+    min_samples_leaf: usize,
+    thresholds: &mut Vec<T>,
+) {
+    let n = pairs.len();
+    if depth == 0 || n < 2 * min_samples_leaf {
+        return;
+    }
+    let (total, squares) = target_sums(pairs);
+    let n_total: T = nalgebra::convert(n as f64);
+    let parent_error = squares - total * total / n_total;
+    // Splits that only reduce the error by rounding are not worth making.
+    let tolerance = squares * T::default_epsilon() * n_total;
+
+    let mut best: Option<(T, usize)> = None;
+    let mut left_sum = T::zero();
+    for split in 1..n {
+        left_sum += pairs[split - 1].1;
+        if split < min_samples_leaf || n - split < min_samples_leaf {
+            continue;
+        }
+        if pairs[split - 1].0 == pairs[split].0 {
+            continue;
+        }
+        let n_left: T = nalgebra::convert(split as f64);
+        let right_sum = total - left_sum;
+        let error =
+            squares - left_sum * left_sum / n_left - right_sum * right_sum / (n_total - n_left);
+        if best.is_none_or(|(best_error, _)| error < best_error) {
+            best = Some((error, split));
+        }
+    }
+    if let Some((error, split)) = best {
+        if error < parent_error - tolerance {
+            let half: T = nalgebra::convert(0.5);
+            tree_thresholds(&pairs[..split], depth - 1, min_samples_leaf, thresholds);
+            thresholds.push((pairs[split - 1].0 + pairs[split].0) * half);
+            tree_thresholds(&pairs[split..], depth - 1, min_samples_leaf, thresholds);
+        }
+    }
+}
+
+/// Remove thresholds until the mean target of the bins is monotone in the direction of the overall
+/// trend, by merging adjacent bins that are out of order (pool adjacent violators).
+fn make_monotone<T: RealField + Copy>(pairs: &[(T, T)], thresholds: Vec<T>) -> Vec<T> {
+    let n: T = nalgebra::convert(pairs.len() as f64);
+    let mean_value = pairs.iter().fold(T::zero(), |sum, &(x, _)| sum + x) / n;
+    let covariance = pairs
+        .iter()
+        .fold(T::zero(), |sum, &(x, y)| sum + (x - mean_value) * y);
+    let increasing = covariance >= T::zero();
+
+    // Each block is the sum and number of targets of some adjacent bins, and the threshold at its
+    // lower edge (none for the first block).
+    let mut blocks: Vec<(T, usize, Option<T>)> = Vec::new();
+    let mut start = 0;
+    for bin in 0..=thresholds.len() {
+        let end = match thresholds.get(bin) {
+            Some(&threshold) => pairs.partition_point(|&(x, _)| x < threshold),
+            None => pairs.len(),
+        };
+        let (sum, _) = target_sums(&pairs[start..end]);
+        let lower = bin.checked_sub(1).map(|edge| thresholds[edge]);
+        blocks.push((sum, end - start, lower));
+        start = end;
+        while blocks.len() >= 2 {
+            let (last_sum, last_count, _) = blocks[blocks.len() - 1];
+            let (previous_sum, previous_count, _) = blocks[blocks.len() - 2];
+            let last_mean = last_sum / nalgebra::convert(last_count as f64);
+            let previous_mean = previous_sum / nalgebra::convert(previous_count as f64);
+            let in_order = match increasing {
+                true => previous_mean <= last_mean,
+                false => previous_mean >= last_mean,
+            };
+            if in_order {
+                break;
+            }
+            blocks.pop();
+            let previous = blocks.last_mut().expect("There are at least two blocks.");
+            previous.0 += last_sum;
+            previous.1 += last_count;
+        }
+    }
+    blocks
+        .into_iter()
+        .filter_map(|(_, _, lower)| lower)
+        .collect()
+}
+
+/// Bins each input variable using the splits of a shallow regression tree fitted to the target,
+/// as in scorecard modelling, so that the bins separate observations with different targets.
+///
+/// Each variable is binned separately, with at most `2^max_depth` bins. Each split is the one
+/// that most reduces the squared error of the target (for a binary target, this is equivalent to
+/// the Gini impurity), leaving at least `min_samples_leaf` observations in each bin. With
+/// [`with_monotone`](Self::with_monotone), adjacent bins are then merged until the mean target
+/// increases (or decreases) across the bins. The transformed value is the number of the bin, from
+/// zero for the lowest values.
+#[derive(Debug, Clone)]
+pub struct DecisionTreeDiscretizer<T> {
+    /// The thresholds between the bins of each variable, in increasing order. A value is in the
+    /// bin above a threshold if it is at least the threshold.
+    pub thresholds: Option<Vec<Vec<T>>>,
+    max_depth: usize,
+    min_samples_leaf: usize,
+    monotone: bool,
+}
+
+impl<T> DecisionTreeDiscretizer<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(max_depth: usize) -> SLearningResult<Self> {
+        if max_depth == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Maximum depth must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            thresholds: None,
+            max_depth,
+            min_samples_leaf: 1,
+            monotone: false,
+        })
+    }
+
+    /// Set the smallest number of training observations in each bin.
+    pub fn with_min_samples_leaf(self, min_samples_leaf: usize) -> SLearningResult<Self> {
+        if min_samples_leaf == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Minimum number of observations in a bin must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            min_samples_leaf,
+            ..self
+        })
+    }
+
+    /// Merge adjacent bins until the mean target is monotone across the bins of each variable.
+    pub fn with_monotone(self) -> Self {
+        Self {
+            monotone: true,
+            ..self
+        }
+    }
+
+    pub fn fit(&mut self, inputs: &DMatrix<T>, outputs: &DVector<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        check_consistent_length(inputs, outputs)?;
+        check_finite(inputs)?;
+        check_finite_values(outputs, "outputs")?;
+        let thresholds = inputs
+            .column_iter()
+            .map(|column| {
+                let mut pairs: Vec<(T, T)> = column
+                    .iter()
+                    .copied()
+                    .zip(outputs.iter().copied())
+                    .collect();
+                pairs.sort_by(|a, b| total_cmp(&a.0, &b.0));
+                let mut thresholds = Vec::new();
+                tree_thresholds(
+                    &pairs,
+                    self.max_depth,
+                    self.min_samples_leaf,
+                    &mut thresholds,
+                );
+                match self.monotone {
+                    true => make_monotone(&pairs, thresholds),
+                    false => thresholds,
+                }
+            })
+            .collect();
+        self.thresholds = Some(thresholds);
+        Ok(())
+    }
+
+    pub fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let thresholds = check_fitted(&self.thresholds)?;
+        check_num_vars(thresholds.len(), inputs.ncols())?;
+        Ok(DMatrix::from_fn(inputs.nrows(), inputs.ncols(), |i, j| {
+            let bin = thresholds[j].partition_point(|&threshold| threshold <= inputs[(i, j)]);
+            nalgebra::convert(bin as f64)
+        }))
+    }
+
+    pub fn fit_transform(
+        &mut self,
+        inputs: &DMatrix<T>,
+        outputs: &DVector<T>,
+    ) -> SLearningResult<DMatrix<T>> {
+        self.fit(inputs, outputs)?;
+        self.transform(inputs)
+    }
+}
+
+/// Weight-of-evidence encoding of categorical input variables for a binary target, as in
+/// scorecard modelling.
+///
+/// Each input variable holds category codes. The weight of evidence of a category is
+/// `ln(p / n)`, where `p` is the share of all positive observations (label one) that are in the
+/// category, and `n` is the share of negative observations (label zero). `smoothing` is added to
+/// the number of positive and negative observations in every category before taking the shares, so
+/// that a category with only one class has a finite weight. Categories that were not seen in
+/// training are encoded as zero, i.e. as no evidence either way.
+///
+/// The information value of a variable, the sum of `(p - n) ln(p / n)` over its categories,
+/// measures how well it separates the classes.
+#[derive(Debug, Clone)]
+pub struct WoeEncoder<T> {
+    /// The categories of each variable in increasing order, with their weights of evidence.
+    pub weights_of_evidence: Option<Vec<Vec<(T, T)>>>,
+    /// The information value of each variable.
+    pub information_values: Option<DVector<T>>,
+    smoothing: T,
+}
+
+impl<T> WoeEncoder<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(smoothing: T) -> SLearningResult<Self> {
+        check_positive(smoothing, "Smoothing")?;
+        Ok(Self {
+            weights_of_evidence: None,
+            information_values: None,
+            smoothing,
+        })
+    }
+
+    pub fn fit(&mut self, inputs: &DMatrix<T>, outputs: &DVector<T>) -> SLearningResult<()> {
+        check_2d_nonempty(inputs)?;
+        check_consistent_length(inputs, outputs)?;
+        check_finite(inputs)?;
+        if outputs.iter().any(|y| !y.is_zero() && !y.is_one()) {
+            return Err(SLearningError::InvalidData(
+                "Binary labels must be zero or one.".to_string(),
+            ));
+        }
+        if outputs.iter().all(|y| y.is_one()) || outputs.iter().all(|y| y.is_zero()) {
+            return Err(SLearningError::InvalidData(
+                "Both classes must be present to compute weights of evidence.".to_string(),
+            ));
+        }
+
+        let mut weights_of_evidence = Vec::with_capacity(inputs.ncols());
+        let mut information_values = DVector::zeros(inputs.ncols());
+        for (var, column) in inputs.column_iter().enumerate() {
+            // The number of positive and negative observations in each category.
+            let mut counts: Vec<(T, T, T)> = Vec::new();
+            let mut order: Vec<usize> = (0..column.len()).collect();
+            order.sort_by(|&i, &j| total_cmp(&column[i], &column[j]));
+            for i in order {
+                if counts
+                    .last()
+                    .is_none_or(|&(category, _, _)| category != column[i])
+                {
+                    counts.push((column[i], T::zero(), T::zero()));
+                }
+                let last = counts.last_mut().expect("There is at least one category.");
+                match outputs[i].is_one() {
+                    true => last.1 += T::one(),
+                    false => last.2 += T::one(),
+                }
+            }
+            let num_categories: T = nalgebra::convert(counts.len() as f64);
+            let (positives, negatives) = counts.iter().fold(
+                (T::zero(), T::zero()),
+                |(p, n), &(_, positive, negative)| (p + positive, n + negative),
+            );
+            let positive_total = positives + self.smoothing * num_categories;
+            let negative_total = negatives + self.smoothing * num_categories;
+
+            let weights = counts
+                .into_iter()
+                .map(|(category, positive, negative)| {
+                    let positive_share = (positive + self.smoothing) / positive_total;
+                    let negative_share = (negative + self.smoothing) / negative_total;
+                    let weight = (positive_share / negative_share).ln();
+                    information_values[var] += (positive_share - negative_share) * weight;
+                    (category, weight)
+                })
+                .collect();
+            weights_of_evidence.push(weights);
+        }
+        self.weights_of_evidence = Some(weights_of_evidence);
+        self.information_values = Some(information_values);
+        Ok(())
+    }
+
+    pub fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let weights_of_evidence = check_fitted(&self.weights_of_evidence)?;
+        check_num_vars(weights_of_evidence.len(), inputs.ncols())?;
+        Ok(DMatrix::from_fn(inputs.nrows(), inputs.ncols(), |i, j| {
+            let weights = &weights_of_evidence[j];
+            let position = weights.partition_point(|&(category, _)| category < inputs[(i, j)]);
+            match weights.get(position) {
+                Some(&(category, weight)) if category == inputs[(i, j)] => weight,
+                _ => T::zero(),
+            }
+        }))
+    }
+
+    pub fn fit_transform(
+        &mut self,
+        inputs: &DMatrix<T>,
+        outputs: &DVector<T>,
+    ) -> SLearningResult<DMatrix<T>> {
+        self.fit(inputs, outputs)?;
+        self.transform(inputs)
+    }
+}
+
+impl<T> Default for WoeEncoder<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self::new(nalgebra::convert(0.5)).expect("The default parameters are valid.")
+    }
+}
@@ -0,0 +1,113 @@
+//! Seeded random number generation, used by every stochastic part of the crate.
+//!
+//! Anything random in the crate (shuffling, resampling, random initialisation, generated data,
+//! etc.) takes a `seed: u64`, either as a function argument or through a `with_seed` builder
+//! method, and draws from an [`Rng`] created from that seed. The same seed always gives exactly the
+//! same result, on every platform and in every version of the crate.
+//!
+//! The generator is xoshiro256**, seeded using SplitMix64. It is fast and has good statistical
+//! properties, but is not cryptographically secure.
+use nalgebra::{self, RealField};
+
+/// A seeded pseudo-random number generator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rng {
+    state: [u64; 4],
+}
+
+/// One step of SplitMix64, used to expand a seed into the generator state.
+fn split_mix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e3779b97f4a7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+    z ^ (z >> 31)
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        let mut seed_state = seed;
+        Self {
+            state: [(); 4].map(|_| split_mix(&mut seed_state)),
+        }
+    }
+
+    /// A new generator seeded from this one, e.g. to give each of several independent components
+    /// its own stream.
+    pub fn fork(&mut self) -> Self {
+        Self::new(self.next_u64())
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let shifted = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= shifted;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+
+    /// A uniformly distributed value in `[0, 1)`.
+    pub fn uniform<T: RealField>(&mut self) -> T {
+        // The top 53 bits give every representable multiple of 2^-53 with equal probability.
+        nalgebra::convert((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64)
+    }
+
+    /// A uniformly distributed integer in `[0, upper)`, where `upper` must be greater than zero.
+    pub fn below(&mut self, upper: usize) -> usize {
+        assert!(upper > 0, "The upper bound must be greater than zero.");
+        let upper = upper as u64;
+        // Reject the values at the top of the range that would make some results more likely.
+        let limit = u64::MAX - u64::MAX % upper;
+        loop {
+            let value = self.next_u64();
+            if value < limit {
+                return (value % upper) as usize;
+            }
+        }
+    }
+
+    /// A standard normally distributed value, using the Box-Muller transform.
+    pub fn standard_normal<T: RealField + Copy>(&mut self) -> T {
+        // Use (0, 1] for the radius, so that the logarithm is finite.
+        let u1 = T::one() - self.uniform::<T>();
+        let u2 = self.uniform::<T>();
+        let two: T = nalgebra::convert(2.0);
+        (-two * u1.ln()).sqrt() * (T::two_pi() * u2).cos()
+    }
+
+    /// Shuffle `values` in place, with every order equally likely (Fisher-Yates).
+    pub fn shuffle<E>(&mut self, values: &mut [E]) {
+        for i in (1..values.len()).rev() {
+            values.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// A random ordering of `0..n`.
+    pub fn permutation(&mut self, n: usize) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..n).collect();
+        self.shuffle(&mut indices);
+        indices
+    }
+
+    /// `k` distinct indices from `0..n`, in random order. If `k` is larger than `n`, all `n`
+    /// indices are returned.
+    pub fn sample_indices(&mut self, n: usize, k: usize) -> Vec<usize> {
+        let k = k.min(n);
+        let mut indices: Vec<usize> = (0..n).collect();
+        // A partial Fisher-Yates shuffle of the first k positions.
+        for i in 0..k {
+            indices.swap(i, i + self.below(n - i));
+        }
+        indices.truncate(k);
+        indices
+    }
+
+    /// `n` indices drawn from `0..n` with replacement, i.e. a bootstrap sample.
+    pub fn bootstrap_indices(&mut self, n: usize) -> Vec<usize> {
+        (0..n).map(|_| self.below(n)).collect()
+    }
+}
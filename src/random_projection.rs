@@ -0,0 +1,255 @@
+//! Dimensionality reduction by projecting onto random directions.
+//!
+//! A random projection is much cheaper to fit than e.g. PCA, since it ignores the data, and the
+//! Johnson-Lindenstrauss lemma guarantees that it approximately preserves the distances between
+//! observations, with a number of components that depends only on the number of observations.
+use crate::random::Rng;
+use crate::traits::Transformer;
+use crate::validation::{
+    check_2d_nonempty, check_finite, check_fitted, check_fraction, check_num_vars,
+    check_open_unit_interval,
+};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, RealField};
+
+/// The smallest number of components that a random projection of `num_obs` observations needs so
+/// that, with high probability, every squared distance between them is preserved within a factor
+/// of `1 - eps` to `1 + eps` (Dasgupta & Gupta, An Elementary Proof of a Theorem of Johnson and
+/// Lindenstrauss, 2003). This is always at least one.
+pub fn johnson_lindenstrauss_min_dim<T: RealField + Copy>(
+    num_obs: usize,
+    eps: T,
+) -> SLearningResult<usize> {
+    check_open_unit_interval(eps, "Eps")?;
+    let four: T = nalgebra::convert(4.0);
+    let denominator = eps.powi(2) / nalgebra::convert(2.0) - eps.powi(3) / nalgebra::convert(3.0);
+    let num_obs: T = nalgebra::convert(num_obs.max(1) as f64);
+    let min_dim = (four * num_obs.ln() / denominator).floor();
+    let min_dim: f64 = nalgebra::try_convert(min_dim).expect("The dimension is a finite number.");
+    Ok((min_dim as usize).max(1))
+}
+
+/// How many components a random projection has.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProjectionSize<T> {
+    Components(usize),
+    /// The [`johnson_lindenstrauss_min_dim`] for the number of training observations and this
+    /// `eps`, which must be at most the number of variables.
+    JohnsonLindenstrauss {
+        eps: T,
+    },
+}
+
+impl<T> ProjectionSize<T>
+where
+    T: RealField + Copy,
+{
+    fn validate(&self) -> SLearningResult<()> {
+        match *self {
+            ProjectionSize::Components(0) => Err(SLearningError::InvalidParameters(
+                "Number of components must be at least one.".to_string(),
+            )),
+            ProjectionSize::Components(_) => Ok(()),
+            ProjectionSize::JohnsonLindenstrauss { eps } => {
+                johnson_lindenstrauss_min_dim(1, eps).map(|_| ())
+            }
+        }
+    }
+
+    fn num_components(&self, inputs: &DMatrix<T>) -> SLearningResult<usize> {
+        check_2d_nonempty(inputs)?;
+        check_finite(inputs)?;
+        match *self {
+            ProjectionSize::Components(n_components) => Ok(n_components),
+            ProjectionSize::JohnsonLindenstrauss { eps } => {
+                let min_dim = johnson_lindenstrauss_min_dim(inputs.nrows(), eps)?;
+                if min_dim > inputs.ncols() {
+                    let error_msg = format!(
+                        "The Johnson-Lindenstrauss bound for {} observations is {} components, but there are only {} variables. Increase eps or project to a fixed number of components.",
+                        inputs.nrows(),
+                        min_dim,
+                        inputs.ncols()
+                    );
+                    return Err(SLearningError::InvalidData(error_msg));
+                }
+                Ok(min_dim)
+            }
+        }
+    }
+}
+
+/// Random projection onto directions with independent normally distributed entries.
+///
+/// The entries have variance `1 / n_components`, so squared distances are preserved in
+/// expectation.
+#[derive(Debug)]
+pub struct GaussianRandomProjection<T>
+where
+    T: RealField,
+{
+    /// The directions (rows) that observations are projected onto.
+    pub components: Option<DMatrix<T>>,
+    size: ProjectionSize<T>,
+    seed: u64,
+}
+
+impl<T> GaussianRandomProjection<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(size: ProjectionSize<T>) -> SLearningResult<Self> {
+        size.validate()?;
+        Ok(Self {
+            components: None,
+            size,
+            seed: 0,
+        })
+    }
+
+    /// Set the seed for the random directions.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+}
+
+impl<T> Transformer<T> for GaussianRandomProjection<T>
+where
+    T: RealField + Copy,
+{
+    fn fit(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        let n_components = self.size.num_components(inputs)?;
+        let scale: T = nalgebra::convert(1.0 / (n_components as f64).sqrt());
+        let mut rng = Rng::new(self.seed);
+        self.components = Some(DMatrix::from_fn(n_components, inputs.ncols(), |_, _| {
+            scale * rng.standard_normal()
+        }));
+        Ok(())
+    }
+
+    fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let components = check_fitted(&self.components)?;
+        check_num_vars(components.ncols(), inputs.ncols())?;
+        check_finite(inputs)?;
+        Ok(inputs * components.transpose())
+    }
+}
+
+/// Random projection onto sparse directions (Li et al., Very Sparse Random Projections, 2006).
+///
+/// Each entry of a direction is non-zero with probability `density`, and then is
+/// `sqrt(1 / (density n_components))` or its negative with equal probability, so squared distances
+/// are preserved in expectation. The default density is one over the square root of the number of
+/// variables, so fitting and transforming only touch a small fraction of the variables of wide
+/// data, and the directions are never stored in full.
+#[derive(Debug)]
+pub struct SparseRandomProjection<T>
+where
+    T: RealField,
+{
+    size: ProjectionSize<T>,
+    density: Option<T>,
+    seed: u64,
+    num_vars: usize,
+    /// The variables with non-zero entries in each direction, and the entries.
+    components: Option<Vec<Vec<(usize, T)>>>,
+}
+
+impl<T> SparseRandomProjection<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(size: ProjectionSize<T>) -> SLearningResult<Self> {
+        size.validate()?;
+        Ok(Self {
+            size,
+            density: None,
+            seed: 0,
+            num_vars: 0,
+            components: None,
+        })
+    }
+
+    /// Set the probability that each entry of a direction is non-zero.
+    pub fn with_density(self, density: T) -> SLearningResult<Self> {
+        check_fraction(density, "Density")?;
+        Ok(Self {
+            density: Some(density),
+            ..self
+        })
+    }
+
+    /// Set the seed for the random directions.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    /// The directions (rows) that observations are projected onto, as a dense matrix.
+    pub fn components(&self) -> SLearningResult<DMatrix<T>> {
+        let components = check_fitted(&self.components)?;
+        let mut dense = DMatrix::zeros(components.len(), self.num_vars);
+        for (i, component) in components.iter().enumerate() {
+            for &(var, value) in component {
+                dense[(i, var)] = value;
+            }
+        }
+        Ok(dense)
+    }
+}
+
+impl<T> Transformer<T> for SparseRandomProjection<T>
+where
+    T: RealField + Copy,
+{
+    fn fit(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        let n_components = self.size.num_components(inputs)?;
+        let num_vars = inputs.ncols();
+        let density = self
+            .density
+            .unwrap_or_else(|| nalgebra::convert(1.0 / (num_vars.max(1) as f64).sqrt()));
+        let value = (T::one() / (density * nalgebra::convert(n_components as f64))).sqrt();
+        let log_miss = (T::one() - density).ln();
+        let mut rng = Rng::new(self.seed);
+        let mut components = Vec::with_capacity(n_components);
+        for _ in 0..n_components {
+            let mut component = Vec::new();
+            // The gaps between non-zero entries are geometrically distributed, so skip straight to
+            // the next one rather than drawing every entry.
+            let mut var = 0;
+            loop {
+                if !density.is_one() {
+                    let gap = ((T::one() - rng.uniform::<T>()).ln() / log_miss).floor();
+                    let gap: f64 = nalgebra::try_convert(gap).expect("The gap is a number.");
+                    var += gap.min(num_vars as f64) as usize;
+                }
+                if var >= num_vars {
+                    break;
+                }
+                let sign = match rng.uniform::<T>() < nalgebra::convert(0.5) {
+                    true => -value,
+                    false => value,
+                };
+                component.push((var, sign));
+                var += 1;
+            }
+            components.push(component);
+        }
+        self.num_vars = num_vars;
+        self.components = Some(components);
+        Ok(())
+    }
+
+    fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let components = check_fitted(&self.components)?;
+        check_num_vars(self.num_vars, inputs.ncols())?;
+        check_finite(inputs)?;
+        let mut projected = DMatrix::zeros(inputs.nrows(), components.len());
+        for (j, component) in components.iter().enumerate() {
+            for &(var, value) in component {
+                projected
+                    .column_mut(j)
+                    .axpy(value, &inputs.column(var), T::one());
+            }
+        }
+        Ok(projected)
+    }
+}
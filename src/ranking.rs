@@ -0,0 +1,258 @@
+//! Learning to rank: models that order the observations of each query by relevance.
+//!
+//! Observations are grouped into queries (e.g. the documents returned for one search) by a query
+//! id for each observation, and each observation has a non-negative relevance, in the same type as
+//! the inputs. Only the order of the relevances within a query matters, so relevances are never
+//! compared between queries. Rankings can be evaluated with [`crate::metrics::ndcg`] and
+//! [`crate::metrics::mean_average_precision`].
+use std::collections::BTreeSet;
+
+use crate::diagnostics::Diagnostics;
+use crate::math::{log1pexp, sigmoid};
+use crate::metrics::query_members;
+use crate::optim::{minimize_lbfgs, ConvergenceConfig, Objective};
+use crate::random::Rng;
+use crate::utils::total_cmp;
+use crate::validation::{
+    check_2d_nonempty, check_consistent_length, check_finite, check_finite_values, check_fitted,
+    check_non_negative, check_num_vars,
+};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+/// The mean logistic loss of the preference pairs, plus an L2 penalty on the coefficients.
+struct PairwiseLogisticLoss<'a, T>
+where
+    T: RealField,
+{
+    /// The difference between the inputs of the preferred and the other observation of each pair
+    /// (rows).
+    differences: &'a DMatrix<T>,
+    penalty: T,
+}
+
+impl<T> Objective<T> for PairwiseLogisticLoss<'_, T>
+where
+    T: RealField + Copy,
+{
+    fn value(&self, params: &DVector<T>) -> T {
+        self.value_and_gradient(params).0
+    }
+
+    fn gradient(&self, params: &DVector<T>) -> DVector<T> {
+        self.value_and_gradient(params).1
+    }
+
+    fn value_and_gradient(&self, params: &DVector<T>) -> (T, DVector<T>) {
+        let num_pairs: T = nalgebra::convert(self.differences.nrows() as f64);
+        let half: T = nalgebra::convert(0.5);
+        let margins = self.differences * params;
+        let value = margins
+            .iter()
+            .fold(T::zero(), |total, &m| total + log1pexp(-m))
+            / num_pairs
+            + half * self.penalty * params.norm_squared();
+        let weights = margins.map(|m| -sigmoid(-m));
+        let gradient = self.differences.transpose() * weights / num_pairs + params * self.penalty;
+        (value, gradient)
+    }
+}
+
+/// The preference pairs `(preferred, other)` of the observations of one query, or a random sample
+/// of at most `max_pairs` of them.
+///
+/// With the observations sorted by increasing relevance, the observations less relevant than each
+/// one are a prefix of the sorted order, so the pairs can be numbered without listing them all.
+fn preference_pairs<T: RealField + Copy>(
+    mut members: Vec<usize>,
+    relevance: &DVector<T>,
+    max_pairs: Option<usize>,
+    rng: &mut Rng,
+) -> Vec<(usize, usize)> {
+    members.sort_by(|&i, &j| total_cmp(&relevance[i], &relevance[j]));
+    // The number of less relevant observations, and the number of the first pair, of each one.
+    let mut num_less = Vec::with_capacity(members.len());
+    let mut first_pair = Vec::with_capacity(members.len());
+    let mut num_pairs = 0;
+    for (position, &i) in members.iter().enumerate() {
+        let less = match num_less.last() {
+            Some(&previous) if relevance[members[position - 1]] == relevance[i] => previous,
+            _ => position,
+        };
+        num_less.push(less);
+        first_pair.push(num_pairs);
+        num_pairs += less;
+    }
+    let pair = |number: usize| {
+        let position = first_pair.partition_point(|&first| first <= number) - 1;
+        (members[position], members[number - first_pair[position]])
+    };
+    match max_pairs {
+        Some(max_pairs) if num_pairs > max_pairs => sample_distinct(num_pairs, max_pairs, rng)
+            .into_iter()
+            .map(pair)
+            .collect(),
+        _ => (0..num_pairs).map(pair).collect(),
+    }
+}
+
+/// `k` distinct indices drawn uniformly from `0..n`, in increasing order, using Floyd's algorithm
+/// so that the memory used does not depend on `n`.
+fn sample_distinct(n: usize, k: usize, rng: &mut Rng) -> BTreeSet<usize> {
+    let mut sample = BTreeSet::new();
+    for upper in n - k..n {
+        let index = rng.below(upper + 1);
+        if !sample.insert(index) {
+            sample.insert(upper);
+        }
+    }
+    sample
+}
+
+/// Linear pairwise ranking model, trained with a logistic loss on preference pairs (as in
+/// RankNet or a logistic RankSVM).
+///
+/// Each observation gets a score `x^T w`. For every pair of observations in the same query with
+/// different relevances, the probability that the more relevant one is ranked first is modelled as
+/// `logistic(s_i - s_j)`. The coefficients minimise the mean negative log-probability of the
+/// observed preferences plus `penalty / 2` times their squared norm, using L-BFGS. There is no
+/// intercept, since it cancels in the differences of scores.
+///
+/// Queries with many observations have a quadratic number of pairs, so the pairs of each query can
+/// be limited to a random sample with [`with_max_pairs_per_query`](Self::with_max_pairs_per_query).
+#[derive(Debug)]
+pub struct PairwiseRanker<T>
+where
+    T: RealField,
+{
+    pub coefficients: Option<DVector<T>>,
+    penalty: T,
+    max_pairs_per_query: Option<usize>,
+    seed: u64,
+    convergence: ConvergenceConfig<T>,
+    diagnostics: Diagnostics,
+}
+
+impl<T> PairwiseRanker<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(penalty: T) -> SLearningResult<Self> {
+        check_non_negative(penalty, "Penalty")?;
+        Ok(Self {
+            coefficients: None,
+            penalty,
+            max_pairs_per_query: None,
+            seed: 0,
+            convergence: ConvergenceConfig::default(),
+            diagnostics: Diagnostics::default(),
+        })
+    }
+
+    /// Set when L-BFGS stops, based on the gradient of the objective.
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+
+    /// Send warnings from training, e.g. that it stopped before converging, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// Train on a random sample of at most this many preference pairs from each query, rather
+    /// than all of them.
+    pub fn with_max_pairs_per_query(self, max_pairs: usize) -> SLearningResult<Self> {
+        if max_pairs == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Maximum number of pairs must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            max_pairs_per_query: Some(max_pairs),
+            ..self
+        })
+    }
+
+    /// Set the seed for sampling preference pairs.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    pub fn train(
+        &mut self,
+        inputs: DMatrix<T>,
+        relevance: DVector<T>,
+        queries: &[usize],
+    ) -> SLearningResult<()> {
+        check_2d_nonempty(&inputs)?;
+        check_consistent_length(&inputs, &relevance)?;
+        if queries.len() != relevance.len() {
+            let error_msg = format!(
+                "Input has {} observation(s), but there are {} query id(s). These must be equal.",
+                inputs.nrows(),
+                queries.len()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        check_finite(&inputs)?;
+        check_finite_values(&relevance, "relevances")?;
+        if relevance.iter().any(|&r| r < T::zero()) {
+            return Err(SLearningError::InvalidData(
+                "Relevance cannot be negative.".to_string(),
+            ));
+        }
+
+        let mut rng = Rng::new(self.seed);
+        let pairs: Vec<(usize, usize)> = query_members(queries)
+            .into_iter()
+            .flat_map(|members| {
+                preference_pairs(members, &relevance, self.max_pairs_per_query, &mut rng)
+            })
+            .collect();
+        if pairs.is_empty() {
+            return Err(SLearningError::InvalidData(
+                "At least one query must have observations with different relevances.".to_string(),
+            ));
+        }
+
+        let differences = DMatrix::from_fn(pairs.len(), inputs.ncols(), |pair, var| {
+            let (preferred, other) = pairs[pair];
+            inputs[(preferred, var)] - inputs[(other, var)]
+        });
+        let objective = PairwiseLogisticLoss {
+            differences: &differences,
+            penalty: self.penalty,
+        };
+        let initial = DVector::zeros(inputs.ncols());
+        self.coefficients = Some(minimize_lbfgs(
+            &objective,
+            initial,
+            self.convergence,
+            &self.diagnostics,
+        )?);
+        Ok(())
+    }
+
+    /// The score of each observation, where observations with higher scores should be ranked
+    /// first within their query.
+    pub fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let coefficients = check_fitted(&self.coefficients)?;
+        check_num_vars(coefficients.len(), inputs.ncols())?;
+        Ok(inputs * coefficients)
+    }
+}
+
+impl<T> Default for PairwiseRanker<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self::new(nalgebra::convert(1e-4)).expect("The default parameters are valid.")
+    }
+}
@@ -0,0 +1,241 @@
+//! Recommender systems, which predict how users would rate items from the ratings they have given.
+//!
+//! Ratings are given as `(user, item, rating)` triples, with users and items numbered from zero, so
+//! the (usually very sparse) user-item matrix is never stored in full. Ids cannot be greater than
+//! the number of ratings.
+use crate::diagnostics::{Diagnostics, Warning};
+use crate::optim::ConvergenceConfig;
+use crate::random::Rng;
+use crate::utils::total_cmp;
+use crate::validation::{check_finite_values, check_fitted, check_positive};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+/// Matrix factorisation by alternating least squares (ALS).
+///
+/// The rating matrix is approximated by `U V^T`, where each row of `U` (or `V`) holds the latent
+/// factors of a user (or item). The factors minimise the squared error on the known ratings plus
+/// `regularization` times the squared norms of the factors. Each iteration solves a ridge
+/// regression for every user with the item factors fixed, then for every item with the user
+/// factors fixed. Training stops once no factor changes by more than the tolerance in an
+/// iteration.
+///
+/// Users and items with no ratings have factors of zero, so are predicted a rating of zero.
+#[derive(Debug)]
+pub struct AlsFactorizer<T>
+where
+    T: RealField,
+{
+    /// The latent factors of each user (rows).
+    pub user_factors: Option<DMatrix<T>>,
+    /// The latent factors of each item (rows).
+    pub item_factors: Option<DMatrix<T>>,
+    n_factors: usize,
+    regularization: T,
+    convergence: ConvergenceConfig<T>,
+    diagnostics: Diagnostics,
+    seed: u64,
+    /// The items rated by each user in training, in increasing order.
+    rated_items: Vec<Vec<usize>>,
+}
+
+impl<T> AlsFactorizer<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(n_factors: usize, regularization: T) -> SLearningResult<Self> {
+        if n_factors == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Number of factors must be at least one.".to_string(),
+            ));
+        }
+        check_positive(regularization, "Regularization")?;
+        Ok(Self {
+            user_factors: None,
+            item_factors: None,
+            n_factors,
+            regularization,
+            convergence: ConvergenceConfig::new(100, nalgebra::convert(1e-6))
+                .expect("The default parameters are valid."),
+            diagnostics: Diagnostics::default(),
+            seed: 0,
+            rated_items: Vec::new(),
+        })
+    }
+
+    /// Set when the iterations stop, based on the largest change in any factor.
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+
+    /// Send warnings from training, e.g. that it stopped before converging, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// Set the seed for the random initial item factors.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    /// Solve for the factors of each row entity (user or item) given the factors of the other
+    /// entities, where `ratings[row]` holds the (other entity, rating) pairs for that row.
+    fn solve_factors(
+        &self,
+        ratings: &[Vec<(usize, T)>],
+        other_factors: &DMatrix<T>,
+    ) -> SLearningResult<DMatrix<T>> {
+        let mut factors = DMatrix::zeros(ratings.len(), self.n_factors);
+        for (row, row_ratings) in ratings.iter().enumerate() {
+            if row_ratings.is_empty() {
+                continue;
+            }
+            let mut gram = DMatrix::identity(self.n_factors, self.n_factors) * self.regularization;
+            let mut target = DVector::zeros(self.n_factors);
+            for &(other, rating) in row_ratings {
+                let other_row = other_factors.row(other).transpose();
+                gram += &other_row * other_row.transpose();
+                target.axpy(rating, &other_row, T::one());
+            }
+            let Some(cholesky) = gram.cholesky() else {
+                return Err(SLearningError::InvalidData(
+                    "The factors could not be solved for because the regularized Gram matrix is not positive definite. Try scaling the ratings."
+                        .to_string(),
+                ));
+            };
+            factors.set_row(row, &cholesky.solve(&target).transpose());
+        }
+        Ok(factors)
+    }
+
+    pub fn train(&mut self, ratings: &[(usize, usize, T)]) -> SLearningResult<()> {
+        if ratings.is_empty() {
+            return Err(SLearningError::InvalidData(
+                "Cannot train with zero ratings.".to_string(),
+            ));
+        }
+        let values = DVector::from_iterator(ratings.len(), ratings.iter().map(|&(_, _, r)| r));
+        check_finite_values(&values, "ratings")?;
+        // The factors have a row for every id up to the largest, so bound the ids by the size of
+        // the input to keep a stray id from sizing the tables.
+        for &(user, item, _) in ratings {
+            if user > ratings.len() || item > ratings.len() {
+                let error_msg = format!(
+                    "User and item ids cannot be greater than the number of ratings ({}).",
+                    ratings.len()
+                );
+                return Err(SLearningError::InvalidData(error_msg));
+            }
+        }
+        let num_users = ratings.iter().map(|&(user, _, _)| user).max().unwrap() + 1;
+        let num_items = ratings.iter().map(|&(_, item, _)| item).max().unwrap() + 1;
+        let mut by_user = vec![Vec::new(); num_users];
+        let mut by_item = vec![Vec::new(); num_items];
+        for &(user, item, rating) in ratings {
+            by_user[user].push((item, rating));
+            by_item[item].push((user, rating));
+        }
+
+        let mut rng = Rng::new(self.seed);
+        let scale: T = nalgebra::convert(1.0 / (self.n_factors as f64).sqrt());
+        let mut item_factors = DMatrix::from_fn(num_items, self.n_factors, |_, _| {
+            rng.standard_normal::<T>() * scale
+        });
+        let mut user_factors = DMatrix::zeros(num_users, self.n_factors);
+        let mut converged = false;
+        for _ in 0..self.convergence.max_iter() {
+            let new_user_factors = self.solve_factors(&by_user, &item_factors)?;
+            let new_item_factors = self.solve_factors(&by_item, &new_user_factors)?;
+            let change = (&new_user_factors - &user_factors)
+                .amax()
+                .max((&new_item_factors - &item_factors).amax());
+            user_factors = new_user_factors;
+            item_factors = new_item_factors;
+            if change <= self.convergence.tol() {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            self.diagnostics.warn(Warning::NotConverged {
+                max_iter: self.convergence.max_iter(),
+            });
+        }
+
+        self.rated_items = by_user
+            .iter()
+            .map(|user_ratings| {
+                let mut items: Vec<usize> = user_ratings.iter().map(|&(item, _)| item).collect();
+                items.sort_unstable();
+                items.dedup();
+                items
+            })
+            .collect();
+        self.user_factors = Some(user_factors);
+        self.item_factors = Some(item_factors);
+        Ok(())
+    }
+
+    fn factors(&self) -> SLearningResult<(&DMatrix<T>, &DMatrix<T>)> {
+        Ok((
+            check_fitted(&self.user_factors)?,
+            check_fitted(&self.item_factors)?,
+        ))
+    }
+
+    fn validate_index(kind: &str, index: usize, count: usize) -> SLearningResult<()> {
+        if index >= count {
+            let error_msg = format!(
+                "{} {} is out of range for a model with {} {}s.",
+                kind,
+                index,
+                count,
+                kind.to_lowercase()
+            );
+            return Err(SLearningError::InvalidParameters(error_msg));
+        }
+        Ok(())
+    }
+
+    /// The predicted rating of `item` by `user`.
+    pub fn predict(&self, user: usize, item: usize) -> SLearningResult<T> {
+        let (user_factors, item_factors) = self.factors()?;
+        Self::validate_index("User", user, user_factors.nrows())?;
+        Self::validate_index("Item", item, item_factors.nrows())?;
+        Ok(user_factors.row(user).dot(&item_factors.row(item)))
+    }
+
+    /// The `n` items with the highest predicted ratings for `user`, best first, leaving out the
+    /// items they rated in training. There may be fewer than `n` if there are not enough unrated
+    /// items.
+    pub fn recommend(&self, user: usize, n: usize) -> SLearningResult<Vec<(usize, T)>> {
+        let (user_factors, item_factors) = self.factors()?;
+        Self::validate_index("User", user, user_factors.nrows())?;
+        let scores = item_factors * user_factors.row(user).transpose();
+        let rated = &self.rated_items[user];
+        let mut candidates: Vec<(usize, T)> = scores
+            .iter()
+            .enumerate()
+            .filter(|(item, _)| rated.binary_search(item).is_err())
+            .map(|(item, &score)| (item, score))
+            .collect();
+        candidates.sort_by(|a, b| total_cmp(&b.1, &a.1).then(a.0.cmp(&b.0)));
+        candidates.truncate(n);
+        Ok(candidates)
+    }
+}
+
+impl<T> Default for AlsFactorizer<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self::new(10, nalgebra::convert(0.1)).expect("The default parameters are valid.")
+    }
+}
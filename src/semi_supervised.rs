@@ -0,0 +1,456 @@
+//! Semi-supervised classification, which learns from a few labelled observations together with
+//! many unlabelled ones.
+//!
+//! Labels are given as a slice with an entry for each observation, which is `None` for unlabelled
+//! observations.
+use crate::diagnostics::{Diagnostics, Warning};
+use crate::distance::Euclidean;
+use crate::kernel::{Kernel, Rbf};
+use crate::metrics::{binary_class_probabilities, min_expected_cost_classes};
+use crate::neighbors::NearestNeighbors;
+use crate::optim::ConvergenceConfig;
+use crate::traits::{BinaryPrediction, SupervisedModel};
+use crate::validation::{
+    check_2d_nonempty, check_finite, check_finite_values, check_fitted, check_open_unit_interval,
+};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+fn validate_partial_labels<T: RealField>(
+    inputs: &DMatrix<T>,
+    labels: &[Option<T>],
+) -> SLearningResult<()> {
+    check_2d_nonempty(inputs)?;
+    check_finite(inputs)?;
+    if inputs.nrows() != labels.len() {
+        let error_msg = format!(
+            "Input has {} observation(s), but there are {} label(s). These must be equal.",
+            inputs.nrows(),
+            labels.len()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    if labels.iter().all(|label| label.is_none()) {
+        return Err(SLearningError::InvalidData(
+            "There must be at least one labelled observation.".to_string(),
+        ));
+    }
+    let values = DVector::from_iterator(
+        labels.len(),
+        labels
+            .iter()
+            .map(|label| label.clone().unwrap_or_else(T::zero)),
+    );
+    check_finite_values(&values, "labels")
+}
+
+/// A binary classifier that trains another model on the labelled observations, then repeatedly
+/// labels the unlabelled observations it is most confident about and retrains.
+///
+/// The wrapped model is trained on zero/one labels and its predictions are used as the probability
+/// of the positive class (e.g. a regression model, or a classifier predicting probabilities). In
+/// each iteration, the unlabelled observations whose predicted probability of either class is at
+/// least `threshold` are given that class as a pseudo-label. Training stops when there are no
+/// unlabelled observations left, none are confident enough, or after `max_iter` iterations.
+/// Predictions are one for probabilities at least one half and zero otherwise.
+#[derive(Debug)]
+pub struct SelfTrainingClassifier<M, T>
+where
+    T: RealField,
+{
+    pub model: M,
+    /// The labels the model was finally trained on, including pseudo-labels, or `None` for
+    /// observations that were never labelled.
+    pub labels: Option<Vec<Option<T>>>,
+    /// The iteration in which each observation was labelled, which is zero for the observations
+    /// that were labelled to begin with.
+    pub labelled_iteration: Option<Vec<Option<usize>>>,
+    threshold: T,
+    max_iter: usize,
+}
+
+impl<M, T> SelfTrainingClassifier<M, T>
+where
+    M: SupervisedModel<T>,
+    T: RealField + Copy,
+{
+    pub fn new(model: M, threshold: T, max_iter: usize) -> SLearningResult<Self> {
+        if !threshold.is_finite() || threshold <= nalgebra::convert(0.5) || threshold > T::one() {
+            return Err(SLearningError::InvalidParameters(
+                "Threshold must be greater than one half and at most one.".to_string(),
+            ));
+        }
+        if max_iter == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Maximum number of iterations must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            model,
+            labels: None,
+            labelled_iteration: None,
+            threshold,
+            max_iter,
+        })
+    }
+
+    pub fn train(&mut self, inputs: DMatrix<T>, labels: &[Option<T>]) -> SLearningResult<()> {
+        validate_partial_labels(&inputs, labels)?;
+        if labels
+            .iter()
+            .flatten()
+            .any(|label| !label.is_zero() && !label.is_one())
+        {
+            return Err(SLearningError::InvalidData(
+                "Binary labels must be zero or one.".to_string(),
+            ));
+        }
+        self.labels = None;
+        self.labelled_iteration = None;
+
+        let mut labels = labels.to_vec();
+        let mut labelled_iteration: Vec<Option<usize>> =
+            labels.iter().map(|label| label.map(|_| 0)).collect();
+        let half: T = nalgebra::convert(0.5);
+        for iteration in 1..=self.max_iter {
+            let labelled: Vec<usize> = (0..labels.len()).filter(|&i| labels[i].is_some()).collect();
+            let outputs = DVector::from_iterator(
+                labelled.len(),
+                labelled.iter().map(|&i| labels[i].unwrap()),
+            );
+            self.model
+                .train(inputs.select_rows(labelled.iter()), outputs)?;
+
+            let unlabelled: Vec<usize> =
+                (0..labels.len()).filter(|&i| labels[i].is_none()).collect();
+            if unlabelled.is_empty() || iteration == self.max_iter {
+                break;
+            }
+            let probabilities = self.model.predict(&inputs.select_rows(unlabelled.iter()))?;
+            let mut num_added = 0;
+            for (&i, &probability) in unlabelled.iter().zip(probabilities.iter()) {
+                if probability.max(T::one() - probability) >= self.threshold {
+                    labels[i] = Some(match probability >= half {
+                        true => T::one(),
+                        false => T::zero(),
+                    });
+                    labelled_iteration[i] = Some(iteration);
+                    num_added += 1;
+                }
+            }
+            if num_added == 0 {
+                break;
+            }
+        }
+        self.labels = Some(labels);
+        self.labelled_iteration = Some(labelled_iteration);
+        Ok(())
+    }
+
+    /// The predicted probability of the positive class, from the wrapped model.
+    pub fn predict_proba(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        check_fitted(&self.labels)?;
+        self.model.predict(inputs)
+    }
+
+    pub fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        Ok(self.predict_with_scores(inputs)?.labels)
+    }
+
+    /// The predicted labels, with the probabilities of the positive class that they were
+    /// thresholded from.
+    pub fn predict_with_scores(&self, inputs: &DMatrix<T>) -> SLearningResult<BinaryPrediction<T>> {
+        let probabilities = self.predict_proba(inputs)?;
+        Ok(BinaryPrediction::from_scores(
+            probabilities,
+            nalgebra::convert(0.5),
+        ))
+    }
+
+    /// The class of each observation that minimises the expected cost under the predicted
+    /// probabilities, given the cost of predicting each true class (rows) as each class
+    /// (columns), with the negative class first. See [`min_expected_cost_classes`].
+    pub fn predict_min_cost(
+        &self,
+        inputs: &DMatrix<T>,
+        cost_matrix: &DMatrix<T>,
+    ) -> SLearningResult<DVector<T>> {
+        let probabilities = binary_class_probabilities(&self.predict_proba(inputs)?);
+        min_expected_cost_classes(&probabilities, cost_matrix)
+    }
+}
+
+/// How the similarity between observations is measured to build the graph for
+/// [`LabelPropagation`].
+#[derive(Debug, Clone, Copy)]
+pub enum Affinity<T> {
+    /// A fully connected graph, weighted by the radial basis function kernel.
+    Rbf(Rbf<T>),
+    /// A graph connecting each observation to its `k` nearest neighbours (by Euclidean distance),
+    /// with weights of one.
+    KNearestNeighbors(usize),
+}
+
+impl<T> Affinity<T>
+where
+    T: RealField + Copy,
+{
+    /// The symmetric affinity matrix between the training observations, without self-loops.
+    fn graph(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let mut weights = match self {
+            Affinity::Rbf(kernel) => kernel.gram_matrix(inputs),
+            Affinity::KNearestNeighbors(k) => {
+                let neighbors =
+                    NearestNeighbors::new(inputs.clone(), Euclidean)?.query_indexed(*k)?;
+                let mut weights = DMatrix::zeros(inputs.nrows(), inputs.nrows());
+                for (i, indices) in neighbors.indices.iter().enumerate() {
+                    for &j in indices {
+                        weights[(i, j)] = T::one();
+                    }
+                }
+                let half: T = nalgebra::convert(0.5);
+                (&weights + weights.transpose()) * half
+            }
+        };
+        weights.fill_diagonal(T::zero());
+        Ok(weights)
+    }
+
+    /// The affinity between each query (rows) and each training observation (columns).
+    fn between(&self, queries: &DMatrix<T>, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        match self {
+            Affinity::Rbf(kernel) => kernel.matrix(queries, inputs),
+            Affinity::KNearestNeighbors(k) => {
+                let neighbors =
+                    NearestNeighbors::new(inputs.clone(), Euclidean)?.query(queries, *k)?;
+                let mut weights = DMatrix::zeros(queries.nrows(), inputs.nrows());
+                for (i, indices) in neighbors.indices.iter().enumerate() {
+                    for &j in indices {
+                        weights[(i, j)] = T::one();
+                    }
+                }
+                Ok(weights)
+            }
+        }
+    }
+}
+
+/// Scale each row to sum to one, or to be uniform if it sums to zero.
+fn normalize_rows<T: RealField + Copy>(matrix: &mut DMatrix<T>) {
+    let uniform = T::one() / nalgebra::convert(matrix.ncols() as f64);
+    for mut row in matrix.row_iter_mut() {
+        let total = row.sum();
+        if total.is_zero() {
+            row.fill(uniform);
+        } else {
+            row /= total;
+        }
+    }
+}
+
+/// Graph-based semi-supervised classification, which spreads the known labels along a graph of
+/// similar observations.
+///
+/// Labelled classes are numbered from zero, in the same type as the inputs. Each observation has a
+/// distribution over the classes, which starts as a one-hot vector for labelled observations. In
+/// label propagation (the default), each iteration replaces every distribution with the weighted
+/// average of its neighbours' distributions, then resets the labelled observations to their labels
+/// (Zhu & Ghahramani, 2002). In label spreading, the labelled observations are not reset, and
+/// instead the distributions are pulled back towards the initial labels, which is more robust to
+/// mislabelled observations (Zhou et al., 2004).
+///
+/// Observations that are not connected to any labelled observation get a uniform distribution.
+/// New observations are classified by the weighted average of the distributions of the training
+/// observations, using the same affinity.
+#[derive(Debug)]
+pub struct LabelPropagation<T>
+where
+    T: RealField,
+{
+    /// The distribution over the classes (columns) of each training observation (rows).
+    pub label_distributions: Option<DMatrix<T>>,
+    /// The most probable class of each training observation.
+    pub transduction: Option<DVector<T>>,
+    affinity: Affinity<T>,
+    /// The weight of the neighbours' distributions, if using label spreading.
+    spreading: Option<T>,
+    convergence: ConvergenceConfig<T>,
+    diagnostics: Diagnostics,
+    inputs: Option<DMatrix<T>>,
+}
+
+impl<T> LabelPropagation<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(affinity: Affinity<T>) -> SLearningResult<Self> {
+        if let Affinity::KNearestNeighbors(0) = affinity {
+            return Err(SLearningError::InvalidParameters(
+                "Number of neighbours must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self {
+            label_distributions: None,
+            transduction: None,
+            affinity,
+            spreading: None,
+            convergence: ConvergenceConfig::default(),
+            diagnostics: Diagnostics::default(),
+            inputs: None,
+        })
+    }
+
+    /// Use label spreading, where each iteration gives weight `alpha` to the neighbours'
+    /// distributions and `1 - alpha` to the initial labels.
+    pub fn with_spreading(self, alpha: T) -> SLearningResult<Self> {
+        check_open_unit_interval(alpha, "Alpha")?;
+        Ok(Self {
+            spreading: Some(alpha),
+            ..self
+        })
+    }
+
+    /// Set when the iterations stop, based on the largest change in any distribution.
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+
+    /// Send warnings from training, e.g. that it stopped before converging, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    pub fn train(&mut self, inputs: DMatrix<T>, labels: &[Option<T>]) -> SLearningResult<()> {
+        validate_partial_labels(&inputs, labels)?;
+        // Classes are numbered without gaps, so each is below the number of labelled observations.
+        let num_labelled = labels.iter().flatten().count();
+        let mut classes = vec![None; labels.len()];
+        for (i, label) in labels.iter().enumerate() {
+            if let Some(label) = *label {
+                if label.is_negative() || label.round() != label {
+                    return Err(SLearningError::InvalidData(
+                        "Class labels must be non-negative integers.".to_string(),
+                    ));
+                }
+                if label >= nalgebra::convert(num_labelled as f64) {
+                    let error_msg = format!(
+                        "Class labels must be less than the number of labelled observations ({}).",
+                        num_labelled
+                    );
+                    return Err(SLearningError::InvalidData(error_msg));
+                }
+                let class =
+                    nalgebra::try_convert::<T, f64>(label).expect("The label is an integer.");
+                classes[i] = Some(class as usize);
+            }
+        }
+        let num_classes = classes.iter().flatten().max().map_or(0, |&max| max + 1);
+        if let Some(empty) = (0..num_classes).find(|class| !classes.contains(&Some(*class))) {
+            let error_msg = format!(
+                "Class {} has no observations. Classes must be numbered from zero without gaps.",
+                empty
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+
+        let weights = self.affinity.graph(&inputs)?;
+        let degrees = weights.column_sum();
+        if let Some(isolated) = degrees.iter().position(|degree| degree.is_zero()) {
+            let error_msg = format!(
+                "Observation {} has no neighbours in the affinity graph.",
+                isolated
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        let num_obs = inputs.nrows();
+        let transition = match self.spreading {
+            // The symmetrically normalised graph, `D^-1/2 W D^-1/2`.
+            Some(_) => DMatrix::from_fn(num_obs, num_obs, |i, j| {
+                weights[(i, j)] / (degrees[i] * degrees[j]).sqrt()
+            }),
+            // The random walk transition matrix, `D^-1 W`.
+            None => DMatrix::from_fn(num_obs, num_obs, |i, j| weights[(i, j)] / degrees[i]),
+        };
+        let mut initial = DMatrix::zeros(num_obs, num_classes);
+        for (i, class) in classes.iter().enumerate() {
+            if let Some(class) = *class {
+                initial[(i, class)] = T::one();
+            }
+        }
+
+        let mut distributions = initial.clone();
+        let mut converged = false;
+        for _ in 0..self.convergence.max_iter() {
+            let mut updated = &transition * &distributions;
+            match self.spreading {
+                Some(alpha) => {
+                    updated *= alpha;
+                    updated += &initial * (T::one() - alpha);
+                }
+                None => {
+                    for (i, class) in classes.iter().enumerate() {
+                        if class.is_some() {
+                            updated.set_row(i, &initial.row(i));
+                        }
+                    }
+                }
+            }
+            let change = (&updated - &distributions).amax();
+            distributions = updated;
+            if change <= self.convergence.tol() {
+                converged = true;
+                break;
+            }
+        }
+        if !converged {
+            self.diagnostics.warn(Warning::NotConverged {
+                max_iter: self.convergence.max_iter(),
+            });
+        }
+        normalize_rows(&mut distributions);
+
+        self.transduction = Some(DVector::from_fn(num_obs, |i, _| {
+            let (class, _) = distributions.row(i).transpose().argmax();
+            nalgebra::convert(class as f64)
+        }));
+        self.label_distributions = Some(distributions);
+        self.inputs = Some(inputs);
+        Ok(())
+    }
+
+    /// The probability of each class (columns) for each observation (rows).
+    pub fn predict_proba(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let training_inputs = check_fitted(&self.inputs)?;
+        let distributions = check_fitted(&self.label_distributions)?;
+        let weights = self.affinity.between(inputs, training_inputs)?;
+        let mut probabilities = weights * distributions;
+        normalize_rows(&mut probabilities);
+        Ok(probabilities)
+    }
+
+    /// The most probable class of each observation.
+    pub fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let probabilities = self.predict_proba(inputs)?;
+        Ok(DVector::from_fn(inputs.nrows(), |i, _| {
+            let (class, _) = probabilities.row(i).transpose().argmax();
+            nalgebra::convert(class as f64)
+        }))
+    }
+
+    /// The class of each observation that minimises the expected cost under the predicted
+    /// probabilities, given the cost of predicting each true class (rows) as each class
+    /// (columns). See [`min_expected_cost_classes`].
+    pub fn predict_min_cost(
+        &self,
+        inputs: &DMatrix<T>,
+        cost_matrix: &DMatrix<T>,
+    ) -> SLearningResult<DVector<T>> {
+        min_expected_cost_classes(&self.predict_proba(inputs)?, cost_matrix)
+    }
+}
@@ -0,0 +1,179 @@
+//! Special functions needed by the statistical parts of the crate.
+//!
+//! These work with `f64`, since they are only used for things like thresholds and p-values, rather
+//! than for the model parameters themselves.
+
+/// Natural logarithm of the gamma function, using the Lanczos approximation.
+pub(crate) fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 6] = [
+        76.18009172947146,
+        -86.50532032941677,
+        24.01409824083091,
+        -1.231739572450155,
+        0.1208650973866179e-2,
+        -0.5395239384953e-5,
+    ];
+    let tmp = x + 5.5;
+    let tmp = tmp - (x + 0.5) * tmp.ln();
+    let mut series = 1.000000000190015;
+    for (i, coefficient) in COEFFICIENTS.iter().enumerate() {
+        series += coefficient / (x + 1.0 + i as f64);
+    }
+    -tmp + (2.5066282746310005 * series / x).ln()
+}
+
+/// Regularized lower incomplete gamma function, P(a, x).
+pub(crate) fn regularized_lower_gamma(a: f64, x: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 500;
+    const EPSILON: f64 = 1e-15;
+
+    if x <= 0.0 {
+        return 0.0;
+    }
+    let log_prefactor = -x + a * x.ln() - ln_gamma(a);
+    if x < a + 1.0 {
+        // Series representation.
+        let mut term = 1.0 / a;
+        let mut sum = term;
+        for n in 1..MAX_ITERATIONS {
+            term *= x / (a + n as f64);
+            sum += term;
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+        (sum.ln() + log_prefactor).exp().min(1.0)
+    } else {
+        // Continued fraction representation of Q(a, x), using Lentz's method.
+        let tiny = f64::MIN_POSITIVE / EPSILON;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for n in 1..MAX_ITERATIONS {
+            let an = -(n as f64) * (n as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            if d.abs() < tiny {
+                d = tiny;
+            }
+            c = b + an / c;
+            if c.abs() < tiny {
+                c = tiny;
+            }
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < EPSILON {
+                break;
+            }
+        }
+        (1.0 - (log_prefactor + h.ln()).exp()).max(0.0)
+    }
+}
+
+/// Cumulative distribution function of the chi-squared distribution.
+pub(crate) fn chi_squared_cdf(x: f64, degrees_of_freedom: f64) -> f64 {
+    regularized_lower_gamma(degrees_of_freedom / 2.0, x / 2.0)
+}
+
+/// Quantile function of the chi-squared distribution, for a probability in (0, 1).
+pub(crate) fn chi_squared_quantile(probability: f64, degrees_of_freedom: f64) -> f64 {
+    invert_increasing(probability, 0.0, degrees_of_freedom.max(1.0), |x| {
+        chi_squared_cdf(x, degrees_of_freedom)
+    })
+}
+
+/// Find `x >= lower` such that `cdf(x) == probability`, where `cdf` is increasing.
+///
+/// The upper end of the search starts at `upper` and is doubled until it brackets the solution.
+fn invert_increasing<F>(probability: f64, lower: f64, upper: f64, cdf: F) -> f64
+where
+    F: Fn(f64) -> f64,
+{
+    let mut lower = lower;
+    let mut upper = upper;
+    while cdf(upper) < probability {
+        lower = upper;
+        upper *= 2.0;
+    }
+    for _ in 0..200 {
+        let middle = 0.5 * (lower + upper);
+        if cdf(middle) < probability {
+            lower = middle;
+        } else {
+            upper = middle;
+        }
+        if upper - lower <= 1e-12 * upper.max(1.0) {
+            break;
+        }
+    }
+    0.5 * (lower + upper)
+}
+
+/// Regularized incomplete beta function, I_x(a, b).
+pub(crate) fn regularized_incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let prefactor =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The continued fraction converges quickly below the mean, so use the symmetry
+    // `I_x(a, b) = 1 - I_{1-x}(b, a)` above it.
+    if x < (a + 1.0) / (a + b + 2.0) {
+        prefactor * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - prefactor * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// The continued fraction for the incomplete beta function, using Lentz's method.
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const MAX_ITERATIONS: usize = 500;
+    const EPSILON: f64 = 1e-15;
+
+    let tiny = f64::MIN_POSITIVE / EPSILON;
+    let clamp = |value: f64| if value.abs() < tiny { tiny } else { value };
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut h = d;
+    for m in 1..MAX_ITERATIONS {
+        let m = m as f64;
+        let even = m * (b - m) * x / ((a + 2.0 * m - 1.0) * (a + 2.0 * m));
+        d = 1.0 / clamp(1.0 + even * d);
+        c = clamp(1.0 + even / c);
+        h *= d * c;
+        let odd = -(a + m) * (a + b + m) * x / ((a + 2.0 * m) * (a + 2.0 * m + 1.0));
+        d = 1.0 / clamp(1.0 + odd * d);
+        c = clamp(1.0 + odd / c);
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+/// The probability that Student's t distribution is at least `|t|` in absolute value.
+pub(crate) fn students_t_two_sided(t: f64, degrees_of_freedom: f64) -> f64 {
+    regularized_incomplete_beta(
+        degrees_of_freedom / 2.0,
+        0.5,
+        degrees_of_freedom / (degrees_of_freedom + t * t),
+    )
+}
+
+/// Cumulative distribution function of the standard normal distribution.
+pub(crate) fn standard_normal_cdf(x: f64) -> f64 {
+    // `erf(z) = P(1/2, z^2)` for `z >= 0`.
+    let half_erf = 0.5 * regularized_lower_gamma(0.5, x * x / 2.0);
+    if x >= 0.0 {
+        0.5 + half_erf
+    } else {
+        0.5 - half_erf
+    }
+}
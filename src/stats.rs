@@ -0,0 +1,419 @@
+//! Descriptive statistics of the columns (variables) of a matrix.
+//!
+//! The functions summarise a whole matrix at once. The online accumulators instead take the
+//! observations one at a time (or a batch at a time), using Welford's algorithm and its parallel
+//! generalisation by Chan et al., so the data never has to be held in memory at once.
+use crate::utils::total_cmp;
+use crate::validation::{check_finite, check_finite_values};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+fn validate_observations<T: RealField>(inputs: &DMatrix<T>) -> SLearningResult<()> {
+    if inputs.nrows() == 0 {
+        return Err(SLearningError::InvalidData(
+            "Cannot compute statistics with zero observations.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_ddof(count: usize, ddof: usize) -> SLearningResult<()> {
+    if ddof >= count {
+        let error_msg = format!(
+            "There are {} observation(s), which must be greater than the delta degrees of freedom ({}).",
+            count, ddof
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(())
+}
+
+fn from_usize<T: RealField>(value: usize) -> T {
+    nalgebra::convert(value as f64)
+}
+
+/// The mean of each column.
+pub fn mean<T: RealField + Copy>(inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+    validate_observations(inputs)?;
+    Ok(inputs.row_mean().transpose())
+}
+
+/// The variance of each column, with `ddof` delta degrees of freedom (so the sum of squares is
+/// divided by `n - ddof`).
+pub fn var<T: RealField + Copy>(inputs: &DMatrix<T>, ddof: usize) -> SLearningResult<DVector<T>> {
+    let means = mean(inputs)?;
+    validate_ddof(inputs.nrows(), ddof)?;
+    let divisor: T = from_usize(inputs.nrows() - ddof);
+    Ok(DVector::from_fn(inputs.ncols(), |j, _| {
+        inputs
+            .column(j)
+            .iter()
+            .fold(T::zero(), |acc, &x| acc + (x - means[j]) * (x - means[j]))
+            / divisor
+    }))
+}
+
+/// The standard deviation of each column, with `ddof` delta degrees of freedom.
+pub fn std<T: RealField + Copy>(inputs: &DMatrix<T>, ddof: usize) -> SLearningResult<DVector<T>> {
+    Ok(var(inputs, ddof)?.map(|variance| variance.sqrt()))
+}
+
+/// The minimum of each column.
+pub fn min<T: RealField + Copy>(inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+    validate_observations(inputs)?;
+    Ok(inputs
+        .row_iter()
+        .skip(1)
+        .fold(inputs.row(0).transpose(), |acc, row| {
+            acc.zip_map(&row.transpose(), |a, b| a.min(b))
+        }))
+}
+
+/// The maximum of each column.
+pub fn max<T: RealField + Copy>(inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+    validate_observations(inputs)?;
+    Ok(inputs
+        .row_iter()
+        .skip(1)
+        .fold(inputs.row(0).transpose(), |acc, row| {
+            acc.zip_map(&row.transpose(), |a, b| a.max(b))
+        }))
+}
+
+/// The `q`-th quantile of sorted values, linearly interpolating between the closest values.
+pub(crate) fn sorted_quantile<T: RealField + Copy>(sorted: &[T], q: T) -> T {
+    let position = q * from_usize::<T>(sorted.len() - 1);
+    let lower = nalgebra::try_convert::<T, f64>(position.floor()).unwrap_or(0.0) as usize;
+    let upper = (lower + 1).min(sorted.len() - 1);
+    let fraction = position - from_usize(lower);
+    sorted[lower] + (sorted[upper] - sorted[lower]) * fraction
+}
+
+/// The median of some finite values, of which there must be at least one.
+pub(crate) fn median<T: RealField + Copy>(values: &DVector<T>) -> T {
+    let mut sorted: Vec<T> = values.iter().copied().collect();
+    sorted.sort_by(total_cmp);
+    sorted_quantile(&sorted, nalgebra::convert(0.5))
+}
+
+/// The `q`-th quantile of each column, for `q` between zero and one.
+///
+/// This linearly interpolates between the closest observations, so `q = 0.5` gives the median.
+pub fn quantile<T: RealField + Copy>(inputs: &DMatrix<T>, q: T) -> SLearningResult<DVector<T>> {
+    if q < T::zero() || q > T::one() {
+        return Err(SLearningError::InvalidParameters(
+            "Quantile must be between zero and one.".to_string(),
+        ));
+    }
+    validate_observations(inputs)?;
+    check_finite(inputs)?;
+    Ok(DVector::from_fn(inputs.ncols(), |j, _| {
+        let mut sorted: Vec<T> = inputs.column(j).iter().copied().collect();
+        sorted.sort_by(total_cmp);
+        sorted_quantile(&sorted, q)
+    }))
+}
+
+fn validate_num_vars(expected: usize, actual: usize) -> SLearningResult<()> {
+    if expected != actual {
+        let error_msg = format!(
+            "The accumulator has {} variables, but the observation(s) have {} variables. These must be equal.",
+            expected, actual
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(())
+}
+
+/// Online accumulator of the mean and variance of each variable.
+#[derive(Debug, Clone)]
+pub struct OnlineMeanVariance<T>
+where
+    T: RealField,
+{
+    count: usize,
+    mean: DVector<T>,
+    /// Sum of squared deviations from the mean.
+    sum_squares: DVector<T>,
+}
+
+impl<T> OnlineMeanVariance<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(num_vars: usize) -> Self {
+        Self {
+            count: 0,
+            mean: DVector::zeros(num_vars),
+            sum_squares: DVector::zeros(num_vars),
+        }
+    }
+
+    /// Add a single observation.
+    pub fn update(&mut self, observation: &DVector<T>) -> SLearningResult<()> {
+        validate_num_vars(self.mean.len(), observation.len())?;
+        self.count += 1;
+        let delta = observation - &self.mean;
+        self.mean += &delta / from_usize::<T>(self.count);
+        self.sum_squares += delta.component_mul(&(observation - &self.mean));
+        Ok(())
+    }
+
+    /// Add a batch of observations (one per row).
+    pub fn update_batch(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        validate_num_vars(self.mean.len(), inputs.ncols())?;
+        if inputs.nrows() == 0 {
+            return Ok(());
+        }
+        let batch_mean = inputs.row_mean().transpose();
+        let batch_sum_squares = DVector::from_fn(inputs.ncols(), |j, _| {
+            inputs.column(j).iter().fold(T::zero(), |acc, &x| {
+                acc + (x - batch_mean[j]) * (x - batch_mean[j])
+            })
+        });
+        self.combine(inputs.nrows(), &batch_mean, &batch_sum_squares);
+        Ok(())
+    }
+
+    fn combine(
+        &mut self,
+        other_count: usize,
+        other_mean: &DVector<T>,
+        other_sum_squares: &DVector<T>,
+    ) {
+        let total = self.count + other_count;
+        let weight: T = from_usize::<T>(other_count) / from_usize(total);
+        let cross_weight: T =
+            from_usize::<T>(self.count) * from_usize::<T>(other_count) / from_usize(total);
+        let delta = other_mean - &self.mean;
+        self.mean += &delta * weight;
+        self.sum_squares += other_sum_squares + delta.component_mul(&delta) * cross_weight;
+        self.count = total;
+    }
+
+    /// Add the observations summarised by `other`, e.g. accumulated on another shard of the data.
+    pub fn merge(&mut self, other: &Self) -> SLearningResult<()> {
+        validate_num_vars(self.mean.len(), other.mean.len())?;
+        if other.count > 0 {
+            self.combine(other.count, &other.mean, &other.sum_squares);
+        }
+        Ok(())
+    }
+
+    /// The number of observations seen so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn mean(&self) -> SLearningResult<DVector<T>> {
+        validate_ddof(self.count, 0)?;
+        Ok(self.mean.clone())
+    }
+
+    /// The variance of each variable, with `ddof` delta degrees of freedom.
+    pub fn var(&self, ddof: usize) -> SLearningResult<DVector<T>> {
+        validate_ddof(self.count, ddof)?;
+        Ok(&self.sum_squares / from_usize::<T>(self.count - ddof))
+    }
+
+    /// The standard deviation of each variable, with `ddof` delta degrees of freedom.
+    pub fn std(&self, ddof: usize) -> SLearningResult<DVector<T>> {
+        Ok(self.var(ddof)?.map(|variance| variance.sqrt()))
+    }
+}
+
+/// Online accumulator of the mean and covariance matrix of the variables.
+#[derive(Debug, Clone)]
+pub struct OnlineCovariance<T>
+where
+    T: RealField,
+{
+    count: usize,
+    mean: DVector<T>,
+    /// Sum of the outer products of the deviations from the mean.
+    comoments: DMatrix<T>,
+}
+
+impl<T> OnlineCovariance<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(num_vars: usize) -> Self {
+        Self {
+            count: 0,
+            mean: DVector::zeros(num_vars),
+            comoments: DMatrix::zeros(num_vars, num_vars),
+        }
+    }
+
+    /// Add a single observation.
+    pub fn update(&mut self, observation: &DVector<T>) -> SLearningResult<()> {
+        validate_num_vars(self.mean.len(), observation.len())?;
+        self.count += 1;
+        let delta = observation - &self.mean;
+        self.mean += &delta / from_usize::<T>(self.count);
+        self.comoments += &delta * (observation - &self.mean).transpose();
+        Ok(())
+    }
+
+    /// Add a batch of observations (one per row).
+    pub fn update_batch(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()> {
+        validate_num_vars(self.mean.len(), inputs.ncols())?;
+        if inputs.nrows() == 0 {
+            return Ok(());
+        }
+        let batch_mean: DVector<T> = inputs.row_mean().transpose();
+        let centered = DMatrix::from_fn(inputs.nrows(), inputs.ncols(), |i, j| {
+            inputs[(i, j)] - batch_mean[j]
+        });
+        let batch_comoments = centered.transpose() * &centered;
+        self.combine(inputs.nrows(), &batch_mean, &batch_comoments);
+        Ok(())
+    }
+
+    fn combine(
+        &mut self,
+        other_count: usize,
+        other_mean: &DVector<T>,
+        other_comoments: &DMatrix<T>,
+    ) {
+        let total = self.count + other_count;
+        let weight: T = from_usize::<T>(other_count) / from_usize(total);
+        let cross_weight: T =
+            from_usize::<T>(self.count) * from_usize::<T>(other_count) / from_usize(total);
+        let delta = other_mean - &self.mean;
+        self.mean += &delta * weight;
+        self.comoments += other_comoments + &delta * delta.transpose() * cross_weight;
+        self.count = total;
+    }
+
+    /// Add the observations summarised by `other`, e.g. accumulated on another shard of the data.
+    pub fn merge(&mut self, other: &Self) -> SLearningResult<()> {
+        validate_num_vars(self.mean.len(), other.mean.len())?;
+        if other.count > 0 {
+            self.combine(other.count, &other.mean, &other.comoments);
+        }
+        Ok(())
+    }
+
+    /// The number of observations seen so far.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    pub fn mean(&self) -> SLearningResult<DVector<T>> {
+        validate_ddof(self.count, 0)?;
+        Ok(self.mean.clone())
+    }
+
+    /// The covariance matrix, with `ddof` delta degrees of freedom.
+    pub fn covariance(&self, ddof: usize) -> SLearningResult<DMatrix<T>> {
+        validate_ddof(self.count, ddof)?;
+        Ok(&self.comoments / from_usize::<T>(self.count - ddof))
+    }
+}
+
+fn validate_pair<T: RealField>(x: &DVector<T>, y: &DVector<T>) -> SLearningResult<()> {
+    if x.len() != y.len() {
+        let error_msg = format!(
+            "The first variable has {} observation(s), but the second variable has {} observation(s). These must be equal.",
+            x.len(),
+            y.len()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    if x.len() < 2 {
+        return Err(SLearningError::InvalidData(
+            "Cannot compute a correlation with fewer than two observations.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Pearson correlation of two variables whose lengths have been validated.
+fn pearson<T: RealField + Copy>(x: &DVector<T>, y: &DVector<T>) -> SLearningResult<T> {
+    let x_centered = x.add_scalar(-x.mean());
+    let y_centered = y.add_scalar(-y.mean());
+    let norms = x_centered.norm() * y_centered.norm();
+    if norms.is_zero() {
+        return Err(SLearningError::InvalidData(
+            "Cannot compute a correlation with a variable that has zero variance.".to_string(),
+        ));
+    }
+    // Clamp to remove any rounding error outside the valid range.
+    Ok((x_centered.dot(&y_centered) / norms).clamp(-T::one(), T::one()))
+}
+
+/// The ranks (starting from one) of `values`, where tied values are given the average of the ranks
+/// they span. The values must be finite.
+pub(crate) fn average_ranks<T: RealField + Copy>(values: &[T]) -> Vec<T> {
+    let mut order: Vec<usize> = (0..values.len()).collect();
+    order.sort_by(|&a, &b| total_cmp(&values[a], &values[b]));
+    let mut ranks = vec![T::zero(); values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        // Positions `start..end` are tied, and span the ranks `start + 1..=end`.
+        let rank: T = from_usize::<T>(start + 1 + end) / nalgebra::convert(2.0);
+        for &index in &order[start..end] {
+            ranks[index] = rank;
+        }
+        start = end;
+    }
+    ranks
+}
+
+/// Pearson (linear) correlation coefficient of two variables.
+pub fn pearson_corr<T: RealField + Copy>(x: &DVector<T>, y: &DVector<T>) -> SLearningResult<T> {
+    validate_pair(x, y)?;
+    pearson(x, y)
+}
+
+/// Spearman (rank) correlation coefficient of two variables.
+///
+/// This is the Pearson correlation of the ranks, so it measures how well the relationship is
+/// described by any monotonic function. Tied values are given their average rank.
+pub fn spearman_corr<T: RealField + Copy>(x: &DVector<T>, y: &DVector<T>) -> SLearningResult<T> {
+    validate_pair(x, y)?;
+    check_finite_values(x, "values of the first variable")?;
+    check_finite_values(y, "values of the second variable")?;
+    let x_ranks = DVector::from_vec(average_ranks(x.as_slice()));
+    let y_ranks = DVector::from_vec(average_ranks(y.as_slice()));
+    pearson(&x_ranks, &y_ranks)
+}
+
+/// The matrix of Pearson correlations between each pair of columns.
+pub fn correlation_matrix<T: RealField + Copy>(inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+    if inputs.nrows() < 2 {
+        return Err(SLearningError::InvalidData(
+            "Cannot compute a correlation with fewer than two observations.".to_string(),
+        ));
+    }
+    let means = mean(inputs)?;
+    let mut centered = DMatrix::from_fn(inputs.nrows(), inputs.ncols(), |i, j| {
+        inputs[(i, j)] - means[j]
+    });
+    for (j, mut column) in centered.column_iter_mut().enumerate() {
+        let norm = column.norm();
+        if norm.is_zero() {
+            let error_msg = format!(
+                "Cannot compute a correlation with a variable that has zero variance (column {}).",
+                j
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        column /= norm;
+    }
+    let mut correlations = centered.transpose() * &centered;
+    for (i, j) in (0..inputs.ncols()).flat_map(|i| (0..inputs.ncols()).map(move |j| (i, j))) {
+        correlations[(i, j)] = if i == j {
+            T::one()
+        } else {
+            correlations[(i, j)].clamp(-T::one(), T::one())
+        };
+    }
+    Ok(correlations)
+}
@@ -0,0 +1,518 @@
+//! Models for the time until an event (survival analysis).
+//!
+//! Each observation has a duration and an event indicator, which is one if the event was observed
+//! at the end of the duration and zero if the observation was censored (the event had not happened
+//! by the end of the duration).
+use crate::diagnostics::Diagnostics;
+use crate::optim::{minimize_lbfgs, ConvergenceConfig, Objective};
+use crate::special::{chi_squared_cdf, chi_squared_quantile};
+use crate::validation::{
+    check_2d_nonempty, check_finite, check_finite_values, check_fitted, check_num_vars,
+    check_open_unit_interval,
+};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+fn validate_survival_data<T: RealField>(
+    durations: &DVector<T>,
+    events: &DVector<T>,
+) -> SLearningResult<()> {
+    check_2d_nonempty(durations)?;
+    if durations.len() != events.len() {
+        let error_msg = format!(
+            "There are {} duration(s), but {} event indicator(s). These must be equal.",
+            durations.len(),
+            events.len()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    check_finite_values(durations, "durations")?;
+    if durations.iter().any(|duration| duration.is_negative()) {
+        return Err(SLearningError::InvalidData(
+            "Durations cannot be negative.".to_string(),
+        ));
+    }
+    if events
+        .iter()
+        .any(|event| !event.is_zero() && !event.is_one())
+    {
+        return Err(SLearningError::InvalidData(
+            "Event indicators must be zero or one.".to_string(),
+        ));
+    }
+    if events.iter().all(|event| event.is_zero()) {
+        return Err(SLearningError::InvalidData(
+            "There must be at least one event.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// The order of the observations from the longest duration to the shortest.
+fn descending_duration_order<T: RealField + Copy>(durations: &DVector<T>) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..durations.len()).collect();
+    order.sort_by(|&i, &j| durations[j].partial_cmp(&durations[i]).unwrap());
+    order
+}
+
+/// How to handle events at the same time in the partial likelihood of a [`CoxPhModel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TiesMethod {
+    /// Breslow's approximation, which treats every tied event as having the whole risk set.
+    Breslow,
+    /// Efron's approximation, which removes the tied events from the risk set gradually. This is
+    /// more accurate than Breslow's when there are many ties.
+    Efron,
+}
+
+/// The log partial likelihood of a Cox model, with its gradient and (optionally) Hessian.
+struct PartialLikelihood<'a, T>
+where
+    T: RealField,
+{
+    inputs: &'a DMatrix<T>,
+    durations: &'a DVector<T>,
+    events: &'a DVector<T>,
+    ties: TiesMethod,
+}
+
+impl<T> PartialLikelihood<'_, T>
+where
+    T: RealField + Copy,
+{
+    fn evaluate(
+        &self,
+        coefficients: &DVector<T>,
+        with_hessian: bool,
+    ) -> (T, DVector<T>, Option<DMatrix<T>>) {
+        let num_vars = self.inputs.ncols();
+        let linear_predictors = self.inputs * coefficients;
+        let order = descending_duration_order(self.durations);
+
+        let mut log_likelihood = T::zero();
+        let mut gradient = DVector::zeros(num_vars);
+        let mut hessian = with_hessian.then(|| DMatrix::zeros(num_vars, num_vars));
+        // Weighted sums of 1, x and x x^T over the risk set, with weights exp(x^T beta).
+        let mut risk_sum = T::zero();
+        let mut risk_first = DVector::zeros(num_vars);
+        let mut risk_second = DMatrix::zeros(num_vars, num_vars);
+
+        let mut start = 0;
+        while start < order.len() {
+            let duration = self.durations[order[start]];
+            let mut end = start;
+            while end < order.len() && self.durations[order[end]] == duration {
+                end += 1;
+            }
+            // The same sums over the events at this time.
+            let mut event_sum = T::zero();
+            let mut event_first = DVector::zeros(num_vars);
+            let mut event_second = DMatrix::zeros(num_vars, num_vars);
+            let mut num_events = 0;
+            for &i in &order[start..end] {
+                let x = self.inputs.row(i).transpose();
+                let weight = linear_predictors[i].exp();
+                risk_sum += weight;
+                risk_first.axpy(weight, &x, T::one());
+                if with_hessian {
+                    risk_second += &x * x.transpose() * weight;
+                }
+                if self.events[i].is_one() {
+                    num_events += 1;
+                    log_likelihood += linear_predictors[i];
+                    gradient += &x;
+                    event_sum += weight;
+                    event_first.axpy(weight, &x, T::one());
+                    if with_hessian {
+                        event_second += &x * x.transpose() * weight;
+                    }
+                }
+            }
+
+            for l in 0..num_events {
+                let fraction: T = match self.ties {
+                    TiesMethod::Breslow => T::zero(),
+                    TiesMethod::Efron => nalgebra::convert(l as f64 / num_events as f64),
+                };
+                let denominator = risk_sum - event_sum * fraction;
+                let numerator = &risk_first - &event_first * fraction;
+                log_likelihood -= denominator.ln();
+                gradient.axpy(-T::one() / denominator, &numerator, T::one());
+                if let Some(hessian) = hessian.as_mut() {
+                    let second = &risk_second - &event_second * fraction;
+                    *hessian -= second / denominator
+                        - &numerator * numerator.transpose() / (denominator * denominator);
+                }
+            }
+            start = end;
+        }
+        (log_likelihood, gradient, hessian)
+    }
+
+    fn num_obs(&self) -> T {
+        nalgebra::convert(self.inputs.nrows() as f64)
+    }
+}
+
+/// The negative log partial likelihood, divided by the number of observations.
+impl<T> Objective<T> for PartialLikelihood<'_, T>
+where
+    T: RealField + Copy,
+{
+    fn value(&self, params: &DVector<T>) -> T {
+        -self.evaluate(params, false).0 / self.num_obs()
+    }
+
+    fn gradient(&self, params: &DVector<T>) -> DVector<T> {
+        self.value_and_gradient(params).1
+    }
+
+    fn value_and_gradient(&self, params: &DVector<T>) -> (T, DVector<T>) {
+        let (log_likelihood, gradient, _) = self.evaluate(params, false);
+        let num_obs = self.num_obs();
+        (-log_likelihood / num_obs, -gradient / num_obs)
+    }
+}
+
+/// Cox proportional hazards model, where the hazard of an observation with inputs `x` is
+/// `h_0(t) exp(x^T beta)` for an unspecified baseline hazard `h_0`.
+///
+/// The coefficients are estimated by maximising the partial likelihood with L-BFGS, and the
+/// standard errors come from the inverse of the observed information matrix. There is no
+/// intercept, since it would be absorbed into the baseline hazard.
+#[derive(Debug)]
+pub struct CoxPhModel<T>
+where
+    T: RealField,
+{
+    pub coefficients: Option<DVector<T>>,
+    /// The standard error of each coefficient.
+    pub standard_errors: Option<DVector<T>>,
+    ties: TiesMethod,
+    convergence: ConvergenceConfig<T>,
+    diagnostics: Diagnostics,
+}
+
+impl<T> CoxPhModel<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(ties: TiesMethod) -> Self {
+        Self {
+            coefficients: None,
+            standard_errors: None,
+            ties,
+            convergence: ConvergenceConfig::default(),
+            diagnostics: Diagnostics::default(),
+        }
+    }
+
+    /// Set when L-BFGS stops, based on the gradient of the objective.
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+
+    /// Send warnings from training, e.g. that it stopped before converging, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    pub fn train(
+        &mut self,
+        inputs: DMatrix<T>,
+        durations: DVector<T>,
+        events: DVector<T>,
+    ) -> SLearningResult<()> {
+        if inputs.nrows() != durations.len() {
+            let error_msg = format!(
+                "Input has {} observation(s), but the durations have {} observation(s). These must be equal.",
+                inputs.nrows(),
+                durations.len()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        validate_survival_data(&durations, &events)?;
+        check_finite(&inputs)?;
+        let objective = PartialLikelihood {
+            inputs: &inputs,
+            durations: &durations,
+            events: &events,
+            ties: self.ties,
+        };
+        let coefficients = minimize_lbfgs(
+            &objective,
+            DVector::zeros(inputs.ncols()),
+            self.convergence,
+            &self.diagnostics,
+        )?;
+        let (_, _, hessian) = objective.evaluate(&coefficients, true);
+        let information = -hessian.expect("The Hessian was requested.");
+        let covariance = information.try_inverse().ok_or_else(|| {
+            SLearningError::InvalidData(
+                "The information matrix is singular, so the coefficients are not identifiable."
+                    .to_string(),
+            )
+        })?;
+
+        self.standard_errors = Some(covariance.diagonal().map(|variance| variance.sqrt()));
+        self.coefficients = Some(coefficients);
+        Ok(())
+    }
+
+    /// The multiplicative effect on the hazard of a unit increase in each input, `exp(beta)`.
+    pub fn hazard_ratios(&self) -> SLearningResult<DVector<T>> {
+        let coefficients = check_fitted(&self.coefficients)?;
+        Ok(coefficients.map(|coefficient| coefficient.exp()))
+    }
+
+    /// The hazard of each observation relative to the baseline hazard, `exp(x^T beta)`.
+    pub fn predict_partial_hazard(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>> {
+        let coefficients = check_fitted(&self.coefficients)?;
+        check_num_vars(coefficients.len(), inputs.ncols())?;
+        Ok((inputs * coefficients).map(|predictor| predictor.exp()))
+    }
+}
+
+impl<T> Default for CoxPhModel<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self::new(TiesMethod::Efron)
+    }
+}
+
+/// The number at risk and the number of events at each distinct event time, in increasing order of
+/// time. Observations are at risk at a time if their duration is at least that time.
+fn event_table<T: RealField + Copy>(
+    durations: &DVector<T>,
+    events: &DVector<T>,
+) -> Vec<(T, usize, usize)> {
+    let mut order = descending_duration_order(durations);
+    order.reverse();
+    let mut table = Vec::new();
+    let mut start = 0;
+    while start < order.len() {
+        let duration = durations[order[start]];
+        let mut end = start;
+        while end < order.len() && durations[order[end]] == duration {
+            end += 1;
+        }
+        let num_events = order[start..end]
+            .iter()
+            .filter(|&&i| events[i].is_one())
+            .count();
+        if num_events > 0 {
+            table.push((duration, order.len() - start, num_events));
+        }
+        start = end;
+    }
+    table
+}
+
+/// A survival curve, from [`KaplanMeier`], with an entry for each distinct event time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SurvivalCurve<T>
+where
+    T: RealField,
+{
+    pub times: DVector<T>,
+    /// The number of observations at risk just before each time.
+    pub at_risk: Vec<usize>,
+    /// The number of events at each time.
+    pub events: Vec<usize>,
+    /// The probability of surviving past each time.
+    pub survival: DVector<T>,
+    /// The lower end of the confidence interval for each survival probability.
+    pub lower: DVector<T>,
+    /// The upper end of the confidence interval for each survival probability.
+    pub upper: DVector<T>,
+}
+
+/// Kaplan-Meier estimator of the survival function, the probability that the event has not
+/// happened by a given time, from durations that may be censored.
+///
+/// The confidence intervals use Greenwood's variance on the log(-log) scale, which keeps them
+/// between zero and one. Where the survival is zero, both ends of the interval are zero.
+#[derive(Debug)]
+pub struct KaplanMeier<T>
+where
+    T: RealField,
+{
+    pub curve: Option<SurvivalCurve<T>>,
+    confidence: T,
+}
+
+impl<T> KaplanMeier<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(confidence: T) -> SLearningResult<Self> {
+        check_open_unit_interval(confidence, "Confidence")?;
+        Ok(Self {
+            curve: None,
+            confidence,
+        })
+    }
+
+    pub fn train(&mut self, durations: &DVector<T>, events: &DVector<T>) -> SLearningResult<()> {
+        validate_survival_data(durations, events)?;
+        let table = event_table(durations, events);
+        let confidence = nalgebra::try_convert::<T, f64>(self.confidence)
+            .expect("The confidence is between zero and one.");
+        // The square of a standard normal variable is chi-squared with one degree of freedom.
+        let z: T = nalgebra::convert(chi_squared_quantile(confidence, 1.0).sqrt());
+
+        let mut survival = T::one();
+        let mut greenwood_sum = T::zero();
+        let mut curve = SurvivalCurve {
+            times: DVector::from_iterator(table.len(), table.iter().map(|&(time, _, _)| time)),
+            at_risk: table.iter().map(|&(_, at_risk, _)| at_risk).collect(),
+            events: table.iter().map(|&(_, _, num_events)| num_events).collect(),
+            survival: DVector::zeros(table.len()),
+            lower: DVector::zeros(table.len()),
+            upper: DVector::zeros(table.len()),
+        };
+        for (k, &(_, at_risk, num_events)) in table.iter().enumerate() {
+            let n: T = nalgebra::convert(at_risk as f64);
+            let d: T = nalgebra::convert(num_events as f64);
+            survival *= T::one() - d / n;
+            curve.survival[k] = survival;
+            if at_risk == num_events {
+                // Everyone left has had the event, so the survival is zero from here on.
+                continue;
+            }
+            greenwood_sum += d / (n * (n - d));
+            let log_survival = survival.ln();
+            let log_log_std = greenwood_sum.sqrt() / log_survival.abs();
+            curve.lower[k] = survival.powf((z * log_log_std).exp());
+            curve.upper[k] = survival.powf((-z * log_log_std).exp());
+        }
+        self.curve = Some(curve);
+        Ok(())
+    }
+
+    /// The estimated probability of surviving past each of `times`.
+    pub fn predict(&self, times: &DVector<T>) -> SLearningResult<DVector<T>> {
+        let curve = check_fitted(&self.curve)?;
+        Ok(times.map(|time| {
+            let num_past = curve.times.iter().filter(|&&t| t <= time).count();
+            match num_past {
+                0 => T::one(),
+                k => curve.survival[k - 1],
+            }
+        }))
+    }
+}
+
+impl<T> Default for KaplanMeier<T>
+where
+    T: RealField + Copy,
+{
+    fn default() -> Self {
+        Self::new(nalgebra::convert(0.95)).expect("The default parameters are valid.")
+    }
+}
+
+/// The result of [`log_rank_test`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogRankTest<T> {
+    /// The chi-squared test statistic.
+    pub statistic: T,
+    /// The degrees of freedom, one less than the number of groups.
+    pub degrees_of_freedom: usize,
+    pub p_value: T,
+}
+
+/// The log-rank test of whether the survival functions of several groups are equal.
+///
+/// `groups` gives the group of each observation, numbered from zero. At each event time, the
+/// number of events in each group is compared with the number expected if the hazard were the same
+/// in every group.
+pub fn log_rank_test<T>(
+    durations: &DVector<T>,
+    events: &DVector<T>,
+    groups: &[usize],
+) -> SLearningResult<LogRankTest<T>>
+where
+    T: RealField + Copy,
+{
+    validate_survival_data(durations, events)?;
+    if groups.len() != durations.len() {
+        let error_msg = format!(
+            "There are {} duration(s), but {} group(s). These must be equal.",
+            durations.len(),
+            groups.len()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    let num_groups = groups.iter().max().map_or(0, |&max| max + 1);
+    if num_groups < 2 {
+        return Err(SLearningError::InvalidData(
+            "There must be at least two groups.".to_string(),
+        ));
+    }
+    if let Some(empty) = (0..num_groups).find(|group| !groups.contains(group)) {
+        let error_msg = format!(
+            "Group {} has no observations. Groups must be numbered from zero without gaps.",
+            empty
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+
+    // Observed minus expected events, and its covariance, for each group.
+    let mut deviations = DVector::<T>::zeros(num_groups);
+    let mut covariance = DMatrix::<T>::zeros(num_groups, num_groups);
+    for (time, at_risk, num_events) in event_table(durations, events) {
+        let mut group_at_risk = vec![0; num_groups];
+        let mut group_events = vec![0; num_groups];
+        for (i, &group) in groups.iter().enumerate() {
+            if durations[i] >= time {
+                group_at_risk[group] += 1;
+                if durations[i] == time && events[i].is_one() {
+                    group_events[group] += 1;
+                }
+            }
+        }
+        let n: T = nalgebra::convert(at_risk as f64);
+        let d: T = nalgebra::convert(num_events as f64);
+        for g in 0..num_groups {
+            let proportion: T = nalgebra::convert(group_at_risk[g] as f64 / at_risk as f64);
+            deviations[g] += nalgebra::convert::<f64, T>(group_events[g] as f64) - d * proportion;
+            if at_risk > 1 {
+                let scale = d * (n - d) / (n - T::one());
+                for h in 0..num_groups {
+                    let other: T = nalgebra::convert(group_at_risk[h] as f64 / at_risk as f64);
+                    let indicator = if g == h { T::one() } else { T::zero() };
+                    covariance[(g, h)] += scale * proportion * (indicator - other);
+                }
+            }
+        }
+    }
+
+    // The deviations sum to zero, so leave out the last group.
+    let degrees_of_freedom = num_groups - 1;
+    let deviations = deviations.rows(0, degrees_of_freedom).into_owned();
+    let covariance = covariance
+        .view((0, 0), (degrees_of_freedom, degrees_of_freedom))
+        .into_owned();
+    let inverse = covariance.try_inverse().ok_or_else(|| {
+        SLearningError::InvalidData(
+            "The covariance of the observed minus expected events is singular.".to_string(),
+        )
+    })?;
+    let statistic = (deviations.transpose() * inverse * &deviations)[(0, 0)];
+    let statistic_f64 =
+        nalgebra::try_convert::<T, f64>(statistic).expect("The statistic is a finite number.");
+    Ok(LogRankTest {
+        statistic,
+        degrees_of_freedom,
+        p_value: nalgebra::convert(1.0 - chi_squared_cdf(statistic_f64, degrees_of_freedom as f64)),
+    })
+}
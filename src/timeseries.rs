@@ -0,0 +1,1364 @@
+//! Models for forecasting time series.
+//!
+//! A series is a vector of observations, equally spaced in time and ordered from oldest to newest.
+//! Each model is trained on a univariate series and forecasts the values that follow it.
+//! Alternatively, a (possibly multivariate) series with a row for each time step can be turned into
+//! features for any supervised model with [`forecasting_dataset`].
+use crate::diagnostics::Diagnostics;
+use crate::linear_regression::OlsRegressor;
+use crate::math::sigmoid;
+use crate::optim::{minimize_lbfgs, ConvergenceConfig, Objective};
+use crate::special::chi_squared_quantile;
+use crate::stats;
+use crate::traits::{SupervisedModel, Transformer};
+use crate::validation::{
+    check_finite, check_finite_values, check_fitted, check_open_unit_interval,
+};
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{self, DMatrix, DVector, RealField};
+
+/// The lagged values of `series` for predicting each value from index `start` onwards, with a
+/// column for each lag from one to `order`.
+fn lagged_design<T: RealField + Copy>(
+    series: &DVector<T>,
+    order: usize,
+    start: usize,
+) -> (DMatrix<T>, DVector<T>) {
+    let num_rows = series.len() - start;
+    let inputs = DMatrix::from_fn(num_rows, order, |row, lag| series[start + row - lag - 1]);
+    let outputs = series.rows(start, num_rows).into_owned();
+    (inputs, outputs)
+}
+
+/// The differences between consecutive values of `series`.
+fn difference<T: RealField + Copy>(series: &DVector<T>) -> DVector<T> {
+    DVector::from_fn(series.len().saturating_sub(1), |i, _| {
+        series[i + 1] - series[i]
+    })
+}
+
+/// Forecasts with a prediction interval around each one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForecastInterval<T>
+where
+    T: RealField,
+{
+    /// The point forecasts.
+    pub forecast: DVector<T>,
+    /// The lower end of the prediction interval for each forecast.
+    pub lower: DVector<T>,
+    /// The upper end of the prediction interval for each forecast.
+    pub upper: DVector<T>,
+}
+
+/// The order of an [`ArModel`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArOrder {
+    /// Use exactly this many lags.
+    Fixed(usize),
+    /// Use the number of lags, up to this maximum, that minimises the Akaike information criterion
+    /// (AIC).
+    SelectByAic(usize),
+}
+
+/// Autoregressive (AR) model, which predicts each value as a linear combination of the previous
+/// `p` values plus an intercept.
+///
+/// The coefficients are estimated by ordinary least squares on the lagged values. When choosing
+/// the order by AIC, every candidate order is fitted on the same observations (those after the
+/// maximum order), then the chosen order is refitted on the whole series.
+#[derive(Debug)]
+pub struct ArModel<T>
+where
+    T: RealField,
+{
+    /// The intercept followed by the coefficient of each lag, from one to the order.
+    pub coefficients: Option<DVector<T>>,
+    /// The order that was fitted, which may have been chosen by AIC.
+    pub fitted_order: Option<usize>,
+    /// The maximum likelihood estimate of the variance of the innovations (the mean squared
+    /// residual).
+    pub noise_variance: Option<T>,
+    order: ArOrder,
+    /// The most recent values of the training series, which forecasts follow on from.
+    history: Vec<T>,
+}
+
+impl<T> ArModel<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(order: ArOrder) -> Self {
+        Self {
+            coefficients: None,
+            fitted_order: None,
+            noise_variance: None,
+            order,
+            history: Vec::new(),
+        }
+    }
+
+    /// Fit an AR model of the given order to the values of `series` from index `start`, returning
+    /// the coefficients and the mean squared residual.
+    fn fit_order(
+        series: &DVector<T>,
+        order: usize,
+        start: usize,
+    ) -> SLearningResult<(DVector<T>, T)> {
+        let (inputs, outputs) = lagged_design(series, order, start);
+        let mut ols = OlsRegressor::new(true);
+        ols.train(inputs.clone(), outputs.clone())?;
+        let residuals = outputs - ols.predict(&inputs)?;
+        let noise_variance = residuals.norm_squared() / nalgebra::convert(residuals.len() as f64);
+        let coefficients = ols.coefficients.ok_or(SLearningError::UntrainedModel)?;
+        Ok((coefficients, noise_variance))
+    }
+
+    pub fn train(&mut self, series: &DVector<T>) -> SLearningResult<()> {
+        check_finite_values(series, "series values")?;
+        let max_order = match self.order {
+            ArOrder::Fixed(order) | ArOrder::SelectByAic(order) => order,
+        };
+        // Each fit needs more observations than coefficients.
+        let min_length = 2 * max_order + 2;
+        if series.len() < min_length {
+            let error_msg = format!(
+                "The series has {} observation(s), but at least {} are needed for an autoregressive model of order {}.",
+                series.len(),
+                min_length,
+                max_order
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+
+        let order = match self.order {
+            ArOrder::Fixed(order) => order,
+            ArOrder::SelectByAic(max_order) => {
+                let num_obs: T = nalgebra::convert((series.len() - max_order) as f64);
+                let mut best: Option<(usize, T)> = None;
+                for order in 0..=max_order {
+                    let (_, noise_variance) = Self::fit_order(series, order, max_order)?;
+                    let num_params: T = nalgebra::convert((order + 1) as f64);
+                    let aic = num_obs * noise_variance.ln() + num_params * nalgebra::convert(2.0);
+                    if best.is_none_or(|(_, best_aic)| aic < best_aic) {
+                        best = Some((order, aic));
+                    }
+                }
+                best.expect("At least one order is tried.").0
+            }
+        };
+
+        let (coefficients, noise_variance) = Self::fit_order(series, order, order)?;
+        self.coefficients = Some(coefficients);
+        self.fitted_order = Some(order);
+        self.noise_variance = Some(noise_variance);
+        self.history = series.iter().skip(series.len() - order).copied().collect();
+        Ok(())
+    }
+
+    /// Forecast the next `horizon` values after the training series, feeding each forecast back
+    /// in as a lagged value for the following ones.
+    pub fn forecast(&self, horizon: usize) -> SLearningResult<DVector<T>> {
+        let coefficients = check_fitted(&self.coefficients)?;
+        let mut values = self.history.clone();
+        let order = values.len();
+        for _ in 0..horizon {
+            let n = values.len();
+            let next = (1..=order).fold(coefficients[0], |acc, lag| {
+                acc + coefficients[lag] * values[n - lag]
+            });
+            values.push(next);
+        }
+        Ok(DVector::from_column_slice(&values[order..]))
+    }
+}
+
+/// The conditional sum of squares of an ARMA model of a (differenced) series, scaled by the number
+/// of residuals and halved. The parameters are the constant, the AR coefficients and then the MA
+/// coefficients.
+///
+/// The residuals are computed from index `ar_order` onwards, with earlier residuals taken to be
+/// zero.
+struct ConditionalSumOfSquares<'a, T>
+where
+    T: RealField,
+{
+    series: &'a DVector<T>,
+    ar_order: usize,
+    ma_order: usize,
+}
+
+impl<T> ConditionalSumOfSquares<'_, T>
+where
+    T: RealField + Copy,
+{
+    /// The residuals and, if `with_gradient` is true, the derivative of each residual with respect
+    /// to the parameters.
+    fn residuals(&self, params: &DVector<T>, with_gradient: bool) -> (Vec<T>, Vec<DVector<T>>) {
+        let (p, q) = (self.ar_order, self.ma_order);
+        let num_params = 1 + p + q;
+        let n = self.series.len();
+        let mut residuals = vec![T::zero(); n];
+        let mut derivatives = if with_gradient {
+            vec![DVector::zeros(num_params); n]
+        } else {
+            Vec::new()
+        };
+        for t in p..n {
+            let mut residual = self.series[t] - params[0];
+            for i in 1..=p {
+                residual -= params[i] * self.series[t - i];
+            }
+            for j in 1..=q.min(t) {
+                residual -= params[p + j] * residuals[t - j];
+            }
+            residuals[t] = residual;
+
+            if with_gradient {
+                let mut derivative = DVector::zeros(num_params);
+                derivative[0] = -T::one();
+                for i in 1..=p {
+                    derivative[i] = -self.series[t - i];
+                }
+                for j in 1..=q.min(t) {
+                    derivative[p + j] -= residuals[t - j];
+                    derivative.axpy(-params[p + j], &derivatives[t - j], T::one());
+                }
+                derivatives[t] = derivative;
+            }
+        }
+        (residuals, derivatives)
+    }
+
+    fn num_residuals(&self) -> T {
+        nalgebra::convert((self.series.len() - self.ar_order) as f64)
+    }
+}
+
+impl<T> Objective<T> for ConditionalSumOfSquares<'_, T>
+where
+    T: RealField + Copy,
+{
+    fn value(&self, params: &DVector<T>) -> T {
+        let (residuals, _) = self.residuals(params, false);
+        let sum_of_squares = residuals.iter().fold(T::zero(), |acc, &e| acc + e * e);
+        sum_of_squares / (self.num_residuals() * nalgebra::convert(2.0))
+    }
+
+    fn gradient(&self, params: &DVector<T>) -> DVector<T> {
+        self.value_and_gradient(params).1
+    }
+
+    fn value_and_gradient(&self, params: &DVector<T>) -> (T, DVector<T>) {
+        let (residuals, derivatives) = self.residuals(params, true);
+        let mut value = T::zero();
+        let mut gradient = DVector::zeros(params.len());
+        for t in self.ar_order..residuals.len() {
+            value += residuals[t] * residuals[t];
+            gradient.axpy(residuals[t], &derivatives[t], T::one());
+        }
+        let num_residuals = self.num_residuals();
+        (
+            value / (num_residuals * nalgebra::convert(2.0)),
+            gradient / num_residuals,
+        )
+    }
+}
+
+/// Autoregressive integrated moving average (ARIMA) model of order (p, d, q).
+///
+/// The series is differenced `d` times, and the differenced series is modelled as
+///
+/// `w_t = c + phi_1 w_{t-1} + ... + phi_p w_{t-p} + e_t + theta_1 e_{t-1} + ... + theta_q e_{t-q}`
+///
+/// where the innovations `e_t` are independent with constant variance. When `d > 0`, the constant
+/// `c` is the drift of the original series.
+///
+/// The parameters are estimated by conditional sum of squares (CSS): the innovations before the
+/// first `p` differenced values are taken to be zero, and the sum of squares of the remaining
+/// residuals is minimised with L-BFGS, starting from an AR fit with no MA terms.
+#[derive(Debug)]
+pub struct Arima<T>
+where
+    T: RealField,
+{
+    pub constant: Option<T>,
+    /// The coefficient of each AR lag, from one to p.
+    pub ar_coefficients: Option<DVector<T>>,
+    /// The coefficient of each MA lag, from one to q.
+    pub ma_coefficients: Option<DVector<T>>,
+    /// The variance of the innovations, estimated by the mean squared residual.
+    pub noise_variance: Option<T>,
+    ar_order: usize,
+    num_differences: usize,
+    ma_order: usize,
+    /// The last value of the series after each number of differences, from zero to `d - 1`.
+    last_levels: Vec<T>,
+    /// The last `p` values of the differenced series.
+    recent_values: Vec<T>,
+    /// The last `q` residuals.
+    recent_residuals: Vec<T>,
+    convergence: ConvergenceConfig<T>,
+    diagnostics: Diagnostics,
+}
+
+impl<T> Arima<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(ar_order: usize, num_differences: usize, ma_order: usize) -> Self {
+        Self {
+            constant: None,
+            ar_coefficients: None,
+            ma_coefficients: None,
+            noise_variance: None,
+            ar_order,
+            num_differences,
+            ma_order,
+            last_levels: Vec::new(),
+            recent_values: Vec::new(),
+            recent_residuals: Vec::new(),
+            convergence: ConvergenceConfig::default(),
+            diagnostics: Diagnostics::default(),
+        }
+    }
+
+    /// Set when L-BFGS stops, based on the gradient of the objective.
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+
+    /// Send warnings from training, e.g. that it stopped before converging, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    pub fn train(&mut self, series: &DVector<T>) -> SLearningResult<()> {
+        check_finite_values(series, "series values")?;
+        let (p, d, q) = (self.ar_order, self.num_differences, self.ma_order);
+        // The fit needs more residuals than parameters.
+        let min_length = d + 2 * p + q + 2;
+        if series.len() < min_length {
+            let error_msg = format!(
+                "The series has {} observation(s), but at least {} are needed for an ARIMA({}, {}, {}) model.",
+                series.len(),
+                min_length,
+                p,
+                d,
+                q
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+
+        let mut last_levels = Vec::with_capacity(d);
+        let mut differenced = series.clone();
+        for _ in 0..d {
+            last_levels.push(differenced[differenced.len() - 1]);
+            differenced = difference(&differenced);
+        }
+
+        let (ar_fit, _) = ArModel::fit_order(&differenced, p, p)?;
+        let mut initial = DVector::zeros(1 + p + q);
+        initial.rows_mut(0, 1 + p).copy_from(&ar_fit);
+        let objective = ConditionalSumOfSquares {
+            series: &differenced,
+            ar_order: p,
+            ma_order: q,
+        };
+        let params = minimize_lbfgs(&objective, initial, self.convergence, &self.diagnostics)?;
+        let (residuals, _) = objective.residuals(&params, false);
+
+        let n = differenced.len();
+        self.constant = Some(params[0]);
+        self.ar_coefficients = Some(params.rows(1, p).into_owned());
+        self.ma_coefficients = Some(params.rows(1 + p, q).into_owned());
+        self.noise_variance = Some(objective.value(&params) * nalgebra::convert(2.0));
+        self.last_levels = last_levels;
+        self.recent_values = differenced.iter().skip(n - p).copied().collect();
+        self.recent_residuals = residuals[n - q..].to_vec();
+        Ok(())
+    }
+
+    /// Forecast the next `horizon` values after the training series, taking future innovations to
+    /// be zero.
+    pub fn forecast(&self, horizon: usize) -> SLearningResult<DVector<T>> {
+        let constant = self.constant.ok_or(SLearningError::UntrainedModel)?;
+        let ar_coefficients = check_fitted(&self.ar_coefficients)?;
+        let ma_coefficients = check_fitted(&self.ma_coefficients)?;
+        let (p, q) = (self.ar_order, self.ma_order);
+
+        let mut values = self.recent_values.clone();
+        let mut residuals = self.recent_residuals.clone();
+        for _ in 0..horizon {
+            let (n, m) = (values.len(), residuals.len());
+            let mut next = constant;
+            for i in 1..=p {
+                next += ar_coefficients[i - 1] * values[n - i];
+            }
+            for j in 1..=q {
+                next += ma_coefficients[j - 1] * residuals[m - j];
+            }
+            values.push(next);
+            residuals.push(T::zero());
+        }
+
+        // Undo the differencing, from the most differenced level back to the original series.
+        let mut forecast = values[p..].to_vec();
+        for &last in self.last_levels.iter().rev() {
+            let mut level = last;
+            for value in forecast.iter_mut() {
+                level += *value;
+                *value = level;
+            }
+        }
+        Ok(DVector::from_vec(forecast))
+    }
+
+    /// Forecast the next `horizon` values, with prediction intervals that contain each future
+    /// value with probability `confidence`, assuming normally distributed innovations.
+    ///
+    /// The intervals only account for the innovations, not the uncertainty in the estimated
+    /// parameters.
+    pub fn forecast_interval(
+        &self,
+        horizon: usize,
+        confidence: T,
+    ) -> SLearningResult<ForecastInterval<T>> {
+        check_open_unit_interval(confidence, "Confidence")?;
+        let forecast = self.forecast(horizon)?;
+        let noise_variance = self.noise_variance.ok_or(SLearningError::UntrainedModel)?;
+        let ar_coefficients = check_fitted(&self.ar_coefficients)?;
+        let ma_coefficients = check_fitted(&self.ma_coefficients)?;
+
+        // The AR polynomial of the original series, including the differencing, as the
+        // coefficients of 1, B, B^2, ... in phi(B) (1 - B)^d.
+        let mut polynomial = vec![T::one()];
+        polynomial.extend(ar_coefficients.iter().map(|&phi| -phi));
+        for _ in 0..self.num_differences {
+            let mut product = polynomial.clone();
+            product.push(T::zero());
+            for i in 1..product.len() {
+                product[i] -= polynomial[i - 1];
+            }
+            polynomial = product;
+        }
+
+        // The weights of the innovations in the infinite MA representation.
+        let mut psi = Vec::with_capacity(horizon);
+        for j in 0..horizon {
+            let mut weight = if j == 0 {
+                T::one()
+            } else if j <= self.ma_order {
+                ma_coefficients[j - 1]
+            } else {
+                T::zero()
+            };
+            for i in 1..polynomial.len().min(j + 1) {
+                weight -= polynomial[i] * psi[j - i];
+            }
+            psi.push(weight);
+        }
+
+        let confidence = nalgebra::try_convert::<T, f64>(confidence)
+            .expect("The confidence is between zero and one.");
+        // The square of a standard normal variable is chi-squared with one degree of freedom.
+        let z: T = nalgebra::convert(chi_squared_quantile(confidence, 1.0).sqrt());
+        let mut cumulative_variance = T::zero();
+        let half_widths = DVector::from_iterator(
+            horizon,
+            psi.iter().map(|&weight| {
+                cumulative_variance += weight * weight;
+                z * (noise_variance * cumulative_variance).sqrt()
+            }),
+        );
+        Ok(ForecastInterval {
+            lower: &forecast - &half_widths,
+            upper: &forecast + &half_widths,
+            forecast,
+        })
+    }
+}
+
+/// The smoothed components of a series at the end of exponential smoothing.
+struct SmoothedComponents<T> {
+    sum_of_squares: T,
+    level: T,
+    trend: T,
+    /// The seasonal components for the next season, in order.
+    seasonals: Vec<T>,
+}
+
+/// The mean of the values in `series` with indices in `start..end`.
+fn mean_of<T: RealField + Copy>(series: &DVector<T>, start: usize, end: usize) -> T {
+    series.rows(start, end - start).sum() / nalgebra::convert((end - start) as f64)
+}
+
+/// The mean squared one-step-ahead error of exponential smoothing, halved, as a function of the
+/// smoothing parameters on the logit scale.
+///
+/// The gradient is approximated by central differences.
+struct SmoothingObjective<'a, T>
+where
+    T: RealField,
+{
+    model: &'a ExponentialSmoothing<T>,
+    series: &'a DVector<T>,
+}
+
+impl<T> SmoothingObjective<'_, T>
+where
+    T: RealField + Copy,
+{
+    fn smooth(&self, params: &DVector<T>) -> SmoothedComponents<T> {
+        let smoothing = params.map(sigmoid);
+        let (beta, gamma) = self.model.split_smoothing(&smoothing);
+        self.model.smooth(self.series, smoothing[0], beta, gamma)
+    }
+}
+
+impl<T> Objective<T> for SmoothingObjective<'_, T>
+where
+    T: RealField + Copy,
+{
+    fn value(&self, params: &DVector<T>) -> T {
+        let num_obs: T = nalgebra::convert(self.series.len() as f64);
+        self.smooth(params).sum_of_squares / (num_obs * nalgebra::convert(2.0))
+    }
+
+    fn gradient(&self, params: &DVector<T>) -> DVector<T> {
+        let step: T = nalgebra::convert(1e-6);
+        DVector::from_fn(params.len(), |i, _| {
+            let mut forward = params.clone();
+            forward[i] += step;
+            let mut backward = params.clone();
+            backward[i] -= step;
+            (self.value(&forward) - self.value(&backward)) / (step * nalgebra::convert(2.0))
+        })
+    }
+}
+
+/// Exponential smoothing, which forecasts with exponentially weighted averages of past values.
+///
+/// There are three variants:
+/// - simple exponential smoothing, which tracks a level;
+/// - Holt's linear method, which also tracks a trend (`trend = true`);
+/// - Holt-Winters' additive method, which also tracks a seasonal component with the given period.
+///
+/// The smoothing parameters (alpha for the level, beta for the trend and gamma for the seasonal
+/// component) are chosen to minimise the sum of squared one-step-ahead errors, using L-BFGS. The
+/// initial level, trend and seasonal components are estimated from the start of the series: the
+/// first value and first difference, or the means of the first two seasons.
+#[derive(Debug)]
+pub struct ExponentialSmoothing<T>
+where
+    T: RealField,
+{
+    /// The smoothing parameter for the level.
+    pub alpha: Option<T>,
+    /// The smoothing parameter for the trend, if there is one.
+    pub beta: Option<T>,
+    /// The smoothing parameter for the seasonal component, if there is one.
+    pub gamma: Option<T>,
+    /// The level at the end of the training series.
+    pub level: Option<T>,
+    /// The trend at the end of the training series, or zero without a trend.
+    pub trend: Option<T>,
+    /// The seasonal components for the season after the training series, in order, or empty
+    /// without a seasonal component.
+    pub seasonals: Option<DVector<T>>,
+    has_trend: bool,
+    seasonal_period: Option<usize>,
+    convergence: ConvergenceConfig<T>,
+    diagnostics: Diagnostics,
+}
+
+impl<T> ExponentialSmoothing<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(trend: bool, seasonal_period: Option<usize>) -> SLearningResult<Self> {
+        if seasonal_period.is_some_and(|period| period < 2) {
+            return Err(SLearningError::InvalidParameters(
+                "Seasonal period must be at least two.".to_string(),
+            ));
+        }
+        Ok(Self {
+            alpha: None,
+            beta: None,
+            gamma: None,
+            level: None,
+            trend: None,
+            seasonals: None,
+            has_trend: trend,
+            seasonal_period,
+            convergence: ConvergenceConfig::default(),
+            diagnostics: Diagnostics::default(),
+        })
+    }
+
+    /// Set when L-BFGS stops, based on the gradient of the objective.
+    pub fn with_convergence(self, convergence: ConvergenceConfig<T>) -> Self {
+        Self {
+            convergence,
+            ..self
+        }
+    }
+
+    /// Send warnings from training, e.g. that it stopped before converging, to `diagnostics`.
+    pub fn with_diagnostics(self, diagnostics: Diagnostics) -> Self {
+        Self {
+            diagnostics,
+            ..self
+        }
+    }
+
+    /// Split the smoothing parameters after alpha into beta and gamma, which are zero for
+    /// components the model does not have.
+    fn split_smoothing(&self, smoothing: &DVector<T>) -> (T, T) {
+        let mut rest = smoothing.iter().skip(1);
+        let beta = match self.has_trend {
+            true => *rest.next().expect("There is a parameter for the trend."),
+            false => T::zero(),
+        };
+        let gamma = match self.seasonal_period {
+            Some(_) => *rest.next().expect("There is a parameter for the season."),
+            None => T::zero(),
+        };
+        (beta, gamma)
+    }
+
+    /// Run the smoothing recursions over the series with the given smoothing parameters.
+    fn smooth(&self, series: &DVector<T>, alpha: T, beta: T, gamma: T) -> SmoothedComponents<T> {
+        let (mut level, mut trend, mut seasonals) = match self.seasonal_period {
+            Some(period) => {
+                let first_mean = mean_of(series, 0, period);
+                let trend = match self.has_trend {
+                    true => {
+                        (mean_of(series, period, 2 * period) - first_mean)
+                            / nalgebra::convert(period as f64)
+                    }
+                    false => T::zero(),
+                };
+                // The first mean is the level in the middle of the first season, so start from the
+                // level before the first observation.
+                let level = first_mean - trend * nalgebra::convert((period + 1) as f64 / 2.0);
+                let seasonals = (0..period)
+                    .map(|i| series[i] - level - trend * nalgebra::convert((i + 1) as f64))
+                    .collect();
+                (level, trend, seasonals)
+            }
+            None => {
+                let trend = match self.has_trend {
+                    true => series[1] - series[0],
+                    false => T::zero(),
+                };
+                (series[0] - trend, trend, Vec::new())
+            }
+        };
+
+        let mut sum_of_squares = T::zero();
+        for (t, &value) in series.iter().enumerate() {
+            let seasonal = match self.seasonal_period {
+                Some(period) => seasonals[t % period],
+                None => T::zero(),
+            };
+            let error = value - (level + trend + seasonal);
+            sum_of_squares += error * error;
+
+            let previous_level = level;
+            level = alpha * (value - seasonal) + (T::one() - alpha) * (level + trend);
+            trend = beta * (level - previous_level) + (T::one() - beta) * trend;
+            if let Some(period) = self.seasonal_period {
+                seasonals[t % period] = gamma * (value - level) + (T::one() - gamma) * seasonal;
+            }
+        }
+        if let Some(period) = self.seasonal_period {
+            seasonals.rotate_left(series.len() % period);
+        }
+        SmoothedComponents {
+            sum_of_squares,
+            level,
+            trend,
+            seasonals,
+        }
+    }
+
+    pub fn train(&mut self, series: &DVector<T>) -> SLearningResult<()> {
+        check_finite_values(series, "series values")?;
+        let min_length = match (self.seasonal_period, self.has_trend) {
+            (Some(period), _) => 2 * period,
+            (None, true) => 3,
+            (None, false) => 2,
+        };
+        if series.len() < min_length {
+            let error_msg = format!(
+                "The series has {} observation(s), but at least {} are needed for this exponential smoothing model.",
+                series.len(),
+                min_length
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+
+        // Start from alpha = 0.5 and beta = gamma = 0.1, on the logit scale.
+        let mut initial = vec![T::zero()];
+        let low_start: T = nalgebra::convert(-(9.0f64.ln()));
+        if self.has_trend {
+            initial.push(low_start);
+        }
+        if self.seasonal_period.is_some() {
+            initial.push(low_start);
+        }
+        let objective = SmoothingObjective {
+            model: self,
+            series,
+        };
+        let params = minimize_lbfgs(
+            &objective,
+            DVector::from_vec(initial),
+            self.convergence,
+            &self.diagnostics,
+        )?;
+
+        let smoothing = params.map(sigmoid);
+        let (beta, gamma) = self.split_smoothing(&smoothing);
+        let components = self.smooth(series, smoothing[0], beta, gamma);
+        self.alpha = Some(smoothing[0]);
+        self.beta = self.has_trend.then_some(beta);
+        self.gamma = self.seasonal_period.map(|_| gamma);
+        self.level = Some(components.level);
+        self.trend = Some(components.trend);
+        self.seasonals = Some(DVector::from_vec(components.seasonals));
+        Ok(())
+    }
+
+    /// Forecast the next `horizon` values after the training series, by extending the final
+    /// trend and repeating the final season.
+    pub fn forecast(&self, horizon: usize) -> SLearningResult<DVector<T>> {
+        let level = self.level.ok_or(SLearningError::UntrainedModel)?;
+        let trend = self.trend.ok_or(SLearningError::UntrainedModel)?;
+        let seasonals = check_fitted(&self.seasonals)?;
+        Ok(DVector::from_fn(horizon, |h, _| {
+            let seasonal = match seasonals.len() {
+                0 => T::zero(),
+                period => seasonals[h % period],
+            };
+            level + trend * nalgebra::convert((h + 1) as f64) + seasonal
+        }))
+    }
+}
+
+/// Trait for features computed at each time step of a series (rows) from the values before it.
+///
+/// Transforming a series with n observations gives a row for each time step from `history()` to
+/// n inclusive, so the last row has the features for forecasting the value after the series.
+/// These transformations are stateless, so fitting them does nothing.
+pub trait SeriesFeatures<T>: Transformer<T> {
+    /// The number of earlier observations needed to compute the features for a time step.
+    fn history(&self) -> usize;
+}
+
+fn validate_history<T: RealField>(series: &DMatrix<T>, history: usize) -> SLearningResult<()> {
+    check_finite(series)?;
+    if series.nrows() < history {
+        let error_msg = format!(
+            "The series has {} observation(s), but at least {} are needed to compute these features.",
+            series.nrows(),
+            history
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(())
+}
+
+/// Lagged values of each variable of a series, i.e. the value some number of steps earlier.
+///
+/// There is a column for each variable and lag, grouped by variable.
+#[derive(Debug, Clone)]
+pub struct LagFeatures {
+    lags: Vec<usize>,
+}
+
+impl LagFeatures {
+    pub fn new(lags: Vec<usize>) -> SLearningResult<Self> {
+        if lags.is_empty() {
+            return Err(SLearningError::InvalidParameters(
+                "At least one lag is needed.".to_string(),
+            ));
+        }
+        if lags.contains(&0) {
+            return Err(SLearningError::InvalidParameters(
+                "Lags must be at least one.".to_string(),
+            ));
+        }
+        Ok(Self { lags })
+    }
+
+    pub fn lags(&self) -> &[usize] {
+        &self.lags
+    }
+}
+
+impl<T> Transformer<T> for LagFeatures
+where
+    T: RealField + Copy,
+{
+    fn fit(&mut self, _inputs: &DMatrix<T>) -> SLearningResult<()> {
+        Ok(())
+    }
+
+    fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        let history = SeriesFeatures::<T>::history(self);
+        validate_history(inputs, history)?;
+        let num_lags = self.lags.len();
+        Ok(DMatrix::from_fn(
+            inputs.nrows() - history + 1,
+            inputs.ncols() * num_lags,
+            |row, column| {
+                let lag = self.lags[column % num_lags];
+                inputs[(history + row - lag, column / num_lags)]
+            },
+        ))
+    }
+}
+
+impl<T> SeriesFeatures<T> for LagFeatures
+where
+    T: RealField + Copy,
+{
+    fn history(&self) -> usize {
+        *self.lags.iter().max().expect("There is at least one lag.")
+    }
+}
+
+/// A summary statistic of a rolling window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RollingStatistic {
+    Mean,
+    /// The population standard deviation (with zero delta degrees of freedom).
+    Std,
+    Min,
+    Max,
+}
+
+/// Statistics of each variable of a series over a rolling window of the most recent values,
+/// excluding the current one.
+///
+/// There is a column for each variable and statistic, grouped by variable.
+#[derive(Debug, Clone)]
+pub struct RollingStats {
+    window: usize,
+    statistics: Vec<RollingStatistic>,
+}
+
+impl RollingStats {
+    pub fn new(window: usize, statistics: Vec<RollingStatistic>) -> SLearningResult<Self> {
+        if window == 0 {
+            return Err(SLearningError::InvalidParameters(
+                "Window must contain at least one observation.".to_string(),
+            ));
+        }
+        if statistics.is_empty() {
+            return Err(SLearningError::InvalidParameters(
+                "At least one statistic is needed.".to_string(),
+            ));
+        }
+        Ok(Self { window, statistics })
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    pub fn statistics(&self) -> &[RollingStatistic] {
+        &self.statistics
+    }
+}
+
+impl<T> Transformer<T> for RollingStats
+where
+    T: RealField + Copy,
+{
+    fn fit(&mut self, _inputs: &DMatrix<T>) -> SLearningResult<()> {
+        Ok(())
+    }
+
+    fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        validate_history(inputs, self.window)?;
+        let num_rows = inputs.nrows() - self.window + 1;
+        let num_stats = self.statistics.len();
+        let mut features = DMatrix::zeros(num_rows, inputs.ncols() * num_stats);
+        for row in 0..num_rows {
+            let window = inputs.rows(row, self.window).into_owned();
+            for (k, statistic) in self.statistics.iter().enumerate() {
+                let values = match statistic {
+                    RollingStatistic::Mean => stats::mean(&window)?,
+                    RollingStatistic::Std => stats::std(&window, 0)?,
+                    RollingStatistic::Min => stats::min(&window)?,
+                    RollingStatistic::Max => stats::max(&window)?,
+                };
+                for (variable, &value) in values.iter().enumerate() {
+                    features[(row, variable * num_stats + k)] = value;
+                }
+            }
+        }
+        Ok(features)
+    }
+}
+
+impl<T> SeriesFeatures<T> for RollingStats
+where
+    T: RealField + Copy,
+{
+    fn history(&self) -> usize {
+        self.window
+    }
+}
+
+/// A supervised dataset for forecasting one variable of a series, from [`forecasting_dataset`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForecastingDataset<T>
+where
+    T: RealField,
+{
+    /// The features for each time step with a known value of the target.
+    pub inputs: DMatrix<T>,
+    /// The value of the target at each of those time steps.
+    pub outputs: DVector<T>,
+    /// The features for the time step after the series, for forecasting its value (one row).
+    pub forecast_inputs: DMatrix<T>,
+}
+
+/// Turn a series (with a row for each time step) into a supervised dataset for forecasting the
+/// variable in `target_column` one step ahead, so that any [`SupervisedModel`] can be used.
+///
+/// The features from each transformer are placed side by side. The first time steps are left out
+/// until every transformer has enough history.
+pub fn forecasting_dataset<T>(
+    series: &DMatrix<T>,
+    target_column: usize,
+    features: &[&dyn SeriesFeatures<T>],
+) -> SLearningResult<ForecastingDataset<T>>
+where
+    T: RealField + Copy,
+{
+    check_finite(series)?;
+    if target_column >= series.ncols() {
+        let error_msg = format!(
+            "Target column {} is out of range for a series with {} variables.",
+            target_column,
+            series.ncols()
+        );
+        return Err(SLearningError::InvalidParameters(error_msg));
+    }
+    if features.is_empty() {
+        return Err(SLearningError::InvalidParameters(
+            "At least one set of features is needed.".to_string(),
+        ));
+    }
+    let history = features
+        .iter()
+        .map(|transformer| transformer.history())
+        .max()
+        .expect("There is at least one transformer.");
+    // There must be at least one time step with a known target.
+    validate_history(series, history + 1)?;
+
+    let num_rows = series.nrows() - history + 1;
+    let blocks = features
+        .iter()
+        .map(|transformer| {
+            let block = transformer.transform(series)?;
+            Ok(block.rows(block.nrows() - num_rows, num_rows).into_owned())
+        })
+        .collect::<SLearningResult<Vec<DMatrix<T>>>>()?;
+    let num_columns = blocks.iter().map(|block| block.ncols()).sum();
+    let mut all_features = DMatrix::zeros(num_rows, num_columns);
+    let mut column = 0;
+    for block in blocks {
+        all_features
+            .columns_mut(column, block.ncols())
+            .copy_from(&block);
+        column += block.ncols();
+    }
+
+    Ok(ForecastingDataset {
+        inputs: all_features.rows(0, num_rows - 1).into_owned(),
+        outputs: series
+            .view((history, target_column), (num_rows - 1, 1))
+            .column(0)
+            .into_owned(),
+        forecast_inputs: all_features.rows(num_rows - 1, 1).into_owned(),
+    })
+}
+
+/// A Gaussian distribution over the state of a [`KalmanFilter`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct GaussianState<T>
+where
+    T: RealField,
+{
+    pub mean: DVector<T>,
+    pub covariance: DMatrix<T>,
+}
+
+/// The distributions of the state at each time step, from [`KalmanFilter::filter`] or
+/// [`KalmanFilter::smooth`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateEstimates<T>
+where
+    T: RealField,
+{
+    pub states: Vec<GaussianState<T>>,
+    /// The log-likelihood of the observations.
+    pub log_likelihood: T,
+}
+
+/// The filtered states, along with the predicted states they were updated from.
+struct FilterPass<T>
+where
+    T: RealField,
+{
+    predicted: Vec<GaussianState<T>>,
+    filtered: Vec<GaussianState<T>>,
+    log_likelihood: T,
+}
+
+/// The smoothed states, along with the covariance between each state and the one before it (an
+/// unused zero matrix for the first state).
+struct SmoothPass<T>
+where
+    T: RealField,
+{
+    smoothed: Vec<GaussianState<T>>,
+    lag_one_covariances: Vec<DMatrix<T>>,
+}
+
+fn validate_shape<T: RealField>(
+    name: &str,
+    matrix: &DMatrix<T>,
+    shape: (usize, usize),
+) -> SLearningResult<()> {
+    if matrix.shape() != shape {
+        let error_msg = format!(
+            "The {} matrix has shape {:?}, but should have shape {:?}.",
+            name,
+            matrix.shape(),
+            shape
+        );
+        return Err(SLearningError::InvalidParameters(error_msg));
+    }
+    Ok(())
+}
+
+/// `(matrix + matrix^T) / 2`, to remove the asymmetry from rounding errors.
+fn symmetrize<T: RealField + Copy>(matrix: DMatrix<T>) -> DMatrix<T> {
+    (&matrix + matrix.transpose()) * nalgebra::convert::<f64, T>(0.5)
+}
+
+/// Kalman filter for the linear Gaussian state-space model
+///
+/// `x_t = F x_{t-1} + w_t` and `y_t = H x_t + v_t`
+///
+/// where the state `x_t` is hidden, the observation `y_t` may be missing, and the noises `w_t` and
+/// `v_t` are independent and normally distributed with covariances `Q` and `R`. The initial
+/// distribution is that of the first state, before the first observation.
+///
+/// Missing observations are given as `None`, in which case the state is only predicted forward.
+/// The noise covariances can be estimated from the observations with expectation maximisation
+/// (EM), using the Rauch-Tung-Striebel smoother.
+#[derive(Debug, Clone)]
+pub struct KalmanFilter<T>
+where
+    T: RealField,
+{
+    transition: DMatrix<T>,
+    observation: DMatrix<T>,
+    process_noise: DMatrix<T>,
+    observation_noise: DMatrix<T>,
+    initial: GaussianState<T>,
+}
+
+impl<T> KalmanFilter<T>
+where
+    T: RealField + Copy,
+{
+    pub fn new(
+        transition: DMatrix<T>,
+        observation: DMatrix<T>,
+        process_noise: DMatrix<T>,
+        observation_noise: DMatrix<T>,
+        initial: GaussianState<T>,
+    ) -> SLearningResult<Self> {
+        let num_states = transition.nrows();
+        let num_observed = observation.nrows();
+        validate_shape("transition", &transition, (num_states, num_states))?;
+        validate_shape("observation", &observation, (num_observed, num_states))?;
+        validate_shape("process noise", &process_noise, (num_states, num_states))?;
+        validate_shape(
+            "observation noise",
+            &observation_noise,
+            (num_observed, num_observed),
+        )?;
+        validate_shape(
+            "initial covariance",
+            &initial.covariance,
+            (num_states, num_states),
+        )?;
+        if initial.mean.len() != num_states {
+            let error_msg = format!(
+                "The initial mean has {} variables, but the state has {} variables. These must be equal.",
+                initial.mean.len(),
+                num_states
+            );
+            return Err(SLearningError::InvalidParameters(error_msg));
+        }
+        Ok(Self {
+            transition,
+            observation,
+            process_noise,
+            observation_noise,
+            initial,
+        })
+    }
+
+    /// The transition matrix, `F`.
+    pub fn transition(&self) -> &DMatrix<T> {
+        &self.transition
+    }
+
+    /// The observation matrix, `H`.
+    pub fn observation(&self) -> &DMatrix<T> {
+        &self.observation
+    }
+
+    /// The covariance of the process noise, `Q`.
+    pub fn process_noise(&self) -> &DMatrix<T> {
+        &self.process_noise
+    }
+
+    /// The covariance of the observation noise, `R`.
+    pub fn observation_noise(&self) -> &DMatrix<T> {
+        &self.observation_noise
+    }
+
+    /// The distribution of the first state.
+    pub fn initial(&self) -> &GaussianState<T> {
+        &self.initial
+    }
+
+    /// The distribution of the next state, given the distribution of the current state.
+    pub fn predict(&self, state: &GaussianState<T>) -> GaussianState<T> {
+        GaussianState {
+            mean: &self.transition * &state.mean,
+            covariance: symmetrize(
+                &self.transition * &state.covariance * self.transition.transpose()
+                    + &self.process_noise,
+            ),
+        }
+    }
+
+    /// The distribution of the state after an observation (or the same distribution if the
+    /// observation is missing).
+    pub fn update(
+        &self,
+        state: &GaussianState<T>,
+        observation: Option<&DVector<T>>,
+    ) -> SLearningResult<GaussianState<T>> {
+        Ok(self.update_with_likelihood(state, observation)?.0)
+    }
+
+    /// The updated state, and the log-likelihood of the observation given the state before it.
+    fn update_with_likelihood(
+        &self,
+        state: &GaussianState<T>,
+        observation: Option<&DVector<T>>,
+    ) -> SLearningResult<(GaussianState<T>, T)> {
+        let Some(observation) = observation else {
+            return Ok((state.clone(), T::zero()));
+        };
+        if observation.len() != self.observation.nrows() {
+            let error_msg = format!(
+                "An observation has {} variables, but the observation matrix has {} rows. These must be equal.",
+                observation.len(),
+                self.observation.nrows()
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+        let innovation = observation - &self.observation * &state.mean;
+        let cross_covariance = &state.covariance * self.observation.transpose();
+        let innovation_covariance = &self.observation * &cross_covariance + &self.observation_noise;
+        let cholesky = symmetrize(innovation_covariance)
+            .cholesky()
+            .ok_or_else(|| {
+                SLearningError::InvalidData(
+                    "The innovation covariance is not positive definite.".to_string(),
+                )
+            })?;
+        // K = P H^T S^{-1}, computed as (S^{-1} H P)^T since S and P are symmetric.
+        let gain = cholesky.solve(&cross_covariance.transpose()).transpose();
+        let mean = &state.mean + &gain * &innovation;
+        let covariance = symmetrize(&state.covariance - &gain * cross_covariance.transpose());
+
+        let log_determinant = cholesky
+            .l()
+            .diagonal()
+            .iter()
+            .fold(T::zero(), |acc, &d| acc + d.ln())
+            * nalgebra::convert(2.0);
+        let mahalanobis = innovation.dot(&cholesky.solve(&innovation));
+        let log_two_pi: T = nalgebra::convert((2.0 * std::f64::consts::PI).ln());
+        let num_observed: T = nalgebra::convert(observation.len() as f64);
+        let log_likelihood =
+            -(num_observed * log_two_pi + log_determinant + mahalanobis) * nalgebra::convert(0.5);
+        Ok((GaussianState { mean, covariance }, log_likelihood))
+    }
+
+    fn filter_pass(&self, observations: &[Option<DVector<T>>]) -> SLearningResult<FilterPass<T>> {
+        let mut predicted = Vec::with_capacity(observations.len());
+        let mut filtered: Vec<GaussianState<T>> = Vec::with_capacity(observations.len());
+        let mut log_likelihood = T::zero();
+        for observation in observations {
+            let prior = match filtered.last() {
+                Some(previous) => self.predict(previous),
+                None => self.initial.clone(),
+            };
+            let (posterior, step_likelihood) =
+                self.update_with_likelihood(&prior, observation.as_ref())?;
+            log_likelihood += step_likelihood;
+            predicted.push(prior);
+            filtered.push(posterior);
+        }
+        Ok(FilterPass {
+            predicted,
+            filtered,
+            log_likelihood,
+        })
+    }
+
+    /// The distribution of the state at each time step, given the observations up to and
+    /// including that time step.
+    pub fn filter(
+        &self,
+        observations: &[Option<DVector<T>>],
+    ) -> SLearningResult<StateEstimates<T>> {
+        let pass = self.filter_pass(observations)?;
+        Ok(StateEstimates {
+            states: pass.filtered,
+            log_likelihood: pass.log_likelihood,
+        })
+    }
+
+    fn smooth_pass(&self, pass: &FilterPass<T>) -> SLearningResult<SmoothPass<T>> {
+        let n = pass.filtered.len();
+        let num_states = self.transition.nrows();
+        let mut smoothed = pass.filtered.clone();
+        let mut lag_one_covariances = vec![DMatrix::zeros(num_states, num_states); n];
+        for t in (0..n.saturating_sub(1)).rev() {
+            let filtered = &pass.filtered[t];
+            let predicted = &pass.predicted[t + 1];
+            let cholesky = predicted.covariance.clone().cholesky().ok_or_else(|| {
+                SLearningError::InvalidData(
+                    "The predicted state covariance is not positive definite.".to_string(),
+                )
+            })?;
+            // J = P_t F^T (P_{t+1|t})^{-1}, computed as ((P_{t+1|t})^{-1} F P_t)^T.
+            let smoother_gain = cholesky
+                .solve(&(&self.transition * &filtered.covariance))
+                .transpose();
+            let mean = &filtered.mean + &smoother_gain * (&smoothed[t + 1].mean - &predicted.mean);
+            let covariance = symmetrize(
+                &filtered.covariance
+                    + &smoother_gain
+                        * (&smoothed[t + 1].covariance - &predicted.covariance)
+                        * smoother_gain.transpose(),
+            );
+            lag_one_covariances[t + 1] = &smoothed[t + 1].covariance * smoother_gain.transpose();
+            smoothed[t] = GaussianState { mean, covariance };
+        }
+        Ok(SmoothPass {
+            smoothed,
+            lag_one_covariances,
+        })
+    }
+
+    /// The distribution of the state at each time step, given all of the observations, using the
+    /// Rauch-Tung-Striebel smoother.
+    pub fn smooth(
+        &self,
+        observations: &[Option<DVector<T>>],
+    ) -> SLearningResult<StateEstimates<T>> {
+        let pass = self.filter_pass(observations)?;
+        Ok(StateEstimates {
+            states: self.smooth_pass(&pass)?.smoothed,
+            log_likelihood: pass.log_likelihood,
+        })
+    }
+
+    /// Estimate the noise covariances `Q` and `R` and the initial distribution by maximum
+    /// likelihood, using `n_iter` iterations of expectation maximisation. The transition and
+    /// observation matrices are kept fixed.
+    ///
+    /// Returns the log-likelihood of the observations before each iteration, which never decreases.
+    pub fn estimate_noise(
+        &mut self,
+        observations: &[Option<DVector<T>>],
+        n_iter: usize,
+    ) -> SLearningResult<Vec<T>> {
+        let num_observed = observations.iter().filter(|y| y.is_some()).count();
+        if observations.len() < 2 || num_observed == 0 {
+            return Err(SLearningError::InvalidData(
+                "Estimating the noise needs at least two time steps and one observation."
+                    .to_string(),
+            ));
+        }
+
+        let mut log_likelihoods = Vec::with_capacity(n_iter);
+        for _ in 0..n_iter {
+            let pass = self.filter_pass(observations)?;
+            log_likelihoods.push(pass.log_likelihood);
+            let SmoothPass {
+                smoothed,
+                lag_one_covariances,
+            } = self.smooth_pass(&pass)?;
+
+            // E[x_t x_t^T] given all observations.
+            let second_moment =
+                |state: &GaussianState<T>| &state.covariance + &state.mean * state.mean.transpose();
+
+            let f = &self.transition;
+            let mut process_noise = DMatrix::zeros(f.nrows(), f.nrows());
+            for t in 1..smoothed.len() {
+                // E[x_t x_{t-1}^T] given all observations.
+                let cross_moment =
+                    &lag_one_covariances[t] + &smoothed[t].mean * smoothed[t - 1].mean.transpose();
+                process_noise += second_moment(&smoothed[t])
+                    - f * cross_moment.transpose()
+                    - &cross_moment * f.transpose()
+                    + f * second_moment(&smoothed[t - 1]) * f.transpose();
+            }
+            let num_transitions: T = nalgebra::convert((smoothed.len() - 1) as f64);
+
+            let h = &self.observation;
+            let mut observation_noise = DMatrix::zeros(h.nrows(), h.nrows());
+            for (state, observation) in smoothed.iter().zip(observations) {
+                if let Some(observation) = observation {
+                    let residual = observation - h * &state.mean;
+                    observation_noise +=
+                        &residual * residual.transpose() + h * &state.covariance * h.transpose();
+                }
+            }
+
+            self.process_noise = symmetrize(process_noise / num_transitions);
+            self.observation_noise =
+                symmetrize(observation_noise / nalgebra::convert::<f64, T>(num_observed as f64));
+            self.initial = smoothed[0].clone();
+        }
+        Ok(log_likelihoods)
+    }
+}
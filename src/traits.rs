@@ -1,14 +1,14 @@
-///! Traits for different abstract models types.
-///
-/// These use dynamically sized matrices and vectors, so that the shape of training and predicting
-/// data does not have to be specified when creating a model. This would constrain the model and
-/// limit it's potential re-use for multiple predictions.
-///
-/// This means the models that implement this trait are responsible for verifying the consistency
-/// of matrix/vector shapes *at runtime*, where necessary (e.g. training inputs and outputs have
-/// the same number of observations).
-///
-use nalgebra::{DMatrix, DVector};
+//! Traits for different abstract models types.
+//!
+//! These use dynamically sized matrices and vectors, so that the shape of training and predicting
+//! data does not have to be specified when creating a model. This would constrain the model and
+//! limit it's potential re-use for multiple predictions.
+//!
+//! This means the models that implement this trait are responsible for verifying the consistency
+//! of matrix/vector shapes *at runtime*, where necessary (e.g. training inputs and outputs have
+//! the same number of observations).
+
+use nalgebra::{DMatrix, DVector, RealField};
 
 use crate::SLearningResult;
 
@@ -19,6 +19,16 @@ pub trait SupervisedModel<T> {
     fn train(&mut self, inputs: DMatrix<T>, outputs: DVector<T>) -> SLearningResult<()>;
 
     fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>>;
+
+    /// Lazily predict each block of observations in turn, e.g. blocks of rows read from a large
+    /// file, so that the whole input and output are never held in memory at once.
+    fn predict_chunks<I>(&self, chunks: I) -> impl Iterator<Item = SLearningResult<DVector<T>>>
+    where
+        Self: Sized,
+        I: IntoIterator<Item = DMatrix<T>>,
+    {
+        chunks.into_iter().map(move |chunk| self.predict(&chunk))
+    }
 }
 
 /// Trait for an unsupervised model.
@@ -28,4 +38,91 @@ pub trait UnsupervisedModel<T> {
     fn train(&mut self, input: &DMatrix<T>) -> SLearningResult<()>;
 
     fn predict(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>>;
+
+    /// Lazily predict each block of observations in turn, e.g. blocks of rows read from a large
+    /// file, so that the whole input and output are never held in memory at once.
+    fn predict_chunks<I>(&self, chunks: I) -> impl Iterator<Item = SLearningResult<DVector<T>>>
+    where
+        Self: Sized,
+        I: IntoIterator<Item = DMatrix<T>>,
+    {
+        chunks.into_iter().map(move |chunk| self.predict(&chunk))
+    }
+}
+
+/// Trait for a transformation of input data, e.g. to create features for a model.
+///
+/// The transformation is fitted to some data, and can then be applied to any data with the same
+/// variables.
+pub trait Transformer<T> {
+    fn fit(&mut self, inputs: &DMatrix<T>) -> SLearningResult<()>;
+
+    fn transform(&self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>>;
+
+    fn fit_transform(&mut self, inputs: &DMatrix<T>) -> SLearningResult<DMatrix<T>> {
+        self.fit(inputs)?;
+        self.transform(inputs)
+    }
+}
+
+/// The predicted labels of a binary classifier, with the scores they were thresholded from, so
+/// that both are available from one prediction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BinaryPrediction<T>
+where
+    T: RealField,
+{
+    /// The predicted label of each observation, one or zero.
+    pub labels: DVector<T>,
+    /// The score of each observation, e.g. the probability that it is positive.
+    pub scores: DVector<T>,
+}
+
+impl<T> BinaryPrediction<T>
+where
+    T: RealField + Copy,
+{
+    /// Label the observations with scores at least `threshold` as one, and the others as zero.
+    pub(crate) fn from_scores(scores: DVector<T>, threshold: T) -> Self {
+        let labels = scores.map(|score| match score >= threshold {
+            true => T::one(),
+            false => T::zero(),
+        });
+        Self { labels, scores }
+    }
+}
+
+/// A trained model that can only make predictions, e.g. to share one model between the worker
+/// threads of a server behind an `Arc`, without cloning it or allowing it to be retrained.
+///
+/// Every model in this crate is `Send + Sync` when its type parameters are, so a frozen model can
+/// be shared between threads. [`predict`](Self::predict) is available for supervised models, and
+/// the model's other methods that only read it (e.g. predicting probabilities, or predicting with
+/// an unsupervised model) through [`model`](Self::model).
+#[derive(Debug, Clone)]
+pub struct FrozenModel<M> {
+    model: M,
+}
+
+impl<M> FrozenModel<M> {
+    pub fn new(model: M) -> Self {
+        Self { model }
+    }
+
+    /// A shared reference to the model, which cannot be used to retrain it.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// The model, which can then be retrained.
+    pub fn into_inner(self) -> M {
+        self.model
+    }
+
+    pub fn predict<T>(&self, inputs: &DMatrix<T>) -> SLearningResult<DVector<T>>
+    where
+        M: SupervisedModel<T>,
+    {
+        self.model.predict(inputs)
+    }
 }
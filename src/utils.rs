@@ -0,0 +1,173 @@
+//! Miscellaneous helpers that are shared between models.
+use nalgebra::RealField;
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::vec;
+
+/// Iterator over the distinct values of a collection, paired with the number of times each value
+/// occurs.
+///
+/// The values are owned, so this can be used directly to build class priors or label maps. The
+/// order of the values depends on the constructor:
+/// - [`UniqueWithCounts::new`] gives an arbitrary order.
+/// - [`UniqueWithCounts::sorted`] gives ascending order.
+/// - [`UniqueWithCounts::by_first_occurrence`] gives the order in which each value first appears.
+#[derive(Debug)]
+pub struct UniqueWithCounts<T> {
+    counts: vec::IntoIter<(T, u64)>,
+}
+
+impl<T> UniqueWithCounts<T>
+where
+    T: Eq + Hash,
+{
+    pub fn new<I>(values: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut counts = HashMap::new();
+        for value in values {
+            *counts.entry(value).or_insert(0) += 1;
+        }
+        Self::from_counts(counts.into_iter().collect())
+    }
+
+    pub fn by_first_occurrence<I>(values: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut first_index_and_counts: HashMap<T, (usize, u64)> = HashMap::new();
+        for (index, value) in values.into_iter().enumerate() {
+            first_index_and_counts.entry(value).or_insert((index, 0)).1 += 1;
+        }
+        let mut counts: Vec<_> = first_index_and_counts.into_iter().collect();
+        counts.sort_unstable_by_key(|(_, (first_index, _))| *first_index);
+        Self::from_counts(
+            counts
+                .into_iter()
+                .map(|(value, (_, count))| (value, count))
+                .collect(),
+        )
+    }
+}
+
+impl<T> UniqueWithCounts<T>
+where
+    T: Ord,
+{
+    pub fn sorted<I>(values: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut counts = BTreeMap::new();
+        for value in values {
+            *counts.entry(value).or_insert(0) += 1;
+        }
+        Self::from_counts(counts.into_iter().collect())
+    }
+}
+
+impl<T> UniqueWithCounts<T>
+where
+    T: Clone + Eq + Hash,
+{
+    /// Count the distinct values of a slice, cloning each distinct value once.
+    pub fn from_slice(values: &[T]) -> Self {
+        Self::new(values.iter().cloned())
+    }
+}
+
+impl<T> UniqueWithCounts<T> {
+    fn from_counts(counts: Vec<(T, u64)>) -> Self {
+        Self {
+            counts: counts.into_iter(),
+        }
+    }
+
+    /// Each distinct value with the proportion of observations that it makes up.
+    ///
+    /// The proportions sum to one (unless there are no values), and keep the order of `self`.
+    pub fn proportions(self) -> vec::IntoIter<(T, f64)> {
+        let counts: Vec<(T, u64)> = self.collect();
+        let total: u64 = counts.iter().map(|(_, count)| count).sum();
+        counts
+            .into_iter()
+            .map(|(value, count)| (value, count as f64 / total as f64))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
+    /// The most frequent value, or `None` if there are no values.
+    ///
+    /// Ties are broken by taking the value that comes first in the order of `self`.
+    pub fn mode(self) -> Option<T> {
+        self.top_k(1).pop().map(|(value, _)| value)
+    }
+
+    /// The `k` most frequent values with their counts, in descending order of count.
+    ///
+    /// Ties are broken by the order of `self`. Fewer than `k` values are returned if there are not
+    /// enough distinct values.
+    pub fn top_k(self, k: usize) -> Vec<(T, u64)> {
+        let mut counts: Vec<(T, u64)> = self.collect();
+        // This is a stable sort, so ties keep their original order.
+        counts.sort_by(|(_, a), (_, b)| b.cmp(a));
+        counts.truncate(k);
+        counts
+    }
+}
+
+impl<T> Iterator for UniqueWithCounts<T> {
+    type Item = (T, u64);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.counts.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.counts.size_hint()
+    }
+}
+
+impl<T> ExactSizeIterator for UniqueWithCounts<T> {}
+
+/// Returns the distinct values of `values` and the number of times each one occurs.
+///
+/// The values are returned in an arbitrary order; use [`unique_with_counts_sorted`] or
+/// [`unique_with_counts_by_first_occurrence`] when the order matters.
+pub fn unique_with_counts<I>(values: I) -> UniqueWithCounts<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Eq + Hash,
+{
+    UniqueWithCounts::new(values)
+}
+
+/// Returns the distinct values of `values` in ascending order, and the number of times each one
+/// occurs.
+pub fn unique_with_counts_sorted<I>(values: I) -> UniqueWithCounts<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Ord,
+{
+    UniqueWithCounts::sorted(values)
+}
+
+/// Returns the distinct values of `values` in the order they first appear, and the number of
+/// times each one occurs.
+pub fn unique_with_counts_by_first_occurrence<I>(values: I) -> UniqueWithCounts<I::Item>
+where
+    I: IntoIterator,
+    I::Item: Eq + Hash,
+{
+    UniqueWithCounts::by_first_occurrence(values)
+}
+
+/// A total order of real values for sorting, which puts NaN after every other value.
+pub(crate) fn total_cmp<T: RealField>(a: &T, b: &T) -> Ordering {
+    // NaN is the only value that cannot be compared with zero.
+    let is_nan = |x: &T| x.partial_cmp(&T::zero()).is_none();
+    a.partial_cmp(b)
+        .unwrap_or_else(|| is_nan(a).cmp(&is_nan(b)))
+}
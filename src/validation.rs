@@ -0,0 +1,124 @@
+//! Checks of model inputs and state that are shared between models, so that the same problem
+//! always gives the same error.
+use crate::{SLearningError, SLearningResult};
+use nalgebra::{DMatrix, DVector, Dim, Matrix, RawStorage, RealField};
+
+/// Whether a value is missing, i.e. NaN, which is the only value that cannot be compared with
+/// itself.
+pub fn is_missing<T: RealField>(value: &T) -> bool {
+    value.partial_cmp(value).is_none()
+}
+
+/// Check that there is at least one observation (row) to train on.
+pub fn check_2d_nonempty<T, R, C, S>(inputs: &Matrix<T, R, C, S>) -> SLearningResult<()>
+where
+    T: RealField,
+    R: Dim,
+    C: Dim,
+    S: RawStorage<T, R, C>,
+{
+    if inputs.nrows() == 0 {
+        return Err(SLearningError::InvalidData(
+            "Cannot train with zero observations.".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Check that the inputs and outputs have the same number of observations.
+pub fn check_consistent_length<T: RealField>(
+    inputs: &DMatrix<T>,
+    outputs: &DVector<T>,
+) -> SLearningResult<()> {
+    if inputs.nrows() != outputs.len() {
+        let error_msg = format!(
+            "Input has {} observation(s), but output has {} observation(s). These must be equal.",
+            inputs.nrows(),
+            outputs.len()
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(())
+}
+
+/// Check that every input value is finite, i.e. neither infinite nor NaN.
+pub fn check_finite<T: RealField>(inputs: &DMatrix<T>) -> SLearningResult<()> {
+    for (var, column) in inputs.column_iter().enumerate() {
+        if let Some(obs) = column.iter().position(|x| !x.is_finite()) {
+            let error_msg = format!(
+                "Input has a non-finite value for observation {} and variable {}.",
+                obs, var
+            );
+            return Err(SLearningError::InvalidData(error_msg));
+        }
+    }
+    Ok(())
+}
+
+/// Check that every value of a vector, such as the durations or scores of the observations, is
+/// finite. `name` is the plural name of the values, used in the error message.
+pub fn check_finite_values<T: RealField>(values: &DVector<T>, name: &str) -> SLearningResult<()> {
+    if let Some(obs) = values.iter().position(|x| !x.is_finite()) {
+        let error_msg = format!(
+            "The {} have a non-finite value for observation {}.",
+            name, obs
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(())
+}
+
+/// Check that a parameter is finite and greater than zero. `name` starts the error message, e.g.
+/// "Gamma".
+pub fn check_positive<T: RealField>(value: T, name: &str) -> SLearningResult<()> {
+    if !value.is_finite() || value <= T::zero() {
+        let error_msg = format!("{} must be finite and greater than zero.", name);
+        return Err(SLearningError::InvalidParameters(error_msg));
+    }
+    Ok(())
+}
+
+/// Check that a parameter, such as a penalty or a tolerance, is finite and not less than zero.
+pub fn check_non_negative<T: RealField>(value: T, name: &str) -> SLearningResult<()> {
+    if !value.is_finite() || value.is_negative() {
+        let error_msg = format!("{} must be finite and cannot be less than zero.", name);
+        return Err(SLearningError::InvalidParameters(error_msg));
+    }
+    Ok(())
+}
+
+/// Check that a parameter, such as a confidence level, is strictly between zero and one.
+pub fn check_open_unit_interval<T: RealField>(value: T, name: &str) -> SLearningResult<()> {
+    if !value.is_finite() || value <= T::zero() || value >= T::one() {
+        let error_msg = format!("{} must be greater than zero and less than one.", name);
+        return Err(SLearningError::InvalidParameters(error_msg));
+    }
+    Ok(())
+}
+
+/// Check that a parameter, such as a proportion of the observations, is greater than zero and at
+/// most one.
+pub fn check_fraction<T: RealField>(value: T, name: &str) -> SLearningResult<()> {
+    if !value.is_finite() || value <= T::zero() || value > T::one() {
+        let error_msg = format!("{} must be greater than zero and at most one.", name);
+        return Err(SLearningError::InvalidParameters(error_msg));
+    }
+    Ok(())
+}
+
+/// Check that an input has as many variables as the model was trained with.
+pub fn check_num_vars(num_train_vars: usize, num_vars: usize) -> SLearningResult<()> {
+    if num_vars != num_train_vars {
+        let error_msg = format!(
+            "This model was trained with {} variables, but this input has {} variables. These must be equal.",
+            num_train_vars, num_vars
+        );
+        return Err(SLearningError::InvalidData(error_msg));
+    }
+    Ok(())
+}
+
+/// The state that a model learns in training, or an error if it hasn't been trained.
+pub fn check_fitted<F>(fitted: &Option<F>) -> SLearningResult<&F> {
+    fitted.as_ref().ok_or(SLearningError::UntrainedModel)
+}
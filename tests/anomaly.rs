@@ -0,0 +1,233 @@
+use nalgebra::{dmatrix, dvector, DMatrix, RowDVector};
+use test_case::test_case;
+
+use slearning::anomaly::{EllipticEnvelope, IsolationForest, LocalOutlierFactor, OneClassSvm};
+use slearning::kernel::Rbf;
+use slearning::random::Rng;
+use slearning::{SLearningError, UnsupervisedModel};
+
+/// 48 observations from a standard normal distribution, followed by two distant outliers.
+fn inputs_with_outliers() -> DMatrix<f64> {
+    let mut rng = Rng::new(0);
+    let mut inputs = DMatrix::from_fn(50, 2, |_, _| rng.standard_normal());
+    inputs.set_row(48, &RowDVector::from_vec(vec![8.0, 8.0]));
+    inputs.set_row(49, &RowDVector::from_vec(vec![-7.0, 9.0]));
+    inputs
+}
+
+#[test]
+fn isolation_forest_works() {
+    let inputs = inputs_with_outliers();
+    let mut forest = IsolationForest::new(100, 32, 0.04).unwrap();
+
+    forest.train(&inputs).unwrap();
+
+    let scores = forest.score_samples(&inputs).unwrap();
+    assert!(scores.iter().all(|&score| score > 0.0 && score < 1.0));
+    assert!(scores[48] > 0.6 && scores[49] > 0.6);
+    let labels = forest.predict(&inputs).unwrap();
+    assert_eq!(labels.sum(), 2.0);
+    assert_eq!((labels[48], labels[49]), (1.0, 1.0));
+    let new_labels = forest.predict(&dmatrix![0.0, 0.0; 10.0, -10.0]).unwrap();
+    assert_eq!(new_labels, dvector![0.0, 1.0]);
+}
+
+#[test]
+fn isolation_forest_predicts_chunks() {
+    let inputs = inputs_with_outliers();
+    let mut forest = IsolationForest::new(100, 32, 0.04).unwrap();
+    forest.train(&inputs).unwrap();
+
+    let chunks = (0..inputs.nrows()).step_by(20).map(|start| {
+        inputs
+            .rows(start, 20.min(inputs.nrows() - start))
+            .into_owned()
+    });
+    let labels: Vec<f64> = forest
+        .predict_chunks(chunks)
+        .map(|chunk| chunk.unwrap())
+        .flat_map(|chunk| chunk.iter().copied().collect::<Vec<f64>>())
+        .collect();
+
+    assert_eq!(labels, forest.predict(&inputs).unwrap().as_slice());
+}
+
+#[test]
+fn isolation_forest_is_reproducible() {
+    let inputs = inputs_with_outliers();
+    let mut first = IsolationForest::default().with_seed(3);
+    let mut second = IsolationForest::default().with_seed(3);
+    let mut other = IsolationForest::default().with_seed(4);
+
+    first.train(&inputs).unwrap();
+    second.train(&inputs).unwrap();
+    other.train(&inputs).unwrap();
+
+    let first_scores = first.score_samples(&inputs).unwrap();
+    assert_eq!(first_scores, second.score_samples(&inputs).unwrap());
+    assert_ne!(first_scores, other.score_samples(&inputs).unwrap());
+}
+
+#[test_case(0, 256, 0.1, "Number of trees must be at least one."; "zero trees")]
+#[test_case(100, 1, 0.1, "Maximum number of samples must be at least two."; "one sample")]
+#[test_case(100, 256, 0.0, "Contamination must be finite and greater than zero."; "zero contamination")]
+#[test_case(100, 256, 0.6, "Contamination must be at most one half."; "large contamination")]
+#[test_case(100, 256, f64::NAN, "Contamination must be finite and greater than zero."; "nan contamination")]
+fn isolation_forest_fails_with_invalid_parameters(
+    n_trees: usize,
+    max_samples: usize,
+    contamination: f64,
+    message: &str,
+) {
+    let actual = IsolationForest::new(n_trees, max_samples, contamination).unwrap_err();
+    assert_eq!(actual, SLearningError::InvalidParameters(message.into()));
+}
+
+#[test]
+fn isolation_forest_fails_with_invalid_inputs() {
+    let mut forest: IsolationForest<f64> = IsolationForest::default();
+    assert_eq!(
+        forest.predict(&dmatrix![1.0, 2.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+    assert_eq!(
+        forest.train(&DMatrix::zeros(0, 2)).unwrap_err(),
+        SLearningError::InvalidData("Cannot train with zero observations.".into())
+    );
+    assert_eq!(
+        forest.train(&dmatrix![1.0, 2.0]).unwrap_err(),
+        SLearningError::InvalidData(
+            "Cannot train an isolation forest with fewer than two observations.".into()
+        )
+    );
+
+    forest.train(&inputs_with_outliers()).unwrap();
+    assert_eq!(
+        forest.predict(&dmatrix![1.0, 2.0, 3.0]).unwrap_err(),
+        SLearningError::InvalidData(
+            "This model was trained with 2 variables, but this input has 3 variables. These must be equal.".into()
+        )
+    );
+}
+
+#[test]
+fn local_outlier_factor_works() {
+    let inputs = dmatrix![0.0; 1.0; 2.0; 3.0; 10.0];
+    let mut lof = LocalOutlierFactor::new(1, 0.2).unwrap();
+
+    lof.train(&inputs).unwrap();
+
+    // The evenly spaced observations all have the same density, and the last has a seventh of it.
+    let scores = lof.training_scores.as_ref().unwrap();
+    assert!((scores - dvector![1.0, 1.0, 1.0, 1.0, 7.0]).amax() < 1e-6);
+    assert_eq!(lof.training_labels, Some(dvector![0.0, 0.0, 0.0, 0.0, 1.0]));
+}
+
+#[test]
+fn local_outlier_factor_detects_novelties() {
+    let inputs = dmatrix![0.0; 1.0; 2.0; 3.0; 10.0];
+    let mut lof = LocalOutlierFactor::new(1, 0.2).unwrap().with_novelty(true);
+    lof.train(&inputs).unwrap();
+
+    let new_inputs = dmatrix![1.5; 30.0];
+    let scores = lof.score_samples(&new_inputs).unwrap();
+    assert!((scores - dvector![1.0, 20.0 / 7.0]).amax() < 1e-6);
+    assert_eq!(lof.predict(&new_inputs).unwrap(), dvector![0.0, 1.0]);
+}
+
+#[test]
+fn local_outlier_factor_fails_with_invalid_inputs() {
+    let inputs = dmatrix![0.0; 1.0; 2.0];
+    let mut lof = LocalOutlierFactor::new(3, 0.1).unwrap();
+    assert_eq!(
+        lof.train(&inputs).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Number of neighbours must be less than the number of observations (3).".into()
+        )
+    );
+
+    let mut lof = LocalOutlierFactor::new(1, 0.1).unwrap();
+    lof.train(&inputs).unwrap();
+    assert_eq!(
+        lof.predict(&dmatrix![0.5]).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Scoring new observations requires novelty detection mode.".into()
+        )
+    );
+    assert_eq!(
+        LocalOutlierFactor::new(0, 0.1).unwrap_err(),
+        SLearningError::InvalidParameters("Number of neighbours must be at least one.".into())
+    );
+}
+
+#[test]
+fn one_class_svm_works() {
+    let inputs = inputs_with_outliers();
+    let nu = 0.1;
+    let mut svm = OneClassSvm::new(Rbf::new(0.5).unwrap(), nu).unwrap();
+
+    svm.train(&inputs).unwrap();
+
+    let dual_coefficients = svm.dual_coefficients.as_ref().unwrap();
+    assert!((dual_coefficients.sum() - 1.0).abs() < 1e-10);
+    assert!(dual_coefficients
+        .iter()
+        .all(|&a| a > 0.0 && a <= 1.0 / (nu * 50.0) + 1e-12));
+    // nu bounds the proportion of training observations strictly outside the boundary above, and
+    // the proportion of support vectors below.
+    let decision = svm.decision_function(&inputs).unwrap();
+    assert!(decision.iter().filter(|&&d| d < -1e-4).count() as f64 <= nu * 50.0);
+    assert!(dual_coefficients.len() as f64 >= nu * 50.0);
+    let new_labels = svm.predict(&dmatrix![0.0, 0.0; 6.0, -6.0]).unwrap();
+    assert_eq!(new_labels, dvector![0.0, 1.0]);
+}
+
+#[test]
+fn one_class_svm_fails_with_invalid_inputs() {
+    assert_eq!(
+        OneClassSvm::new(Rbf::new(1.0).unwrap(), 0.0).unwrap_err(),
+        SLearningError::InvalidParameters("Nu must be greater than zero and at most one.".into())
+    );
+    let svm = OneClassSvm::new(Rbf::new(1.0).unwrap(), 0.5).unwrap();
+    assert_eq!(
+        svm.predict(&dmatrix![1.0, 2.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
+
+#[test]
+fn elliptic_envelope_works() {
+    let inputs = inputs_with_outliers();
+    let mut envelope = EllipticEnvelope::new(0.025).unwrap();
+
+    envelope.train(&inputs).unwrap();
+
+    // The 0.975 quantile of the chi-squared distribution with two degrees of freedom.
+    assert!((envelope.threshold.unwrap() - 7.377758908227871).abs() < 1e-8);
+    let labels = envelope.predict(&inputs).unwrap();
+    assert_eq!((labels[48], labels[49]), (1.0, 1.0));
+    assert!(labels.sum() <= 4.0);
+    let new_labels = envelope.predict(&dmatrix![0.1, -0.2; 5.0, 5.0]).unwrap();
+    assert_eq!(new_labels, dvector![0.0, 1.0]);
+}
+
+#[test]
+fn elliptic_envelope_fails_with_invalid_inputs() {
+    assert_eq!(
+        EllipticEnvelope::new(0.7).unwrap_err(),
+        SLearningError::InvalidParameters("Contamination must be at most one half.".into())
+    );
+    assert_eq!(
+        EllipticEnvelope::<f64>::default()
+            .with_support_fraction(0.0)
+            .unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Support fraction must be greater than zero and at most one.".into()
+        )
+    );
+    let envelope: EllipticEnvelope<f64> = EllipticEnvelope::default();
+    assert_eq!(
+        envelope.predict(&dmatrix![1.0, 2.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
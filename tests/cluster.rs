@@ -0,0 +1,438 @@
+use nalgebra::{dmatrix, dvector, DMatrix, DVector};
+use test_case::test_case;
+
+use slearning::cluster::{AffinityPropagation, Birch, Optics};
+use slearning::distance::Euclidean;
+use slearning::random::Rng;
+use slearning::{SLearningError, UnsupervisedModel};
+
+#[test]
+fn optics_orders_by_reachability() {
+    let inputs = dmatrix![0.0; 1.0; 2.0; 10.0; 11.0; 30.0];
+    let mut model = Optics::new(2, Euclidean).unwrap();
+
+    model.train(&inputs).unwrap();
+
+    assert_eq!(model.ordering.as_ref().unwrap(), &vec![0, 1, 2, 3, 4, 5]);
+    assert_eq!(
+        model.core_distances.as_ref().unwrap(),
+        &dvector![1.0, 1.0, 1.0, 1.0, 1.0, 19.0]
+    );
+    assert_eq!(
+        model.reachability.as_ref().unwrap(),
+        &dvector![f64::INFINITY, 1.0, 1.0, 8.0, 1.0, 19.0]
+    );
+}
+
+#[test_case(0.5, vec![None, None, None, None, None, None]; "all noise")]
+#[test_case(2.0, vec![Some(0), Some(0), Some(0), Some(1), Some(1), None]; "two clusters")]
+#[test_case(10.0, vec![Some(0), Some(0), Some(0), Some(0), Some(0), None]; "one cluster")]
+fn optics_extracts_dbscan_clusters(eps: f64, expected: Vec<Option<usize>>) {
+    let inputs = dmatrix![0.0; 1.0; 2.0; 10.0; 11.0; 30.0];
+    let mut model = Optics::new(2, Euclidean).unwrap();
+    model.train(&inputs).unwrap();
+
+    assert_eq!(model.extract_dbscan(eps).unwrap(), expected);
+}
+
+/// A dense and a sparse blob of 40 observations each, which are 6 apart, and two far outliers.
+fn blobs_of_different_density() -> DMatrix<f64> {
+    let mut rng = Rng::new(2);
+    let mut inputs = DMatrix::from_fn(82, 2, |i, _| match i < 40 {
+        true => 0.1 * rng.standard_normal::<f64>(),
+        false => 6.0 + 0.8 * rng.standard_normal::<f64>(),
+    });
+    inputs.set_row(80, &dmatrix![-20.0, 20.0].row(0));
+    inputs.set_row(81, &dmatrix![30.0, -10.0].row(0));
+    inputs
+}
+
+#[test]
+fn optics_finds_clusters_at_different_densities() {
+    let inputs = blobs_of_different_density();
+    let mut model = Optics::new(5, Euclidean)
+        .unwrap()
+        .with_max_eps(5.0)
+        .unwrap();
+    model.train(&inputs).unwrap();
+
+    // A small eps only finds the dense blob, and a larger eps also finds the sparse one.
+    let dense_only = model.extract_dbscan(0.2).unwrap();
+    assert!(dense_only[..40].iter().filter(|&&l| l == Some(0)).count() >= 35);
+    assert!(dense_only[40..].iter().all(|label| label.is_none()));
+
+    let both = model.extract_dbscan(2.0).unwrap();
+    let dense_label = both[0].unwrap();
+    assert!(both[..40].iter().all(|&label| label == Some(dense_label)));
+    let sparse_label = both[40].unwrap();
+    assert_ne!(sparse_label, dense_label);
+    assert!(both[40..80]
+        .iter()
+        .all(|&label| label == Some(sparse_label)));
+    assert_eq!(both[80..], [None, None]);
+}
+
+#[test]
+fn optics_max_eps_bounds_distances() {
+    let inputs = dmatrix![0.0; 1.0; 2.0; 10.0; 11.0; 30.0];
+    let mut model = Optics::new(2, Euclidean)
+        .unwrap()
+        .with_max_eps(5.0)
+        .unwrap();
+    model.train(&inputs).unwrap();
+
+    assert_eq!(
+        model.reachability.as_ref().unwrap(),
+        &dvector![f64::INFINITY, 1.0, 1.0, f64::INFINITY, 1.0, f64::INFINITY]
+    );
+    assert_eq!(model.core_distances.as_ref().unwrap()[5], f64::INFINITY);
+    assert_eq!(
+        model.extract_dbscan(6.0).unwrap_err(),
+        SLearningError::InvalidParameters("Eps cannot be more than the maximum eps.".to_string())
+    );
+}
+
+#[test]
+fn optics_fails_with_invalid_parameters() {
+    assert_eq!(
+        Optics::<f64, _>::new(1, Euclidean).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Minimum number of samples must be at least two.".to_string()
+        )
+    );
+    assert_eq!(
+        Optics::new(2, Euclidean)
+            .unwrap()
+            .with_max_eps(0.0)
+            .unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Maximum eps must be finite and greater than zero.".to_string()
+        )
+    );
+
+    let mut model = Optics::new(3, Euclidean).unwrap();
+    assert_eq!(
+        model.extract_dbscan(1.0).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+    assert_eq!(
+        model.train(&dmatrix![0.0; 1.0]).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Minimum number of samples is 3, but must be at most the number of observations (2)."
+                .to_string()
+        )
+    );
+    model.train(&dmatrix![0.0; 1.0; 2.0]).unwrap();
+    for eps in [-1.0, f64::NAN, f64::INFINITY] {
+        assert_eq!(
+            model.extract_dbscan(eps).unwrap_err(),
+            SLearningError::InvalidParameters(
+                "Eps must be finite and cannot be less than zero.".to_string()
+            )
+        );
+    }
+}
+
+/// 15 observations around each of three centres that are 10 apart.
+fn three_blobs() -> DMatrix<f64> {
+    let mut rng = Rng::new(4);
+    let centres = dmatrix![0.0, 0.0; 10.0, 0.0; 0.0, 10.0];
+    DMatrix::from_fn(45, 2, |i, j| {
+        centres[(i / 15, j)] + 0.5 * rng.standard_normal::<f64>()
+    })
+}
+
+#[test]
+fn affinity_propagation_finds_clusters() {
+    let inputs = three_blobs();
+    let mut model = AffinityPropagation::new().with_preference(-50.0).unwrap();
+
+    model.train(&inputs).unwrap();
+
+    assert_eq!(model.converged, Some(true));
+    let exemplar_indices = model.exemplar_indices.as_ref().unwrap();
+    assert_eq!(exemplar_indices.len(), 3);
+    let labels = model.labels.as_ref().unwrap();
+    for (cluster, &exemplar) in exemplar_indices.iter().enumerate() {
+        assert_eq!(labels[exemplar], cluster);
+        // Each blob is one cluster, with an exemplar from the blob.
+        let blob = exemplar / 15;
+        assert!(labels[blob * 15..(blob + 1) * 15]
+            .iter()
+            .all(|&label| label == cluster));
+    }
+    let centres = dmatrix![0.0, 0.0; 10.0, 0.0; 0.0, 10.0];
+    let predicted = model.predict(&centres).unwrap();
+    let expected = dvector![labels[0], labels[15], labels[30]].map(|label| label as f64);
+    assert_eq!(predicted, expected);
+}
+
+#[test]
+fn affinity_propagation_preference_controls_number_of_clusters() {
+    let inputs = three_blobs();
+    let num_clusters = |preference: f64| {
+        let mut model = AffinityPropagation::new()
+            .with_damping(0.9)
+            .unwrap()
+            .with_preference(preference)
+            .unwrap();
+        model.train(&inputs).unwrap();
+        model.exemplar_indices.unwrap().len()
+    };
+
+    assert_eq!(num_clusters(-10000.0), 1);
+    assert_eq!(num_clusters(-50.0), 3);
+    assert!(num_clusters(-0.1) > 3);
+}
+
+#[test]
+fn affinity_propagation_fits_similarity_matrix() {
+    // Two pairs of similar observations.
+    let similarity = dmatrix![
+        0.0, -1.0, -9.0, -8.0;
+        -1.0, 0.0, -9.0, -9.0;
+        -9.0, -9.0, 0.0, -2.0;
+        -8.0, -9.0, -2.0, 0.0
+    ];
+    let mut model = AffinityPropagation::new().with_preference(-4.0).unwrap();
+
+    model.fit_similarity(&similarity).unwrap();
+
+    assert_eq!(model.labels.as_ref().unwrap(), &vec![0, 0, 1, 1]);
+    let exemplar_indices = model.exemplar_indices.as_ref().unwrap();
+    assert_eq!(exemplar_indices.len(), 2);
+    assert!(exemplar_indices[0] < 2 && exemplar_indices[1] >= 2);
+    assert_eq!(
+        model.predict(&dmatrix![0.0]).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Predicting needs a model fitted to observations, rather than a similarity matrix."
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn affinity_propagation_fits_one_observation() {
+    let mut model = AffinityPropagation::new();
+
+    model.train(&dmatrix![1.0, 2.0]).unwrap();
+
+    assert_eq!(model.exemplar_indices, Some(vec![0]));
+    assert_eq!(model.labels, Some(vec![0]));
+    assert_eq!(model.predict(&dmatrix![5.0, 5.0]).unwrap(), dvector![0.0]);
+}
+
+#[test_case(0.4; "small damping")]
+#[test_case(1.0; "large damping")]
+#[test_case(f64::NAN; "nan damping")]
+fn affinity_propagation_fails_with_invalid_damping(damping: f64) {
+    let actual = AffinityPropagation::new().with_damping(damping);
+
+    assert_eq!(
+        actual.unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Damping must be at least one half and less than one.".to_string()
+        )
+    );
+}
+
+#[test_case(f64::NAN; "nan preference")]
+#[test_case(f64::NEG_INFINITY; "infinite preference")]
+fn affinity_propagation_fails_with_invalid_preference(preference: f64) {
+    let actual = AffinityPropagation::new().with_preference(preference);
+
+    assert_eq!(
+        actual.unwrap_err(),
+        SLearningError::InvalidParameters("Preference must be finite.".to_string())
+    );
+}
+
+#[test]
+fn affinity_propagation_fails_with_invalid_data() {
+    let mut model = AffinityPropagation::new();
+    assert_eq!(
+        model.predict(&dmatrix![1.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+    assert_eq!(
+        model.train(&DMatrix::zeros(0, 2)).unwrap_err(),
+        SLearningError::InvalidData("Cannot train with zero observations.".to_string())
+    );
+    assert_eq!(
+        model.fit_similarity(&DMatrix::zeros(2, 3)).unwrap_err(),
+        SLearningError::InvalidData(
+            "The similarity matrix has 2 rows and 3 columns. These must be equal.".to_string()
+        )
+    );
+
+    model
+        .train(&dmatrix![0.0, 0.0; 1.0, 1.0; 5.0, 5.0])
+        .unwrap();
+    assert_eq!(
+        model.predict(&dmatrix![1.0]).unwrap_err(),
+        SLearningError::InvalidData(
+            "This model was trained with 2 variables, but this input has 1 variables. These must be equal."
+                .to_string()
+        )
+    );
+}
+
+/// Whether each block of 15 consecutive labels is one cluster, with different clusters.
+fn labels_match_blobs(labels: &DVector<f64>) -> bool {
+    let blob_labels = [labels[0], labels[15], labels[30]];
+    labels
+        .iter()
+        .enumerate()
+        .all(|(i, &label)| label == blob_labels[i / 15])
+        && blob_labels[0] != blob_labels[1]
+        && blob_labels[1] != blob_labels[2]
+        && blob_labels[0] != blob_labels[2]
+}
+
+#[test_case(50; "one leaf")]
+#[test_case(3; "deep tree")]
+fn birch_finds_clusters(branching_factor: usize) {
+    let inputs = three_blobs();
+    let mut model = Birch::new(0.5)
+        .unwrap()
+        .with_branching_factor(branching_factor)
+        .unwrap()
+        .with_n_clusters(3)
+        .unwrap();
+
+    model.train(&inputs).unwrap();
+
+    let num_subclusters = model.subcluster_centers.as_ref().unwrap().nrows();
+    assert!(num_subclusters > 3 && num_subclusters < 45);
+    let subcluster_labels = model.subcluster_labels.as_ref().unwrap();
+    assert_eq!(subcluster_labels.len(), num_subclusters);
+    assert!(labels_match_blobs(&model.predict(&inputs).unwrap()));
+}
+
+#[test]
+fn birch_subclusters_are_observations_with_small_threshold() {
+    let inputs = dmatrix![0.0, 0.0; 1.0, 0.0; 0.0, 1.0; 5.0, 5.0; 5.0, 6.0];
+    let mut model = Birch::new(1e-3).unwrap().with_branching_factor(2).unwrap();
+
+    model.train(&inputs).unwrap();
+
+    let centers = model.subcluster_centers.as_ref().unwrap();
+    assert_eq!(centers.nrows(), 5);
+    for row in inputs.row_iter() {
+        assert!(centers.row_iter().any(|center| center == row));
+    }
+    // Without a number of clusters, each subcluster is a cluster.
+    assert_eq!(
+        model.subcluster_labels.as_ref().unwrap(),
+        &vec![0, 1, 2, 3, 4]
+    );
+    let mut labels: Vec<f64> = model.predict(&inputs).unwrap().iter().copied().collect();
+    labels.sort_by(f64::total_cmp);
+    assert_eq!(labels, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+}
+
+#[test]
+fn birch_partial_fit_matches_train() {
+    let inputs = three_blobs();
+    let mut model = Birch::new(0.5)
+        .unwrap()
+        .with_branching_factor(4)
+        .unwrap()
+        .with_n_clusters(3)
+        .unwrap();
+    model.train(&inputs).unwrap();
+    let mut streaming = Birch::new(0.5)
+        .unwrap()
+        .with_branching_factor(4)
+        .unwrap()
+        .with_n_clusters(3)
+        .unwrap();
+
+    for chunk in 0..3 {
+        streaming
+            .partial_fit(&inputs.rows(chunk * 15, 15).into_owned())
+            .unwrap();
+    }
+
+    assert_eq!(streaming.subcluster_centers, model.subcluster_centers);
+    assert_eq!(streaming.subcluster_labels, model.subcluster_labels);
+    // Training starts again with a new tree.
+    let first_chunk = inputs.rows(0, 15).into_owned();
+    streaming.train(&first_chunk).unwrap();
+    model.train(&first_chunk).unwrap();
+    assert_eq!(streaming.subcluster_centers, model.subcluster_centers);
+}
+
+#[test]
+fn birch_fails_with_invalid_parameters() {
+    assert_eq!(
+        Birch::new(0.0).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Threshold must be finite and greater than zero.".to_string()
+        )
+    );
+    let model = Birch::new(0.5).unwrap();
+    assert_eq!(
+        model.with_branching_factor(1).unwrap_err(),
+        SLearningError::InvalidParameters("Branching factor must be at least two.".to_string())
+    );
+    let model = Birch::new(0.5).unwrap();
+    assert_eq!(
+        model.with_n_clusters(0).unwrap_err(),
+        SLearningError::InvalidParameters("Number of clusters must be at least one.".to_string())
+    );
+}
+
+#[test]
+fn birch_fails_with_invalid_data() {
+    let mut model = Birch::new(0.5).unwrap();
+    assert_eq!(
+        model.predict(&dmatrix![1.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+    assert_eq!(
+        model.train(&DMatrix::zeros(0, 2)).unwrap_err(),
+        SLearningError::InvalidData("Cannot train with zero observations.".to_string())
+    );
+
+    model.train(&dmatrix![0.0, 0.0; 1.0, 1.0]).unwrap();
+    let expected = SLearningError::InvalidData(
+        "This model was trained with 2 variables, but this input has 1 variables. These must be equal."
+            .to_string(),
+    );
+    assert_eq!(model.predict(&dmatrix![1.0]).unwrap_err(), expected);
+    assert_eq!(model.partial_fit(&dmatrix![1.0]).unwrap_err(), expected);
+}
+
+#[test]
+fn clusterers_fail_with_non_finite_inputs() {
+    let inputs = dmatrix![0.0, 0.0; 1.0, f64::INFINITY; 5.0, 5.0];
+    let expected = SLearningError::InvalidData(
+        "Input has a non-finite value for observation 1 and variable 1.".to_string(),
+    );
+
+    let mut optics = Optics::new(2, Euclidean).unwrap();
+    assert_eq!(optics.train(&inputs).unwrap_err(), expected);
+    let mut affinity_propagation = AffinityPropagation::new();
+    assert_eq!(affinity_propagation.train(&inputs).unwrap_err(), expected);
+    let mut birch = Birch::new(0.5).unwrap();
+    assert_eq!(birch.train(&inputs).unwrap_err(), expected);
+}
+
+#[test]
+fn optics_cannot_predict_new_observations() {
+    let mut model = Optics::new(2, Euclidean).unwrap();
+    assert_eq!(
+        model.predict(&dmatrix![0.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+
+    model.train(&dmatrix![0.0; 1.0; 2.0]).unwrap();
+
+    assert_eq!(
+        model.predict(&dmatrix![0.0]).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "OPTICS cannot predict the clusters of new observations. Use extract_dbscan for the training observations.".to_string()
+        )
+    );
+}
@@ -0,0 +1,186 @@
+use nalgebra::{dmatrix, dvector, DMatrix};
+
+use slearning::covariance::{EmpiricalCovariance, LedoitWolf, MinCovDet};
+use slearning::random::Rng;
+use slearning::{SLearningError, UnsupervisedModel};
+
+fn square() -> DMatrix<f64> {
+    dmatrix![
+        0.0, 0.0;
+        2.0, 0.0;
+        0.0, 2.0;
+        2.0, 2.0
+    ]
+}
+
+#[test]
+fn empirical_covariance_works() {
+    let mut estimator = EmpiricalCovariance::new();
+    estimator.train(&square()).unwrap();
+
+    assert_eq!(estimator.location, Some(dvector![1.0, 1.0]));
+    assert_eq!(estimator.covariance, Some(dmatrix![1.0, 0.0; 0.0, 1.0]));
+    assert_eq!(estimator.precision, Some(dmatrix![1.0, 0.0; 0.0, 1.0]));
+
+    let distances = estimator.predict(&dmatrix![1.0, 1.0; 3.0, 1.0]).unwrap();
+    assert_eq!(distances, dvector![0.0, 4.0]);
+}
+
+#[test]
+fn empirical_covariance_fails_with_singular_covariance() {
+    let inputs = dmatrix![1.0, 2.0, 3.0; 4.0, 5.0, 6.0];
+    let expected = SLearningError::InvalidData("The covariance matrix is not invertible.".into());
+
+    let mut estimator = EmpiricalCovariance::new();
+    assert_eq!(estimator.train(&inputs).unwrap_err(), expected);
+}
+
+#[test]
+fn ledoit_wolf_does_not_shrink_scaled_identity() {
+    let mut estimator = LedoitWolf::new();
+    estimator.train(&square()).unwrap();
+
+    assert_eq!(estimator.shrinkage, Some(0.0));
+    assert_eq!(estimator.covariance, Some(dmatrix![1.0, 0.0; 0.0, 1.0]));
+}
+
+#[test]
+fn ledoit_wolf_works_with_more_variables_than_observations() {
+    let inputs = dmatrix![
+        1.0, 2.0, 3.0, 0.0;
+        4.0, 5.0, 7.0, 1.0;
+        2.0, 0.0, 1.0, 3.0
+    ];
+
+    let mut estimator = LedoitWolf::new();
+    estimator.train(&inputs).unwrap();
+
+    let shrinkage = estimator.shrinkage.unwrap();
+    assert!(shrinkage > 0.0 && shrinkage <= 1.0);
+    assert!(estimator.precision.is_some());
+}
+
+#[test]
+fn min_cov_det_ignores_outliers() {
+    let inputs = dmatrix![
+        -1.0, -1.0;
+        -1.0, 0.0;
+        -1.0, 1.0;
+        0.0, -1.0;
+        0.0, 0.0;
+        0.0, 1.0;
+        1.0, -1.0;
+        1.0, 0.0;
+        1.0, 1.0;
+        0.5, -0.5;
+        -0.5, 0.5;
+        50.0, 50.0
+    ];
+
+    let mut robust = MinCovDet::default();
+    robust.train(&inputs).unwrap();
+    let mut empirical = EmpiricalCovariance::new();
+    empirical.train(&inputs).unwrap();
+
+    let support = robust.support.as_ref().unwrap();
+    assert!(support[..11].iter().all(|&in_support| in_support));
+    assert!(!support[11]);
+    assert_eq!(robust.location, Some(dvector![0.0, 0.0]));
+
+    // The outlier is much further away under the robust estimate.
+    let outlier = dmatrix![50.0, 50.0];
+    let robust_distance = robust.predict(&outlier).unwrap()[0];
+    let empirical_distance = empirical.predict(&outlier).unwrap()[0];
+    assert!(robust_distance > 100.0 * empirical_distance);
+}
+
+#[test]
+fn min_cov_det_fails_with_invalid_support_fraction() {
+    let expected = SLearningError::InvalidParameters(
+        "Support fraction must be greater than zero and at most one.".into(),
+    );
+
+    assert_eq!(MinCovDet::new(0.0).unwrap_err(), expected);
+    assert_eq!(MinCovDet::new(1.5).unwrap_err(), expected);
+}
+
+#[test]
+fn covariance_fails_to_train_with_zero_observations() {
+    let inputs: DMatrix<f64> = dmatrix![];
+    let expected = SLearningError::InvalidData("Cannot train with zero observations.".into());
+
+    assert_eq!(
+        EmpiricalCovariance::new().train(&inputs).unwrap_err(),
+        expected
+    );
+    assert_eq!(LedoitWolf::new().train(&inputs).unwrap_err(), expected);
+    assert_eq!(MinCovDet::default().train(&inputs).unwrap_err(), expected);
+}
+
+#[test]
+fn covariance_fails_to_train_with_non_finite_inputs() {
+    let mut inputs = square();
+    inputs[(2, 1)] = f64::NAN;
+    let expected = SLearningError::InvalidData(
+        "Input has a non-finite value for observation 2 and variable 1.".into(),
+    );
+
+    assert_eq!(
+        EmpiricalCovariance::new().train(&inputs).unwrap_err(),
+        expected
+    );
+    assert_eq!(LedoitWolf::new().train(&inputs).unwrap_err(), expected);
+    assert_eq!(MinCovDet::default().train(&inputs).unwrap_err(), expected);
+}
+
+#[test]
+fn covariance_fails_to_predict_when_untrained() {
+    let estimator: EmpiricalCovariance<f64> = EmpiricalCovariance::new();
+    let actual = estimator.predict(&square()).unwrap_err();
+    assert_eq!(actual, SLearningError::UntrainedModel);
+}
+
+#[test]
+fn covariance_fails_to_predict_with_wrong_dimensions() {
+    let mut estimator = LedoitWolf::new();
+    estimator.train(&square()).unwrap();
+    let expected = SLearningError::InvalidData(
+        "This model was trained with 2 variables, but this input has 3 variables. These must be equal.".to_string()
+    );
+
+    let actual = estimator.predict(&dmatrix![1.0, 2.0, 3.0]).unwrap_err();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn covariance_samples_match_estimate() {
+    let mut model = EmpiricalCovariance::new();
+    model
+        .train(&dmatrix![0.0, 1.0; 2.0, 2.0; 1.0, 4.0; 3.0, 5.0; 4.0, 3.0])
+        .unwrap();
+    let mut rng = Rng::new(5);
+
+    let samples = model.sample(20_000, &mut rng).unwrap();
+
+    assert_eq!(samples.shape(), (20_000, 2));
+    let mut refit = EmpiricalCovariance::new();
+    refit.train(&samples).unwrap();
+    let location_error = refit.location.unwrap() - model.location.as_ref().unwrap();
+    let covariance_error = refit.covariance.unwrap() - model.covariance.as_ref().unwrap();
+    assert!(location_error.amax() < 0.05);
+    assert!(covariance_error.amax() < 0.1);
+}
+
+#[test]
+fn covariance_sample_fails_when_untrained() {
+    let mut rng = Rng::new(0);
+
+    assert_eq!(
+        LedoitWolf::<f64>::new().sample(3, &mut rng).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+    assert_eq!(
+        MinCovDet::<f64>::default().sample(3, &mut rng).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
@@ -0,0 +1,490 @@
+use nalgebra::{dmatrix, DMatrix};
+use test_case::test_case;
+
+use slearning::decomposition::{
+    Cca, DictionaryLearning, IncrementalPca, MissingValuesPca, SparseCoding,
+};
+use slearning::optim::ConvergenceConfig;
+use slearning::random::Rng;
+use slearning::{SLearningError, Transformer};
+
+/// Two views of 500 observations that share two latent variables, with correlations of about 0.9
+/// and 0.6 between the views' noisy copies of them.
+fn two_views() -> (DMatrix<f64>, DMatrix<f64>) {
+    let mut rng = Rng::new(11);
+    let num_obs = 500;
+    let latent = DMatrix::from_fn(num_obs, 2, |_, _| rng.standard_normal::<f64>());
+    let noise_scales = [1.0 / 3.0, 1.0 / 1.5f64.sqrt()];
+    let mut noisy = |scale: f64| {
+        DMatrix::from_fn(num_obs, 2, |i, j| {
+            latent[(i, j)] + noise_scales[j] * scale * rng.standard_normal::<f64>()
+        })
+    };
+    let x_latent = noisy(1.0);
+    let y_latent = noisy(1.0);
+    let x = DMatrix::from_fn(num_obs, 3, |i, j| match j {
+        0 => x_latent[(i, 0)] + x_latent[(i, 1)],
+        1 => x_latent[(i, 0)] - x_latent[(i, 1)],
+        _ => rng.standard_normal(),
+    });
+    let y = DMatrix::from_fn(num_obs, 2, |i, j| match j {
+        0 => 2.0 * y_latent[(i, 0)],
+        _ => y_latent[(i, 1)] - y_latent[(i, 0)],
+    });
+    (x, y)
+}
+
+fn correlation(a: &DMatrix<f64>, b: &DMatrix<f64>, column: usize) -> f64 {
+    let a = a.column(column).add_scalar(-a.column(column).mean());
+    let b = b.column(column).add_scalar(-b.column(column).mean());
+    a.dot(&b) / (a.norm() * b.norm())
+}
+
+#[test]
+fn cca_finds_correlated_projections() {
+    let (x, y) = two_views();
+    let mut cca = Cca::new(2).unwrap();
+
+    cca.fit(&x, &y).unwrap();
+
+    let correlations = cca.correlations.as_ref().unwrap();
+    assert!((correlations[0] - 0.9).abs() < 0.05);
+    assert!((correlations[1] - 0.6).abs() < 0.1);
+
+    let x_scores = cca.transform_x(&x).unwrap();
+    let y_scores = cca.transform_y(&y).unwrap();
+    assert_eq!(x_scores.shape(), (500, 2));
+    for component in 0..2 {
+        let actual = correlation(&x_scores, &y_scores, component);
+        assert!((actual - correlations[component]).abs() < 1e-10);
+        assert!((x_scores.column(component).variance() - 1.0).abs() < 1e-10);
+    }
+    assert!(correlation(&x_scores, &x_scores.columns(1, 1).into_owned(), 0).abs() < 1e-10);
+}
+
+#[test]
+fn cca_regularization_allows_collinear_variables() {
+    let (x, y) = two_views();
+    let mut collinear = x.clone().insert_column(3, 0.0);
+    collinear.set_column(3, &x.column(0));
+
+    let expected = SLearningError::InvalidData(
+        "The covariance matrix of the first view is not positive definite. Try adding regularization."
+            .to_string(),
+    );
+    assert_eq!(
+        Cca::new(1).unwrap().fit(&collinear, &y).unwrap_err(),
+        expected
+    );
+
+    let mut cca = Cca::new(1).unwrap().with_regularization(1e-3).unwrap();
+    cca.fit(&collinear, &y).unwrap();
+    assert!((cca.correlations.unwrap()[0] - 0.9).abs() < 0.05);
+}
+
+#[test]
+fn cca_fails_with_invalid_parameters() {
+    let (x, y) = two_views();
+    assert_eq!(
+        Cca::<f64>::new(0).unwrap_err(),
+        SLearningError::InvalidParameters("Number of components must be at least one.".to_string())
+    );
+    assert_eq!(
+        Cca::new(1).unwrap().with_regularization(-1.0).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Regularization must be finite and cannot be less than zero.".to_string()
+        )
+    );
+    assert_eq!(
+        Cca::new(1)
+            .unwrap()
+            .with_regularization(f64::NAN)
+            .unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Regularization must be finite and cannot be less than zero.".to_string()
+        )
+    );
+    assert_eq!(
+        Cca::new(3).unwrap().fit(&x, &y).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Number of components is 3, but must be at most 2, the number of variables in the smaller view."
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn cca_fails_with_invalid_data() {
+    let mut cca = Cca::new(1).unwrap();
+    assert_eq!(
+        cca.transform_x(&dmatrix![1.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+    assert_eq!(
+        cca.fit(&dmatrix![1.0; 2.0], &dmatrix![1.0]).unwrap_err(),
+        SLearningError::InvalidData(
+            "The first view has 2 observation(s), but the second view has 1 observation(s). These must be equal."
+                .to_string()
+        )
+    );
+
+    let (x, y) = two_views();
+    assert_eq!(
+        cca.fit(&x, &DMatrix::zeros(0, 2)).unwrap_err(),
+        SLearningError::InvalidData("Cannot train with zero observations.".to_string())
+    );
+    let mut y_nan = y.clone();
+    y_nan[(1, 0)] = f64::NAN;
+    assert_eq!(
+        cca.fit(&x, &y_nan).unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has a non-finite value for observation 1 and variable 0.".to_string()
+        )
+    );
+    cca.fit(&x, &y).unwrap();
+    assert_eq!(
+        cca.transform_y(&x).unwrap_err(),
+        SLearningError::InvalidData(
+            "This model was trained with 2 variables, but this input has 3 variables. These must be equal."
+                .to_string()
+        )
+    );
+}
+
+/// 200 observations that are each a random multiple of one of five random unit atoms in ten
+/// dimensions, and the atoms (rows).
+fn sparse_signals() -> (DMatrix<f64>, DMatrix<f64>) {
+    let mut rng = Rng::new(5);
+    let mut atoms = DMatrix::from_fn(5, 10, |_, _| rng.standard_normal::<f64>());
+    for mut atom in atoms.row_iter_mut() {
+        atom /= atom.norm();
+    }
+    let mut codes = DMatrix::zeros(200, 5);
+    for i in 0..200 {
+        let sign = if rng.uniform::<f64>() < 0.5 {
+            -1.0
+        } else {
+            1.0
+        };
+        codes[(i, rng.below(5))] = sign * (1.0 + rng.uniform::<f64>());
+    }
+    (codes * &atoms, atoms)
+}
+
+#[test_case(SparseCoding::Omp { n_nonzero: 1 }, None, 0, 1e-8; "omp")]
+#[test_case(SparseCoding::Omp { n_nonzero: 1 }, Some(50), 4, 1e-4; "omp mini-batch")]
+#[test_case(SparseCoding::Lasso { alpha: 0.05 }, None, 0, 0.05; "lasso")]
+fn dictionary_learning_recovers_atoms(
+    coding: SparseCoding<f64>,
+    batch_size: Option<usize>,
+    seed: u64,
+    max_error: f64,
+) {
+    let (inputs, true_atoms) = sparse_signals();
+    let mut model = DictionaryLearning::new(5, coding)
+        .unwrap()
+        .with_convergence(ConvergenceConfig::new(400, 1e-12).unwrap())
+        .with_seed(seed);
+    if let Some(batch_size) = batch_size {
+        model = model.with_batch_size(batch_size).unwrap();
+    }
+
+    let codes = model.fit_transform(&inputs).unwrap();
+
+    let components = model.components.as_ref().unwrap();
+    assert_eq!(components.shape(), (5, 10));
+    for true_atom in true_atoms.row_iter() {
+        let best_match = components
+            .row_iter()
+            .map(|atom| (atom.dot(&true_atom) / atom.norm()).abs())
+            .fold(0.0, f64::max);
+        assert!(best_match > 0.99, "best match {best_match}");
+    }
+    let reconstruction_error = (&codes * components - &inputs).norm() / inputs.norm();
+    assert!(
+        reconstruction_error < max_error,
+        "error {reconstruction_error}"
+    );
+    for code in codes.row_iter() {
+        assert!(code.iter().filter(|c| c.abs() > 1e-8).count() <= 1);
+    }
+}
+
+#[test_case(0, SparseCoding::Omp { n_nonzero: 1 }, "Number of components must be at least one."; "zero components")]
+#[test_case(2, SparseCoding::Omp { n_nonzero: 0 }, "Number of non-zero coefficients must be at least one."; "zero non-zero")]
+#[test_case(2, SparseCoding::Lasso { alpha: -1.0 }, "Alpha must be finite and cannot be less than zero."; "negative alpha")]
+#[test_case(2, SparseCoding::Lasso { alpha: f64::NAN }, "Alpha must be finite and cannot be less than zero."; "nan alpha")]
+fn dictionary_learning_fails_with_invalid_parameters(
+    n_components: usize,
+    coding: SparseCoding<f64>,
+    message: &str,
+) {
+    let error = DictionaryLearning::new(n_components, coding).unwrap_err();
+
+    assert_eq!(
+        error,
+        SLearningError::InvalidParameters(message.to_string())
+    );
+}
+
+#[test]
+fn dictionary_learning_fails_with_invalid_data() {
+    let mut model = DictionaryLearning::new(2, SparseCoding::Omp { n_nonzero: 1 }).unwrap();
+    assert_eq!(
+        model.transform(&dmatrix![1.0, 2.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+
+    model.fit(&dmatrix![1.0, 2.0; 3.0, 1.0; 0.0, 1.0]).unwrap();
+    assert_eq!(
+        model.transform(&dmatrix![1.0]).unwrap_err(),
+        SLearningError::InvalidData(
+            "This model was trained with 2 variables, but this input has 1 variables. These must be equal."
+                .to_string()
+        )
+    );
+}
+
+#[test_case(f64::NAN; "nan")]
+#[test_case(f64::INFINITY; "infinite")]
+fn dictionary_learning_fails_with_non_finite_data(value: f64) {
+    let mut model = DictionaryLearning::new(2, SparseCoding::Omp { n_nonzero: 1 }).unwrap();
+    let expected = SLearningError::InvalidData(
+        "Input has a non-finite value for observation 1 and variable 0.".to_string(),
+    );
+
+    assert_eq!(
+        model.fit(&dmatrix![1.0, 2.0; value, 1.0]).unwrap_err(),
+        expected
+    );
+    model.fit(&dmatrix![1.0, 2.0; 3.0, 1.0; 0.0, 1.0]).unwrap();
+    assert_eq!(
+        model
+            .transform(&dmatrix![1.0, 2.0; value, 1.0])
+            .unwrap_err(),
+        expected
+    );
+}
+
+/// 60 observations of four variables, which span a two-dimensional plane around (1, -2, 3, 0)
+/// when `noise` is zero.
+fn planar_inputs(noise: f64) -> DMatrix<f64> {
+    let mut rng = Rng::new(5);
+    DMatrix::from_fn(60, 4, |i, j| {
+        let (a, b) = ((i as f64 * 0.7).sin() * 3.0, (i as f64 * 1.3).cos());
+        let plane = match j {
+            0 => 1.0 + a,
+            1 => -2.0 + a - b,
+            2 => 3.0 + 2.0 * b,
+            _ => a + b,
+        };
+        plane + noise * rng.standard_normal::<f64>()
+    })
+}
+
+#[test_case(0.0, 2; "exact plane")]
+#[test_case(0.5, 4; "all components")]
+fn incremental_pca_batches_match_full_fit(noise: f64, n_components: usize) {
+    let inputs = planar_inputs(noise);
+    let mut full = IncrementalPca::new(n_components).unwrap();
+    let full_projections = full.fit_transform(&inputs).unwrap();
+    let mut batched = IncrementalPca::new(n_components)
+        .unwrap()
+        .with_batch_size(7)
+        .unwrap();
+    let batched_projections = batched.fit_transform(&inputs).unwrap();
+
+    assert_eq!(batched.count(), 60);
+    assert!((batched.mean().unwrap() - full.mean().unwrap()).amax() < 1e-10);
+    let full_components = full.components.as_ref().unwrap();
+    let batched_components = batched.components.as_ref().unwrap();
+    assert!((batched_components - full_components).amax() < 1e-8);
+    assert!((batched_projections - full_projections).amax() < 1e-8);
+    let full_variance = full.explained_variance.as_ref().unwrap();
+    let batched_variance = batched.explained_variance.as_ref().unwrap();
+    assert!((batched_variance - full_variance).amax() < 1e-8);
+    assert!(batched_variance.iter().all(|&v| v > 0.0));
+}
+
+#[test]
+fn incremental_pca_explains_all_variance_with_all_components() {
+    let inputs = planar_inputs(0.5);
+    let mut model = IncrementalPca::new(4).unwrap().with_batch_size(10).unwrap();
+    let projections = model.fit_transform(&inputs).unwrap();
+
+    let total_variance: f64 = slearning::stats::var(&inputs, 1).unwrap().sum();
+    let explained = model.explained_variance.as_ref().unwrap();
+    assert!((explained.sum() - total_variance).abs() < 1e-8);
+    let ratio = model.explained_variance_ratio.as_ref().unwrap();
+    assert!((ratio.sum() - 1.0).abs() < 1e-10);
+    assert!(ratio.as_slice().windows(2).all(|pair| pair[0] >= pair[1]));
+
+    let reconstructed = model.inverse_transform(&projections).unwrap();
+    assert!((reconstructed - &inputs).amax() < 1e-8);
+}
+
+#[test]
+fn incremental_pca_fails_with_invalid_parameters() {
+    let expected = |message: &str| SLearningError::InvalidParameters(message.to_string());
+    assert_eq!(
+        IncrementalPca::<f64>::new(0).unwrap_err(),
+        expected("Number of components must be at least one.")
+    );
+    assert_eq!(
+        IncrementalPca::<f64>::new(1)
+            .unwrap()
+            .with_batch_size(0)
+            .unwrap_err(),
+        expected("Batch size must be at least one.")
+    );
+    let mut model = IncrementalPca::new(3).unwrap();
+    assert_eq!(
+        model.partial_fit(&dmatrix![1.0, 2.0, 3.0; 3.0, 1.0, 0.0]).unwrap_err(),
+        expected("Number of components is 3, but must be at most 2, the smaller of the number of observations and variables in the first batch.")
+    );
+}
+
+#[test]
+fn incremental_pca_fails_with_invalid_data() {
+    let mut model = IncrementalPca::new(1).unwrap();
+    assert_eq!(
+        model.transform(&dmatrix![1.0, 2.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+
+    model.partial_fit(&dmatrix![1.0, 2.0; 3.0, 1.0]).unwrap();
+    let expected = SLearningError::InvalidData(
+        "This model was trained with 2 variables, but this input has 1 variables. These must be equal."
+            .to_string(),
+    );
+    assert_eq!(model.partial_fit(&dmatrix![1.0]).unwrap_err(), expected);
+    assert_eq!(model.transform(&dmatrix![1.0]).unwrap_err(), expected);
+    assert_eq!(
+        model.inverse_transform(&dmatrix![1.0, 2.0]).unwrap_err(),
+        SLearningError::InvalidData(
+            "This model has 1 components, but this input has 2 columns. These must be equal."
+                .to_string()
+        )
+    );
+}
+
+#[test_case(f64::NAN; "nan")]
+#[test_case(f64::INFINITY; "infinite")]
+fn incremental_pca_fails_with_non_finite_batch(value: f64) {
+    let mut model = IncrementalPca::new(1).unwrap();
+    let expected = SLearningError::InvalidData(
+        "Input has a non-finite value for observation 1 and variable 0.".to_string(),
+    );
+
+    assert_eq!(
+        model
+            .partial_fit(&dmatrix![1.0, 2.0; value, 1.0])
+            .unwrap_err(),
+        expected
+    );
+    model.partial_fit(&dmatrix![1.0, 2.0; 3.0, 1.0]).unwrap();
+    assert_eq!(
+        model
+            .partial_fit(&dmatrix![1.0, 2.0; value, 1.0])
+            .unwrap_err(),
+        expected
+    );
+}
+
+/// The planar inputs with every seventh value (by column-major position) missing.
+fn with_missing(inputs: &DMatrix<f64>) -> DMatrix<f64> {
+    let mut missing = inputs.clone();
+    for (index, value) in missing.iter_mut().enumerate() {
+        if index % 7 == 3 {
+            *value = f64::NAN;
+        }
+    }
+    missing
+}
+
+#[test]
+fn missing_values_pca_recovers_low_rank_values() {
+    let complete = planar_inputs(0.0);
+    let inputs = with_missing(&complete);
+    let mut model = MissingValuesPca::new(2).unwrap();
+    let scores = model.fit_transform(&inputs).unwrap();
+
+    assert!((model.imputed.as_ref().unwrap() - &complete).amax() < 1e-4);
+    assert!((model.impute(&inputs).unwrap() - &complete).amax() < 1e-4);
+
+    let mut full = IncrementalPca::new(2).unwrap();
+    let full_scores = full.fit_transform(&complete).unwrap();
+    assert!((model.components.as_ref().unwrap() - full.components.as_ref().unwrap()).amax() < 1e-4);
+    assert!((scores - &full_scores).amax() < 1e-4);
+    assert!((model.transform(&complete).unwrap() - full_scores).amax() < 1e-4);
+}
+
+#[test]
+fn missing_values_pca_matches_pca_without_missing_values() {
+    let inputs = planar_inputs(0.5);
+    let mut model = MissingValuesPca::new(3).unwrap();
+    let scores = model.fit_transform(&inputs).unwrap();
+    let mut full = IncrementalPca::new(3).unwrap();
+    let full_scores = full.fit_transform(&inputs).unwrap();
+
+    assert!(
+        (model.components.as_ref().unwrap() - full.components.as_ref().unwrap()).amax() < 1e-10
+    );
+    assert!(
+        (model.explained_variance.as_ref().unwrap() - full.explained_variance.as_ref().unwrap())
+            .amax()
+            < 1e-10
+    );
+    assert!((scores - full_scores).amax() < 1e-10);
+    assert_eq!(model.imputed.as_ref().unwrap(), &inputs);
+}
+
+#[test]
+fn missing_values_pca_fails_with_invalid_parameters() {
+    assert_eq!(
+        MissingValuesPca::<f64>::new(0).unwrap_err(),
+        SLearningError::InvalidParameters("Number of components must be at least one.".to_string())
+    );
+    let mut model = MissingValuesPca::new(3).unwrap();
+    assert_eq!(
+        model.fit(&dmatrix![1.0, 2.0, 3.0; 3.0, 1.0, 0.0]).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Number of components is 3, but must be at most 2, the smaller of the number of observations and variables."
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn missing_values_pca_fails_with_invalid_data() {
+    let mut model = MissingValuesPca::new(1).unwrap();
+    assert_eq!(
+        model.transform(&dmatrix![1.0, 2.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+    assert_eq!(
+        model
+            .fit(&dmatrix![1.0, f64::NAN; 3.0, f64::NAN])
+            .unwrap_err(),
+        SLearningError::InvalidData("Variable 1 has no observed values.".to_string())
+    );
+    assert_eq!(
+        model
+            .fit(&dmatrix![1.0, f64::NAN; 3.0, f64::INFINITY])
+            .unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has a non-finite value for observation 1 and variable 1.".to_string()
+        )
+    );
+
+    model
+        .fit(&dmatrix![1.0, 2.0; 3.0, f64::NAN; 0.0, 1.0])
+        .unwrap();
+    assert_eq!(
+        model.impute(&dmatrix![1.0]).unwrap_err(),
+        SLearningError::InvalidData(
+            "This model was trained with 2 variables, but this input has 1 variables. These must be equal."
+                .to_string()
+        )
+    );
+}
@@ -0,0 +1,260 @@
+use nalgebra::{dmatrix, DMatrix, DVector};
+use std::sync::{Arc, Mutex};
+
+use slearning::anomaly::OneClassSvm;
+use slearning::cluster::AffinityPropagation;
+use slearning::decomposition::{DictionaryLearning, MissingValuesPca, SparseCoding};
+use slearning::diagnostics::{Diagnostics, Warning};
+use slearning::gaussian_process::GpcClassifier;
+use slearning::kernel::Rbf;
+use slearning::linear_regression::{OlsRegressor, RidgeRegressor};
+use slearning::optim::ConvergenceConfig;
+use slearning::ordinal_regression::OrdinalRegressor;
+use slearning::random::Rng;
+use slearning::ranking::PairwiseRanker;
+use slearning::recommendation::AlsFactorizer;
+use slearning::semi_supervised::{Affinity, LabelPropagation};
+use slearning::survival::{CoxPhModel, TiesMethod};
+use slearning::timeseries::{Arima, ExponentialSmoothing};
+use slearning::{SupervisedModel, Transformer, UnsupervisedModel};
+
+/// Diagnostics that collect the warnings, and the collected warnings.
+fn collector() -> (Diagnostics, Arc<Mutex<Vec<Warning>>>) {
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&warnings);
+    let diagnostics = Diagnostics::new(move |warning: &Warning| {
+        sink.lock().unwrap().push(warning.clone());
+    });
+    (diagnostics, warnings)
+}
+
+#[test]
+fn well_conditioned_fit_has_no_warnings() {
+    let (diagnostics, warnings) = collector();
+    let mut ols = OlsRegressor::default().with_diagnostics(diagnostics);
+
+    ols.train(
+        dmatrix![0.0, 1.0; 1.0, 0.0; 2.0, 2.0; 3.0, 1.0],
+        DVector::from_vec(vec![1.0, 2.0, 4.0, 4.0]),
+    )
+    .unwrap();
+
+    assert!(warnings.lock().unwrap().is_empty());
+}
+
+#[test]
+fn ols_warns_when_ill_conditioned() {
+    let (diagnostics, warnings) = collector();
+    let mut ols = OlsRegressor::default().with_diagnostics(diagnostics);
+    let inputs = DMatrix::from_fn(20, 2, |i, j| i as f64 + j as f64 * 1e-6 * (i % 3) as f64);
+    let outputs = DVector::from_fn(20, |i, _| i as f64);
+
+    ols.train(inputs, outputs).unwrap();
+
+    let warnings = warnings.lock().unwrap();
+    assert_eq!(warnings.len(), 1);
+    assert!(
+        matches!(warnings[0], Warning::IllConditioned { condition_number } if condition_number > 1e8)
+    );
+}
+
+#[test]
+fn ridge_warns_about_low_variance_variables() {
+    let (diagnostics, warnings) = collector();
+    let mut ridge = RidgeRegressor::new(1.0, true)
+        .unwrap()
+        .with_diagnostics(diagnostics);
+    let inputs = DMatrix::from_fn(10, 2, |i, j| match j {
+        0 => i as f64,
+        _ => 1e6 + 1e-4 * i as f64,
+    });
+
+    ridge
+        .train(inputs, DVector::from_fn(10, |i, _| i as f64))
+        .unwrap();
+
+    let warnings = warnings.lock().unwrap();
+    assert_eq!(warnings[0], Warning::LowVariance { variables: vec![1] });
+    assert_eq!(
+        warnings[0].to_string(),
+        "Input variable(s) 1 have (nearly) zero variance."
+    );
+}
+
+#[test]
+fn gpc_warns_when_not_converged() {
+    let (diagnostics, warnings) = collector();
+    let mut gpc = GpcClassifier::new(Rbf::new(1.0).unwrap())
+        .with_convergence(ConvergenceConfig::new(1, 1e-10).unwrap())
+        .with_diagnostics(diagnostics);
+
+    gpc.train(
+        dmatrix![-2.0; -1.0; 1.0; 2.0],
+        DVector::from_vec(vec![0.0, 0.0, 1.0, 1.0]),
+    )
+    .unwrap();
+
+    let warnings = warnings.lock().unwrap();
+    assert_eq!(*warnings, vec![Warning::NotConverged { max_iter: 1 }]);
+    assert_eq!(
+        warnings[0].to_string(),
+        "Stopped after the maximum of 1 iterations without converging."
+    );
+}
+
+#[test]
+fn lbfgs_models_warn_when_line_search_fails() {
+    let (diagnostics, warnings) = collector();
+
+    // Every step from zero coefficients saturates the probabilities, so the objective is infinite.
+    OrdinalRegressor::new()
+        .with_diagnostics(diagnostics)
+        .train(
+            dmatrix![-1e150; 1e150; -2e150; 2e150],
+            DVector::from_vec(vec![0.0, 1.0, 1.0, 0.0]),
+        )
+        .unwrap();
+
+    let warnings = warnings.lock().unwrap();
+    assert_eq!(*warnings, vec![Warning::LineSearchFailed { iterations: 0 }]);
+    assert_eq!(
+        warnings[0].to_string(),
+        "Stopped after 0 iterations without converging, because the line search could not decrease the objective."
+    );
+}
+
+#[test]
+fn lbfgs_models_warn_when_not_converged() {
+    let mut rng = Rng::new(4);
+    let inputs = DMatrix::from_fn(30, 2, |_, _| rng.standard_normal::<f64>());
+    let outputs = DVector::from_fn(30, |i, _| (i % 3) as f64);
+    let series = DVector::from_fn(30, |i, _| {
+        (i as f64 * 0.4).sin() + rng.standard_normal::<f64>()
+    });
+    let convergence = || ConvergenceConfig::new(1, 0.0).unwrap();
+    let expected = vec![Warning::NotConverged { max_iter: 1 }];
+
+    let (diagnostics, warnings) = collector();
+    OrdinalRegressor::new()
+        .with_convergence(convergence())
+        .with_diagnostics(diagnostics)
+        .train(inputs.clone(), outputs.clone())
+        .unwrap();
+    assert_eq!(*warnings.lock().unwrap(), expected);
+
+    let (diagnostics, warnings) = collector();
+    let queries: Vec<usize> = (0..30).map(|i| i / 10).collect();
+    PairwiseRanker::new(0.1)
+        .unwrap()
+        .with_convergence(convergence())
+        .with_diagnostics(diagnostics)
+        .train(inputs.clone(), outputs.clone(), &queries)
+        .unwrap();
+    assert_eq!(*warnings.lock().unwrap(), expected);
+
+    let (diagnostics, warnings) = collector();
+    let durations = DVector::from_fn(30, |i, _| 1.0 + i as f64);
+    let events = DVector::from_fn(30, |i, _| (i % 4 != 0) as u8 as f64);
+    CoxPhModel::new(TiesMethod::Breslow)
+        .with_convergence(convergence())
+        .with_diagnostics(diagnostics)
+        .train(inputs, durations, events)
+        .unwrap();
+    assert_eq!(*warnings.lock().unwrap(), expected);
+
+    let (diagnostics, warnings) = collector();
+    Arima::new(1, 0, 1)
+        .with_convergence(convergence())
+        .with_diagnostics(diagnostics)
+        .train(&series)
+        .unwrap();
+    assert_eq!(*warnings.lock().unwrap(), expected);
+
+    let (diagnostics, warnings) = collector();
+    ExponentialSmoothing::new(true, None)
+        .unwrap()
+        .with_convergence(convergence())
+        .with_diagnostics(diagnostics)
+        .train(&series)
+        .unwrap();
+    assert_eq!(*warnings.lock().unwrap(), expected);
+}
+
+#[test]
+fn iterative_models_warn_when_not_converged() {
+    let mut rng = Rng::new(8);
+    let inputs = DMatrix::from_fn(20, 2, |_, _| rng.standard_normal::<f64>());
+    let convergence = || ConvergenceConfig::new(1, 0.0).unwrap();
+    let expected = vec![Warning::NotConverged { max_iter: 1 }];
+
+    let (diagnostics, warnings) = collector();
+    OneClassSvm::new(Rbf::new(0.5).unwrap(), 0.2)
+        .unwrap()
+        .with_convergence(convergence())
+        .with_diagnostics(diagnostics)
+        .train(&inputs)
+        .unwrap();
+    assert_eq!(*warnings.lock().unwrap(), expected);
+
+    let (diagnostics, warnings) = collector();
+    let labels: Vec<Option<f64>> = (0..20)
+        .map(|i| match i {
+            0 => Some(0.0),
+            1 => Some(1.0),
+            _ => None,
+        })
+        .collect();
+    LabelPropagation::new(Affinity::KNearestNeighbors(3))
+        .unwrap()
+        .with_convergence(convergence())
+        .with_diagnostics(diagnostics)
+        .train(inputs.clone(), &labels)
+        .unwrap();
+    assert_eq!(*warnings.lock().unwrap(), expected);
+
+    let (diagnostics, warnings) = collector();
+    // The exemplars can never be unchanged for more iterations than there are.
+    AffinityPropagation::new()
+        .with_convergence(
+            ConvergenceConfig::new(50, 0.0)
+                .unwrap()
+                .with_patience(100)
+                .unwrap(),
+        )
+        .with_diagnostics(diagnostics)
+        .train(&inputs)
+        .unwrap();
+    assert_eq!(
+        *warnings.lock().unwrap(),
+        vec![Warning::NotConverged { max_iter: 50 }]
+    );
+
+    let (diagnostics, warnings) = collector();
+    DictionaryLearning::new(2, SparseCoding::Omp { n_nonzero: 1 })
+        .unwrap()
+        .with_convergence(convergence())
+        .with_diagnostics(diagnostics)
+        .fit(&inputs)
+        .unwrap();
+    assert_eq!(*warnings.lock().unwrap(), expected);
+
+    let (diagnostics, warnings) = collector();
+    AlsFactorizer::new(2, 0.1)
+        .unwrap()
+        .with_convergence(convergence())
+        .with_diagnostics(diagnostics)
+        .train(&[(0, 0, 3.0), (0, 1, 1.0), (1, 0, 4.0), (2, 1, 2.0)])
+        .unwrap();
+    assert_eq!(*warnings.lock().unwrap(), expected);
+
+    let (diagnostics, warnings) = collector();
+    let mut missing = inputs;
+    missing[(3, 1)] = f64::NAN;
+    MissingValuesPca::new(1)
+        .unwrap()
+        .with_convergence(convergence())
+        .with_diagnostics(diagnostics)
+        .fit(&missing)
+        .unwrap();
+    assert_eq!(*warnings.lock().unwrap(), expected);
+}
@@ -0,0 +1,119 @@
+use nalgebra::{dmatrix, dvector, DMatrix};
+use test_case::test_case;
+
+use slearning::distance::{
+    mahalanobis_distance, mahalanobis_distances, pairwise_distances, Chebyshev, Cosine, Euclidean,
+    Hamming, Manhattan, Metric, Minkowski, SquaredEuclidean,
+};
+use slearning::SLearningError;
+
+fn x() -> DMatrix<f64> {
+    dmatrix![
+        0.0, 0.0;
+        3.0, 4.0;
+        1.0, 1.0
+    ]
+}
+
+fn y() -> DMatrix<f64> {
+    dmatrix![
+        0.0, 0.0;
+        3.0, 0.0
+    ]
+}
+
+#[test_case(Euclidean, dmatrix![0.0, 3.0; 5.0, 4.0; 2.0f64.sqrt(), 5.0f64.sqrt()]; "euclidean")]
+#[test_case(SquaredEuclidean, dmatrix![0.0, 9.0; 25.0, 16.0; 2.0, 5.0]; "squared euclidean")]
+#[test_case(Manhattan, dmatrix![0.0, 3.0; 7.0, 4.0; 2.0, 3.0]; "manhattan")]
+#[test_case(Chebyshev, dmatrix![0.0, 3.0; 4.0, 4.0; 1.0, 2.0]; "chebyshev")]
+#[test_case(Hamming, dmatrix![0.0, 0.5; 1.0, 0.5; 1.0, 1.0]; "hamming")]
+fn metrics_work<M: Metric<f64>>(metric: M, expected: DMatrix<f64>) {
+    let actual = pairwise_distances(&x(), &y(), &metric).unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test_case(1.0, 7.0; "manhattan")]
+#[test_case(2.0, 5.0; "euclidean")]
+#[test_case(3.0, 91.0f64.powf(1.0 / 3.0); "cubic")]
+fn minkowski_works(p: f64, expected: f64) {
+    let metric = Minkowski::new(p).unwrap();
+    let actual = metric.distance(x().row(0), x().row(1));
+    assert!((actual - expected).abs() < 1e-12);
+}
+
+#[test]
+fn cosine_works() {
+    let inputs = dmatrix![
+        1.0, 0.0;
+        0.0, 2.0;
+        3.0, 3.0;
+        -1.0, 0.0;
+        0.0, 0.0
+    ];
+
+    let actual = pairwise_distances(&inputs.rows(0, 1).into_owned(), &inputs, &Cosine).unwrap();
+    let expected = dmatrix![0.0, 1.0, 1.0 - 0.5f64.sqrt(), 2.0, 1.0];
+    assert!((actual - expected).abs().max() < 1e-12);
+}
+
+#[test]
+fn pairwise_distances_fails_with_inconsistent_dimensions() {
+    let expected = SLearningError::InvalidData(
+        "The first input has 2 variables, but the second input has 1 variables. These must be equal.".to_string()
+    );
+
+    let actual = pairwise_distances(&x(), &dmatrix![1.0], &Euclidean).unwrap_err();
+    assert_eq!(actual, expected);
+    assert_eq!(
+        Euclidean.pairwise(&x(), &dmatrix![1.0]).unwrap_err(),
+        expected
+    );
+    assert_eq!(
+        SquaredEuclidean.pairwise(&x(), &dmatrix![1.0]).unwrap_err(),
+        expected
+    );
+    assert_eq!(
+        Manhattan.pairwise(&x(), &dmatrix![1.0]).unwrap_err(),
+        expected
+    );
+}
+
+#[test_case(0.5; "less than one")]
+#[test_case(f64::NAN; "nan")]
+#[test_case(f64::INFINITY; "infinite")]
+fn minkowski_fails_with_invalid_order(p: f64) {
+    let expected =
+        SLearningError::InvalidParameters("The order p must be finite and at least one.".into());
+    assert_eq!(Minkowski::new(p).unwrap_err(), expected);
+}
+
+#[test]
+fn mahalanobis_distance_works() {
+    let mean = dvector![1.0, 1.0];
+    let precision = dmatrix![0.25, 0.0; 0.0, 1.0];
+
+    let actual = mahalanobis_distance(&dvector![3.0, 1.0], &mean, &precision).unwrap();
+    assert_eq!(actual, 1.0);
+
+    let inputs = dmatrix![1.0, 1.0; 3.0, 1.0; 1.0, 4.0; 5.0, 4.0];
+    let actual = mahalanobis_distances(&inputs, &mean, &precision).unwrap();
+    assert_eq!(actual, dvector![0.0, 1.0, 3.0, 13.0f64.sqrt()]);
+}
+
+#[test]
+fn mahalanobis_distance_fails_with_inconsistent_dimensions() {
+    let mean = dvector![1.0, 1.0];
+
+    let expected = SLearningError::InvalidData(
+        "The mean has 2 variables, but the precision matrix has shape (1, 1). The precision matrix must be square with the same number of variables.".to_string()
+    );
+    let actual = mahalanobis_distance(&dvector![1.0, 1.0], &mean, &dmatrix![1.0]).unwrap_err();
+    assert_eq!(actual, expected);
+
+    let expected = SLearningError::InvalidData(
+        "The mean has 2 variables, but the input has 3 variables. These must be equal.".to_string(),
+    );
+    let precision = DMatrix::identity(2, 2);
+    let actual = mahalanobis_distances(&dmatrix![1.0, 2.0, 3.0], &mean, &precision).unwrap_err();
+    assert_eq!(actual, expected);
+}
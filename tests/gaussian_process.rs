@@ -0,0 +1,151 @@
+use nalgebra::{dmatrix, dvector, DMatrix, DVector};
+use test_case::test_case;
+
+use slearning::gaussian_process::GpcClassifier;
+use slearning::kernel::{Kernel, Rbf, Sigmoid};
+use slearning::random::Rng;
+use slearning::{SLearningError, SupervisedModel};
+
+/// Forty evenly spaced points between -3 and 3, which are positive when above zero, except for two
+/// mislabelled points near the boundary.
+fn step_data() -> (DMatrix<f64>, DVector<f64>) {
+    let inputs = DMatrix::from_fn(40, 1, |i, _| -3.0 + 6.0 * i as f64 / 39.0);
+    let mut outputs = inputs.column(0).map(|x| if x > 0.0 { 1.0 } else { 0.0 });
+    outputs[18] = 1.0;
+    outputs[22] = 0.0;
+    (inputs, outputs)
+}
+
+#[test]
+fn gpc_works() {
+    let (inputs, outputs) = step_data();
+    let kernel = Rbf::new(0.5).unwrap();
+    let mut gpc = GpcClassifier::new(kernel);
+
+    gpc.train(inputs.clone(), outputs.clone()).unwrap();
+
+    // The latent mode satisfies `f = K (y - logistic(f))`.
+    let mode = gpc.latent_mode.as_ref().unwrap();
+    let probabilities = mode.map(|f| 1.0 / (1.0 + (-f).exp()));
+    let fixed_point = kernel.gram_matrix(&inputs) * (&outputs - probabilities);
+    assert!((mode - fixed_point).amax() < 1e-6);
+    assert!(gpc.log_marginal_likelihood.unwrap() < 0.0);
+
+    let test_inputs = dmatrix![-2.5; 2.5; 50.0];
+    assert_eq!(
+        gpc.predict(&test_inputs).unwrap().rows(0, 2),
+        dvector![0.0, 1.0]
+    );
+    let latent = gpc.predict_latent(&test_inputs).unwrap();
+    assert!(latent.variance[0] < 0.5);
+    assert!((latent.variance[2] - 1.0).abs() < 1e-10);
+    let probabilities = gpc.predict_proba(&test_inputs).unwrap();
+    assert!(probabilities[0] < 0.2);
+    assert!(probabilities[1] > 0.8);
+    assert!((probabilities[2] - 0.5).abs() < 1e-10);
+}
+
+#[test]
+fn gpc_predicts_labels_with_probabilities() {
+    let (inputs, outputs) = step_data();
+    let mut gpc = GpcClassifier::new(Rbf::new(0.5).unwrap());
+    gpc.train(inputs, outputs).unwrap();
+
+    let test_inputs = dmatrix![-2.0; -0.1; 0.1; 1.0; 3.5];
+    let prediction = gpc.predict_with_scores(&test_inputs).unwrap();
+
+    assert_eq!(prediction.labels, gpc.predict(&test_inputs).unwrap());
+    assert_eq!(prediction.scores, gpc.predict_proba(&test_inputs).unwrap());
+}
+
+#[test]
+fn gpc_predicts_classes_with_minimum_expected_cost() {
+    let (inputs, outputs) = step_data();
+    let mut gpc = GpcClassifier::new(Rbf::new(0.5).unwrap());
+    gpc.train(inputs, outputs).unwrap();
+    let test_inputs = dmatrix![-2.0; -0.5; 0.5; 2.0];
+
+    let zero_one = gpc
+        .predict_min_cost(&test_inputs, &dmatrix![0.0, 1.0; 1.0, 0.0])
+        .unwrap();
+    let cautious = gpc
+        .predict_min_cost(&test_inputs, &dmatrix![0.0, 1.0; 1000.0, 0.0])
+        .unwrap();
+
+    assert_eq!(zero_one, gpc.predict(&test_inputs).unwrap());
+    // Missing a positive is so costly that every observation is predicted to be positive.
+    assert_eq!(cautious, dvector![1.0, 1.0, 1.0, 1.0]);
+}
+
+#[test]
+fn gpc_probabilities_are_less_confident_than_the_latent_mean() {
+    let (inputs, outputs) = step_data();
+    let mut gpc = GpcClassifier::new(Rbf::new(0.5).unwrap());
+    gpc.train(inputs, outputs).unwrap();
+
+    let test_inputs = dmatrix![-2.0; 1.0; 3.5];
+    let latent = gpc.predict_latent(&test_inputs).unwrap();
+    let probabilities = gpc.predict_proba(&test_inputs).unwrap();
+    for (mean, probability) in latent.mean.iter().zip(probabilities.iter()) {
+        let plug_in = 1.0 / (1.0 + (-mean).exp());
+        assert!((probability - 0.5f64).abs() < (plug_in - 0.5f64).abs());
+    }
+}
+
+#[test_case(dmatrix![1.0; 2.0], dvector![0.0, 0.5], "Binary labels must be zero or one."; "non-binary")]
+#[test_case(dmatrix![1.0; 2.0], dvector![0.0], "Input has 2 observation(s), but output has 1 observation(s). These must be equal."; "lengths")]
+#[test_case(dmatrix![1.0; f64::NAN], dvector![0.0, 1.0], "Input has a non-finite value for observation 1 and variable 0."; "nan input")]
+#[test_case(dmatrix![1.0; 2.0], dvector![0.0, f64::NAN], "The outputs have a non-finite value for observation 1."; "nan output")]
+fn gpc_fails_to_train_with_invalid_data(
+    inputs: DMatrix<f64>,
+    outputs: DVector<f64>,
+    message: &str,
+) {
+    let mut gpc = GpcClassifier::new(Rbf::new(1.0).unwrap());
+
+    let error = gpc.train(inputs, outputs).unwrap_err();
+
+    assert_eq!(error, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn gpc_fails_to_train_with_indefinite_kernel() {
+    let mut rng = Rng::new(3);
+    let inputs = DMatrix::from_fn(20, 2, |_, _| rng.standard_normal::<f64>());
+    let outputs = DVector::from_fn(20, |i, _| (i % 2) as f64);
+    let mut gpc = GpcClassifier::new(Sigmoid::new(5.0, -1.0));
+
+    let error = gpc.train(inputs, outputs).unwrap_err();
+
+    assert_eq!(
+        error,
+        SLearningError::InvalidData(
+            "The Laplace approximation failed because the kernel matrix is not positive semi-definite. Use a positive semi-definite kernel."
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn gpc_fails_to_predict_with_invalid_inputs() {
+    let mut gpc = GpcClassifier::new(Rbf::new(1.0).unwrap());
+    assert_eq!(
+        gpc.predict(&dmatrix![1.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+
+    gpc.train(dmatrix![0.0; 1.0], dvector![0.0, 1.0]).unwrap();
+    assert_eq!(
+        gpc.predict_proba(&dmatrix![1.0, 2.0]).unwrap_err(),
+        SLearningError::InvalidData(
+            "This model was trained with 1 variables, but this input has 2 variables. These must be equal."
+                .to_string()
+        )
+    );
+    assert_eq!(
+        gpc.predict_proba(&dmatrix![f64::INFINITY]).unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has a non-finite value for observation 0 and variable 0.".to_string()
+        )
+    );
+}
@@ -0,0 +1,44 @@
+use test_case::test_case;
+
+use slearning::impurity::{
+    entropy, entropy_from_counts, gini, gini_from_counts, misclassification_rate,
+    misclassification_rate_from_counts,
+};
+use slearning::utils::unique_with_counts;
+
+#[test_case(vec![1, 1, 1, 1], 0.0, 0.0, 0.0; "pure")]
+#[test_case(vec![0, 1, 0, 1], 1.0, 0.5, 0.5; "two balanced classes")]
+#[test_case(vec![0, 1, 2, 3], 2.0, 0.75, 0.75; "four balanced classes")]
+#[test_case(vec![0, 0, 0, 1], 0.8112781244591328, 0.375, 0.25; "imbalanced")]
+#[test_case(vec![], 0.0, 0.0, 0.0; "empty")]
+fn impurity_from_labels(
+    labels: Vec<i32>,
+    expected_entropy: f64,
+    expected_gini: f64,
+    expected_misclassification_rate: f64,
+) {
+    assert_eq!(entropy(&labels), expected_entropy);
+    assert_eq!(gini(&labels), expected_gini);
+    assert_eq!(
+        misclassification_rate(&labels),
+        expected_misclassification_rate
+    );
+}
+
+#[test]
+fn impurity_from_counts_matches_labels() {
+    let labels = vec!["a", "b", "b", "c", "c", "c"];
+
+    assert_eq!(
+        entropy_from_counts(unique_with_counts(labels.clone())),
+        entropy(&labels)
+    );
+    assert_eq!(
+        gini_from_counts(unique_with_counts(labels.clone())),
+        gini(&labels)
+    );
+    assert_eq!(
+        misclassification_rate_from_counts(unique_with_counts(labels.clone())),
+        misclassification_rate(&labels)
+    );
+}
@@ -0,0 +1,199 @@
+use nalgebra::{dmatrix, dvector, DMatrix, DVector};
+use test_case::test_case;
+
+use slearning::inspection::{partial_dependence, permutation_importance, stability_report};
+use slearning::linear_regression::OlsRegressor;
+use slearning::model_selection::resample;
+use slearning::{SLearningError, SLearningResult, SupervisedModel};
+
+fn negative_mse(actual: &DVector<f64>, predicted: &DVector<f64>) -> f64 {
+    -(actual - predicted).norm_squared() / actual.len() as f64
+}
+
+fn trained_model() -> OlsRegressor<f64> {
+    // The output only depends on the first variable.
+    let inputs = dmatrix![
+        1.0, 3.0;
+        2.0, 1.0;
+        3.0, 4.0;
+        4.0, 1.0;
+        5.0, 5.0;
+        6.0, 9.0
+    ];
+    let outputs = dvector![3.0, 5.0, 7.0, 9.0, 11.0, 13.0];
+    let mut ols = OlsRegressor::default();
+    ols.train(inputs, outputs).unwrap();
+    ols
+}
+
+#[test]
+fn permutation_importance_works() {
+    let ols = trained_model();
+    let inputs = dmatrix![1.0, 2.0; 2.0, 7.0; 3.0, 1.0; 4.0, 8.0; 5.0, 2.0; 6.0, 8.0];
+    let outputs = dvector![3.0, 5.0, 7.0, 9.0, 11.0, 13.0];
+
+    let importance = permutation_importance(&ols, &inputs, &outputs, negative_mse, 5, 0).unwrap();
+
+    assert!(importance.baseline_score.abs() < 1e-10);
+    assert_eq!(importance.importances.shape(), (2, 5));
+    assert!(importance.importances_mean[0] > 1.0);
+    assert!(importance.importances_mean[1].abs() < 1e-10);
+    assert!(importance.importances_std[1].abs() < 1e-10);
+}
+
+#[test]
+fn permutation_importance_has_zero_std_for_equal_repeats() {
+    let ols = trained_model();
+    let inputs = dmatrix![1.0, 2.0; 2.0, 7.0; 3.0, 1.0; 4.0, 8.0; 5.0, 2.0; 6.0, 8.0];
+    let outputs = dvector![3.0, 5.0, 7.0, 9.0, 11.0, 13.0];
+    let unshuffled = ols.predict(&inputs).unwrap();
+    // Every shuffle decreases the score by 0.1.
+    let score = |_: &DVector<f64>, predicted: &DVector<f64>| match *predicted == unshuffled {
+        true => 0.1,
+        false => 0.0,
+    };
+
+    let importance = permutation_importance(&ols, &inputs, &outputs, score, 3, 0).unwrap();
+
+    assert!(importance.importances.iter().all(|&value| value == 0.1));
+    assert_eq!(importance.importances_std, dvector![0.0, 0.0]);
+}
+
+#[test]
+fn permutation_importance_is_reproducible() {
+    let ols = trained_model();
+    let inputs = dmatrix![1.0, 2.0; 2.0, 7.0; 3.0, 1.0; 4.0, 8.0];
+    let outputs = dvector![3.0, 5.0, 7.0, 9.0];
+
+    let first = permutation_importance(&ols, &inputs, &outputs, negative_mse, 3, 9).unwrap();
+    let second = permutation_importance(&ols, &inputs, &outputs, negative_mse, 3, 9).unwrap();
+
+    assert_eq!(first, second);
+}
+
+#[test]
+fn permutation_importance_fails_with_invalid_inputs() {
+    let ols = trained_model();
+    let inputs = dmatrix![1.0, 2.0; 2.0, 7.0];
+
+    assert_eq!(
+        permutation_importance(&ols, &inputs, &dvector![3.0, 5.0], negative_mse, 0, 0).unwrap_err(),
+        SLearningError::InvalidParameters("Number of repeats must be at least one.".into())
+    );
+    assert_eq!(
+        permutation_importance(&ols, &DMatrix::zeros(0, 2), &dvector![], negative_mse, 1, 0)
+            .unwrap_err(),
+        SLearningError::InvalidData("Cannot train with zero observations.".into())
+    );
+    assert_eq!(
+        permutation_importance(&ols, &inputs, &dvector![3.0], negative_mse, 1, 0).unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has 2 observation(s), but output has 1 observation(s). These must be equal."
+                .into()
+        )
+    );
+}
+
+#[test]
+fn partial_dependence_works() {
+    let ols = trained_model();
+    let coefficients = ols.coefficients.clone().unwrap();
+    let inputs = dmatrix![1.0, 2.0; 2.0, 7.0; 3.0, 1.0];
+    let grid = dvector![0.0, 10.0];
+
+    let dependence = partial_dependence(&ols, &inputs, 0, &grid).unwrap();
+
+    // For a linear model, the curves are parallel lines with slope equal to the coefficient.
+    assert_eq!(dependence.grid, grid);
+    assert_eq!(dependence.individual.shape(), (3, 2));
+    for i in 0..3 {
+        let slope = (dependence.individual[(i, 1)] - dependence.individual[(i, 0)]) / 10.0;
+        assert!((slope - coefficients[1]).abs() < 1e-10);
+    }
+    let expected_average = dependence.individual.row_mean().transpose();
+    assert!((dependence.average - expected_average).amax() < 1e-12);
+}
+
+#[test_case(2, dvector![1.0], "Feature index 2 is out of range for an input with 2 variables."; "out of range")]
+#[test_case(0, dvector![], "The grid must contain at least one value."; "empty grid")]
+fn partial_dependence_fails_with_invalid_parameters(
+    feature_index: usize,
+    grid: DVector<f64>,
+    message: &str,
+) {
+    let ols = trained_model();
+    let inputs = dmatrix![1.0, 2.0; 2.0, 7.0];
+
+    let actual = partial_dependence(&ols, &inputs, feature_index, &grid).unwrap_err();
+    assert_eq!(actual, SLearningError::InvalidParameters(message.into()));
+}
+
+/// OLS trained on a bootstrap sample drawn with its seed.
+struct BaggedOls {
+    ols: OlsRegressor<f64>,
+    seed: u64,
+}
+
+impl SupervisedModel<f64> for BaggedOls {
+    fn train(&mut self, inputs: DMatrix<f64>, outputs: DVector<f64>) -> SLearningResult<()> {
+        let (inputs, outputs) = resample(&inputs, &outputs, inputs.nrows(), None, true, self.seed)?;
+        self.ols.train(inputs, outputs)
+    }
+
+    fn predict(&self, inputs: &DMatrix<f64>) -> SLearningResult<DVector<f64>> {
+        self.ols.predict(inputs)
+    }
+}
+
+#[test]
+fn stability_report_works() {
+    let inputs = dmatrix![1.0; 2.0; 3.0; 4.0; 5.0; 6.0; 7.0; 8.0];
+    let outputs = dvector![1.2, 1.8, 3.3, 3.9, 5.4, 5.7, 7.4, 7.8];
+    let make_model = |seed| BaggedOls {
+        ols: OlsRegressor::default(),
+        seed,
+    };
+
+    let report = stability_report(make_model, &inputs, &outputs, 5, |model: &BaggedOls| {
+        model.ols.coefficients.clone().unwrap()
+    })
+    .unwrap();
+
+    assert_eq!(report.predictions.shape(), (8, 5));
+    // The summaries are the intercept and the slope.
+    assert_eq!(report.summaries.shape(), (2, 5));
+    assert!(report.summaries_std[1] > 0.0);
+    assert!((report.summaries_mean[1] - 1.0).abs() < 0.2);
+    assert!(report.predictions_std.iter().all(|&std| std > 0.0));
+}
+
+#[test]
+fn stability_report_of_deterministic_model_has_no_variation() {
+    let inputs = dmatrix![1.0; 2.0; 3.0; 4.0];
+    let outputs = dvector![2.0, 3.5, 6.5, 8.0];
+
+    let report = stability_report(
+        |_| OlsRegressor::default(),
+        &inputs,
+        &outputs,
+        3,
+        |ols: &OlsRegressor<f64>| ols.coefficients.clone().unwrap(),
+    )
+    .unwrap();
+
+    assert!(report.predictions_std.amax() < 1e-12);
+    assert!(report.summaries_std.amax() < 1e-12);
+}
+
+#[test]
+fn stability_report_needs_two_seeds() {
+    let result = stability_report(
+        |_| OlsRegressor::default(),
+        &dmatrix![1.0; 2.0],
+        &dvector![1.0, 2.0],
+        1,
+        |_: &OlsRegressor<f64>| DVector::zeros(0),
+    );
+
+    assert!(matches!(result, Err(SLearningError::InvalidParameters(_))));
+}
@@ -0,0 +1,108 @@
+use nalgebra::{dmatrix, DMatrix};
+use test_case::test_case;
+
+use slearning::kernel::{Kernel, Linear, Matern, MaternNu, Polynomial, Rbf, Sigmoid};
+use slearning::SLearningError;
+
+fn x() -> DMatrix<f64> {
+    dmatrix![
+        0.0, 1.0;
+        1.0, 2.0;
+        3.0, -1.0
+    ]
+}
+
+fn y() -> DMatrix<f64> {
+    dmatrix![
+        1.0, 1.0;
+        -2.0, 0.5
+    ]
+}
+
+/// The kernel matrix should match computing the kernel for each pair of rows.
+fn assert_matrix_matches_pairwise<K: Kernel<f64>>(kernel: &K) {
+    let (x, y) = (x(), y());
+    let matrix = kernel.matrix(&x, &y).unwrap();
+    assert_eq!(matrix.shape(), (3, 2));
+    for i in 0..x.nrows() {
+        for j in 0..y.nrows() {
+            let expected = kernel.compute(x.row(i), y.row(j));
+            assert!((matrix[(i, j)] - expected).abs() < 1e-12);
+        }
+    }
+}
+
+#[test]
+fn linear_kernel_works() {
+    let matrix = Linear.matrix(&x(), &y()).unwrap();
+    assert_eq!(matrix, dmatrix![1.0, 0.5; 3.0, -1.0; 2.0, -6.5]);
+    assert_matrix_matches_pairwise(&Linear);
+}
+
+#[test]
+fn polynomial_kernel_works() {
+    let kernel = Polynomial::new(2, 0.5, 1.0).unwrap();
+    assert_eq!(kernel.compute(x().row(1), y().row(0)), 6.25);
+    assert_matrix_matches_pairwise(&kernel);
+}
+
+#[test]
+fn rbf_kernel_works() {
+    let kernel = Rbf::new(0.5).unwrap();
+    assert_eq!(kernel.compute(x().row(0), y().row(0)), (-0.5f64).exp());
+    assert_matrix_matches_pairwise(&kernel);
+
+    let gram = kernel.gram_matrix(&x());
+    assert_eq!(gram.diagonal(), nalgebra::dvector![1.0, 1.0, 1.0]);
+    assert_eq!(gram, gram.transpose());
+}
+
+#[test]
+fn sigmoid_kernel_works() {
+    let kernel = Sigmoid::new(0.5, -1.0);
+    assert_eq!(kernel.compute(x().row(1), y().row(0)), 0.5f64.tanh());
+    assert_matrix_matches_pairwise(&kernel);
+}
+
+#[test_case(MaternNu::Half, (-1.0f64).exp(); "half")]
+#[test_case(MaternNu::ThreeHalves, (1.0 + 3.0f64.sqrt()) * (-(3.0f64.sqrt())).exp(); "three halves")]
+#[test_case(MaternNu::FiveHalves, (1.0 + 5.0f64.sqrt() + 5.0 / 3.0) * (-(5.0f64.sqrt())).exp(); "five halves")]
+fn matern_kernel_works(nu: MaternNu, expected: f64) {
+    let kernel = Matern::new(2.0, nu).unwrap();
+    // These rows are a distance of 2 apart, which is one length scale.
+    let actual = kernel.compute(x().row(0), dmatrix![0.0, 3.0].row(0));
+    assert!((actual - expected).abs() < 1e-12);
+    assert_matrix_matches_pairwise(&kernel);
+}
+
+#[test]
+fn kernel_matrix_fails_with_inconsistent_dimensions() {
+    let expected = SLearningError::InvalidData(
+        "The first input has 2 variables, but the second input has 3 variables. These must be equal.".to_string()
+    );
+
+    let actual = Linear.matrix(&x(), &dmatrix![1.0, 2.0, 3.0]).unwrap_err();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn kernels_fail_with_invalid_parameters() {
+    assert_eq!(
+        Rbf::new(0.0).unwrap_err(),
+        SLearningError::InvalidParameters("Gamma must be finite and greater than zero.".into())
+    );
+    assert_eq!(
+        Rbf::new(f64::NAN).unwrap_err(),
+        SLearningError::InvalidParameters("Gamma must be finite and greater than zero.".into())
+    );
+    assert_eq!(
+        Polynomial::new(0, 1.0, 0.0).unwrap_err(),
+        SLearningError::InvalidParameters("Degree must be at least one.".into())
+    );
+    assert_eq!(
+        Matern::new(-1.0, MaternNu::Half).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Length scale must be finite and greater than zero.".into()
+        )
+    );
+}
@@ -0,0 +1,84 @@
+use nalgebra::{dmatrix, DMatrix};
+
+use slearning::kernel::{Kernel, Polynomial, Rbf};
+use slearning::kernel_approximation::{Nystroem, RbfSampler};
+use slearning::{SLearningError, Transformer};
+
+fn inputs() -> DMatrix<f64> {
+    dmatrix![
+        0.0, 1.0;
+        1.0, 0.5;
+        -0.5, 0.2;
+        0.3, -1.0;
+        0.8, 0.9;
+        -1.0, -0.4
+    ]
+}
+
+#[test]
+fn nystroem_with_every_landmark_reproduces_the_kernel() {
+    let kernel = Rbf::new(0.7).unwrap();
+    let mut nystroem = Nystroem::new(kernel, 6).unwrap().with_seed(3);
+
+    let features = nystroem.fit_transform(&inputs()).unwrap();
+
+    assert_eq!(features.shape(), (6, 6));
+    let approximation = &features * features.transpose();
+    assert!((approximation - kernel.gram_matrix(&inputs())).amax() < 1e-8);
+}
+
+#[test]
+fn nystroem_is_exact_for_the_landmarks() {
+    let kernel = Polynomial::new(2, 1.0, 1.0).unwrap();
+    let mut nystroem = Nystroem::new(kernel, 3).unwrap();
+
+    nystroem.fit(&inputs()).unwrap();
+
+    let landmarks = nystroem.landmarks.clone().unwrap();
+    let features = nystroem.transform(&landmarks).unwrap();
+    let approximation = &features * features.transpose();
+    assert!((approximation - kernel.gram_matrix(&landmarks)).amax() < 1e-8);
+}
+
+#[test]
+fn nystroem_rejects_more_landmarks_than_observations() {
+    let mut nystroem = Nystroem::new(Rbf::new(1.0).unwrap(), 7).unwrap();
+
+    let result = nystroem.fit(&inputs());
+
+    assert!(matches!(result, Err(SLearningError::InvalidData(_))));
+}
+
+#[test]
+fn rbf_sampler_approximates_the_kernel() {
+    let kernel = Rbf::new(0.5).unwrap();
+    let mut sampler = RbfSampler::new(kernel, 5000).unwrap().with_seed(1);
+
+    let features = sampler.fit_transform(&inputs()).unwrap();
+
+    assert_eq!(features.shape(), (6, 5000));
+    let approximation = &features * features.transpose();
+    assert!((approximation - kernel.gram_matrix(&inputs())).amax() < 0.05);
+}
+
+#[test]
+fn rbf_sampler_checks_number_of_variables() {
+    let mut sampler = RbfSampler::new(Rbf::new(0.5).unwrap(), 10).unwrap();
+    sampler.fit(&inputs()).unwrap();
+
+    let result = sampler.transform(&dmatrix![1.0, 2.0, 3.0]);
+
+    assert!(matches!(result, Err(SLearningError::InvalidData(_))));
+}
+
+#[test]
+fn kernel_approximations_need_a_component() {
+    assert!(matches!(
+        Nystroem::new(Rbf::new(1.0).unwrap(), 0),
+        Err(SLearningError::InvalidParameters(_))
+    ));
+    assert!(matches!(
+        RbfSampler::new(Rbf::<f64>::new(1.0).unwrap(), 0),
+        Err(SLearningError::InvalidParameters(_))
+    ));
+}
@@ -1,7 +1,13 @@
 use nalgebra::{dmatrix, dvector, DMatrix, DVector, RealField};
 use test_case::test_case;
 
-use slearning::linear_regression::{OlsRegressor, RidgeRegressor};
+use slearning::linear_regression::{
+    CoefficientBounds, ConstantColumns, ErrorCovariance, GlsRegressor, ImputeStrategy,
+    LinearParameters, MissingValues, MixedLmRegressor, NnlsRegressor, NormalEquations,
+    OlsRegressor, RidgeRegressor,
+};
+use slearning::optim::ConvergenceConfig;
+use slearning::random::Rng;
 use slearning::{SLearningError, SupervisedModel};
 
 #[test_case(
@@ -96,13 +102,94 @@ fn ols_fails_to_train_with_collinear_input_variables() {
         2.0, 4.0
     ];
     let train_output = DVector::from_vec(vec![1.5, 3.5]);
-    let expected_error = SLearningError::InvalidData("The normal matrix is not invertible.".into());
+    let expected_error = SLearningError::InvalidData(
+        "The normal matrix is not invertible. The inputs have rank 2, but there are 3 coefficients to estimate. Input variable(s) 1 are (nearly) linear combinations of the intercept and earlier variables."
+            .into(),
+    );
 
     let mut ols = OlsRegressor::default();
     let actual_error = ols.train(train_input, train_output).unwrap_err();
     assert_eq!(actual_error, expected_error);
 }
 
+#[test_case(
+    true,
+    dmatrix![1.0, 5.0, 2.0; 2.0, 5.0, 2.0; 3.0, 5.0, 4.0],
+    "Input variable(s) 1 are constant, so they cannot be distinguished from the intercept. Remove them or drop constant columns.";
+    "with intercept"
+)]
+#[test_case(
+    false,
+    dmatrix![1.0, 0.0, 2.0; 2.0, 0.0, 3.0; 3.0, 0.0, 1.0],
+    "Input variable(s) 1 are always zero, so their coefficients cannot be estimated. Remove them or drop constant columns.";
+    "without intercept"
+)]
+fn ols_fails_to_train_with_constant_input_variables(
+    fit_intercept: bool,
+    train_input: DMatrix<f64>,
+    expected_msg: &str,
+) {
+    let mut ols = OlsRegressor::new(fit_intercept);
+    let actual_error = ols.train(train_input, dvector![1.0, 2.0, 4.0]).unwrap_err();
+    assert_eq!(
+        actual_error,
+        SLearningError::InvalidData(expected_msg.to_string())
+    );
+}
+
+#[test]
+fn ols_drops_constant_input_variables() {
+    let train_input = dmatrix![
+        5.0, 0.0;
+        5.0, 1.0;
+        5.0, 2.0
+    ];
+    let train_output = dvector![1.0, 3.0, 5.0];
+    let mut ols = OlsRegressor::default().with_constant_columns(ConstantColumns::Drop);
+
+    ols.train(train_input, train_output).unwrap();
+
+    let coefficients = ols.coefficients.as_ref().unwrap();
+    assert!((coefficients - dvector![1.0, 0.0, 2.0]).amax() < 1e-10);
+    let predictions = ols.predict(&dmatrix![5.0, 3.0; 7.0, 3.0]).unwrap();
+    assert!((predictions - dvector![7.0, 7.0]).amax() < 1e-10);
+}
+
+#[test]
+fn ridge_trains_with_constant_input_variables() {
+    let mut ridge = RidgeRegressor::<f64>::new(0.5, true).unwrap();
+    ridge
+        .train(
+            dmatrix![5.0, 0.0; 5.0, 1.0; 5.0, 2.0],
+            dvector![1.0, 3.0, 5.0],
+        )
+        .unwrap();
+    assert!(ridge.coefficients.unwrap()[1].abs() < 1e-10);
+}
+
+#[test]
+fn ols_reports_linearly_dependent_input_variables() {
+    let train_input = dmatrix![
+        1.0, 0.0, 1.0, 3.0;
+        0.0, 1.0, 1.0, 1.0;
+        1.0, 1.0, 2.0, 4.0;
+        2.0, 1.0, 3.0, 7.0;
+        1.0, 2.0, 3.0, 5.0
+    ];
+    let train_output = dvector![1.0, 2.0, 3.0, 4.0, 6.0];
+
+    let mut ols = OlsRegressor::new(false);
+    let actual_error = ols.train(train_input, train_output).unwrap_err();
+
+    assert_eq!(
+        actual_error,
+        SLearningError::InvalidData(
+            "The normal matrix is not invertible. The inputs have rank 2, but there are 4 coefficients to estimate. Input variable(s) 2, 3 are (nearly) linear combinations of earlier variables."
+                .to_string()
+        )
+    );
+}
+
 #[test]
 fn ols_fails_to_predict_when_untrained() {
     let test_input = dmatrix![
@@ -135,6 +222,346 @@ fn ols_fails_to_predict_with_wrong_dimensions() {
     assert_eq!(actual, expected);
 }
 
+#[test]
+fn ols_predicts_chunks() {
+    let mut ols = OlsRegressor::default();
+    ols.train(dmatrix![0.0; 1.0; 2.0], dvector![1.0, 3.0, 5.0])
+        .unwrap();
+    let chunks = vec![dmatrix![3.0; 4.0], dmatrix![-1.0], dmatrix![0.0, 0.0]];
+
+    let mut predictions = ols.predict_chunks(chunks);
+
+    let first = predictions.next().unwrap().unwrap();
+    assert!((first - dvector![7.0, 9.0]).amax() < 1e-12);
+    let second = predictions.next().unwrap().unwrap();
+    assert!((second - dvector![-1.0]).amax() < 1e-12);
+    assert_eq!(
+        predictions.next().unwrap().unwrap_err(),
+        SLearningError::InvalidData(
+            "This model was trained with 2 variables, but this input has 3 variables. These must be equal."
+                .to_string()
+        )
+    );
+    assert!(predictions.next().is_none());
+}
+
+#[test]
+fn ridge_predicts_into_buffer() {
+    let mut ridge = RidgeRegressor::new(0.0, false).unwrap();
+    ridge
+        .train(
+            dmatrix![1.0, 0.0; 0.0, 1.0; 1.0, 1.0],
+            dvector![2.0, -1.0, 1.0],
+        )
+        .unwrap();
+    let inputs = dmatrix![2.0, 1.0; 0.0, 3.0];
+    let mut predictions = DVector::zeros(2);
+
+    ridge.predict_into(&inputs, &mut predictions).unwrap();
+
+    assert!((&predictions - ridge.predict(&inputs).unwrap()).amax() < 1e-12);
+    assert!((predictions - dvector![3.0, -3.0]).amax() < 1e-12);
+    let mut wrong_length = DVector::zeros(3);
+    assert_eq!(
+        ridge.predict_into(&inputs, &mut wrong_length).unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has 2 observation(s), but there is space for 3 prediction(s). These must be equal."
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn ols_fails_with_missing_values_by_default() {
+    let mut ols = OlsRegressor::default();
+    let error = ols
+        .train(dmatrix![0.0; f64::NAN; 2.0], dvector![1.0, 3.0, 5.0])
+        .unwrap_err();
+    assert_eq!(
+        error,
+        SLearningError::InvalidData(
+            "Input has a missing (NaN) value for observation 1 and variable 0.".to_string()
+        )
+    );
+    let error = ols
+        .train(dmatrix![0.0; 1.0; 2.0], dvector![1.0, 3.0, f64::NAN])
+        .unwrap_err();
+    assert_eq!(
+        error,
+        SLearningError::InvalidData(
+            "Output has a missing (NaN) value for observation 2.".to_string()
+        )
+    );
+
+    ols.train(dmatrix![0.0; 1.0; 2.0], dvector![1.0, 3.0, 5.0])
+        .unwrap();
+    assert_eq!(
+        ols.predict(&dmatrix![1.0; f64::NAN]).unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has a missing (NaN) value for observation 1 and variable 0.".to_string()
+        )
+    );
+}
+
+#[test]
+fn ols_drops_rows_with_missing_values() {
+    let mut ols = OlsRegressor::default().with_missing_values(MissingValues::DropRows);
+    let inputs = dmatrix![0.0, 1.0; f64::NAN, 5.0; 1.0, 0.0; 2.0, 2.0; 3.0, 1.0];
+    let outputs = dvector![0.0, 100.0, 3.0, 3.0, f64::NAN];
+
+    ols.train(inputs, outputs).unwrap();
+
+    let coefficients = ols.coefficients.as_ref().unwrap();
+    assert!((coefficients - dvector![1.0, 2.0, -1.0]).amax() < 1e-10);
+    let predictions = ols.predict(&dmatrix![1.0, 1.0; f64::NAN, 1.0]).unwrap();
+    assert!((predictions[0] - 2.0).abs() < 1e-10);
+    assert!(predictions[1].is_nan());
+}
+
+#[test_case(ImputeStrategy::Mean, dvector![7.0 / 3.0, 3.0]; "mean")]
+#[test_case(ImputeStrategy::Median, dvector![2.0, 2.0]; "median")]
+#[test_case(ImputeStrategy::Constant(0.5), dvector![0.5, 0.5]; "constant")]
+fn ridge_imputes_missing_values(strategy: ImputeStrategy<f64>, fill_values: DVector<f64>) {
+    let mut ridge = RidgeRegressor::new(0.1, true)
+        .unwrap()
+        .with_missing_values(MissingValues::Impute(strategy));
+    let inputs = dmatrix![1.0, 1.0; 2.0, f64::NAN; 4.0, 2.0; f64::NAN, 6.0; 3.0, f64::NAN];
+    let outputs = dvector![1.0, 4.0, 2.0, 3.0, f64::NAN];
+
+    ridge.train(inputs.clone(), outputs.clone()).unwrap();
+
+    let filled = dmatrix![
+        1.0, 1.0;
+        2.0, fill_values[1];
+        4.0, 2.0;
+        fill_values[0], 6.0
+    ];
+    let mut expected = RidgeRegressor::new(0.1, true).unwrap();
+    expected
+        .train(filled, outputs.rows(0, 4).into_owned())
+        .unwrap();
+    let imputed = ridge.predict(&dmatrix![f64::NAN, f64::NAN]).unwrap();
+    let direct = expected
+        .predict(&dmatrix![fill_values[0], fill_values[1]])
+        .unwrap();
+    assert!((imputed - direct).amax() < 1e-10);
+}
+
+#[test]
+fn nnls_fails_to_impute_without_observed_values() {
+    let mut nnls =
+        NnlsRegressor::default().with_missing_values(MissingValues::Impute(ImputeStrategy::Mean));
+    let error = nnls
+        .train(dmatrix![1.0, f64::NAN; 2.0, f64::NAN], dvector![1.0, 2.0])
+        .unwrap_err();
+    assert_eq!(
+        error,
+        SLearningError::InvalidData(
+            "Variable 1 has no observed values to impute from.".to_string()
+        )
+    );
+}
+
+#[test]
+fn ols_information_criteria_work() {
+    let train_input = dmatrix![0.0; 1.0; 2.0; 3.0; 4.0];
+    let train_output = dvector![1.0, 3.0, 2.0, 5.0, 4.0];
+    let mut ols = OlsRegressor::default();
+    ols.train(train_input.clone(), train_output.clone())
+        .unwrap();
+
+    assert!((ols.noise_variance.unwrap() - 0.72f64).abs() < 1e-12);
+    let training_log_likelihood = ols.log_likelihood(&train_input, &train_output).unwrap();
+    assert!((training_log_likelihood - -6.273432498593275f64).abs() < 1e-12);
+    assert!((ols.aic().unwrap() - 18.54686499718655f64).abs() < 1e-12);
+    assert!((ols.bic().unwrap() - 17.37517873448885f64).abs() < 1e-12);
+
+    let test_log_likelihood = ols
+        .log_likelihood(&dmatrix![1.0; 5.0], &dvector![2.0, 6.0])
+        .unwrap();
+    assert!((test_log_likelihood - -1.787150777215087f64).abs() < 1e-12);
+}
+
+#[test]
+fn ols_information_criteria_fail_when_untrained() {
+    let ols: OlsRegressor<f64> = OlsRegressor::default();
+    assert_eq!(ols.aic().unwrap_err(), SLearningError::UntrainedModel);
+    assert_eq!(ols.bic().unwrap_err(), SLearningError::UntrainedModel);
+    assert_eq!(
+        ols.log_likelihood(&dmatrix![1.0], &dvector![1.0])
+            .unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
+
+#[test_case(true, Some(1.0), vec![2.0, -1.0]; "with intercept")]
+#[test_case(false, None, vec![7.0 / 3.0, -8.0 / 9.0]; "without intercept")]
+fn ols_parameters_work(fit_intercept: bool, intercept: Option<f64>, coefficients: Vec<f64>) {
+    let inputs = dmatrix![1.0, 1.0; 2.0, 1.0; 1.0, 3.0; 3.0, 4.0];
+    let outputs = dvector![2.0, 4.0, 0.0, 3.0];
+    let mut ols = OlsRegressor::new(fit_intercept);
+    ols.train(inputs.clone(), outputs).unwrap();
+
+    let parameters = ols.parameters().unwrap();
+
+    assert_eq!(parameters.intercept.is_some(), intercept.is_some());
+    if let (Some(actual), Some(expected)) = (parameters.intercept, intercept) {
+        assert!((actual - expected).abs() < 1e-10);
+    }
+    let expected = DVector::from_vec(coefficients);
+    assert!((DVector::from_vec(parameters.coefficients.clone()) - expected).amax() < 1e-10);
+
+    let restored = OlsRegressor::default().with_parameters(parameters);
+    assert_eq!(
+        restored.predict(&inputs).unwrap(),
+        ols.predict(&inputs).unwrap()
+    );
+    assert_eq!(restored.aic().unwrap_err(), SLearningError::UntrainedModel);
+}
+
+/// The predictions of a model restored from a trained model's parameters, and of the trained
+/// model.
+fn restored_predictions<M, F, R>(
+    mut model: M,
+    parameters: F,
+    restore: R,
+) -> (DVector<f64>, DVector<f64>)
+where
+    M: SupervisedModel<f64>,
+    F: Fn(&M) -> LinearParameters<f64>,
+    R: Fn(LinearParameters<f64>) -> M,
+{
+    let inputs = dmatrix![1.0, 1.0; 2.0, 1.0; 1.0, 3.0; 3.0, 4.0];
+    model
+        .train(inputs.clone(), dvector![2.0, 4.0, 0.0, 3.0])
+        .unwrap();
+    let restored = restore(parameters(&model));
+    (
+        restored.predict(&inputs).unwrap(),
+        model.predict(&inputs).unwrap(),
+    )
+}
+
+#[test_case(true; "with intercept")]
+#[test_case(false; "without intercept")]
+fn linear_parameters_round_trip(fit_intercept: bool) {
+    let (restored, trained) = restored_predictions(
+        RidgeRegressor::new(0.5, fit_intercept).unwrap(),
+        |model| model.parameters().unwrap(),
+        |parameters| {
+            RidgeRegressor::new(0.5, !fit_intercept)
+                .unwrap()
+                .with_parameters(parameters)
+        },
+    );
+    assert_eq!(restored, trained);
+
+    let (restored, trained) = restored_predictions(
+        NnlsRegressor::new(fit_intercept),
+        |model| model.parameters().unwrap(),
+        |parameters| NnlsRegressor::default().with_parameters(parameters),
+    );
+    assert_eq!(restored, trained);
+
+    let covariance = ErrorCovariance::Ar1(0.3);
+    let (restored, trained) = restored_predictions(
+        GlsRegressor::new(covariance.clone(), fit_intercept).unwrap(),
+        |model| model.parameters().unwrap(),
+        |parameters| {
+            GlsRegressor::new(covariance.clone(), true)
+                .unwrap()
+                .with_parameters(parameters)
+        },
+    );
+    assert_eq!(restored, trained);
+}
+
+#[test_case(Some(1.5), vec![2.0, -0.25], "1.5 + 2 * x - 0.25 * y", r#"1.5 + 2 * "x" - 0.25 * "y""#; "with intercept")]
+#[test_case(None, vec![-2.0, 0.5], "-2 * x + 0.5 * y", r#"-2 * "x" + 0.5 * "y""#; "without intercept")]
+#[test_case(Some(-3.0), vec![], "-3", "-3"; "intercept only")]
+#[test_case(None, vec![], "0", "0"; "empty")]
+fn linear_parameters_export_works(
+    intercept: Option<f64>,
+    coefficients: Vec<f64>,
+    formula: &str,
+    sql: &str,
+) {
+    let names = ["x", "y"];
+    let names = &names[..coefficients.len()];
+    let parameters = LinearParameters {
+        intercept,
+        coefficients,
+    };
+
+    assert_eq!(parameters.to_formula(names).unwrap(), formula);
+    assert_eq!(parameters.to_sql(names).unwrap(), sql);
+}
+
+#[test]
+fn linear_parameters_sql_quotes_columns() {
+    let parameters = LinearParameters {
+        intercept: Some(0.0),
+        coefficients: vec![1.0],
+    };
+
+    assert_eq!(
+        parameters.to_sql(&[r#"odd "name""#]).unwrap(),
+        r#"0 + 1 * "odd ""name""""#
+    );
+}
+
+#[test]
+fn linear_parameters_export_fails_with_invalid_data() {
+    let parameters = LinearParameters {
+        intercept: Some(1.0),
+        coefficients: vec![1.0, 2.0],
+    };
+    assert_eq!(
+        parameters.to_formula(&["x"]).unwrap_err(),
+        SLearningError::InvalidData(
+            "There are 2 coefficient(s), but 1 variable name(s). These must be equal.".to_string()
+        )
+    );
+
+    let parameters = LinearParameters {
+        intercept: None,
+        coefficients: vec![f64::NAN],
+    };
+    assert_eq!(
+        parameters.to_sql(&["x"]).unwrap_err(),
+        SLearningError::InvalidData("Cannot export parameters that are not finite.".to_string())
+    );
+}
+
+#[test]
+fn linear_parameters_fail_when_untrained() {
+    let ols: OlsRegressor<f64> = OlsRegressor::default();
+    assert_eq!(
+        ols.parameters().unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+    let ridge: RidgeRegressor<f64> = RidgeRegressor::new(1.0, true).unwrap();
+    assert_eq!(
+        ridge.parameters().unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
+
+#[test]
+fn ols_log_likelihood_fails_with_inconsistent_dimensions() {
+    let mut ols = OlsRegressor::default();
+    ols.train(dmatrix![0.0; 1.0; 2.0], dvector![1.0, 2.0, 4.0])
+        .unwrap();
+    let expected = SLearningError::InvalidData(
+        "Input has 2 observation(s), but output has 3 observation(s). These must be equal."
+            .to_string(),
+    );
+    let actual = ols
+        .log_likelihood(&dmatrix![0.0; 1.0], &dvector![1.0, 2.0, 3.0])
+        .unwrap_err();
+    assert_eq!(actual, expected);
+}
+
 #[test_case(
     1.0,
     true,
@@ -298,3 +725,799 @@ fn ridge_fails_with_negative_penalty() {
     let ridge = RidgeRegressor::new(-0.5, true).unwrap_err();
     assert_eq!(ridge, expected);
 }
+
+// The least squares fit of y = 1 + 2 x1 - x2 has a negative coefficient, so NNLS drops x2.
+#[test_case(
+    true,
+    dmatrix![1.0, 1.0; 2.0, 1.0; 3.0, 2.0],
+    dvector![2.0, 4.0, 5.0],
+    dvector![2.0 / 3.0, 1.5, 0.0];
+    "with intercept"
+)]
+#[test_case(
+    false,
+    dmatrix![1.0, 1.0; 2.0, 1.0; 3.0, 2.0],
+    dvector![1.0, 3.0, 4.0],
+    dvector![19.0 / 14.0, 0.0];
+    "without intercept"
+)]
+// NNLS is equivalent to OLS when the OLS coefficients are already non-negative.
+#[test_case(
+    true,
+    dmatrix![1.0, 1.0; 1.0, 2.0; 2.0, 2.0; 2.0, 3.0],
+    dvector![6.0, 8.0, 9.0, 11.0],
+    dvector![3.0, 1.0, 2.0];
+    "non-negative least squares fit"
+)]
+fn nnls_works(
+    fit_intercept: bool,
+    train_input: DMatrix<f64>,
+    train_output: DVector<f64>,
+    expected_coefficients: DVector<f64>,
+) {
+    let mut nnls = NnlsRegressor::new(fit_intercept);
+
+    nnls.train(train_input.clone(), train_output).unwrap();
+
+    let coefficients = nnls.coefficients.as_ref().unwrap();
+    assert!((coefficients - &expected_coefficients).amax() < 1e-10);
+
+    let prediction = nnls.predict(&train_input).unwrap();
+    let expected_prediction = if fit_intercept {
+        train_input.insert_column(0, 1.0) * expected_coefficients
+    } else {
+        train_input * expected_coefficients
+    };
+    assert!((prediction - expected_prediction).amax() < 1e-10);
+}
+
+#[test]
+fn nnls_fails_to_train_with_zero_observations() {
+    let train_input: DMatrix<f64> = dmatrix![];
+    let train_output: DVector<f64> = dvector![];
+    let expected_error =
+        SLearningError::InvalidData("Cannot train with zero observations.".to_string());
+
+    let mut nnls = NnlsRegressor::default();
+    let actual_error = nnls.train(train_input, train_output).unwrap_err();
+    assert_eq!(actual_error, expected_error);
+}
+
+#[test]
+fn nnls_fails_to_predict_when_untrained() {
+    let test_input = dmatrix![1.0, 2.0; 3.0, 2.0];
+
+    let nnls: NnlsRegressor<f64> = NnlsRegressor::default();
+    let actual = nnls.predict(&test_input).unwrap_err();
+    assert_eq!(actual, SLearningError::UntrainedModel);
+}
+
+#[test_case(
+    dvector![f64::NEG_INFINITY, f64::NEG_INFINITY],
+    dvector![f64::INFINITY, 1.0],
+    dvector![3.5, 2.0, 1.0];
+    "active upper bound"
+)]
+#[test_case(
+    dvector![0.0, 0.0],
+    dvector![5.0, 5.0],
+    dvector![3.0, 1.0, 2.0];
+    "inactive bounds"
+)]
+#[test_case(
+    dvector![1.5, f64::NEG_INFINITY],
+    dvector![1.5, f64::INFINITY],
+    dvector![2.75, 1.5, 1.75];
+    "fixed coefficient"
+)]
+fn ols_with_bounds_works(lower: DVector<f64>, upper: DVector<f64>, expected: DVector<f64>) {
+    let train_input = dmatrix![1.0, 1.0; 1.0, 2.0; 2.0, 2.0; 2.0, 3.0];
+    let train_output = dvector![6.0, 8.0, 9.0, 11.0];
+    let bounds = CoefficientBounds::new(lower, upper).unwrap();
+    let mut ols = OlsRegressor::default().with_bounds(bounds);
+
+    ols.train(train_input, train_output).unwrap();
+
+    let coefficients = ols.coefficients.as_ref().unwrap();
+    assert!((coefficients - expected).amax() < 1e-6);
+}
+
+#[test]
+fn bounded_ols_matches_nnls() {
+    let train_input = dmatrix![1.0, 1.0; 2.0, 1.0; 3.0, 2.0];
+    let train_output = dvector![2.0, 4.0, 5.0];
+    let bounds =
+        CoefficientBounds::new(dvector![0.0, 0.0], dvector![f64::INFINITY, f64::INFINITY]).unwrap();
+    let mut ols = OlsRegressor::default().with_bounds(bounds);
+    let mut nnls = NnlsRegressor::default();
+
+    ols.train(train_input.clone(), train_output.clone())
+        .unwrap();
+    nnls.train(train_input, train_output).unwrap();
+
+    let difference = ols.coefficients.unwrap() - nnls.coefficients.unwrap();
+    assert!(difference.amax() < 1e-6);
+}
+
+// Bounds also make a model with collinear input variables trainable.
+#[test]
+fn ridge_with_bounds_works() {
+    let train_input = dmatrix![1.0, 2.0; 2.0, 4.0];
+    let train_output = dvector![1.5, 3.5];
+    let bounds = CoefficientBounds::new(dvector![0.0, 0.0], dvector![0.2, 0.2]).unwrap();
+    let mut ridge = RidgeRegressor::new(1.0f64, true)
+        .unwrap()
+        .with_bounds(bounds);
+
+    ridge.train(train_input, train_output).unwrap();
+
+    let coefficients = ridge.coefficients.unwrap();
+    assert!((coefficients[1] - 0.2).abs() < 1e-6);
+    assert!((coefficients[2] - 0.2).abs() < 1e-6);
+}
+
+#[test]
+fn bounds_fail_with_invalid_parameters() {
+    assert_eq!(
+        CoefficientBounds::new(dvector![0.0, 0.0], dvector![1.0]).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "There are 2 lower bound(s), but 1 upper bound(s). These must be equal.".into()
+        )
+    );
+    assert_eq!(
+        CoefficientBounds::new(dvector![1.0], dvector![0.0]).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Lower bounds cannot be greater than upper bounds.".into()
+        )
+    );
+}
+
+#[test]
+fn bounded_ols_fails_to_train_with_wrong_number_of_bounds() {
+    let bounds = CoefficientBounds::new(dvector![0.0], dvector![1.0]).unwrap();
+    let mut ols = OlsRegressor::default().with_bounds(bounds);
+
+    let actual = ols
+        .train(dmatrix![1.0, 2.0; 2.0, 3.0], dvector![1.0, 2.0])
+        .unwrap_err();
+    assert_eq!(
+        actual,
+        SLearningError::InvalidData(
+            "The bounds have 1 variables, but the input has 2 variables. These must be equal."
+                .into()
+        )
+    );
+}
+
+// With uncorrelated, equal-variance errors, GLS is equivalent to OLS.
+#[test_case(ErrorCovariance::Known(DMatrix::identity(4, 4) * 2.0); "identity covariance")]
+#[test_case(ErrorCovariance::Ar1(0.0); "zero autocorrelation")]
+fn gls_with_independent_errors_matches_ols(error_covariance: ErrorCovariance<f64>) {
+    let train_input = dmatrix![1.0, 1.0; 1.0, 2.0; 2.0, 2.0; 2.0, 3.0];
+    let train_output = dvector![6.0, 8.0, 9.0, 11.0];
+    let mut gls = GlsRegressor::new(error_covariance, true).unwrap();
+
+    gls.train(train_input, train_output).unwrap();
+
+    let coefficients = gls.coefficients.as_ref().unwrap();
+    assert!((coefficients - dvector![3.0, 1.0, 2.0]).amax() < 1e-10);
+    let prediction = gls.predict(&dmatrix![3.0, 5.0; 2.0, 1.0]).unwrap();
+    assert!((prediction - dvector![16.0, 7.0]).amax() < 1e-10);
+}
+
+// GLS with a diagonal covariance is weighted least squares, i.e. OLS on rows scaled by the inverse
+// standard deviations.
+#[test]
+fn gls_with_diagonal_covariance_works() {
+    let train_input = dmatrix![1.0; 2.0; 3.0; 4.0; 5.0];
+    let train_output = dvector![1.0, 3.0, 2.0, 5.0, 4.0];
+    let std_devs = dvector![1.0, 2.0, 1.0, 2.0, 1.0];
+    let covariance = DMatrix::from_diagonal(&std_devs.component_mul(&std_devs));
+    let mut gls = GlsRegressor::new(ErrorCovariance::Known(covariance), true).unwrap();
+    gls.train(train_input.clone(), train_output.clone())
+        .unwrap();
+
+    let scaled_input = DMatrix::from_fn(5, 2, |i, j| {
+        if j == 0 {
+            1.0 / std_devs[i]
+        } else {
+            train_input[(i, 0)] / std_devs[i]
+        }
+    });
+    let scaled_output = train_output.component_div(&std_devs);
+    let mut ols = OlsRegressor::new(false);
+    ols.train(scaled_input, scaled_output).unwrap();
+
+    let difference = gls.coefficients.unwrap() - ols.coefficients.unwrap();
+    assert!(difference.amax() < 1e-10);
+}
+
+// The AR(1) transformation is equivalent to the covariance matrix with entries rho^|i - j|.
+#[test]
+fn gls_with_ar1_errors_matches_known_covariance() {
+    let rho = 0.6;
+    let train_input = dmatrix![1.0, 0.5; 2.0, 1.5; 3.0, 0.5; 4.0, 2.0; 5.0, 1.0; 6.0, 3.0];
+    let train_output = dvector![1.0, 3.0, 2.0, 5.0, 4.0, 7.0];
+    let covariance = DMatrix::from_fn(6, 6, |i, j| f64::powi(rho, i.abs_diff(j) as i32));
+
+    let mut ar1 = GlsRegressor::new(ErrorCovariance::Ar1(rho), true).unwrap();
+    ar1.train(train_input.clone(), train_output.clone())
+        .unwrap();
+    let mut known = GlsRegressor::new(ErrorCovariance::Known(covariance), true).unwrap();
+    known.train(train_input, train_output).unwrap();
+
+    let difference = ar1.coefficients.unwrap() - known.coefficients.unwrap();
+    assert!(difference.amax() < 1e-10);
+}
+
+#[test]
+fn gls_fails_with_invalid_autocorrelation() {
+    let actual = GlsRegressor::new(ErrorCovariance::Ar1(1.0), true).unwrap_err();
+    assert_eq!(
+        actual,
+        SLearningError::InvalidParameters(
+            "The autocorrelation must be strictly between minus one and one.".into()
+        )
+    );
+}
+
+#[test_case(
+    DMatrix::identity(3, 3),
+    "The error covariance matrix has shape (3, 3), but there are 2 observation(s). The covariance matrix must be square with a row for each observation.";
+    "wrong shape"
+)]
+#[test_case(
+    dmatrix![1.0, 2.0; 2.0, 1.0],
+    "The error covariance matrix is not positive definite.";
+    "not positive definite"
+)]
+fn gls_fails_to_train_with_invalid_covariance(covariance: DMatrix<f64>, message: &str) {
+    let mut gls = GlsRegressor::new(ErrorCovariance::Known(covariance), true).unwrap();
+
+    let actual = gls
+        .train(dmatrix![1.0; 2.0], dvector![1.0, 2.0])
+        .unwrap_err();
+    assert_eq!(actual, SLearningError::InvalidData(message.into()));
+}
+
+#[test]
+fn explain_works() {
+    let train_input = dmatrix![1.0f64, 1.0; 1.0, 2.0; 2.0, 2.0; 2.0, 3.0];
+    let train_output = dvector![6.0, 8.0, 9.0, 11.0];
+    let mut ols = OlsRegressor::default();
+    ols.train(train_input, train_output).unwrap();
+    let test_input = dmatrix![3.0, 5.0; 2.0, 1.0];
+
+    let explanation = ols.explain(test_input.row(0)).unwrap();
+
+    assert!((explanation.intercept - 3.0).abs() < 1e-10);
+    assert!((&explanation.contributions - dvector![3.0, 10.0]).amax() < 1e-10);
+    let prediction = ols.predict(&test_input).unwrap();
+    assert!((explanation.prediction() - prediction[0]).abs() < 1e-10);
+}
+
+#[test]
+fn explain_works_without_intercept() {
+    let train_input = dmatrix![1.0, 1.0; 1.0, 2.0; 2.0, 2.0; 2.0, 3.0];
+    let train_output = dvector![6.0, 8.0, 9.0, 11.0];
+    let mut ridge = RidgeRegressor::new(1.0, false).unwrap();
+    ridge.train(train_input, train_output).unwrap();
+    let coefficients = ridge.coefficients.clone().unwrap();
+
+    let explanation = ridge.explain(dmatrix![3.0, 5.0].row(0)).unwrap();
+
+    assert_eq!(explanation.intercept, 0.0);
+    assert_eq!(
+        explanation.contributions,
+        dvector![3.0 * coefficients[0], 5.0 * coefficients[1]]
+    );
+}
+
+#[test]
+fn explain_fails_with_invalid_input() {
+    let mut ols = OlsRegressor::default();
+    let test_input = dmatrix![1.0, 2.0, 3.0];
+    assert_eq!(
+        ols.explain(test_input.row(0)).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+
+    ols.train(
+        dmatrix![1.0, 1.0; 1.0, 2.0; 2.0, 2.0; 2.0, 3.0],
+        dvector![6.0, 8.0, 9.0, 11.0],
+    )
+    .unwrap();
+    assert_eq!(
+        ols.explain(test_input.row(0)).unwrap_err(),
+        SLearningError::InvalidData(
+            "This model was trained with 3 variables, but this input has 4 variables. These must be equal.".into()
+        )
+    );
+}
+
+/// A balanced one-way layout with three groups of three, with group means 2, 5 and 8, a within
+/// group mean square of 1 and a between group mean square of 27.
+fn one_way_layout() -> (DMatrix<f64>, DVector<f64>, Vec<usize>) {
+    let outputs = dvector![1.0, 2.0, 3.0, 4.0, 6.0, 5.0, 9.0, 7.0, 8.0];
+    let groups = vec![0, 0, 0, 1, 1, 1, 2, 2, 2];
+    (DMatrix::zeros(9, 0), outputs, groups)
+}
+
+// The variance components have closed forms for a balanced one-way layout: the residual variance
+// is the within group mean square, and the random intercept variance is
+// (27 - 1) / 3 for REML or (27 * 2 / 3 - 1) / 3 for ML.
+#[test_case(true, 26.0 / 3.0; "reml")]
+#[test_case(false, 17.0 / 3.0; "ml")]
+fn mixed_lm_matches_one_way_anova(reml: bool, expected_group_variance: f64) {
+    let (inputs, outputs, groups) = one_way_layout();
+    let mut model = MixedLmRegressor::new(true, vec![])
+        .with_reml(reml)
+        .with_convergence(ConvergenceConfig::new(100_000, 1e-13).unwrap());
+
+    model.train(inputs.clone(), outputs, &groups).unwrap();
+
+    assert!((model.coefficients.as_ref().unwrap()[0] - 5.0).abs() < 1e-6);
+    assert!((model.residual_variance.unwrap() - 1.0).abs() < 1e-4);
+    let group_variance = model.random_effects_covariance.as_ref().unwrap()[(0, 0)];
+    assert!((group_variance - expected_group_variance).abs() < 1e-4);
+    // The random intercepts shrink the group mean deviations towards zero.
+    let shrinkage = group_variance / (group_variance + 1.0 / 3.0);
+    let expected_effects = dmatrix![-3.0; 0.0; 3.0] * shrinkage;
+    assert!((model.random_effects.as_ref().unwrap() - expected_effects).amax() < 1e-4);
+
+    let predictions = model
+        .predict_with_groups(&DMatrix::zeros(2, 0), &[2, 5])
+        .unwrap();
+    assert!((predictions - dvector![5.0 + 3.0 * shrinkage, 5.0]).amax() < 1e-4);
+}
+
+#[test]
+fn mixed_lm_recovers_random_slopes() {
+    let mut rng = Rng::new(8);
+    let num_groups = 40;
+    let group_effects: Vec<(f64, f64)> = (0..num_groups)
+        .map(|_| {
+            (
+                2.0 * rng.standard_normal::<f64>(),
+                0.5 * rng.standard_normal::<f64>(),
+            )
+        })
+        .collect();
+    let groups: Vec<usize> = (0..num_groups * 15).map(|i| i / 15).collect();
+    let inputs = DMatrix::from_fn(groups.len(), 1, |_, _| rng.standard_normal::<f64>());
+    let outputs = DVector::from_fn(groups.len(), |i, _| {
+        let (intercept, slope) = group_effects[groups[i]];
+        1.0 + intercept + (3.0 + slope) * inputs[(i, 0)] + rng.standard_normal::<f64>()
+    });
+    let mut model = MixedLmRegressor::new(true, vec![0]);
+
+    model.train(inputs, outputs, &groups).unwrap();
+
+    let coefficients = model.coefficients.as_ref().unwrap();
+    assert!((coefficients - dvector![1.0, 3.0]).amax() < 0.8);
+    let covariance = model.random_effects_covariance.as_ref().unwrap();
+    assert!((covariance[(0, 0)] - 4.0).abs() < 2.0);
+    assert!((covariance[(1, 1)] - 0.25).abs() < 0.2);
+    assert!((model.residual_variance.unwrap() - 1.0).abs() < 0.2);
+    let random_effects = model.random_effects.as_ref().unwrap();
+    let mean_error = (0..num_groups)
+        .map(|g| (random_effects[(g, 0)] - group_effects[g].0).abs())
+        .sum::<f64>()
+        / num_groups as f64;
+    assert!(mean_error < 0.5);
+}
+
+#[test]
+fn mixed_lm_fails_with_invalid_random_slope() {
+    let mut model = MixedLmRegressor::new(true, vec![1]);
+
+    let actual = model
+        .train(dmatrix![1.0; 2.0; 3.0], dvector![1.0, 2.0, 3.0], &[0, 0, 1])
+        .unwrap_err();
+
+    let message = "Random slope column 1 is out of range for an input with 1 variables.";
+    assert_eq!(
+        actual,
+        SLearningError::InvalidParameters(message.to_string())
+    );
+}
+
+#[test]
+fn mixed_lm_fails_with_wrong_number_of_groups() {
+    let mut model = MixedLmRegressor::new(true, vec![]);
+
+    let actual = model
+        .train(dmatrix![1.0; 2.0; 3.0], dvector![1.0, 2.0, 3.0], &[0, 1])
+        .unwrap_err();
+
+    let message = "Output has 3 observation(s), but there are 2 group(s). These must be equal.";
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
+
+fn sharded_data() -> (DMatrix<f64>, DVector<f64>) {
+    let inputs = dmatrix![
+        1.0, 2.0;
+        2.0, 1.0;
+        3.0, 5.0;
+        4.0, 3.0;
+        5.0, 8.0;
+        6.0, 4.0
+    ];
+    let outputs = dvector![3.1, 4.2, 8.9, 8.1, 14.2, 11.0];
+    (inputs, outputs)
+}
+
+/// The normal equations of the first three and last three observations, merged.
+fn merged_normal_equations(fit_intercept: bool) -> NormalEquations<f64> {
+    let (inputs, outputs) = sharded_data();
+    let mut first = NormalEquations::new(2, fit_intercept);
+    let mut second = NormalEquations::new(2, fit_intercept);
+    first
+        .update(
+            &inputs.rows(0, 3).into_owned(),
+            &outputs.rows(0, 3).into_owned(),
+        )
+        .unwrap();
+    second
+        .update(
+            &inputs.rows(3, 3).into_owned(),
+            &outputs.rows(3, 3).into_owned(),
+        )
+        .unwrap();
+    first.merge(&second).unwrap();
+    first
+}
+
+#[test]
+fn ols_trained_on_merged_normal_equations_matches_full_data() {
+    let (inputs, outputs) = sharded_data();
+    let mut full = OlsRegressor::default();
+    full.train(inputs, outputs).unwrap();
+
+    let mut sharded = OlsRegressor::default();
+    sharded
+        .train_normal_equations(&merged_normal_equations(true))
+        .unwrap();
+
+    let difference = sharded.coefficients.as_ref().unwrap() - full.coefficients.as_ref().unwrap();
+    assert!(difference.amax() < 1e-10);
+    assert!((sharded.noise_variance.unwrap() - full.noise_variance.unwrap()).abs() < 1e-10);
+    assert!((sharded.aic().unwrap() - full.aic().unwrap()).abs() < 1e-8);
+}
+
+#[test_case(false, false; "plain")]
+#[test_case(false, true; "standardized without intercept")]
+#[test_case(true, true; "standardized with intercept")]
+fn ridge_trained_on_merged_normal_equations_matches_full_data(
+    fit_intercept: bool,
+    standardize: bool,
+) {
+    let (inputs, outputs) = sharded_data();
+    let mut full = RidgeRegressor::new(2.0, fit_intercept)
+        .unwrap()
+        .with_standardize(standardize);
+    full.train(inputs, outputs).unwrap();
+
+    let mut sharded = RidgeRegressor::new(2.0, fit_intercept)
+        .unwrap()
+        .with_standardize(standardize);
+    sharded
+        .train_normal_equations(&merged_normal_equations(fit_intercept))
+        .unwrap();
+
+    let difference = sharded.coefficients.unwrap() - full.coefficients.unwrap();
+    assert!(difference.amax() < 1e-10);
+}
+
+#[test]
+fn normal_equations_must_match_the_model() {
+    let mut ols = OlsRegressor::new(false);
+
+    let result = ols.train_normal_equations(&merged_normal_equations(true));
+
+    assert!(matches!(result, Err(SLearningError::InvalidParameters(_))));
+    let mut normal_equations = NormalEquations::<f64>::new(2, true);
+    assert!(matches!(
+        normal_equations.merge(&NormalEquations::new(3, true)),
+        Err(SLearningError::InvalidData(_))
+    ));
+    assert!(matches!(
+        OlsRegressor::default().train_normal_equations(&normal_equations),
+        Err(SLearningError::InvalidData(_))
+    ));
+}
+
+#[test_case(true; "with intercept")]
+#[test_case(false; "without intercept")]
+fn standardized_ridge_matches_ridge_on_standardized_inputs(fit_intercept: bool) {
+    let (inputs, outputs) = sharded_data();
+    let num_obs = inputs.nrows() as f64;
+    let centers = inputs.row_mean();
+    let scales = DVector::from_fn(inputs.ncols(), |var, _| {
+        let center = if fit_intercept { centers[var] } else { 0.0 };
+        let sum_squares: f64 = inputs
+            .column(var)
+            .iter()
+            .map(|x| (x - center).powi(2))
+            .sum();
+        (sum_squares / num_obs).sqrt()
+    });
+    let scaled_inputs = DMatrix::from_fn(inputs.nrows(), inputs.ncols(), |i, j| {
+        inputs[(i, j)] / scales[j]
+    });
+    let mut scaled = RidgeRegressor::new(1.5, fit_intercept).unwrap();
+    scaled.train(scaled_inputs, outputs.clone()).unwrap();
+    let scaled_coefficients = scaled.coefficients.unwrap();
+
+    let mut standardized = RidgeRegressor::new(1.5, fit_intercept)
+        .unwrap()
+        .with_standardize(true);
+    standardized.train(inputs, outputs).unwrap();
+
+    let offset = if fit_intercept { 1 } else { 0 };
+    let coefficients = standardized.coefficients.unwrap();
+    if fit_intercept {
+        assert!((coefficients[0] - scaled_coefficients[0]).abs() < 1e-10);
+    }
+    for var in 0..scales.len() {
+        let expected = scaled_coefficients[var + offset] / scales[var];
+        assert!((coefficients[var + offset] - expected).abs() < 1e-10);
+    }
+}
+
+#[test]
+fn standardized_ridge_does_not_depend_on_units() {
+    let (inputs, outputs) = sharded_data();
+    let mut rescaled_inputs = inputs.clone();
+    rescaled_inputs.column_mut(1).scale_mut(1000.0);
+
+    let mut original = RidgeRegressor::new(3.0, true)
+        .unwrap()
+        .with_standardize(true);
+    original.train(inputs.clone(), outputs.clone()).unwrap();
+    let mut rescaled = RidgeRegressor::new(3.0, true)
+        .unwrap()
+        .with_standardize(true);
+    rescaled.train(rescaled_inputs.clone(), outputs).unwrap();
+
+    let original_predictions = original.predict(&inputs).unwrap();
+    let rescaled_predictions = rescaled.predict(&rescaled_inputs).unwrap();
+    assert!((original_predictions - rescaled_predictions).amax() < 1e-8);
+    let original_coefficients = original.coefficients.unwrap();
+    let rescaled_coefficients = rescaled.coefficients.unwrap();
+    assert!((rescaled_coefficients[2] * 1000.0 - original_coefficients[2]).abs() < 1e-8);
+}
+
+/// Outputs for two targets of the sharded data, the second unrelated to the first.
+fn two_target_outputs() -> DMatrix<f64> {
+    let (_, outputs) = sharded_data();
+    DMatrix::from_fn(outputs.len(), 2, |i, t| match t {
+        0 => outputs[i],
+        _ => [1.0, -2.0, 0.5, 3.0, -1.0, 2.5][i],
+    })
+}
+
+#[test_case(true, false; "with intercept")]
+#[test_case(false, false; "without intercept")]
+#[test_case(true, true; "standardized")]
+fn multi_output_ridge_matches_ridge_per_target(fit_intercept: bool, standardize: bool) {
+    let (inputs, _) = sharded_data();
+    let outputs = two_target_outputs();
+    let penalties = dvector![0.5, 4.0];
+    let mut model = RidgeRegressor::new(1.0, fit_intercept)
+        .unwrap()
+        .with_standardize(standardize)
+        .with_target_penalties(penalties.clone())
+        .unwrap();
+    model
+        .train_multi_output(inputs.clone(), outputs.clone())
+        .unwrap();
+    let coefficients = model.multi_output_coefficients.as_ref().unwrap();
+    let predictions = model.predict_multi_output(&inputs).unwrap();
+
+    for target in 0..2 {
+        let mut single = RidgeRegressor::new(penalties[target], fit_intercept)
+            .unwrap()
+            .with_standardize(standardize);
+        single
+            .train(inputs.clone(), outputs.column(target).into_owned())
+            .unwrap();
+        let expected = single.coefficients.as_ref().unwrap();
+        assert!((coefficients.column(target) - expected).amax() < 1e-10);
+        let expected_predictions = single.predict(&inputs).unwrap();
+        assert!((predictions.column(target) - expected_predictions).amax() < 1e-10);
+    }
+}
+
+#[test]
+fn multi_output_ridge_uses_scalar_penalty_for_all_targets() {
+    let (inputs, _) = sharded_data();
+    let outputs = two_target_outputs();
+    let mut model = RidgeRegressor::new(2.0, true).unwrap();
+    model
+        .train_multi_output(inputs.clone(), outputs.clone())
+        .unwrap();
+
+    let mut per_target = RidgeRegressor::new(0.0, true)
+        .unwrap()
+        .with_target_penalties(dvector![2.0, 2.0])
+        .unwrap();
+    per_target.train_multi_output(inputs, outputs).unwrap();
+    let difference =
+        model.multi_output_coefficients.unwrap() - per_target.multi_output_coefficients.unwrap();
+    assert!(difference.amax() < 1e-12);
+}
+
+#[test]
+fn multi_output_ridge_fails_with_invalid_parameters() {
+    assert_eq!(
+        RidgeRegressor::new(1.0, true)
+            .unwrap()
+            .with_target_penalties(dvector![1.0, -1.0])
+            .unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Penalty must be finite and cannot be less than zero.".to_string()
+        )
+    );
+    assert_eq!(
+        RidgeRegressor::new(1.0, true)
+            .unwrap()
+            .with_target_penalties(dvector![f64::NAN, 1.0])
+            .unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Penalty must be finite and cannot be less than zero.".to_string()
+        )
+    );
+    assert_eq!(
+        RidgeRegressor::new(1.0, true)
+            .unwrap()
+            .with_target_penalties(DVector::zeros(0))
+            .unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Target penalties must have at least one penalty.".to_string()
+        )
+    );
+    let bounds = CoefficientBounds::new(dvector![0.0, 0.0], dvector![1.0, 1.0]).unwrap();
+    let mut bounded = RidgeRegressor::new(1.0, true).unwrap().with_bounds(bounds);
+    let (inputs, _) = sharded_data();
+    assert_eq!(
+        bounded
+            .train_multi_output(inputs, two_target_outputs())
+            .unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Bounds are not supported when training on multiple outputs.".to_string()
+        )
+    );
+}
+
+#[test]
+fn multi_output_ridge_fails_with_invalid_data() {
+    let (inputs, _) = sharded_data();
+    let mut model = RidgeRegressor::new(0.0, true)
+        .unwrap()
+        .with_target_penalties(dvector![1.0, 0.0, 2.0])
+        .unwrap();
+    assert_eq!(
+        model.predict_multi_output(&inputs).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+    assert_eq!(
+        model
+            .train_multi_output(inputs.clone(), two_target_outputs())
+            .unwrap_err(),
+        SLearningError::InvalidData(
+            "There are 3 target penalties, but the output has 2 targets. These must be equal."
+                .to_string()
+        )
+    );
+
+    let collinear = DMatrix::from_fn(6, 2, |i, j| (i + 1) as f64 * (j + 1) as f64);
+    let mut model = RidgeRegressor::new(0.0, true)
+        .unwrap()
+        .with_target_penalties(dvector![1.0, 0.0])
+        .unwrap();
+    assert_eq!(
+        model
+            .train_multi_output(collinear, two_target_outputs())
+            .unwrap_err(),
+        SLearningError::InvalidData(
+            "The inputs are linearly dependent, so the penalty for target 1 must be greater than zero."
+                .to_string()
+        )
+    );
+
+    let mut outputs = two_target_outputs();
+    outputs[(4, 1)] = f64::NAN;
+    assert_eq!(
+        model.train_multi_output(inputs, outputs).unwrap_err(),
+        SLearningError::InvalidData(
+            "Output has a missing (NaN) value for observation 4 and target 1.".to_string()
+        )
+    );
+}
+
+#[test]
+fn ridge_with_parameters_clears_multi_output_coefficients() {
+    let (inputs, _) = sharded_data();
+    let mut model = RidgeRegressor::new(1.0, true).unwrap();
+    model
+        .train_multi_output(inputs.clone(), two_target_outputs())
+        .unwrap();
+
+    let model = model.with_parameters(LinearParameters {
+        intercept: Some(1.0),
+        coefficients: vec![2.0, 3.0],
+    });
+
+    assert_eq!(
+        model.predict_multi_output(&inputs).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
+
+#[test_case(0.0; "zero penalty")]
+#[test_case(2.0; "penalty")]
+#[test_case(1e6; "large penalty")]
+fn ridge_effective_degrees_of_freedom_and_gcv(penalty: f64) {
+    let (inputs, outputs) = sharded_data();
+    let mut ridge = RidgeRegressor::new(penalty, true).unwrap();
+    ridge.train(inputs.clone(), outputs.clone()).unwrap();
+
+    // With an unpenalised intercept, `df = 1 + sum(d / (d + penalty))` for the eigenvalues `d` of
+    // the centred normal matrix.
+    let means = inputs.row_mean();
+    let centered = DMatrix::from_fn(6, 2, |i, j| inputs[(i, j)] - means[j]);
+    let eigenvalues = (centered.transpose() * &centered).symmetric_eigenvalues();
+    let expected_dof = 1.0 + eigenvalues.map(|d| d / (d + penalty)).sum();
+    let dof = ridge.effective_degrees_of_freedom().unwrap();
+    assert!((dof - expected_dof).abs() < 1e-8);
+
+    let residual_sum_of_squares = (&outputs - ridge.predict(&inputs).unwrap()).norm_squared();
+    let expected_gcv = 6.0 * residual_sum_of_squares / (6.0 - expected_dof).powi(2);
+    assert!((ridge.gcv().unwrap() - expected_gcv).abs() < 1e-8);
+
+    let mut sharded = RidgeRegressor::new(penalty, true).unwrap();
+    sharded
+        .train_normal_equations(&merged_normal_equations(true))
+        .unwrap();
+    assert!((sharded.effective_degrees_of_freedom().unwrap() - dof).abs() < 1e-8);
+    assert!((sharded.gcv().unwrap() - ridge.gcv().unwrap()).abs() < 1e-8);
+}
+
+#[test]
+fn ridge_effective_degrees_of_freedom_count_estimable_coefficients() {
+    let inputs = dmatrix![1.0, 5.0; 2.0, 5.0; 3.0, 5.0; 4.0, 5.0];
+    let mut ridge = RidgeRegressor::<f64>::new(0.0, true)
+        .unwrap()
+        .with_constant_columns(ConstantColumns::Drop);
+    ridge.train(inputs, dvector![1.0, 3.0, 2.0, 5.0]).unwrap();
+
+    assert!((ridge.effective_degrees_of_freedom().unwrap() - 2.0).abs() < 1e-10);
+}
+
+#[test]
+fn ridge_gcv_fails_without_a_linear_fit() {
+    let ridge: RidgeRegressor<f64> = RidgeRegressor::new(1.0, true).unwrap();
+    assert_eq!(ridge.gcv().unwrap_err(), SLearningError::UntrainedModel);
+
+    let (inputs, outputs) = sharded_data();
+    let bounds = CoefficientBounds::new(dvector![0.0, 0.0], dvector![1.0, 1.0]).unwrap();
+    let mut bounded = RidgeRegressor::new(1.0, true).unwrap().with_bounds(bounds);
+    bounded.train(inputs, outputs).unwrap();
+    assert_eq!(
+        bounded.effective_degrees_of_freedom().unwrap_err(),
+        SLearningError::InvalidData(
+            "The effective degrees of freedom are only known for a model trained without bounds."
+                .to_string()
+        )
+    );
+
+    let mut interpolating = RidgeRegressor::new(0.0, true).unwrap();
+    interpolating
+        .train(dmatrix![1.0; 2.0], dvector![3.0, 1.0])
+        .unwrap();
+    assert!(matches!(
+        interpolating.gcv().unwrap_err(),
+        SLearningError::InvalidData(_)
+    ));
+}
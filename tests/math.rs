@@ -0,0 +1,55 @@
+use nalgebra::{dmatrix, dvector, DVector};
+use test_case::test_case;
+
+use slearning::math::{log1pexp, log_sum_exp, log_sum_exp_rows, sigmoid, softmax, softmax_rows};
+
+#[test_case(0.0, 0.5; "zero")]
+#[test_case(2.0, 1.0 / (1.0 + (-2.0f64).exp()); "positive")]
+#[test_case(-2.0, 1.0 / (1.0 + 2.0f64.exp()); "negative")]
+#[test_case(1000.0, 1.0; "large positive")]
+#[test_case(-1000.0, 0.0; "large negative")]
+fn sigmoid_works(x: f64, expected: f64) {
+    assert!((sigmoid(x) - expected).abs() < 1e-15);
+}
+
+#[test_case(0.0, 2.0f64.ln(); "zero")]
+#[test_case(1.0, 1.0f64.exp().ln_1p(); "positive")]
+#[test_case(1000.0, 1000.0; "large positive")]
+#[test_case(-1000.0, 0.0; "large negative")]
+fn log1pexp_works(x: f64, expected: f64) {
+    assert!((log1pexp(x) - expected).abs() < 1e-12);
+}
+
+#[test_case(dvector![0.0, 0.0], 2.0f64.ln(); "small")]
+#[test_case(dvector![1000.0, 1000.0], 1000.0 + 2.0f64.ln(); "large")]
+#[test_case(dvector![-1000.0, -1000.0], -1000.0 + 2.0f64.ln(); "large negative")]
+#[test_case(dvector![f64::NEG_INFINITY, 0.0], 0.0; "negative infinity")]
+#[test_case(dvector![], f64::NEG_INFINITY; "empty")]
+fn log_sum_exp_works(x: DVector<f64>, expected: f64) {
+    let actual = log_sum_exp(&x);
+    assert!(actual == expected || (actual - expected).abs() < 1e-12);
+}
+
+#[test]
+fn softmax_works() {
+    let actual = softmax(&dvector![1.0, 2.0, 3.0]);
+    let total = 1.0f64.exp() + 2.0f64.exp() + 3.0f64.exp();
+    let expected = dvector![1.0f64.exp(), 2.0f64.exp(), 3.0f64.exp()] / total;
+    assert!((actual - expected).abs().max() < 1e-12);
+
+    // Adding a constant does not change the softmax, even if it would overflow `exp`.
+    let actual: DVector<f64> = softmax(&dvector![1001.0, 1002.0, 1003.0]);
+    assert!(actual.iter().all(|p| p.is_finite()));
+    assert!((actual.sum() - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn row_wise_functions_work() {
+    let x = dmatrix![0.0, 0.0; 1000.0, -1000.0];
+
+    let actual = log_sum_exp_rows(&x);
+    assert!((actual - dvector![2.0f64.ln(), 1000.0]).abs().max() < 1e-12);
+
+    let actual = softmax_rows(&x);
+    assert!((actual - dmatrix![0.5, 0.5; 1.0, 0.0]).abs().max() < 1e-12);
+}
@@ -0,0 +1,355 @@
+use nalgebra::{dmatrix, dvector, DMatrix, DVector};
+use test_case::test_case;
+
+use slearning::metrics::fairness::{
+    demographic_parity_difference, equalized_odds_difference, group_metrics,
+};
+use slearning::metrics::{
+    calibration_curve, confusion_matrix, hamming_loss, mean_average_precision,
+    min_expected_cost_classes, ndcg, subset_accuracy, total_cost, tune_threshold, BinaryConfusion,
+    ThresholdMetric,
+};
+use slearning::SLearningError;
+
+#[test]
+fn binary_confusion_works() {
+    let actual = dvector![1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 0.0];
+    let predicted = dvector![1.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0];
+
+    let confusion = BinaryConfusion::from_predictions(&actual, &predicted).unwrap();
+
+    assert_eq!(
+        confusion,
+        BinaryConfusion {
+            true_positives: 2,
+            false_positives: 1,
+            true_negatives: 3,
+            false_negatives: 1,
+        }
+    );
+    assert_eq!(confusion.precision::<f64>(), 2.0 / 3.0);
+    assert_eq!(confusion.recall::<f64>(), 2.0 / 3.0);
+    assert_eq!(confusion.false_positive_rate::<f64>(), 0.25);
+    assert_eq!(confusion.f1_score::<f64>(), 2.0 / 3.0);
+    assert_eq!(confusion.accuracy::<f64>(), 5.0 / 7.0);
+}
+
+#[test]
+fn binary_confusion_fails_with_invalid_labels() {
+    let actual = dvector![1.0, 2.0];
+    let predicted = dvector![1.0, 0.0];
+
+    assert_eq!(
+        BinaryConfusion::from_predictions(&actual, &predicted).unwrap_err(),
+        SLearningError::InvalidData("Binary labels must be zero or one.".into())
+    );
+}
+
+#[test_case(
+    dvector![0.0, 0.0, 1.0, 1.0, 0.0, 1.0],
+    dvector![0.1, 0.4, 0.35, 0.8, 0.2, 0.9],
+    ThresholdMetric::F1,
+    0.35,
+    6.0 / 7.0;
+    "f1"
+)]
+#[test_case(
+    dvector![0.0, 1.0, 0.0, 1.0],
+    dvector![0.1, 0.3, 0.2, 0.4],
+    ThresholdMetric::YoudenJ,
+    0.3,
+    1.0;
+    "youden j"
+)]
+#[test_case(
+    dvector![0.0, 0.0, 1.0, 1.0, 0.0, 1.0],
+    dvector![0.1, 0.4, 0.35, 0.8, 0.2, 0.9],
+    ThresholdMetric::PrecisionAtRecall(0.9),
+    0.35,
+    0.75;
+    "precision at high recall"
+)]
+#[test_case(
+    dvector![0.0, 0.0, 1.0, 1.0, 0.0, 1.0],
+    dvector![0.1, 0.4, 0.35, 0.8, 0.2, 0.9],
+    ThresholdMetric::PrecisionAtRecall(0.5),
+    0.8,
+    1.0;
+    "precision at low recall"
+)]
+fn tune_threshold_works(
+    actual: DVector<f64>,
+    scores: DVector<f64>,
+    metric: ThresholdMetric<f64>,
+    expected_threshold: f64,
+    expected_score: f64,
+) {
+    let tuned = tune_threshold(&actual, &scores, metric).unwrap();
+
+    assert_eq!(tuned.threshold, expected_threshold);
+    assert!((tuned.score - expected_score).abs() < 1e-12);
+}
+
+#[test_case(
+    dvector![1.0, 1.0],
+    ThresholdMetric::F1,
+    SLearningError::InvalidData("Both classes must be present to tune a threshold.".into());
+    "one class"
+)]
+#[test_case(
+    dvector![0.0, 1.0],
+    ThresholdMetric::PrecisionAtRecall(1.5),
+    SLearningError::InvalidParameters("Recall must be greater than zero and at most one.".into());
+    "invalid recall"
+)]
+#[test_case(
+    dvector![0.0, 1.0],
+    ThresholdMetric::PrecisionAtRecall(f64::NAN),
+    SLearningError::InvalidParameters("Recall must be greater than zero and at most one.".into());
+    "nan recall"
+)]
+fn tune_threshold_fails_with_invalid_inputs(
+    actual: DVector<f64>,
+    metric: ThresholdMetric<f64>,
+    expected: SLearningError,
+) {
+    let scores = dvector![0.2, 0.7];
+
+    assert_eq!(
+        tune_threshold(&actual, &scores, metric).unwrap_err(),
+        expected
+    );
+}
+
+#[test]
+fn tune_threshold_fails_with_nan_score() {
+    let actual = dvector![0.0, 1.0, 1.0];
+    let scores = dvector![0.2, f64::NAN, 0.7];
+
+    assert_eq!(
+        tune_threshold(&actual, &scores, ThresholdMetric::F1).unwrap_err(),
+        SLearningError::InvalidData("The scores have a non-finite value for observation 1.".into())
+    );
+}
+
+#[test]
+fn calibration_curve_works() {
+    let actual = dvector![0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 1.0];
+    let probabilities = dvector![0.1, 0.25, 0.3, 0.65, 0.7, 0.9, 1.0];
+
+    let curve = calibration_curve(&actual, &probabilities, 5).unwrap();
+
+    // The bin [0.4, 0.6) is empty, and a probability of one is in the last bin.
+    assert_eq!(curve.counts, vec![1, 2, 2, 2]);
+    let expected_mean_predicted = dvector![0.1, 0.275, 0.675, 0.95];
+    assert!((curve.mean_predicted - expected_mean_predicted).amax() < 1e-12);
+    assert_eq!(curve.observed_frequency, dvector![0.0, 0.5, 0.5, 1.0]);
+}
+
+#[test_case(dvector![0.5, 1.5], 2, SLearningError::InvalidData("Probabilities must be between zero and one.".into()); "invalid probability")]
+#[test_case(dvector![0.5, f64::NAN], 2, SLearningError::InvalidData("The probabilities have a non-finite value for observation 1.".into()); "nan probability")]
+#[test_case(dvector![0.5, 0.5], 0, SLearningError::InvalidParameters("Number of bins must be at least one.".into()); "zero bins")]
+fn calibration_curve_fails_with_invalid_inputs(
+    probabilities: DVector<f64>,
+    n_bins: usize,
+    expected: SLearningError,
+) {
+    let actual = dvector![0.0, 1.0];
+
+    assert_eq!(
+        calibration_curve(&actual, &probabilities, n_bins).unwrap_err(),
+        expected
+    );
+}
+
+#[test]
+fn multilabel_metrics_work() {
+    let actual = dmatrix![1.0, 0.0, 1.0; 0.0, 1.0, 0.0; 1.0, 1.0, 0.0; 0.0, 0.0, 0.0];
+    let predicted = dmatrix![1.0, 0.0, 1.0; 0.0, 0.0, 0.0; 1.0, 1.0, 1.0; 0.0, 0.0, 0.0];
+
+    assert_eq!(hamming_loss(&actual, &predicted).unwrap(), 2.0 / 12.0);
+    assert_eq!(subset_accuracy(&actual, &predicted).unwrap(), 0.5);
+}
+
+#[test_case(dmatrix![1.0, 0.0], "The true labels have shape (1, 2), but the predictions have shape (1, 3). These must be equal."; "shape")]
+#[test_case(dmatrix![1.0, 0.5, 0.0], "Binary labels must be zero or one."; "non-binary")]
+fn multilabel_metrics_fail_with_invalid_inputs(actual: DMatrix<f64>, message: &str) {
+    let predicted = dmatrix![1.0, 0.0, 0.0];
+
+    let error = hamming_loss(&actual, &predicted).unwrap_err();
+
+    assert_eq!(error, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test_case(None, 0.9360403422435027; "full ranking")]
+#[test_case(Some(2), 0.7871546029909718; "cutoff")]
+fn ndcg_works(cutoff: Option<usize>, expected: f64) {
+    // The second query has no relevant observations, so is left out.
+    let relevance = dvector![3.0, 2.0, 0.0, 1.0, 0.0, 0.0];
+    let scores = dvector![0.9, 0.1, 0.5, 0.3, 0.2, 0.4];
+    let queries = [0, 0, 0, 0, 1, 1];
+
+    let actual = ndcg(&relevance, &scores, &queries, cutoff).unwrap();
+
+    assert!((actual - expected).abs() < 1e-12);
+}
+
+#[test]
+fn mean_average_precision_works() {
+    let relevance = dvector![1.0, 0.0, 1.0, 0.0, 2.0, 0.0];
+    let scores = dvector![0.2, 0.9, 0.5, 0.1, 0.8, 0.3];
+    let queries = [5, 5, 5, 2, 2, 7];
+
+    let actual = mean_average_precision(&relevance, &scores, &queries).unwrap();
+
+    assert!((actual - 19.0f64 / 24.0).abs() < 1e-12);
+}
+
+#[test_case(dvector![1.0, -1.0], &[0, 0], "Relevance cannot be negative."; "negative relevance")]
+#[test_case(dvector![1.0, 0.0], &[0], "The true labels have 2 observation(s), but there are 1 query id(s). These must be equal."; "query ids")]
+#[test_case(dvector![0.0, 0.0], &[0, 1], "At least one query must have a relevant observation."; "no relevant")]
+#[test_case(dvector![1.0, f64::NAN], &[0, 0], "The relevance values have a non-finite value for observation 1."; "nan relevance")]
+fn ranking_metrics_fail_with_invalid_inputs(
+    relevance: DVector<f64>,
+    queries: &[usize],
+    message: &str,
+) {
+    let scores = dvector![0.5, 0.2];
+    let expected = SLearningError::InvalidData(message.to_string());
+
+    assert_eq!(
+        ndcg(&relevance, &scores, queries, None).unwrap_err(),
+        expected
+    );
+    assert_eq!(
+        mean_average_precision(&relevance, &scores, queries).unwrap_err(),
+        expected
+    );
+}
+
+fn grouped_predictions() -> (DVector<f64>, DVector<f64>, Vec<usize>) {
+    let actual = dvector![1.0, 0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 0.0];
+    let predicted = dvector![1.0, 0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0];
+    let groups = vec![3, 3, 3, 3, 1, 1, 1, 1];
+    (actual, predicted, groups)
+}
+
+#[test]
+fn group_metrics_works() {
+    let (actual, predicted, groups) = grouped_predictions();
+
+    let metrics = group_metrics(&actual, &predicted, &groups).unwrap();
+
+    assert_eq!(metrics.len(), 2);
+    assert_eq!(metrics[0].group, 1);
+    assert_eq!(metrics[0].accuracy, 0.75);
+    assert_eq!(metrics[0].true_positive_rate, 0.5);
+    assert_eq!(metrics[0].false_positive_rate, 0.0);
+    assert_eq!(metrics[0].selection_rate, 0.25);
+    assert_eq!(metrics[1].group, 3);
+    assert_eq!(metrics[1].accuracy, 1.0);
+    assert_eq!(metrics[1].selection_rate, 0.5);
+}
+
+#[test]
+fn fairness_gaps_work() {
+    let (actual, predicted, groups) = grouped_predictions();
+
+    let parity = demographic_parity_difference(&predicted, &groups).unwrap();
+    let odds = equalized_odds_difference(&actual, &predicted, &groups).unwrap();
+
+    assert_eq!(parity, 0.25);
+    assert_eq!(odds, 0.5);
+}
+
+#[test]
+fn equalized_odds_ignores_rates_that_are_undefined() {
+    // The second group has no negatives, so only its true positive rate is compared.
+    let actual = dvector![1.0, 0.0, 1.0, 1.0];
+    let predicted = dvector![1.0, 1.0, 1.0, 1.0];
+
+    let odds = equalized_odds_difference(&actual, &predicted, &[0, 0, 1, 1]).unwrap();
+
+    assert_eq!(odds, 0.0);
+}
+
+#[test]
+fn fairness_metrics_check_group_ids() {
+    let (actual, predicted, _) = grouped_predictions();
+
+    let result = group_metrics(&actual, &predicted, &[0, 1]);
+
+    assert!(matches!(result, Err(SLearningError::InvalidData(_))));
+    assert!(matches!(
+        demographic_parity_difference(&predicted, &[0]),
+        Err(SLearningError::InvalidData(_))
+    ));
+}
+
+#[test]
+fn confusion_matrix_and_total_cost_work() {
+    let actual = dvector![0.0, 1.0, 2.0, 2.0, 1.0, 0.0];
+    let predicted = dvector![0.0, 2.0, 2.0, 1.0, 1.0, 0.0];
+    let cost_matrix = dmatrix![
+        0.0, 1.0, 4.0;
+        2.0, 0.0, 1.0;
+        8.0, 3.0, 0.0
+    ];
+
+    let confusion = confusion_matrix(&actual, &predicted, 3).unwrap();
+    let cost = total_cost(&confusion, &cost_matrix).unwrap();
+
+    assert_eq!(
+        confusion,
+        dmatrix![2.0, 0.0, 0.0; 0.0, 1.0, 1.0; 0.0, 1.0, 1.0]
+    );
+    assert_eq!(cost, 4.0);
+}
+
+#[test_case(dvector![0.0, 3.0] ; "label too large")]
+#[test_case(dvector![0.0, 0.5] ; "fractional label")]
+#[test_case(dvector![-1.0, 0.0] ; "negative label")]
+fn confusion_matrix_rejects_invalid_labels(predicted: DVector<f64>) {
+    let result = confusion_matrix(&dvector![0.0, 1.0], &predicted, 3);
+
+    assert!(matches!(result, Err(SLearningError::InvalidData(_))));
+}
+
+#[test]
+fn min_expected_cost_classes_works() {
+    let probabilities = dmatrix![0.7, 0.3; 0.9, 0.1; 0.4, 0.6];
+    // Missing a positive costs five times as much as a false alarm.
+    let cost_matrix = dmatrix![0.0, 1.0; 5.0, 0.0];
+    let zero_one = dmatrix![0.0, 1.0; 1.0, 0.0];
+
+    let classes = min_expected_cost_classes(&probabilities, &cost_matrix).unwrap();
+    let most_probable = min_expected_cost_classes(&probabilities, &zero_one).unwrap();
+
+    assert_eq!(classes, dvector![1.0, 0.0, 1.0]);
+    assert_eq!(most_probable, dvector![0.0, 0.0, 1.0]);
+}
+
+#[test]
+fn cost_matrix_must_match_the_classes() {
+    let probabilities = dmatrix![0.7, 0.3];
+
+    let result = min_expected_cost_classes(&probabilities, &DMatrix::zeros(3, 3));
+
+    assert!(matches!(result, Err(SLearningError::InvalidData(_))));
+    assert!(matches!(
+        total_cost(&DMatrix::<f64>::zeros(2, 2), &DMatrix::zeros(2, 3)),
+        Err(SLearningError::InvalidData(_))
+    ));
+}
+
+#[test]
+fn total_cost_fails_with_non_square_confusion_matrix() {
+    let actual = total_cost(&DMatrix::<f64>::zeros(2, 3), &DMatrix::zeros(2, 2)).unwrap_err();
+
+    assert_eq!(
+        actual,
+        SLearningError::InvalidData(
+            "The confusion matrix has 2 row(s) and 3 column(s). It must be square, with a row and column for each class.".into()
+        )
+    );
+}
@@ -0,0 +1,511 @@
+use nalgebra::{dmatrix, dvector, DMatrix, DVector};
+use test_case::test_case;
+
+use slearning::linear_regression::{OlsRegressor, RidgeRegressor};
+use slearning::metrics::ThresholdMetric;
+use slearning::model_selection::compare::{
+    five_by_two_cv_test, paired_t_test, wilcoxon_signed_rank, TTest, WilcoxonTest,
+};
+use slearning::model_selection::{
+    cross_validate, nested_cv, quantile_bins, resample, resample_indices, train_test_split,
+    GridSearchCv, KFold, ThresholdClassifier,
+};
+use slearning::{SLearningError, SLearningResult, SupervisedModel};
+
+#[test]
+fn threshold_classifier_works() {
+    let inputs = dmatrix![0.0; 1.0; 2.0; 3.0; 4.0; 5.0];
+    let outputs = dvector![0.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+    let mut classifier =
+        ThresholdClassifier::new(OlsRegressor::default(), ThresholdMetric::F1).unwrap();
+
+    classifier.train(inputs, outputs).unwrap();
+
+    // OLS scores the observations in order, so the threshold separates the classes perfectly.
+    let threshold = classifier.threshold.unwrap();
+    let scores = classifier.predict_scores(&dmatrix![2.0; 3.0]).unwrap();
+    assert!(scores[0] < threshold && threshold <= scores[1]);
+    let prediction = classifier.predict(&dmatrix![-1.0; 2.0; 3.0; 10.0]).unwrap();
+    assert_eq!(prediction, dvector![0.0, 0.0, 1.0, 1.0]);
+}
+
+#[test]
+fn threshold_classifier_predicts_labels_with_scores() {
+    let inputs = dmatrix![0.0; 1.0; 2.0; 3.0; 4.0; 5.0];
+    let outputs = dvector![0.0, 0.0, 0.0, 1.0, 1.0, 1.0];
+    let mut classifier =
+        ThresholdClassifier::new(OlsRegressor::default(), ThresholdMetric::F1).unwrap();
+    classifier.train(inputs, outputs).unwrap();
+
+    let test_inputs = dmatrix![-1.0; 2.0; 3.0; 10.0];
+    let prediction = classifier.predict_with_scores(&test_inputs).unwrap();
+
+    assert_eq!(prediction.labels, classifier.predict(&test_inputs).unwrap());
+    assert_eq!(
+        prediction.scores,
+        classifier.predict_scores(&test_inputs).unwrap()
+    );
+}
+
+#[test]
+fn threshold_classifier_fails_to_predict_when_untrained() {
+    let classifier: ThresholdClassifier<OlsRegressor<f64>, f64> =
+        ThresholdClassifier::new(OlsRegressor::default(), ThresholdMetric::YoudenJ).unwrap();
+
+    assert_eq!(
+        classifier.predict(&dmatrix![1.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
+
+#[test_case(20, false; "without replacement")]
+#[test_case(60, true; "with replacement")]
+fn stratified_resampling_keeps_proportions(n_samples: usize, replace: bool) {
+    // Three strata with 24, 12 and 4 observations.
+    let strata: Vec<usize> = (0..40)
+        .map(|i| match i {
+            0..=23 => 0,
+            24..=35 => 1,
+            _ => 2,
+        })
+        .collect();
+
+    let indices = resample_indices(40, n_samples, Some(&strata), replace, 7).unwrap();
+
+    assert_eq!(indices.len(), n_samples);
+    let counts: Vec<usize> = (0..3)
+        .map(|stratum| indices.iter().filter(|&&i| strata[i] == stratum).count())
+        .collect();
+    assert_eq!(
+        counts,
+        vec![n_samples * 3 / 5, n_samples * 3 / 10, n_samples / 10]
+    );
+    if !replace {
+        let mut distinct = indices.clone();
+        distinct.sort();
+        distinct.dedup();
+        assert_eq!(distinct.len(), n_samples);
+    }
+}
+
+#[test]
+fn stratified_resampling_rounds_by_largest_remainder() {
+    // Shares of 7 samples are 3.5, 2.1 and 1.4, so the first stratum gets the extra sample.
+    let strata = [0, 0, 0, 0, 0, 1, 1, 1, 2, 2];
+
+    let indices = resample_indices(10, 7, Some(&strata), false, 0).unwrap();
+
+    let counts: Vec<usize> = (0..3)
+        .map(|stratum| indices.iter().filter(|&&i| strata[i] == stratum).count())
+        .collect();
+    assert_eq!(counts, vec![4, 2, 1]);
+}
+
+#[test]
+fn resample_selects_matching_rows() {
+    let inputs = DMatrix::from_fn(10, 2, |i, j| (10 * i + j) as f64);
+    let outputs = DVector::from_fn(10, |i, _| i as f64);
+
+    let (sampled_inputs, sampled_outputs) = resample(&inputs, &outputs, 15, None, true, 3).unwrap();
+
+    assert_eq!(sampled_inputs.nrows(), 15);
+    for (row, &output) in sampled_inputs.row_iter().zip(sampled_outputs.iter()) {
+        assert_eq!(row[0], 10.0 * output);
+        assert_eq!(row[1], 10.0 * output + 1.0);
+    }
+    let (repeated, _) = resample(&inputs, &outputs, 15, None, true, 3).unwrap();
+    assert_eq!(repeated, sampled_inputs);
+}
+
+#[test]
+fn resample_fails_with_invalid_data() {
+    assert_eq!(
+        resample_indices(5, 0, None, true, 0).unwrap_err(),
+        SLearningError::InvalidParameters("Number of samples must be at least one.".to_string())
+    );
+    assert_eq!(
+        resample_indices(5, 6, None, false, 0).unwrap_err(),
+        SLearningError::InvalidData(
+            "Cannot draw 6 samples without replacement from 5 observation(s).".to_string()
+        )
+    );
+    assert_eq!(
+        resample_indices(5, 2, Some(&[0, 1]), false, 0).unwrap_err(),
+        SLearningError::InvalidData(
+            "There are 5 observation(s), but 2 strata label(s). These must be equal.".to_string()
+        )
+    );
+    assert_eq!(
+        resample(&dmatrix![1.0; 2.0], &dvector![1.0], 1, None, false, 0).unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has 2 observation(s), but output has 1 observation(s). These must be equal."
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn kfold_splits_into_contiguous_folds() {
+    let folds = KFold::new(3).unwrap().split(10).unwrap();
+
+    let tests: Vec<Vec<usize>> = folds.iter().map(|fold| fold.test.clone()).collect();
+    assert_eq!(tests, vec![vec![0, 1, 2, 3], vec![4, 5, 6], vec![7, 8, 9]]);
+    assert_eq!(folds[1].train, vec![0, 1, 2, 3, 7, 8, 9]);
+}
+
+#[test]
+fn shuffled_kfold_tests_each_observation_once() {
+    let folds = KFold::new(4).unwrap().with_shuffle(5).split(11).unwrap();
+
+    let mut tested: Vec<usize> = folds.iter().flat_map(|fold| fold.test.clone()).collect();
+    assert_ne!(tested, (0..11).collect::<Vec<_>>());
+    tested.sort();
+    assert_eq!(tested, (0..11).collect::<Vec<_>>());
+    for fold in &folds {
+        assert_eq!(fold.train.len() + fold.test.len(), 11);
+        assert!(fold.test.iter().all(|i| !fold.train.contains(i)));
+    }
+}
+
+#[test]
+fn kfold_fails_with_invalid_parameters() {
+    assert_eq!(
+        KFold::new(1).unwrap_err(),
+        SLearningError::InvalidParameters("Number of folds must be at least two.".to_string())
+    );
+    assert_eq!(
+        KFold::new(5).unwrap().split(4).unwrap_err(),
+        SLearningError::InvalidData("Cannot split 4 observation(s) into 5 folds.".to_string())
+    );
+}
+
+fn mean_squared_error(actual: &DVector<f64>, predicted: &DVector<f64>) -> SLearningResult<f64> {
+    Ok((actual - predicted).norm_squared() / actual.len() as f64)
+}
+
+#[test]
+fn cross_validate_works() {
+    let inputs = DMatrix::from_fn(20, 1, |i, _| i as f64);
+    let outputs = DVector::from_fn(20, |i, _| 1.0 + 2.0 * i as f64 + [0.5, -0.5][i % 2]);
+    let folds = KFold::new(5).unwrap().with_shuffle(1);
+
+    let results = cross_validate(
+        OlsRegressor::default,
+        &inputs,
+        &outputs,
+        &folds,
+        mean_squared_error,
+    )
+    .unwrap();
+
+    assert_eq!(results.folds.len(), 5);
+    for fold in &results.folds {
+        let coefficients = fold.model.coefficients.as_ref().unwrap();
+        assert!((coefficients[1] - 2.0).abs() < 0.1);
+        assert_eq!(fold.fold.test.len(), 4);
+    }
+    let mean = results.mean_score();
+    assert!(mean > 0.2 && mean < 0.4);
+    let (lower, upper) = results.confidence_interval(0.9, 1000, 0).unwrap();
+    assert!(lower <= mean && mean <= upper);
+    assert!(lower >= results.scores().min() && upper <= results.scores().max());
+}
+
+#[test_case(0.0, 100; "zero level")]
+#[test_case(1.0, 100; "full level")]
+#[test_case(0.95, 0; "no resamples")]
+fn confidence_interval_fails_with_invalid_parameters(level: f64, n_bootstrap: usize) {
+    let inputs = DMatrix::from_fn(6, 1, |i, _| i as f64);
+    let outputs = DVector::from_fn(6, |i, _| i as f64);
+    let results = cross_validate(
+        OlsRegressor::default,
+        &inputs,
+        &outputs,
+        &KFold::new(3).unwrap(),
+        mean_squared_error,
+    )
+    .unwrap();
+
+    assert!(matches!(
+        results.confidence_interval(level, n_bootstrap, 0),
+        Err(SLearningError::InvalidParameters(_))
+    ));
+}
+
+#[test]
+fn confidence_interval_fails_with_non_finite_scores() {
+    let inputs = DMatrix::from_fn(6, 1, |i, _| i as f64);
+    let outputs = DVector::from_fn(6, |i, _| i as f64);
+    let results = cross_validate(
+        OlsRegressor::default,
+        &inputs,
+        &outputs,
+        &KFold::new(3).unwrap(),
+        |_: &DVector<f64>, _: &DVector<f64>| Ok(f64::NAN),
+    )
+    .unwrap();
+
+    assert_eq!(
+        results.confidence_interval(0.9, 100, 0).unwrap_err(),
+        SLearningError::InvalidData(
+            "The fold scores have a non-finite value for observation 0.".to_string()
+        )
+    );
+}
+
+/// 30 observations of a noisy line.
+fn line_data() -> (DMatrix<f64>, DVector<f64>) {
+    let inputs = DMatrix::from_fn(30, 1, |i, _| i as f64 / 3.0);
+    let outputs = DVector::from_fn(30, |i, _| 2.0 * i as f64 / 3.0 + [0.3, -0.1, -0.2][i % 3]);
+    (inputs, outputs)
+}
+
+#[test]
+fn grid_search_works() {
+    let (inputs, outputs) = line_data();
+
+    let search = GridSearchCv::new(
+        vec![1e4, 0.0, 100.0],
+        |&penalty: &f64| RidgeRegressor::new(penalty, true).unwrap(),
+        mean_squared_error,
+        KFold::new(3).unwrap().with_shuffle(0),
+    )
+    .unwrap()
+    .minimise();
+
+    let result = search.search(&inputs, &outputs).unwrap();
+
+    assert_eq!(result.best_index, 1);
+    assert_eq!(result.mean_scores.argmin().0, 1);
+    assert!(result.mean_scores[0] > result.mean_scores[2]);
+    let coefficients = result.model.coefficients.unwrap();
+    assert!((coefficients[1] - 2.0).abs() < 0.05);
+}
+
+#[test]
+fn nested_cv_works() {
+    let (inputs, outputs) = line_data();
+    let search = GridSearchCv::new(
+        vec![1e4, 0.0, 100.0],
+        |&penalty: &f64| RidgeRegressor::new(penalty, true).unwrap(),
+        mean_squared_error,
+        KFold::new(3).unwrap().with_shuffle(0),
+    )
+    .unwrap()
+    .minimise();
+
+    let results = nested_cv(
+        &search,
+        &KFold::new(5).unwrap().with_shuffle(1),
+        &inputs,
+        &outputs,
+    )
+    .unwrap();
+
+    assert_eq!(results.folds.len(), 5);
+    for fold in &results.folds {
+        assert_eq!(fold.best_index, 1);
+        assert_eq!(fold.fold.test.len(), 6);
+    }
+    let mean = results.mean_score();
+    assert!(mean > 0.0 && mean < 0.1);
+    assert_eq!(results.outer_scores().mean(), mean);
+}
+
+#[test]
+fn grid_search_fails_without_candidates() {
+    let search = GridSearchCv::new(
+        Vec::<f64>::new(),
+        |&penalty: &f64| RidgeRegressor::new(penalty, true).unwrap(),
+        mean_squared_error,
+        KFold::new(3).unwrap(),
+    );
+    assert_eq!(
+        search.err(),
+        Some(SLearningError::InvalidParameters(
+            "There must be at least one candidate.".to_string()
+        ))
+    );
+}
+
+#[test]
+fn paired_t_test_works() {
+    let scores_a = dvector![0.9, 0.85, 0.88, 0.92, 0.87];
+    let scores_b = dvector![0.86, 0.84, 0.85, 0.9, 0.86];
+
+    let test: TTest<f64> = paired_t_test(&scores_a, &scores_b).unwrap();
+
+    assert!((test.statistic - 3.7729688731).abs() < 1e-8);
+    assert_eq!(test.degrees_of_freedom, 4);
+    assert!((test.p_value - 0.0195542127).abs() < 1e-8);
+}
+
+#[test]
+fn paired_t_test_with_two_folds_matches_the_cauchy_distribution() {
+    // With one degree of freedom, the t distribution is the Cauchy distribution.
+    let test: TTest<f64> = paired_t_test(&dvector![3.0, 1.0], &dvector![0.0, 0.0]).unwrap();
+
+    assert!((test.statistic - 2.0).abs() < 1e-12);
+    assert!((test.p_value - (1.0 - 2.0 / std::f64::consts::PI * 2.0_f64.atan())).abs() < 1e-10);
+}
+
+#[test_case(dvector![0.9, 0.8], dvector![0.9] ; "mismatched lengths")]
+#[test_case(dvector![0.9], dvector![0.8] ; "one fold")]
+#[test_case(dvector![1.0, 2.0], dvector![0.0, 1.0] ; "constant differences")]
+fn paired_t_test_rejects_invalid_scores(scores_a: DVector<f64>, scores_b: DVector<f64>) {
+    let result = paired_t_test(&scores_a, &scores_b);
+
+    assert!(matches!(result, Err(SLearningError::InvalidData(_))));
+}
+
+#[test]
+fn wilcoxon_signed_rank_is_exact_without_ties() {
+    let scores_a = dvector![1.0, 2.0, 3.0, 4.0, 0.0, 7.0];
+    let scores_b = dvector![0.0, 0.0, 0.0, 0.0, 5.0, 7.0];
+
+    let test: WilcoxonTest<f64> = wilcoxon_signed_rank(&scores_a, &scores_b).unwrap();
+
+    // The equal pair is dropped, and 10 of the 32 sign patterns have a rank sum of at most 5.
+    assert_eq!(test.statistic, 5.0);
+    assert!((test.p_value - 20.0 / 32.0).abs() < 1e-12);
+}
+
+#[test]
+fn wilcoxon_signed_rank_uses_the_normal_approximation_with_ties() {
+    let scores_a = dvector![1.0, 1.0, 2.0, 0.0];
+    let scores_b = dvector![0.0, 0.0, 0.0, 3.0];
+
+    let test: WilcoxonTest<f64> = wilcoxon_signed_rank(&scores_a, &scores_b).unwrap();
+
+    assert_eq!(test.statistic, 4.0);
+    assert!((test.p_value - 0.7127018567).abs() < 1e-8);
+}
+
+#[test]
+fn wilcoxon_signed_rank_rejects_equal_scores() {
+    let result = wilcoxon_signed_rank(&dvector![0.5, 0.6], &dvector![0.5, 0.6]);
+
+    assert!(matches!(result, Err(SLearningError::InvalidData(_))));
+}
+
+#[test]
+fn paired_t_test_fails_with_non_finite_scores() {
+    let result = paired_t_test(&dvector![0.9, 0.8], &dvector![0.7, f64::NAN]);
+
+    assert_eq!(
+        result.unwrap_err(),
+        SLearningError::InvalidData(
+            "The scores have a non-finite value for observation 1.".to_string()
+        )
+    );
+}
+
+#[test]
+fn five_by_two_cv_test_works() {
+    let scores_a = dmatrix![
+        0.8, 0.82;
+        0.79, 0.81;
+        0.83, 0.8;
+        0.78, 0.8;
+        0.81, 0.83
+    ];
+    let scores_b = dmatrix![
+        0.77, 0.8;
+        0.78, 0.77;
+        0.8, 0.79;
+        0.76, 0.79;
+        0.8, 0.8
+    ];
+
+    let test: TTest<f64> = five_by_two_cv_test(&scores_a, &scores_b).unwrap();
+
+    assert!((test.statistic - 2.1764287503).abs() < 1e-8);
+    assert_eq!(test.degrees_of_freedom, 5);
+    assert!((test.p_value - 0.0814792637).abs() < 1e-8);
+}
+
+#[test]
+fn five_by_two_cv_test_rejects_wrong_shape() {
+    let scores = DMatrix::from_element(10, 1, 0.5);
+
+    let result = five_by_two_cv_test(&scores, &scores);
+
+    assert!(matches!(result, Err(SLearningError::InvalidData(_))));
+}
+
+#[test]
+fn five_by_two_cv_test_fails_with_non_finite_scores() {
+    let scores_a = DMatrix::from_element(5, 2, 0.5);
+    let mut scores_b = scores_a.clone();
+    scores_b[(3, 1)] = f64::INFINITY;
+
+    let result = five_by_two_cv_test(&scores_a, &scores_b);
+
+    assert_eq!(
+        result.unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has a non-finite value for observation 3 and variable 1.".to_string()
+        )
+    );
+}
+
+#[test]
+fn quantile_bins_have_equal_sizes_and_keep_ties_together() {
+    let values = dvector![5.0, 1.0, 3.0, 2.0, 8.0, 3.0, 7.0, 6.0];
+
+    let bins = quantile_bins(&values, 4).unwrap();
+
+    // The sorted values are 1, 2, 3, 3, 5, 6, 7, 8.
+    assert_eq!(bins, vec![2, 0, 1, 0, 3, 1, 3, 2]);
+    let ties = quantile_bins(&dvector![1.0, 1.0, 1.0, 2.0], 2).unwrap();
+    assert_eq!(ties, vec![0, 0, 0, 1]);
+}
+
+#[test]
+fn quantile_bins_fails_with_non_finite_values() {
+    assert_eq!(
+        quantile_bins(&dvector![1.0, f64::NAN], 2).unwrap_err(),
+        SLearningError::InvalidData(
+            "The values have a non-finite value for observation 1.".to_string()
+        )
+    );
+}
+
+#[test]
+fn train_test_split_partitions_observations() {
+    let split = train_test_split(10, 0.3, None, 4).unwrap();
+
+    assert_eq!(split.test.len(), 3);
+    let mut all: Vec<usize> = split.train.iter().chain(&split.test).copied().collect();
+    all.sort();
+    assert_eq!(all, (0..10).collect::<Vec<_>>());
+}
+
+#[test]
+fn train_test_split_stratifies_a_continuous_target() {
+    let outputs = DVector::from_fn(20, |i, _| ((i * 7) % 20) as f64);
+    let bins = quantile_bins(&outputs, 4).unwrap();
+
+    let split = train_test_split(20, 0.25, Some(&bins), 1).unwrap();
+
+    // Each quartile of the target has five observations and gives one to the test set, and the
+    // remaining test observation comes from the first quartile, since all remainders are equal.
+    let mut test_bins: Vec<usize> = split.test.iter().map(|&i| bins[i]).collect();
+    test_bins.sort();
+    assert_eq!(test_bins, vec![0, 0, 1, 2, 3]);
+}
+
+#[test_case(10, 0.0, None ; "zero test size")]
+#[test_case(10, 1.0, None ; "whole test size")]
+#[test_case(10, f64::NAN, None ; "nan test size")]
+#[test_case(1, 0.5, None ; "one observation")]
+#[test_case(3, 0.5, Some(vec![0, 1]) ; "mismatched strata")]
+fn train_test_split_rejects_invalid_arguments(
+    num_obs: usize,
+    test_size: f64,
+    strata: Option<Vec<usize>>,
+) {
+    let result = train_test_split(num_obs, test_size, strata.as_deref(), 0);
+
+    assert!(result.is_err());
+}
@@ -0,0 +1,147 @@
+use nalgebra::{dmatrix, DMatrix};
+
+use slearning::linear_regression::OlsRegressor;
+use slearning::metrics::{hamming_loss, ThresholdMetric};
+use slearning::model_selection::ThresholdClassifier;
+use slearning::multilabel::{ClassifierChain, MultiLabelBinarizer, MultiOutputClassifier};
+use slearning::random::Rng;
+use slearning::SLearningError;
+
+fn thresholded_ols() -> ThresholdClassifier<OlsRegressor<f64>, f64> {
+    ThresholdClassifier::new(OlsRegressor::new(true), ThresholdMetric::F1).unwrap()
+}
+
+/// Two inputs, with one label for each input being positive and a third for both being positive.
+fn multilabel_data(seed: u64) -> (DMatrix<f64>, DMatrix<f64>) {
+    let mut rng = Rng::new(seed);
+    let inputs = DMatrix::from_fn(200, 2, |_, _| rng.standard_normal::<f64>());
+    let outputs = DMatrix::from_fn(200, 3, |i, j| {
+        let positive = |k: usize| inputs[(i, k)] > 0.0;
+        let label = match j {
+            0 | 1 => positive(j),
+            _ => positive(0) && positive(1),
+        };
+        if label {
+            1.0
+        } else {
+            0.0
+        }
+    });
+    (inputs, outputs)
+}
+
+#[test]
+fn binarizer_round_trips() {
+    let label_sets = vec![vec!["b", "a"], vec![], vec!["c", "a"]];
+    let mut binarizer = MultiLabelBinarizer::new();
+
+    let indicators: DMatrix<f64> = binarizer.fit_transform(&label_sets).unwrap();
+
+    assert_eq!(binarizer.classes().unwrap(), &["a", "b", "c"]);
+    assert_eq!(
+        indicators,
+        dmatrix![1.0, 1.0, 0.0; 0.0, 0.0, 0.0; 1.0, 0.0, 1.0]
+    );
+    let expected = vec![vec!["a", "b"], vec![], vec!["a", "c"]];
+    assert_eq!(binarizer.inverse_transform(&indicators).unwrap(), expected);
+}
+
+#[test]
+fn binarizer_fails_with_unseen_label() {
+    let mut binarizer = MultiLabelBinarizer::new();
+    binarizer.fit(&[vec![1, 2]]).unwrap();
+
+    let actual = binarizer.transform::<f64>(&[vec![3]]).unwrap_err();
+
+    let message = "Label 3 was not seen when fitting.";
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn multi_output_classifier_works() {
+    let (inputs, outputs) = multilabel_data(1);
+    let mut classifier = MultiOutputClassifier::new(thresholded_ols);
+
+    classifier.train(inputs.clone(), outputs.clone()).unwrap();
+
+    assert_eq!(classifier.models.len(), 3);
+    let predictions = classifier.predict(&inputs).unwrap();
+    assert_eq!(predictions.shape(), (200, 3));
+    assert!(hamming_loss(&outputs, &predictions).unwrap() < 0.15);
+}
+
+#[test]
+fn classifier_chain_uses_earlier_labels() {
+    let (inputs, outputs) = multilabel_data(2);
+    let mut chain = ClassifierChain::new(thresholded_ols).with_order(vec![2, 0, 1]);
+
+    chain.train(inputs.clone(), outputs.clone()).unwrap();
+
+    // Each classifier has an intercept, the two inputs, then the earlier labels in the chain.
+    let num_coefficients: Vec<usize> = chain
+        .models
+        .iter()
+        .map(|model| model.model.coefficients.as_ref().unwrap().len())
+        .collect();
+    assert_eq!(num_coefficients, vec![3, 4, 5]);
+    let predictions = chain.predict(&inputs).unwrap();
+    assert!(hamming_loss(&outputs, &predictions).unwrap() < 0.15);
+}
+
+#[test]
+fn classifier_chain_fails_with_invalid_order() {
+    let (inputs, outputs) = multilabel_data(3);
+    let mut chain = ClassifierChain::new(thresholded_ols).with_order(vec![0, 0, 1]);
+
+    let actual = chain.train(inputs, outputs).unwrap_err();
+
+    let message = "The chain order must be a permutation of the 3 label columns.";
+    assert_eq!(
+        actual,
+        SLearningError::InvalidParameters(message.to_string())
+    );
+}
+
+#[test]
+fn multi_output_classifier_fails_when_untrained() {
+    let classifier = MultiOutputClassifier::new(thresholded_ols);
+
+    let actual = classifier.predict(&dmatrix![1.0, 2.0]).unwrap_err();
+
+    assert_eq!(actual, SLearningError::UntrainedModel);
+}
+
+#[test]
+fn multilabel_classifiers_fail_with_invalid_inputs() {
+    let (inputs, outputs) = multilabel_data(4);
+    let mut classifier = MultiOutputClassifier::new(thresholded_ols);
+    let mut chain = ClassifierChain::new(thresholded_ols);
+
+    let length_message =
+        "Input has 200 observation(s), but output has 199 observation(s). These must be equal.";
+    let short_outputs = outputs.rows(0, 199).into_owned();
+    assert_eq!(
+        classifier
+            .train(inputs.clone(), short_outputs.clone())
+            .unwrap_err(),
+        SLearningError::InvalidData(length_message.to_string())
+    );
+    assert_eq!(
+        chain.train(inputs.clone(), short_outputs).unwrap_err(),
+        SLearningError::InvalidData(length_message.to_string())
+    );
+
+    classifier.train(inputs.clone(), outputs.clone()).unwrap();
+    chain.train(inputs, outputs).unwrap();
+    let vars_message = SLearningError::InvalidData(
+        "This model was trained with 2 variables, but this input has 3 variables. These must be equal.".to_string(),
+    );
+    assert_eq!(
+        classifier.predict(&dmatrix![1.0, 2.0, 3.0]).unwrap_err(),
+        vars_message
+    );
+    assert_eq!(
+        chain.predict(&dmatrix![1.0, 2.0, 3.0]).unwrap_err(),
+        vars_message
+    );
+}
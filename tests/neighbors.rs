@@ -0,0 +1,207 @@
+use nalgebra::{dmatrix, DMatrix};
+use test_case::test_case;
+
+use slearning::distance::{Cosine, Euclidean, Manhattan, Metric};
+use slearning::neighbors::{LshConfig, LshFamily, LshNeighbors, NearestNeighbors, Neighbors};
+use slearning::random::Rng;
+use slearning::SLearningError;
+
+#[test]
+fn query_works() {
+    let inputs = dmatrix![0.0, 0.0; 1.0, 0.0; 0.0, 2.0; 5.0, 5.0];
+    let index = NearestNeighbors::new(inputs, Euclidean).unwrap();
+
+    let neighbors = index.query(&dmatrix![0.9, 0.1; 4.0, 4.0], 2).unwrap();
+
+    assert_eq!(neighbors.indices, vec![vec![1, 0], vec![3, 2]]);
+    let expected_distances = dmatrix![
+        0.02f64.sqrt(), 0.82f64.sqrt();
+        2.0f64.sqrt(), 20.0f64.sqrt()
+    ];
+    assert!((neighbors.distances - expected_distances).amax() < 1e-12);
+}
+
+#[test]
+fn query_indexed_excludes_self() {
+    let inputs = dmatrix![0.0; 1.0; 3.0; 3.0];
+    let index = NearestNeighbors::new(inputs, Manhattan).unwrap();
+
+    let neighbors = index.query_indexed(1).unwrap();
+
+    // Duplicates are still neighbours of each other.
+    assert_eq!(neighbors.indices, vec![vec![1], vec![0], vec![3], vec![2]]);
+    assert_eq!(neighbors.distances, dmatrix![1.0; 1.0; 0.0; 0.0]);
+}
+
+#[test_case(0, "Number of neighbours must be between one and 3, but is 0."; "zero")]
+#[test_case(4, "Number of neighbours must be between one and 3, but is 4."; "too many")]
+fn query_fails_with_invalid_k(k: usize, message: &str) {
+    let index = NearestNeighbors::new(dmatrix![0.0; 1.0; 2.0], Euclidean).unwrap();
+
+    let actual = index.query(&dmatrix![0.5], k).unwrap_err();
+    assert_eq!(actual, SLearningError::InvalidParameters(message.into()));
+}
+
+#[test]
+fn nearest_neighbors_fails_with_invalid_inputs() {
+    assert_eq!(
+        NearestNeighbors::new(DMatrix::<f64>::zeros(0, 2), Euclidean).unwrap_err(),
+        SLearningError::InvalidData("Cannot build an index with zero observations.".into())
+    );
+    assert_eq!(
+        NearestNeighbors::new(dmatrix![0.0, f64::NAN], Euclidean).unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has a non-finite value for observation 0 and variable 1.".into()
+        )
+    );
+    let index = NearestNeighbors::new(dmatrix![0.0, 1.0], Euclidean).unwrap();
+    assert_eq!(
+        index.query(&dmatrix![0.5], 1).unwrap_err(),
+        SLearningError::InvalidData(
+            "The first input has 2 variables, but the second input has 1 variables. These must be equal.".into()
+        )
+    );
+    assert_eq!(
+        index.query(&dmatrix![0.5, f64::INFINITY], 1).unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has a non-finite value for observation 0 and variable 1.".into()
+        )
+    );
+}
+
+/// 300 observations in 20 dimensions, in 30 tight clusters around random centres.
+fn clustered() -> DMatrix<f64> {
+    let mut rng = Rng::new(8);
+    let centres = DMatrix::from_fn(30, 20, |_, _| 10.0 * rng.standard_normal::<f64>());
+    DMatrix::from_fn(300, 20, |i, j| {
+        centres[(i % 30, j)] + 0.3 * rng.standard_normal::<f64>()
+    })
+}
+
+/// The fraction of the exact neighbours that are found.
+fn recall(approximate: &Neighbors<f64>, exact: &Neighbors<f64>) -> f64 {
+    let found: usize = approximate
+        .indices
+        .iter()
+        .zip(&exact.indices)
+        .map(|(approximate, exact)| exact.iter().filter(|j| approximate.contains(j)).count())
+        .sum();
+    let total: usize = exact.indices.iter().map(|indices| indices.len()).sum();
+    found as f64 / total as f64
+}
+
+#[test_case(LshFamily::PStable { bucket_width: 4.0 }, Euclidean; "p-stable")]
+#[test_case(LshFamily::RandomHyperplane, Cosine; "random hyperplane")]
+fn lsh_neighbors_finds_most_neighbours<M: Metric<f64> + Copy>(family: LshFamily<f64>, metric: M) {
+    let inputs = clustered();
+    let config = LshConfig::new(family).unwrap();
+    let index = LshNeighbors::new(inputs.clone(), metric, config).unwrap();
+    let exact = NearestNeighbors::new(inputs, metric).unwrap();
+
+    let approximate = index.query_indexed(5).unwrap();
+
+    let exact = exact.query_indexed(5).unwrap();
+    let recall = recall(&approximate, &exact);
+    assert!(recall > 0.9, "recall {recall}");
+    // The distances of the neighbours found are exact, and in increasing order.
+    for (i, indices) in approximate.indices.iter().enumerate() {
+        for (column, &j) in indices.iter().enumerate() {
+            let distance = metric.distance(index.inputs().row(i), index.inputs().row(j));
+            assert_eq!(approximate.distances[(i, column)], distance);
+        }
+        for column in 1..indices.len() {
+            assert!(approximate.distances[(i, column - 1)] <= approximate.distances[(i, column)]);
+        }
+    }
+}
+
+#[test]
+fn lsh_neighbors_falls_back_to_exact_search() {
+    let inputs = dmatrix![0.0, 0.0; 1.0, 0.0; 0.0, 2.0; 5.0, 5.0];
+    // Buckets this narrow almost never hold two observations.
+    let config = LshConfig::new(LshFamily::PStable { bucket_width: 1e-6 })
+        .unwrap()
+        .with_tables(2, 8)
+        .unwrap();
+    let index = LshNeighbors::new(inputs.clone(), Euclidean, config).unwrap();
+    let exact = NearestNeighbors::new(inputs, Euclidean).unwrap();
+
+    let queries = dmatrix![0.9, 0.1; 4.0, 4.0];
+    let pairs = [
+        (index.query(&queries, 2), exact.query(&queries, 2)),
+        (index.query_indexed(3), exact.query_indexed(3)),
+    ];
+    for (approximate, exact) in pairs {
+        let (approximate, exact) = (approximate.unwrap(), exact.unwrap());
+        assert_eq!(approximate.indices, exact.indices);
+        assert!((approximate.distances - exact.distances).amax() < 1e-12);
+    }
+}
+
+#[test]
+fn lsh_neighbors_depends_on_seed() {
+    let inputs = clustered();
+    let query = |seed| {
+        let config = LshConfig::new(LshFamily::RandomHyperplane)
+            .unwrap()
+            .with_tables(1, 12)
+            .unwrap()
+            .with_seed(seed);
+        let index = LshNeighbors::new(inputs.clone(), Cosine, config).unwrap();
+        index.query_indexed(3).unwrap()
+    };
+
+    assert_eq!(query(1), query(1));
+    assert_ne!(query(1), query(2));
+}
+
+#[test_case(LshFamily::PStable { bucket_width: 0.0 }, 10, 4, "Bucket width must be finite and greater than zero."; "zero width")]
+#[test_case(LshFamily::RandomHyperplane, 0, 4, "Number of tables must be at least one."; "zero tables")]
+#[test_case(LshFamily::RandomHyperplane, 10, 0, "Number of hashes per table must be at least one."; "zero hashes")]
+fn lsh_config_fails_with_invalid_parameters(
+    family: LshFamily<f64>,
+    n_tables: usize,
+    n_hashes: usize,
+    message: &str,
+) {
+    let actual = LshConfig::new(family).and_then(|config| config.with_tables(n_tables, n_hashes));
+
+    assert_eq!(
+        actual.unwrap_err(),
+        SLearningError::InvalidParameters(message.into())
+    );
+}
+
+#[test]
+fn lsh_neighbors_fails_with_invalid_inputs() {
+    let config = LshConfig::new(LshFamily::RandomHyperplane).unwrap();
+    assert_eq!(
+        LshNeighbors::new(DMatrix::<f64>::zeros(0, 2), Cosine, config).unwrap_err(),
+        SLearningError::InvalidData("Cannot build an index with zero observations.".into())
+    );
+    assert_eq!(
+        LshNeighbors::new(dmatrix![0.0, f64::NAN], Cosine, config).unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has a non-finite value for observation 0 and variable 1.".into()
+        )
+    );
+    let index = LshNeighbors::new(dmatrix![0.0, 1.0; 1.0, 0.0], Cosine, config).unwrap();
+    assert_eq!(
+        index.query(&dmatrix![0.5, f64::NAN], 1).unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has a non-finite value for observation 0 and variable 1.".into()
+        )
+    );
+    assert_eq!(
+        index.query(&dmatrix![0.5], 1).unwrap_err(),
+        SLearningError::InvalidData(
+            "The first input has 2 variables, but the second input has 1 variables. These must be equal.".into()
+        )
+    );
+    assert_eq!(
+        index.query_indexed(2).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Number of neighbours must be between one and 1, but is 2.".into()
+        )
+    );
+}
@@ -0,0 +1,207 @@
+use std::ops::ControlFlow;
+
+use nalgebra::{dvector, DVector};
+use test_case::test_case;
+
+use slearning::optim::{
+    Adam, ConvergenceConfig, GradientDescent, Lbfgs, LearningRate, Objective, Solver,
+};
+use slearning::SLearningError;
+
+/// `(x - 1)^2 + 10 (y + 2)^2`, with minimum at (1, -2).
+struct Quadratic;
+
+impl Objective<f64> for Quadratic {
+    fn value(&self, params: &DVector<f64>) -> f64 {
+        (params[0] - 1.0).powi(2) + 10.0 * (params[1] + 2.0).powi(2)
+    }
+
+    fn gradient(&self, params: &DVector<f64>) -> DVector<f64> {
+        dvector![2.0 * (params[0] - 1.0), 20.0 * (params[1] + 2.0)]
+    }
+}
+
+/// The Rosenbrock function, with minimum at (1, 1).
+struct Rosenbrock;
+
+impl Objective<f64> for Rosenbrock {
+    fn value(&self, params: &DVector<f64>) -> f64 {
+        let (x, y) = (params[0], params[1]);
+        (1.0 - x).powi(2) + 100.0 * (y - x * x).powi(2)
+    }
+
+    fn gradient(&self, params: &DVector<f64>) -> DVector<f64> {
+        let (x, y) = (params[0], params[1]);
+        dvector![
+            -2.0 * (1.0 - x) - 400.0 * x * (y - x * x),
+            200.0 * (y - x * x)
+        ]
+    }
+}
+
+fn assert_close(actual: &DVector<f64>, expected: &DVector<f64>, tolerance: f64) {
+    assert!(
+        (actual - expected).amax() < tolerance,
+        "{} is not close to {}",
+        actual,
+        expected
+    );
+}
+
+#[test]
+fn gradient_descent_works() {
+    let solver = GradientDescent::new(ConvergenceConfig::new(1000, 1e-8).unwrap());
+    let minimum = solver.minimize(&Quadratic, dvector![0.0, 0.0]).unwrap();
+
+    assert!(minimum.converged);
+    assert_close(&minimum.params, &dvector![1.0, -2.0], 1e-6);
+    assert!(minimum.value < 1e-12);
+}
+
+#[test]
+fn gradient_descent_with_learning_rate_works() {
+    let solver = GradientDescent::default()
+        .with_learning_rate(LearningRate::Constant(0.05))
+        .unwrap();
+    let minimum = solver.minimize(&Quadratic, dvector![0.0, 0.0]).unwrap();
+
+    assert!(minimum.converged);
+    assert_close(&minimum.params, &dvector![1.0, -2.0], 1e-5);
+}
+
+#[test]
+fn lbfgs_works() {
+    let solver = Lbfgs::default();
+
+    let minimum = solver.minimize(&Quadratic, dvector![0.0, 0.0]).unwrap();
+    assert!(minimum.converged);
+    assert_close(&minimum.params, &dvector![1.0, -2.0], 1e-6);
+
+    let minimum = solver.minimize(&Rosenbrock, dvector![-1.2, 1.0]).unwrap();
+    assert!(minimum.converged);
+    assert!(minimum.iterations < 100);
+    assert_close(&minimum.params, &dvector![1.0, 1.0], 1e-5);
+}
+
+#[test]
+fn adam_works() {
+    let convergence = ConvergenceConfig::new(5000, 1e-6).unwrap();
+    let solver = Adam::new(LearningRate::Constant(0.1), convergence).unwrap();
+    let minimum = solver.minimize(&Quadratic, dvector![0.0, 0.0]).unwrap();
+
+    assert!(minimum.converged);
+    assert_close(&minimum.params, &dvector![1.0, -2.0], 1e-4);
+}
+
+#[test]
+fn solver_stops_at_max_iter() {
+    let solver = GradientDescent::new(ConvergenceConfig::new(3, 1e-12).unwrap());
+    let minimum = solver.minimize(&Rosenbrock, dvector![-1.2, 1.0]).unwrap();
+
+    assert!(!minimum.converged);
+    assert_eq!(minimum.iterations, 3);
+}
+
+#[test]
+fn solver_stops_when_out_of_patience() {
+    // A tiny learning rate means the objective barely improves on each iteration.
+    let convergence = ConvergenceConfig::new(1000, 1e-3)
+        .unwrap()
+        .with_patience(5)
+        .unwrap();
+    let solver = GradientDescent::new(convergence)
+        .with_learning_rate(LearningRate::Constant(1e-7))
+        .unwrap();
+    let minimum = solver.minimize(&Quadratic, dvector![0.0, 0.0]).unwrap();
+
+    assert!(minimum.converged);
+    assert_eq!(minimum.iterations, 5);
+}
+
+#[test]
+fn solver_records_history() {
+    let solver = Lbfgs::default();
+    let minimum = solver.minimize(&Rosenbrock, dvector![-1.2, 1.0]).unwrap();
+
+    assert_eq!(minimum.history.len(), minimum.iterations + 1);
+    assert!((minimum.history[0] - 24.2).abs() < 1e-12);
+    assert_eq!(*minimum.history.last().unwrap(), minimum.value);
+    // The line search only accepts steps that decrease the objective.
+    assert!(minimum.history.windows(2).all(|pair| pair[1] < pair[0]));
+}
+
+#[test]
+fn callback_can_stop_solver() {
+    let solver = GradientDescent::new(ConvergenceConfig::new(1000, 1e-12).unwrap());
+    let mut seen = Vec::new();
+    let minimum = solver
+        .minimize_with_callback(&Rosenbrock, dvector![-1.2, 1.0], |progress| {
+            seen.push(progress.iteration);
+            if progress.iteration == 4 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .unwrap();
+
+    assert_eq!(seen, vec![1, 2, 3, 4]);
+    assert!(!minimum.converged);
+    assert_eq!(minimum.iterations, 4);
+    assert_eq!(minimum.history.len(), 5);
+}
+
+#[test_case(LearningRate::Constant(0.5), [0.5, 0.5, 0.5]; "constant")]
+#[test_case(LearningRate::InverseScaling { initial: 1.0, power: 1.0 }, [1.0, 0.5, 1.0 / 3.0]; "inverse scaling")]
+#[test_case(LearningRate::ExponentialDecay { initial: 2.0, decay: 0.5 }, [2.0, 1.0, 0.5]; "exponential decay")]
+#[test_case(LearningRate::Cosine { initial: 1.0, minimum: 0.0, period: 2 }, [1.0, 0.5, 1.0]; "cosine")]
+fn learning_rate_schedules_work(learning_rate: LearningRate<f64>, expected: [f64; 3]) {
+    learning_rate.validate().unwrap();
+    for (iteration, expected_rate) in expected.into_iter().enumerate() {
+        assert!((learning_rate.rate(iteration) - expected_rate).abs() < 1e-12);
+    }
+}
+
+#[test_case(LearningRate::Constant(0.0), "Learning rate must be finite and greater than zero."; "zero rate")]
+#[test_case(LearningRate::InverseScaling { initial: 1.0, power: -1.0 }, "Power cannot be less than zero."; "negative power")]
+#[test_case(LearningRate::ExponentialDecay { initial: 1.0, decay: 1.5 }, "Decay must be greater than zero and at most one."; "large decay")]
+#[test_case(LearningRate::Cosine { initial: 1.0, minimum: 0.0, period: 0 }, "Period must be at least one."; "zero period")]
+fn learning_rate_fails_with_invalid_parameters(learning_rate: LearningRate<f64>, message: &str) {
+    let expected = SLearningError::InvalidParameters(message.to_string());
+    assert_eq!(learning_rate.validate().unwrap_err(), expected);
+}
+
+#[test]
+fn solvers_fail_with_invalid_parameters() {
+    assert_eq!(
+        ConvergenceConfig::new(0, 1e-6).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Maximum number of iterations must be at least one.".into()
+        )
+    );
+    for tol in [-1.0, f64::NAN] {
+        assert_eq!(
+            ConvergenceConfig::new(100, tol).unwrap_err(),
+            SLearningError::InvalidParameters(
+                "Tolerance must be finite and cannot be less than zero.".into()
+            )
+        );
+    }
+    assert_eq!(
+        ConvergenceConfig::new(100, 1e-6)
+            .unwrap()
+            .with_patience(0)
+            .unwrap_err(),
+        SLearningError::InvalidParameters("Patience must be at least one.".into())
+    );
+    assert_eq!(
+        Lbfgs::<f64>::new(0, ConvergenceConfig::default()).unwrap_err(),
+        SLearningError::InvalidParameters("Memory must be at least one.".into())
+    );
+    assert_eq!(
+        Adam::new(LearningRate::Constant(0.0), ConvergenceConfig::default()).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Learning rate must be finite and greater than zero.".into()
+        )
+    );
+}
@@ -0,0 +1,80 @@
+use nalgebra::{dmatrix, dvector, DMatrix, DVector};
+use test_case::test_case;
+
+use slearning::ordinal_regression::OrdinalRegressor;
+use slearning::random::Rng;
+use slearning::{SLearningError, SupervisedModel};
+
+/// Classes from a latent variable `1.5 x_1 - x_2` plus logistic noise, cut at -1, 0.5 and 2.
+fn latent_classes(num_obs: usize, seed: u64) -> (DMatrix<f64>, DVector<f64>) {
+    let mut rng = Rng::new(seed);
+    let inputs = DMatrix::from_fn(num_obs, 2, |_, _| rng.standard_normal::<f64>());
+    let outputs = DVector::from_fn(num_obs, |i, _| {
+        let u: f64 = rng.uniform::<f64>().clamp(1e-12, 1.0 - 1e-12);
+        let latent = 1.5 * inputs[(i, 0)] - inputs[(i, 1)] + (u / (1.0 - u)).ln();
+        [-1.0, 0.5, 2.0].iter().filter(|&&cut| latent > cut).count() as f64
+    });
+    (inputs, outputs)
+}
+
+#[test]
+fn ordinal_regressor_recovers_parameters() {
+    let (inputs, outputs) = latent_classes(2000, 1);
+    let mut model = OrdinalRegressor::new();
+
+    model.train(inputs.clone(), outputs.clone()).unwrap();
+
+    let coefficients = model.coefficients.as_ref().unwrap();
+    assert!((coefficients - dvector![1.5, -1.0]).amax() < 0.2);
+    let thresholds = model.thresholds.as_ref().unwrap();
+    assert!((thresholds - dvector![-1.0, 0.5, 2.0]).amax() < 0.25);
+
+    let probabilities = model.predict_proba(&inputs).unwrap();
+    assert_eq!(probabilities.ncols(), 4);
+    assert!(probabilities
+        .row_iter()
+        .all(|row| (row.sum() - 1.0).abs() < 1e-12));
+    let predictions = model.predict(&inputs).unwrap();
+    let accuracy = predictions
+        .iter()
+        .zip(outputs.iter())
+        .filter(|(p, o)| p == o)
+        .count() as f64
+        / outputs.len() as f64;
+    assert!(accuracy > 0.5);
+}
+
+#[test]
+fn ordinal_regressor_predicts_higher_classes_for_larger_predictors() {
+    let mut model = OrdinalRegressor::<f64>::new();
+    model.coefficients = Some(dvector![1.0]);
+    model.thresholds = Some(dvector![-2.0, 2.0]);
+
+    let predictions = model.predict(&dmatrix![-5.0; 0.0; 5.0]).unwrap();
+
+    assert_eq!(predictions, dvector![0.0, 1.0, 2.0]);
+}
+
+#[test_case(dvector![0.0, 1.5, 2.0], "Ordinal labels must be non-negative integers."; "non-integer")]
+#[test_case(dvector![0.0, -1.0, 2.0], "Ordinal labels must be non-negative integers."; "negative")]
+#[test_case(dvector![0.0, 0.0, 0.0], "There must be at least two classes."; "one class")]
+#[test_case(dvector![0.0, 2.0, 2.0], "Class 1 has no observations. Classes must be numbered from zero without gaps."; "gap")]
+#[test_case(dvector![0.0, 1.0, 3.0], "Ordinal labels must be less than the number of observations (3)."; "large label")]
+#[test_case(dvector![0.0, 1.0, f64::INFINITY], "The ordinal labels have a non-finite value for observation 2."; "infinite label")]
+fn ordinal_regressor_fails_with_invalid_labels(outputs: DVector<f64>, message: &str) {
+    let mut model = OrdinalRegressor::new();
+
+    let actual = model.train(dmatrix![1.0; 2.0; 3.0], outputs).unwrap_err();
+
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn ordinal_regressor_fails_when_untrained() {
+    let model = OrdinalRegressor::<f64>::new();
+
+    assert_eq!(
+        model.predict(&dmatrix![1.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
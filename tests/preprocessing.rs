@@ -0,0 +1,250 @@
+use nalgebra::{dmatrix, dvector, DMatrix, DVector};
+use test_case::test_case;
+
+use slearning::preprocessing::{
+    DecisionTreeDiscretizer, FeatureUnion, FunctionTransformer, WoeEncoder,
+};
+use slearning::random_projection::{GaussianRandomProjection, ProjectionSize};
+use slearning::timeseries::LagFeatures;
+use slearning::{SLearningError, Transformer};
+
+#[test]
+fn function_transformer_applies_and_inverts_function() {
+    let inputs = dmatrix![0.0, 1.0; 3.0, 7.0];
+    let mut transformer = FunctionTransformer::elementwise(f64::ln_1p)
+        .with_inverse(|transformed: &DMatrix<f64>| transformed.map(f64::exp_m1));
+
+    let transformed = transformer.fit_transform(&inputs).unwrap();
+    let restored = transformer.inverse_transform(&transformed).unwrap();
+
+    assert!((transformed[(1, 0)] - 4.0_f64.ln()).abs() < 1e-12);
+    assert!((restored - inputs).amax() < 1e-12);
+}
+
+#[test]
+fn function_transformer_can_change_the_number_of_variables() {
+    let inputs = dmatrix![1.0, 2.0; 3.0, 4.0];
+    let mut transformer = FunctionTransformer::new(|inputs: &DMatrix<f64>| {
+        DMatrix::from_fn(inputs.nrows(), 1, |i, _| inputs.row(i).sum())
+    });
+
+    let transformed = transformer.fit_transform(&inputs).unwrap();
+
+    assert_eq!(transformed, dmatrix![3.0; 7.0]);
+}
+
+#[test]
+fn function_transformer_without_inverse_cannot_invert() {
+    let mut transformer = FunctionTransformer::elementwise(|x: f64| x.clamp(0.0, 1.0));
+    transformer.fit(&dmatrix![0.5]).unwrap();
+
+    let result = transformer.inverse_transform(&dmatrix![0.5]);
+
+    assert!(matches!(result, Err(SLearningError::InvalidParameters(_))));
+}
+
+#[test_case(dmatrix![1.0, 2.0] ; "wrong number of variables")]
+#[test_case(dmatrix![1.0; 2.0] ; "function drops observations")]
+fn function_transformer_rejects_invalid_data(inputs: DMatrix<f64>) {
+    let mut transformer =
+        FunctionTransformer::new(|inputs: &DMatrix<f64>| inputs.rows(0, 1).into());
+    transformer.fit(&dmatrix![0.0; 0.0]).unwrap();
+
+    let result = transformer.transform(&inputs);
+
+    assert!(matches!(result, Err(SLearningError::InvalidData(_))));
+}
+
+#[test]
+fn function_transformer_must_be_fitted() {
+    let transformer = FunctionTransformer::elementwise(|x: f64| 2.0 * x);
+
+    let result = transformer.transform(&dmatrix![1.0]);
+
+    assert!(matches!(result, Err(SLearningError::UntrainedModel)));
+}
+
+#[test]
+fn feature_union_concatenates_outputs() {
+    let inputs = dmatrix![1.0, 2.0; 3.0, 4.0; 5.0, 6.0];
+    let mut union = FeatureUnion::new(vec![
+        Box::new(FunctionTransformer::elementwise(|x: f64| x)),
+        Box::new(FunctionTransformer::elementwise(|x: f64| x * x)),
+        Box::new(GaussianRandomProjection::new(ProjectionSize::Components(1)).unwrap()),
+    ])
+    .unwrap();
+
+    let transformed = union.fit_transform(&inputs).unwrap();
+
+    assert_eq!(transformed.shape(), (3, 5));
+    assert_eq!(
+        transformed.columns(0, 4),
+        dmatrix![1.0, 2.0, 1.0, 4.0; 3.0, 4.0, 9.0, 16.0; 5.0, 6.0, 25.0, 36.0]
+    );
+    assert_eq!(union.transform(&inputs).unwrap(), transformed);
+}
+
+#[test]
+fn feature_union_rejects_outputs_with_different_observations() {
+    let mut union = FeatureUnion::new(vec![
+        Box::new(FunctionTransformer::elementwise(|x: f64| x)),
+        Box::new(LagFeatures::new(vec![2]).unwrap()),
+    ])
+    .unwrap();
+
+    let result = union.fit_transform(&dmatrix![1.0; 2.0; 3.0]);
+
+    assert!(matches!(result, Err(SLearningError::InvalidData(_))));
+}
+
+#[test]
+fn feature_union_needs_a_transformer() {
+    let result = FeatureUnion::<f64>::new(vec![]);
+
+    assert!(matches!(result, Err(SLearningError::InvalidParameters(_))));
+}
+
+fn credit_data() -> (DMatrix<f64>, DVector<f64>) {
+    let inputs = DMatrix::from_fn(8, 1, |i, _| (i + 1) as f64);
+    let outputs = dvector![0.0, 1.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0];
+    (inputs, outputs)
+}
+
+#[test]
+fn decision_tree_discretizer_splits_on_the_target() {
+    let inputs = DMatrix::from_fn(8, 2, |i, j| if j == 0 { i as f64 } else { 1.0 });
+    let outputs = dvector![0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0];
+    let mut discretizer = DecisionTreeDiscretizer::new(3).unwrap();
+
+    discretizer.fit(&inputs, &outputs).unwrap();
+
+    // One split separates the classes, and a constant variable cannot be split.
+    assert_eq!(discretizer.thresholds, Some(vec![vec![3.5], vec![]]));
+    let bins = discretizer
+        .transform(&dmatrix![-1.0, 5.0; 3.5, 1.0])
+        .unwrap();
+    assert_eq!(bins, dmatrix![0.0, 0.0; 1.0, 0.0]);
+}
+
+#[test]
+fn decision_tree_discretizer_merges_bins_to_be_monotone() {
+    let (inputs, outputs) = credit_data();
+    let mut discretizer = DecisionTreeDiscretizer::new(2).unwrap();
+    let mut monotone = DecisionTreeDiscretizer::new(2).unwrap().with_monotone();
+
+    discretizer.fit(&inputs, &outputs).unwrap();
+    monotone.fit(&inputs, &outputs).unwrap();
+
+    // The bins have mean targets 0.5, 0, 1 and 0.5, so the first two and the last two are merged.
+    assert_eq!(discretizer.thresholds, Some(vec![vec![2.5, 4.5, 6.5]]));
+    assert_eq!(monotone.thresholds, Some(vec![vec![4.5]]));
+}
+
+#[test]
+fn decision_tree_discretizer_respects_min_samples_leaf() {
+    let (inputs, outputs) = credit_data();
+    let mut discretizer = DecisionTreeDiscretizer::new(2)
+        .unwrap()
+        .with_min_samples_leaf(3)
+        .unwrap();
+
+    let bins = discretizer.fit_transform(&inputs, &outputs).unwrap();
+
+    for bin in 0..=discretizer.thresholds.as_ref().unwrap()[0].len() {
+        assert!(bins.iter().filter(|&&b| b == bin as f64).count() >= 3);
+    }
+}
+
+#[test]
+fn decision_tree_discretizer_rejects_invalid_parameters() {
+    assert!(matches!(
+        DecisionTreeDiscretizer::<f64>::new(0),
+        Err(SLearningError::InvalidParameters(_))
+    ));
+    assert!(matches!(
+        DecisionTreeDiscretizer::<f64>::new(1)
+            .unwrap()
+            .with_min_samples_leaf(0),
+        Err(SLearningError::InvalidParameters(_))
+    ));
+}
+
+#[test]
+fn decision_tree_discretizer_rejects_non_finite_data() {
+    let mut discretizer = DecisionTreeDiscretizer::new(2).unwrap();
+    assert_eq!(
+        discretizer
+            .fit(&dmatrix![0.0; f64::NAN], &dvector![0.0, 1.0])
+            .unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has a non-finite value for observation 1 and variable 0.".to_string()
+        )
+    );
+    assert_eq!(
+        discretizer
+            .fit(&dmatrix![0.0; 1.0], &dvector![0.0, f64::NAN])
+            .unwrap_err(),
+        SLearningError::InvalidData(
+            "The outputs have a non-finite value for observation 1.".to_string()
+        )
+    );
+}
+
+#[test]
+fn woe_encoder_works() {
+    let inputs = dmatrix![0.0; 1.0; 0.0; 1.0; 0.0; 1.0; 1.0];
+    let outputs = dvector![1.0, 0.0, 1.0, 0.0, 0.0, 1.0, 0.0];
+    let mut encoder = WoeEncoder::default();
+
+    let encoded = encoder.fit_transform(&inputs, &outputs).unwrap();
+
+    // Category 0 has two of the three positives and one of the four negatives, and category 1 has
+    // the rest, with half an observation of each class added to each category.
+    let woe_0 = f64::ln((2.5 / 4.0) / (1.5 / 5.0));
+    let woe_1 = f64::ln((1.5 / 4.0) / (3.5 / 5.0));
+    assert!((encoded[(0, 0)] - woe_0).abs() < 1e-12);
+    assert!((encoded[(1, 0)] - woe_1).abs() < 1e-12);
+    let information_value = encoder.information_values.unwrap()[0];
+    assert!((information_value - 0.4413901323).abs() < 1e-9);
+}
+
+#[test]
+fn woe_encoder_encodes_unseen_categories_as_zero() {
+    let mut encoder = WoeEncoder::default();
+    encoder
+        .fit(&dmatrix![0.0; 1.0; 1.0], &dvector![1.0, 0.0, 1.0])
+        .unwrap();
+
+    let encoded = encoder.transform(&dmatrix![2.0; -1.0]).unwrap();
+
+    assert_eq!(encoded, dmatrix![0.0; 0.0]);
+}
+
+#[test_case(dvector![0.0, 1.0, 2.0] ; "non-binary labels")]
+#[test_case(dvector![1.0, 1.0, 1.0] ; "one class")]
+fn woe_encoder_rejects_invalid_labels(outputs: DVector<f64>) {
+    let mut encoder = WoeEncoder::default();
+
+    let result = encoder.fit(&dmatrix![0.0; 1.0; 1.0], &outputs);
+
+    assert!(matches!(result, Err(SLearningError::InvalidData(_))));
+}
+
+#[test]
+fn woe_encoder_rejects_non_finite_categories() {
+    let mut encoder = WoeEncoder::default();
+
+    let actual = encoder
+        .fit(&dmatrix![0.0; f64::NAN; 1.0], &dvector![1.0, 0.0, 0.0])
+        .unwrap_err();
+
+    let message = "Input has a non-finite value for observation 1 and variable 0.";
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn woe_encoder_needs_positive_smoothing() {
+    let result = WoeEncoder::new(0.0);
+
+    assert!(matches!(result, Err(SLearningError::InvalidParameters(_))));
+}
@@ -0,0 +1,108 @@
+use test_case::test_case;
+
+use slearning::random::Rng;
+
+// The stream for each seed is part of the crate's reproducibility guarantee, so must not change.
+#[test]
+fn rng_is_reproducible() {
+    let mut rng = Rng::new(42);
+    let values: Vec<u64> = (0..3).map(|_| rng.next_u64()).collect();
+
+    assert_eq!(
+        values,
+        vec![
+            1546998764402558742,
+            6990951692964543102,
+            12544586762248559009
+        ]
+    );
+}
+
+#[test]
+fn rng_depends_on_seed() {
+    let mut first = Rng::new(1);
+    let mut second = Rng::new(2);
+
+    assert_ne!(first.next_u64(), second.next_u64());
+}
+
+#[test]
+fn forked_rng_is_independent() {
+    let mut rng = Rng::new(7);
+    let mut fork = rng.fork();
+
+    assert_ne!(rng.next_u64(), fork.next_u64());
+}
+
+#[test]
+fn uniform_works() {
+    let mut rng = Rng::new(0);
+    let values: Vec<f64> = (0..10_000).map(|_| rng.uniform()).collect();
+
+    assert!(values.iter().all(|&x| (0.0..1.0).contains(&x)));
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    assert!((mean - 0.5).abs() < 0.01);
+}
+
+#[test]
+fn standard_normal_works() {
+    let mut rng = Rng::new(0);
+    let values: Vec<f64> = (0..10_000).map(|_| rng.standard_normal()).collect();
+
+    let mean = values.iter().sum::<f64>() / values.len() as f64;
+    let var = values.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / values.len() as f64;
+    assert!(mean.abs() < 0.05);
+    assert!((var - 1.0).abs() < 0.05);
+}
+
+#[test_case(1; "one")]
+#[test_case(3; "three")]
+#[test_case(10; "ten")]
+fn below_works(upper: usize) {
+    let mut rng = Rng::new(3);
+    let mut seen = vec![false; upper];
+    for _ in 0..1000 {
+        let value = rng.below(upper);
+        assert!(value < upper);
+        seen[value] = true;
+    }
+
+    assert!(seen.into_iter().all(|x| x));
+}
+
+#[test]
+fn permutation_works() {
+    let mut rng = Rng::new(5);
+    let mut permutation = rng.permutation(20);
+
+    assert_ne!(permutation, (0..20).collect::<Vec<_>>());
+    permutation.sort();
+    assert_eq!(permutation, (0..20).collect::<Vec<_>>());
+}
+
+#[test_case(10, 4, 4; "fewer than n")]
+#[test_case(5, 8, 5; "more than n")]
+fn sample_indices_works(n: usize, k: usize, expected_len: usize) {
+    let mut rng = Rng::new(11);
+    let mut sample = rng.sample_indices(n, k);
+
+    assert_eq!(sample.len(), expected_len);
+    sample.sort();
+    sample.dedup();
+    assert_eq!(sample.len(), expected_len);
+    assert!(sample.iter().all(|&i| i < n));
+}
+
+#[test]
+fn bootstrap_indices_works() {
+    let mut rng = Rng::new(13);
+    let sample = rng.bootstrap_indices(100);
+
+    assert_eq!(sample.len(), 100);
+    assert!(sample.iter().all(|&i| i < 100));
+    // Sampling with replacement almost surely repeats some indices.
+    let mut unique = sample.clone();
+    unique.sort();
+    unique.dedup();
+    assert!(unique.len() < 100);
+}
@@ -0,0 +1,174 @@
+use nalgebra::{dmatrix, DMatrix};
+use test_case::test_case;
+
+use slearning::random::Rng;
+use slearning::random_projection::{
+    johnson_lindenstrauss_min_dim, GaussianRandomProjection, ProjectionSize, SparseRandomProjection,
+};
+use slearning::{SLearningError, Transformer};
+
+/// 40 observations of 2000 normally distributed variables.
+fn wide_data() -> DMatrix<f64> {
+    let mut rng = Rng::new(3);
+    DMatrix::from_fn(40, 2000, |_, _| rng.standard_normal())
+}
+
+/// The largest relative change in the squared distance between any two observations.
+fn max_distortion(inputs: &DMatrix<f64>, projected: &DMatrix<f64>) -> f64 {
+    let mut max_distortion = 0.0f64;
+    for i in 0..inputs.nrows() {
+        for j in 0..i {
+            let original = (inputs.row(i) - inputs.row(j)).norm_squared();
+            let reduced = (projected.row(i) - projected.row(j)).norm_squared();
+            max_distortion = max_distortion.max((reduced / original - 1.0).abs());
+        }
+    }
+    max_distortion
+}
+
+#[test_case(1_000_000, 0.5, 663)]
+#[test_case(1_000_000, 0.1, 11841)]
+#[test_case(40, 0.5, 177)]
+#[test_case(1, 0.5, 1)]
+fn johnson_lindenstrauss_min_dim_matches(num_obs: usize, eps: f64, expected: usize) {
+    assert_eq!(
+        johnson_lindenstrauss_min_dim(num_obs, eps).unwrap(),
+        expected
+    );
+}
+
+#[test_case(0.0)]
+#[test_case(1.0)]
+#[test_case(f64::NAN)]
+fn johnson_lindenstrauss_min_dim_fails_with_invalid_eps(eps: f64) {
+    assert_eq!(
+        johnson_lindenstrauss_min_dim(100, eps).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Eps must be greater than zero and less than one.".to_string()
+        )
+    );
+}
+
+#[test]
+fn gaussian_random_projection_preserves_distances() {
+    let inputs = wide_data();
+    let mut model =
+        GaussianRandomProjection::new(ProjectionSize::JohnsonLindenstrauss { eps: 0.5 }).unwrap();
+
+    let projected = model.fit_transform(&inputs).unwrap();
+
+    assert_eq!(projected.shape(), (40, 177));
+    assert_eq!(model.components.as_ref().unwrap().shape(), (177, 2000));
+    assert!(max_distortion(&inputs, &projected) < 0.5);
+}
+
+#[test_case(None, 1.0 / 2000f64.sqrt(); "default density")]
+#[test_case(Some(0.1), 0.1; "given density")]
+#[test_case(Some(1.0), 1.0; "dense")]
+fn sparse_random_projection_preserves_distances(density: Option<f64>, expected_density: f64) {
+    let inputs = wide_data();
+    let mut model =
+        SparseRandomProjection::new(ProjectionSize::JohnsonLindenstrauss { eps: 0.5 }).unwrap();
+    if let Some(density) = density {
+        model = model.with_density(density).unwrap();
+    }
+
+    let projected = model.fit_transform(&inputs).unwrap();
+
+    assert_eq!(projected.shape(), (40, 177));
+    assert!(max_distortion(&inputs, &projected) < 0.5);
+    let components = model.components().unwrap();
+    assert_eq!(components.shape(), (177, 2000));
+    let value = (1.0 / (expected_density * 177.0)).sqrt();
+    assert!(components
+        .iter()
+        .all(|&c| c == 0.0 || (c.abs() - value).abs() < 1e-12));
+    let density = components.iter().filter(|&&c| c != 0.0).count() as f64 / components.len() as f64;
+    assert!((density - expected_density).abs() < 0.1 * expected_density);
+    assert!(projected.relative_eq(&(&inputs * components.transpose()), 1e-10, 1e-10));
+}
+
+#[test]
+fn random_projections_depend_on_seed() {
+    let inputs = wide_data();
+    let fit_gaussian = |seed| {
+        let mut model = GaussianRandomProjection::new(ProjectionSize::Components(5))
+            .unwrap()
+            .with_seed(seed);
+        model.fit_transform(&inputs).unwrap()
+    };
+    let fit_sparse = |seed| {
+        let mut model = SparseRandomProjection::new(ProjectionSize::Components(5))
+            .unwrap()
+            .with_seed(seed);
+        model.fit_transform(&inputs).unwrap()
+    };
+
+    assert_eq!(fit_gaussian(1).shape(), (40, 5));
+    assert_eq!(fit_gaussian(1), fit_gaussian(1));
+    assert_ne!(fit_gaussian(1), fit_gaussian(2));
+    assert_eq!(fit_sparse(1), fit_sparse(1));
+    assert_ne!(fit_sparse(1), fit_sparse(2));
+}
+
+#[test_case(ProjectionSize::Components(0), "Number of components must be at least one."; "zero components")]
+#[test_case(ProjectionSize::JohnsonLindenstrauss { eps: 1.5 }, "Eps must be greater than zero and less than one."; "invalid eps")]
+fn random_projections_fail_with_invalid_size(size: ProjectionSize<f64>, message: &str) {
+    let expected = SLearningError::InvalidParameters(message.to_string());
+    assert_eq!(GaussianRandomProjection::new(size).unwrap_err(), expected);
+    assert_eq!(SparseRandomProjection::new(size).unwrap_err(), expected);
+}
+
+#[test_case(0.0)]
+#[test_case(1.5)]
+#[test_case(f64::NAN)]
+#[test_case(f64::INFINITY)]
+fn sparse_random_projection_fails_with_invalid_density(density: f64) {
+    let model = SparseRandomProjection::new(ProjectionSize::Components(2)).unwrap();
+    assert_eq!(
+        model.with_density(density).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Density must be greater than zero and at most one.".to_string()
+        )
+    );
+}
+
+#[test]
+fn random_projections_fail_with_invalid_data() {
+    let size = ProjectionSize::JohnsonLindenstrauss { eps: 0.5 };
+    let mut gaussian = GaussianRandomProjection::new(size).unwrap();
+    let mut sparse = SparseRandomProjection::new(size).unwrap();
+    let inputs = dmatrix![1.0, 2.0; 3.0, 1.0; 0.0, 1.0];
+    assert_eq!(
+        gaussian.transform(&inputs).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+    assert_eq!(
+        sparse.transform(&inputs).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+
+    let too_narrow = SLearningError::InvalidData(
+        "The Johnson-Lindenstrauss bound for 3 observations is 52 components, but there are only 2 variables. Increase eps or project to a fixed number of components."
+            .to_string(),
+    );
+    assert_eq!(gaussian.fit(&inputs).unwrap_err(), too_narrow);
+    assert_eq!(sparse.fit(&inputs).unwrap_err(), too_narrow);
+
+    let non_finite = SLearningError::InvalidData(
+        "Input has a non-finite value for observation 0 and variable 1.".to_string(),
+    );
+    let nan_inputs = dmatrix![1.0, f64::NAN];
+    let mut gaussian = GaussianRandomProjection::new(ProjectionSize::Components(1)).unwrap();
+    let mut sparse = SparseRandomProjection::new(ProjectionSize::Components(1)).unwrap();
+    assert_eq!(gaussian.fit(&nan_inputs).unwrap_err(), non_finite);
+    assert_eq!(sparse.fit(&nan_inputs).unwrap_err(), non_finite);
+    gaussian.fit(&inputs).unwrap();
+    sparse.fit(&inputs).unwrap();
+    let wrong_width = SLearningError::InvalidData(
+        "This model was trained with 2 variables, but this input has 1 variables. These must be equal."
+            .to_string(),
+    );
+    assert_eq!(gaussian.transform(&dmatrix![1.0]).unwrap_err(), wrong_width);
+    assert_eq!(sparse.transform(&dmatrix![1.0]).unwrap_err(), wrong_width);
+}
@@ -0,0 +1,109 @@
+use nalgebra::{dmatrix, dvector, DMatrix, DVector};
+use test_case::test_case;
+
+use slearning::metrics::ndcg;
+use slearning::random::Rng;
+use slearning::ranking::PairwiseRanker;
+use slearning::SLearningError;
+
+/// Queries of ten observations each, whose relevance (from zero to three) increases with a noisy
+/// linear score of three input variables.
+fn ranking_data(num_queries: usize) -> (DMatrix<f64>, DVector<f64>, Vec<usize>) {
+    let true_coefficients = dvector![2.0, -1.0, 0.5];
+    let mut rng = Rng::new(7);
+    let num_obs = 10 * num_queries;
+    let inputs = DMatrix::from_fn(num_obs, 3, |_, _| rng.standard_normal());
+    let relevance = DVector::from_fn(num_obs, |i, _| {
+        let score =
+            inputs.row(i).dot(&true_coefficients.transpose()) + 0.3 * rng.standard_normal::<f64>();
+        (score + 1.5).round().clamp(0.0, 3.0)
+    });
+    let queries = (0..num_obs).map(|i| i / 10).collect();
+    (inputs, relevance, queries)
+}
+
+#[test_case(None; "all pairs")]
+#[test_case(Some(10); "sampled pairs")]
+fn pairwise_ranker_recovers_ranking(max_pairs: Option<usize>) {
+    let (inputs, relevance, queries) = ranking_data(30);
+    let mut ranker = PairwiseRanker::default().with_seed(1);
+    if let Some(max_pairs) = max_pairs {
+        ranker = ranker.with_max_pairs_per_query(max_pairs).unwrap();
+    }
+
+    ranker
+        .train(inputs.clone(), relevance.clone(), &queries)
+        .unwrap();
+
+    let coefficients = ranker.coefficients.as_ref().unwrap();
+    let direction = coefficients.normalize();
+    let true_direction = dvector![2.0, -1.0, 0.5].normalize();
+    assert!(direction.dot(&true_direction) > 0.95);
+
+    let scores = ranker.predict(&inputs).unwrap();
+    assert!(ndcg(&relevance, &scores, &queries, None).unwrap() > 0.9);
+}
+
+#[test]
+fn pairwise_ranker_ignores_relevance_between_queries() {
+    // Relevance only increases with the input within each query, even though the second query is
+    // more relevant overall.
+    let inputs = dmatrix![0.0; 1.0; 2.0; -2.0; -1.0; 0.0];
+    let relevance = dvector![0.0, 1.0, 2.0, 3.0, 4.0, 5.0];
+    let queries = [0, 0, 0, 1, 1, 1];
+    let mut ranker = PairwiseRanker::new(0.1).unwrap();
+
+    ranker.train(inputs, relevance, &queries).unwrap();
+
+    assert!(ranker.coefficients.unwrap()[0] > 0.0);
+}
+
+#[test]
+fn pairwise_ranker_fails_with_invalid_parameters() {
+    assert_eq!(
+        PairwiseRanker::new(-1.0).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Penalty must be finite and cannot be less than zero.".to_string()
+        )
+    );
+    assert_eq!(
+        PairwiseRanker::new(f64::INFINITY).unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Penalty must be finite and cannot be less than zero.".to_string()
+        )
+    );
+    assert_eq!(
+        PairwiseRanker::<f64>::default()
+            .with_max_pairs_per_query(0)
+            .unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Maximum number of pairs must be at least one.".to_string()
+        )
+    );
+}
+
+#[test_case(dvector![1.0, 2.0, 3.0], vec![0, 0], "Input has 3 observation(s), but there are 2 query id(s). These must be equal."; "query ids")]
+#[test_case(dvector![1.0, -2.0, 3.0], vec![0, 0, 0], "Relevance cannot be negative."; "negative relevance")]
+#[test_case(dvector![1.0, 1.0, 2.0], vec![0, 0, 1], "At least one query must have observations with different relevances."; "no pairs")]
+#[test_case(dvector![1.0, f64::NAN, 2.0], vec![0, 0, 0], "The relevances have a non-finite value for observation 1."; "nan relevance")]
+fn pairwise_ranker_fails_to_train_with_invalid_data(
+    relevance: DVector<f64>,
+    queries: Vec<usize>,
+    message: &str,
+) {
+    let inputs = dmatrix![1.0; 2.0; 3.0];
+    let mut ranker = PairwiseRanker::default();
+
+    let error = ranker.train(inputs, relevance, &queries).unwrap_err();
+
+    assert_eq!(error, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn pairwise_ranker_fails_to_predict_when_untrained() {
+    let ranker = PairwiseRanker::<f64>::default();
+
+    let error = ranker.predict(&dmatrix![1.0]).unwrap_err();
+
+    assert_eq!(error, SLearningError::UntrainedModel);
+}
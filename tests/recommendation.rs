@@ -0,0 +1,129 @@
+use test_case::test_case;
+
+use slearning::optim::ConvergenceConfig;
+use slearning::recommendation::AlsFactorizer;
+use slearning::SLearningError;
+
+/// An entry of a rank-two rating matrix.
+fn rating(user: usize, item: usize) -> f64 {
+    let (u, i) = (user as f64, item as f64);
+    (1.0 + 0.3 * u) * (2.0 - 0.2 * i) + (0.5 - 0.1 * u) * (0.4 * i - 1.0)
+}
+
+/// The ratings of the rank-two matrix, leaving out the entries where `(user + item) % 5 == 0`.
+fn low_rank_ratings() -> Vec<(usize, usize, f64)> {
+    let mut ratings = Vec::new();
+    for user in 0..8 {
+        for item in 0..6 {
+            if (user + item) % 5 != 0 {
+                ratings.push((user, item, rating(user, item)));
+            }
+        }
+    }
+    ratings
+}
+
+#[test]
+fn als_completes_low_rank_matrix() {
+    let ratings = low_rank_ratings();
+    let mut model = AlsFactorizer::new(2, 1e-4)
+        .unwrap()
+        .with_convergence(ConvergenceConfig::new(200, 1e-10).unwrap())
+        .with_seed(3);
+
+    model.train(&ratings).unwrap();
+
+    assert_eq!(model.user_factors.as_ref().unwrap().shape(), (8, 2));
+    assert_eq!(model.item_factors.as_ref().unwrap().shape(), (6, 2));
+    for user in 0..8 {
+        for item in 0..6 {
+            let error = model.predict(user, item).unwrap() - rating(user, item);
+            assert!(error.abs() < 0.05, "user {user}, item {item}");
+        }
+    }
+}
+
+#[test]
+fn als_recommends_unrated_items() {
+    let ratings = low_rank_ratings();
+    let mut model = AlsFactorizer::new(2, 1e-4).unwrap();
+    model.train(&ratings).unwrap();
+
+    // User 2 did not rate item 3 in training, and rated every other item.
+    let recommendations = model.recommend(2, 3).unwrap();
+
+    assert_eq!(recommendations.len(), 1);
+    assert_eq!(recommendations[0].0, 3);
+    assert_eq!(recommendations[0].1, model.predict(2, 3).unwrap());
+}
+
+#[test]
+fn als_gives_zero_for_users_without_ratings() {
+    let mut model = AlsFactorizer::new(2, 0.1).unwrap();
+
+    model.train(&[(0, 0, 3.0), (2, 1, 4.0)]).unwrap();
+
+    assert_eq!(model.predict(1, 0).unwrap(), 0.0);
+}
+
+#[test_case(0, 0.1, "Number of factors must be at least one."; "zero factors")]
+#[test_case(2, 0.0, "Regularization must be finite and greater than zero."; "zero regularization")]
+#[test_case(2, f64::NAN, "Regularization must be finite and greater than zero."; "nan regularization")]
+#[test_case(2, f64::INFINITY, "Regularization must be finite and greater than zero."; "infinite regularization")]
+fn als_fails_with_invalid_parameters(n_factors: usize, regularization: f64, message: &str) {
+    let actual = AlsFactorizer::new(n_factors, regularization).unwrap_err();
+
+    assert_eq!(
+        actual,
+        SLearningError::InvalidParameters(message.to_string())
+    );
+}
+
+#[test_case(&[], "Cannot train with zero ratings."; "no ratings")]
+#[test_case(&[(0, 0, 3.0), (1, 0, f64::NAN)], "The ratings have a non-finite value for observation 1."; "nan rating")]
+#[test_case(&[(0, 0, f64::INFINITY)], "The ratings have a non-finite value for observation 0."; "infinite rating")]
+#[test_case(&[(0, 0, 3.0), (3, 1, 4.0)], "User and item ids cannot be greater than the number of ratings (2)."; "large user id")]
+#[test_case(&[(0, usize::MAX, 3.0)], "User and item ids cannot be greater than the number of ratings (1)."; "maximum item id")]
+fn als_fails_with_invalid_ratings(ratings: &[(usize, usize, f64)], message: &str) {
+    let mut model = AlsFactorizer::default();
+
+    let actual = model.train(ratings).unwrap_err();
+
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn als_fails_with_out_of_range_indices() {
+    let mut model = AlsFactorizer::default();
+    model.train(&[(0, 0, 3.0), (1, 1, 4.0)]).unwrap();
+
+    let actual = model.predict(0, 2).unwrap_err();
+
+    let message = "Item 2 is out of range for a model with 2 items.";
+    assert_eq!(
+        actual,
+        SLearningError::InvalidParameters(message.to_string())
+    );
+}
+
+#[test]
+fn als_fails_when_untrained() {
+    let model = AlsFactorizer::<f64>::default();
+
+    assert_eq!(
+        model.recommend(0, 1).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
+
+#[test]
+fn als_fails_when_factors_overflow() {
+    let mut model = AlsFactorizer::new(2, 0.1).unwrap();
+
+    let actual = model
+        .train(&[(0, 0, 1e300), (0, 1, -1e300), (1, 0, 1e300)])
+        .unwrap_err();
+
+    let message = "The factors could not be solved for because the regularized Gram matrix is not positive definite. Try scaling the ratings.";
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
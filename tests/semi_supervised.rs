@@ -0,0 +1,194 @@
+use nalgebra::{dmatrix, DMatrix, DVector};
+use test_case::test_case;
+
+use slearning::kernel::Rbf;
+use slearning::linear_regression::OlsRegressor;
+use slearning::semi_supervised::{Affinity, LabelPropagation, SelfTrainingClassifier};
+use slearning::SLearningError;
+
+/// Two clusters of ten observations, around -2 (class zero) and 2 (class one), with only one
+/// labelled observation in each.
+fn two_clusters() -> (DMatrix<f64>, Vec<Option<f64>>) {
+    let inputs = DMatrix::from_fn(20, 1, |i, _| match i < 10 {
+        true => -3.0 + 2.0 * i as f64 / 9.0,
+        false => 1.0 + 2.0 * (i - 10) as f64 / 9.0,
+    });
+    let mut labels = vec![None; 20];
+    labels[4] = Some(0.0);
+    labels[15] = Some(1.0);
+    (inputs, labels)
+}
+
+#[test]
+fn self_training_labels_clusters() {
+    let (inputs, labels) = two_clusters();
+    let mut classifier = SelfTrainingClassifier::new(OlsRegressor::default(), 0.7, 10).unwrap();
+
+    classifier.train(inputs.clone(), &labels).unwrap();
+
+    let final_labels = classifier.labels.as_ref().unwrap();
+    for (i, label) in final_labels.iter().enumerate() {
+        let expected = if i < 10 { 0.0 } else { 1.0 };
+        assert_eq!(*label, Some(expected), "observation {i}");
+    }
+    let iterations = classifier.labelled_iteration.as_ref().unwrap();
+    assert_eq!(iterations[4], Some(0));
+    assert_eq!(iterations[15], Some(0));
+    // The observations furthest from the boundary are confident after training on two points.
+    assert_eq!(iterations[0], Some(1));
+    assert_eq!(iterations[19], Some(1));
+
+    let predictions = classifier.predict(&dmatrix![-1.5; 0.5; 4.0]).unwrap();
+    assert_eq!(predictions, nalgebra::dvector![0.0, 1.0, 1.0]);
+}
+
+#[test]
+fn self_training_stops_after_max_iter() {
+    let (inputs, labels) = two_clusters();
+    let mut classifier = SelfTrainingClassifier::new(OlsRegressor::default(), 0.8, 1).unwrap();
+
+    classifier.train(inputs, &labels).unwrap();
+
+    assert_eq!(classifier.labels.as_ref().unwrap(), &labels);
+    // With only two points, the model is the line through them.
+    let probabilities = classifier.predict_proba(&dmatrix![0.0]).unwrap();
+    assert!((probabilities[0] - 0.5f64).abs() < 1e-12);
+}
+
+#[test_case(0.5, 10, "Threshold must be greater than one half and at most one."; "threshold too low")]
+#[test_case(1.5, 10, "Threshold must be greater than one half and at most one."; "threshold too high")]
+#[test_case(f64::NAN, 10, "Threshold must be greater than one half and at most one."; "nan threshold")]
+#[test_case(0.9, 0, "Maximum number of iterations must be at least one."; "zero iterations")]
+fn self_training_fails_with_invalid_parameters(threshold: f64, max_iter: usize, message: &str) {
+    let error =
+        SelfTrainingClassifier::new(OlsRegressor::default(), threshold, max_iter).unwrap_err();
+
+    assert_eq!(
+        error,
+        SLearningError::InvalidParameters(message.to_string())
+    );
+}
+
+#[test_case(vec![None, None], "There must be at least one labelled observation."; "no labels")]
+#[test_case(vec![Some(0.0), Some(2.0)], "Binary labels must be zero or one."; "non-binary")]
+#[test_case(vec![Some(0.0)], "Input has 2 observation(s), but there are 1 label(s). These must be equal."; "lengths")]
+fn self_training_fails_with_invalid_labels(labels: Vec<Option<f64>>, message: &str) {
+    let mut classifier = SelfTrainingClassifier::new(OlsRegressor::default(), 0.9, 10).unwrap();
+
+    let error = classifier.train(dmatrix![0.0; 1.0], &labels).unwrap_err();
+
+    assert_eq!(error, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn self_training_fails_to_predict_when_untrained() {
+    let classifier = SelfTrainingClassifier::new(OlsRegressor::default(), 0.9, 10).unwrap();
+
+    assert_eq!(
+        classifier.predict(&dmatrix![0.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
+
+/// Two parallel lines of ten observations, one unit apart along each line and three units apart
+/// between the lines, with only the first observation of each line labelled.
+fn two_lines() -> (DMatrix<f64>, Vec<Option<f64>>, DVector<f64>) {
+    let inputs = DMatrix::from_fn(20, 2, |i, j| match j {
+        0 => (i % 10) as f64,
+        _ => 3.0 * (i / 10) as f64,
+    });
+    let mut labels = vec![None; 20];
+    labels[0] = Some(0.0);
+    labels[10] = Some(1.0);
+    let classes = DVector::from_fn(20, |i, _| (i / 10) as f64);
+    (inputs, labels, classes)
+}
+
+#[test_case(Affinity::KNearestNeighbors(2), None; "knn propagation")]
+#[test_case(Affinity::Rbf(Rbf::new(1.0).unwrap()), None; "rbf propagation")]
+#[test_case(Affinity::KNearestNeighbors(2), Some(0.9); "knn spreading")]
+#[test_case(Affinity::Rbf(Rbf::new(1.0).unwrap()), Some(0.9); "rbf spreading")]
+fn label_propagation_follows_the_graph(affinity: Affinity<f64>, spreading: Option<f64>) {
+    let (inputs, labels, classes) = two_lines();
+    let mut model = LabelPropagation::new(affinity).unwrap();
+    if let Some(alpha) = spreading {
+        model = model.with_spreading(alpha).unwrap();
+    }
+
+    model.train(inputs, &labels).unwrap();
+
+    assert_eq!(model.transduction.as_ref().unwrap(), &classes);
+    let distributions = model.label_distributions.as_ref().unwrap();
+    assert_eq!(distributions.shape(), (20, 2));
+    for row in distributions.row_iter() {
+        assert!((row.sum() - 1.0).abs() < 1e-12);
+    }
+    if spreading.is_none() {
+        assert_eq!(distributions[(0, 0)], 1.0);
+        assert_eq!(distributions[(10, 1)], 1.0);
+    }
+
+    let predictions = model.predict(&dmatrix![8.5, 0.5; 8.5, 2.5]).unwrap();
+    assert_eq!(predictions, nalgebra::dvector![0.0, 1.0]);
+}
+
+#[test]
+fn label_propagation_fails_with_invalid_parameters() {
+    let expected =
+        SLearningError::InvalidParameters("Number of neighbours must be at least one.".to_string());
+    assert_eq!(
+        LabelPropagation::<f64>::new(Affinity::KNearestNeighbors(0)).unwrap_err(),
+        expected
+    );
+
+    let expected = SLearningError::InvalidParameters(
+        "Alpha must be greater than zero and less than one.".to_string(),
+    );
+    let model = LabelPropagation::new(Affinity::KNearestNeighbors(2)).unwrap();
+    assert_eq!(model.with_spreading(1.0).unwrap_err(), expected);
+}
+
+#[test_case(Affinity::KNearestNeighbors(1), vec![Some(0.0), Some(2.0), Some(2.0)], "Class 1 has no observations. Classes must be numbered from zero without gaps."; "gap")]
+#[test_case(Affinity::KNearestNeighbors(1), vec![Some(0.0), None, Some(2.0)], "Class labels must be less than the number of labelled observations (2)."; "large label")]
+#[test_case(Affinity::KNearestNeighbors(1), vec![Some(0.0), None, Some(1e12)], "Class labels must be less than the number of labelled observations (2)."; "huge label")]
+#[test_case(Affinity::KNearestNeighbors(1), vec![Some(0.0), None, Some(f64::INFINITY)], "The labels have a non-finite value for observation 2."; "infinite label")]
+#[test_case(Affinity::KNearestNeighbors(1), vec![Some(0.5), None, None], "Class labels must be non-negative integers."; "non-integer")]
+#[test_case(Affinity::Rbf(Rbf::new(1e4).unwrap()), vec![Some(0.0), None, None], "Observation 0 has no neighbours in the affinity graph."; "isolated")]
+fn label_propagation_fails_with_invalid_data(
+    affinity: Affinity<f64>,
+    labels: Vec<Option<f64>>,
+    message: &str,
+) {
+    let mut model = LabelPropagation::new(affinity).unwrap();
+
+    let error = model.train(dmatrix![0.0; 1.0; 2.0], &labels).unwrap_err();
+
+    assert_eq!(error, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn label_propagation_fails_to_predict_when_untrained() {
+    let model = LabelPropagation::<f64>::new(Affinity::KNearestNeighbors(2)).unwrap();
+
+    assert_eq!(
+        model.predict(&dmatrix![0.0]).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
+
+#[test]
+fn semi_supervised_models_fail_with_non_finite_inputs() {
+    let inputs = dmatrix![0.0; f64::NAN; 2.0];
+    let labels = vec![Some(0.0), None, Some(1.0)];
+    let expected = SLearningError::InvalidData(
+        "Input has a non-finite value for observation 1 and variable 0.".to_string(),
+    );
+
+    let mut classifier = SelfTrainingClassifier::new(OlsRegressor::default(), 0.9, 10).unwrap();
+    assert_eq!(
+        classifier.train(inputs.clone(), &labels).unwrap_err(),
+        expected
+    );
+    let mut model = LabelPropagation::new(Affinity::KNearestNeighbors(1)).unwrap();
+    assert_eq!(model.train(inputs, &labels).unwrap_err(), expected);
+}
@@ -0,0 +1,269 @@
+use nalgebra::{dmatrix, dvector, DMatrix};
+use test_case::test_case;
+
+use slearning::stats::{self, OnlineCovariance, OnlineMeanVariance};
+use slearning::SLearningError;
+
+fn inputs() -> DMatrix<f64> {
+    dmatrix![
+        1.0, 10.0;
+        2.0, 30.0;
+        4.0, 20.0;
+        5.0, 0.0
+    ]
+}
+
+#[test]
+fn column_summaries_work() {
+    let inputs = inputs();
+
+    assert_eq!(stats::mean(&inputs).unwrap(), dvector![3.0, 15.0]);
+    assert_eq!(stats::var(&inputs, 0).unwrap(), dvector![2.5, 125.0]);
+    assert_eq!(
+        stats::var(&inputs, 1).unwrap(),
+        dvector![10.0 / 3.0, 500.0 / 3.0]
+    );
+    assert_eq!(
+        stats::std(&inputs, 0).unwrap(),
+        dvector![2.5f64.sqrt(), 125.0f64.sqrt()]
+    );
+    assert_eq!(stats::min(&inputs).unwrap(), dvector![1.0, 0.0]);
+    assert_eq!(stats::max(&inputs).unwrap(), dvector![5.0, 30.0]);
+}
+
+#[test_case(0.0, dvector![1.0, 0.0]; "minimum")]
+#[test_case(0.5, dvector![3.0, 15.0]; "median")]
+#[test_case(0.25, dvector![1.75, 7.5]; "lower quartile")]
+#[test_case(1.0, dvector![5.0, 30.0]; "maximum")]
+fn quantile_works(q: f64, expected: nalgebra::DVector<f64>) {
+    assert_eq!(stats::quantile(&inputs(), q).unwrap(), expected);
+}
+
+#[test]
+fn column_summaries_fail_with_zero_observations() {
+    let inputs: DMatrix<f64> = DMatrix::zeros(0, 2);
+    let expected =
+        SLearningError::InvalidData("Cannot compute statistics with zero observations.".into());
+
+    assert_eq!(stats::mean(&inputs).unwrap_err(), expected);
+    assert_eq!(stats::min(&inputs).unwrap_err(), expected);
+    assert_eq!(stats::quantile(&inputs, 0.5).unwrap_err(), expected);
+}
+
+#[test]
+fn variance_fails_with_too_few_observations() {
+    let expected = SLearningError::InvalidData(
+        "There are 1 observation(s), which must be greater than the delta degrees of freedom (1)."
+            .into(),
+    );
+    assert_eq!(stats::var(&dmatrix![1.0, 2.0], 1).unwrap_err(), expected);
+}
+
+#[test]
+fn quantile_fails_with_invalid_q() {
+    let expected =
+        SLearningError::InvalidParameters("Quantile must be between zero and one.".into());
+    assert_eq!(stats::quantile(&inputs(), 1.5).unwrap_err(), expected);
+}
+
+#[test]
+fn quantile_fails_with_nan() {
+    let inputs = dmatrix![1.0, 2.0; f64::NAN, 3.0];
+    let expected = SLearningError::InvalidData(
+        "Input has a non-finite value for observation 1 and variable 0.".into(),
+    );
+    assert_eq!(stats::quantile(&inputs, 0.5).unwrap_err(), expected);
+}
+
+#[test]
+fn online_mean_variance_matches_batch() {
+    let inputs = inputs();
+
+    let mut one_at_a_time = OnlineMeanVariance::new(2);
+    for row in inputs.row_iter() {
+        one_at_a_time.update(&row.transpose()).unwrap();
+    }
+    let mut batched = OnlineMeanVariance::new(2);
+    batched
+        .update_batch(&inputs.rows(0, 1).into_owned())
+        .unwrap();
+    batched
+        .update_batch(&inputs.rows(1, 3).into_owned())
+        .unwrap();
+
+    for accumulator in [one_at_a_time, batched] {
+        assert_eq!(accumulator.count(), 4);
+        assert_eq!(accumulator.mean().unwrap(), dvector![3.0, 15.0]);
+        let variance = accumulator.var(1).unwrap();
+        assert!((variance - stats::var(&inputs, 1).unwrap()).abs().max() < 1e-12);
+    }
+}
+
+#[test]
+fn online_covariance_matches_batch() {
+    let inputs = inputs();
+    let expected = dmatrix![2.5, -7.5; -7.5, 125.0];
+
+    let mut one_at_a_time = OnlineCovariance::new(2);
+    for row in inputs.row_iter() {
+        one_at_a_time.update(&row.transpose()).unwrap();
+    }
+    let mut batched = OnlineCovariance::new(2);
+    batched
+        .update_batch(&inputs.rows(0, 3).into_owned())
+        .unwrap();
+    batched
+        .update_batch(&inputs.rows(3, 1).into_owned())
+        .unwrap();
+
+    for accumulator in [one_at_a_time, batched] {
+        assert_eq!(accumulator.mean().unwrap(), dvector![3.0, 15.0]);
+        let covariance = accumulator.covariance(0).unwrap();
+        assert!((covariance - &expected).abs().max() < 1e-12);
+    }
+}
+
+#[test]
+fn online_accumulators_merge_shards() {
+    let inputs = inputs();
+    let expected = dmatrix![2.5, -7.5; -7.5, 125.0];
+
+    let mut mean_variance = OnlineMeanVariance::new(2);
+    let mut other_mean_variance = OnlineMeanVariance::new(2);
+    mean_variance
+        .update_batch(&inputs.rows(0, 1).into_owned())
+        .unwrap();
+    other_mean_variance
+        .update_batch(&inputs.rows(1, 3).into_owned())
+        .unwrap();
+    mean_variance.merge(&other_mean_variance).unwrap();
+    mean_variance.merge(&OnlineMeanVariance::new(2)).unwrap();
+    let mut covariance = OnlineCovariance::new(2);
+    let mut other_covariance = OnlineCovariance::new(2);
+    covariance
+        .update_batch(&inputs.rows(0, 2).into_owned())
+        .unwrap();
+    other_covariance
+        .update_batch(&inputs.rows(2, 2).into_owned())
+        .unwrap();
+    covariance.merge(&other_covariance).unwrap();
+
+    assert_eq!(mean_variance.count(), 4);
+    assert_eq!(mean_variance.mean().unwrap(), dvector![3.0, 15.0]);
+    assert!(
+        (mean_variance.var(1).unwrap() - stats::var(&inputs, 1).unwrap())
+            .abs()
+            .max()
+            < 1e-12
+    );
+    assert_eq!(covariance.count(), 4);
+    assert!((covariance.covariance(0).unwrap() - expected).abs().max() < 1e-12);
+    assert!(matches!(
+        covariance.merge(&OnlineCovariance::new(3)),
+        Err(SLearningError::InvalidData(_))
+    ));
+}
+
+#[test]
+fn online_accumulators_fail_with_inconsistent_dimensions() {
+    let expected = SLearningError::InvalidData(
+        "The accumulator has 2 variables, but the observation(s) have 3 variables. These must be equal.".into(),
+    );
+
+    let mut accumulator = OnlineMeanVariance::new(2);
+    assert_eq!(
+        accumulator.update(&dvector![1.0, 2.0, 3.0]).unwrap_err(),
+        expected
+    );
+    let mut accumulator = OnlineCovariance::new(2);
+    assert_eq!(
+        accumulator
+            .update_batch(&dmatrix![1.0, 2.0, 3.0])
+            .unwrap_err(),
+        expected
+    );
+    assert_eq!(
+        accumulator.mean().unwrap_err(),
+        SLearningError::InvalidData(
+            "There are 0 observation(s), which must be greater than the delta degrees of freedom (0).".into()
+        )
+    );
+}
+
+#[test]
+fn pearson_corr_works() {
+    let x = dvector![1.0f64, 2.0, 3.0, 4.0];
+
+    let positive = stats::pearson_corr(&x, &dvector![2.0, 4.0, 6.0, 8.0]).unwrap();
+    assert!((positive - 1.0).abs() < 1e-12);
+    let negative = stats::pearson_corr(&x, &dvector![4.0, 3.0, 2.0, 1.0]).unwrap();
+    assert!((negative + 1.0).abs() < 1e-12);
+    let uncorrelated = stats::pearson_corr(&x, &dvector![1.0, -1.0, -1.0, 1.0]).unwrap();
+    assert!(uncorrelated.abs() < 1e-12);
+}
+
+#[test]
+fn spearman_corr_works() {
+    let x = dvector![1.0, 2.0, 3.0, 4.0, 5.0];
+
+    // Any monotonic relationship has a rank correlation of one.
+    let y = x.map(|value: f64| value.exp());
+    assert!((stats::spearman_corr(&x, &y).unwrap() - 1.0).abs() < 1e-12);
+
+    // The ranks with ties are [1, 2.5, 2.5, 4, 5].
+    let y = dvector![10.0, 20.0, 20.0, 30.0, 40.0];
+    let expected = 9.5 / (10.0f64 * 9.5).sqrt();
+    assert!((stats::spearman_corr(&x, &y).unwrap() - expected).abs() < 1e-12);
+}
+
+#[test]
+fn correlation_matrix_works() {
+    let inputs = dmatrix![
+        1.0, 2.0, 4.0;
+        2.0, 4.0, 3.0;
+        3.0, 6.0, 2.0;
+        4.0, 8.0, 1.0
+    ];
+
+    let expected = dmatrix![
+        1.0, 1.0, -1.0;
+        1.0, 1.0, -1.0;
+        -1.0, -1.0, 1.0
+    ];
+    let actual = stats::correlation_matrix(&inputs).unwrap();
+    assert!((actual - expected).abs().max() < 1e-12);
+}
+
+#[test]
+fn correlation_fails_with_zero_variance() {
+    let expected = SLearningError::InvalidData(
+        "Cannot compute a correlation with a variable that has zero variance.".into(),
+    );
+    let actual = stats::pearson_corr(&dvector![1.0, 2.0], &dvector![3.0, 3.0]).unwrap_err();
+    assert_eq!(actual, expected);
+
+    let expected = SLearningError::InvalidData(
+        "Cannot compute a correlation with a variable that has zero variance (column 1).".into(),
+    );
+    let actual = stats::correlation_matrix(&dmatrix![1.0, 5.0; 2.0, 5.0]).unwrap_err();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn correlation_fails_with_inconsistent_lengths() {
+    let expected = SLearningError::InvalidData(
+        "The first variable has 2 observation(s), but the second variable has 3 observation(s). These must be equal.".into(),
+    );
+    let actual = stats::spearman_corr(&dvector![1.0, 2.0], &dvector![1.0, 2.0, 3.0]).unwrap_err();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn spearman_corr_fails_with_nan() {
+    let expected = SLearningError::InvalidData(
+        "The values of the second variable have a non-finite value for observation 2.".into(),
+    );
+    let actual =
+        stats::spearman_corr(&dvector![1.0, 2.0, 3.0], &dvector![1.0, 2.0, f64::NAN]).unwrap_err();
+    assert_eq!(actual, expected);
+}
@@ -0,0 +1,232 @@
+use nalgebra::{dmatrix, dvector, DMatrix, DVector};
+use test_case::test_case;
+
+use slearning::random::Rng;
+use slearning::survival::{log_rank_test, CoxPhModel, KaplanMeier, TiesMethod};
+use slearning::SLearningError;
+
+/// Exponential survival times with hazard `exp(0.7 x_1 - 0.5 x_2)`, censored at uniform random
+/// times, and with durations rounded down to a multiple of `resolution` (zero for no rounding).
+fn proportional_hazards_data(
+    num_obs: usize,
+    resolution: f64,
+    seed: u64,
+) -> (DMatrix<f64>, DVector<f64>, DVector<f64>) {
+    let mut rng = Rng::new(seed);
+    let inputs = DMatrix::from_fn(num_obs, 2, |_, _| rng.standard_normal::<f64>());
+    let mut durations = DVector::zeros(num_obs);
+    let mut events = DVector::zeros(num_obs);
+    for i in 0..num_obs {
+        let hazard = (0.7 * inputs[(i, 0)] - 0.5 * inputs[(i, 1)]).exp();
+        let event_time = -(1.0 - rng.uniform::<f64>()).ln() / hazard;
+        let censoring_time = 3.0 * rng.uniform::<f64>();
+        let duration = event_time.min(censoring_time);
+        durations[i] = match resolution {
+            0.0 => duration,
+            _ => (duration / resolution).floor() * resolution,
+        };
+        events[i] = if event_time <= censoring_time {
+            1.0
+        } else {
+            0.0
+        };
+    }
+    (inputs, durations, events)
+}
+
+#[test]
+fn cox_model_recovers_coefficients() {
+    let (inputs, durations, events) = proportional_hazards_data(1000, 0.0, 1);
+    let mut model = CoxPhModel::default();
+
+    model.train(inputs, durations, events).unwrap();
+
+    let coefficients = model.coefficients.as_ref().unwrap();
+    let standard_errors = model.standard_errors.as_ref().unwrap();
+    let expected = dvector![0.7, -0.5];
+    for j in 0..2 {
+        assert!(standard_errors[j] > 0.0 && standard_errors[j] < 0.1);
+        assert!((coefficients[j] - expected[j]).abs() < 3.0 * standard_errors[j]);
+    }
+    let hazard_ratios = model.hazard_ratios().unwrap();
+    assert!((hazard_ratios - coefficients.map(f64::exp)).amax() < 1e-12);
+}
+
+#[test]
+fn cox_ties_methods_agree_without_ties() {
+    let (inputs, durations, events) = proportional_hazards_data(200, 0.0, 2);
+    let mut breslow = CoxPhModel::new(TiesMethod::Breslow);
+    let mut efron = CoxPhModel::new(TiesMethod::Efron);
+
+    breslow
+        .train(inputs.clone(), durations.clone(), events.clone())
+        .unwrap();
+    efron.train(inputs, durations, events).unwrap();
+
+    let difference = breslow.coefficients.unwrap() - efron.coefficients.unwrap();
+    assert!(difference.amax() < 1e-4);
+}
+
+#[test]
+fn cox_efron_is_less_biased_with_many_ties() {
+    let (inputs, durations, events) = proportional_hazards_data(2000, 0.5, 3);
+    let mut breslow = CoxPhModel::new(TiesMethod::Breslow);
+    let mut efron = CoxPhModel::new(TiesMethod::Efron);
+
+    breslow
+        .train(inputs.clone(), durations.clone(), events.clone())
+        .unwrap();
+    efron.train(inputs, durations, events).unwrap();
+
+    // Breslow's approximation shrinks the coefficients towards zero when there are many ties.
+    let breslow_coefficient = breslow.coefficients.unwrap()[0];
+    let efron_coefficient = efron.coefficients.unwrap()[0];
+    assert!(breslow_coefficient < efron_coefficient);
+    assert!((efron_coefficient - 0.7f64).abs() < 0.1);
+}
+
+#[test]
+fn cox_predicts_partial_hazard() {
+    let mut model = CoxPhModel::<f64>::default();
+    model.coefficients = Some(dvector![0.5, -1.0]);
+
+    let hazards = model
+        .predict_partial_hazard(&dmatrix![0.0, 0.0; 2.0, 1.0])
+        .unwrap();
+
+    assert!((hazards - dvector![1.0, 1.0]).amax() < 1e-12);
+}
+
+#[test_case(dvector![1.0, 2.0], dvector![1.0, 0.0, 1.0], "Input has 3 observation(s), but the durations have 2 observation(s). These must be equal."; "durations length")]
+#[test_case(dvector![1.0, 2.0, 3.0], dvector![1.0, 0.0], "There are 3 duration(s), but 2 event indicator(s). These must be equal."; "events length")]
+#[test_case(dvector![1.0, -2.0, 3.0], dvector![1.0, 0.0, 1.0], "Durations cannot be negative."; "negative duration")]
+#[test_case(dvector![1.0, 2.0, 3.0], dvector![1.0, 0.5, 1.0], "Event indicators must be zero or one."; "non-binary events")]
+#[test_case(dvector![1.0, 2.0, 3.0], dvector![0.0, 0.0, 0.0], "There must be at least one event."; "no events")]
+#[test_case(dvector![1.0, f64::NAN, 3.0], dvector![1.0, 0.0, 1.0], "The durations have a non-finite value for observation 1."; "nan duration")]
+fn cox_fails_with_invalid_data(durations: DVector<f64>, events: DVector<f64>, message: &str) {
+    let mut model = CoxPhModel::default();
+
+    let actual = model
+        .train(dmatrix![1.0; 2.0; 3.0], durations, events)
+        .unwrap_err();
+
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn cox_fails_with_non_finite_inputs() {
+    let mut model = CoxPhModel::default();
+
+    let actual = model
+        .train(
+            dmatrix![1.0; f64::INFINITY; 3.0],
+            dvector![1.0, 2.0, 3.0],
+            dvector![1.0, 0.0, 1.0],
+        )
+        .unwrap_err();
+
+    let message = "Input has a non-finite value for observation 1 and variable 0.";
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn kaplan_meier_fails_with_nan_duration() {
+    let mut estimator = KaplanMeier::<f64>::default();
+
+    let actual = estimator
+        .train(&dvector![1.0, 2.0, f64::NAN], &dvector![1.0, 1.0, 0.0])
+        .unwrap_err();
+
+    let message = "The durations have a non-finite value for observation 2.";
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn cox_fails_when_untrained() {
+    let model = CoxPhModel::<f64>::default();
+
+    assert_eq!(
+        model.hazard_ratios().unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
+
+#[test]
+fn kaplan_meier_works() {
+    let durations = dvector![1.0, 2.0, 2.0, 3.0, 4.0, 5.0];
+    let events = dvector![1.0, 1.0, 0.0, 1.0, 0.0, 1.0];
+    let mut estimator = KaplanMeier::default();
+
+    estimator.train(&durations, &events).unwrap();
+
+    let curve = estimator.curve.as_ref().unwrap();
+    assert_eq!(curve.times, dvector![1.0, 2.0, 3.0, 5.0]);
+    assert_eq!(curve.at_risk, vec![6, 5, 3, 1]);
+    assert_eq!(curve.events, vec![1, 1, 1, 1]);
+    let expected_survival = dvector![5.0 / 6.0, 2.0 / 3.0, 4.0 / 9.0, 0.0];
+    assert!((&curve.survival - expected_survival).amax() < 1e-12);
+
+    let survival = 5.0f64 / 6.0;
+    let log_log_std = (1.0f64 / 30.0).sqrt() / survival.ln().abs();
+    assert!((curve.lower[0] - survival.powf((1.959964 * log_log_std).exp())).abs() < 1e-6);
+    assert!((curve.upper[0] - survival.powf((-1.959964 * log_log_std).exp())).abs() < 1e-6);
+    assert!(curve
+        .lower
+        .iter()
+        .zip(curve.upper.iter())
+        .all(|(l, u)| l <= u));
+    assert_eq!((curve.lower[3], curve.upper[3]), (0.0, 0.0));
+
+    let predictions = estimator.predict(&dvector![0.5, 2.0, 2.5, 10.0]).unwrap();
+    assert!((predictions - dvector![1.0, 2.0 / 3.0, 2.0 / 3.0, 0.0]).amax() < 1e-12);
+}
+
+#[test]
+fn kaplan_meier_fails_with_invalid_confidence() {
+    let actual = KaplanMeier::new(1.0).unwrap_err();
+
+    let message = "Confidence must be greater than zero and less than one.";
+    assert_eq!(
+        actual,
+        SLearningError::InvalidParameters(message.to_string())
+    );
+}
+
+#[test]
+fn log_rank_test_works() {
+    let durations = dvector![1.0, 3.0, 5.0, 2.0, 4.0, 6.0, 7.0, 8.0];
+    let events = dvector![1.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0, 0.0];
+
+    let test = log_rank_test(&durations, &events, &[0, 0, 0, 1, 1, 1, 0, 1]).unwrap();
+
+    assert!((test.statistic - 0.07844815512223954f64).abs() < 1e-12);
+    assert_eq!(test.degrees_of_freedom, 1);
+    assert!((test.p_value - 0.7794115422103931f64).abs() < 1e-6);
+}
+
+#[test]
+fn log_rank_test_detects_different_groups() {
+    let mut rng = Rng::new(4);
+    let groups: Vec<usize> = (0..300).map(|i| i % 3).collect();
+    let durations = DVector::from_fn(300, |i, _| {
+        let hazard = [1.0, 1.0, 3.0][groups[i]];
+        -(1.0 - rng.uniform::<f64>()).ln() / hazard
+    });
+    let events = DVector::from_element(300, 1.0);
+
+    let test = log_rank_test(&durations, &events, &groups).unwrap();
+
+    assert_eq!(test.degrees_of_freedom, 2);
+    assert!(test.p_value < 1e-6);
+}
+
+#[test_case(&[0, 0, 0], "There must be at least two groups."; "one group")]
+#[test_case(&[0, 2, 2], "Group 1 has no observations. Groups must be numbered from zero without gaps."; "gap")]
+fn log_rank_test_fails_with_invalid_groups(groups: &[usize], message: &str) {
+    let durations = dvector![1.0, 2.0, 3.0];
+    let events = dvector![1.0, 1.0, 1.0];
+
+    let actual = log_rank_test(&durations, &events, groups).unwrap_err();
+
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
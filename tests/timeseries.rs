@@ -0,0 +1,535 @@
+use nalgebra::{dmatrix, dvector, DMatrix, DVector};
+use test_case::test_case;
+
+use slearning::linear_regression::OlsRegressor;
+use slearning::random::Rng;
+use slearning::timeseries::{
+    forecasting_dataset, ArModel, ArOrder, Arima, ExponentialSmoothing, GaussianState,
+    KalmanFilter, LagFeatures, RollingStatistic, RollingStats,
+};
+use slearning::{SLearningError, SupervisedModel, Transformer};
+
+/// A series following `y_t = 1 + 0.5 y_{t-1} - 0.3 y_{t-2} + noise_scale * e_t`.
+fn ar2_series(length: usize, noise_scale: f64, seed: u64) -> DVector<f64> {
+    let mut rng = Rng::new(seed);
+    let mut values = vec![0.0, 3.0];
+    while values.len() < length {
+        let n = values.len();
+        let noise: f64 = rng.standard_normal();
+        values.push(1.0 + 0.5 * values[n - 1] - 0.3 * values[n - 2] + noise_scale * noise);
+    }
+    DVector::from_vec(values)
+}
+
+/// A series following `y_t = 2 + 0.6 y_{t-1} + e_t + 0.3 e_{t-1}`.
+fn arma11_series(length: usize, seed: u64) -> DVector<f64> {
+    let mut rng = Rng::new(seed);
+    let mut values = vec![5.0];
+    let mut previous_noise = 0.0;
+    while values.len() < length {
+        let noise: f64 = rng.standard_normal();
+        values.push(2.0 + 0.6 * values[values.len() - 1] + noise + 0.3 * previous_noise);
+        previous_noise = noise;
+    }
+    DVector::from_vec(values)
+}
+
+#[test]
+fn ar_model_recovers_exact_recursion() {
+    let series = ar2_series(12, 0.0, 0);
+    let mut model = ArModel::new(ArOrder::Fixed(2));
+
+    model.train(&series).unwrap();
+
+    let coefficients = model.coefficients.as_ref().unwrap();
+    assert!((coefficients - dvector![1.0, 0.5, -0.3]).amax() < 1e-8);
+    assert_eq!(model.fitted_order, Some(2));
+    assert!(model.noise_variance.unwrap() < 1e-12);
+
+    let forecast = model.forecast(3).unwrap();
+    let expected = ar2_series(15, 0.0, 0).rows(12, 3).into_owned();
+    assert!((forecast - expected).amax() < 1e-8);
+}
+
+#[test]
+fn ar_model_selects_order_by_aic() {
+    let series = ar2_series(500, 1.0, 1);
+    let mut model = ArModel::new(ArOrder::SelectByAic(6));
+
+    model.train(&series).unwrap();
+
+    assert_eq!(model.fitted_order, Some(2));
+    let coefficients = model.coefficients.as_ref().unwrap();
+    assert_eq!(coefficients.len(), 3);
+    assert!((coefficients - dvector![1.0, 0.5, -0.3]).amax() < 0.15);
+    assert!((model.noise_variance.unwrap() - 1.0).abs() < 0.15);
+}
+
+#[test]
+fn ar_model_of_order_zero_forecasts_mean() {
+    let series = dvector![1.0, 3.0, 2.0, 6.0];
+    let mut model = ArModel::new(ArOrder::Fixed(0));
+
+    model.train(&series).unwrap();
+
+    assert!((model.forecast(2).unwrap() - dvector![3.0, 3.0]).amax() < 1e-12);
+}
+
+#[test_case(ArOrder::Fixed(2), 5, "The series has 5 observation(s), but at least 6 are needed for an autoregressive model of order 2."; "fixed")]
+#[test_case(ArOrder::SelectByAic(3), 7, "The series has 7 observation(s), but at least 8 are needed for an autoregressive model of order 3."; "aic")]
+fn ar_model_fails_with_short_series(order: ArOrder, length: usize, message: &str) {
+    let mut model = ArModel::new(order);
+
+    let actual = model
+        .train(&DVector::from_fn(length, |i, _| i as f64))
+        .unwrap_err();
+
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn ar_model_forecast_fails_when_untrained() {
+    let model = ArModel::<f64>::new(ArOrder::Fixed(1));
+
+    assert_eq!(
+        model.forecast(1).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
+
+#[test]
+fn arima_recovers_arma_coefficients() {
+    let series = arma11_series(1000, 3);
+    let mut model = Arima::new(1, 0, 1);
+
+    model.train(&series).unwrap();
+
+    assert!((model.constant.unwrap() - 2.0).abs() < 0.3);
+    assert!((model.ar_coefficients.unwrap()[0] - 0.6).abs() < 0.1);
+    assert!((model.ma_coefficients.unwrap()[0] - 0.3).abs() < 0.1);
+    assert!((model.noise_variance.unwrap() - 1.0).abs() < 0.1);
+}
+
+#[test]
+fn arima_random_walk_with_drift() {
+    let series = dvector![1.0, 2.0, 4.0, 5.0, 8.0];
+    let mut model = Arima::new(0, 1, 0);
+
+    model.train(&series).unwrap();
+
+    // The differences are 1, 2, 1 and 3, with mean 1.75 and mean squared deviation 0.6875.
+    assert!((model.constant.unwrap() - 1.75f64).abs() < 1e-6);
+    assert!((model.noise_variance.unwrap() - 0.6875f64).abs() < 1e-6);
+    let intervals = model.forecast_interval(2, 0.95).unwrap();
+    assert!((&intervals.forecast - dvector![9.75, 11.5]).amax() < 1e-6);
+    let half_width = 1.959964 * 0.6875f64.sqrt();
+    let expected_upper = dvector![9.75 + half_width, 11.5 + half_width * 2.0f64.sqrt()];
+    assert!((intervals.upper - expected_upper).amax() < 1e-5);
+    assert!(
+        (intervals.forecast - intervals.lower - dvector![half_width, half_width * 2.0f64.sqrt()])
+            .amax()
+            < 1e-5
+    );
+}
+
+#[test]
+fn arima_intervals_widen_with_ar_weights() {
+    let series = arma11_series(300, 4);
+    let mut model = Arima::new(1, 0, 0);
+    model.train(&series).unwrap();
+    let phi = model.ar_coefficients.as_ref().unwrap()[0];
+    let sigma = model.noise_variance.unwrap().sqrt();
+
+    let intervals = model.forecast_interval(2, 0.95).unwrap();
+
+    let half_widths = &intervals.upper - &intervals.forecast;
+    assert!((half_widths[0] - 1.959964 * sigma).abs() < 1e-5);
+    assert!((half_widths[1] - 1.959964 * sigma * (1.0 + phi * phi).sqrt()).abs() < 1e-5);
+}
+
+#[test]
+fn arima_fails_with_short_series() {
+    let mut model = Arima::new(2, 1, 1);
+
+    let actual = model
+        .train(&dvector![1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0])
+        .unwrap_err();
+
+    let message =
+        "The series has 7 observation(s), but at least 8 are needed for an ARIMA(2, 1, 1) model.";
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn arima_fails_with_non_finite_series() {
+    let mut model = Arima::new(1, 0, 0);
+
+    let actual = model
+        .train(&dvector![1.0, 2.0, f64::NAN, 4.0, 5.0, 6.0, 7.0])
+        .unwrap_err();
+
+    let message = "The series values have a non-finite value for observation 2.";
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test_case(0.0; "zero")]
+#[test_case(1.0; "one")]
+fn arima_interval_fails_with_invalid_confidence(confidence: f64) {
+    let mut model = Arima::new(0, 1, 0);
+    model.train(&dvector![1.0, 2.0, 4.0]).unwrap();
+
+    let actual = model.forecast_interval(1, confidence).unwrap_err();
+
+    let message = "Confidence must be greater than zero and less than one.";
+    assert_eq!(
+        actual,
+        SLearningError::InvalidParameters(message.to_string())
+    );
+}
+
+#[test]
+fn arima_forecast_fails_when_untrained() {
+    let model = Arima::<f64>::new(1, 1, 1);
+
+    assert_eq!(
+        model.forecast(1).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
+
+#[test]
+fn simple_exponential_smoothing_forecasts_flat_level() {
+    let series = dvector![3.0, 5.0, 4.0, 6.0, 5.0, 4.0, 5.0, 6.0];
+    let mut model = ExponentialSmoothing::new(false, None).unwrap();
+
+    model.train(&series).unwrap();
+
+    let alpha = model.alpha.unwrap();
+    assert!(alpha > 0.0 && alpha < 1.0);
+    assert_eq!(model.beta, None);
+    assert_eq!(model.gamma, None);
+    let level = model.level.unwrap();
+    assert_eq!(model.forecast(3).unwrap(), dvector![level, level, level]);
+}
+
+#[test]
+fn holt_continues_linear_trend() {
+    let series = DVector::from_fn(10, |t, _| 2.0 + 3.0 * t as f64);
+    let mut model = ExponentialSmoothing::new(true, None).unwrap();
+
+    model.train(&series).unwrap();
+
+    assert!((model.trend.unwrap() - 3.0f64).abs() < 1e-8);
+    assert!((model.forecast(2).unwrap() - dvector![32.0, 35.0]).amax() < 1e-8);
+}
+
+#[test]
+fn holt_winters_continues_trend_and_season() {
+    let season = [1.0, -2.0, 3.0, -2.0];
+    let value = |t: usize| 10.0 + 0.5 * t as f64 + season[t % 4];
+    let series = DVector::from_fn(22, |t, _| value(t));
+    let mut model = ExponentialSmoothing::new(true, Some(4)).unwrap();
+
+    model.train(&series).unwrap();
+
+    assert!(model.gamma.is_some());
+    let expected = DVector::from_fn(6, |h, _| value(22 + h));
+    assert!((model.forecast(6).unwrap() - expected).amax() < 1e-6);
+}
+
+#[test]
+fn holt_winters_fits_noisy_seasonal_series() {
+    let mut rng = Rng::new(5);
+    let season = [4.0, 0.0, -4.0];
+    let series = DVector::from_fn(60, |t, _| {
+        let noise: f64 = rng.standard_normal();
+        20.0 + season[t % 3] + 0.5 * noise
+    });
+    let mut model = ExponentialSmoothing::new(false, Some(3)).unwrap();
+
+    model.train(&series).unwrap();
+
+    let forecast = model.forecast(3).unwrap();
+    assert!((forecast - dvector![24.0, 20.0, 16.0]).amax() < 1.0);
+}
+
+#[test]
+fn exponential_smoothing_fails_with_short_period() {
+    let actual = ExponentialSmoothing::<f64>::new(true, Some(1)).unwrap_err();
+
+    let message = "Seasonal period must be at least two.";
+    assert_eq!(
+        actual,
+        SLearningError::InvalidParameters(message.to_string())
+    );
+}
+
+#[test_case(false, None, 1, 2; "simple")]
+#[test_case(true, None, 2, 3; "holt")]
+#[test_case(true, Some(4), 7, 8; "holt winters")]
+fn exponential_smoothing_fails_with_short_series(
+    trend: bool,
+    seasonal_period: Option<usize>,
+    length: usize,
+    min_length: usize,
+) {
+    let mut model = ExponentialSmoothing::new(trend, seasonal_period).unwrap();
+
+    let actual = model
+        .train(&DVector::from_fn(length, |i, _| i as f64))
+        .unwrap_err();
+
+    let message = format!(
+        "The series has {} observation(s), but at least {} are needed for this exponential smoothing model.",
+        length, min_length
+    );
+    assert_eq!(actual, SLearningError::InvalidData(message));
+}
+
+#[test]
+fn exponential_smoothing_forecast_fails_when_untrained() {
+    let model = ExponentialSmoothing::<f64>::new(false, None).unwrap();
+
+    assert_eq!(
+        model.forecast(1).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}
+
+#[test]
+fn lag_features_works() {
+    let series = dmatrix![1.0, 10.0; 2.0, 20.0; 3.0, 30.0; 4.0, 40.0];
+    let lags = LagFeatures::new(vec![1, 2]).unwrap();
+
+    let features = Transformer::<f64>::transform(&lags, &series).unwrap();
+
+    // Rows for the third and fourth values, and the value after the series.
+    let expected = dmatrix![
+        2.0, 1.0, 20.0, 10.0;
+        3.0, 2.0, 30.0, 20.0;
+        4.0, 3.0, 40.0, 30.0
+    ];
+    assert_eq!(features, expected);
+}
+
+#[test]
+fn rolling_stats_works() {
+    let series = dmatrix![1.0; 3.0; 2.0; 6.0];
+    let rolling = RollingStats::new(
+        2,
+        vec![
+            RollingStatistic::Mean,
+            RollingStatistic::Std,
+            RollingStatistic::Min,
+            RollingStatistic::Max,
+        ],
+    )
+    .unwrap();
+
+    let features = Transformer::<f64>::transform(&rolling, &series).unwrap();
+
+    let expected = dmatrix![
+        2.0, 1.0, 1.0, 3.0;
+        2.5, 0.5, 2.0, 3.0;
+        4.0, 2.0, 2.0, 6.0
+    ];
+    assert_eq!(features, expected);
+}
+
+#[test]
+fn forecasting_dataset_aligns_features() {
+    let series = dmatrix![1.0; 2.0; 4.0; 8.0; 16.0];
+    let lags = LagFeatures::new(vec![1]).unwrap();
+    let rolling = RollingStats::new(3, vec![RollingStatistic::Mean]).unwrap();
+
+    let dataset = forecasting_dataset(&series, 0, &[&lags, &rolling]).unwrap();
+
+    assert_eq!(dataset.inputs, dmatrix![4.0, 7.0 / 3.0; 8.0, 14.0 / 3.0]);
+    assert_eq!(dataset.outputs, dvector![8.0, 16.0]);
+    assert_eq!(dataset.forecast_inputs, dmatrix![16.0, 28.0 / 3.0]);
+}
+
+#[test]
+fn forecasting_dataset_works_with_regressor() {
+    let series = DMatrix::from_fn(20, 1, |t, _| 1.0 + 0.5 * t as f64);
+    let lags = LagFeatures::new(vec![1]).unwrap();
+    let dataset = forecasting_dataset(&series, 0, &[&lags]).unwrap();
+    let mut model = OlsRegressor::new(true);
+
+    model.train(dataset.inputs, dataset.outputs).unwrap();
+
+    let forecast = model.predict(&dataset.forecast_inputs).unwrap();
+    assert!((forecast[0] - 11.0f64).abs() < 1e-8);
+}
+
+#[test_case(vec![], "At least one lag is needed."; "empty")]
+#[test_case(vec![1, 0], "Lags must be at least one."; "zero")]
+fn lag_features_fails_with_invalid_lags(lags: Vec<usize>, message: &str) {
+    let actual = LagFeatures::new(lags).unwrap_err();
+
+    assert_eq!(
+        actual,
+        SLearningError::InvalidParameters(message.to_string())
+    );
+}
+
+#[test_case(0, vec![RollingStatistic::Mean], "Window must contain at least one observation."; "empty window")]
+#[test_case(2, vec![], "At least one statistic is needed."; "no statistics")]
+fn rolling_stats_fails_with_invalid_parameters(
+    window: usize,
+    statistics: Vec<RollingStatistic>,
+    message: &str,
+) {
+    let actual = RollingStats::new(window, statistics).unwrap_err();
+
+    assert_eq!(
+        actual,
+        SLearningError::InvalidParameters(message.to_string())
+    );
+}
+
+#[test]
+fn forecasting_dataset_fails_with_short_series() {
+    let lags = LagFeatures::new(vec![3]).unwrap();
+
+    let actual = forecasting_dataset(&dmatrix![1.0; 2.0; 3.0], 0, &[&lags]).unwrap_err();
+
+    let message =
+        "The series has 3 observation(s), but at least 4 are needed to compute these features.";
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn forecasting_dataset_fails_with_invalid_target() {
+    let lags = LagFeatures::new(vec![1]).unwrap();
+
+    let actual = forecasting_dataset(&dmatrix![1.0, 2.0; 3.0, 4.0], 2, &[&lags]).unwrap_err();
+
+    let message = "Target column 2 is out of range for a series with 2 variables.";
+    assert_eq!(
+        actual,
+        SLearningError::InvalidParameters(message.to_string())
+    );
+}
+
+/// A local level (random walk plus noise) model with one state.
+fn local_level(process_noise: f64, observation_noise: f64) -> KalmanFilter<f64> {
+    KalmanFilter::new(
+        dmatrix![1.0],
+        dmatrix![1.0],
+        dmatrix![process_noise],
+        dmatrix![observation_noise],
+        GaussianState {
+            mean: dvector![0.0],
+            covariance: dmatrix![1.0],
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+fn kalman_filter_works() {
+    let filter = local_level(1.0, 1.0);
+
+    let estimates = filter
+        .filter(&[Some(dvector![2.0]), Some(dvector![4.0])])
+        .unwrap();
+
+    assert!((estimates.states[0].mean[0] - 1.0f64).abs() < 1e-12);
+    assert!((estimates.states[0].covariance[(0, 0)] - 0.5f64).abs() < 1e-12);
+    assert!((estimates.states[1].mean[0] - 2.8f64).abs() < 1e-12);
+    assert!((estimates.states[1].covariance[(0, 0)] - 0.6f64).abs() < 1e-12);
+    let log_two_pi = (2.0 * std::f64::consts::PI).ln();
+    let expected_log_likelihood =
+        -0.5 * (log_two_pi + 2.0f64.ln() + 2.0) - 0.5 * (log_two_pi + 2.5f64.ln() + 9.0 / 2.5);
+    assert!((estimates.log_likelihood - expected_log_likelihood).abs() < 1e-12);
+}
+
+#[test]
+fn kalman_filter_predicts_through_missing_observations() {
+    let filter = local_level(1.0, 1.0);
+
+    let estimates = filter.filter(&[Some(dvector![2.0]), None]).unwrap();
+
+    assert_eq!(estimates.states[1], filter.predict(&estimates.states[0]));
+    assert!((estimates.states[1].covariance[(0, 0)] - 1.5f64).abs() < 1e-12);
+}
+
+#[test]
+fn kalman_smoother_works() {
+    let filter = local_level(1.0, 1.0);
+
+    let estimates = filter
+        .smooth(&[Some(dvector![2.0]), Some(dvector![4.0])])
+        .unwrap();
+
+    assert!((estimates.states[0].mean[0] - 1.6f64).abs() < 1e-12);
+    assert!((estimates.states[0].covariance[(0, 0)] - 0.4f64).abs() < 1e-12);
+    assert!((estimates.states[1].mean[0] - 2.8f64).abs() < 1e-12);
+}
+
+#[test]
+fn kalman_filter_estimates_noise_by_em() {
+    let mut rng = Rng::new(11);
+    let mut level = 0.0;
+    let observations: Vec<Option<DVector<f64>>> = (0..2000)
+        .map(|t| {
+            let process: f64 = rng.standard_normal();
+            let observation: f64 = rng.standard_normal();
+            level += 0.5f64.sqrt() * process;
+            // Leave out some observations.
+            (t % 10 != 3).then(|| dvector![level + 2.0f64.sqrt() * observation])
+        })
+        .collect();
+    let mut filter = local_level(1.0, 1.0);
+
+    let log_likelihoods = filter.estimate_noise(&observations, 50).unwrap();
+
+    assert!(log_likelihoods
+        .windows(2)
+        .all(|pair| pair[1] >= pair[0] - 1e-8));
+    assert!((filter.process_noise()[(0, 0)] - 0.5).abs() < 0.15);
+    assert!((filter.observation_noise()[(0, 0)] - 2.0).abs() < 0.3);
+}
+
+#[test]
+fn kalman_filter_fails_with_mismatched_shapes() {
+    let actual = KalmanFilter::new(
+        dmatrix![1.0, 0.0; 0.0, 1.0],
+        dmatrix![1.0, 0.0],
+        dmatrix![1.0],
+        dmatrix![1.0],
+        GaussianState {
+            mean: dvector![0.0, 0.0],
+            covariance: DMatrix::identity(2, 2),
+        },
+    )
+    .unwrap_err();
+
+    let message = "The process noise matrix has shape (1, 1), but should have shape (2, 2).";
+    assert_eq!(
+        actual,
+        SLearningError::InvalidParameters(message.to_string())
+    );
+}
+
+#[test]
+fn kalman_filter_fails_with_wrong_observation_length() {
+    let filter = local_level(1.0, 1.0);
+
+    let actual = filter.filter(&[Some(dvector![1.0, 2.0])]).unwrap_err();
+
+    let message =
+        "An observation has 2 variables, but the observation matrix has 1 rows. These must be equal.";
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
+
+#[test]
+fn kalman_noise_estimation_fails_without_observations() {
+    let mut filter = local_level(1.0, 1.0);
+
+    let actual = filter.estimate_noise(&[None, None], 1).unwrap_err();
+
+    let message = "Estimating the noise needs at least two time steps and one observation.";
+    assert_eq!(actual, SLearningError::InvalidData(message.to_string()));
+}
@@ -0,0 +1,104 @@
+use std::sync::Arc;
+use std::thread;
+
+use nalgebra::{dmatrix, dvector};
+
+use slearning::anomaly::{EllipticEnvelope, IsolationForest, LocalOutlierFactor, OneClassSvm};
+use slearning::cluster::{AffinityPropagation, Birch, Optics};
+use slearning::covariance::{EmpiricalCovariance, LedoitWolf, MinCovDet};
+use slearning::decomposition::{Cca, DictionaryLearning};
+use slearning::distance::Euclidean;
+use slearning::gaussian_process::GpcClassifier;
+use slearning::kernel::Rbf;
+use slearning::linear_regression::{
+    GlsRegressor, MixedLmRegressor, NnlsRegressor, OlsRegressor, RidgeRegressor,
+};
+use slearning::model_selection::ThresholdClassifier;
+use slearning::neighbors::{LshNeighbors, NearestNeighbors};
+use slearning::ordinal_regression::OrdinalRegressor;
+use slearning::random_projection::{GaussianRandomProjection, SparseRandomProjection};
+use slearning::ranking::PairwiseRanker;
+use slearning::recommendation::AlsFactorizer;
+use slearning::semi_supervised::{LabelPropagation, SelfTrainingClassifier};
+use slearning::survival::{CoxPhModel, KaplanMeier};
+use slearning::timeseries::{ArModel, Arima, ExponentialSmoothing, KalmanFilter};
+use slearning::{FrozenModel, SupervisedModel, UnsupervisedModel};
+
+fn assert_send_sync<M: Send + Sync>() {}
+
+#[test]
+fn models_are_send_and_sync() {
+    assert_send_sync::<OlsRegressor<f64>>();
+    assert_send_sync::<RidgeRegressor<f64>>();
+    assert_send_sync::<NnlsRegressor<f64>>();
+    assert_send_sync::<GlsRegressor<f64>>();
+    assert_send_sync::<MixedLmRegressor<f64>>();
+    assert_send_sync::<OrdinalRegressor<f64>>();
+    assert_send_sync::<PairwiseRanker<f64>>();
+    assert_send_sync::<GpcClassifier<f64, Rbf<f64>>>();
+    assert_send_sync::<ThresholdClassifier<OlsRegressor<f64>, f64>>();
+    assert_send_sync::<SelfTrainingClassifier<OlsRegressor<f64>, f64>>();
+    assert_send_sync::<LabelPropagation<f64>>();
+    assert_send_sync::<IsolationForest<f64>>();
+    assert_send_sync::<LocalOutlierFactor<f64>>();
+    assert_send_sync::<EllipticEnvelope<f64>>();
+    assert_send_sync::<OneClassSvm<f64, Rbf<f64>>>();
+    assert_send_sync::<EmpiricalCovariance<f64>>();
+    assert_send_sync::<LedoitWolf<f64>>();
+    assert_send_sync::<MinCovDet<f64>>();
+    assert_send_sync::<Cca<f64>>();
+    assert_send_sync::<DictionaryLearning<f64>>();
+    assert_send_sync::<GaussianRandomProjection<f64>>();
+    assert_send_sync::<SparseRandomProjection<f64>>();
+    assert_send_sync::<NearestNeighbors<f64, Euclidean>>();
+    assert_send_sync::<LshNeighbors<f64, Euclidean>>();
+    assert_send_sync::<Optics<f64, Euclidean>>();
+    assert_send_sync::<AffinityPropagation<f64>>();
+    assert_send_sync::<Birch<f64>>();
+    assert_send_sync::<AlsFactorizer<f64>>();
+    assert_send_sync::<CoxPhModel<f64>>();
+    assert_send_sync::<KaplanMeier<f64>>();
+    assert_send_sync::<ArModel<f64>>();
+    assert_send_sync::<Arima<f64>>();
+    assert_send_sync::<ExponentialSmoothing<f64>>();
+    assert_send_sync::<KalmanFilter<f64>>();
+}
+
+#[test]
+fn frozen_model_predicts_from_many_threads() {
+    let mut ols = OlsRegressor::default();
+    ols.train(dmatrix![0.0; 1.0; 2.0], dvector![1.0, 3.0, 5.0])
+        .unwrap();
+    let frozen = Arc::new(FrozenModel::new(ols));
+
+    let predictions: Vec<f64> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let frozen = Arc::clone(&frozen);
+                scope.spawn(move || frozen.predict(&dmatrix![i as f64]).unwrap()[0])
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    for (i, prediction) in predictions.into_iter().enumerate() {
+        assert!((prediction - (1.0 + 2.0 * i as f64)).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn frozen_model_can_be_unfrozen() {
+    let inputs = dmatrix![0.0, 0.0; 0.1, 0.0; 0.0, 0.1; 0.1, 0.1; 5.0, 5.0];
+    let mut forest = IsolationForest::new(50, 5, 0.2).unwrap();
+    forest.train(&inputs).unwrap();
+    let expected = forest.predict(&inputs).unwrap();
+    let frozen = FrozenModel::new(forest);
+
+    assert_eq!(frozen.model().predict(&inputs).unwrap(), expected);
+
+    let mut forest = frozen.into_inner();
+    forest.train(&inputs.rows(0, 4).into_owned()).unwrap();
+}
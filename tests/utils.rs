@@ -0,0 +1,70 @@
+use test_case::test_case;
+
+use slearning::utils::{
+    unique_with_counts, unique_with_counts_by_first_occurrence, unique_with_counts_sorted,
+    UniqueWithCounts,
+};
+
+#[test]
+fn unique_with_counts_yields_owned_values() {
+    let labels = vec![0, 1, 0, 2, 0, 1];
+
+    let mut actual: Vec<(i32, u64)> = unique_with_counts(labels).collect();
+    actual.sort();
+    assert_eq!(actual, vec![(0, 3), (1, 2), (2, 1)]);
+}
+
+#[test]
+fn unique_with_counts_from_slice() {
+    let labels = ["b".to_string(), "a".to_string(), "b".to_string()];
+
+    let mut actual: Vec<(String, u64)> = UniqueWithCounts::from_slice(&labels).collect();
+    actual.sort();
+    assert_eq!(actual, vec![("a".to_string(), 1), ("b".to_string(), 2)]);
+}
+
+#[test]
+fn unique_with_counts_empty() {
+    let labels: Vec<u8> = vec![];
+
+    assert_eq!(unique_with_counts(labels).count(), 0);
+}
+
+#[test]
+fn unique_with_counts_sorted_is_ascending() {
+    let labels = vec![3, 1, 2, 3, 1, 3];
+
+    let actual: Vec<(i32, u64)> = unique_with_counts_sorted(labels).collect();
+    assert_eq!(actual, vec![(1, 2), (2, 1), (3, 3)]);
+}
+
+#[test]
+fn unique_with_counts_by_first_occurrence_keeps_input_order() {
+    let labels = vec!["c", "a", "c", "b", "a"];
+
+    let actual: Vec<(&str, u64)> = unique_with_counts_by_first_occurrence(labels).collect();
+    assert_eq!(actual, vec![("c", 2), ("a", 2), ("b", 1)]);
+}
+
+#[test]
+fn unique_with_counts_proportions() {
+    let labels = vec![1, 0, 1, 1];
+
+    let actual: Vec<(i32, f64)> = unique_with_counts_sorted(labels).proportions().collect();
+    assert_eq!(actual, vec![(0, 0.25), (1, 0.75)]);
+}
+
+#[test_case(vec![2, 1, 2, 3], Some(2); "single mode")]
+#[test_case(vec![3, 1, 1, 3], Some(1); "ties broken by order")]
+#[test_case(vec![], None; "empty")]
+fn unique_with_counts_mode(labels: Vec<i32>, expected: Option<i32>) {
+    assert_eq!(unique_with_counts_sorted(labels).mode(), expected);
+}
+
+#[test_case(2, vec![(4, 3), (2, 2)]; "fewer than distinct")]
+#[test_case(5, vec![(4, 3), (2, 2), (1, 1), (3, 1)]; "more than distinct")]
+fn unique_with_counts_top_k(k: usize, expected: Vec<(i32, u64)>) {
+    let labels = vec![1, 2, 2, 3, 4, 4, 4];
+
+    assert_eq!(unique_with_counts_sorted(labels).top_k(k), expected);
+}
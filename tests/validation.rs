@@ -0,0 +1,142 @@
+use nalgebra::{dmatrix, dvector, DMatrix};
+use test_case::test_case;
+
+use slearning::validation::{
+    check_2d_nonempty, check_consistent_length, check_finite, check_finite_values, check_fitted,
+    check_fraction, check_non_negative, check_num_vars, check_open_unit_interval, check_positive,
+    is_missing,
+};
+use slearning::SLearningError;
+
+#[test]
+fn checks_pass_with_valid_data() {
+    let inputs = dmatrix![1.0, 2.0; 3.0, 4.0];
+
+    assert!(check_2d_nonempty(&inputs).is_ok());
+    assert!(check_consistent_length(&inputs, &dvector![1.0, 2.0]).is_ok());
+    assert!(check_finite(&inputs).is_ok());
+    assert!(check_finite_values(&dvector![1.0, 2.0], "scores").is_ok());
+    assert!(check_positive(1e6, "Gamma").is_ok());
+    assert!(check_non_negative(0.0, "Penalty").is_ok());
+    assert!(check_open_unit_interval(0.95, "Confidence").is_ok());
+    assert!(check_fraction(1.0, "Density").is_ok());
+    assert!(check_num_vars(2, inputs.ncols()).is_ok());
+    assert_eq!(check_fitted(&Some(3)), Ok(&3));
+}
+
+#[test]
+fn check_2d_nonempty_fails_with_zero_observations() {
+    assert_eq!(
+        check_2d_nonempty(&DMatrix::<f64>::zeros(0, 2)).unwrap_err(),
+        SLearningError::InvalidData("Cannot train with zero observations.".to_string())
+    );
+}
+
+#[test]
+fn check_consistent_length_fails_with_inconsistent_dimensions() {
+    assert_eq!(
+        check_consistent_length(&dmatrix![1.0; 2.0], &dvector![1.0, 2.0, 3.0]).unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has 2 observation(s), but output has 3 observation(s). These must be equal."
+                .to_string()
+        )
+    );
+}
+
+#[test_case(f64::NAN; "nan")]
+#[test_case(f64::INFINITY; "infinity")]
+#[test_case(f64::NEG_INFINITY; "negative infinity")]
+fn check_finite_fails_with_non_finite_values(value: f64) {
+    assert_eq!(
+        check_finite(&dmatrix![1.0, 2.0; 3.0, value]).unwrap_err(),
+        SLearningError::InvalidData(
+            "Input has a non-finite value for observation 1 and variable 1.".to_string()
+        )
+    );
+}
+
+#[test_case(f64::NAN; "nan")]
+#[test_case(f64::INFINITY; "infinity")]
+fn check_finite_values_fails_with_non_finite_values(value: f64) {
+    assert_eq!(
+        check_finite_values(&dvector![1.0, value, 2.0], "scores").unwrap_err(),
+        SLearningError::InvalidData(
+            "The scores have a non-finite value for observation 1.".to_string()
+        )
+    );
+}
+
+#[test_case(0.0; "zero")]
+#[test_case(-1.0; "negative")]
+#[test_case(f64::NAN; "nan")]
+#[test_case(f64::INFINITY; "infinity")]
+fn check_positive_fails_with_invalid_values(value: f64) {
+    assert_eq!(
+        check_positive(value, "Gamma").unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Gamma must be finite and greater than zero.".to_string()
+        )
+    );
+}
+
+#[test_case(-1.0; "negative")]
+#[test_case(f64::NAN; "nan")]
+#[test_case(f64::INFINITY; "infinity")]
+fn check_non_negative_fails_with_invalid_values(value: f64) {
+    assert_eq!(
+        check_non_negative(value, "Penalty").unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Penalty must be finite and cannot be less than zero.".to_string()
+        )
+    );
+}
+
+#[test_case(0.0; "zero")]
+#[test_case(1.0; "one")]
+#[test_case(f64::NAN; "nan")]
+fn check_open_unit_interval_fails_with_invalid_values(value: f64) {
+    assert_eq!(
+        check_open_unit_interval(value, "Confidence").unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Confidence must be greater than zero and less than one.".to_string()
+        )
+    );
+}
+
+#[test_case(0.0; "zero")]
+#[test_case(1.5; "greater than one")]
+#[test_case(f64::NAN; "nan")]
+fn check_fraction_fails_with_invalid_values(value: f64) {
+    assert_eq!(
+        check_fraction(value, "Density").unwrap_err(),
+        SLearningError::InvalidParameters(
+            "Density must be greater than zero and at most one.".to_string()
+        )
+    );
+}
+
+#[test]
+fn is_missing_is_only_true_for_nan() {
+    assert!(is_missing(&f64::NAN));
+    assert!(!is_missing(&f64::INFINITY));
+    assert!(!is_missing(&1.0));
+}
+
+#[test]
+fn check_num_vars_fails_with_wrong_dimensions() {
+    assert_eq!(
+        check_num_vars(2, 3).unwrap_err(),
+        SLearningError::InvalidData(
+            "This model was trained with 2 variables, but this input has 3 variables. These must be equal."
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn check_fitted_fails_when_untrained() {
+    assert_eq!(
+        check_fitted::<f64>(&None).unwrap_err(),
+        SLearningError::UntrainedModel
+    );
+}